@@ -480,6 +480,7 @@ async fn run<B: Backend + 'static, F: Future<Output = ()>>(
         show_all,
         log_detail,
         log_level: log_level.map_or_else(|| IssueSeverity::Error, |l| l.0),
+        issue_policy: Default::default(),
     });
     let task = tt.spawn_root_task(move || {
         let dir = dir.clone();
@@ -12,7 +12,7 @@ use anyhow::{Context, Result};
 use glob::glob;
 use syn::{
     parse_quote, Attribute, Ident, Item, ItemEnum, ItemFn, ItemImpl, ItemMacro, ItemMod,
-    ItemStruct, ItemTrait, TraitItem, TraitItemMethod,
+    ItemStruct, ItemTrait, Lit, Meta, MetaNameValue, NestedMeta, TraitItem, TraitItemMethod,
 };
 use turbo_tasks_macros_shared::{
     get_impl_function_ident, get_native_function_ident, get_path_ident,
@@ -266,6 +266,9 @@ impl<'a> RegisterContext<'a> {
     }
 
     fn process_mod(&mut self, mod_item: ItemMod) -> Result<()> {
+        if !is_cfg_feature_enabled(&mod_item.attrs) {
+            return Ok(());
+        }
         if mod_item.content.is_none() {
             let name = mod_item.ident.to_string();
             let parent_path = self.file_path.parent().unwrap();
@@ -496,6 +499,33 @@ impl<'a> RegisterContext<'a> {
     }
 }
 
+/// Returns `false` if the attributes contain a `#[cfg(feature = "...")]` for a
+/// feature that is not enabled in the crate being built.
+fn is_cfg_feature_enabled(attrs: &[Attribute]) -> bool {
+    attrs.iter().all(|attr| {
+        if !attr.path.is_ident("cfg") {
+            return true;
+        }
+        let Ok(Meta::List(list)) = attr.parse_meta() else {
+            return true;
+        };
+        if list.nested.len() != 1 {
+            return true;
+        }
+        match list.nested.first() {
+            Some(NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                path,
+                lit: Lit::Str(feature),
+                ..
+            }))) if path.is_ident("feature") => {
+                let feature = feature.value().to_uppercase().replace('-', "_");
+                env::var_os(format!("CARGO_FEATURE_{feature}")).is_some()
+            }
+            _ => true,
+        }
+    })
+}
+
 fn has_attribute(attrs: &[Attribute], name: &str) -> bool {
     attrs.iter().any(|a| is_attribute(a, name))
 }
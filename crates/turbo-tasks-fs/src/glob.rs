@@ -18,14 +18,22 @@ enum GlobPart {
     /// `/`: Matches the path separator
     PathSeparator,
 
-    /// `[abc]`: Matches any char of the list
-    FileChar(Vec<char>),
+    /// `[abc]`, `[a-z]` or `[!abc]`: Matches a single filename char which is
+    /// (or with `!` isn't) in one of the ranges
+    FileChar {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
 
     /// `abc`: Matches literal filename
     File(String),
 
-    /// `{a,b,c}`: Matches any of the globs in the list
+    /// `{a,b,c}` or `@(a|b|c)`: Matches any of the globs in the list
     Alternatives(Vec<Glob>),
+
+    /// `!(a|b|c)`: Matches any part of a filename which isn't matched by any
+    /// of the globs in the list
+    Negation(Vec<Glob>),
 }
 
 // Examples:
@@ -36,6 +44,9 @@ enum GlobPart {
 // - **/*.js = AnyDirectories, PathSeparator, AnyFile, File(.js)
 // - {a/**,*}/file = Alternatives([File(a), PathSeparator, AnyDirectories],
 //   [AnyFile]), PathSeparator, File(file)
+// - *.[jt]s = AnyFile, File(.), FileChar([j, t]), File(s)
+// - !(*.d).ts = Negation([AnyFile, File(.d)]), File(.ts)
+// - !**/node_modules/** = negated glob of **/node_modules/**
 
 // Note: a/**/b does match a/b, so we need some special logic about path
 // separators
@@ -44,13 +55,21 @@ enum GlobPart {
 #[derive(Debug, Clone)]
 pub struct Glob {
     expression: Vec<GlobPart>,
+    /// A leading `!` matches all paths which aren't matched by the expression.
+    negated: bool,
 }
 
 impl Glob {
     pub fn execute(&self, path: &str) -> bool {
         let match_partial = path.ends_with('/');
+        if self.negated && match_partial {
+            // Any directory can contain paths which aren't matched by the
+            // expression
+            return true;
+        }
         self.iter_matches(path, true, match_partial)
             .any(|result| matches!(result, ("", _)))
+            != self.negated
     }
 
     fn iter_matches<'a>(
@@ -70,17 +89,23 @@ impl Glob {
     }
 
     pub fn parse(input: &str) -> Result<Glob> {
-        let mut current = input;
+        let (negated, mut current) = match input.strip_prefix('!') {
+            Some(remainder) if !remainder.starts_with('(') => (true, remainder),
+            _ => (false, input),
+        };
         let mut expression = Vec::new();
 
         while !current.is_empty() {
-            let (part, remainder) = GlobPart::parse(current, false)
+            let (part, remainder) = GlobPart::parse(current, &[])
                 .with_context(|| anyhow!("Failed to parse glob {input}"))?;
             expression.push(part);
             current = remainder;
         }
 
-        Ok(Glob { expression })
+        Ok(Glob {
+            expression,
+            negated,
+        })
     }
 }
 
@@ -162,7 +187,9 @@ impl GlobPart {
         }
     }
 
-    fn parse(input: &str, inside_of_braces: bool) -> Result<(GlobPart, &str)> {
+    /// Parses the next part of the glob. Literals end at any of the
+    /// `terminators`, which delimit the globs inside of braces or parentheses.
+    fn parse<'a>(input: &'a str, terminators: &[char]) -> Result<(GlobPart, &'a str)> {
         debug_assert!(!input.is_empty());
         let two_chars = {
             let mut chars = input.chars();
@@ -173,65 +200,111 @@ impl GlobPart {
             ('*', Some('*')) => Ok((GlobPart::AnyDirectories, &input[2..])),
             ('*', _) => Ok((GlobPart::AnyFile, &input[1..])),
             ('?', _) => Ok((GlobPart::AnyFileChar, &input[1..])),
-            ('[', Some('[')) => todo!("glob char classes are not implemented yet"),
-            ('[', _) => todo!("glob char sequences are not implemented yet"),
-            ('{', Some(_)) => {
-                let mut current = &input[1..];
-                let mut alternatives = Vec::new();
-                let mut expression = Vec::new();
-
-                loop {
-                    let (part, remainder) = GlobPart::parse(current, true)?;
-                    expression.push(part);
-                    current = remainder;
-                    match current.chars().next() {
-                        Some(',') => {
-                            alternatives.push(Glob {
-                                expression: take(&mut expression),
-                            });
-                            current = &current[1..];
-                        }
-                        Some('}') => {
-                            alternatives.push(Glob {
-                                expression: take(&mut expression),
-                            });
-                            current = &current[1..];
-                            break;
-                        }
-                        None => bail!("Unterminated glob braces"),
-                        _ => {
-                            // next part of the glob
-                        }
+            ('[', _) => {
+                let class = &input[1..];
+                let (negated, class) = match class.strip_prefix(['!', '^']) {
+                    Some(class) => (true, class),
+                    None => (false, class),
+                };
+                // A `]` at the start of the class is a literal char
+                let end = class
+                    .char_indices()
+                    .skip(1)
+                    .find_map(|(index, c)| (c == ']').then_some(index))
+                    .context("Unterminated glob character class")?;
+
+                let mut ranges = Vec::new();
+                let mut chars = class[..end].chars();
+                while let Some(start) = chars.next() {
+                    let range_end = chars
+                        .as_str()
+                        .strip_prefix('-')
+                        .and_then(|remainder| remainder.chars().next());
+                    if let Some(range_end) = range_end {
+                        chars.nth(1);
+                        ranges.push((start, range_end));
+                    } else {
+                        ranges.push((start, start));
                     }
                 }
 
-                Ok((GlobPart::Alternatives(alternatives), current))
+                Ok((GlobPart::FileChar { ranges, negated }, &class[end + 1..]))
+            }
+            ('{', Some(_)) => {
+                let (alternatives, remainder) = Self::parse_alternatives(&input[1..], ',', '}')?;
+                Ok((GlobPart::Alternatives(alternatives), remainder))
             }
             ('{', None) => {
                 bail!("Unterminated glob braces")
             }
+            ('!', Some('(')) => {
+                let (alternatives, remainder) = Self::parse_alternatives(&input[2..], '|', ')')?;
+                Ok((GlobPart::Negation(alternatives), remainder))
+            }
+            ('@', Some('(')) => {
+                let (alternatives, remainder) = Self::parse_alternatives(&input[2..], '|', ')')?;
+                Ok((GlobPart::Alternatives(alternatives), remainder))
+            }
             _ => {
-                let mut is_escaped = false;
                 let mut literal = String::new();
-                let mut index = 0;
-                for c in input.chars() {
-                    if is_escaped {
-                        is_escaped = false;
-                    } else if c == '\\' {
-                        is_escaped = true;
-                    } else if c == '/'
-                        || c == '*'
-                        || c == '?'
-                        || c == '['
-                        || c == '{'
-                        || (inside_of_braces && (c == ',' || c == '}'))
-                    {
-                        break;
+                let mut end = input.len();
+                let mut chars = input.char_indices().peekable();
+                while let Some((index, c)) = chars.next() {
+                    let is_group = chars.peek().is_some_and(|(_, next)| *next == '(');
+                    match c {
+                        '\\' => {
+                            if let Some((_, escaped)) = chars.next() {
+                                literal.push(escaped);
+                            }
+                        }
+                        '/' | '*' | '?' | '[' | '{' => {
+                            end = index;
+                            break;
+                        }
+                        '!' | '@' if is_group => {
+                            end = index;
+                            break;
+                        }
+                        c if terminators.contains(&c) => {
+                            end = index;
+                            break;
+                        }
+                        c => literal.push(c),
                     }
-                    literal.push(c);
-                    index += 1;
                 }
-                Ok((GlobPart::File(literal), &input[index..]))
+                Ok((GlobPart::File(literal), &input[end..]))
+            }
+        }
+    }
+
+    /// Parses a list of globs separated by `separator` up to the closing
+    /// `end`, e.g. the contents of `{a,b}` or `!(a|b)`.
+    fn parse_alternatives(input: &str, separator: char, end: char) -> Result<(Vec<Glob>, &str)> {
+        let mut current = input;
+        let mut alternatives = Vec::new();
+        let mut expression = Vec::new();
+
+        loop {
+            if current.is_empty() {
+                bail!("Unterminated glob, expected `{end}`");
+            }
+            let (part, remainder) = GlobPart::parse(current, &[separator, end])?;
+            expression.push(part);
+            current = remainder;
+            match current.chars().next() {
+                Some(c) if c == separator || c == end => {
+                    alternatives.push(Glob {
+                        expression: take(&mut expression),
+                        negated: false,
+                    });
+                    current = &current[1..];
+                    if c == end {
+                        return Ok((alternatives, current));
+                    }
+                }
+                _ => {
+                    // next part of the glob
+                }
             }
         }
     }
@@ -289,7 +362,14 @@ impl<'a> Iterator for GlobPartMatchesIterator<'a> {
                     None
                 }
             }
-            GlobPart::AnyFileChar => todo!(),
+            GlobPart::AnyFileChar => {
+                if self.index > 0 {
+                    return None;
+                }
+                self.index = 1;
+                let c = self.path.chars().next()?;
+                (c != '/').then(|| (&self.path[c.len_utf8()..], false))
+            }
             GlobPart::PathSeparator => {
                 if self.index == 0 {
                     self.index = 1;
@@ -304,16 +384,17 @@ impl<'a> Iterator for GlobPartMatchesIterator<'a> {
                     None
                 }
             }
-            GlobPart::FileChar(chars) => loop {
-                if let Some(c) = chars.get(self.index) {
-                    self.index += 1;
-                    if self.path.starts_with(*c) {
-                        return Some((&self.path[1..], false));
-                    }
-                } else {
+            GlobPart::FileChar { ranges, negated } => {
+                if self.index > 0 {
                     return None;
                 }
-            },
+                self.index = 1;
+                let c = self.path.chars().next()?;
+                let in_ranges = ranges
+                    .iter()
+                    .any(|&(start, end)| (start..=end).contains(&c));
+                (c != '/' && in_ranges != *negated).then(|| (&self.path[c.len_utf8()..], false))
+            }
             GlobPart::File(name) => {
                 if self.index == 0 && self.path.starts_with(name) {
                     self.index += 1;
@@ -340,6 +421,32 @@ impl<'a> Iterator for GlobPartMatchesIterator<'a> {
                     return None;
                 }
             },
+            GlobPart::Negation(alternatives) => {
+                // Tries every prefix of the current filename, shortest first
+                let path = self.path;
+                let filename_len = path.find('/').unwrap_or(path.len());
+                while self.index <= filename_len {
+                    let end = self.index;
+                    self.index += 1;
+                    if !path.is_char_boundary(end) {
+                        continue;
+                    }
+                    let (candidate, remainder) = path.split_at(end);
+                    let is_excluded = alternatives.iter().any(|alternative| {
+                        alternative
+                            .iter_matches(
+                                candidate,
+                                self.previous_part_is_path_separator_equivalent,
+                                false,
+                            )
+                            .any(|(rest, _)| rest.is_empty())
+                    });
+                    if !is_excluded {
+                        return Some((remainder, false));
+                    }
+                }
+                None
+            }
         }
     }
 }
@@ -408,6 +515,20 @@ mod tests {
     #[case::alternatives_nested2("{a,b/c,d/e/{f,g/h}}", "b/c")]
     #[case::alternatives_nested3("{a,b/c,d/e/{f,g/h}}", "d/e/f")]
     #[case::alternatives_nested4("{a,b/c,d/e/{f,g/h}}", "d/e/g/h")]
+    #[case::alternatives_empty("file{,.min}.js", "file.js")]
+    #[case::alternatives_empty("file{,.min}.js", "file.min.js")]
+    #[case::alternatives_globstar("{src,lib}/**/*.{js,ts}", "lib/dir/file.ts")]
+    #[case::escaped_braces("\\{a,b\\}.js", "{a,b}.js")]
+    #[case::any_char("file.?s", "file.ts")]
+    #[case::char_class("*.[jt]s", "file.ts")]
+    #[case::char_range("file[0-9].js", "file1.js")]
+    #[case::char_class_negated("*.[!j]s", "file.ts")]
+    #[case::extglob_alternatives("*.@(js|ts)", "file.ts")]
+    #[case::extglob_negation("!(*.test).js", "file.js")]
+    #[case::extglob_negation_dir("src/!(vendor)/**/*.js", "src/app/file.js")]
+    #[case::extglob_negation_in_braces("{!(*.d).ts,*.js}", "file.ts")]
+    #[case::negated("!**/node_modules/**", "src/file.js")]
+    #[case::negated_partial("!**/node_modules/**", "node_modules/")]
     fn glob_match(#[case] glob: &str, #[case] path: &str) {
         let glob = Glob::parse(glob).unwrap();
 
@@ -418,6 +539,15 @@ mod tests {
 
     #[rstest]
     #[case::early_end("*.raw", "hello.raw.js")]
+    #[case::alternatives("file.{ts,js}", "file.jsx")]
+    #[case::any_char("file.?s", "file.s")]
+    #[case::any_char_separator("dir?file.js", "dir/file.js")]
+    #[case::char_class("*.[jt]s", "file.cs")]
+    #[case::char_class_negated("*.[!j]s", "file.js")]
+    #[case::extglob_negation("!(*.test).js", "file.test.js")]
+    #[case::extglob_negation_dir("src/!(vendor)/**/*.js", "src/vendor/file.js")]
+    #[case::extglob_negation_in_braces("{!(*.d).ts,*.js}", "file.d.ts")]
+    #[case::negated("!**/node_modules/**", "node_modules/next/index.js")]
     fn glob_not_matching(#[case] glob: &str, #[case] path: &str) {
         let glob = Glob::parse(glob).unwrap();
 
@@ -14,12 +14,14 @@ mod invalidation;
 mod invalidator_map;
 pub mod json;
 mod mutex_map;
+pub mod overlay_fs;
 mod read_glob;
 mod retry;
 pub mod rope;
 pub mod source_context;
 pub mod util;
 pub(crate) mod virtual_fs;
+pub mod zip_fs;
 
 use std::{
     borrow::Cow,
@@ -49,7 +51,8 @@ use glob::Glob;
 use invalidator_map::InvalidatorMap;
 use jsonc_parser::{parse_to_serde_value, ParseOptions};
 use mime::Mime;
-use notify::{watcher, DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use notify::{watcher, DebouncedEvent, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+pub use overlay_fs::OverlayFileSystem;
 use read_glob::read_glob;
 pub use read_glob::ReadGlobResult;
 use serde::{Deserialize, Serialize};
@@ -61,12 +64,13 @@ use tokio::{
 };
 use tracing::{instrument, Level};
 use turbo_tasks::{
-    mark_stateful, spawn_thread, trace::TraceRawVcs, Completion, InvalidationReason, Invalidator,
-    ReadRef, ValueToString, Vc,
+    mark_stateful, spawn_thread, trace::TraceRawVcs, Completion, FunctionId, InvalidationReason,
+    Invalidator, ReadRef, ValueToString, Vc,
 };
 use turbo_tasks_hash::{hash_xxh3_hash64, DeterministicHash, DeterministicHasher};
 use util::{extract_disk_access, join_path, normalize_path, sys_to_unix, unix_to_sys};
 pub use virtual_fs::VirtualFileSystem;
+pub use zip_fs::{ZipArchiveFileSystem, ZipFileSystem};
 
 use self::{
     invalidation::{WatchStart, Write},
@@ -103,9 +107,44 @@ pub trait FileSystem: ValueToString {
     fn metadata(self: Vc<Self>, fs_path: Vc<FileSystemPath>) -> Vc<FileMeta>;
 }
 
+/// Configures how a [DiskFileSystem] watches for changes, see
+/// [DiskFileSystem::start_watching_with_options].
+#[derive(Clone, Debug, Default)]
+pub struct WatchOptions {
+    /// Globs of paths relative to the root whose changes are ignored, e.g.
+    /// `.git` or `data/**/*.csv`. A path is also ignored when one of its parent
+    /// directories matches. Reads of ignored files are not invalidated when
+    /// they change.
+    pub ignore: Vec<String>,
+    /// Polls for changes in this interval instead of relying on the file
+    /// system events of the OS, e.g. for network file systems or docker
+    /// volumes where these events don't arrive.
+    pub poll_interval: Option<Duration>,
+    /// Reports the changed paths as reason of the invalidations, see
+    /// [DiskFileSystem::start_watching_with_invalidation_reason].
+    pub report_invalidation_reason: bool,
+}
+
+/// The watcher of a [DiskWatcher], which uses file system events or polling.
+enum NotifyWatcher {
+    Native(RecommendedWatcher),
+    Polling(PollWatcher),
+}
+
+impl NotifyWatcher {
+    fn watch(&mut self, path: &Path, mode: RecursiveMode) -> notify::Result<()> {
+        match self {
+            NotifyWatcher::Native(watcher) => watcher.watch(path, mode),
+            NotifyWatcher::Polling(watcher) => watcher.watch(path, mode),
+        }
+    }
+}
+
 #[derive(Default)]
 struct DiskWatcher {
-    watcher: Mutex<Option<RecommendedWatcher>>,
+    watcher: Mutex<Option<NotifyWatcher>>,
+    /// The parsed [WatchOptions::ignore] globs.
+    ignore: Mutex<Vec<Glob>>,
     /// Keeps track of which directories are currently watched. This is only
     /// used on a OS that doesn't support recursive watching.
     #[cfg(not(any(target_os = "macos", target_os = "windows")))]
@@ -113,6 +152,45 @@ struct DiskWatcher {
 }
 
 impl DiskWatcher {
+    /// Whether changes to `path` are ignored, see [WatchOptions::ignore].
+    fn is_ignored(&self, path: &Path, root_path: &Path) -> bool {
+        let ignore = self.ignore.lock().unwrap();
+        if ignore.is_empty() {
+            return false;
+        }
+        let Ok(relative_path) = path.strip_prefix(root_path) else {
+            return false;
+        };
+        let relative_path = relative_path.to_string_lossy();
+        let relative_path = sys_to_unix(&relative_path);
+        let mut path = relative_path.as_ref();
+        loop {
+            if ignore.iter().any(|glob| glob.execute(path)) {
+                return true;
+            }
+            let Some((parent, _)) = path.rsplit_once('/') else {
+                return false;
+            };
+            path = parent;
+        }
+    }
+
+    /// Whether all paths affected by `event` are ignored.
+    fn is_ignored_event(&self, event: &DebouncedEvent, root_path: &Path) -> bool {
+        match event {
+            DebouncedEvent::Write(path)
+            | DebouncedEvent::Create(path)
+            | DebouncedEvent::Remove(path)
+            | DebouncedEvent::Chmod(path)
+            | DebouncedEvent::NoticeRemove(path)
+            | DebouncedEvent::NoticeWrite(path) => self.is_ignored(path, root_path),
+            DebouncedEvent::Rename(source, destination) => {
+                self.is_ignored(source, root_path) && self.is_ignored(destination, root_path)
+            }
+            DebouncedEvent::Rescan | DebouncedEvent::Error(..) => false,
+        }
+    }
+
     #[cfg(not(any(target_os = "macos", target_os = "windows")))]
     fn restore_if_watching(&self, dir_path: &Path, root_path: &Path) -> Result<()> {
         if self.watching.contains(dir_path) {
@@ -137,10 +215,13 @@ impl DiskWatcher {
     #[cfg(not(any(target_os = "macos", target_os = "windows")))]
     fn start_watching(
         &self,
-        watcher: &mut std::sync::MutexGuard<Option<RecommendedWatcher>>,
+        watcher: &mut std::sync::MutexGuard<Option<NotifyWatcher>>,
         dir_path: &Path,
         root_path: &Path,
     ) -> Result<()> {
+        if self.is_ignored(dir_path, root_path) {
+            return Ok(());
+        }
         if let Some(watcher) = watcher.as_mut() {
             let mut path = dir_path;
             while let Err(err) = watcher.watch(path, RecursiveMode::NonRecursive) {
@@ -187,6 +268,19 @@ pub struct DiskFileSystem {
 }
 
 impl DiskFileSystem {
+    /// The functions which read from the disk. Their cached results are
+    /// stale when files were changed while no watcher was running, e.g.
+    /// between two runs of a process with a persistent cache.
+    pub fn volatile_functions() -> Vec<FunctionId> {
+        vec![
+            *DISKFILESYSTEM_IMPL_TRAIT_FILESYSTEM_READ_FUNCTION_ID,
+            *DISKFILESYSTEM_IMPL_TRAIT_FILESYSTEM_READ_LINK_FUNCTION_ID,
+            *DISKFILESYSTEM_IMPL_TRAIT_FILESYSTEM_READ_DIR_FUNCTION_ID,
+            *DISKFILESYSTEM_IMPL_TRAIT_FILESYSTEM_TRACK_FUNCTION_ID,
+            *DISKFILESYSTEM_IMPL_TRAIT_FILESYSTEM_METADATA_FUNCTION_ID,
+        ]
+    }
+
     /// Returns the root as Path
     fn root_path(&self) -> &Path {
         simplified(Path::new(&self.root))
@@ -258,18 +352,30 @@ impl DiskFileSystem {
     }
 
     pub fn start_watching(&self) -> Result<()> {
-        self.start_watching_internal(false)
+        self.start_watching_with_options(&WatchOptions::default())
     }
 
     pub fn start_watching_with_invalidation_reason(&self) -> Result<()> {
-        self.start_watching_internal(true)
+        self.start_watching_with_options(&WatchOptions {
+            report_invalidation_reason: true,
+            ..Default::default()
+        })
     }
 
-    fn start_watching_internal(&self, report_invalidation_reason: bool) -> Result<()> {
+    /// Starts watching for changes like [DiskFileSystem::start_watching], but
+    /// allows to ignore paths and to poll for changes. Does nothing when the
+    /// file system is already watched.
+    pub fn start_watching_with_options(&self, options: &WatchOptions) -> Result<()> {
         let mut watcher_guard = self.watcher.watcher.lock().unwrap();
         if watcher_guard.is_some() {
             return Ok(());
         }
+        *self.watcher.ignore.lock().unwrap() = options
+            .ignore
+            .iter()
+            .map(|glob| Glob::parse(glob))
+            .collect::<Result<Vec<_>>>()?;
+        let report_invalidation_reason = options.report_invalidation_reason;
         let invalidator_map = self.invalidator_map.clone();
         let dir_invalidator_map = self.dir_invalidator_map.clone();
         let root = self.root.clone();
@@ -282,15 +388,21 @@ impl DiskFileSystem {
         // Create a channel to receive the events.
         let (tx, rx) = channel();
         // Create a watcher object, delivering debounced events.
-        // The notification back-end is selected based on the platform.
-        let mut watcher = watcher(tx, Duration::from_millis(1))?;
+        // The notification back-end is selected based on the platform, unless
+        // polling is requested.
+        let mut watcher = match options.poll_interval {
+            Some(interval) => NotifyWatcher::Polling(PollWatcher::new(tx, interval)?),
+            None => NotifyWatcher::Native(watcher(tx, Duration::from_millis(1))?),
+        };
         // Add a path to be watched. All files and directories at that path and
         // below will be monitored for changes.
         #[cfg(any(target_os = "macos", target_os = "windows"))]
         watcher.watch(&root_path, RecursiveMode::Recursive)?;
         #[cfg(not(any(target_os = "macos", target_os = "windows")))]
         for dir_path in self.watcher.watching.iter() {
-            watcher.watch(&*dir_path, RecursiveMode::NonRecursive)?;
+            if !self.watcher.is_ignored(&dir_path, &root_path) {
+                watcher.watch(&dir_path, RecursiveMode::NonRecursive)?;
+            }
         }
 
         // We need to invalidate all reads that happened before watching
@@ -321,7 +433,6 @@ impl DiskFileSystem {
         watcher_guard.replace(watcher);
         drop(watcher_guard);
 
-        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
         let disk_watcher = self.watcher.clone();
 
         spawn_thread(move || {
@@ -338,6 +449,9 @@ impl DiskFileSystem {
                 });
                 loop {
                     match event {
+                        Ok(ref event) if disk_watcher.is_ignored_event(event, &root_path) => {
+                            // ignored
+                        }
                         Ok(DebouncedEvent::Write(path)) => {
                             batched_invalidate_path.insert(path);
                         }
@@ -2139,6 +2253,24 @@ mod tests {
 
     use super::{virtual_fs::VirtualFileSystem, *};
 
+    #[test]
+    fn watch_ignore() {
+        let watcher = DiskWatcher::default();
+        *watcher.ignore.lock().unwrap() = vec![
+            Glob::parse(".git").unwrap(),
+            Glob::parse("data/**/*.csv").unwrap(),
+        ];
+        let root = Path::new("/project");
+        let is_ignored = |path: &str| watcher.is_ignored(&root.join(path), root);
+
+        assert!(is_ignored(".git"));
+        assert!(is_ignored(".git/refs/heads/main"));
+        assert!(is_ignored("data/2023/prices.csv"));
+        assert!(!is_ignored(".github/workflows/ci.yml"));
+        assert!(!is_ignored("data/2023/prices.json"));
+        assert!(!is_ignored("src/index.js"));
+    }
+
     #[tokio::test]
     async fn with_extension() {
         crate::register();
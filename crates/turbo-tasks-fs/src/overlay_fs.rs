@@ -0,0 +1,242 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{bail, Result};
+use auto_hash_map::AutoMap;
+use turbo_tasks::{Completion, ValueToString, Vc};
+
+use crate::{
+    util::normalize_path, DirectoryContent, DirectoryEntry, FileContent, FileMeta, FileSystem,
+    FileSystemPath, LinkContent,
+};
+
+/// A read-only [FileSystem] which serves files from memory on top of an
+/// optional `base` directory, e.g. to provide test fixtures or generated files
+/// without writing them to disk. Files in memory take precedence over the
+/// files in `base`.
+#[turbo_tasks::value]
+pub struct OverlayFileSystem {
+    base: Option<Vc<FileSystemPath>>,
+    files: BTreeMap<String, Vc<FileContent>>,
+    /// The directories which contain files in memory, including the root.
+    directories: BTreeSet<String>,
+}
+
+impl OverlayFileSystem {
+    /// Creates a new [`Vc<OverlayFileSystem>`] with the given files, whose
+    /// paths are relative to its root.
+    ///
+    /// NOTE: This function is not a `turbo_tasks::function`, so that each
+    /// overlay is a distinct file system, like a [super::VirtualFileSystem].
+    pub fn new(
+        base: Option<Vc<FileSystemPath>>,
+        files: impl IntoIterator<Item = (String, Vc<FileContent>)>,
+    ) -> Vc<Self> {
+        let files = files
+            .into_iter()
+            .map(|(path, content)| (normalize_path(&path).unwrap_or(path), content))
+            .collect::<BTreeMap<_, _>>();
+        let mut directories = BTreeSet::new();
+        for path in files.keys() {
+            let mut path = path.as_str();
+            while let Some((dir, _)) = path.rsplit_once('/') {
+                directories.insert(dir.to_string());
+                path = dir;
+            }
+            directories.insert(String::new());
+        }
+        Self::cell(OverlayFileSystem {
+            base,
+            files,
+            directories,
+        })
+    }
+
+    fn contains(&self, path: &str) -> bool {
+        self.files.contains_key(path) || self.directories.contains(path)
+    }
+}
+
+/// Returns the name of `path` if it's a direct child of `dir`.
+fn child_name<'a>(dir: &str, path: &'a str) -> Option<&'a str> {
+    let name = if dir.is_empty() {
+        path
+    } else {
+        path.strip_prefix(dir)?.strip_prefix('/')?
+    };
+    (!name.is_empty() && !name.contains('/')).then_some(name)
+}
+
+#[turbo_tasks::value_impl]
+impl FileSystem for OverlayFileSystem {
+    #[turbo_tasks::function]
+    async fn read(&self, fs_path: Vc<FileSystemPath>) -> Result<Vc<FileContent>> {
+        let path = &fs_path.await?.path;
+        Ok(match (self.files.get(path), self.base) {
+            (Some(content), _) => *content,
+            (None, Some(base)) if !self.directories.contains(path) => {
+                base.join(path.clone()).read()
+            }
+            (None, _) => FileContent::NotFound.cell(),
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn read_link(&self, fs_path: Vc<FileSystemPath>) -> Result<Vc<LinkContent>> {
+        let path = &fs_path.await?.path;
+        Ok(match self.base {
+            Some(base) if !self.contains(path) => base.join(path.clone()).read_link(),
+            _ => LinkContent::NotFound.cell(),
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn read_dir(&self, fs_path: Vc<FileSystemPath>) -> Result<Vc<DirectoryContent>> {
+        let path = &fs_path.await?.path;
+        let mut found = self.directories.contains(path);
+        let mut entries = AutoMap::new();
+        if let Some(base) = self.base {
+            if let DirectoryContent::Entries(base_entries) =
+                &*base.join(path.clone()).read_dir().await?
+            {
+                found = true;
+                for (name, entry) in base_entries {
+                    use DirectoryEntry::*;
+
+                    let path = fs_path.join(name.clone());
+                    let entry = match *entry {
+                        File(_) => File(path),
+                        Directory(_) => Directory(path),
+                        Symlink(_) => Symlink(path),
+                        Other(_) => Other(path),
+                        Error => Error,
+                    };
+                    entries.insert(name.clone(), entry);
+                }
+            }
+        }
+        if !found {
+            return Ok(DirectoryContent::not_found());
+        }
+
+        for name in self.files.keys().filter_map(|file| child_name(path, file)) {
+            let entry = DirectoryEntry::File(fs_path.join(name.to_string()));
+            entries.insert(name.to_string(), entry);
+        }
+        for name in self
+            .directories
+            .iter()
+            .filter_map(|dir| child_name(path, dir))
+        {
+            let entry = DirectoryEntry::Directory(fs_path.join(name.to_string()));
+            entries.insert(name.to_string(), entry);
+        }
+        Ok(DirectoryContent::new(entries))
+    }
+
+    #[turbo_tasks::function]
+    async fn track(&self, fs_path: Vc<FileSystemPath>) -> Result<Vc<Completion>> {
+        let path = &fs_path.await?.path;
+        Ok(match self.base {
+            Some(base) if !self.files.contains_key(path) => base.join(path.clone()).track(),
+            _ => Completion::immutable(),
+        })
+    }
+
+    #[turbo_tasks::function]
+    fn write(
+        &self,
+        _fs_path: Vc<FileSystemPath>,
+        _content: Vc<FileContent>,
+    ) -> Result<Vc<Completion>> {
+        bail!("Writing is not possible on the overlay file system")
+    }
+
+    #[turbo_tasks::function]
+    fn write_link(
+        &self,
+        _fs_path: Vc<FileSystemPath>,
+        _target: Vc<LinkContent>,
+    ) -> Result<Vc<Completion>> {
+        bail!("Writing is not possible on the overlay file system")
+    }
+
+    #[turbo_tasks::function]
+    async fn metadata(&self, fs_path: Vc<FileSystemPath>) -> Result<Vc<FileMeta>> {
+        let path = &fs_path.await?.path;
+        if let Some(content) = self.files.get(path) {
+            return Ok(match &*content.await? {
+                FileContent::Content(file) => file.meta.clone().cell(),
+                FileContent::NotFound => bail!("{} not found", fs_path.to_string().await?),
+            });
+        }
+        match self.base {
+            Some(base) if !self.directories.contains(path) => {
+                Ok(base.join(path.clone()).metadata())
+            }
+            _ => Ok(FileMeta::default().cell()),
+        }
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for OverlayFileSystem {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell(match self.base {
+            Some(base) => format!("overlay file system on {}", base.to_string().await?),
+            None => "overlay file system".to_string(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use turbo_tasks::Vc;
+
+    use super::OverlayFileSystem;
+    use crate::{DirectoryContent, File, FileContent, FileSystem, FileSystemEntryType};
+
+    #[tokio::test]
+    async fn files_in_memory() {
+        crate::register();
+
+        turbo_tasks_testing::VcStorage::with(async {
+            let content = |content: &str| FileContent::Content(File::from(content)).cell();
+            let fs = OverlayFileSystem::new(
+                None,
+                [
+                    ("src/index.js".to_string(), content("import './a'")),
+                    ("./src/a.js".to_string(), content("export {}")),
+                ],
+            );
+            let root = Vc::upcast::<Box<dyn FileSystem>>(fs).root();
+
+            let index = root.join("src/index.js".to_string());
+            let FileContent::Content(file) = &*index.read().await? else {
+                panic!("src/index.js should exist");
+            };
+            assert_eq!(file.content().to_str()?, "import './a'");
+            assert_eq!(
+                *root.join("src/b.js".to_string()).get_type().await?,
+                FileSystemEntryType::NotFound
+            );
+            assert_eq!(
+                *root.join("src".to_string()).get_type().await?,
+                FileSystemEntryType::Directory
+            );
+
+            let DirectoryContent::Entries(entries) =
+                &*root.join("src".to_string()).read_dir().await?
+            else {
+                panic!("src should be a directory");
+            };
+            let mut names = entries.iter().map(|(name, _)| name).collect::<Vec<_>>();
+            names.sort();
+            assert_eq!(names, ["a.js", "index.js"]);
+
+            anyhow::Ok(())
+        })
+        .await
+        .unwrap()
+    }
+}
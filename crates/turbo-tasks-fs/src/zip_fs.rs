@@ -0,0 +1,547 @@
+use std::{
+    collections::HashMap,
+    io::{Cursor, Read},
+    sync::Arc,
+};
+
+use anyhow::{bail, Context, Result};
+use auto_hash_map::AutoMap;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, Completion, ValueToString, Vc};
+use zip::ZipArchive;
+
+use crate::{
+    DirectoryContent, DirectoryEntry, File, FileContent, FileMeta, FileSystem, FileSystemEntryType,
+    FileSystemPath, LinkContent,
+};
+
+/// A read-only [FileSystem] which exposes the files below `root` the way the
+/// file system layers of Yarn Plug'n'Play do:
+///
+/// * Zip archives can be accessed like directories, e.g.
+///   `.yarn/cache/react-npm-18.2.0-a1b2c3.zip/node_modules/react/index.js`.
+/// * `__virtual__` paths, which Yarn uses to give packages with peer
+///   dependencies a distinct location per set of peers, point to the path they
+///   are a virtual copy of, e.g. `.yarn/__virtual__/foo-virtual-d4e5f6/0/cache`
+///   points to `.yarn/cache`.
+///
+/// The contents of the archives are read with a [ZipArchiveFileSystem] per
+/// archive.
+#[turbo_tasks::value]
+pub struct ZipFileSystem {
+    root: Vc<FileSystemPath>,
+}
+
+#[turbo_tasks::value_impl]
+impl ZipFileSystem {
+    /// Creates a [ZipFileSystem] whose root is `root`. The file system is the
+    /// same for the same `root`.
+    #[turbo_tasks::function]
+    pub fn new(root: Vc<FileSystemPath>) -> Vc<Self> {
+        Self::cell(ZipFileSystem { root })
+    }
+
+    /// Finds where the file at `fs_path` is stored.
+    #[turbo_tasks::function]
+    async fn locate(&self, fs_path: Vc<FileSystemPath>) -> Result<Vc<ZipFsLocation>> {
+        let Some(path) = resolve_virtual_path(&fs_path.await?.path) else {
+            return Ok(ZipFsLocation::NotFound.cell());
+        };
+        let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+        let mut outer_path = String::new();
+        while let Some(segment) = segments.next() {
+            if !outer_path.is_empty() {
+                outer_path.push('/');
+            }
+            outer_path.push_str(segment);
+            if segment.ends_with(".zip") {
+                let archive = self.root.join(outer_path.clone()).resolve().await?;
+                if *archive.get_type().await? == FileSystemEntryType::File {
+                    let inner_path = segments.collect::<Vec<_>>().join("/");
+                    return Ok(ZipFsLocation::InArchive(
+                        ZipArchiveFileSystem::new(archive).root().join(inner_path),
+                    )
+                    .cell());
+                }
+            }
+        }
+        Ok(ZipFsLocation::Outside(self.root.join(path)).cell())
+    }
+}
+
+#[turbo_tasks::value]
+enum ZipFsLocation {
+    /// A path of the underlying file system, outside of any zip archive.
+    Outside(Vc<FileSystemPath>),
+    /// A path on the [ZipArchiveFileSystem] of a zip archive.
+    InArchive(Vc<FileSystemPath>),
+    NotFound,
+}
+
+impl ZipFsLocation {
+    fn path(&self) -> Option<Vc<FileSystemPath>> {
+        match self {
+            ZipFsLocation::Outside(path) | ZipFsLocation::InArchive(path) => Some(*path),
+            ZipFsLocation::NotFound => None,
+        }
+    }
+}
+
+/// Resolves the `__virtual__/<hash>/<depth>` segments of a path. The depth is
+/// the number of directories to go up from the parent of `__virtual__`.
+/// Returns `None` when the path is incomplete or escapes the root.
+fn resolve_virtual_path(path: &str) -> Option<String> {
+    let mut resolved = Vec::new();
+    let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+    while let Some(segment) = segments.next() {
+        if segment == "__virtual__" || segment == "$$virtual" {
+            let _hash = segments.next()?;
+            let depth = segments.next()?.parse::<usize>().ok()?;
+            for _ in 0..depth {
+                resolved.pop()?;
+            }
+        } else {
+            resolved.push(segment);
+        }
+    }
+    Some(resolved.join("/"))
+}
+
+#[turbo_tasks::value_impl]
+impl FileSystem for ZipFileSystem {
+    #[turbo_tasks::function]
+    async fn read(self: Vc<Self>, fs_path: Vc<FileSystemPath>) -> Result<Vc<FileContent>> {
+        Ok(match self.locate(fs_path).await?.path() {
+            Some(path) => path.read(),
+            None => FileContent::NotFound.cell(),
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn read_link(self: Vc<Self>, fs_path: Vc<FileSystemPath>) -> Result<Vc<LinkContent>> {
+        Ok(match self.locate(fs_path).await?.path() {
+            Some(path) => path.read_link(),
+            None => LinkContent::NotFound.cell(),
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn read_dir(self: Vc<Self>, fs_path: Vc<FileSystemPath>) -> Result<Vc<DirectoryContent>> {
+        let location = self.locate(fs_path).await?;
+        let Some(path) = location.path() else {
+            return Ok(DirectoryContent::not_found());
+        };
+        let DirectoryContent::Entries(entries) = &*path.read_dir().await? else {
+            return Ok(DirectoryContent::not_found());
+        };
+        let is_outside = matches!(&*location, ZipFsLocation::Outside(_));
+        let mut converted_entries = AutoMap::with_capacity(entries.len());
+        for (name, entry) in entries {
+            use DirectoryEntry::*;
+
+            let path = fs_path.join(name.clone());
+            let entry = match *entry {
+                File(_) if is_outside && name.ends_with(".zip") => Directory(path),
+                File(_) => File(path),
+                Directory(_) => Directory(path),
+                Symlink(_) => Symlink(path),
+                Other(_) => Other(path),
+                Error => Error,
+            };
+            converted_entries.insert(name.clone(), entry);
+        }
+        Ok(DirectoryContent::new(converted_entries))
+    }
+
+    #[turbo_tasks::function]
+    async fn track(self: Vc<Self>, fs_path: Vc<FileSystemPath>) -> Result<Vc<Completion>> {
+        Ok(match self.locate(fs_path).await?.path() {
+            Some(path) => path.track(),
+            None => Completion::immutable(),
+        })
+    }
+
+    #[turbo_tasks::function]
+    fn write(
+        &self,
+        _fs_path: Vc<FileSystemPath>,
+        _content: Vc<FileContent>,
+    ) -> Result<Vc<Completion>> {
+        bail!("Writing is not possible on the zip file system")
+    }
+
+    #[turbo_tasks::function]
+    fn write_link(
+        &self,
+        _fs_path: Vc<FileSystemPath>,
+        _target: Vc<LinkContent>,
+    ) -> Result<Vc<Completion>> {
+        bail!("Writing is not possible on the zip file system")
+    }
+
+    #[turbo_tasks::function]
+    async fn metadata(self: Vc<Self>, fs_path: Vc<FileSystemPath>) -> Result<Vc<FileMeta>> {
+        match self.locate(fs_path).await?.path() {
+            Some(path) => Ok(path.metadata()),
+            None => bail!("{} not found", fs_path.to_string().await?),
+        }
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ZipFileSystem {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell(format!(
+            "zip file system at {}",
+            self.root.to_string().await?
+        )))
+    }
+}
+
+/// A read-only [FileSystem] which contains the files and directories of a zip
+/// archive. Links in the archive are not supported.
+#[turbo_tasks::value]
+pub struct ZipArchiveFileSystem {
+    archive: Vc<FileSystemPath>,
+}
+
+#[turbo_tasks::value_impl]
+impl ZipArchiveFileSystem {
+    /// Creates a [ZipArchiveFileSystem] for the zip archive at `archive`. The
+    /// file system is the same for the same `archive`.
+    #[turbo_tasks::function]
+    pub fn new(archive: Vc<FileSystemPath>) -> Vc<Self> {
+        Self::cell(ZipArchiveFileSystem { archive })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+enum ZipEntry {
+    /// A file with the index of its entry in the archive.
+    File(usize),
+    Directory,
+}
+
+/// A zip archive whose central directory has been parsed, so its entries can
+/// be read without parsing the archive again. Clones of the archive share the
+/// parsed directory and the content.
+#[turbo_tasks::value(serialization = "none", eq = "manual", cell = "new")]
+struct ParsedZipArchive {
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    zip: Option<ZipArchive<Cursor<Arc<[u8]>>>>,
+}
+
+impl PartialEq for ParsedZipArchive {
+    fn eq(&self, _other: &Self) -> bool {
+        false
+    }
+}
+
+#[turbo_tasks::function]
+async fn parse_zip_archive(archive: Vc<FileSystemPath>) -> Result<Vc<ParsedZipArchive>> {
+    let FileContent::Content(file) = &*archive.read().await? else {
+        return Ok(ParsedZipArchive { zip: None }.cell());
+    };
+    let content = Arc::<[u8]>::from(&*file.content().to_bytes()?);
+    let zip = ZipArchive::new(Cursor::new(content))
+        .with_context(|| format!("unable to read zip archive {}", archive.to_string().await?))?;
+    Ok(ParsedZipArchive { zip: Some(zip) }.cell())
+}
+
+/// The files and directories in a zip archive, by their path in the archive.
+#[turbo_tasks::value]
+struct ZipIndex {
+    entries: HashMap<String, ZipEntry>,
+}
+
+#[turbo_tasks::function]
+async fn zip_index(archive: Vc<FileSystemPath>) -> Result<Vc<ZipIndex>> {
+    let mut entries = HashMap::new();
+    if let Some(zip) = &parse_zip_archive(archive).await?.zip {
+        let mut zip = zip.clone();
+        for index in 0..zip.len() {
+            let entry = zip.by_index_raw(index)?;
+            let name = entry.name().trim_end_matches('/').to_string();
+            // Archives don't need to contain entries for all directories.
+            let mut parent = name.as_str();
+            while let Some((dir, _)) = parent.rsplit_once('/') {
+                entries
+                    .entry(dir.to_string())
+                    .or_insert(ZipEntry::Directory);
+                parent = dir;
+            }
+            let entry = if entry.is_dir() {
+                ZipEntry::Directory
+            } else {
+                ZipEntry::File(index)
+            };
+            entries.insert(name, entry);
+        }
+    }
+    Ok(ZipIndex { entries }.cell())
+}
+
+#[turbo_tasks::function]
+async fn read_zip_entry(archive: Vc<FileSystemPath>, index: usize) -> Result<Vc<FileContent>> {
+    let Some(zip) = &parse_zip_archive(archive).await?.zip else {
+        return Ok(FileContent::NotFound.cell());
+    };
+    let mut zip = zip.clone();
+    let mut entry = zip.by_index(index)?;
+    let mut content = Vec::with_capacity(entry.size() as usize);
+    entry.read_to_end(&mut content)?;
+    Ok(FileContent::Content(File::from(content)).cell())
+}
+
+#[turbo_tasks::value_impl]
+impl FileSystem for ZipArchiveFileSystem {
+    #[turbo_tasks::function]
+    async fn read(&self, fs_path: Vc<FileSystemPath>) -> Result<Vc<FileContent>> {
+        let fs_path = fs_path.await?;
+        Ok(
+            match zip_index(self.archive).await?.entries.get(&fs_path.path) {
+                Some(ZipEntry::File(index)) => read_zip_entry(self.archive, *index),
+                _ => FileContent::NotFound.cell(),
+            },
+        )
+    }
+
+    #[turbo_tasks::function]
+    fn read_link(&self, _fs_path: Vc<FileSystemPath>) -> Vc<LinkContent> {
+        LinkContent::NotFound.cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn read_dir(&self, fs_path: Vc<FileSystemPath>) -> Result<Vc<DirectoryContent>> {
+        let path = &fs_path.await?.path;
+        let index = zip_index(self.archive).await?;
+        if !path.is_empty() && index.entries.get(path) != Some(&ZipEntry::Directory) {
+            return Ok(DirectoryContent::not_found());
+        }
+        let mut entries = AutoMap::new();
+        for (entry_path, entry) in index.entries.iter() {
+            let name = match entry_path.rsplit_once('/') {
+                Some((dir, name)) if dir == path => name,
+                None if path.is_empty() => entry_path.as_str(),
+                _ => continue,
+            };
+            let entry_path = fs_path.join(name.to_string());
+            let entry = match entry {
+                ZipEntry::File(_) => DirectoryEntry::File(entry_path),
+                ZipEntry::Directory => DirectoryEntry::Directory(entry_path),
+            };
+            entries.insert(name.to_string(), entry);
+        }
+        Ok(DirectoryContent::new(entries))
+    }
+
+    #[turbo_tasks::function]
+    fn track(&self, _fs_path: Vc<FileSystemPath>) -> Vc<Completion> {
+        self.archive.track()
+    }
+
+    #[turbo_tasks::function]
+    fn write(
+        &self,
+        _fs_path: Vc<FileSystemPath>,
+        _content: Vc<FileContent>,
+    ) -> Result<Vc<Completion>> {
+        bail!("Writing is not possible on the zip archive file system")
+    }
+
+    #[turbo_tasks::function]
+    fn write_link(
+        &self,
+        _fs_path: Vc<FileSystemPath>,
+        _target: Vc<LinkContent>,
+    ) -> Result<Vc<Completion>> {
+        bail!("Writing is not possible on the zip archive file system")
+    }
+
+    #[turbo_tasks::function]
+    fn metadata(&self, _fs_path: Vc<FileSystemPath>) -> Vc<FileMeta> {
+        FileMeta::default().cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ZipArchiveFileSystem {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell(format!(
+            "zip archive {}",
+            self.archive.to_string().await?
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use turbo_tasks::Vc;
+    use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+    use super::{resolve_virtual_path, ZipArchiveFileSystem, ZipFileSystem};
+    use crate::{
+        overlay_fs::OverlayFileSystem, DirectoryContent, DirectoryEntry, File, FileContent,
+        FileSystem, FileSystemEntryType, FileSystemPath,
+    };
+
+    /// Creates a zip archive with a stored and a deflated file, but without an
+    /// entry for their directory.
+    fn archive() -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file(
+            "node_modules/foo/index.js",
+            FileOptions::default().compression_method(CompressionMethod::Stored),
+        )
+        .unwrap();
+        zip.write_all(b"module.exports = 'foo';").unwrap();
+        zip.start_file(
+            "node_modules/foo/package.json",
+            FileOptions::default().compression_method(CompressionMethod::Deflated),
+        )
+        .unwrap();
+        zip.write_all(br#"{ "name": "foo" }"#).unwrap();
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn project() -> Vc<FileSystemPath> {
+        let fs = OverlayFileSystem::new(
+            None,
+            [(
+                ".yarn/cache/foo.zip".to_string(),
+                FileContent::Content(File::from(archive())).cell(),
+            )],
+        );
+        Vc::upcast::<Box<dyn FileSystem>>(fs).root()
+    }
+
+    async fn read_to_string(path: Vc<FileSystemPath>) -> anyhow::Result<Option<String>> {
+        Ok(match &*path.read().await? {
+            FileContent::Content(file) => Some(file.content().to_str()?.into_owned()),
+            FileContent::NotFound => None,
+        })
+    }
+
+    async fn entry_names(path: Vc<FileSystemPath>) -> anyhow::Result<Vec<String>> {
+        let DirectoryContent::Entries(entries) = &*path.read_dir().await? else {
+            panic!("expected a directory");
+        };
+        let mut names = entries
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+        Ok(names)
+    }
+
+    #[tokio::test]
+    async fn archive_files_and_directories() {
+        crate::register();
+
+        turbo_tasks_testing::VcStorage::with(async {
+            let archive = project().join(".yarn/cache/foo.zip".to_string());
+            let root = Vc::upcast::<Box<dyn FileSystem>>(ZipArchiveFileSystem::new(archive)).root();
+
+            assert_eq!(
+                read_to_string(root.join("node_modules/foo/index.js".to_string())).await?,
+                Some("module.exports = 'foo';".to_string())
+            );
+            assert_eq!(
+                read_to_string(root.join("node_modules/foo/package.json".to_string())).await?,
+                Some(r#"{ "name": "foo" }"#.to_string())
+            );
+            assert_eq!(
+                read_to_string(root.join("node_modules/foo/missing.js".to_string())).await?,
+                None
+            );
+            assert_eq!(
+                read_to_string(root.join("node_modules/foo".to_string())).await?,
+                None
+            );
+
+            assert_eq!(entry_names(root).await?, ["node_modules"]);
+            assert_eq!(
+                entry_names(root.join("node_modules/foo".to_string())).await?,
+                ["index.js", "package.json"]
+            );
+            assert_eq!(
+                *root.join("node_modules".to_string()).get_type().await?,
+                FileSystemEntryType::Directory
+            );
+            assert!(matches!(
+                &*root.join("node_modules/bar".to_string()).read_dir().await?,
+                DirectoryContent::NotFound
+            ));
+
+            anyhow::Ok(())
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn archives_as_directories() {
+        crate::register();
+
+        turbo_tasks_testing::VcStorage::with(async {
+            let root = Vc::upcast::<Box<dyn FileSystem>>(ZipFileSystem::new(project())).root();
+
+            assert_eq!(
+                read_to_string(
+                    root.join(".yarn/cache/foo.zip/node_modules/foo/index.js".to_string())
+                )
+                .await?,
+                Some("module.exports = 'foo';".to_string())
+            );
+            let virtual_path =
+                ".yarn/__virtual__/foo-virtual-abc/0/cache/foo.zip/node_modules/foo/index.js";
+            assert_eq!(
+                read_to_string(root.join(virtual_path.to_string())).await?,
+                Some("module.exports = 'foo';".to_string())
+            );
+
+            let DirectoryContent::Entries(entries) =
+                &*root.join(".yarn/cache".to_string()).read_dir().await?
+            else {
+                panic!(".yarn/cache should be a directory");
+            };
+            assert!(matches!(
+                entries.get("foo.zip"),
+                Some(DirectoryEntry::Directory(_))
+            ));
+            assert_eq!(
+                entry_names(root.join(".yarn/cache/foo.zip/node_modules".to_string())).await?,
+                ["foo"]
+            );
+
+            anyhow::Ok(())
+        })
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn virtual_paths() {
+        assert_eq!(
+            resolve_virtual_path(
+                ".yarn/__virtual__/foo-virtual-abc/0/cache/foo.zip/node_modules/foo"
+            )
+            .as_deref(),
+            Some(".yarn/cache/foo.zip/node_modules/foo")
+        );
+        assert_eq!(
+            resolve_virtual_path("a/b/__virtual__/foo-virtual-abc/2/c").as_deref(),
+            Some("c")
+        );
+        assert_eq!(
+            resolve_virtual_path("a/__virtual__/foo-virtual-abc/2/c"),
+            None
+        );
+        assert_eq!(resolve_virtual_path("a/__virtual__/foo-virtual-abc"), None);
+        assert_eq!(resolve_virtual_path("a/b").as_deref(), Some("a/b"));
+    }
+}
@@ -0,0 +1,653 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::ErrorKind,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use anyhow::{Context, Result};
+use dashmap::DashMap;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use turbo_tasks::{
+    backend::PersistentTaskType,
+    persisted_graph::{
+        ActivateResult, DeactivateResult, PersistResult, PersistTaskState, PersistedGraph,
+        PersistedGraphApi, ReadTaskState, TaskData,
+    },
+    registry, with_task_id_mapping, FunctionId, IdMapping, RawVc, TaskId,
+};
+
+/// The version of the layout of the cache file. Caches with a different
+/// version are discarded.
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    format_version: u32,
+    cache_key: String,
+    /// Serialized [PersistentTaskType]s. The index is the persisted id of the
+    /// task, which, unlike a [TaskId], is stable across processes.
+    task_types: Vec<Vec<u8>>,
+    tasks: Vec<(usize, StoredTask)>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct StoredTask {
+    /// The serialized [TaskData].
+    data: Vec<u8>,
+    /// The serialized [RawVc]s the task depends on.
+    dependencies: Vec<Vec<u8>>,
+    /// The global name of the function of the task, to find the tasks of
+    /// volatile functions. Unlike a [FunctionId], it's stable across
+    /// processes. `None` for trait method calls, which are resolved to a
+    /// function by separate tasks.
+    function: Option<String>,
+    clean: bool,
+}
+
+#[derive(Default)]
+struct Graph {
+    task_types: Vec<Vec<u8>>,
+    task_type_ids: HashMap<Vec<u8>, usize>,
+    tasks: HashMap<usize, StoredTask>,
+    /// The persisted ids of the tasks depending on a serialized [RawVc].
+    dependents: HashMap<Vec<u8>, HashSet<usize>>,
+    changed: bool,
+}
+
+impl Graph {
+    fn from_snapshot(snapshot: Snapshot) -> Self {
+        let mut graph = Graph {
+            task_type_ids: snapshot
+                .task_types
+                .iter()
+                .enumerate()
+                .map(|(id, task_type)| (task_type.clone(), id))
+                .collect(),
+            task_types: snapshot.task_types,
+            ..Default::default()
+        };
+        for (id, task) in snapshot.tasks {
+            graph.insert_task(id, task);
+        }
+        graph.changed = false;
+        graph
+    }
+
+    fn to_snapshot(&self, cache_key: &str) -> Snapshot {
+        Snapshot {
+            format_version: FORMAT_VERSION,
+            cache_key: cache_key.to_string(),
+            task_types: self.task_types.clone(),
+            tasks: self
+                .tasks
+                .iter()
+                .map(|(id, task)| (*id, task.clone()))
+                .collect(),
+        }
+    }
+
+    fn intern_task_type(&mut self, task_type: Vec<u8>) -> usize {
+        if let Some(id) = self.task_type_ids.get(&task_type) {
+            return *id;
+        }
+        let id = self.task_types.len();
+        self.task_types.push(task_type.clone());
+        self.task_type_ids.insert(task_type, id);
+        self.changed = true;
+        id
+    }
+
+    fn insert_task(&mut self, id: usize, task: StoredTask) {
+        if let Some(old) = self.tasks.remove(&id) {
+            for dependency in old.dependencies {
+                if let Some(dependents) = self.dependents.get_mut(&dependency) {
+                    dependents.remove(&id);
+                }
+            }
+        }
+        for dependency in task.dependencies.iter() {
+            self.dependents
+                .entry(dependency.clone())
+                .or_default()
+                .insert(id);
+        }
+        self.tasks.insert(id, task);
+        self.changed = true;
+    }
+
+    fn set_clean(&mut self, id: usize, clean: bool) -> bool {
+        match self.tasks.get_mut(&id) {
+            Some(task) if task.clean != clean => {
+                task.clean = clean;
+                self.changed = true;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Where a [DiskPersistedGraph] keeps its serialized snapshot.
+pub trait PersistedGraphStorage: Send + Sync {
+    /// Reads the last written snapshot, if there is one.
+    fn read(&self) -> Result<Option<Vec<u8>>>;
+
+    /// Replaces the snapshot.
+    fn write(&self, snapshot: &[u8]) -> Result<()>;
+}
+
+impl PersistedGraphStorage for PathBuf {
+    fn read(&self) -> Result<Option<Vec<u8>>> {
+        match fs::read(self) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => {
+                Err(err).with_context(|| format!("failed to read cache {}", self.display()))
+            }
+        }
+    }
+
+    fn write(&self, snapshot: &[u8]) -> Result<()> {
+        if let Some(parent) = self.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write to a temporary file first, so a crash doesn't leave a partial
+        // cache behind.
+        let temp_path = self.with_extension("tmp");
+        fs::write(&temp_path, snapshot)
+            .with_context(|| format!("failed to write cache {}", temp_path.display()))?;
+        fs::rename(&temp_path, self)
+            .with_context(|| format!("failed to write cache {}", self.display()))?;
+        Ok(())
+    }
+}
+
+/// A [PersistedGraph] which keeps the results of tasks in a file, or another
+/// [PersistedGraphStorage], so they survive restarts of the process. Use it
+/// with [MemoryBackendWithPersistedGraph](crate::MemoryBackendWithPersistedGraph).
+///
+/// Tasks are keyed by their serialized [PersistentTaskType], i. e. the
+/// function and the serialized values of its arguments. Tasks referencing
+/// values which are not serializable are not persisted.
+///
+/// The cache is written when turbo-tasks is stopped.
+pub struct DiskPersistedGraph {
+    storage: Box<dyn PersistedGraphStorage>,
+    cache_key: String,
+    graph: Mutex<Graph>,
+    /// The [TaskId]s of persisted ids in the current process.
+    task_ids: DashMap<usize, TaskId>,
+    /// The persisted ids of [TaskId]s in the current process.
+    persisted_ids: DashMap<TaskId, usize>,
+}
+
+impl DiskPersistedGraph {
+    /// Opens the cache file at `path`.
+    ///
+    /// The cache is discarded when it was written with a different
+    /// `cache_key`, so it should include everything which affects the
+    /// results of tasks without being an input of them, e.g. the version of
+    /// the toolchain and a hash of the configuration.
+    pub fn new(path: PathBuf, cache_key: String) -> Result<Self> {
+        Self::with_storage(Box::new(path), cache_key)
+    }
+
+    /// Like [DiskPersistedGraph::new], but keeps the snapshot in `storage`
+    /// instead of a file.
+    pub fn with_storage(
+        storage: Box<dyn PersistedGraphStorage>,
+        cache_key: String,
+    ) -> Result<Self> {
+        let graph = match storage.read()? {
+            Some(bytes) => match postcard::from_bytes::<Snapshot>(&bytes) {
+                Ok(snapshot)
+                    if snapshot.format_version == FORMAT_VERSION
+                        && snapshot.cache_key == cache_key =>
+                {
+                    Graph::from_snapshot(snapshot)
+                }
+                _ => Graph::default(),
+            },
+            None => Graph::default(),
+        };
+        Ok(Self {
+            storage,
+            cache_key,
+            graph: Mutex::new(graph),
+            task_ids: DashMap::new(),
+            persisted_ids: DashMap::new(),
+        })
+    }
+
+    /// Marks all cached tasks of the given functions, e.g.
+    /// `DiskFileSystem::volatile_functions()`, as dirty. Use it for functions
+    /// which read external state that might have changed while the process
+    /// wasn't running. They are executed again when needed, and tasks
+    /// depending on them are only invalidated when their results changed.
+    pub fn with_volatile_functions(self, functions: impl IntoIterator<Item = FunctionId>) -> Self {
+        let names = functions
+            .into_iter()
+            .map(registry::get_function_global_name)
+            .collect::<HashSet<_>>();
+        {
+            let mut graph = self.graph.lock().unwrap();
+            let ids = graph
+                .tasks
+                .iter()
+                .filter(|(_, task)| {
+                    task.function
+                        .as_deref()
+                        .map_or(false, |function| names.contains(function))
+                })
+                .map(|(id, _)| *id)
+                .collect::<Vec<_>>();
+            for id in ids {
+                graph.set_clean(id, false);
+            }
+        }
+        self
+    }
+
+    /// Writes the cache, if anything changed.
+    pub fn save(&self) -> Result<()> {
+        let snapshot = {
+            let mut graph = self.graph.lock().unwrap();
+            if !graph.changed {
+                return Ok(());
+            }
+            graph.changed = false;
+            graph.to_snapshot(&self.cache_key)
+        };
+        let bytes = postcard::to_allocvec(&snapshot)?;
+        self.storage.write(&bytes)
+    }
+
+    fn link(&self, task: TaskId, id: usize) {
+        self.task_ids.insert(id, task);
+        self.persisted_ids.insert(task, id);
+    }
+
+    fn persisted_id(&self, task: TaskId, api: &dyn PersistedGraphApi) -> Option<usize> {
+        let mapping = TaskIdMapping::new(self, api);
+        let id = with_task_id_mapping(&mapping, || mapping.forward(task));
+        mapping.succeeded().then_some(id)
+    }
+
+    fn serialize<T: Serialize>(&self, value: &T, api: &dyn PersistedGraphApi) -> Option<Vec<u8>> {
+        let mapping = TaskIdMapping::new(self, api);
+        let bytes = with_task_id_mapping(&mapping, || postcard::to_allocvec(value)).ok()?;
+        mapping.succeeded().then_some(bytes)
+    }
+
+    fn deserialize<T: DeserializeOwned>(
+        &self,
+        bytes: &[u8],
+        api: &dyn PersistedGraphApi,
+    ) -> Option<T> {
+        let mapping = TaskIdMapping::new(self, api);
+        let value = with_task_id_mapping(&mapping, || postcard::from_bytes(bytes)).ok()?;
+        mapping.succeeded().then_some(value)
+    }
+}
+
+fn function_global_name(task_type: &PersistentTaskType) -> Option<String> {
+    match task_type {
+        PersistentTaskType::Native(function, _)
+        | PersistentTaskType::ResolveNative(function, _) => {
+            Some(registry::get_function_global_name(*function).to_string())
+        }
+        PersistentTaskType::ResolveTrait(..) => None,
+    }
+}
+
+/// Maps [TaskId]s to persisted ids and back, while (de)serializing task types
+/// and task data.
+struct TaskIdMapping<'a> {
+    graph: &'a DiskPersistedGraph,
+    api: &'a dyn PersistedGraphApi,
+    /// Set when a task can't be mapped, e.g. a root task, which can't be
+    /// persisted, or a task type which is no longer deserializable.
+    failed: AtomicBool,
+}
+
+impl<'a> TaskIdMapping<'a> {
+    fn new(graph: &'a DiskPersistedGraph, api: &'a dyn PersistedGraphApi) -> Self {
+        Self {
+            graph,
+            api,
+            failed: AtomicBool::new(false),
+        }
+    }
+
+    fn succeeded(&self) -> bool {
+        !self.failed.load(Ordering::Relaxed)
+    }
+
+    fn fail(&self) {
+        self.failed.store(true, Ordering::Relaxed);
+    }
+}
+
+impl IdMapping<TaskId> for TaskIdMapping<'_> {
+    fn forward(&self, task: TaskId) -> usize {
+        if let Some(id) = self.graph.persisted_ids.get(&task) {
+            return *id;
+        }
+        let Some(task_type) = self.api.try_lookup_task_type(task) else {
+            self.fail();
+            return usize::MAX;
+        };
+        // Task ids in the arguments are mapped recursively, as the mapping is
+        // still active.
+        let Ok(task_type) = postcard::to_allocvec(task_type) else {
+            self.fail();
+            return usize::MAX;
+        };
+        let id = self.graph.graph.lock().unwrap().intern_task_type(task_type);
+        self.graph.link(task, id);
+        id
+    }
+
+    fn backward(&self, id: usize) -> TaskId {
+        if let Some(task) = self.graph.task_ids.get(&id) {
+            return *task;
+        }
+        let task_type = self.graph.graph.lock().unwrap().task_types.get(id).cloned();
+        let Some(task_type) =
+            task_type.and_then(|bytes| postcard::from_bytes::<PersistentTaskType>(&bytes).ok())
+        else {
+            self.fail();
+            // Placeholder, the result is discarded.
+            return TaskId::from(usize::MAX);
+        };
+        let task = self.api.get_or_create_task_type(task_type);
+        self.graph.link(task, id);
+        task
+    }
+}
+
+impl PersistedGraph for DiskPersistedGraph {
+    fn read(
+        &self,
+        task: TaskId,
+        api: &dyn PersistedGraphApi,
+    ) -> Result<Option<(TaskData, ReadTaskState)>> {
+        let Some(id) = self.persisted_id(task, api) else {
+            return Ok(None);
+        };
+        let Some(stored) = self.graph.lock().unwrap().tasks.get(&id).cloned() else {
+            return Ok(None);
+        };
+        let Some(data) = self.deserialize::<TaskData>(&stored.data, api) else {
+            return Ok(None);
+        };
+        Ok(Some((
+            data,
+            ReadTaskState {
+                clean: stored.clean,
+                keeps_external_active: false,
+            },
+        )))
+    }
+
+    fn lookup(
+        &self,
+        _partial_task_type: &PersistentTaskType,
+        _api: &dyn PersistedGraphApi,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn lookup_one(
+        &self,
+        task_type: &PersistentTaskType,
+        api: &dyn PersistedGraphApi,
+    ) -> Result<Option<TaskId>> {
+        let Some(bytes) = self.serialize(task_type, api) else {
+            return Ok(None);
+        };
+        let id = {
+            let graph = self.graph.lock().unwrap();
+            match graph.task_type_ids.get(&bytes) {
+                Some(id) if graph.tasks.contains_key(id) => *id,
+                _ => return Ok(None),
+            }
+        };
+        let task = api.get_or_create_task_type(task_type.clone());
+        self.link(task, id);
+        Ok(Some(task))
+    }
+
+    fn is_persisted(&self, task: TaskId, api: &dyn PersistedGraphApi) -> Result<bool> {
+        Ok(self
+            .persisted_id(task, api)
+            .is_some_and(|id| self.graph.lock().unwrap().tasks.contains_key(&id)))
+    }
+
+    fn persist(
+        &self,
+        task: TaskId,
+        data: TaskData,
+        _state: PersistTaskState,
+        api: &dyn PersistedGraphApi,
+    ) -> Result<Option<PersistResult>> {
+        let Some(id) = self.persisted_id(task, api) else {
+            return Ok(None);
+        };
+        let Some(dependencies) = data
+            .dependencies
+            .iter()
+            .map(|vc| self.serialize(vc, api))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Ok(None);
+        };
+        let Some(bytes) = self.serialize(&data, api) else {
+            return Ok(None);
+        };
+        let stored = StoredTask {
+            data: bytes,
+            dependencies,
+            function: function_global_name(api.lookup_task_type(task)),
+            clean: true,
+        };
+        self.graph.lock().unwrap().insert_task(id, stored);
+        Ok(Some(PersistResult {
+            tasks_to_activate: Vec::new(),
+            tasks_to_deactivate: Vec::new(),
+        }))
+    }
+
+    fn activate_when_needed(
+        &self,
+        _task: TaskId,
+        _api: &dyn PersistedGraphApi,
+    ) -> Result<Option<ActivateResult>> {
+        Ok(None)
+    }
+
+    fn deactivate_when_needed(
+        &self,
+        _task: TaskId,
+        _api: &dyn PersistedGraphApi,
+    ) -> Result<Option<DeactivateResult>> {
+        Ok(None)
+    }
+
+    fn set_externally_active(&self, _task: TaskId, _api: &dyn PersistedGraphApi) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn unset_externally_active(&self, _task: TaskId, _api: &dyn PersistedGraphApi) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn remove_outdated_externally_active(
+        &self,
+        _api: &dyn PersistedGraphApi,
+    ) -> Result<Vec<TaskId>> {
+        Ok(Vec::new())
+    }
+
+    fn make_dirty(&self, task: TaskId, api: &dyn PersistedGraphApi) -> Result<bool> {
+        if let Some(id) = self.persisted_id(task, api) {
+            self.graph.lock().unwrap().set_clean(id, false);
+        }
+        // The memory graph schedules the task when it's active.
+        Ok(false)
+    }
+
+    fn make_clean(&self, task: TaskId, api: &dyn PersistedGraphApi) -> Result<()> {
+        if let Some(id) = self.persisted_id(task, api) {
+            self.graph.lock().unwrap().set_clean(id, true);
+        }
+        Ok(())
+    }
+
+    fn make_dependent_dirty(&self, vc: RawVc, api: &dyn PersistedGraphApi) -> Result<Vec<TaskId>> {
+        let Some(vc) = self.serialize(&vc, api) else {
+            return Ok(Vec::new());
+        };
+        let dirty = {
+            let mut graph = self.graph.lock().unwrap();
+            let dependents = graph
+                .dependents
+                .get(&vc)
+                .map(|dependents| dependents.iter().copied().collect::<Vec<_>>())
+                .unwrap_or_default();
+            dependents
+                .into_iter()
+                .filter(|id| graph.set_clean(*id, false))
+                .collect::<Vec<_>>()
+        };
+        // Tasks which are not known to the current process are read as dirty
+        // when they are needed.
+        Ok(dirty
+            .into_iter()
+            .filter_map(|id| self.task_ids.get(&id).map(|task| *task))
+            .collect())
+    }
+
+    fn get_active_external_tasks(&self, _api: &dyn PersistedGraphApi) -> Result<Vec<TaskId>> {
+        Ok(Vec::new())
+    }
+
+    fn get_dirty_active_tasks(&self, _api: &dyn PersistedGraphApi) -> Result<Vec<TaskId>> {
+        Ok(Vec::new())
+    }
+
+    fn get_pending_active_update(
+        &self,
+        _api: &dyn PersistedGraphApi,
+    ) -> Result<(Vec<TaskId>, Vec<TaskId>)> {
+        Ok((Vec::new(), Vec::new()))
+    }
+
+    fn stop(&self, _api: &dyn PersistedGraphApi) -> Result<()> {
+        self.save()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use anyhow::Result;
+
+    use super::{DiskPersistedGraph, Graph, PersistedGraphStorage, StoredTask};
+
+    #[derive(Clone, Default)]
+    struct MemoryStorage(Arc<Mutex<Option<Vec<u8>>>>);
+
+    impl PersistedGraphStorage for MemoryStorage {
+        fn read(&self) -> Result<Option<Vec<u8>>> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+
+        fn write(&self, snapshot: &[u8]) -> Result<()> {
+            *self.0.lock().unwrap() = Some(snapshot.to_vec());
+            Ok(())
+        }
+    }
+
+    fn task(dependencies: &[&[u8]]) -> StoredTask {
+        StoredTask {
+            data: vec![1, 2, 3],
+            dependencies: dependencies.iter().map(|d| d.to_vec()).collect(),
+            function: Some("function".to_string()),
+            clean: true,
+        }
+    }
+
+    fn open(storage: &MemoryStorage, cache_key: &str) -> DiskPersistedGraph {
+        DiskPersistedGraph::with_storage(Box::new(storage.clone()), cache_key.to_string()).unwrap()
+    }
+
+    #[test]
+    fn tracks_dependents() {
+        let mut graph = Graph::default();
+        let a = graph.intern_task_type(b"a".to_vec());
+        let b = graph.intern_task_type(b"b".to_vec());
+        assert_eq!(graph.intern_task_type(b"a".to_vec()), a);
+        graph.insert_task(a, task(&[b"x", b"y"]));
+        graph.insert_task(b, task(&[b"y"]));
+        assert_eq!(graph.dependents[&b"y".to_vec()].len(), 2);
+
+        // Replacing a task removes its old dependencies.
+        graph.insert_task(a, task(&[b"z"]));
+        assert!(graph.dependents[&b"x".to_vec()].is_empty());
+        assert_eq!(graph.dependents[&b"y".to_vec()].len(), 1);
+        assert!(graph.dependents[&b"z".to_vec()].contains(&a));
+
+        assert!(graph.set_clean(a, false));
+        assert!(!graph.set_clean(a, false));
+        assert!(!graph.set_clean(42, false));
+    }
+
+    #[test]
+    fn persists_snapshots() {
+        let storage = MemoryStorage::default();
+        let graph = open(&storage, "v1");
+        {
+            let mut inner = graph.graph.lock().unwrap();
+            let id = inner.intern_task_type(b"a".to_vec());
+            inner.insert_task(id, task(&[b"x"]));
+        }
+        graph.save().unwrap();
+        let snapshot = storage.read().unwrap().unwrap();
+
+        // Unchanged graphs are not written again.
+        let graph = open(&storage, "v1");
+        *storage.0.lock().unwrap() = None;
+        graph.save().unwrap();
+        assert_eq!(storage.read().unwrap(), None);
+
+        *storage.0.lock().unwrap() = Some(snapshot);
+        let graph = open(&storage, "v1");
+        let inner = graph.graph.lock().unwrap();
+        assert_eq!(inner.task_type_ids[&b"a".to_vec()], 0);
+        assert_eq!(inner.tasks[&0].data, vec![1, 2, 3]);
+        assert!(inner.dependents[&b"x".to_vec()].contains(&0));
+    }
+
+    #[test]
+    fn discards_snapshots_of_other_cache_keys() {
+        let storage = MemoryStorage::default();
+        let graph = open(&storage, "v1");
+        {
+            let mut inner = graph.graph.lock().unwrap();
+            let id = inner.intern_task_type(b"a".to_vec());
+            inner.insert_task(id, task(&[]));
+        }
+        graph.save().unwrap();
+
+        assert!(open(&storage, "v2").graph.lock().unwrap().tasks.is_empty());
+        *storage.0.lock().unwrap() = Some(b"not a snapshot".to_vec());
+        assert!(open(&storage, "v1").graph.lock().unwrap().tasks.is_empty());
+    }
+}
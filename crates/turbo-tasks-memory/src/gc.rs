@@ -48,8 +48,52 @@ pub enum GcPriority {
     Placeholder,
 }
 
+/// Configures when and how aggressively garbage collection unloads tasks and
+/// cells.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GcPolicy {
+    /// The memory budget in bytes. Garbage collection is disabled when it's
+    /// `usize::MAX`.
+    pub memory_limit: usize,
+    /// The share of the memory limit which garbage collection aims for while
+    /// tasks are executing.
+    pub busy_target: f64,
+    /// The share of the memory limit which garbage collection aims for while
+    /// turbo-tasks is idle.
+    pub idle_target: f64,
+    /// The maximum share of the queued tasks, out of 255, which are collected
+    /// in a single run.
+    pub max_collect_factor: u8,
+}
+
+impl GcPolicy {
+    /// A policy which keeps memory usage below `memory_limit` bytes.
+    pub fn with_memory_limit(memory_limit: usize) -> Self {
+        Self {
+            memory_limit,
+            ..Default::default()
+        }
+    }
+
+    /// Whether garbage collection is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.memory_limit != usize::MAX
+    }
+}
+
+impl Default for GcPolicy {
+    fn default() -> Self {
+        Self {
+            memory_limit: usize::MAX,
+            busy_target: 7.0 / 8.0,
+            idle_target: 3.0 / 4.0,
+            max_collect_factor: u8::MAX / 8,
+        }
+    }
+}
+
 /// Statistics about actions performed during garbage collection.
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct GcStats {
     /// How many tasks were unloaded.
     pub unloaded: usize,
@@ -70,6 +114,20 @@ pub struct GcStats {
     pub no_gc_possible: usize,
 }
 
+impl GcStats {
+    /// Adds the counts of `other` to these statistics.
+    pub fn add(&mut self, other: &GcStats) {
+        self.unloaded += other.unloaded;
+        self.empty_unused += other.empty_unused;
+        self.empty_unused_fast += other.empty_unused_fast;
+        self.empty_cells += other.empty_cells;
+        self.priority_updated += other.priority_updated;
+        self.priority_updated_fast += other.priority_updated_fast;
+        self.no_gc_needed += other.no_gc_needed;
+        self.no_gc_possible += other.no_gc_possible;
+    }
+}
+
 /// State about garbage collection for a task.
 #[derive(Debug, Default)]
 pub struct GcTaskState {
@@ -11,6 +11,7 @@ mod aggregation_tree;
 mod cell;
 mod concurrent_priority_queue;
 mod count_hash_set;
+mod disk_persisted_graph;
 mod gc;
 mod map_guard;
 mod memory_backend;
@@ -20,5 +21,7 @@ pub mod stats;
 mod task;
 pub mod viz;
 
-pub use memory_backend::MemoryBackend;
+pub use disk_persisted_graph::{DiskPersistedGraph, PersistedGraphStorage};
+pub use gc::{GcPolicy, GcStats};
+pub use memory_backend::{MemoryBackend, MemoryStats};
 pub use memory_backend_with_pg::MemoryBackendWithPersistedGraph;
@@ -6,7 +6,7 @@ use std::{
     hash::{BuildHasher, BuildHasherDefault, Hash},
     pin::Pin,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc,
     },
     time::{Duration, Instant},
@@ -15,6 +15,7 @@ use std::{
 use anyhow::{bail, Result};
 use auto_hash_map::{AutoMap, AutoSet};
 use dashmap::{mapref::entry::Entry, DashMap};
+use parking_lot::Mutex;
 use rustc_hash::FxHasher;
 use tokio::task::futures::TaskLocalFuture;
 use tracing::trace_span;
@@ -30,7 +31,7 @@ use turbo_tasks::{
 
 use crate::{
     cell::RecomputingCell,
-    gc::GcQueue,
+    gc::{GcPolicy, GcQueue, GcStats},
     output::Output,
     task::{Task, TaskDependency, DEPENDENCIES_TO_TRACK},
 };
@@ -40,9 +41,27 @@ pub struct MemoryBackend {
     backend_jobs: NoMoveVec<Job>,
     backend_job_id_factory: IdFactory<BackendJobId>,
     task_cache: DashMap<Arc<PersistentTaskType>, TaskId, BuildHasherDefault<FxHasher>>,
-    memory_limit: usize,
+    gc_policy: GcPolicy,
     gc_queue: Option<GcQueue>,
     idle_gc_active: AtomicBool,
+    gc_runs: AtomicUsize,
+    gc_stats: Mutex<GcStats>,
+}
+
+/// A snapshot of the memory usage of a [MemoryBackend] and of the work done by
+/// its garbage collection so far.
+#[derive(Debug, Clone)]
+pub struct MemoryStats {
+    /// The memory currently allocated by the process in bytes.
+    pub memory_usage: usize,
+    /// The memory budget of the [GcPolicy] in bytes.
+    pub memory_limit: usize,
+    /// The number of cached persistent tasks.
+    pub tasks: usize,
+    /// How often garbage collection collected tasks or cells.
+    pub gc_runs: usize,
+    /// The sum of the statistics of all garbage collection runs.
+    pub gc_stats: GcStats,
 }
 
 impl Default for MemoryBackend {
@@ -53,6 +72,10 @@ impl Default for MemoryBackend {
 
 impl MemoryBackend {
     pub fn new(memory_limit: usize) -> Self {
+        Self::with_gc_policy(GcPolicy::with_memory_limit(memory_limit))
+    }
+
+    pub fn with_gc_policy(gc_policy: GcPolicy) -> Self {
         Self {
             memory_tasks: NoMoveVec::new(),
             backend_jobs: NoMoveVec::new(),
@@ -62,9 +85,26 @@ impl MemoryBackend {
                 (std::thread::available_parallelism().map_or(1, usize::from) * 32)
                     .next_power_of_two(),
             ),
-            memory_limit,
-            gc_queue: (memory_limit != usize::MAX).then(GcQueue::new),
+            gc_policy,
+            gc_queue: gc_policy.is_enabled().then(GcQueue::new),
             idle_gc_active: AtomicBool::new(false),
+            gc_runs: AtomicUsize::new(0),
+            gc_stats: Mutex::new(GcStats::default()),
+        }
+    }
+
+    pub fn gc_policy(&self) -> GcPolicy {
+        self.gc_policy
+    }
+
+    /// Returns the current memory usage and garbage collection statistics.
+    pub fn memory_stats(&self) -> MemoryStats {
+        MemoryStats {
+            memory_usage: turbo_tasks_malloc::TurboMalloc::memory_usage(),
+            memory_limit: self.gc_policy.memory_limit,
+            tasks: self.task_cache.len(),
+            gc_runs: self.gc_runs.load(Ordering::Relaxed),
+            gc_stats: self.gc_stats.lock().clone(),
         }
     }
 
@@ -132,15 +172,18 @@ impl MemoryBackend {
 
     pub fn run_gc(&self, idle: bool, turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>) {
         if let Some(gc_queue) = &self.gc_queue {
-            const MAX_COLLECT_FACTOR: u8 = u8::MAX / 8;
-
-            let mem_limit = self.memory_limit;
+            let GcPolicy {
+                memory_limit: mem_limit,
+                busy_target,
+                idle_target,
+                max_collect_factor,
+            } = self.gc_policy;
 
             let usage = turbo_tasks_malloc::TurboMalloc::memory_usage();
             let target = if idle {
-                mem_limit * 3 / 4
+                (mem_limit as f64 * idle_target) as usize
             } else {
-                mem_limit * 7 / 8
+                (mem_limit as f64 * busy_target) as usize
             };
             if usage < target {
                 if idle {
@@ -151,11 +194,15 @@ impl MemoryBackend {
             }
 
             let collect_factor = min(
-                MAX_COLLECT_FACTOR as usize,
-                (usage - target) * u8::MAX as usize / (mem_limit - target),
+                max_collect_factor as usize,
+                (usage - target) * u8::MAX as usize / mem_limit.saturating_sub(target).max(1),
             ) as u8;
 
             let collected = gc_queue.run_gc(collect_factor, self, turbo_tasks);
+            if let Some((_, _, stats)) = &collected {
+                self.gc_runs.fetch_add(1, Ordering::Relaxed);
+                self.gc_stats.lock().add(stats);
+            }
 
             if idle {
                 if let Some((_collected, _count, _stats)) = collected {
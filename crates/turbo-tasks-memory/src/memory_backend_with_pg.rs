@@ -1654,6 +1654,14 @@ impl<'a, P: PersistedGraph> PersistedGraphApi for MemoryBackendPersistedGraphApi
             _ => panic!("lookup_task_type should only be used for PersistentTaskType"),
         }
     }
+
+    fn try_lookup_task_type(&self, id: TaskId) -> Option<&PersistentTaskType> {
+        let task = self.backend.tasks.get(*id)?;
+        match &task.task_type {
+            TaskType::Persistent(ty) => Some(ty),
+            _ => None,
+        }
+    }
 }
 
 impl<P: PersistedGraph> MemoryBackendWithPersistedGraph<P> {
@@ -200,6 +200,10 @@ pub trait PersistedGraphApi {
     fn get_or_create_task_type(&self, ty: PersistentTaskType) -> TaskId;
 
     fn lookup_task_type(&self, id: TaskId) -> &PersistentTaskType;
+
+    /// Like [Self::lookup_task_type], but returns `None` for tasks which are
+    /// not persistent, e.g. root tasks.
+    fn try_lookup_task_type(&self, id: TaskId) -> Option<&PersistentTaskType>;
 }
 
 /*
@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use serde::Serialize;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{File, FileSystemPath};
+use turbopack_core::{
+    asset::AssetContent, output::OutputAsset, virtual_output::VirtualOutputAsset,
+};
+
+/// The entry chunks of a build for one environment, by the name of the entry.
+#[turbo_tasks::value(transparent)]
+pub struct EntryChunks(IndexMap<String, Vc<Box<dyn OutputAsset>>>);
+
+/// The output files of an entry in the build manifest.
+#[derive(Default, Serialize)]
+struct BuildManifestEntry {
+    /// The files for `<script type="module">` tags.
+    module: Vec<String>,
+    /// The files for `<script nomodule>` tags.
+    nomodule: Vec<String>,
+}
+
+#[derive(Default, Serialize)]
+struct BuildManifest {
+    entries: BTreeMap<String, BuildManifestEntry>,
+}
+
+/// Creates the build manifest at `path`, which maps each entry to the entry
+/// chunk of the `module` build and to the one of the `nomodule` build, e.g.
+/// the builds of the same entries for modern and legacy browsers. The paths of
+/// the chunks are relative to the directory of the manifest.
+///
+/// Only entry chunks are listed, as they load the other chunks of their chunk
+/// group.
+#[turbo_tasks::function]
+pub async fn module_nomodule_manifest(
+    path: Vc<FileSystemPath>,
+    module: Vc<EntryChunks>,
+    nomodule: Vc<EntryChunks>,
+) -> Result<Vc<Box<dyn OutputAsset>>> {
+    let dir = path.parent().await?;
+    let mut manifest = BuildManifest::default();
+    for (entry_chunks, is_nomodule) in [(module, false), (nomodule, true)] {
+        for (name, chunk) in entry_chunks.await?.iter() {
+            let chunk_path = chunk.ident().path().await?;
+            let Some(chunk_path) = dir.get_path_to(&chunk_path) else {
+                continue;
+            };
+            let entry = manifest.entries.entry(name.clone()).or_default();
+            if is_nomodule {
+                entry.nomodule.push(chunk_path.to_string());
+            } else {
+                entry.module.push(chunk_path.to_string());
+            }
+        }
+    }
+
+    let manifest = serde_json::to_string_pretty(&manifest)?;
+    Ok(Vc::upcast(VirtualOutputAsset::new(
+        path,
+        AssetContent::file(File::from(manifest).into()),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::indexmap;
+    use lazy_static::lazy_static;
+    use serde_json::json;
+    use turbo_tasks::Vc;
+    use turbo_tasks_fs::{File, FileJsonContent, VirtualFileSystem};
+    use turbo_tasks_testing::run;
+    use turbopack_core::{
+        asset::{Asset, AssetContent},
+        output::OutputAsset,
+        virtual_output::VirtualOutputAsset,
+    };
+
+    use super::{module_nomodule_manifest, EntryChunks};
+
+    lazy_static! {
+        static ref REGISTER: () = crate::register();
+    }
+
+    #[tokio::test]
+    async fn lists_the_entry_chunks_of_both_builds() {
+        run! {
+            let dist = VirtualFileSystem::new().root().join("dist".to_string());
+            let chunk = |path: &str| -> Vc<Box<dyn OutputAsset>> {
+                Vc::upcast(VirtualOutputAsset::new(
+                    dist.join(path.to_string()),
+                    AssetContent::file(File::from("").into()),
+                ))
+            };
+            let module = Vc::<EntryChunks>::cell(indexmap! {
+                "main".to_string() => chunk("main.entry.js"),
+                "admin".to_string() => chunk("admin.entry.js"),
+            });
+            let nomodule = Vc::<EntryChunks>::cell(indexmap! {
+                "main".to_string() => chunk("legacy/main.entry.js"),
+            });
+
+            let manifest = module_nomodule_manifest(
+                dist.join("build-manifest.json".to_string()),
+                module,
+                nomodule,
+            );
+            let FileJsonContent::Content(json) = &*manifest.content().parse_json().await? else {
+                panic!("the manifest should be JSON");
+            };
+            assert_eq!(
+                json,
+                &json!({
+                    "entries": {
+                        "admin": {
+                            "module": ["admin.entry.js"],
+                            "nomodule": [],
+                        },
+                        "main": {
+                            "module": ["main.entry.js"],
+                            "nomodule": ["legacy/main.entry.js"],
+                        },
+                    },
+                })
+            );
+        }
+    }
+}
@@ -1,28 +1,50 @@
 use std::iter::once;
 
 use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use turbo_tasks::{trace::TraceRawVcs, TaskInput, Value, Vc};
-use turbo_tasks_fs::FileSystemPath;
+use turbo_tasks_fs::{glob::Glob, FileSystemEntryType, FileSystemPath};
 use turbopack_core::{
+    asset::AssetContent,
     chunk::{
         availability_info::AvailabilityInfo,
-        chunk_group::{make_chunk_group, MakeChunkGroupResult},
+        chunk_group::{make_chunk_group, make_sync_chunk_group, MakeChunkGroupResult},
+        chunking::ReferencedOutputAssetsPlacement,
+        module_id_strategy::{module_id_from_ident, ModuleIdStrategy},
+        preload::{url_origin, OriginHint, OriginHints},
+        split_point::SplitPoints,
         Chunk, ChunkItem, ChunkableModule, ChunkingContext, EvaluatableAssets, ModuleId,
     },
+    content_hash::{content_hash, ContentHashAlgorithm},
     environment::Environment,
     ident::AssetIdent,
     module::Module,
     output::{OutputAsset, OutputAssets},
+    resolve::{
+        options::{External, ExternalRequest, Externals, ImportMap, ImportMapping},
+        ExternalType,
+    },
+    source_map::SourcePathRewrites,
 };
 use turbopack_ecmascript::{
     chunk::{EcmascriptChunk, EcmascriptChunkPlaceable, EcmascriptChunkingContext},
-    manifest::{chunk_asset::ManifestAsyncModule, loader_item::ManifestLoaderChunkItem},
+    manifest::{
+        chunk_asset::ManifestAsyncModule, inlined_loader_item::InlinedLoaderChunkItem,
+        loader_item::ManifestLoaderChunkItem,
+    },
 };
 use turbopack_ecmascript_runtime::RuntimeType;
 
-use crate::ecmascript::node::{
-    chunk::EcmascriptBuildNodeChunk, entry::chunk::EcmascriptBuildNodeEntryChunk,
+use crate::ecmascript::{
+    federation::container::EcmascriptBuildFederationContainer,
+    library::{bundle::EcmascriptBuildLibraryBundle, declarations::declaration_assets},
+    node::{
+        bundle::EcmascriptBuildNodeBundle, chunk::EcmascriptBuildNodeChunk,
+        entry::chunk::EcmascriptBuildNodeEntryChunk,
+    },
+    react_native::bundle::EcmascriptBuildReactNativeBundle,
+    service_worker::chunk::EcmascriptBuildServiceWorkerChunk,
 };
 
 #[derive(
@@ -46,6 +68,276 @@ pub enum MinifyType {
     NoMinify,
 }
 
+/// How source maps of emitted chunks are generated.
+#[derive(
+    Debug,
+    Default,
+    TaskInput,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    TraceRawVcs,
+)]
+pub enum SourceMapsType {
+    /// Source maps in separate `.map` files, referenced by a
+    /// `sourceMappingURL` comment.
+    #[default]
+    Full,
+    /// Like [SourceMapsType::Full], but only lines are mapped, which makes
+    /// the maps much smaller.
+    Cheap,
+    /// Source maps embedded into the chunks as data URLs.
+    Inline,
+    /// Source maps in separate `.map` files, but without a `sourceMappingURL`
+    /// comment, e.g. to upload them to an error reporting service without
+    /// exposing them to browsers.
+    Hidden,
+    /// No source maps.
+    None,
+}
+
+/// How the Node.js runtime loads chunks which are not loaded by the entry
+/// chunk, e.g. the chunks of dynamic imports.
+#[derive(
+    Debug,
+    Default,
+    TaskInput,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    TraceRawVcs,
+)]
+pub enum NodeChunkLoading {
+    /// Chunks are loaded synchronously with `require()`.
+    #[default]
+    Require,
+    /// Chunks are loaded asynchronously with a native `import()`, e.g. so
+    /// loaders can intercept them. The runtime and the chunks are still
+    /// CommonJS modules.
+    Import,
+}
+
+/// The module format of a Node.js bundle, see
+/// [BuildChunkingContext::node_bundle].
+#[derive(
+    Debug,
+    Default,
+    TaskInput,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    TraceRawVcs,
+)]
+pub enum NodeBundleFormat {
+    /// A CommonJS module whose `module.exports` are the exports of the entry.
+    #[default]
+    CommonJs,
+    /// An ES module, e.g. `server.mjs`, which exports the exports of the
+    /// entry.
+    Esm,
+}
+
+/// The options of a Node.js bundle, see [BuildChunkingContext::node_bundle].
+/// To load packages from `node_modules` at runtime instead of bundling them,
+/// see [turbopack_core::resolve::options::ResolvedMap::external_node_modules].
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone, Default)]
+pub struct NodeBundleOptions {
+    pub format: NodeBundleFormat,
+    /// Whether the chunks of dynamic imports are inlined into the bundle.
+    /// Otherwise they are emitted next to it and loaded on demand.
+    pub inline_async_chunks: bool,
+}
+
+/// How a library bundle exposes the exports of its entry, see
+/// [BuildChunkingContext::library_bundle].
+#[derive(
+    Debug,
+    Default,
+    TaskInput,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    TraceRawVcs,
+)]
+pub enum LibraryFormat {
+    /// An immediately invoked function expression which assigns the exports
+    /// to a global, e.g. for a `<script>` tag.
+    #[default]
+    Iife,
+    /// A Universal Module Definition, which can be loaded with `require()`,
+    /// with an AMD loader or as a script which assigns the exports to a
+    /// global.
+    Umd,
+    /// An ES module, e.g. `index.mjs`, which exports the exports of the entry
+    /// and statically imports all external modules, e.g. the dependencies of
+    /// the package (see
+    /// [turbopack_core::resolve::options::Externals::package_dependencies]).
+    /// Exports which are only known at runtime, e.g. from `export *` of a
+    /// CommonJS module, are not exported.
+    Esm,
+}
+
+/// The options of a library bundle, see [BuildChunkingContext::library_bundle].
+/// ES module bundles don't use globals.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone, Default)]
+pub struct LibraryOptions {
+    pub format: LibraryFormat,
+    /// The global which the exports of the entry are assigned to when the
+    /// bundle is loaded as a script. When it's `None`, the entry is only
+    /// evaluated.
+    pub global_name: Option<String>,
+    /// Maps the requests of external modules to the globals which provide
+    /// them when the bundle is loaded as a script, e.g. `react` to `React`.
+    /// With UMD, they are required or loaded with AMD by their request
+    /// otherwise. The entry must be resolved with the
+    /// [LibraryOptions::import_map], so that they aren't bundled.
+    pub external_globals: IndexMap<String, String>,
+    /// A type declaration file which is emitted next to the bundle, e.g.
+    /// `index.d.mts` for `index.mjs`, along with the declaration files it
+    /// imports with relative specifiers. Defaults to the `.d.ts` file next to
+    /// the entry, if there is one.
+    pub declaration: Option<Vc<FileSystemPath>>,
+    /// The package.json of the package which is bundled. Its dependencies are
+    /// external modules, see [LibraryOptions::externals].
+    pub package_json: Option<Vc<FileSystemPath>>,
+}
+
+#[turbo_tasks::value_impl]
+impl LibraryOptions {
+    /// Maps the requests of the external globals to external modules, which
+    /// are provided by the wrapper of the bundle at runtime. It extends the
+    /// import map of the resolve options of the entry, since the modules of
+    /// the entry are already resolved when the bundle is generated.
+    #[turbo_tasks::function]
+    pub fn import_map(&self) -> Vc<ImportMap> {
+        let mut import_map = ImportMap::empty();
+        for request in self.external_globals.keys() {
+            import_map.insert_exact_alias(request, ImportMapping::External(None).cell());
+        }
+        import_map.cell()
+    }
+
+    /// The dependencies of the [LibraryOptions::package_json], which are
+    /// imported or required by the bundle instead of being bundled. Like the
+    /// [LibraryOptions::import_map], they are part of the resolve options of
+    /// the entry.
+    #[turbo_tasks::function]
+    pub fn externals(&self) -> Vc<Externals> {
+        match self.package_json {
+            Some(package_json) => {
+                Externals::package_dependencies(package_json, Value::new(ExternalType::CommonJs))
+            }
+            None => Vc::cell(Vec::new()),
+        }
+    }
+}
+
+/// A package which is shared between the host and the containers of a module
+/// federation, e.g. `react`, see [FederationContainerOptions].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+pub struct SharedPackage {
+    /// The entry of the package, which is bundled as the fallback when no
+    /// compatible version is provided by the host or another container. The
+    /// package can use the other shared packages, e.g. `react-dom` uses the
+    /// shared `react`.
+    pub module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+    /// The version of the bundled package, e.g. `18.2.0`.
+    pub version: String,
+    /// The range of versions which the exposed modules are compatible with,
+    /// e.g. `^18.0.0`. Defaults to `^` followed by `version`.
+    pub required_version: Option<String>,
+    /// Whether only a single version of the package may be loaded, which is
+    /// the version which was loaded first or else the highest version in the
+    /// share scope.
+    pub singleton: bool,
+}
+
+/// The options of a module federation container, see
+/// [BuildChunkingContext::federation_container].
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct FederationContainerOptions {
+    /// The name of the container, which hosts refer to it by.
+    pub name: String,
+    /// Maps the names of the exposed modules, e.g. `./Button`, to their
+    /// entries.
+    pub exposes: IndexMap<String, Vc<Box<dyn EcmascriptChunkPlaceable>>>,
+    /// Maps the requests of shared packages, e.g. `react`, to the packages.
+    /// They are ordered by their dependencies, e.g. `react` before
+    /// `react-dom`, since the container loads them in this order.
+    pub shared: IndexMap<String, SharedPackage>,
+}
+
+/// Maps the requests of the shared packages of a module federation container,
+/// e.g. `react`, to external modules, which are provided by the container once
+/// the versions to use have been negotiated with the host. They must be part
+/// of the resolve options of the exposed modules and of the modules of the
+/// shared packages, but not of the requests of the shared packages themselves.
+#[turbo_tasks::function]
+pub fn federation_externals(shared_requests: Vec<String>) -> Vc<Externals> {
+    Vc::cell(
+        shared_requests
+            .into_iter()
+            .map(|request| External {
+                request: ExternalRequest::Exact(request),
+                ty: ExternalType::CommonJs,
+                name: None,
+            })
+            .collect(),
+    )
+}
+
+/// Text which is added to the start and end of emitted ecmascript chunks,
+/// e.g. a license header or a `#!/usr/bin/env node` shebang. It's added after
+/// minification, so it's preserved verbatim.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct ChunkBanner {
+    /// Matched against the path of a chunk relative to the output root, e.g.
+    /// `server/**` or the path of an entry chunk.
+    pub glob: Vc<Glob>,
+    pub banner: Option<String>,
+    pub footer: Option<String>,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct ChunkBanners(Vec<ChunkBanner>);
+
+#[turbo_tasks::value_impl]
+impl ChunkBanners {
+    /// Creates a new empty [Vc<ChunkBanners>].
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        Vc::cell(Vec::new())
+    }
+}
+
 /// A builder for [`Vc<BuildChunkingContext>`].
 pub struct BuildChunkingContextBuilder {
     chunking_context: BuildChunkingContext,
@@ -57,16 +349,112 @@ impl BuildChunkingContextBuilder {
         self
     }
 
+    pub fn deferred_public_path(mut self, deferred_public_path: bool) -> Self {
+        self.chunking_context.deferred_public_path = deferred_public_path;
+        self
+    }
+
     pub fn minify_type(mut self, minify_type: MinifyType) -> Self {
         self.chunking_context.minify_type = minify_type;
         self
     }
 
+    pub fn source_maps_type(mut self, source_maps_type: SourceMapsType) -> Self {
+        self.chunking_context.source_maps_type = source_maps_type;
+        self
+    }
+
+    pub fn source_map_source_root(mut self, source_root: Option<String>) -> Self {
+        self.chunking_context.source_map_source_root = source_root;
+        self
+    }
+
+    pub fn source_map_path_rewrites(mut self, rewrites: Vc<SourcePathRewrites>) -> Self {
+        self.chunking_context.source_map_path_rewrites = rewrites;
+        self
+    }
+
+    pub fn source_map_omit_source_contents(mut self, sources: Option<Vc<Glob>>) -> Self {
+        self.chunking_context.source_map_omit_source_contents = sources;
+        self
+    }
+
+    pub fn node_chunk_loading(mut self, node_chunk_loading: NodeChunkLoading) -> Self {
+        self.chunking_context.node_chunk_loading = node_chunk_loading;
+        self
+    }
+
+    pub fn node_chunk_manifest(mut self, node_chunk_manifest: bool) -> Self {
+        self.chunking_context.node_chunk_manifest = node_chunk_manifest;
+        self
+    }
+
     pub fn runtime_type(mut self, runtime_type: RuntimeType) -> Self {
         self.chunking_context.runtime_type = runtime_type;
         self
     }
 
+    /// Whether entry chunks load the runtime from a separate chunk which is
+    /// shared by all of them, which is the default. Changes to the runtime
+    /// then don't change the entry chunks. Otherwise each entry chunk includes
+    /// the runtime and doesn't depend on other files to start.
+    pub fn runtime_chunk(mut self, runtime_chunk: bool) -> Self {
+        self.chunking_context.runtime_chunk = runtime_chunk;
+        self
+    }
+
+    pub fn referenced_output_assets_placement(
+        mut self,
+        placement: ReferencedOutputAssetsPlacement,
+    ) -> Self {
+        self.chunking_context.referenced_output_assets_placement = placement;
+        self
+    }
+
+    pub fn split_points(mut self, split_points: Vc<SplitPoints>) -> Self {
+        self.chunking_context.split_points = split_points;
+        self
+    }
+
+    pub fn inline_async_chunk_size(mut self, inline_async_chunk_size: usize) -> Self {
+        self.chunking_context.inline_async_chunk_size = inline_async_chunk_size;
+        self
+    }
+
+    pub fn startup_cost_budget(mut self, startup_cost_budget: usize) -> Self {
+        self.chunking_context.startup_cost_budget = startup_cost_budget;
+        self
+    }
+
+    /// Whether async modules, e.g. modules with top-level await, are supported
+    /// by the consumer of the output. When disabled, they are reported.
+    pub fn async_modules(mut self, async_modules: bool) -> Self {
+        self.chunking_context.async_modules = async_modules;
+        self
+    }
+
+    pub fn chunk_banners(mut self, chunk_banners: Vc<ChunkBanners>) -> Self {
+        self.chunking_context.chunk_banners = chunk_banners;
+        self
+    }
+
+    pub fn content_hash_algorithm(mut self, algorithm: ContentHashAlgorithm) -> Self {
+        self.chunking_context.content_hash_algorithm = algorithm;
+        self
+    }
+
+    pub fn content_hash_length(mut self, length: usize) -> Self {
+        self.chunking_context.content_hash_length = length;
+        self
+    }
+
+    /// How the ids of chunk items are derived from their modules, e.g.
+    /// [ModuleIdStrategy::Deterministic] for short ids in production.
+    pub fn module_id_strategy(mut self, module_id_strategy: ModuleIdStrategy) -> Self {
+        self.chunking_context.module_id_strategy = module_id_strategy;
+        self
+    }
+
     /// Builds the chunking context.
     pub fn build(self) -> Vc<BuildChunkingContext> {
         BuildChunkingContext::new(Value::new(self.chunking_context))
@@ -88,14 +476,52 @@ pub struct BuildChunkingContext {
     chunk_root_path: Vc<FileSystemPath>,
     /// Static assets are placed at this path
     asset_root_path: Vc<FileSystemPath>,
+    /// The algorithm of the content hashes in static asset paths.
+    content_hash_algorithm: ContentHashAlgorithm,
+    /// The number of hex digits of content hashes in static asset paths.
+    content_hash_length: usize,
+    /// How the ids of chunk items are derived from their modules.
+    module_id_strategy: ModuleIdStrategy,
     /// Static assets requested from this url base
     asset_prefix: Vc<Option<String>>,
+    /// Static assets are requested relative to `__turbopack_public_path__`,
+    /// which is read at runtime. `asset_prefix` is ignored.
+    deferred_public_path: bool,
     /// The environment chunks will be evaluated in.
     environment: Vc<Environment>,
     /// The kind of runtime to include in the output.
     runtime_type: RuntimeType,
+    /// Whether entry chunks load the runtime from a shared runtime chunk.
+    runtime_chunk: bool,
     /// Whether to minify resulting chunks
     minify_type: MinifyType,
+    /// How source maps of chunks are generated
+    source_maps_type: SourceMapsType,
+    /// The `sourceRoot` of source maps of chunks
+    source_map_source_root: Option<String>,
+    /// Rewrites applied to the `sources` of source maps of chunks
+    source_map_path_rewrites: Vc<SourcePathRewrites>,
+    /// Sources whose `sourcesContent` is omitted from source maps of chunks,
+    /// e.g. `**/node_modules/**`. Matched before `source_map_path_rewrites`.
+    source_map_omit_source_contents: Option<Vc<Glob>>,
+    /// How the Node.js runtime loads chunks
+    node_chunk_loading: NodeChunkLoading,
+    /// Whether a manifest which maps chunk paths to the emitted files is
+    /// emitted next to Node.js entry chunks
+    node_chunk_manifest: bool,
+    /// To which chunks output assets referenced by chunk items are attached.
+    referenced_output_assets_placement: ReferencedOutputAssetsPlacement,
+    /// Modules which are forced into separate chunks.
+    split_points: Vc<SplitPoints>,
+    /// Async chunk groups up to this size are inlined into their only parent.
+    inline_async_chunk_size: usize,
+    /// Modules in entry chunk groups with a higher estimated startup cost are
+    /// reported.
+    startup_cost_budget: usize,
+    /// Whether async modules can be evaluated.
+    async_modules: bool,
+    /// Text added to the start and end of matching ecmascript chunks.
+    chunk_banners: Vc<ChunkBanners>,
 }
 
 impl BuildChunkingContext {
@@ -115,10 +541,27 @@ impl BuildChunkingContext {
                 client_root,
                 chunk_root_path,
                 asset_root_path,
+                content_hash_algorithm: Default::default(),
+                content_hash_length: 8,
+                module_id_strategy: Default::default(),
                 asset_prefix: Default::default(),
+                deferred_public_path: false,
                 environment,
                 runtime_type: Default::default(),
+                runtime_chunk: true,
                 minify_type: MinifyType::Minify,
+                source_maps_type: SourceMapsType::Full,
+                source_map_source_root: None,
+                source_map_path_rewrites: SourcePathRewrites::empty(),
+                source_map_omit_source_contents: None,
+                node_chunk_loading: Default::default(),
+                node_chunk_manifest: false,
+                referenced_output_assets_placement: Default::default(),
+                split_points: SplitPoints::empty(),
+                inline_async_chunk_size: 0,
+                startup_cost_budget: 0,
+                async_modules: true,
+                chunk_banners: ChunkBanners::empty(),
             },
         }
     }
@@ -136,6 +579,38 @@ impl BuildChunkingContext {
     pub fn minify_type(&self) -> MinifyType {
         self.minify_type
     }
+
+    pub fn source_maps_type(&self) -> SourceMapsType {
+        self.source_maps_type
+    }
+
+    pub(crate) fn source_map_source_root(&self) -> Option<String> {
+        self.source_map_source_root.clone()
+    }
+
+    pub(crate) fn source_map_path_rewrites(&self) -> Vc<SourcePathRewrites> {
+        self.source_map_path_rewrites
+    }
+
+    pub(crate) fn source_map_omit_source_contents(&self) -> Option<Vc<Glob>> {
+        self.source_map_omit_source_contents
+    }
+
+    pub fn node_chunk_loading(&self) -> NodeChunkLoading {
+        self.node_chunk_loading
+    }
+
+    pub(crate) fn node_chunk_manifest(&self) -> bool {
+        self.node_chunk_manifest
+    }
+
+    pub(crate) fn runtime_chunk(&self) -> bool {
+        self.runtime_chunk
+    }
+
+    pub(crate) fn chunk_banners(&self) -> Vc<ChunkBanners> {
+        self.chunk_banners
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -158,7 +633,7 @@ impl BuildChunkingContext {
     ) -> Result<Vc<Box<dyn OutputAsset>>> {
         let availability_info = AvailabilityInfo::Root;
 
-        let MakeChunkGroupResult { chunks } = make_chunk_group(
+        let MakeChunkGroupResult { chunks, .. } = make_chunk_group(
             Vc::upcast(self),
             once(Vc::upcast(module)).chain(
                 evaluatable_assets
@@ -166,6 +641,7 @@ impl BuildChunkingContext {
                     .iter()
                     .map(|&asset| Vc::upcast(asset)),
             ),
+            None,
             availability_info,
         )
         .await?;
@@ -186,6 +662,194 @@ impl BuildChunkingContext {
         Ok(asset)
     }
 
+    /// Generates a single output file for React Native that contains the
+    /// runtime and all modules reachable from `module`, including those of
+    /// async chunk groups. It evaluates the given assets and then `module`.
+    #[turbo_tasks::function]
+    pub async fn react_native_bundle(
+        self: Vc<Self>,
+        path: Vc<FileSystemPath>,
+        module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+    ) -> Result<Vc<Box<dyn OutputAsset>>> {
+        let MakeChunkGroupResult { chunks, .. } = make_chunk_group(
+            Vc::upcast(self),
+            once(Vc::upcast(module)).chain(
+                evaluatable_assets
+                    .await?
+                    .iter()
+                    .map(|&asset| Vc::upcast(asset)),
+            ),
+            None,
+            AvailabilityInfo::Root,
+        )
+        .await?;
+
+        let chunks: Vec<_> = chunks
+            .iter()
+            .map(|chunk| self.generate_chunk(*chunk))
+            .collect();
+
+        Ok(Vc::upcast(EcmascriptBuildReactNativeBundle::new(
+            path,
+            self,
+            Vc::cell(chunks),
+            evaluatable_assets,
+            module,
+        )))
+    }
+
+    /// Generates a single output file for Node.js which embeds the runtime and
+    /// the entry chunk group of `module`, and exports the exports of `module`
+    /// in the format of `options`. It evaluates the given assets before
+    /// `module`.
+    #[turbo_tasks::function]
+    pub async fn node_bundle(
+        self: Vc<Self>,
+        path: Vc<FileSystemPath>,
+        module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+        options: Vc<NodeBundleOptions>,
+    ) -> Result<Vc<Box<dyn OutputAsset>>> {
+        let MakeChunkGroupResult { chunks, .. } = make_chunk_group(
+            Vc::upcast(self),
+            once(Vc::upcast(module)).chain(
+                evaluatable_assets
+                    .await?
+                    .iter()
+                    .map(|&asset| Vc::upcast(asset)),
+            ),
+            None,
+            AvailabilityInfo::Root,
+        )
+        .await?;
+
+        let chunks: Vec<_> = chunks
+            .iter()
+            .map(|chunk| self.generate_chunk(*chunk))
+            .collect();
+
+        Ok(Vc::upcast(EcmascriptBuildNodeBundle::new(
+            path,
+            self,
+            Vc::cell(chunks),
+            evaluatable_assets,
+            module,
+            options,
+        )))
+    }
+
+    /// Generates a single output file which embeds the runtime and all modules
+    /// reachable from `module`, and exposes the exports of `module` in the
+    /// format of `options`, e.g. to build embeddable widgets, SDKs or
+    /// packages. It evaluates the given assets before `module`.
+    ///
+    /// `module` must be resolved with the [LibraryOptions::import_map] and the
+    /// [LibraryOptions::externals] of `options`, so that the external modules
+    /// are provided by the wrapper or imported instead of being bundled.
+    #[turbo_tasks::function]
+    pub async fn library_bundle(
+        self: Vc<Self>,
+        path: Vc<FileSystemPath>,
+        module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+        options: Vc<LibraryOptions>,
+    ) -> Result<Vc<Box<dyn OutputAsset>>> {
+        let MakeChunkGroupResult { chunks, .. } = make_chunk_group(
+            Vc::upcast(self),
+            once(Vc::upcast(module)).chain(
+                evaluatable_assets
+                    .await?
+                    .iter()
+                    .map(|&asset| Vc::upcast(asset)),
+            ),
+            None,
+            AvailabilityInfo::Root,
+        )
+        .await?;
+
+        let chunks: Vec<_> = chunks
+            .iter()
+            .map(|chunk| self.generate_chunk(*chunk))
+            .collect();
+
+        let declaration = match options.await?.declaration {
+            Some(declaration) => Some(declaration),
+            None => {
+                let declaration = module.ident().path().with_extension("d.ts".to_string());
+                (*declaration.get_type().await? == FileSystemEntryType::File).then_some(declaration)
+            }
+        };
+        let declarations = match declaration {
+            Some(declaration) => {
+                let extension = match path.await?.extension_ref() {
+                    Some("mjs") => "d.mts",
+                    Some("cjs") => "d.cts",
+                    _ => "d.ts",
+                };
+                declaration_assets(declaration, path.with_extension(extension.to_string()))
+            }
+            None => OutputAssets::empty(),
+        };
+
+        Ok(Vc::upcast(EcmascriptBuildLibraryBundle::new(
+            path,
+            self,
+            Vc::cell(chunks),
+            evaluatable_assets,
+            module,
+            options,
+            declarations,
+        )))
+    }
+
+    /// Generates a module federation container, i.e. a `remoteEntry.mjs` at
+    /// `path` which exposes modules to other builds at runtime. The chunk
+    /// groups of the exposed modules and of the shared packages are loaded on
+    /// demand and share the runtime of the container, so modules they have in
+    /// common are only instantiated once. An `mf-manifest.json` which
+    /// describes the container is emitted next to it.
+    ///
+    /// The exposed modules must be resolved with the
+    /// [federation_externals] of the shared packages, so that they are
+    /// provided by the container instead of being bundled.
+    #[turbo_tasks::function]
+    pub fn federation_container(
+        self: Vc<Self>,
+        path: Vc<FileSystemPath>,
+        options: Vc<FederationContainerOptions>,
+    ) -> Vc<Box<dyn OutputAsset>> {
+        Vc::upcast(EcmascriptBuildFederationContainer::new(path, self, options))
+    }
+
+    #[turbo_tasks::function]
+    async fn chunk_group_with_name(
+        self: Vc<Self>,
+        module: Vc<Box<dyn ChunkableModule>>,
+        chunk_name: Option<String>,
+        availability_info: Value<AvailabilityInfo>,
+    ) -> Result<Vc<OutputAssets>> {
+        let MakeChunkGroupResult { chunks, .. } = make_chunk_group(
+            Vc::upcast(self),
+            [Vc::upcast(module)],
+            chunk_name,
+            availability_info.into_value(),
+        )
+        .await?;
+
+        let mut assets: Vec<Vc<Box<dyn OutputAsset>>> = chunks
+            .iter()
+            .map(|chunk| self.generate_chunk(*chunk))
+            .collect();
+
+        // Resolve assets
+        for asset in assets.iter_mut() {
+            *asset = asset.resolve().await?;
+        }
+
+        Ok(Vc::cell(assets))
+    }
+
     #[turbo_tasks::function]
     async fn generate_chunk(
         self: Vc<Self>,
@@ -232,15 +896,21 @@ impl ChunkingContext for BuildChunkingContext {
             .strip_prefix(&format!("{}/", this.client_root.await?.path))
             .context("expected client root to contain asset path")?;
 
-        Ok(Vc::cell(format!(
-            "{}{}",
-            this.asset_prefix
+        if this.deferred_public_path {
+            return Ok(Vc::cell(asset_path.to_string()));
+        }
+
+        let asset_prefix = match &*this.asset_prefix.await? {
+            Some(asset_prefix) => asset_prefix.to_owned(),
+            None => this
+                .environment
+                .asset_prefix()
                 .await?
-                .as_ref()
-                .map(|s| s.to_owned())
+                .clone()
                 .unwrap_or_else(|| "/".to_owned()),
-            asset_path
-        )))
+        };
+
+        Ok(Vc::cell(format!("{}{}", asset_prefix, asset_path)))
     }
 
     #[turbo_tasks::function]
@@ -256,7 +926,10 @@ impl ChunkingContext for BuildChunkingContext {
 
     #[turbo_tasks::function]
     fn reference_chunk_source_maps(&self, _chunk: Vc<Box<dyn OutputAsset>>) -> Vc<bool> {
-        Vc::cell(true)
+        Vc::cell(matches!(
+            self.source_maps_type,
+            SourceMapsType::Full | SourceMapsType::Cheap | SourceMapsType::Hidden
+        ))
     }
 
     #[turbo_tasks::function]
@@ -277,6 +950,16 @@ impl ChunkingContext for BuildChunkingContext {
         Ok(Vc::cell(false))
     }
 
+    #[turbo_tasks::function]
+    fn content_hash(&self, content: Vc<AssetContent>) -> Vc<String> {
+        content_hash(content, self.content_hash_algorithm)
+    }
+
+    #[turbo_tasks::function]
+    fn chunk_item_id_from_ident(&self, ident: Vc<AssetIdent>) -> Vc<ModuleId> {
+        module_id_from_ident(ident, self.module_id_strategy)
+    }
+
     #[turbo_tasks::function]
     async fn asset_path(
         &self,
@@ -285,70 +968,172 @@ impl ChunkingContext for BuildChunkingContext {
     ) -> Result<Vc<FileSystemPath>> {
         let source_path = original_asset_ident.path().await?;
         let basename = source_path.file_name();
+        let content_hash = &content_hash[..self.content_hash_length.min(content_hash.len())];
+        // Without a hash, the asset keeps the name of its source
+        if content_hash.is_empty() {
+            return Ok(self.asset_root_path.join(basename.to_string()));
+        }
         let asset_path = match source_path.extension_ref() {
             Some(ext) => format!(
                 "{basename}.{content_hash}.{ext}",
                 basename = &basename[..basename.len() - ext.len() - 1],
-                content_hash = &content_hash[..8]
-            ),
-            None => format!(
-                "{basename}.{content_hash}",
-                content_hash = &content_hash[..8]
             ),
+            None => format!("{basename}.{content_hash}"),
         };
         Ok(self.asset_root_path.join(asset_path))
     }
 
     #[turbo_tasks::function]
-    async fn chunk_group(
+    fn referenced_output_assets_placement(&self) -> Vc<ReferencedOutputAssetsPlacement> {
+        self.referenced_output_assets_placement.cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn origin_hints(&self) -> Result<Vc<OriginHints>> {
+        // The asset prefix is ignored when the public path is deferred
+        if self.deferred_public_path {
+            return Ok(OriginHints::empty());
+        }
+        let asset_prefix = match &*self.asset_prefix.await? {
+            Some(asset_prefix) => Some(asset_prefix.to_owned()),
+            None => self.environment.asset_prefix().await?.clone(),
+        };
+        let Some(origin) = asset_prefix.as_deref().and_then(url_origin) else {
+            return Ok(OriginHints::empty());
+        };
+        Ok(Vc::cell(vec![OriginHint {
+            origin,
+            crossorigin: false,
+        }]))
+    }
+
+    #[turbo_tasks::function]
+    fn split_points(&self) -> Vc<SplitPoints> {
+        self.split_points
+    }
+
+    #[turbo_tasks::function]
+    fn inline_async_chunk_size(&self) -> Vc<usize> {
+        Vc::cell(self.inline_async_chunk_size)
+    }
+
+    #[turbo_tasks::function]
+    fn startup_cost_budget(&self) -> Vc<usize> {
+        Vc::cell(self.startup_cost_budget)
+    }
+
+    #[turbo_tasks::function]
+    fn supports_async_modules(&self) -> Vc<bool> {
+        Vc::cell(self.async_modules)
+    }
+
+    #[turbo_tasks::function]
+    fn chunk_group(
+        self: Vc<Self>,
+        module: Vc<Box<dyn ChunkableModule>>,
+        availability_info: Value<AvailabilityInfo>,
+    ) -> Vc<OutputAssets> {
+        self.chunk_group_with_name(module, None, availability_info)
+    }
+
+    #[turbo_tasks::function]
+    fn named_chunk_group(
         self: Vc<Self>,
         module: Vc<Box<dyn ChunkableModule>>,
+        chunk_name: String,
         availability_info: Value<AvailabilityInfo>,
+    ) -> Vc<OutputAssets> {
+        self.chunk_group_with_name(module, Some(chunk_name), availability_info)
+    }
+
+    #[turbo_tasks::function]
+    async fn evaluated_chunk_group(
+        self: Vc<Self>,
+        ident: Vc<AssetIdent>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
     ) -> Result<Vc<OutputAssets>> {
-        let MakeChunkGroupResult { chunks } = make_chunk_group(
+        // The group is loaded through a single entry chunk, e.g. as the script of
+        // a worker, which loads the runtime and the other chunks itself.
+        let Some(&last_asset) = evaluatable_assets.await?.last() else {
+            bail!("evaluated chunk groups need at least one evaluatable asset");
+        };
+        let module = Vc::try_resolve_sidecast::<Box<dyn EcmascriptChunkPlaceable>>(last_asset)
+            .await?
+            .context("the last evaluatable asset of a chunk group must be an ecmascript module")?;
+        let entry_chunk = self.entry_chunk_group(
+            self.chunk_path(ident, ".js".to_string()),
+            module,
+            evaluatable_assets,
+        );
+        Ok(Vc::cell(vec![entry_chunk]))
+    }
+
+    #[turbo_tasks::function]
+    async fn service_worker_entry_chunk(
+        self: Vc<Self>,
+        path: Vc<FileSystemPath>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+    ) -> Result<Vc<Box<dyn OutputAsset>>> {
+        let evaluatable_assets_ref = evaluatable_assets.await?;
+        let Some(&last_asset) = evaluatable_assets_ref.last() else {
+            bail!("service worker entries need at least one evaluatable asset");
+        };
+        if Vc::try_resolve_sidecast::<Box<dyn EcmascriptChunkPlaceable>>(last_asset)
+            .await?
+            .is_none()
+        {
+            bail!("the entry of a service worker must be an ecmascript module");
+        }
+
+        // Event handlers of service workers must be registered during the initial
+        // evaluation of the script, so it can't wait for async modules.
+        let MakeChunkGroupResult { chunks, .. } = make_sync_chunk_group(
             Vc::upcast(self),
-            [Vc::upcast(module)],
-            availability_info.into_value(),
+            evaluatable_assets_ref
+                .iter()
+                .map(|&asset| Vc::upcast(asset)),
+            None,
+            AvailabilityInfo::Root,
+            "a service worker script",
         )
         .await?;
 
-        let mut assets: Vec<Vc<Box<dyn OutputAsset>>> = chunks
+        let chunks: Vec<_> = chunks
             .iter()
             .map(|chunk| self.generate_chunk(*chunk))
             .collect();
 
-        // Resolve assets
-        for asset in assets.iter_mut() {
-            *asset = asset.resolve().await?;
-        }
-
-        Ok(Vc::cell(assets))
-    }
-
-    #[turbo_tasks::function]
-    async fn evaluated_chunk_group(
-        self: Vc<Self>,
-        _ident: Vc<AssetIdent>,
-        _evaluatable_assets: Vc<EvaluatableAssets>,
-    ) -> Result<Vc<OutputAssets>> {
-        // TODO(alexkirsz) This method should be part of a separate trait that is
-        // only implemented for client/edge runtimes.
-        bail!("the build chunking context does not support evaluated chunk groups")
+        Ok(Vc::upcast(EcmascriptBuildServiceWorkerChunk::new(
+            path,
+            self,
+            Vc::cell(chunks),
+            evaluatable_assets,
+        )))
     }
 
     #[turbo_tasks::function]
     fn async_loader_chunk_item(
         self: Vc<Self>,
         module: Vc<Box<dyn ChunkableModule>>,
+        chunk_name: Option<String>,
         availability_info: Value<AvailabilityInfo>,
     ) -> Vc<Box<dyn ChunkItem>> {
-        let manifest_asset = ManifestAsyncModule::new(module, Vc::upcast(self), availability_info);
+        let manifest_asset =
+            ManifestAsyncModule::new(module, Vc::upcast(self), chunk_name, availability_info);
         Vc::upcast(ManifestLoaderChunkItem::new(
             manifest_asset,
             Vc::upcast(self),
         ))
     }
 
+    #[turbo_tasks::function]
+    fn inlined_async_loader_chunk_item(
+        self: Vc<Self>,
+        module: Vc<Box<dyn ChunkableModule>>,
+    ) -> Vc<Box<dyn ChunkItem>> {
+        Vc::upcast(InlinedLoaderChunkItem::new(module, Vc::upcast(self)))
+    }
+
     #[turbo_tasks::function]
     fn async_loader_chunk_item_id(
         self: Vc<Self>,
@@ -359,4 +1144,92 @@ impl ChunkingContext for BuildChunkingContext {
 }
 
 #[turbo_tasks::value_impl]
-impl EcmascriptChunkingContext for BuildChunkingContext {}
+impl EcmascriptChunkingContext for BuildChunkingContext {
+    #[turbo_tasks::function]
+    fn is_public_path_deferred(&self) -> Vc<bool> {
+        Vc::cell(self.deferred_public_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::indexmap;
+    use lazy_static::lazy_static;
+    use turbo_tasks::Value;
+    use turbo_tasks_fs::VirtualFileSystem;
+    use turbo_tasks_testing::run;
+    use turbopack_core::{
+        chunk::ChunkingContext,
+        environment::{Environment, ExecutionEnvironment, NodeJsEnvironment},
+        ident::AssetIdent,
+        resolve::{options::ImportMapResult, parse::Request, pattern::Pattern, ResolveResultItem},
+    };
+
+    use super::{BuildChunkingContext, LibraryOptions};
+
+    lazy_static! {
+        static ref REGISTER: () = crate::register();
+    }
+
+    #[tokio::test]
+    async fn external_globals_are_external_modules() {
+        run! {
+            let options = LibraryOptions {
+                external_globals: indexmap! {
+                    "react".to_string() => "React".to_string(),
+                },
+                ..Default::default()
+            }
+            .cell();
+            let import_map = options.import_map().await?;
+            let lookup_path = VirtualFileSystem::new().root();
+            let lookup = |request: &str| {
+                import_map.lookup(
+                    lookup_path,
+                    Request::parse(Value::new(Pattern::Constant(request.to_string()))),
+                )
+            };
+
+            let ImportMapResult::Result(result) = lookup("react").await? else {
+                panic!("react should be mapped");
+            };
+            assert!(matches!(
+                result.await?.primary[..],
+                [ResolveResultItem::OriginalReferenceExternal]
+            ));
+            assert!(matches!(
+                lookup("react-dom").await?,
+                ImportMapResult::NoEntry
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn asset_paths_omit_an_empty_content_hash() {
+        run! {
+            let root = VirtualFileSystem::new().root();
+            let environment = Environment::new(Value::new(ExecutionEnvironment::NodeJsBuildTime(
+                NodeJsEnvironment::default().cell(),
+            )));
+            let asset_path = |content_hash_length| {
+                BuildChunkingContext::builder(
+                    root,
+                    root,
+                    root,
+                    root,
+                    root.join("static".to_string()),
+                    environment,
+                )
+                .content_hash_length(content_hash_length)
+                .build()
+                .asset_path(
+                    "0123456789abcdef".to_string(),
+                    AssetIdent::from_path(root.join("logo.png".to_string())),
+                )
+            };
+
+            assert_eq!(asset_path(8).await?.path, "static/logo.01234567.png");
+            assert_eq!(asset_path(0).await?.path, "static/logo.png");
+        }
+    }
+}
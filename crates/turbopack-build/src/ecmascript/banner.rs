@@ -0,0 +1,56 @@
+use std::io::Write;
+
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::{
+    chunk::ChunkingContext,
+    code_builder::{Code, CodeBuilder},
+};
+
+use crate::BuildChunkingContext;
+
+/// Adds the banners and footers of all [crate::ChunkBanner]s matching the
+/// chunk at `path` to its (possibly minified) `code`.
+#[turbo_tasks::function]
+pub(crate) async fn apply_chunk_banners(
+    chunking_context: Vc<BuildChunkingContext>,
+    path: Vc<FileSystemPath>,
+    code: Vc<Code>,
+) -> Result<Vc<Code>> {
+    let chunk_banners = chunking_context.await?.chunk_banners().await?;
+    if chunk_banners.is_empty() {
+        return Ok(code);
+    }
+
+    let output_root = chunking_context.output_root().await?;
+    let path = path.await?;
+    let Some(chunk_path) = output_root.get_path_to(&path) else {
+        return Ok(code);
+    };
+    let mut matching = Vec::new();
+    for chunk_banner in chunk_banners.iter() {
+        if chunk_banner.glob.await?.execute(chunk_path) {
+            matching.push(chunk_banner);
+        }
+    }
+    if matching.is_empty() {
+        return Ok(code);
+    }
+
+    let mut builder = CodeBuilder::default();
+    for banner in matching
+        .iter()
+        .filter_map(|chunk_banner| chunk_banner.banner.as_ref())
+    {
+        writeln!(builder, "{banner}")?;
+    }
+    builder.push_code(&*code.await?);
+    for footer in matching
+        .iter()
+        .filter_map(|chunk_banner| chunk_banner.footer.as_ref())
+    {
+        write!(builder, "\n{footer}")?;
+    }
+    Ok(builder.build().cell())
+}
@@ -0,0 +1,350 @@
+use std::io::Write;
+
+use anyhow::{bail, Result};
+use indexmap::IndexSet;
+use turbo_tasks::{TryJoinIterExt, Vc};
+use turbo_tasks_fs::{File, FileSystemPath};
+use turbopack_core::{
+    asset::AssetContent,
+    chunk::{ChunkItemExt, ChunkableModule, ChunkingContext, EvaluatableAssets},
+    code_builder::{Code, CodeBuilder},
+    output::{OutputAsset, OutputAssets},
+    source_map::SourceMapAsset,
+};
+use turbopack_ecmascript::{
+    chunk::{EcmascriptChunkItemExt, EcmascriptChunkPlaceable, EcmascriptExports},
+    utils::StringifyJs,
+};
+
+use crate::{
+    chunking_context::MinifyType,
+    ecmascript::{
+        banner::apply_chunk_banners, minify::minify, node::chunk::EcmascriptBuildNodeChunk,
+        source_maps::apply_source_maps_type,
+    },
+    BuildChunkingContext,
+};
+
+/// The output assets which are reachable from the chunks of a single-file
+/// bundle, split into chunks which are inlined and assets which are emitted.
+#[turbo_tasks::value]
+pub(crate) struct BundleAssets {
+    pub chunks: Vec<Vc<EcmascriptBuildNodeChunk>>,
+    pub assets: Vec<Vc<Box<dyn OutputAsset>>>,
+}
+
+/// Collects the chunks to inline them into a single-file bundle. With
+/// `inline_async_chunks`, all chunks which are reachable from them, e.g. async
+/// chunks, are inlined too. Otherwise they are emitted next to the bundle.
+#[turbo_tasks::function]
+pub(crate) async fn bundle_assets(
+    chunks: Vc<OutputAssets>,
+    inline_async_chunks: bool,
+) -> Result<Vc<BundleAssets>> {
+    let mut visited = IndexSet::new();
+    let chunks = chunks.await?;
+    let mut queue = chunks.clone_value();
+    let mut inlined = Vec::new();
+    let mut assets = Vec::new();
+    while let Some(asset) = queue.pop() {
+        let asset = asset.resolve().await?;
+        if !visited.insert(asset) {
+            continue;
+        }
+        let inline = inline_async_chunks || chunks.contains(&asset);
+        let chunk = if inline {
+            Vc::try_resolve_downcast_type::<EcmascriptBuildNodeChunk>(asset).await?
+        } else {
+            None
+        };
+        if let Some(chunk) = chunk {
+            inlined.push(chunk);
+            queue.extend(asset.references().await?.iter().copied());
+        } else if Vc::try_resolve_downcast_type::<SourceMapAsset>(asset)
+            .await?
+            .is_none()
+        {
+            // Source maps of inlined chunks are replaced by the source map
+            // of the bundle.
+            assets.push(asset);
+        }
+    }
+
+    Ok(BundleAssets {
+        chunks: inlined,
+        assets,
+    }
+    .cell())
+}
+
+/// Writes a call of the runtime function `register_chunk` with the chunk items
+/// of each of the `chunks`.
+pub(crate) async fn write_registered_chunks(
+    code: &mut CodeBuilder,
+    register_chunk: &str,
+    chunks: &[Vc<EcmascriptBuildNodeChunk>],
+) -> Result<()> {
+    for chunk in chunks {
+        let content = chunk.chunk_content().await?;
+        writeln!(code, "{register_chunk}({{")?;
+        for (id, item_code) in content
+            .chunk_items
+            .iter()
+            .map(|&(chunk_item, async_module_info)| async move {
+                Ok((
+                    chunk_item.id().await?,
+                    chunk_item.code(async_module_info).await?,
+                ))
+            })
+            .try_join()
+            .await?
+        {
+            write!(code, "{}: ", StringifyJs(&id))?;
+            code.push_code(&item_code);
+            writeln!(code, ",")?;
+        }
+        writeln!(code, "}});")?;
+    }
+    Ok(())
+}
+
+/// Returns the names of the exports of the `entry_module` of an ES module
+/// bundle, or `None` when its exports are only known at runtime and are
+/// exported as the default export.
+async fn esm_export_names(
+    entry_module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+) -> Result<Option<Vec<String>>> {
+    Ok(match &*entry_module.get_exports().await? {
+        EcmascriptExports::EsmExports(exports) => Some(
+            exports
+                .expand_exports()
+                .await?
+                .exports
+                .keys()
+                .cloned()
+                .collect(),
+        ),
+        EcmascriptExports::None => Some(Vec::new()),
+        EcmascriptExports::DynamicNamespace
+        | EcmascriptExports::CommonJs
+        | EcmascriptExports::Value => None,
+    })
+}
+
+/// Writes the bindings of the exports of an ES module bundle and the
+/// `__turbopack_update_exports__` function, which assigns them the exports of
+/// the `entry_module` from `__turbopack_bundle_exports__`.
+///
+/// The bindings are declared before the runtime, so that the runtime can
+/// call the function when the exports may have changed, which keeps them
+/// live.
+pub(crate) async fn write_esm_export_bindings(
+    code: &mut CodeBuilder,
+    entry_module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+) -> Result<()> {
+    let names = esm_export_names(entry_module).await?;
+    write_export_bindings(code, names.as_deref().unwrap_or_default())
+}
+
+fn write_export_bindings(code: &mut CodeBuilder, names: &[String]) -> Result<()> {
+    writeln!(code, "let __turbopack_bundle_exports__;")?;
+    if !names.is_empty() {
+        let bindings = (0..names.len())
+            .map(|index| format!("__turbopack_export_{index}__"))
+            .collect::<Vec<_>>();
+        writeln!(code, "let {};", bindings.join(", "))?;
+    }
+    writeln!(code, "function __turbopack_update_exports__() {{")?;
+    writeln!(
+        code,
+        "if (__turbopack_bundle_exports__ === undefined) return;"
+    )?;
+    for (index, name) in names.iter().enumerate() {
+        writeln!(
+            code,
+            "__turbopack_export_{index}__ = __turbopack_bundle_exports__[{}];",
+            StringifyJs(name)
+        )?;
+    }
+    writeln!(code, "}}")?;
+    Ok(())
+}
+
+/// Writes the `export` statements of an ES module bundle, which export the
+/// bindings of [write_esm_export_bindings] once `__turbopack_bundle_exports__`
+/// has been assigned.
+pub(crate) async fn write_esm_exports(
+    code: &mut CodeBuilder,
+    entry_module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+) -> Result<()> {
+    let names = esm_export_names(entry_module).await?;
+    write_exports(code, names.as_deref())
+}
+
+fn write_exports(code: &mut CodeBuilder, names: Option<&[String]>) -> Result<()> {
+    let Some(names) = names else {
+        writeln!(code, "export default __turbopack_bundle_exports__;")?;
+        return Ok(());
+    };
+
+    writeln!(code, "__turbopack_update_exports__();")?;
+    let specifiers = names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            if is_identifier_name(name) {
+                format!("__turbopack_export_{index}__ as {name}")
+            } else {
+                format!("__turbopack_export_{index}__ as {}", StringifyJs(name))
+            }
+        })
+        .collect::<Vec<_>>();
+    writeln!(code, "export {{ {} }};", specifiers.join(", "))?;
+    Ok(())
+}
+
+fn is_identifier_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Writes the statements which instantiate the `evaluatable_assets` as
+/// runtime modules.
+pub(crate) async fn write_evaluated_assets(
+    code: &mut CodeBuilder,
+    chunking_context: Vc<BuildChunkingContext>,
+    evaluatable_assets: Vc<EvaluatableAssets>,
+) -> Result<()> {
+    for evaluatable_asset in &*evaluatable_assets.await? {
+        if let Some(placeable) =
+            Vc::try_resolve_sidecast::<Box<dyn EcmascriptChunkPlaceable>>(*evaluatable_asset)
+                .await?
+        {
+            let runtime_module_id = placeable
+                .as_chunk_item(Vc::upcast(chunking_context))
+                .id()
+                .await?;
+
+            writeln!(
+                code,
+                "getOrInstantiateRuntimeModule({}, CHUNK_PUBLIC_PATH);",
+                StringifyJs(&*runtime_module_id),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the path of the bundle at `bundle_path` relative to the output
+/// root, which its runtime resolves the paths of chunks and assets against.
+pub(crate) async fn bundle_public_path(
+    chunking_context: Vc<BuildChunkingContext>,
+    bundle_path: Vc<FileSystemPath>,
+) -> Result<String> {
+    let output_root = chunking_context.output_root().await?;
+    let bundle_path = bundle_path.await?;
+    let Some(bundle_public_path) = output_root.get_path_to(&bundle_path) else {
+        bail!(
+            "bundle path ({}) is not in output root ({})",
+            bundle_path.to_string(),
+            output_root.to_string()
+        );
+    };
+    Ok(bundle_public_path.to_string())
+}
+
+/// Minifies the `code` of the bundle at `bundle_path`, adds the banners of
+/// the chunking context and prepares its source map.
+#[turbo_tasks::function]
+pub(crate) async fn finalize_bundle_code(
+    chunking_context: Vc<BuildChunkingContext>,
+    bundle_path: Vc<FileSystemPath>,
+    code: Vc<Code>,
+) -> Result<Vc<Code>> {
+    let mut code = code;
+    if matches!(chunking_context.await?.minify_type(), MinifyType::Minify) {
+        code = minify(bundle_path, code);
+    }
+
+    code = apply_chunk_banners(chunking_context, bundle_path, code);
+
+    Ok(apply_source_maps_type(chunking_context, bundle_path, code))
+}
+
+/// The content of a bundle with the (finalized) `code`.
+pub(crate) async fn bundle_content(code: Vc<Code>) -> Result<Vc<AssetContent>> {
+    let code = code.await?;
+    Ok(AssetContent::file(
+        File::from(code.source_code().clone()).into(),
+    ))
+}
+
+/// The references of the `bundle`, which are the assets emitted next to it,
+/// e.g. the `assets` of [bundle_assets], and its source map.
+pub(crate) async fn bundle_references(
+    bundle: Vc<Box<dyn OutputAsset>>,
+    chunking_context: Vc<BuildChunkingContext>,
+    mut assets: Vec<Vc<Box<dyn OutputAsset>>>,
+) -> Result<Vc<OutputAssets>> {
+    if *chunking_context.reference_chunk_source_maps(bundle).await? {
+        assets.push(Vc::upcast(SourceMapAsset::new(Vc::upcast(bundle))));
+    }
+    Ok(Vc::cell(assets))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use turbopack_core::code_builder::CodeBuilder;
+
+    use super::{is_identifier_name, write_export_bindings, write_exports};
+
+    fn written(write: impl FnOnce(&mut CodeBuilder)) -> String {
+        let mut code = CodeBuilder::default();
+        write(&mut code);
+        code.build().source_code().to_str().unwrap().into_owned()
+    }
+
+    #[test]
+    fn exports_live_bindings() {
+        let names = ["a".to_string(), "not an identifier".to_string()];
+        assert_eq!(
+            written(|code| write_export_bindings(code, &names).unwrap()),
+            indoc! {r#"
+                let __turbopack_bundle_exports__;
+                let __turbopack_export_0__, __turbopack_export_1__;
+                function __turbopack_update_exports__() {
+                if (__turbopack_bundle_exports__ === undefined) return;
+                __turbopack_export_0__ = __turbopack_bundle_exports__["a"];
+                __turbopack_export_1__ = __turbopack_bundle_exports__["not an identifier"];
+                }
+            "#}
+        );
+        assert_eq!(
+            written(|code| write_exports(code, Some(&names)).unwrap()),
+            indoc! {r#"
+                __turbopack_update_exports__();
+                export { __turbopack_export_0__ as a, __turbopack_export_1__ as "not an identifier" };
+            "#}
+        );
+    }
+
+    #[test]
+    fn exports_runtime_exports_as_default() {
+        assert_eq!(
+            written(|code| write_exports(code, None).unwrap()),
+            "export default __turbopack_bundle_exports__;\n"
+        );
+    }
+
+    #[test]
+    fn identifier_names() {
+        assert!(is_identifier_name("$a_1"));
+        assert!(!is_identifier_name("1a"));
+        assert!(!is_identifier_name("a-b"));
+        assert!(!is_identifier_name(""));
+    }
+}
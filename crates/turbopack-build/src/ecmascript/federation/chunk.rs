@@ -0,0 +1,106 @@
+use std::io::Write;
+
+use anyhow::Result;
+use turbo_tasks::{ValueToString, Vc};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    code_builder::{Code, CodeBuilder},
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets},
+    source_map::{GenerateSourceMap, OptionSourceMap},
+};
+
+use crate::{
+    ecmascript::{
+        bundle::{
+            bundle_content, bundle_references, finalize_bundle_code, write_registered_chunks,
+        },
+        node::chunk::EcmascriptBuildNodeChunk,
+    },
+    BuildChunkingContext,
+};
+
+/// A chunk of a module federation container, which is imported on demand by
+/// the container (see
+/// [crate::ecmascript::federation::container::EcmascriptBuildFederationContainer]).
+///
+/// It's an ES module whose default export registers the chunk items of the
+/// wrapped chunk with the `registerChunk` function of the runtime of the
+/// container. Its path is the one of the wrapped chunk with an `.mjs`
+/// extension, which is derived from the chunk items, so a chunk which is part
+/// of the chunk groups of several exposed modules is only emitted once.
+#[turbo_tasks::value(shared)]
+pub(crate) struct EcmascriptBuildFederationChunk {
+    chunking_context: Vc<BuildChunkingContext>,
+    chunk: Vc<EcmascriptBuildNodeChunk>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptBuildFederationChunk {
+    /// Creates a new [`Vc<EcmascriptBuildFederationChunk>`].
+    #[turbo_tasks::function]
+    pub fn new(
+        chunking_context: Vc<BuildChunkingContext>,
+        chunk: Vc<EcmascriptBuildNodeChunk>,
+    ) -> Vc<Self> {
+        EcmascriptBuildFederationChunk {
+            chunking_context,
+            chunk,
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn code(self: Vc<Self>) -> Result<Vc<Code>> {
+        let this = self.await?;
+
+        let mut code = CodeBuilder::default();
+        writeln!(code, "export default function (registerChunk) {{")?;
+        write_registered_chunks(&mut code, "registerChunk", &[this.chunk]).await?;
+        write!(code, "}}")?;
+
+        Ok(finalize_bundle_code(
+            this.chunking_context,
+            self.ident().path(),
+            code.build().cell(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for EcmascriptBuildFederationChunk {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell("Ecmascript Build Federation Chunk".to_string()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for EcmascriptBuildFederationChunk {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.chunk.ident().path().with_extension("mjs".to_string()))
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        let this = self.await?;
+        bundle_references(Vc::upcast(self), this.chunking_context, Vec::new()).await
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for EcmascriptBuildFederationChunk {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        bundle_content(self.code()).await
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for EcmascriptBuildFederationChunk {
+    #[turbo_tasks::function]
+    fn generate_source_map(self: Vc<Self>) -> Vc<OptionSourceMap> {
+        self.code().generate_source_map()
+    }
+}
@@ -0,0 +1,463 @@
+use std::io::Write;
+
+use anyhow::{bail, Result};
+use indexmap::{IndexMap, IndexSet};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, ReadRef, Value, ValueToString, Vc};
+use turbo_tasks_fs::{File, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{
+        availability_info::AvailabilityInfo, ChunkItemExt, ChunkableModule, ChunkingContext,
+        ModuleId,
+    },
+    code_builder::{Code, CodeBuilder},
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets},
+    source_map::{GenerateSourceMap, OptionSourceMap},
+    virtual_output::VirtualOutputAsset,
+};
+use turbopack_ecmascript::{chunk::EcmascriptChunkPlaceable, utils::StringifyJs};
+
+use super::chunk::EcmascriptBuildFederationChunk;
+use crate::{
+    chunking_context::FederationContainerOptions,
+    ecmascript::bundle::{
+        bundle_assets, bundle_content, bundle_public_path, bundle_references, finalize_bundle_code,
+    },
+    BuildChunkingContext,
+};
+
+/// The entry of a module federation container, e.g. `remoteEntry.mjs`, which
+/// exports the `init` and `get` functions that hosts load exposed modules
+/// with.
+///
+/// It contains the library runtime, which all chunks of the container
+/// register their modules with. The chunk groups of the exposed modules and
+/// of the fallbacks of the shared packages are loaded on demand, see
+/// [EcmascriptBuildFederationChunk]. The shared packages are external modules
+/// of the chunks, which the container provides once it negotiated their
+/// versions with the host.
+#[turbo_tasks::value(shared)]
+pub(crate) struct EcmascriptBuildFederationContainer {
+    path: Vc<FileSystemPath>,
+    chunking_context: Vc<BuildChunkingContext>,
+    options: Vc<FederationContainerOptions>,
+}
+
+/// A chunk group of a container, i.e. a module which is instantiated once
+/// the chunks it needs have been loaded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+struct ContainerChunkGroup {
+    id: ModuleId,
+    /// The paths of the chunks relative to the container, e.g.
+    /// `./chunks/[project]_src_Button_tsx_1f2c3d._.mjs`.
+    chunks: Vec<String>,
+}
+
+/// The chunk group of the fallback of a shared package and the versions it
+/// is compatible with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+struct SharedChunkGroup {
+    version: String,
+    required_version: String,
+    singleton: bool,
+    chunk_group: ContainerChunkGroup,
+}
+
+#[turbo_tasks::value]
+struct ContainerChunkGroups {
+    exposes: IndexMap<String, ContainerChunkGroup>,
+    shared: IndexMap<String, SharedChunkGroup>,
+    /// The chunks of all chunk groups and the output assets they reference,
+    /// e.g. CSS chunks and static assets.
+    assets: Vec<Vc<Box<dyn OutputAsset>>>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptBuildFederationContainer {
+    /// Creates a new [`Vc<EcmascriptBuildFederationContainer>`].
+    #[turbo_tasks::function]
+    pub fn new(
+        path: Vc<FileSystemPath>,
+        chunking_context: Vc<BuildChunkingContext>,
+        options: Vc<FederationContainerOptions>,
+    ) -> Vc<Self> {
+        EcmascriptBuildFederationContainer {
+            path,
+            chunking_context,
+            options,
+        }
+        .cell()
+    }
+
+    /// The chunk groups of the exposed modules and the shared packages.
+    /// Chunks which are part of several chunk groups are only emitted once.
+    #[turbo_tasks::function]
+    async fn chunk_groups(self: Vc<Self>) -> Result<Vc<ContainerChunkGroups>> {
+        let this = self.await?;
+        let options = this.options.await?;
+        let mut collector = ChunkGroupCollector {
+            chunking_context: this.chunking_context,
+            directory: this.path.parent().await?,
+            chunks: IndexMap::new(),
+            assets: IndexSet::new(),
+        };
+
+        let mut exposes = IndexMap::new();
+        for (name, &module) in &options.exposes {
+            exposes.insert(name.clone(), collector.chunk_group(module).await?);
+        }
+        let mut shared = IndexMap::new();
+        for (request, package) in &options.shared {
+            shared.insert(
+                request.clone(),
+                SharedChunkGroup {
+                    version: package.version.clone(),
+                    required_version: package
+                        .required_version
+                        .clone()
+                        .unwrap_or_else(|| format!("^{}", package.version)),
+                    singleton: package.singleton,
+                    chunk_group: collector.chunk_group(package.module).await?,
+                },
+            );
+        }
+
+        Ok(ContainerChunkGroups {
+            exposes,
+            shared,
+            assets: collector
+                .chunks
+                .into_values()
+                .chain(collector.assets)
+                .collect(),
+        }
+        .cell())
+    }
+
+    /// The `mf-manifest.json` next to the container, which describes its
+    /// exposed modules and shared packages, e.g. for deployment tooling.
+    #[turbo_tasks::function]
+    async fn manifest(self: Vc<Self>) -> Result<Vc<Box<dyn OutputAsset>>> {
+        let this = self.await?;
+        let options = this.options.await?;
+        let chunk_groups = self.chunk_groups().await?;
+
+        let manifest =
+            container_manifest(&options.name, &chunk_groups.exposes, &chunk_groups.shared);
+        Ok(Vc::upcast(VirtualOutputAsset::new(
+            this.path.parent().join("mf-manifest.json".to_string()),
+            AssetContent::file(File::from(serde_json::to_string_pretty(&manifest)?).into()),
+        )))
+    }
+
+    #[turbo_tasks::function]
+    async fn code(self: Vc<Self>) -> Result<Vc<Code>> {
+        let this = self.await?;
+        let options = this.options.await?;
+        let chunk_groups = self.chunk_groups().await?;
+
+        let container_path = self.ident().path();
+        let container_public_path =
+            bundle_public_path(this.chunking_context, container_path).await?;
+
+        let mut code = CodeBuilder::default();
+        writeln!(
+            code,
+            "const CHUNK_PUBLIC_PATH = {};",
+            StringifyJs(&container_public_path)
+        )?;
+        writeln!(code, "const LIBRARY_EXTERNALS = {{}};")?;
+
+        let runtime_code = turbopack_ecmascript_runtime::get_federation_container_runtime_code(
+            this.chunking_context.environment(),
+        );
+        code.push_code(&*runtime_code.await?);
+
+        write_container(
+            &mut code,
+            &options.name,
+            &chunk_groups.exposes,
+            &chunk_groups.shared,
+        )?;
+
+        Ok(finalize_bundle_code(
+            this.chunking_context,
+            container_path,
+            code.build().cell(),
+        ))
+    }
+}
+
+/// Collects the chunk groups of a container and the output assets of their
+/// chunks.
+struct ChunkGroupCollector {
+    chunking_context: Vc<BuildChunkingContext>,
+    directory: ReadRef<FileSystemPath>,
+    /// The chunks of all chunk groups by their path relative to the
+    /// container. The path is derived from the chunk items, so chunks with
+    /// the same path are the same.
+    chunks: IndexMap<String, Vc<Box<dyn OutputAsset>>>,
+    assets: IndexSet<Vc<Box<dyn OutputAsset>>>,
+}
+
+impl ChunkGroupCollector {
+    async fn chunk_group(
+        &mut self,
+        module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+    ) -> Result<ContainerChunkGroup> {
+        let chunks = self
+            .chunking_context
+            .chunk_group(Vc::upcast(module), Value::new(AvailabilityInfo::Root));
+        // Async chunks are registered along with the chunk group, since the
+        // library runtime can't load chunks.
+        let bundle_assets = bundle_assets(chunks, true).await?;
+
+        let mut chunk_paths = Vec::new();
+        for &chunk in &bundle_assets.chunks {
+            let chunk: Vc<Box<dyn OutputAsset>> = Vc::upcast(EcmascriptBuildFederationChunk::new(
+                self.chunking_context,
+                chunk,
+            ));
+            let chunk_path = chunk.ident().path().await?;
+            let Some(relative_path) = self.directory.get_relative_path_to(&chunk_path) else {
+                bail!(
+                    "cannot find a relative path from the container ({}) to the chunk ({})",
+                    self.directory.to_string(),
+                    chunk_path.to_string(),
+                );
+            };
+            if !chunk_paths.contains(&relative_path) {
+                chunk_paths.push(relative_path.clone());
+            }
+            self.chunks.entry(relative_path).or_insert(chunk);
+        }
+        for &asset in &bundle_assets.assets {
+            self.assets.insert(asset);
+        }
+
+        Ok(ContainerChunkGroup {
+            id: module
+                .as_chunk_item(Vc::upcast(self.chunking_context))
+                .id()
+                .await?
+                .clone_value(),
+            chunks: chunk_paths,
+        })
+    }
+}
+
+/// Writes the call of `createFederationContainer` of the container runtime
+/// and the `init` and `get` exports of the container.
+fn write_container(
+    code: &mut CodeBuilder,
+    name: &str,
+    exposes: &IndexMap<String, ContainerChunkGroup>,
+    shared: &IndexMap<String, SharedChunkGroup>,
+) -> Result<()> {
+    writeln!(
+        code,
+        "const __turbopack_container__ = createFederationContainer({}, {{",
+        StringifyJs(name)
+    )?;
+    for (name, chunk_group) in exposes {
+        writeln!(
+            code,
+            "{}: {},",
+            StringifyJs(name),
+            chunk_group_object(chunk_group, "")
+        )?;
+    }
+    writeln!(code, "}}, {{")?;
+    for (request, shared) in shared {
+        let config = format!(
+            "version: {}, requiredVersion: {}, singleton: {}, ",
+            StringifyJs(&shared.version),
+            StringifyJs(&shared.required_version),
+            shared.singleton,
+        );
+        writeln!(
+            code,
+            "{}: {},",
+            StringifyJs(request),
+            chunk_group_object(&shared.chunk_group, &config)
+        )?;
+    }
+    writeln!(code, "}});")?;
+    writeln!(code, "export const init = __turbopack_container__.init;")?;
+    write!(code, "export const get = __turbopack_container__.get;")?;
+    Ok(())
+}
+
+/// Formats the object literal of a chunk group, which imports its chunks on
+/// demand. `properties` are added before the properties of the chunk group.
+fn chunk_group_object(chunk_group: &ContainerChunkGroup, properties: &str) -> String {
+    let chunks = chunk_group
+        .chunks
+        .iter()
+        .map(|chunk| format!("() => import({})", StringifyJs(chunk)))
+        .collect::<Vec<_>>();
+    format!(
+        "{{ {properties}id: {}, chunks: [{}] }}",
+        StringifyJs(&chunk_group.id),
+        chunks.join(", ")
+    )
+}
+
+fn container_manifest(
+    name: &str,
+    exposes: &IndexMap<String, ContainerChunkGroup>,
+    shared: &IndexMap<String, SharedChunkGroup>,
+) -> serde_json::Value {
+    let exposes = exposes
+        .iter()
+        .map(|(name, chunk_group)| {
+            (
+                name.clone(),
+                serde_json::json!({ "chunks": chunk_group.chunks }),
+            )
+        })
+        .collect::<serde_json::Map<_, _>>();
+    let shared = shared
+        .iter()
+        .map(|(request, shared)| {
+            (
+                request.clone(),
+                serde_json::json!({
+                    "version": shared.version,
+                    "requiredVersion": shared.required_version,
+                    "singleton": shared.singleton,
+                    "chunks": shared.chunk_group.chunks,
+                }),
+            )
+        })
+        .collect::<serde_json::Map<_, _>>();
+    serde_json::json!({
+        "name": name,
+        "exposes": exposes,
+        "shared": shared,
+    })
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for EcmascriptBuildFederationContainer {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell(
+            "Ecmascript Build Federation Container".to_string(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for EcmascriptBuildFederationContainer {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        let this = self.await?;
+        let mut assets = self.chunk_groups().await?.assets.clone();
+        assets.push(self.manifest());
+        bundle_references(Vc::upcast(self), this.chunking_context, assets).await
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for EcmascriptBuildFederationContainer {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        bundle_content(self.code()).await
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for EcmascriptBuildFederationContainer {
+    #[turbo_tasks::function]
+    fn generate_source_map(self: Vc<Self>) -> Vc<OptionSourceMap> {
+        self.code().generate_source_map()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::{indexmap, IndexMap};
+    use indoc::indoc;
+    use serde_json::json;
+    use turbopack_core::{chunk::ModuleId, code_builder::CodeBuilder};
+
+    use super::{container_manifest, write_container, ContainerChunkGroup, SharedChunkGroup};
+
+    fn chunk_groups() -> (
+        IndexMap<String, ContainerChunkGroup>,
+        IndexMap<String, SharedChunkGroup>,
+    ) {
+        let exposes = indexmap! {
+            "./Button".to_string() => ContainerChunkGroup {
+                id: ModuleId::String("button".to_string()),
+                chunks: vec!["./chunks/button.mjs".to_string(), "./chunks/utils.mjs".to_string()],
+            },
+            "./Header".to_string() => ContainerChunkGroup {
+                id: ModuleId::Number(1),
+                chunks: vec!["./chunks/header.mjs".to_string(), "./chunks/utils.mjs".to_string()],
+            },
+        };
+        let shared = indexmap! {
+            "react".to_string() => SharedChunkGroup {
+                version: "18.2.0".to_string(),
+                required_version: "^18.0.0".to_string(),
+                singleton: true,
+                chunk_group: ContainerChunkGroup {
+                    id: ModuleId::String("react".to_string()),
+                    chunks: vec!["./chunks/react.mjs".to_string()],
+                },
+            },
+        };
+        (exposes, shared)
+    }
+
+    #[test]
+    fn creates_the_container_with_the_chunk_groups() {
+        let (exposes, shared) = chunk_groups();
+        let mut code = CodeBuilder::default();
+        write_container(&mut code, "app2", &exposes, &shared).unwrap();
+        assert_eq!(
+            code.build().source_code().to_str().unwrap(),
+            indoc! {r#"
+                const __turbopack_container__ = createFederationContainer("app2", {
+                "./Button": { id: "button", chunks: [() => import("./chunks/button.mjs"), () => import("./chunks/utils.mjs")] },
+                "./Header": { id: 1, chunks: [() => import("./chunks/header.mjs"), () => import("./chunks/utils.mjs")] },
+                }, {
+                "react": { version: "18.2.0", requiredVersion: "^18.0.0", singleton: true, id: "react", chunks: [() => import("./chunks/react.mjs")] },
+                });
+                export const init = __turbopack_container__.init;
+                export const get = __turbopack_container__.get;"#}
+        );
+    }
+
+    #[test]
+    fn lists_the_chunks_in_the_manifest() {
+        let (exposes, shared) = chunk_groups();
+        assert_eq!(
+            container_manifest("app2", &exposes, &shared),
+            json!({
+                "name": "app2",
+                "exposes": {
+                    "./Button": { "chunks": ["./chunks/button.mjs", "./chunks/utils.mjs"] },
+                    "./Header": { "chunks": ["./chunks/header.mjs", "./chunks/utils.mjs"] },
+                },
+                "shared": {
+                    "react": {
+                        "version": "18.2.0",
+                        "requiredVersion": "^18.0.0",
+                        "singleton": true,
+                        "chunks": ["./chunks/react.mjs"],
+                    },
+                },
+            })
+        );
+    }
+}
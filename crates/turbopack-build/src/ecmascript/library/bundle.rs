@@ -0,0 +1,352 @@
+use std::io::Write;
+
+use anyhow::{bail, Result};
+use indexmap::IndexSet;
+use indoc::writedoc;
+use turbo_tasks::{ValueToString, Vc};
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{ChunkItem, ChunkItemExt, ChunkableModule, ChunkingContext, EvaluatableAssets},
+    code_builder::{Code, CodeBuilder},
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets},
+    resolve::ExternalType,
+    source_map::{GenerateSourceMap, OptionSourceMap},
+};
+use turbopack_ecmascript::{
+    chunk::EcmascriptChunkPlaceable, references::external_module::ExternalModule,
+    utils::StringifyJs,
+};
+
+use crate::{
+    chunking_context::{LibraryFormat, LibraryOptions},
+    ecmascript::{
+        bundle::{
+            bundle_assets, bundle_content, bundle_public_path, bundle_references,
+            finalize_bundle_code, write_esm_export_bindings, write_esm_exports,
+            write_evaluated_assets, write_registered_chunks,
+        },
+        node::chunk::EcmascriptBuildNodeChunk,
+    },
+    BuildChunkingContext,
+};
+
+/// A single file which contains the runtime and all modules of a library and
+/// exposes the exports of its entry as an IIFE, UMD or ES module bundle.
+///
+/// Like the [crate::ecmascript::react_native::bundle::EcmascriptBuildReactNativeBundle],
+/// all chunks which are reachable from the entry are inlined. External
+/// modules are provided by the wrapper or imported, see [LibraryOptions].
+#[turbo_tasks::value(shared)]
+pub(crate) struct EcmascriptBuildLibraryBundle {
+    path: Vc<FileSystemPath>,
+    chunking_context: Vc<BuildChunkingContext>,
+    chunks: Vc<OutputAssets>,
+    evaluatable_assets: Vc<EvaluatableAssets>,
+    entry_module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+    options: Vc<LibraryOptions>,
+    /// The type declaration files which are emitted next to the bundle.
+    declarations: Vc<OutputAssets>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptBuildLibraryBundle {
+    /// Creates a new [`Vc<EcmascriptBuildLibraryBundle>`].
+    #[turbo_tasks::function]
+    pub fn new(
+        path: Vc<FileSystemPath>,
+        chunking_context: Vc<BuildChunkingContext>,
+        chunks: Vc<OutputAssets>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+        entry_module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+        options: Vc<LibraryOptions>,
+        declarations: Vc<OutputAssets>,
+    ) -> Vc<Self> {
+        EcmascriptBuildLibraryBundle {
+            path,
+            chunking_context,
+            chunks,
+            evaluatable_assets,
+            entry_module,
+            options,
+            declarations,
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn code(self: Vc<Self>) -> Result<Vc<Code>> {
+        let this = self.await?;
+        let options = this.options.await?;
+
+        let bundle_path = self.ident().path();
+        let bundle_public_path = bundle_public_path(this.chunking_context, bundle_path).await?;
+
+        let bundle_assets = bundle_assets(this.chunks, true).await?;
+        let esm_externals = match options.format {
+            LibraryFormat::Esm => esm_externals(&bundle_assets.chunks).await?,
+            LibraryFormat::Iife | LibraryFormat::Umd => Vec::new(),
+        };
+
+        let mut code = CodeBuilder::default();
+
+        match options.format {
+            LibraryFormat::Iife | LibraryFormat::Umd => {
+                writeln!(code, "(function (root, factory) {{")?;
+                write_library_wrapper(&mut code, &options)?;
+                writeln!(
+                    code,
+                    r#"}})(typeof self !== "undefined" ? self : this, function (LIBRARY_EXTERNALS) {{"#
+                )?;
+            }
+            LibraryFormat::Esm => {
+                for (index, request) in esm_externals.iter().enumerate() {
+                    writeln!(
+                        code,
+                        "import * as __turbopack_external_{index}__ from {};",
+                        StringifyJs(request)
+                    )?;
+                }
+                write_esm_export_bindings(&mut code, this.entry_module).await?;
+                writeln!(
+                    code,
+                    "__turbopack_bundle_exports__ = await (function (LIBRARY_EXTERNALS) {{"
+                )?;
+            }
+        }
+        writeln!(
+            code,
+            "const CHUNK_PUBLIC_PATH = {};",
+            StringifyJs(&bundle_public_path)
+        )?;
+
+        let runtime_code = turbopack_ecmascript_runtime::get_library_runtime_code(
+            this.chunking_context.environment(),
+        );
+        code.push_code(&*runtime_code.await?);
+
+        write_registered_chunks(&mut code, "registerChunk", &bundle_assets.chunks).await?;
+        write_evaluated_assets(&mut code, this.chunking_context, this.evaluatable_assets).await?;
+
+        let entry_module_id = this
+            .entry_module
+            .as_chunk_item(Vc::upcast(this.chunking_context))
+            .id()
+            .await?;
+        if let LibraryFormat::Esm = options.format {
+            writeln!(code, "onModuleInstantiated = __turbopack_update_exports__;")?;
+        }
+        writeln!(
+            code,
+            "return getOrInstantiateRuntimeModule({}, CHUNK_PUBLIC_PATH).exports;",
+            StringifyJs(&*entry_module_id),
+        )?;
+
+        match options.format {
+            LibraryFormat::Iife | LibraryFormat::Umd => {
+                write!(code, "}});")?;
+            }
+            LibraryFormat::Esm => {
+                let from_imports =
+                    externals_object(esm_externals.iter().enumerate().map(|(index, request)| {
+                        (request, format!("__turbopack_external_{index}__"))
+                    }));
+                writeln!(code, "}})({});", from_imports)?;
+                write_esm_exports(&mut code, this.entry_module).await?;
+            }
+        }
+
+        Ok(finalize_bundle_code(
+            this.chunking_context,
+            bundle_path,
+            code.build().cell(),
+        ))
+    }
+}
+
+/// Writes the body of the wrapper function, which calls `factory` with the
+/// external modules and exposes the exports it returns.
+fn write_library_wrapper(code: &mut CodeBuilder, options: &LibraryOptions) -> Result<()> {
+    let from_globals = externals_object(
+        options
+            .external_globals
+            .iter()
+            .map(|(request, global)| (request, format!("root[{}]", StringifyJs(global)))),
+    );
+    let from_globals = format!("factory({})", from_globals);
+    let expose_from_globals = match &options.global_name {
+        Some(global_name) => format!("root[{}] = {};", StringifyJs(global_name), from_globals),
+        None => format!("{};", from_globals),
+    };
+
+    match options.format {
+        LibraryFormat::Iife => {
+            writeln!(code, "{}", expose_from_globals)?;
+        }
+        LibraryFormat::Umd => {
+            let from_require = externals_object(
+                options
+                    .external_globals
+                    .keys()
+                    .map(|request| (request, format!("require({})", StringifyJs(request)))),
+            );
+            let amd_dependencies = options
+                .external_globals
+                .keys()
+                .map(|request| StringifyJs(request).to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let amd_params = (0..options.external_globals.len())
+                .map(|index| format!("e{index}"))
+                .collect::<Vec<_>>();
+            let from_amd = externals_object(
+                options
+                    .external_globals
+                    .keys()
+                    .zip(amd_params.iter().cloned()),
+            );
+
+            writedoc!(
+                code,
+                r#"
+                    if (typeof exports === "object" && typeof module === "object") {{
+                    module.exports = factory({from_require});
+                    }} else if (typeof define === "function" && define.amd) {{
+                    define([{amd_dependencies}], function ({amd_params}) {{
+                    return factory({from_amd});
+                    }});
+                    }} else {{
+                    {expose_from_globals}
+                    }}
+                "#,
+                amd_params = amd_params.join(", "),
+            )?;
+        }
+        LibraryFormat::Esm => bail!("ES module bundles have no wrapper function"),
+    }
+    Ok(())
+}
+
+/// Returns the requests of the external modules of the `chunks`, which are
+/// imported by an ES module bundle.
+async fn esm_externals(chunks: &[Vc<EcmascriptBuildNodeChunk>]) -> Result<Vec<String>> {
+    let mut externals = IndexSet::new();
+    for chunk in chunks {
+        for &(chunk_item, _) in &chunk.chunk_content().await?.chunk_items {
+            let Some(external) =
+                Vc::try_resolve_downcast_type::<ExternalModule>(chunk_item.module()).await?
+            else {
+                continue;
+            };
+            // Globals are read from `globalThis` instead.
+            if *external.ty().await? != ExternalType::Global {
+                externals.insert(external.name().await?.clone_value());
+            }
+        }
+    }
+    Ok(externals.into_iter().collect())
+}
+
+/// Formats an object literal which maps the requests of external modules to
+/// the expressions which provide them.
+fn externals_object<'a>(externals: impl Iterator<Item = (&'a String, String)>) -> String {
+    let properties = externals
+        .map(|(request, expr)| format!("{}: {}", StringifyJs(request), expr))
+        .collect::<Vec<_>>();
+    if properties.is_empty() {
+        "{}".to_string()
+    } else {
+        format!("{{ {} }}", properties.join(", "))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for EcmascriptBuildLibraryBundle {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell("Ecmascript Build Library Bundle".to_string()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for EcmascriptBuildLibraryBundle {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        let this = self.await?;
+        let mut assets = bundle_assets(this.chunks, true).await?.assets.clone();
+        assets.extend(this.declarations.await?.iter().copied());
+        bundle_references(Vc::upcast(self), this.chunking_context, assets).await
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for EcmascriptBuildLibraryBundle {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        bundle_content(self.code()).await
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for EcmascriptBuildLibraryBundle {
+    #[turbo_tasks::function]
+    fn generate_source_map(self: Vc<Self>) -> Vc<OptionSourceMap> {
+        self.code().generate_source_map()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::indexmap;
+    use indoc::indoc;
+    use turbopack_core::code_builder::CodeBuilder;
+
+    use super::write_library_wrapper;
+    use crate::chunking_context::{LibraryFormat, LibraryOptions};
+
+    fn wrapper(format: LibraryFormat) -> String {
+        let options = LibraryOptions {
+            format,
+            global_name: Some("Widget".to_string()),
+            external_globals: indexmap! {
+                "react".to_string() => "React".to_string(),
+            },
+            ..Default::default()
+        };
+        let mut code = CodeBuilder::default();
+        write_library_wrapper(&mut code, &options).unwrap();
+        code.build().source_code().to_str().unwrap().into_owned()
+    }
+
+    #[test]
+    fn iife_reads_externals_from_globals() {
+        assert_eq!(
+            wrapper(LibraryFormat::Iife),
+            "root[\"Widget\"] = factory({ \"react\": root[\"React\"] });\n"
+        );
+    }
+
+    #[test]
+    fn umd_requires_externals() {
+        assert_eq!(
+            wrapper(LibraryFormat::Umd),
+            indoc! {r#"
+                if (typeof exports === "object" && typeof module === "object") {
+                module.exports = factory({ "react": require("react") });
+                } else if (typeof define === "function" && define.amd) {
+                define(["react"], function (e0) {
+                return factory({ "react": e0 });
+                });
+                } else {
+                root["Widget"] = factory({ "react": root["React"] });
+                }
+            "#}
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{FileContent, FileSystemEntryType, FileSystemPath};
+use turbopack_core::{
+    asset::AssetContent,
+    output::{OutputAsset, OutputAssets},
+    virtual_output::VirtualOutputAsset,
+};
+
+/// Emits the type declaration file of the entry of a library bundle at
+/// `output_path`, along with the declaration files which it imports with
+/// relative specifiers, e.g. `./types`, so that the types can be resolved
+/// next to the bundle.
+///
+/// The imported files are emitted at the same path relative to the bundle as
+/// they have relative to the declaration of the entry. Files outside of the
+/// directory of the declaration of the entry aren't emitted.
+#[turbo_tasks::function]
+pub(crate) async fn declaration_assets(
+    declaration: Vc<FileSystemPath>,
+    output_path: Vc<FileSystemPath>,
+) -> Result<Vc<OutputAssets>> {
+    let root = declaration.parent().await?;
+    let output_dir = output_path.parent();
+
+    let mut assets: Vec<Vc<Box<dyn OutputAsset>>> = vec![Vc::upcast(VirtualOutputAsset::new(
+        output_path,
+        AssetContent::file(declaration.read()),
+    ))];
+    let mut visited = HashSet::from([declaration.await?.path.clone()]);
+    let mut queue = vec![declaration];
+    while let Some(file) = queue.pop() {
+        let FileContent::Content(content) = &*file.read().await? else {
+            continue;
+        };
+        let Ok(code) = content.content().to_str() else {
+            continue;
+        };
+        let dir = file.parent();
+        for specifier in relative_specifiers(&code) {
+            for candidate in declaration_candidates(specifier) {
+                let Some(path) = *dir.try_join(candidate).await? else {
+                    continue;
+                };
+                if *path.get_type().await? != FileSystemEntryType::File {
+                    continue;
+                }
+                let path_value = path.await?;
+                if let Some(relative) = root.get_path_to(&path_value) {
+                    if visited.insert(path_value.path.clone()) {
+                        assets.push(Vc::upcast(VirtualOutputAsset::new(
+                            output_dir.join(relative.to_string()),
+                            AssetContent::file(path.read()),
+                        )));
+                        queue.push(path);
+                    }
+                }
+                break;
+            }
+        }
+    }
+
+    Ok(Vc::cell(assets))
+}
+
+/// Returns the relative specifiers of the imports, exports and references of
+/// a type declaration file, e.g. `./types` of `import { A } from "./types"`.
+fn relative_specifiers(code: &str) -> Vec<&str> {
+    let mut specifiers = Vec::new();
+    let mut index = 0;
+    while index < code.len() {
+        let rest = &code[index..];
+        if rest.starts_with("//") {
+            let line = &rest[..rest.find('\n').unwrap_or(rest.len())];
+            // e.g. `/// <reference path="./globals.d.ts" />`
+            if line.starts_with("///") {
+                if let Some(path) = line.find("path=") {
+                    specifiers.extend(
+                        string_literal(&line[path + "path=".len()..])
+                            .filter(|specifier| is_relative(specifier)),
+                    );
+                }
+            }
+            index += line.len();
+        } else if rest.starts_with("/*") {
+            index += rest.find("*/").map_or(rest.len(), |end| end + "*/".len());
+        } else if let Some(literal) = string_literal(rest) {
+            let before = code[..index].trim_end();
+            if ["from", "import", "import(", "require("]
+                .iter()
+                .any(|keyword| before.ends_with(keyword))
+                && is_relative(literal)
+            {
+                specifiers.push(literal);
+            }
+            index += literal.len() + 2;
+        } else {
+            index += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    specifiers
+}
+
+/// Returns the content of the string literal at the start of `code`.
+fn string_literal(code: &str) -> Option<&str> {
+    let quote = code
+        .chars()
+        .next()
+        .filter(|c| matches!(c, '"' | '\'' | '`'))?;
+    let literal = &code[1..];
+    literal.find(quote).map(|end| &literal[..end])
+}
+
+fn is_relative(specifier: &str) -> bool {
+    specifier.starts_with("./") || specifier.starts_with("../")
+}
+
+/// Returns the declaration files which a relative specifier may refer to, in
+/// the order in which TypeScript looks them up.
+fn declaration_candidates(specifier: &str) -> Vec<String> {
+    if [".d.ts", ".d.mts", ".d.cts"]
+        .iter()
+        .any(|extension| specifier.ends_with(extension))
+    {
+        return vec![specifier.to_string()];
+    }
+    for (extension, declaration_extension) in
+        [(".js", ".d.ts"), (".mjs", ".d.mts"), (".cjs", ".d.cts")]
+    {
+        if let Some(stem) = specifier.strip_suffix(extension) {
+            return vec![format!("{stem}{declaration_extension}")];
+        }
+    }
+    vec![
+        format!("{specifier}.d.ts"),
+        format!("{specifier}/index.d.ts"),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{declaration_candidates, relative_specifiers};
+
+    #[test]
+    fn finds_relative_specifiers() {
+        let code = r#"
+/// <reference path="./globals.d.ts" />
+// Doesn't import "./commented"
+import type { A } from "./a";
+import { B } from 'b';
+export * from "../c.js";
+export type D = import("./d").D;
+declare const e: "./not-a-specifier";
+"#;
+        assert_eq!(
+            relative_specifiers(code),
+            vec!["./globals.d.ts", "./a", "../c.js", "./d"]
+        );
+    }
+
+    #[test]
+    fn maps_specifiers_to_declaration_files() {
+        assert_eq!(declaration_candidates("./a.d.ts"), vec!["./a.d.ts"]);
+        assert_eq!(declaration_candidates("./a.mjs"), vec!["./a.d.mts"]);
+        assert_eq!(
+            declaration_candidates("./a"),
+            vec!["./a.d.ts", "./a/index.d.ts"]
+        );
+    }
+}
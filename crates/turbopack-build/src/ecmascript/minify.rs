@@ -1,4 +1,4 @@
-use std::{io::Write, sync::Arc};
+use std::sync::Arc;
 
 use anyhow::{bail, Context, Result};
 use swc_core::{
@@ -39,9 +39,6 @@ pub async fn minify(path: Vc<FileSystemPath>, code: Vc<Code>) -> Result<Vc<Code>
 
     let mut builder = CodeBuilder::default();
     builder.push_source(minified_code.await?.source_code(), merged);
-    let path = &*path.await?;
-    let filename = path.file_name();
-    write!(builder, "\n\n//# sourceMappingURL={}.map", filename)?;
     Ok(builder.build().cell())
 }
 
@@ -1,2 +1,9 @@
+pub(crate) mod banner;
+pub(crate) mod bundle;
+pub(crate) mod federation;
+pub(crate) mod library;
 pub(crate) mod minify;
 pub(crate) mod node;
+pub(crate) mod react_native;
+pub(crate) mod service_worker;
+pub(crate) mod source_maps;
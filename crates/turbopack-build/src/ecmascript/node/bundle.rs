@@ -0,0 +1,204 @@
+use std::io::Write;
+
+use anyhow::Result;
+use indoc::writedoc;
+use turbo_tasks::{ValueToString, Vc};
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{ChunkItemExt, ChunkableModule, ChunkingContext, EvaluatableAssets},
+    code_builder::{Code, CodeBuilder},
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets},
+    source_map::{GenerateSourceMap, OptionSourceMap},
+};
+use turbopack_ecmascript::{chunk::EcmascriptChunkPlaceable, utils::StringifyJs};
+use turbopack_ecmascript_runtime::RuntimeType;
+
+use crate::{
+    chunking_context::{NodeBundleFormat, NodeBundleOptions},
+    ecmascript::bundle::{
+        bundle_assets, bundle_content, bundle_public_path, bundle_references, finalize_bundle_code,
+        write_esm_export_bindings, write_esm_exports, write_evaluated_assets,
+        write_registered_chunks,
+    },
+    BuildChunkingContext, NodeChunkLoading,
+};
+
+/// A single CommonJS or ES module file for Node.js which contains the runtime
+/// and the modules of the entry chunk group.
+///
+/// Async chunks are inlined too, or emitted next to the bundle and loaded by
+/// the runtime relative to the bundle, see [NodeBundleOptions]. ES module
+/// bundles define `require`, `__filename` and `__dirname`, which the runtime
+/// and CommonJS modules use.
+#[turbo_tasks::value(shared)]
+pub(crate) struct EcmascriptBuildNodeBundle {
+    path: Vc<FileSystemPath>,
+    chunking_context: Vc<BuildChunkingContext>,
+    chunks: Vc<OutputAssets>,
+    evaluatable_assets: Vc<EvaluatableAssets>,
+    entry_module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+    options: Vc<NodeBundleOptions>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptBuildNodeBundle {
+    /// Creates a new [`Vc<EcmascriptBuildNodeBundle>`].
+    #[turbo_tasks::function]
+    pub fn new(
+        path: Vc<FileSystemPath>,
+        chunking_context: Vc<BuildChunkingContext>,
+        chunks: Vc<OutputAssets>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+        entry_module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+        options: Vc<NodeBundleOptions>,
+    ) -> Vc<Self> {
+        EcmascriptBuildNodeBundle {
+            path,
+            chunking_context,
+            chunks,
+            evaluatable_assets,
+            entry_module,
+            options,
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn code(self: Vc<Self>) -> Result<Vc<Code>> {
+        let this = self.await?;
+        let options = this.options.await?;
+        let chunking_context = this.chunking_context.await?;
+
+        let bundle_path = self.ident().path();
+        let bundle_public_path = bundle_public_path(this.chunking_context, bundle_path).await?;
+
+        let chunk_loading = match chunking_context.node_chunk_loading() {
+            NodeChunkLoading::Require => "require",
+            NodeChunkLoading::Import => "import",
+        };
+
+        let mut code = CodeBuilder::default();
+
+        // The runtime is a CommonJS module, so it's evaluated with its own
+        // `module` object to not overwrite the exports of the bundle.
+        match options.format {
+            NodeBundleFormat::CommonJs => {
+                writeln!(code, "module.exports = (function (module) {{")?;
+            }
+            NodeBundleFormat::Esm => {
+                writedoc!(
+                    code,
+                    r#"
+                        import {{ createRequire as __turbopack_create_require__ }} from "node:module";
+                        import {{ fileURLToPath as __turbopack_file_url_to_path__ }} from "node:url";
+                        import {{ dirname as __turbopack_dirname__ }} from "node:path";
+                        const require = __turbopack_create_require__(import.meta.url);
+                        const __filename = __turbopack_file_url_to_path__(import.meta.url);
+                        const __dirname = __turbopack_dirname__(__filename);
+                    "#
+                )?;
+                write_esm_export_bindings(&mut code, this.entry_module).await?;
+                writeln!(
+                    code,
+                    "__turbopack_bundle_exports__ = await (function (module) {{"
+                )?;
+            }
+        }
+
+        writedoc!(
+            code,
+            r#"
+                const RUNTIME_PUBLIC_PATH = {};
+                const RUNTIME_CHUNK_LOADING = {};
+                const CHUNK_PUBLIC_PATH = RUNTIME_PUBLIC_PATH;
+            "#,
+            StringifyJs(&bundle_public_path),
+            StringifyJs(chunk_loading),
+        )?;
+
+        match chunking_context.runtime_type() {
+            RuntimeType::Default => {
+                let runtime_code = turbopack_ecmascript_runtime::get_build_runtime_code(
+                    this.chunking_context.environment(),
+                );
+                code.push_code(&*runtime_code.await?);
+            }
+            #[cfg(feature = "test")]
+            RuntimeType::Dummy => {
+                let runtime_code = turbopack_ecmascript_runtime::get_dummy_runtime_code();
+                code.push_code(&runtime_code);
+            }
+        }
+
+        let bundle_assets = bundle_assets(this.chunks, options.inline_async_chunks).await?;
+        write_registered_chunks(&mut code, "registerChunkModules", &bundle_assets.chunks).await?;
+        write_evaluated_assets(&mut code, this.chunking_context, this.evaluatable_assets).await?;
+
+        let entry_module_id = this
+            .entry_module
+            .as_chunk_item(Vc::upcast(this.chunking_context))
+            .id()
+            .await?;
+        writeln!(
+            code,
+            "return getOrInstantiateRuntimeModule({}, CHUNK_PUBLIC_PATH).exports;",
+            StringifyJs(&*entry_module_id),
+        )?;
+        writeln!(code, "}})({{ exports: {{}} }});")?;
+
+        if let NodeBundleFormat::Esm = options.format {
+            write_esm_exports(&mut code, this.entry_module).await?;
+        }
+
+        Ok(finalize_bundle_code(
+            this.chunking_context,
+            bundle_path,
+            code.build().cell(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for EcmascriptBuildNodeBundle {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell("Ecmascript Build Node Bundle".to_string()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for EcmascriptBuildNodeBundle {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        let this = self.await?;
+        let inline_async_chunks = this.options.await?.inline_async_chunks;
+        let assets = bundle_assets(this.chunks, inline_async_chunks)
+            .await?
+            .assets
+            .clone();
+        bundle_references(Vc::upcast(self), this.chunking_context, assets).await
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for EcmascriptBuildNodeBundle {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        bundle_content(self.code()).await
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for EcmascriptBuildNodeBundle {
+    #[turbo_tasks::function]
+    fn generate_source_map(self: Vc<Self>) -> Vc<OptionSourceMap> {
+        self.code().generate_source_map()
+    }
+}
@@ -9,7 +9,10 @@ use turbopack_core::{
     output::{OutputAsset, OutputAssets},
     source_map::{GenerateSourceMap, OptionSourceMap, SourceMapAsset},
 };
-use turbopack_ecmascript::chunk::EcmascriptChunk;
+use turbopack_ecmascript::{
+    chunk::{EcmascriptChunk, EcmascriptChunkContent, EcmascriptOutputChunk},
+    references::InlinedValues,
+};
 
 use super::content::EcmascriptBuildNodeChunkContent;
 use crate::BuildChunkingContext;
@@ -58,6 +61,19 @@ impl EcmascriptBuildNodeChunk {
             this.chunk.chunk_content(),
         ))
     }
+
+    #[turbo_tasks::function]
+    pub(crate) fn chunk_content(&self) -> Vc<EcmascriptChunkContent> {
+        self.chunk.chunk_content()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptOutputChunk for EcmascriptBuildNodeChunk {
+    #[turbo_tasks::function]
+    fn inlined_values(&self) -> Vc<InlinedValues> {
+        self.chunk.inlined_values()
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -17,7 +17,13 @@ use turbopack_ecmascript::{
 };
 
 use super::chunk::EcmascriptBuildNodeChunk;
-use crate::{chunking_context::MinifyType, ecmascript::minify::minify, BuildChunkingContext};
+use crate::{
+    chunking_context::MinifyType,
+    ecmascript::{
+        banner::apply_chunk_banners, minify::minify, source_maps::apply_source_maps_type,
+    },
+    BuildChunkingContext,
+};
 
 #[turbo_tasks::value]
 pub(super) struct EcmascriptBuildNodeChunkContent {
@@ -49,7 +55,6 @@ impl EcmascriptBuildNodeChunkContent {
     async fn code(self: Vc<Self>) -> Result<Vc<Code>> {
         let this = self.await?;
         let chunk_path_vc = this.chunk.ident().path();
-        let chunk_path = chunk_path_vc.await?;
 
         let mut code = CodeBuilder::default();
 
@@ -81,20 +86,21 @@ impl EcmascriptBuildNodeChunkContent {
 
         write!(code, "\n}};")?;
 
-        if code.has_source_map() {
-            let filename = chunk_path.file_name();
-            write!(code, "\n\n//# sourceMappingURL={}.map", filename)?;
-        }
-
-        let code = code.build().cell();
+        let mut code = code.build().cell();
         if matches!(
             this.chunking_context.await?.minify_type(),
             MinifyType::Minify
         ) {
-            return Ok(minify(chunk_path_vc, code));
+            code = minify(chunk_path_vc, code);
         }
 
-        Ok(code)
+        code = apply_chunk_banners(this.chunking_context, chunk_path_vc, code);
+
+        Ok(apply_source_maps_type(
+            this.chunking_context,
+            chunk_path_vc,
+            code,
+        ))
     }
 
     #[turbo_tasks::function]
@@ -14,8 +14,14 @@ use turbopack_core::{
 };
 use turbopack_ecmascript::{chunk::EcmascriptChunkPlaceable, utils::StringifyJs};
 
-use super::runtime::EcmascriptBuildNodeRuntimeChunk;
-use crate::BuildChunkingContext;
+use super::runtime::{write_runtime, EcmascriptBuildNodeRuntimeChunk};
+use crate::{
+    ecmascript::{
+        banner::apply_chunk_banners, node::manifest::EcmascriptBuildNodeChunkManifest,
+        source_maps::apply_source_maps_type,
+    },
+    BuildChunkingContext,
+};
 
 /// An Ecmascript chunk that loads a list of parallel chunks, then instantiates
 /// runtime entries.
@@ -55,18 +61,20 @@ impl EcmascriptBuildNodeEntryChunk {
 
         let output_root = this.chunking_context.output_root().await?;
         let chunk_path = self.ident().path().await?;
-        let chunk_directory = self.ident().path().parent().await?;
-        let runtime_path = self.runtime_chunk().ident().path().await?;
-        let runtime_relative_path =
-            if let Some(path) = chunk_directory.get_relative_path_to(&runtime_path) {
-                path
-            } else {
+        let runtime_relative_path = if let Some(runtime_chunk) = *self.runtime_chunk().await? {
+            let chunk_directory = self.ident().path().parent().await?;
+            let runtime_path = runtime_chunk.ident().path().await?;
+            let Some(path) = chunk_directory.get_relative_path_to(&runtime_path) else {
                 bail!(
                     "cannot find a relative path from the chunk ({}) to the runtime chunk ({})",
                     chunk_path.to_string(),
                     runtime_path.to_string(),
                 );
             };
+            Some(path)
+        } else {
+            None
+        };
         let chunk_public_path = if let Some(path) = output_root.get_path_to(&chunk_path) {
             path
         } else {
@@ -79,15 +87,28 @@ impl EcmascriptBuildNodeEntryChunk {
 
         let mut code = CodeBuilder::default();
 
-        writedoc!(
-            code,
-            r#"
-                const CHUNK_PUBLIC_PATH = {};
-                const runtime = require({});
-            "#,
-            StringifyJs(chunk_public_path),
-            StringifyJs(&*runtime_relative_path)
+        write_runtime_start(
+            &mut code,
+            chunk_public_path,
+            runtime_relative_path.as_deref(),
         )?;
+        if runtime_relative_path.is_none() {
+            write_runtime(this.chunking_context, &mut code).await?;
+            write_inline_runtime_end(&mut code)?;
+        }
+
+        if let Some(chunk_manifest) = *self.chunk_manifest().await? {
+            let manifest_path = chunk_manifest.ident().path().await?;
+            if let Some(manifest_public_path) = output_root.get_path_to(&manifest_path) {
+                writedoc!(
+                    code,
+                    r#"
+                        runtime.loadChunkManifest({});
+                    "#,
+                    StringifyJs(manifest_public_path)
+                )?;
+            }
+        }
 
         let other_chunks = this.other_chunks.await?;
         for other_chunk in &*other_chunks {
@@ -140,16 +161,92 @@ impl EcmascriptBuildNodeEntryChunk {
             StringifyJs(&*runtime_module_id),
         )?;
 
-        Ok(Code::cell(code.build()))
+        let code = apply_chunk_banners(this.chunking_context, this.path, Code::cell(code.build()));
+
+        Ok(apply_source_maps_type(
+            this.chunking_context,
+            this.path,
+            code,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    async fn runtime_chunk(self: Vc<Self>) -> Result<Vc<OptionRuntimeChunk>> {
+        let this = self.await?;
+        if !this.chunking_context.await?.runtime_chunk() {
+            return Ok(Vc::cell(None));
+        }
+        Ok(Vc::cell(Some(EcmascriptBuildNodeRuntimeChunk::new(
+            this.chunking_context,
+        ))))
     }
 
     #[turbo_tasks::function]
-    async fn runtime_chunk(self: Vc<Self>) -> Result<Vc<EcmascriptBuildNodeRuntimeChunk>> {
+    async fn chunk_manifest(self: Vc<Self>) -> Result<Vc<OptionChunkManifest>> {
         let this = self.await?;
-        Ok(EcmascriptBuildNodeRuntimeChunk::new(this.chunking_context))
+        if !this.chunking_context.await?.node_chunk_manifest() {
+            return Ok(Vc::cell(None));
+        }
+        Ok(Vc::cell(Some(EcmascriptBuildNodeChunkManifest::new(
+            this.path,
+            this.chunking_context,
+            this.other_chunks,
+        ))))
     }
 }
 
+#[turbo_tasks::value(transparent)]
+struct OptionChunkManifest(Option<Vc<EcmascriptBuildNodeChunkManifest>>);
+
+#[turbo_tasks::value(transparent)]
+struct OptionRuntimeChunk(Option<Vc<EcmascriptBuildNodeRuntimeChunk>>);
+
+/// Writes the start of an entry chunk, which requires the runtime chunk at
+/// `runtime_relative_path`. Without a runtime chunk, the runtime code follows,
+/// and is evaluated with its own `module` object until
+/// [write_inline_runtime_end].
+fn write_runtime_start(
+    code: &mut impl Write,
+    chunk_public_path: &str,
+    runtime_relative_path: Option<&str>,
+) -> Result<()> {
+    writedoc!(
+        code,
+        r#"
+            const CHUNK_PUBLIC_PATH = {};
+        "#,
+        StringifyJs(chunk_public_path),
+    )?;
+    match runtime_relative_path {
+        Some(runtime_relative_path) => writedoc!(
+            code,
+            r#"
+                const runtime = require({});
+            "#,
+            StringifyJs(runtime_relative_path)
+        )?,
+        None => writedoc!(
+            code,
+            r#"
+                const runtime = (function (module) {{
+                const RUNTIME_PUBLIC_PATH = CHUNK_PUBLIC_PATH;
+            "#
+        )?,
+    }
+    Ok(())
+}
+
+fn write_inline_runtime_end(code: &mut impl Write) -> Result<()> {
+    writedoc!(
+        code,
+        r#"
+            return module.exports;
+            }})({{ exports: {{}} }});
+        "#
+    )?;
+    Ok(())
+}
+
 #[turbo_tasks::value_impl]
 impl ValueToString for EcmascriptBuildNodeEntryChunk {
     #[turbo_tasks::function]
@@ -178,7 +275,11 @@ impl OutputAsset for EcmascriptBuildNodeEntryChunk {
     #[turbo_tasks::function]
     async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
         let this = self.await?;
-        let mut references = vec![Vc::upcast(self.runtime_chunk())];
+        let mut references = vec![];
+
+        if let Some(runtime_chunk) = *self.runtime_chunk().await? {
+            references.push(Vc::upcast(runtime_chunk));
+        }
 
         if *this
             .chunking_context
@@ -188,6 +289,10 @@ impl OutputAsset for EcmascriptBuildNodeEntryChunk {
             references.push(Vc::upcast(SourceMapAsset::new(Vc::upcast(self))))
         }
 
+        if let Some(chunk_manifest) = *self.chunk_manifest().await? {
+            references.push(Vc::upcast(chunk_manifest));
+        }
+
         let other_chunks = this.other_chunks.await?;
         for &other_chunk in &*other_chunks {
             references.push(Vc::upcast(other_chunk));
@@ -215,3 +320,47 @@ impl GenerateSourceMap for EcmascriptBuildNodeEntryChunk {
         self.code().generate_source_map()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::{write_inline_runtime_end, write_runtime_start};
+
+    #[test]
+    fn requires_the_runtime_chunk() {
+        let mut code = Vec::new();
+        write_runtime_start(
+            &mut code,
+            "index.entry.js",
+            Some("./[turbopack]_runtime.js"),
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(code).unwrap(),
+            indoc! {r#"
+                const CHUNK_PUBLIC_PATH = "index.entry.js";
+                const runtime = require("./[turbopack]_runtime.js");
+            "#}
+        );
+    }
+
+    #[test]
+    fn evaluates_the_inlined_runtime_with_its_own_module() {
+        let mut code = Vec::new();
+        write_runtime_start(&mut code, "index.entry.js", None).unwrap();
+        code.extend_from_slice(b"module.exports = { loadChunk };\n");
+        write_inline_runtime_end(&mut code).unwrap();
+        assert_eq!(
+            String::from_utf8(code).unwrap(),
+            indoc! {r#"
+                const CHUNK_PUBLIC_PATH = "index.entry.js";
+                const runtime = (function (module) {
+                const RUNTIME_PUBLIC_PATH = CHUNK_PUBLIC_PATH;
+                module.exports = { loadChunk };
+                return module.exports;
+                })({ exports: {} });
+            "#}
+        );
+    }
+}
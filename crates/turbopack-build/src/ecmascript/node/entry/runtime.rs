@@ -15,9 +15,10 @@ use turbopack_core::{
 use turbopack_ecmascript::utils::StringifyJs;
 use turbopack_ecmascript_runtime::RuntimeType;
 
-use crate::BuildChunkingContext;
+use crate::{BuildChunkingContext, NodeChunkLoading};
 
-/// An Ecmascript chunk that contains the Node.js runtime code.
+/// An Ecmascript chunk that contains the Node.js runtime code. It's shared by
+/// all entry chunks, see [crate::BuildChunkingContextBuilder::runtime_chunk].
 #[turbo_tasks::value(shared)]
 pub(crate) struct EcmascriptBuildNodeRuntimeChunk {
     chunking_context: Vc<BuildChunkingContext>,
@@ -54,27 +55,50 @@ impl EcmascriptBuildNodeRuntimeChunk {
             r#"
                 const RUNTIME_PUBLIC_PATH = {};
             "#,
-            StringifyJs(runtime_public_path)
+            StringifyJs(runtime_public_path),
         )?;
-
-        match this.chunking_context.await?.runtime_type() {
-            RuntimeType::Default => {
-                let runtime_code = turbopack_ecmascript_runtime::get_build_runtime_code(
-                    this.chunking_context.environment(),
-                );
-                code.push_code(&*runtime_code.await?);
-            }
-            #[cfg(feature = "test")]
-            RuntimeType::Dummy => {
-                let runtime_code = turbopack_ecmascript_runtime::get_dummy_runtime_code();
-                code.push_code(&runtime_code);
-            }
-        }
+        write_runtime(this.chunking_context, &mut code).await?;
 
         Ok(Code::cell(code.build()))
     }
 }
 
+/// Writes the Node.js runtime code of the `chunking_context`, which expects
+/// `RUNTIME_PUBLIC_PATH` to be the path of the file it's in.
+pub(crate) async fn write_runtime(
+    chunking_context: Vc<BuildChunkingContext>,
+    code: &mut CodeBuilder,
+) -> Result<()> {
+    let chunking_context_ref = chunking_context.await?;
+    let chunk_loading = match chunking_context_ref.node_chunk_loading() {
+        NodeChunkLoading::Require => "require",
+        NodeChunkLoading::Import => "import",
+    };
+
+    writedoc!(
+        code,
+        r#"
+            const RUNTIME_CHUNK_LOADING = {};
+        "#,
+        StringifyJs(chunk_loading)
+    )?;
+
+    match chunking_context_ref.runtime_type() {
+        RuntimeType::Default => {
+            let runtime_code = turbopack_ecmascript_runtime::get_build_runtime_code(
+                chunking_context.environment(),
+            );
+            code.push_code(&*runtime_code.await?);
+        }
+        #[cfg(feature = "test")]
+        RuntimeType::Dummy => {
+            let runtime_code = turbopack_ecmascript_runtime::get_dummy_runtime_code();
+            code.push_code(&runtime_code);
+        }
+    }
+    Ok(())
+}
+
 #[turbo_tasks::value_impl]
 impl ValueToString for EcmascriptBuildNodeRuntimeChunk {
     #[turbo_tasks::function]
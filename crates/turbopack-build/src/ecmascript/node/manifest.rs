@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use turbo_tasks::{ValueToString, Vc};
+use turbo_tasks_fs::{File, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets},
+    reference::all_assets_from_entries,
+};
+
+use crate::BuildChunkingContext;
+
+/// A JSON manifest which maps the paths of all chunks loaded by a Node.js
+/// entry chunk to the paths of the emitted files, both relative to the output
+/// root.
+///
+/// The Node.js runtime resolves chunks through this manifest, so deployment
+/// tooling can move or rename emitted chunks by rewriting it.
+#[turbo_tasks::value(shared)]
+pub(crate) struct EcmascriptBuildNodeChunkManifest {
+    entry_path: Vc<FileSystemPath>,
+    chunking_context: Vc<BuildChunkingContext>,
+    chunks: Vc<OutputAssets>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptBuildNodeChunkManifest {
+    /// Creates a new [`Vc<EcmascriptBuildNodeChunkManifest>`].
+    #[turbo_tasks::function]
+    pub fn new(
+        entry_path: Vc<FileSystemPath>,
+        chunking_context: Vc<BuildChunkingContext>,
+        chunks: Vc<OutputAssets>,
+    ) -> Vc<Self> {
+        EcmascriptBuildNodeChunkManifest {
+            entry_path,
+            chunking_context,
+            chunks,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for EcmascriptBuildNodeChunkManifest {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell("Ecmascript Build Node Chunk Manifest".to_string()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for EcmascriptBuildNodeChunkManifest {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.entry_path.append(".chunks.json".to_string()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for EcmascriptBuildNodeChunkManifest {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<AssetContent>> {
+        let output_root = self.chunking_context.output_root().await?;
+
+        let mut manifest = BTreeMap::new();
+        for chunk in &*all_assets_from_entries(self.chunks).await? {
+            let chunk_path = chunk.ident().path().await?;
+            // The runtime only loads JS chunks, other assets are not relevant here.
+            if chunk_path.extension_ref() != Some("js") {
+                continue;
+            }
+            if let Some(public_path) = output_root.get_path_to(&chunk_path) {
+                manifest.insert(public_path.to_string(), public_path.to_string());
+            }
+        }
+
+        Ok(AssetContent::file(
+            File::from(serde_json::to_string_pretty(&manifest)?).into(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lazy_static::lazy_static;
+    use serde_json::json;
+    use turbo_tasks::{Value, Vc};
+    use turbo_tasks_fs::{File, FileJsonContent, VirtualFileSystem};
+    use turbo_tasks_testing::run;
+    use turbopack_core::{
+        asset::{Asset, AssetContent},
+        environment::{Environment, ExecutionEnvironment, NodeJsEnvironment},
+        output::{OutputAsset, OutputAssets},
+        virtual_output::VirtualOutputAsset,
+    };
+
+    use super::EcmascriptBuildNodeChunkManifest;
+    use crate::BuildChunkingContext;
+
+    lazy_static! {
+        static ref REGISTER: () = crate::register();
+    }
+
+    #[tokio::test]
+    async fn maps_the_js_chunks_in_the_output_root() {
+        run! {
+            let root = VirtualFileSystem::new().root();
+            let dist = root.join("dist".to_string());
+            let environment = Environment::new(Value::new(ExecutionEnvironment::NodeJsBuildTime(
+                NodeJsEnvironment::default().cell(),
+            )));
+            let chunking_context = BuildChunkingContext::builder(
+                root,
+                dist,
+                dist,
+                dist.join("chunks".to_string()),
+                dist.join("assets".to_string()),
+                environment,
+            )
+            .build();
+            let chunk = |path: &str| -> Vc<Box<dyn OutputAsset>> {
+                Vc::upcast(VirtualOutputAsset::new(
+                    root.join(path.to_string()),
+                    AssetContent::file(File::from("").into()),
+                ))
+            };
+            let chunks = Vc::<OutputAssets>::cell(vec![
+                chunk("dist/chunks/a.js"),
+                chunk("dist/chunks/a.css"),
+                chunk("dist/chunks/nested/b.js"),
+                chunk("other/c.js"),
+            ]);
+
+            let manifest = EcmascriptBuildNodeChunkManifest::new(
+                dist.join("index.js".to_string()),
+                chunking_context,
+                chunks,
+            );
+            assert_eq!(
+                manifest.ident().path().await?.path,
+                "dist/index.js.chunks.json"
+            );
+            let FileJsonContent::Content(json) = &*manifest.content().parse_json().await? else {
+                panic!("the manifest should be JSON");
+            };
+            assert_eq!(
+                json,
+                &json!({
+                    "chunks/a.js": "chunks/a.js",
+                    "chunks/nested/b.js": "chunks/nested/b.js",
+                })
+            );
+        }
+    }
+}
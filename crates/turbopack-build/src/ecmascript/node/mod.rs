@@ -1,3 +1,5 @@
+pub(crate) mod bundle;
 pub(crate) mod chunk;
 pub(crate) mod content;
 pub(crate) mod entry;
+pub(crate) mod manifest;
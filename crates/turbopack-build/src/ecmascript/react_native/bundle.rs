@@ -0,0 +1,150 @@
+use std::io::Write;
+
+use anyhow::Result;
+use indoc::writedoc;
+use turbo_tasks::{ValueToString, Vc};
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{ChunkItemExt, ChunkableModule, ChunkingContext, EvaluatableAssets},
+    code_builder::{Code, CodeBuilder},
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets},
+    source_map::{GenerateSourceMap, OptionSourceMap},
+};
+use turbopack_ecmascript::{chunk::EcmascriptChunkPlaceable, utils::StringifyJs};
+
+use crate::{
+    ecmascript::bundle::{
+        bundle_assets, bundle_content, bundle_public_path, bundle_references, finalize_bundle_code,
+        write_evaluated_assets, write_registered_chunks,
+    },
+    BuildChunkingContext,
+};
+
+/// A single file which contains the runtime and all modules of an app, like
+/// the bundles Metro produces for React Native.
+///
+/// The chunks of the entry chunk group and all async chunk groups which are
+/// reachable from it are inlined and register their modules before the entry
+/// is instantiated. Other output assets, e.g. images, are emitted next to the
+/// bundle.
+#[turbo_tasks::value(shared)]
+pub(crate) struct EcmascriptBuildReactNativeBundle {
+    path: Vc<FileSystemPath>,
+    chunking_context: Vc<BuildChunkingContext>,
+    chunks: Vc<OutputAssets>,
+    evaluatable_assets: Vc<EvaluatableAssets>,
+    entry_module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptBuildReactNativeBundle {
+    /// Creates a new [`Vc<EcmascriptBuildReactNativeBundle>`].
+    #[turbo_tasks::function]
+    pub fn new(
+        path: Vc<FileSystemPath>,
+        chunking_context: Vc<BuildChunkingContext>,
+        chunks: Vc<OutputAssets>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+        entry_module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+    ) -> Vc<Self> {
+        EcmascriptBuildReactNativeBundle {
+            path,
+            chunking_context,
+            chunks,
+            evaluatable_assets,
+            entry_module,
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn code(self: Vc<Self>) -> Result<Vc<Code>> {
+        let this = self.await?;
+
+        let bundle_path = self.ident().path();
+        let bundle_public_path = bundle_public_path(this.chunking_context, bundle_path).await?;
+
+        let mut code = CodeBuilder::default();
+
+        writedoc!(
+            code,
+            r#"
+                (function () {{
+                const CHUNK_PUBLIC_PATH = {};
+            "#,
+            StringifyJs(&bundle_public_path),
+        )?;
+
+        let runtime_code = turbopack_ecmascript_runtime::get_react_native_runtime_code(
+            this.chunking_context.environment(),
+        );
+        code.push_code(&*runtime_code.await?);
+
+        write_registered_chunks(
+            &mut code,
+            "registerChunk",
+            &bundle_assets(this.chunks, true).await?.chunks,
+        )
+        .await?;
+        write_evaluated_assets(&mut code, this.chunking_context, this.evaluatable_assets).await?;
+
+        let entry_module_id = this
+            .entry_module
+            .as_chunk_item(Vc::upcast(this.chunking_context))
+            .id()
+            .await?;
+        writeln!(
+            code,
+            "getOrInstantiateRuntimeModule({}, CHUNK_PUBLIC_PATH);",
+            StringifyJs(&*entry_module_id),
+        )?;
+        write!(code, "}})();")?;
+
+        Ok(finalize_bundle_code(
+            this.chunking_context,
+            bundle_path,
+            code.build().cell(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for EcmascriptBuildReactNativeBundle {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell("Ecmascript Build React Native Bundle".to_string()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for EcmascriptBuildReactNativeBundle {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        let this = self.await?;
+        let assets = bundle_assets(this.chunks, true).await?.assets.clone();
+        bundle_references(Vc::upcast(self), this.chunking_context, assets).await
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for EcmascriptBuildReactNativeBundle {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        bundle_content(self.code()).await
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for EcmascriptBuildReactNativeBundle {
+    #[turbo_tasks::function]
+    fn generate_source_map(self: Vc<Self>) -> Vc<OptionSourceMap> {
+        self.code().generate_source_map()
+    }
+}
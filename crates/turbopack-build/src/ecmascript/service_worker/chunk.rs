@@ -0,0 +1,144 @@
+use std::io::Write;
+
+use anyhow::Result;
+use indoc::writedoc;
+use turbo_tasks::{ValueToString, Vc};
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{ChunkingContext, EvaluatableAssets},
+    code_builder::{Code, CodeBuilder},
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets},
+    source_map::{GenerateSourceMap, OptionSourceMap},
+};
+use turbopack_ecmascript::utils::StringifyJs;
+
+use crate::{
+    ecmascript::bundle::{
+        bundle_assets, bundle_content, bundle_public_path, bundle_references, finalize_bundle_code,
+        write_evaluated_assets, write_registered_chunks,
+    },
+    BuildChunkingContext,
+};
+
+/// A single classic script that contains all chunks of an evaluated chunk
+/// group, preceded by the runtime, like
+/// `turbopack_dev::ecmascript::service_worker::chunk::EcmascriptDevServiceWorkerChunk`.
+///
+/// Service workers need to be registered with a stable URL and can't load
+/// additional chunks on demand, so everything they need has to be in a single
+/// file. They use the runtime of single-file bundles, which never loads chunks
+/// or touches the DOM.
+#[turbo_tasks::value(shared)]
+pub(crate) struct EcmascriptBuildServiceWorkerChunk {
+    path: Vc<FileSystemPath>,
+    chunking_context: Vc<BuildChunkingContext>,
+    chunks: Vc<OutputAssets>,
+    evaluatable_assets: Vc<EvaluatableAssets>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptBuildServiceWorkerChunk {
+    /// Creates a new [`Vc<EcmascriptBuildServiceWorkerChunk>`].
+    #[turbo_tasks::function]
+    pub fn new(
+        path: Vc<FileSystemPath>,
+        chunking_context: Vc<BuildChunkingContext>,
+        chunks: Vc<OutputAssets>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+    ) -> Vc<Self> {
+        EcmascriptBuildServiceWorkerChunk {
+            path,
+            chunking_context,
+            chunks,
+            evaluatable_assets,
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn code(self: Vc<Self>) -> Result<Vc<Code>> {
+        let this = self.await?;
+
+        let chunk_path = self.ident().path();
+        let chunk_public_path = bundle_public_path(this.chunking_context, chunk_path).await?;
+
+        let mut code = CodeBuilder::default();
+
+        writedoc!(
+            code,
+            r#"
+                (function () {{
+                const CHUNK_PUBLIC_PATH = {};
+            "#,
+            StringifyJs(&chunk_public_path),
+        )?;
+
+        let runtime_code = turbopack_ecmascript_runtime::get_react_native_runtime_code(
+            this.chunking_context.environment(),
+        );
+        code.push_code(&*runtime_code.await?);
+
+        write_registered_chunks(
+            &mut code,
+            "registerChunk",
+            &bundle_assets(this.chunks, true).await?.chunks,
+        )
+        .await?;
+        // Event handlers of service workers must be registered during the
+        // initial evaluation of the script, so the entry is instantiated
+        // synchronously.
+        write_evaluated_assets(&mut code, this.chunking_context, this.evaluatable_assets).await?;
+        write!(code, "}})();")?;
+
+        Ok(finalize_bundle_code(
+            this.chunking_context,
+            chunk_path,
+            code.build().cell(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for EcmascriptBuildServiceWorkerChunk {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell(
+            "Ecmascript Build Service Worker Chunk".to_string(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for EcmascriptBuildServiceWorkerChunk {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        let this = self.await?;
+        // The chunks themselves are inlined, but the assets they reference still
+        // need to be emitted.
+        let assets = bundle_assets(this.chunks, true).await?.assets.clone();
+        bundle_references(Vc::upcast(self), this.chunking_context, assets).await
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for EcmascriptBuildServiceWorkerChunk {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        bundle_content(self.code()).await
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for EcmascriptBuildServiceWorkerChunk {
+    #[turbo_tasks::function]
+    fn generate_source_map(self: Vc<Self>) -> Vc<OptionSourceMap> {
+        self.code().generate_source_map()
+    }
+}
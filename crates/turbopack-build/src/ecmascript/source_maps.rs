@@ -0,0 +1,74 @@
+use std::io::Write;
+
+use anyhow::Result;
+use base64::{display::Base64Display, engine::general_purpose::STANDARD};
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::{
+    code_builder::{Code, CodeBuilder},
+    source_map::GenerateSourceMap,
+};
+
+use crate::{BuildChunkingContext, SourceMapsType};
+
+/// Prepares the source map of the final `code` of the chunk at `path`
+/// according to the [SourceMapsType] and the source path options of the
+/// chunking context, and adds the `sourceMappingURL` comment if the map is
+/// referenced from the chunk.
+#[turbo_tasks::function]
+pub(crate) async fn apply_source_maps_type(
+    chunking_context: Vc<BuildChunkingContext>,
+    path: Vc<FileSystemPath>,
+    code: Vc<Code>,
+) -> Result<Vc<Code>> {
+    let chunking_context = chunking_context.await?;
+    let source_maps_type = chunking_context.source_maps_type();
+    if let SourceMapsType::None = source_maps_type {
+        return Ok(code);
+    }
+    let Some(mut map) = *code.generate_source_map().await? else {
+        return Ok(code);
+    };
+
+    let source_root = chunking_context.source_map_source_root();
+    let rewrites = chunking_context.source_map_path_rewrites();
+    let mut modified = false;
+    if let Some(sources) = chunking_context.source_map_omit_source_contents() {
+        map = map.omit_source_contents(sources);
+        modified = true;
+    }
+    if source_root.is_some() || !rewrites.await?.is_empty() {
+        map = map.rewrite_sources(source_root, rewrites);
+        modified = true;
+    }
+    if let SourceMapsType::Cheap = source_maps_type {
+        map = map.lines_only();
+        modified = true;
+    }
+    if !modified && matches!(source_maps_type, SourceMapsType::Hidden) {
+        return Ok(code);
+    }
+
+    let mut builder = CodeBuilder::default();
+    if modified {
+        builder.push_source(code.await?.source_code(), Some(Vc::upcast(map)));
+    } else {
+        builder.push_code(&*code.await?);
+    }
+    match source_maps_type {
+        SourceMapsType::Inline => {
+            let map = map.to_rope().await?.to_bytes()?;
+            write!(
+                builder,
+                "\n\n//# sourceMappingURL=data:application/json;charset=utf-8;base64,{}",
+                Base64Display::new(&map, &STANDARD)
+            )?;
+        }
+        SourceMapsType::Full | SourceMapsType::Cheap => {
+            let path = path.await?;
+            write!(builder, "\n\n//# sourceMappingURL={}.map", path.file_name())?;
+        }
+        SourceMapsType::Hidden | SourceMapsType::None => {}
+    }
+    Ok(builder.build().cell())
+}
@@ -3,10 +3,16 @@
 #![feature(arbitrary_self_types)]
 #![feature(async_fn_in_trait)]
 
+pub(crate) mod build_manifest;
 pub(crate) mod chunking_context;
 pub(crate) mod ecmascript;
 
-pub use chunking_context::{BuildChunkingContext, BuildChunkingContextBuilder, MinifyType};
+pub use build_manifest::{module_nomodule_manifest, EntryChunks};
+pub use chunking_context::{
+    federation_externals, BuildChunkingContext, BuildChunkingContextBuilder, ChunkBanner,
+    ChunkBanners, FederationContainerOptions, LibraryFormat, LibraryOptions, MinifyType,
+    NodeBundleFormat, NodeBundleOptions, NodeChunkLoading, SharedPackage, SourceMapsType,
+};
 
 pub fn register() {
     turbo_tasks::register();
@@ -0,0 +1,272 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tracing::{
+    field::{Field, Visit},
+    span, Subscriber,
+};
+use tracing_subscriber::{filter::filter_fn, registry::LookupSpan, Layer};
+
+/// The number of modules reported in [BuildTimings::hotspots].
+const HOTSPOTS: usize = 10;
+
+/// A phase of a compilation. Each phase corresponds to spans with the name
+/// returned by [BuildPhase::span_name].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BuildPhase {
+    Resolve,
+    Parse,
+    Transform,
+    Chunking,
+    Emit,
+}
+
+impl BuildPhase {
+    pub const ALL: [BuildPhase; 5] = [
+        BuildPhase::Resolve,
+        BuildPhase::Parse,
+        BuildPhase::Transform,
+        BuildPhase::Chunking,
+        BuildPhase::Emit,
+    ];
+
+    pub fn span_name(self) -> &'static str {
+        match self {
+            BuildPhase::Resolve => "resolve",
+            BuildPhase::Parse => "parse",
+            BuildPhase::Transform => "transform",
+            BuildPhase::Chunking => "chunking",
+            BuildPhase::Emit => "emit",
+        }
+    }
+
+    fn from_span_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|phase| phase.span_name() == name)
+    }
+}
+
+/// The time spent in a single module.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModuleTiming {
+    /// The module's identifier.
+    pub module: String,
+    /// The time spent parsing and transforming the module.
+    pub duration: Duration,
+}
+
+/// A summary of where the time of a compilation was spent.
+///
+/// Durations are busy times summed across all threads, so they can exceed the
+/// wall time of a parallel compilation.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BuildTimings {
+    pub resolve: Duration,
+    pub parse: Duration,
+    pub transform: Duration,
+    pub chunking: Duration,
+    pub emit: Duration,
+    /// The modules which took the longest to parse and transform, slowest
+    /// first.
+    pub hotspots: Vec<ModuleTiming>,
+}
+
+impl BuildTimings {
+    pub fn phase(&self, phase: BuildPhase) -> Duration {
+        match phase {
+            BuildPhase::Resolve => self.resolve,
+            BuildPhase::Parse => self.parse,
+            BuildPhase::Transform => self.transform,
+            BuildPhase::Chunking => self.chunking,
+            BuildPhase::Emit => self.emit,
+        }
+    }
+
+    fn phase_mut(&mut self, phase: BuildPhase) -> &mut Duration {
+        match phase {
+            BuildPhase::Resolve => &mut self.resolve,
+            BuildPhase::Parse => &mut self.parse,
+            BuildPhase::Transform => &mut self.transform,
+            BuildPhase::Chunking => &mut self.chunking,
+            BuildPhase::Emit => &mut self.emit,
+        }
+    }
+}
+
+impl Display for BuildTimings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for phase in BuildPhase::ALL {
+            writeln!(f, "{:>10}  {:>10.1?}", phase.span_name(), self.phase(phase))?;
+        }
+        if !self.hotspots.is_empty() {
+            writeln!(f, "slowest modules:")?;
+            for hotspot in &self.hotspots {
+                writeln!(f, "{:>10.1?}  {}", hotspot.duration, hotspot.module)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct TimingsState {
+    phases: HashMap<BuildPhase, Duration>,
+    modules: HashMap<String, Duration>,
+}
+
+/// A handle to retrieve the [BuildTimings] recorded by a
+/// [BuildTimingsLayer].
+#[derive(Clone, Default)]
+pub struct BuildTimingsHandle {
+    state: Arc<Mutex<TimingsState>>,
+}
+
+impl BuildTimingsHandle {
+    /// Returns the timings recorded since the last call and resets them, so
+    /// every compilation can be reported separately.
+    pub fn take(&self) -> BuildTimings {
+        let state = std::mem::take(&mut *self.state.lock().unwrap());
+
+        let mut timings = BuildTimings::default();
+        for (phase, duration) in state.phases {
+            *timings.phase_mut(phase) = duration;
+        }
+
+        let mut hotspots = state
+            .modules
+            .into_iter()
+            .map(|(module, duration)| ModuleTiming { module, duration })
+            .collect::<Vec<_>>();
+        hotspots.sort_by(|a, b| b.duration.cmp(&a.duration));
+        hotspots.truncate(HOTSPOTS);
+        timings.hotspots = hotspots;
+
+        timings
+    }
+}
+
+/// Per span data of a [BuildPhase] span.
+struct PhaseSpan {
+    phase: BuildPhase,
+    module: Option<String>,
+    busy: Duration,
+    entered: Option<Instant>,
+}
+
+/// A tracing layer which sums up the time spent in [BuildPhase] spans. Use
+/// [BuildTimingsLayer::filtered] to only receive these spans.
+pub struct BuildTimingsLayer<S: Subscriber + for<'a> LookupSpan<'a>> {
+    handle: BuildTimingsHandle,
+    _phantom: PhantomData<fn(S)>,
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> BuildTimingsLayer<S> {
+    pub fn new() -> Self {
+        Self {
+            handle: Default::default(),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn handle(&self) -> BuildTimingsHandle {
+        self.handle.clone()
+    }
+
+    /// Returns the layer with a filter which only enables [BuildPhase] spans,
+    /// so it doesn't enable other spans on its own.
+    pub fn filtered(self) -> impl Layer<S> {
+        self.with_filter(filter_fn(|metadata| {
+            metadata.is_span() && BuildPhase::from_span_name(metadata.name()).is_some()
+        }))
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Default for BuildTimingsLayer<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for BuildTimingsLayer<S> {
+    fn on_new_span(
+        &self,
+        attrs: &span::Attributes<'_>,
+        id: &span::Id,
+        ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        let Some(phase) = BuildPhase::from_span_name(attrs.metadata().name()) else {
+            return;
+        };
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut visitor = ModuleVisitor { module: None };
+        attrs.record(&mut visitor);
+        span.extensions_mut().insert(PhaseSpan {
+            phase,
+            module: visitor.module,
+            busy: Duration::ZERO,
+            entered: None,
+        });
+    }
+
+    fn on_enter(&self, id: &span::Id, ctx: tracing_subscriber::layer::Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(phase_span) = span.extensions_mut().get_mut::<PhaseSpan>() {
+                phase_span.entered = Some(Instant::now());
+            }
+        }
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: tracing_subscriber::layer::Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(phase_span) = span.extensions_mut().get_mut::<PhaseSpan>() {
+                if let Some(entered) = phase_span.entered.take() {
+                    phase_span.busy += entered.elapsed();
+                }
+            }
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: tracing_subscriber::layer::Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(phase_span) = span.extensions_mut().remove::<PhaseSpan>() else {
+            return;
+        };
+        let mut state = self.handle.state.lock().unwrap();
+        *state.phases.entry(phase_span.phase).or_default() += phase_span.busy;
+        if let Some(module) = phase_span.module {
+            *state.modules.entry(module).or_default() += phase_span.busy;
+        }
+    }
+}
+
+/// Extracts the `name` field of a span, which identifies the module of
+/// per-module phases.
+struct ModuleVisitor {
+    module: Option<String>,
+}
+
+impl Visit for ModuleVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "name" {
+            self.module = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "name" {
+            self.module = Some(format!("{:?}", value));
+        }
+    }
+}
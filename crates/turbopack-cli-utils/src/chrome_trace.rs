@@ -0,0 +1,253 @@
+use std::{
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Instant,
+};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tracing::{span, Subscriber};
+use tracing_subscriber::{registry::LookupSpan, Layer};
+
+use crate::{raw_trace::ValuesVisitor, trace_writer::TraceWriter, tracing::TraceValue};
+
+/// The process id reported for all events. Traces only ever cover a single
+/// process.
+const PID: u64 = 1;
+
+/// A tracing layer that writes a trace in the Chrome trace event format,
+/// which can be opened directly in `chrome://tracing` or the Perfetto UI.
+///
+/// Spans of turbo-tasks task executions (`turbo_tasks::function` and friends)
+/// are named after the function they run, so the trace shows task
+/// boundaries.
+///
+/// Recording can be turned on and off at runtime with the
+/// [ChromeTraceToggle] returned by [ChromeTraceLayer::toggle].
+pub struct ChromeTraceLayer<S: Subscriber + for<'a> LookupSpan<'a>> {
+    trace_writer: TraceWriter,
+    start: Instant,
+    enabled: Arc<AtomicBool>,
+    _phantom: PhantomData<fn(S)>,
+}
+
+/// A handle to turn recording of a [ChromeTraceLayer] on and off.
+#[derive(Clone, Debug)]
+pub struct ChromeTraceToggle {
+    enabled: Arc<AtomicBool>,
+}
+
+impl ChromeTraceToggle {
+    pub fn enable(&self) {
+        self.enabled.store(true, Ordering::Release);
+    }
+
+    pub fn disable(&self) {
+        self.enabled.store(false, Ordering::Release);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Acquire)
+    }
+}
+
+/// The event data of a span, stored in the span's extensions until it is
+/// entered.
+struct ChromeSpan {
+    name: String,
+    category: String,
+    args: Map<String, Value>,
+    /// Whether a begin event has been written which has not been ended yet.
+    open: bool,
+}
+
+/// A single event of the Chrome trace event format.
+#[derive(Serialize)]
+struct TraceEvent<'a> {
+    name: &'a str,
+    cat: &'a str,
+    ph: &'static str,
+    ts: u64,
+    pid: u64,
+    tid: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    s: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    args: Option<&'a Map<String, Value>>,
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> ChromeTraceLayer<S> {
+    /// Creates a new layer which starts recording immediately.
+    pub fn new(trace_writer: TraceWriter) -> Self {
+        // The trailing `]` of the JSON array is optional in the trace event
+        // format, which allows to write events until the process exits.
+        trace_writer.write(
+            br#"[{"name":"process_name","ph":"M","pid":1,"tid":0,"args":{"name":"turbopack"}}"#
+                .to_vec(),
+        );
+        Self {
+            trace_writer,
+            start: Instant::now(),
+            enabled: Arc::new(AtomicBool::new(true)),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Sets whether the layer records events initially.
+    pub fn with_enabled(self, enabled: bool) -> Self {
+        self.enabled.store(enabled, Ordering::Release);
+        self
+    }
+
+    /// Returns a handle to turn recording on and off.
+    pub fn toggle(&self) -> ChromeTraceToggle {
+        ChromeTraceToggle {
+            enabled: self.enabled.clone(),
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Acquire)
+    }
+
+    fn ts(&self) -> u64 {
+        self.start.elapsed().as_micros() as u64
+    }
+
+    fn write(&self, event: TraceEvent<'_>) {
+        let mut buf = b",\n".to_vec();
+        serde_json::to_writer(&mut buf, &event).unwrap();
+        self.trace_writer.write(buf);
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for ChromeTraceLayer<S> {
+    fn on_new_span(
+        &self,
+        attrs: &span::Attributes<'_>,
+        id: &span::Id,
+        ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut values = ValuesVisitor::new();
+        attrs.values().record(&mut values);
+
+        let metadata = attrs.metadata();
+        let mut name = metadata.name().to_string();
+        let mut category = metadata.target().to_string();
+        let mut args = Map::new();
+        for (key, value) in values.values {
+            // Task spans carry the name of the task function, which is more
+            // useful than the generic span name.
+            if key == "name" && metadata.name().starts_with("turbo_tasks::") {
+                if let Some(task_name) = value.as_str() {
+                    category = metadata.name().to_string();
+                    name = task_name.to_string();
+                    continue;
+                }
+            }
+            args.insert(key.into_owned(), to_json(value));
+        }
+
+        span.extensions_mut().insert(ChromeSpan {
+            name,
+            category,
+            args,
+            open: false,
+        });
+    }
+
+    fn on_enter(&self, id: &span::Id, ctx: tracing_subscriber::layer::Context<'_, S>) {
+        if !self.is_enabled() {
+            return;
+        }
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        let Some(chrome_span) = extensions.get_mut::<ChromeSpan>() else {
+            return;
+        };
+        chrome_span.open = true;
+        self.write(TraceEvent {
+            name: &chrome_span.name,
+            cat: &chrome_span.category,
+            ph: "B",
+            ts: self.ts(),
+            pid: PID,
+            tid: thread::current().id().as_u64().into(),
+            s: None,
+            args: (!chrome_span.args.is_empty()).then_some(&chrome_span.args),
+        });
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: tracing_subscriber::layer::Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        let Some(chrome_span) = extensions.get_mut::<ChromeSpan>() else {
+            return;
+        };
+        // Always close begin events, even when recording has been disabled in
+        // the meantime, to keep the trace balanced.
+        if !chrome_span.open {
+            return;
+        }
+        chrome_span.open = false;
+        self.write(TraceEvent {
+            name: &chrome_span.name,
+            cat: &chrome_span.category,
+            ph: "E",
+            ts: self.ts(),
+            pid: PID,
+            tid: thread::current().id().as_u64().into(),
+            s: None,
+            args: None,
+        });
+    }
+
+    fn on_event(
+        &self,
+        event: &tracing::Event<'_>,
+        _ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut values = ValuesVisitor::new();
+        event.record(&mut values);
+        let args = values
+            .values
+            .into_iter()
+            .map(|(key, value)| (key.into_owned(), to_json(value)))
+            .collect::<Map<_, _>>();
+        let metadata = event.metadata();
+        self.write(TraceEvent {
+            name: metadata.name(),
+            cat: metadata.target(),
+            ph: "i",
+            ts: self.ts(),
+            pid: PID,
+            tid: thread::current().id().as_u64().into(),
+            s: Some("t"),
+            args: Some(&args),
+        });
+    }
+}
+
+fn to_json(value: TraceValue<'_>) -> Value {
+    match value {
+        TraceValue::String(s) => Value::String(s.into_owned()),
+        TraceValue::Bool(b) => Value::Bool(b),
+        TraceValue::UInt(u) => Value::from(u),
+        TraceValue::Int(i) => Value::from(i),
+        TraceValue::Float(f) => Value::from(f),
+    }
+}
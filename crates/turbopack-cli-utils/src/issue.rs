@@ -14,8 +14,8 @@ use owo_colors::{OwoColorize as _, Style};
 use turbo_tasks::{RawVc, ReadRef, TransientInstance, TransientValue, TryJoinIterExt, Vc};
 use turbo_tasks_fs::{source_context::get_source_context, FileLinesContent};
 use turbopack_core::issue::{
-    CapturedIssues, Issue, IssueReporter, IssueSeverity, PlainIssue, PlainIssueProcessingPathItem,
-    PlainIssueSource,
+    CapturedIssues, IssuePolicy, IssueReporter, IssueSeverity, PlainIssue,
+    PlainIssueProcessingPathItem, PlainIssueSource,
 };
 
 use crate::source_context::format_source_context_lines;
@@ -130,12 +130,13 @@ pub fn format_issue(
     let &LogOptions {
         ref current_dir,
         log_detail,
+        ref issue_policy,
         ..
     } = options;
 
     let mut issue_text = String::new();
 
-    let severity = plain_issue.severity;
+    let severity = issue_policy.severity(plain_issue);
     // TODO CLICKABLE PATHS
     let context_path = plain_issue
         .file_path
@@ -221,6 +222,7 @@ pub struct LogOptions {
     pub show_all: bool,
     pub log_detail: bool,
     pub log_level: IssueSeverity,
+    pub issue_policy: IssuePolicy,
 }
 
 /// Tracks the state of currently seen issues.
@@ -360,6 +362,7 @@ impl IssueReporter for ConsoleUi {
             show_all,
             log_detail,
             log_level,
+            ref issue_policy,
             ..
         } = self.options;
         let mut grouped_issues: GroupedIssues = HashMap::new();
@@ -387,7 +390,7 @@ impl IssueReporter for ConsoleUi {
                 continue;
             }
 
-            let severity = plain_issue.severity;
+            let severity = issue_policy.severity(&plain_issue);
             if severity <= *min_failing_severity.await? {
                 has_fatal = true;
             }
@@ -525,6 +528,11 @@ impl IssueReporter for ConsoleUi {
 
         Ok(Vc::cell(has_fatal))
     }
+
+    #[turbo_tasks::function]
+    fn issue_policy(&self) -> Vc<IssuePolicy> {
+        self.options.issue_policy.clone().cell()
+    }
 }
 
 fn make_relative_to_cwd<'a>(path: &'a str, project_dir: &Path, cwd: &Path) -> Cow<'a, str> {
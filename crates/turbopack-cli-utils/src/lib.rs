@@ -5,6 +5,8 @@
 #![feature(arbitrary_self_types)]
 #![feature(async_fn_in_trait)]
 
+pub mod build_timings;
+pub mod chrome_trace;
 pub mod exit;
 pub mod issue;
 pub mod raw_trace;
@@ -101,12 +101,12 @@ impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for RawTraceLayer<S> {
     }
 }
 
-struct ValuesVisitor {
-    values: Vec<(Cow<'static, str>, TraceValue<'static>)>,
+pub(crate) struct ValuesVisitor {
+    pub(crate) values: Vec<(Cow<'static, str>, TraceValue<'static>)>,
 }
 
 impl ValuesVisitor {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self { values: Vec::new() }
     }
 }
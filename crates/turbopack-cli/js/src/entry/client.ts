@@ -14,5 +14,6 @@ export function initializeHMR(options: { assetPrefix: string }) {
     assetPrefix: options.assetPrefix,
     log: true,
     path: "/turbopack-hmr",
+    compression: true,
   });
 }
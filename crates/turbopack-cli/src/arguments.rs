@@ -3,14 +3,19 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use clap::{Args, Parser};
+use clap::{Args, Parser, ValueEnum};
+use serde::Deserialize;
 use turbopack_cli_utils::issue::IssueSeverityCliOption;
 
+use crate::build::federation::FederationOptions;
+
 #[derive(Debug, Parser)]
 #[clap(author, version, about, long_about = None)]
 pub enum Arguments {
     Build(BuildArguments),
     Dev(DevArguments),
+    Analyze(AnalyzeArguments),
+    Gc(GcArguments),
 }
 
 impl Arguments {
@@ -19,6 +24,27 @@ impl Arguments {
         match self {
             Arguments::Build(args) => args.common.dir.as_deref(),
             Arguments::Dev(args) => args.common.dir.as_deref(),
+            Arguments::Analyze(args) => args.build.common.dir.as_deref(),
+            Arguments::Gc(_) => None,
+        }
+    }
+
+    /// Whether a summary of the build timings should be printed.
+    pub fn timings(&self) -> bool {
+        match self {
+            Arguments::Build(args) => args.timings,
+            Arguments::Analyze(args) => args.build.timings,
+            Arguments::Dev(_) | Arguments::Gc(_) => false,
+        }
+    }
+
+    /// The arguments shared by all commands which compile the application.
+    pub fn common(&self) -> Option<&CommonArguments> {
+        match self {
+            Arguments::Build(args) => Some(&args.common),
+            Arguments::Dev(args) => Some(&args.common),
+            Arguments::Analyze(args) => Some(&args.build.common),
+            Arguments::Gc(_) => None,
         }
     }
 }
@@ -41,6 +67,15 @@ pub struct CommonArguments {
     #[clap(long, value_parser)]
     pub root: Option<PathBuf>,
 
+    /// The config file to load. Defaults to `turbopack.config.json` in the
+    /// application directory, if it exists.
+    #[clap(short, long, value_parser)]
+    pub config: Option<PathBuf>,
+
+    /// The browserslist query of the targeted browsers.
+    #[clap(long)]
+    pub browserslist: Option<String>,
+
     /// Filter by issue severity.
     #[clap(short, long)]
     pub log_level: Option<IssueSeverityCliOption>,
@@ -85,6 +120,16 @@ pub struct DevArguments {
     #[clap(long)]
     pub eager_compile: bool,
 
+    /// Don't compile dynamically imported modules until they are imported
+    /// for the first time
+    #[clap(long)]
+    pub lazy_compilation: bool,
+
+    /// Serve the runtime as a separate chunk shared by all entries, instead
+    /// of including it in each of them
+    #[clap(long)]
+    pub runtime_chunk: bool,
+
     /// Don't open the browser automatically when the dev server has started.
     #[clap(long)]
     pub no_open: bool,
@@ -107,4 +152,136 @@ pub struct BuildArguments {
     /// Don't minify build output.
     #[clap(long)]
     pub no_minify: bool,
+
+    /// Include the runtime in each entry chunk, instead of loading it from a
+    /// runtime chunk shared by all entries.
+    #[clap(long)]
+    pub no_runtime_chunk: bool,
+
+    /// How source maps are emitted. Defaults to `full`.
+    #[clap(long, value_enum)]
+    pub source_maps: Option<SourceMapsOption>,
+
+    /// How module ids are generated. Defaults to `deterministic`.
+    #[clap(long, value_enum)]
+    pub module_ids: Option<ModuleIdsOption>,
+
+    /// Print a summary of the time spent in each phase of the build.
+    #[clap(long)]
+    pub timings: bool,
+
+    /// Report all warnings as errors, which fail the build.
+    #[clap(long)]
+    pub warnings_as_errors: bool,
+
+    /// Report warnings with this code (eg TP1002) as errors. Can be passed
+    /// multiple times.
+    #[clap(long = "error-code", value_name = "CODE")]
+    pub error_codes: Vec<String>,
+
+    /// Keep reporting warnings with this code as warnings when using
+    /// `--warnings-as-errors`. Can be passed multiple times.
+    #[clap(long = "allow-code", value_name = "CODE")]
+    pub allowed_codes: Vec<String>,
+
+    /// Resolve packages which are installed multiple times to their highest
+    /// version, instead of the version nearest to the importer.
+    #[clap(long)]
+    pub dedupe_packages: bool,
+
+    /// Also build the entries for the legacy browsers matching this
+    /// browserslist query into `dist/legacy`, and list the entry chunks of both
+    /// builds in `dist/build-manifest.json`.
+    #[clap(long, value_name = "QUERY")]
+    pub legacy_browserslist: Option<String>,
+
+    /// Report import cycles as issues with this severity.
+    #[clap(long, value_name = "SEVERITY")]
+    pub circular_dependencies: Option<IssueSeverityCliOption>,
+
+    /// Don't report import cycles containing a module whose path matches this
+    /// glob, e.g. `**/node_modules/**`. Can be passed multiple times.
+    #[clap(long = "allow-circular", value_name = "GLOB")]
+    pub allowed_circular_dependencies: Vec<String>,
+
+    /// Type check the TypeScript modules with the `typescript` package of the
+    /// project and report the diagnostics as issues.
+    #[clap(long)]
+    pub type_check: bool,
+
+    /// The `tsconfig.json` used by `--type-check`, relative to the project
+    /// directory. Defaults to the nearest `tsconfig.json`.
+    #[clap(long, value_name = "PATH")]
+    pub tsconfig: Option<String>,
+
+    /// Cache the results of the build in the shared artifact store, to speed
+    /// up subsequent builds. Run `gc` to limit the size of the store.
+    #[clap(long)]
+    pub persistent_caching: bool,
+
+    /// The module federation options, which can only be set in the config
+    /// file.
+    #[clap(skip)]
+    pub federation: Option<FederationOptions>,
+}
+
+#[derive(Debug, Args)]
+#[clap(author, version, about, long_about = None)]
+pub struct AnalyzeArguments {
+    #[clap(flatten)]
+    pub build: BuildArguments,
+
+    /// Print the report as JSON.
+    #[clap(long)]
+    pub json: bool,
+
+    /// Report the compile-time values (defines and environment variables)
+    /// inlined into the output chunks instead of the asset sizes.
+    #[clap(long)]
+    pub inlined_values: bool,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceMapsOption {
+    /// Separate `.map` files referenced from the chunks.
+    Full,
+    /// Separate `.map` files which only map lines.
+    Cheap,
+    /// Source maps embedded into the chunks.
+    Inline,
+    /// Separate `.map` files which are not referenced from the chunks.
+    Hidden,
+    /// No source maps.
+    None,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModuleIdsOption {
+    /// The paths of the modules, which are readable but long.
+    Path,
+    /// Hashes of the paths of the modules.
+    Hashed,
+    /// Short numbers derived from the paths of the modules.
+    Deterministic,
+}
+
+#[derive(Debug, Args)]
+#[clap(author, version, about, long_about = None)]
+pub struct GcArguments {
+    /// The directory of the shared artifact store.
+    /// If no directory is provided, `TURBOPACK_ARTIFACT_STORE` or the user's
+    /// cache directory will be used.
+    #[clap(long, value_parser)]
+    pub store: Option<PathBuf>,
+
+    /// Remove artifacts which have not been used for this number of days.
+    #[clap(long)]
+    pub max_age_days: Option<u64>,
+
+    /// Remove the least recently used artifacts until the store is smaller
+    /// than this size in MB.
+    #[clap(long)]
+    pub max_size: Option<u64>,
 }
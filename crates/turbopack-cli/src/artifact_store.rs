@@ -0,0 +1,422 @@
+use std::{
+    fs,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{bail, Context, Result};
+use filetime::FileTime;
+use sha2::{Digest, Sha256};
+use turbo_tasks_hash::encode_hex_string;
+use turbo_tasks_memory::PersistedGraphStorage;
+
+use crate::arguments::GcArguments;
+
+/// Environment variable which overrides the location of the shared artifact
+/// store.
+const ARTIFACT_STORE_ENV: &str = "TURBOPACK_ARTIFACT_STORE";
+
+/// Temporary files younger than this are considered to be written by a running
+/// process and are left alone by [ArtifactStore::gc]. Older ones are leftovers
+/// of a crashed process.
+const TEMP_FILE_GRACE_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// Distinguishes the temporary files of concurrent writes within a process.
+static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A content-addressable store for intermediate build artifacts.
+///
+/// Artifacts are keyed by the SHA-256 hash of their content, so identical
+/// artifacts produced by different projects are only stored once. The content
+/// is verified against the key when it's read, so corrupted artifacts are
+/// treated as missing. The store lives in a
+/// per-user cache directory and is shared across all projects on the machine.
+///
+/// The modification time of an artifact is bumped whenever it is stored or
+/// read again, which allows [ArtifactStore::gc] to evict the least recently
+/// used artifacts first.
+pub struct ArtifactStore {
+    root: PathBuf,
+}
+
+/// Limits applied by [ArtifactStore::gc].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GcPolicy {
+    /// Artifacts which have not been used for longer than this are removed.
+    pub max_age: Option<Duration>,
+    /// The least recently used artifacts are removed until the total size of
+    /// the store is below this number of bytes.
+    pub max_size: Option<u64>,
+}
+
+/// The outcome of a garbage collection run.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GcStats {
+    pub removed_artifacts: usize,
+    pub removed_bytes: u64,
+    pub remaining_artifacts: usize,
+    pub remaining_bytes: u64,
+}
+
+struct ArtifactEntry {
+    path: PathBuf,
+    size: u64,
+    last_used: SystemTime,
+}
+
+impl ArtifactStore {
+    /// Opens the store at `root`, creating the directory if needed.
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        fs::create_dir_all(&root)
+            .with_context(|| format!("Unable to create artifact store at {}", root.display()))?;
+        Ok(Self { root })
+    }
+
+    /// The location of the store which is shared across projects. It can be
+    /// overridden with the `TURBOPACK_ARTIFACT_STORE` environment variable.
+    pub fn default_root() -> Result<PathBuf> {
+        if let Some(root) = std::env::var_os(ARTIFACT_STORE_ENV) {
+            return Ok(PathBuf::from(root));
+        }
+        let cache_dir = match std::env::var_os("XDG_CACHE_HOME") {
+            Some(cache_dir) => PathBuf::from(cache_dir),
+            None => match std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")) {
+                Some(home) => PathBuf::from(home).join(".cache"),
+                None => bail!(
+                    "Unable to determine the artifact store location, set {ARTIFACT_STORE_ENV}"
+                ),
+            },
+        };
+        Ok(cache_dir.join("turbopack").join("artifacts"))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Stores `content` and returns the key it can be read back with.
+    pub fn put(&self, content: &[u8]) -> Result<String> {
+        let key = content_key(content);
+        let path = self.artifact_path(&key);
+        match fs::metadata(&path) {
+            // A truncated artifact is replaced below.
+            Ok(metadata) if metadata.len() == content.len() as u64 => {
+                touch(&path)?;
+                return Ok(key);
+            }
+            Ok(_) => {}
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+        let dir = path.parent().unwrap();
+        fs::create_dir_all(dir)?;
+        // Write to a temporary file first, so concurrent readers never observe
+        // a partially written artifact.
+        let temp_path = dir.join(format!(
+            "{key}.{}.{}.tmp",
+            std::process::id(),
+            TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&temp_path, &path)?;
+        Ok(key)
+    }
+
+    /// Reads the artifact stored under `key`, if it exists and its content
+    /// matches the key.
+    pub fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        if !is_valid_key(key) {
+            return Ok(None);
+        }
+        let path = self.artifact_path(key);
+        match fs::read(&path) {
+            Ok(content) if content_key(&content) == key => {
+                touch(&path)?;
+                Ok(Some(content))
+            }
+            Ok(_) => {
+                // The artifact is corrupted, e.g. by a crash or a full disk.
+                match fs::remove_file(&path) {
+                    Ok(()) => Ok(None),
+                    Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+                    Err(err) => Err(err.into()),
+                }
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Removes artifacts according to `policy`. Artifacts older than
+    /// `max_age` are removed first, then the least recently used artifacts
+    /// until the store fits into `max_size`. Temporary files of writes which
+    /// might still be in progress are kept.
+    pub fn gc(&self, policy: GcPolicy) -> Result<GcStats> {
+        let now = SystemTime::now();
+        let (mut entries, temp_files) = self.entries()?;
+        for temp_file in temp_files {
+            let stale = now
+                .duration_since(temp_file.last_used)
+                .map_or(false, |age| age > TEMP_FILE_GRACE_PERIOD);
+            if stale {
+                remove_file(&temp_file.path)?;
+            }
+        }
+        entries.sort_by_key(|entry| entry.last_used);
+
+        let mut stats = GcStats {
+            remaining_artifacts: entries.len(),
+            remaining_bytes: entries.iter().map(|entry| entry.size).sum(),
+            ..Default::default()
+        };
+        for entry in entries {
+            let expired = policy.max_age.map_or(false, |max_age| {
+                now.duration_since(entry.last_used)
+                    .map_or(false, |age| age > max_age)
+            });
+            let over_size = policy
+                .max_size
+                .map_or(false, |max_size| stats.remaining_bytes > max_size);
+            if !expired && !over_size {
+                // Entries are sorted by last use, so all remaining entries are
+                // newer and the store already fits into the size limit.
+                break;
+            }
+            remove_file(&entry.path)?;
+            stats.removed_artifacts += 1;
+            stats.removed_bytes += entry.size;
+            stats.remaining_artifacts -= 1;
+            stats.remaining_bytes -= entry.size;
+        }
+        Ok(stats)
+    }
+
+    fn artifact_path(&self, key: &str) -> PathBuf {
+        let (prefix, rest) = key.split_at(2.min(key.len()));
+        self.root.join(prefix).join(rest)
+    }
+
+    /// Returns the artifacts and the temporary files in the store.
+    fn entries(&self) -> Result<(Vec<ArtifactEntry>, Vec<ArtifactEntry>)> {
+        let mut entries = Vec::new();
+        let mut temp_files = Vec::new();
+        for dir in fs::read_dir(&self.root)? {
+            let dir = dir?;
+            if !dir.file_type()?.is_dir() {
+                continue;
+            }
+            for file in fs::read_dir(dir.path())? {
+                let file = file?;
+                let metadata = file.metadata()?;
+                if !metadata.is_file() {
+                    continue;
+                }
+                let entry = ArtifactEntry {
+                    path: file.path(),
+                    size: metadata.len(),
+                    last_used: metadata.modified()?,
+                };
+                if entry.path.extension().map_or(false, |ext| ext == "tmp") {
+                    temp_files.push(entry);
+                } else {
+                    entries.push(entry);
+                }
+            }
+        }
+        Ok((entries, temp_files))
+    }
+}
+
+/// Keeps the [DiskPersistedGraph](turbo_tasks_memory::DiskPersistedGraph) of
+/// a project in an [ArtifactStore]. The project only stores the key of its
+/// latest snapshot, in the `index` file.
+pub struct PersistedGraphArtifact {
+    store: ArtifactStore,
+    index: PathBuf,
+}
+
+impl PersistedGraphArtifact {
+    pub fn new(store: ArtifactStore, index: PathBuf) -> Self {
+        Self { store, index }
+    }
+}
+
+impl PersistedGraphStorage for PersistedGraphArtifact {
+    fn read(&self) -> Result<Option<Vec<u8>>> {
+        let key = match fs::read_to_string(&self.index) {
+            Ok(key) => key,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        // The snapshot might have been garbage collected in the meantime.
+        self.store.get(key.trim())
+    }
+
+    fn write(&self, snapshot: &[u8]) -> Result<()> {
+        let key = self.store.put(snapshot)?;
+        if let Some(parent) = self.index.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.index, key)
+            .with_context(|| format!("Unable to write {}", self.index.display()))
+    }
+}
+
+fn content_key(content: &[u8]) -> String {
+    encode_hex_string(&Sha256::digest(content))
+}
+
+fn is_valid_key(key: &str) -> bool {
+    key.len() == 64 && key.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn touch(path: &Path) -> Result<()> {
+    filetime::set_file_mtime(path, FileTime::now())?;
+    Ok(())
+}
+
+fn remove_file(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        // Another process may have collected it concurrently.
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Runs garbage collection on the shared artifact store.
+pub fn gc(args: &GcArguments) -> Result<()> {
+    let root = match &args.store {
+        Some(store) => store.clone(),
+        None => ArtifactStore::default_root()?,
+    };
+    let store = ArtifactStore::open(root)?;
+    let stats = store.gc(GcPolicy {
+        max_age: args
+            .max_age_days
+            .map(|days| Duration::from_secs(days * 24 * 60 * 60)),
+        max_size: args.max_size.map(|mb| mb * 1024 * 1024),
+    })?;
+    println!(
+        "Removed {} artifacts ({} bytes) from {}, {} artifacts ({} bytes) remaining",
+        stats.removed_artifacts,
+        stats.removed_bytes,
+        store.root().display(),
+        stats.remaining_artifacts,
+        stats.remaining_bytes
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use filetime::FileTime;
+
+    use super::{content_key, ArtifactStore, GcPolicy};
+
+    fn temp_store(name: &str) -> ArtifactStore {
+        let root = std::env::temp_dir().join(format!(
+            "turbopack-artifact-store-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&root);
+        ArtifactStore::open(root).unwrap()
+    }
+
+    fn age(store: &ArtifactStore, key: &str, seconds: i64) {
+        let time = FileTime::from_unix_time(FileTime::now().unix_seconds() - seconds, 0);
+        filetime::set_file_mtime(store.artifact_path(key), time).unwrap();
+    }
+
+    #[test]
+    fn put_and_get() {
+        let store = temp_store("put-get");
+        let key = store.put(b"hello").unwrap();
+        assert_eq!(store.put(b"hello").unwrap(), key);
+        assert_eq!(store.get(&key).unwrap().as_deref(), Some(&b"hello"[..]));
+        assert_eq!(store.get(&content_key(b"missing")).unwrap(), None);
+        assert_eq!(store.get("../../etc/passwd").unwrap(), None);
+        std::fs::remove_dir_all(store.root()).unwrap();
+    }
+
+    #[test]
+    fn corrupted_artifacts_are_missing() {
+        let store = temp_store("corrupted");
+        let key = store.put(b"hello").unwrap();
+        std::fs::write(store.artifact_path(&key), b"hallo").unwrap();
+        assert_eq!(store.get(&key).unwrap(), None);
+        assert!(!store.artifact_path(&key).exists());
+
+        // A truncated artifact is replaced when it's stored again.
+        std::fs::write(store.artifact_path(&key), b"he").unwrap();
+        assert_eq!(store.put(b"hello").unwrap(), key);
+        assert_eq!(store.get(&key).unwrap().as_deref(), Some(&b"hello"[..]));
+        std::fs::remove_dir_all(store.root()).unwrap();
+    }
+
+    #[test]
+    fn gc_keeps_in_flight_temp_files() {
+        let store = temp_store("gc-temp");
+        let key = store.put(b"hello").unwrap();
+        let dir = store.artifact_path(&key).parent().unwrap().to_path_buf();
+        let in_flight = dir.join(format!("{key}.1.0.tmp"));
+        let stale = dir.join(format!("{key}.2.0.tmp"));
+        std::fs::write(&in_flight, b"hel").unwrap();
+        std::fs::write(&stale, b"hel").unwrap();
+        let time = FileTime::from_unix_time(FileTime::now().unix_seconds() - 2 * 60 * 60, 0);
+        filetime::set_file_mtime(&stale, time).unwrap();
+
+        let stats = store
+            .gc(GcPolicy {
+                max_age: None,
+                max_size: Some(0),
+            })
+            .unwrap();
+        assert_eq!(stats.removed_artifacts, 1);
+        assert!(in_flight.exists());
+        assert!(!stale.exists());
+        std::fs::remove_dir_all(store.root()).unwrap();
+    }
+
+    #[test]
+    fn gc_by_age_and_size() {
+        let store = temp_store("gc");
+        let old = store.put(b"old").unwrap();
+        let used = store.put(b"used").unwrap();
+        let new = store.put(b"new").unwrap();
+        age(&store, &old, 3 * 60 * 60);
+        age(&store, &used, 2 * 60 * 60);
+        age(&store, &new, 60 * 60);
+
+        let stats = store
+            .gc(GcPolicy {
+                max_age: Some(Duration::from_secs(150 * 60)),
+                max_size: None,
+            })
+            .unwrap();
+        assert_eq!(stats.removed_artifacts, 1);
+        assert_eq!(store.get(&old).unwrap(), None);
+
+        // Reading an artifact marks it as recently used.
+        store.get(&used).unwrap();
+        let stats = store
+            .gc(GcPolicy {
+                max_age: None,
+                max_size: Some(4),
+            })
+            .unwrap();
+        assert_eq!(stats.removed_artifacts, 1);
+        assert_eq!(stats.remaining_bytes, 4);
+        assert_eq!(store.get(&new).unwrap(), None);
+        assert!(store.get(&used).unwrap().is_some());
+        std::fs::remove_dir_all(store.root()).unwrap();
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use turbo_tasks::{TryJoinIterExt, Vc};
+use turbo_tasks_fs::FileContent;
+use turbopack::ecmascript::chunk::EcmascriptOutputChunk;
+use turbopack_core::{asset::Asset, output::OutputAssets};
+
+/// The size of an emitted output asset.
+#[turbo_tasks::value(shared)]
+#[derive(Clone)]
+pub(crate) struct OutputAssetSize {
+    /// The path of the asset, relative to the output file system root.
+    pub path: String,
+    /// The size of the asset in bytes.
+    pub size: usize,
+}
+
+#[turbo_tasks::value(transparent)]
+pub(crate) struct OutputAssetSizes(Vec<OutputAssetSize>);
+
+/// Computes the sizes of the output assets, largest first.
+#[turbo_tasks::function]
+pub(crate) async fn output_asset_sizes(
+    output_assets: Vc<OutputAssets>,
+) -> Result<Vc<OutputAssetSizes>> {
+    let mut sizes = output_assets
+        .await?
+        .iter()
+        .map(|asset| async move {
+            let path = asset.ident().path().await?.path.clone();
+            let size = match &*asset.content().file_content().await? {
+                FileContent::Content(file) => file.content().len(),
+                FileContent::NotFound => 0,
+            };
+            Ok(OutputAssetSize { path, size })
+        })
+        .try_join()
+        .await?;
+
+    sizes.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+
+    Ok(Vc::cell(sizes))
+}
+
+pub(crate) fn print_report(sizes: &[OutputAssetSize], json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(sizes)?);
+        return Ok(());
+    }
+
+    let total: usize = sizes.iter().map(|asset| asset.size).sum();
+    for asset in sizes {
+        println!("{:>12}  {}", format_size(asset.size), asset.path);
+    }
+    println!("{:>12}  total ({} assets)", format_size(total), sizes.len());
+
+    Ok(())
+}
+
+/// A compile-time value which is inlined into output chunks.
+#[turbo_tasks::value(shared)]
+#[derive(Clone)]
+pub(crate) struct InlinedValueUsage {
+    /// The dot-separated name of the value, e.g. `process.env.API_URL`.
+    pub name: String,
+    /// The paths of the chunks the value is inlined into.
+    pub chunks: Vec<String>,
+    /// The number of places the value is inlined at, across all chunks.
+    pub sites: usize,
+}
+
+#[turbo_tasks::value(transparent)]
+pub(crate) struct InlinedValueUsages(Vec<InlinedValueUsage>);
+
+/// Collects the compile-time values inlined into the ecmascript chunks of the
+/// output assets, ordered by name.
+#[turbo_tasks::function]
+pub(crate) async fn inlined_value_usages(
+    output_assets: Vc<OutputAssets>,
+) -> Result<Vc<InlinedValueUsages>> {
+    let mut usages = BTreeMap::new();
+    for &asset in output_assets.await?.iter() {
+        let Some(chunk) = Vc::try_resolve_sidecast::<Box<dyn EcmascriptOutputChunk>>(asset).await?
+        else {
+            continue;
+        };
+        let path = asset.ident().path().await?.path.clone();
+        for (name, &sites) in chunk.inlined_values().await?.iter() {
+            let usage = usages
+                .entry(name.clone())
+                .or_insert_with(|| InlinedValueUsage {
+                    name: name.clone(),
+                    chunks: Vec::new(),
+                    sites: 0,
+                });
+            usage.chunks.push(path.clone());
+            usage.sites += sites;
+        }
+    }
+
+    Ok(Vc::cell(usages.into_values().collect()))
+}
+
+pub(crate) fn print_inlined_values_report(usages: &[InlinedValueUsage], json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(usages)?);
+        return Ok(());
+    }
+
+    for usage in usages {
+        println!(
+            "{}  ({} sites in {} chunks)",
+            usage.name,
+            usage.sites,
+            usage.chunks.len()
+        );
+        for chunk in &usage.chunks {
+            println!("  {chunk}");
+        }
+    }
+    println!("{} inlined values", usages.len());
+
+    Ok(())
+}
+
+fn format_size(size: usize) -> String {
+    if size < 1024 {
+        format!("{size} B")
+    } else if size < 1024 * 1024 {
+        format!("{:.1} KiB", size as f64 / 1024.0)
+    } else {
+        format!("{:.1} MiB", size as f64 / (1024.0 * 1024.0))
+    }
+}
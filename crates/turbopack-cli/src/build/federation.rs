@@ -0,0 +1,285 @@
+use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, TryJoinIterExt, Value, Vc};
+use turbo_tasks_fs::{FileJsonContent, FileSystemPath};
+use turbopack::{
+    ecmascript::chunk::EcmascriptChunkPlaceable, federation::FederationRemotes, resolve_options,
+    resolve_options_context::ResolveOptionsContext,
+};
+use turbopack_build::{
+    federation_externals, BuildChunkingContext, FederationContainerOptions, SharedPackage,
+};
+use turbopack_core::{
+    context::AssetContext,
+    module::Module,
+    output::OutputAsset,
+    reference_type::{EntryReferenceSubType, ReferenceType},
+    resolve::{
+        options::Externals,
+        origin::{PlainResolveOrigin, ResolveOriginExt},
+        parse::Request,
+        resolve,
+    },
+};
+
+/// The file name of the container in the output directory when no `filename`
+/// is configured.
+const DEFAULT_FILENAME: &str = "remoteEntry.mjs";
+
+/// The module federation options of a build, i.e. the `federation` of the
+/// config file. A build which exposes modules emits a container, which other
+/// builds load them from at runtime, and a build with remotes is a host which
+/// loads modules from their containers. Both share the `shared` packages.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FederationOptions {
+    /// The name of the container, which hosts refer to it by. Required when
+    /// modules are exposed.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// The file name of the container in the output directory. Defaults to
+    /// `remoteEntry.mjs`.
+    #[serde(default)]
+    pub filename: Option<String>,
+    /// Maps the names of the exposed modules, e.g. `./Button`, to their
+    /// requests relative to the project directory, e.g. `./src/Button.tsx`.
+    #[serde(default)]
+    pub exposes: IndexMap<String, String>,
+    /// The packages which are shared with the host and the other containers,
+    /// e.g. `react`, ordered by their dependencies.
+    #[serde(default)]
+    pub shared: IndexMap<String, SharedOptions>,
+    /// Maps the names of the containers which modules are loaded from to the
+    /// URLs of their entries, e.g. `app2` to
+    /// `https://app2.example.com/remoteEntry.mjs`.
+    #[serde(default)]
+    pub remotes: IndexMap<String, String>,
+}
+
+/// The options of a shared package, see [SharedPackage].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SharedOptions {
+    /// The version of the package. Defaults to the version which is installed
+    /// in the `node_modules` of the project.
+    pub version: Option<String>,
+    /// The range of versions which the modules of the build are compatible
+    /// with. Defaults to `^` followed by `version`.
+    pub required_version: Option<String>,
+    /// Whether only a single version of the package may be loaded.
+    #[serde(default)]
+    pub singleton: bool,
+}
+
+/// The containers which a host loads modules from, with the versions of the
+/// packages which it shares with them.
+#[turbo_tasks::function]
+pub async fn federation_remotes(
+    project_path: Vc<FileSystemPath>,
+    options: Vc<FederationOptions>,
+) -> Result<Vc<FederationRemotes>> {
+    let options = options.await?;
+    let shared = options
+        .shared
+        .iter()
+        .map(|(request, shared)| async move {
+            Ok((
+                request.clone(),
+                shared_version(project_path, request, shared).await?,
+            ))
+        })
+        .try_join()
+        .await?
+        .into_iter()
+        .collect();
+
+    Ok(FederationRemotes {
+        remotes: options.remotes.clone(),
+        shared,
+        project_path,
+    }
+    .cell())
+}
+
+/// The shared packages of a container as externals, which its exposed modules
+/// and the fallbacks of the shared packages are resolved with.
+#[turbo_tasks::function]
+pub async fn container_externals(options: Vc<FederationOptions>) -> Result<Vc<Externals>> {
+    Ok(federation_externals(
+        options.await?.shared.keys().cloned().collect(),
+    ))
+}
+
+/// Generates the container of the modules exposed by `options` in
+/// `build_output_root`. `asset_context` processes the exposed modules and the
+/// fallbacks of the shared packages with [container_externals], while
+/// `resolve_options_context` resolves the requests of the shared packages to
+/// their fallbacks.
+#[turbo_tasks::function]
+pub async fn federation_container(
+    project_path: Vc<FileSystemPath>,
+    asset_context: Vc<Box<dyn AssetContext>>,
+    resolve_options_context: Vc<ResolveOptionsContext>,
+    chunking_context: Vc<BuildChunkingContext>,
+    build_output_root: Vc<FileSystemPath>,
+    options: Vc<FederationOptions>,
+) -> Result<Vc<Box<dyn OutputAsset>>> {
+    let options = options.await?;
+    let Some(name) = options.name.clone() else {
+        bail!("The container needs a `name` in the federation options to expose modules.");
+    };
+
+    let origin = PlainResolveOrigin::new(asset_context, project_path.join("_".to_string()));
+    let exposes = options
+        .exposes
+        .iter()
+        .map(|(expose, request)| async move {
+            let ty = Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined));
+            let module = origin
+                .resolve_asset(
+                    Request::parse_string(request.clone()),
+                    origin.resolve_options(ty.clone()),
+                    ty,
+                )
+                .first_module()
+                .await?
+                .with_context(|| {
+                    format!("Unable to resolve the module {request} exposed as {expose}.")
+                })?;
+            Ok((expose.clone(), ecmascript_module(module, request).await?))
+        })
+        .try_join()
+        .await?
+        .into_iter()
+        .collect();
+
+    let resolve_options = resolve_options(project_path, resolve_options_context);
+    let shared = options
+        .shared
+        .iter()
+        .map(|(request, shared)| async move {
+            let result = resolve(
+                project_path,
+                Request::parse_string(request.clone()),
+                resolve_options,
+            );
+            let Some(source) = *result.first_source().await? else {
+                bail!("Unable to resolve the shared package {request}.");
+            };
+            let module = asset_context.process(source, Value::new(ReferenceType::Undefined));
+            Ok((
+                request.clone(),
+                SharedPackage {
+                    module: ecmascript_module(module, request).await?,
+                    version: shared_version(project_path, request, shared).await?,
+                    required_version: shared.required_version.clone(),
+                    singleton: shared.singleton,
+                },
+            ))
+        })
+        .try_join()
+        .await?
+        .into_iter()
+        .collect();
+
+    let filename = options
+        .filename
+        .clone()
+        .unwrap_or_else(|| DEFAULT_FILENAME.to_string());
+    Ok(chunking_context.federation_container(
+        build_output_root.join(filename),
+        FederationContainerOptions {
+            name,
+            exposes,
+            shared,
+        }
+        .cell(),
+    ))
+}
+
+async fn ecmascript_module(
+    module: Vc<Box<dyn Module>>,
+    request: &str,
+) -> Result<Vc<Box<dyn EcmascriptChunkPlaceable>>> {
+    Vc::try_resolve_sidecast::<Box<dyn EcmascriptChunkPlaceable>>(module)
+        .await?
+        .with_context(|| {
+            format!("{request} is not an EcmaScript module, so it can't be part of a container")
+        })
+}
+
+/// The version of the shared package of `request`, which defaults to the
+/// version which is installed in the project.
+async fn shared_version(
+    project_path: Vc<FileSystemPath>,
+    request: &str,
+    options: &SharedOptions,
+) -> Result<String> {
+    if let Some(version) = &options.version {
+        return Ok(version.clone());
+    }
+
+    let package_json = project_path.join(format!(
+        "node_modules/{}/package.json",
+        package_name(request)
+    ));
+    if let FileJsonContent::Content(json) = &*package_json.read_json().await? {
+        if let Some(version) = json["version"].as_str() {
+            return Ok(version.to_string());
+        }
+    }
+    bail!(
+        "Unable to read the version of the shared package {request} from {}, set its `version` in \
+         the federation options.",
+        package_json.await?
+    )
+}
+
+/// The name of the package of a request, e.g. `react` of `react/jsx-runtime`.
+fn package_name(request: &str) -> &str {
+    let separators = if request.starts_with('@') { 2 } else { 1 };
+    match request.match_indices('/').nth(separators - 1) {
+        Some((index, _)) => &request[..index],
+        None => request,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{package_name, FederationOptions, SharedOptions};
+
+    #[test]
+    fn reads_the_package_name_of_requests() {
+        assert_eq!(package_name("react"), "react");
+        assert_eq!(package_name("react/jsx-runtime"), "react");
+        assert_eq!(package_name("@emotion/react"), "@emotion/react");
+        assert_eq!(package_name("@emotion/react/jsx-runtime"), "@emotion/react");
+    }
+
+    #[test]
+    fn parses_the_federation_options() {
+        let options: FederationOptions = serde_json::from_str(
+            r#"{
+                "name": "app2",
+                "exposes": { "./Button": "./src/Button.tsx" },
+                "shared": { "react": { "requiredVersion": "^18.0.0", "singleton": true } }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(options.name.as_deref(), Some("app2"));
+        assert_eq!(options.filename, None);
+        assert_eq!(options.exposes["./Button"], "./src/Button.tsx");
+        assert_eq!(
+            options.shared["react"],
+            SharedOptions {
+                version: None,
+                required_version: Some("^18.0.0".to_string()),
+                singleton: true,
+            }
+        );
+        assert!(options.remotes.is_empty());
+    }
+}
@@ -1,24 +1,38 @@
 use std::{
     collections::HashSet,
-    env::current_dir,
-    path::{PathBuf, MAIN_SEPARATOR},
+    env::{current_dir, current_exe},
+    fs,
+    path::{Path, PathBuf, MAIN_SEPARATOR},
     sync::Arc,
 };
 
 use anyhow::{bail, Context, Result};
-use turbo_tasks::{TransientInstance, TryJoinIterExt, TurboTasks, Value, Vc};
-use turbo_tasks_fs::FileSystem;
-use turbo_tasks_memory::MemoryBackend;
-use turbopack::ecmascript::EcmascriptModuleAsset;
-use turbopack_build::{BuildChunkingContext, MinifyType};
-use turbopack_cli_utils::issue::{ConsoleUi, LogOptions};
+use indexmap::IndexMap;
+use tracing::Instrument;
+use turbo_tasks::{backend::Backend, TransientInstance, TryJoinIterExt, TurboTasks, Value, Vc};
+use turbo_tasks_fs::{DiskFileSystem, FileSystem, FileSystemPath};
+use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64};
+use turbo_tasks_memory::{DiskPersistedGraph, MemoryBackend, MemoryBackendWithPersistedGraph};
+use turbopack::{ecmascript::EcmascriptModuleAsset, type_check::type_check_module_graph};
+use turbopack_build::{
+    module_nomodule_manifest, BuildChunkingContext, EntryChunks, MinifyType, SourceMapsType,
+};
+use turbopack_cli_utils::{
+    build_timings::BuildTimingsHandle,
+    issue::{ConsoleUi, LogOptions},
+};
 use turbopack_core::{
     asset::Asset,
-    chunk::{ChunkableModule, ChunkingContextExt, EvaluatableAssets},
+    chunk::{
+        module_id_strategy::{detect_module_id_collisions, ModuleIdStrategy},
+        ChunkableModule, ChunkingContextExt, EvaluatableAssets,
+    },
+    circular_dependencies::{detect_circular_dependencies, CircularDependenciesOptions},
+    duplicate_packages::detect_duplicate_packages,
     environment::{BrowserEnvironment, Environment, ExecutionEnvironment},
-    issue::{handle_issues, IssueReporter, IssueSeverity},
+    issue::{handle_issues, IssuePolicy, IssueReporter, IssueSeverity},
     module::Module,
-    output::OutputAsset,
+    output::{OutputAsset, OutputAssets},
     reference::all_assets_from_entries,
     reference_type::{EntryReferenceSubType, ReferenceType},
     resolve::{
@@ -26,51 +40,85 @@ use turbopack_core::{
         parse::Request,
     },
 };
-use turbopack_env::dotenv::load_env;
-use turbopack_node::execution_context::ExecutionContext;
+use turbopack_env::dotenv::load_env_for_mode;
+use turbopack_node::{execution_context::ExecutionContext, type_check::TypeCheckOptions};
 
+use self::{
+    analyze::{
+        inlined_value_usages, output_asset_sizes, print_inlined_values_report, print_report,
+    },
+    federation::{
+        container_externals, federation_container, federation_remotes, FederationOptions,
+    },
+};
 use crate::{
-    arguments::BuildArguments,
-    contexts::{get_client_asset_context, get_client_compile_time_info, NodeEnv},
+    arguments::{AnalyzeArguments, BuildArguments, ModuleIdsOption, SourceMapsOption},
+    artifact_store::{ArtifactStore, PersistedGraphArtifact},
+    contexts::{
+        get_client_asset_context, get_client_compile_time_info, get_client_module_options_context,
+        get_client_resolve_options_context, NodeEnv,
+    },
     util::{
         normalize_dirs, normalize_entries, output_fs, project_fs, EntryRequest, EntryRequests,
         NormalizedDirs,
     },
 };
 
+pub(crate) mod analyze;
+pub mod federation;
+
+/// The directory in `dist` which the build for legacy browsers is written to.
+const LEGACY_OUTPUT_DIR: &str = "legacy";
+
 pub fn register() {
     turbopack::register();
     include!(concat!(env!("OUT_DIR"), "/register.rs"));
 }
 
-pub struct TurbopackBuildBuilder {
-    turbo_tasks: Arc<TurboTasks<MemoryBackend>>,
+pub struct TurbopackBuildBuilder<B: Backend + 'static = MemoryBackend> {
+    turbo_tasks: Arc<TurboTasks<B>>,
     project_dir: String,
     root_dir: String,
     entry_requests: Vec<EntryRequest>,
     browserslist_query: String,
+    legacy_browserslist_query: Option<String>,
     log_level: IssueSeverity,
     show_all: bool,
     log_detail: bool,
     minify_type: MinifyType,
+    runtime_chunk: bool,
+    source_maps_type: SourceMapsType,
+    module_id_strategy: ModuleIdStrategy,
+    timings: Option<BuildTimingsHandle>,
+    issue_policy: IssuePolicy,
+    dedupe_packages: bool,
+    circular_dependencies: Option<CircularDependenciesOptions>,
+    type_check: Option<TypeCheckOptions>,
+    federation: Option<FederationOptions>,
 }
 
-impl TurbopackBuildBuilder {
-    pub fn new(
-        turbo_tasks: Arc<TurboTasks<MemoryBackend>>,
-        project_dir: String,
-        root_dir: String,
-    ) -> Self {
+impl<B: Backend + 'static> TurbopackBuildBuilder<B> {
+    pub fn new(turbo_tasks: Arc<TurboTasks<B>>, project_dir: String, root_dir: String) -> Self {
         TurbopackBuildBuilder {
             turbo_tasks,
             project_dir,
             root_dir,
             entry_requests: vec![],
             browserslist_query: "chrome 64, edge 79, firefox 67, opera 51, safari 12".to_owned(),
+            legacy_browserslist_query: None,
             log_level: IssueSeverity::Warning,
             show_all: false,
             log_detail: false,
             minify_type: MinifyType::Minify,
+            runtime_chunk: true,
+            source_maps_type: SourceMapsType::Full,
+            module_id_strategy: ModuleIdStrategy::Deterministic,
+            timings: None,
+            issue_policy: Default::default(),
+            dedupe_packages: false,
+            circular_dependencies: None,
+            type_check: None,
+            federation: None,
         }
     }
 
@@ -84,6 +132,15 @@ impl TurbopackBuildBuilder {
         self
     }
 
+    /// Additionally builds the entries for the legacy browsers matching
+    /// `browserslist_query`, e.g. `ie 11`, into `dist/legacy`. The entry chunks
+    /// of both builds are listed in `dist/build-manifest.json`, for
+    /// `<script type="module">` and `<script nomodule>` tags.
+    pub fn legacy_browserslist_query(mut self, browserslist_query: String) -> Self {
+        self.legacy_browserslist_query = Some(browserslist_query);
+        self
+    }
+
     pub fn log_level(mut self, log_level: IssueSeverity) -> Self {
         self.log_level = log_level;
         self
@@ -104,9 +161,85 @@ impl TurbopackBuildBuilder {
         self
     }
 
+    /// Whether entry chunks load the runtime from a shared runtime chunk, see
+    /// [turbopack_build::BuildChunkingContextBuilder::runtime_chunk].
+    pub fn runtime_chunk(mut self, runtime_chunk: bool) -> Self {
+        self.runtime_chunk = runtime_chunk;
+        self
+    }
+
+    pub fn source_maps_type(mut self, source_maps_type: SourceMapsType) -> Self {
+        self.source_maps_type = source_maps_type;
+        self
+    }
+
+    pub fn module_id_strategy(mut self, module_id_strategy: ModuleIdStrategy) -> Self {
+        self.module_id_strategy = module_id_strategy;
+        self
+    }
+
+    pub fn issue_policy(mut self, issue_policy: IssuePolicy) -> Self {
+        self.issue_policy = issue_policy;
+        self
+    }
+
+    /// Resolves packages which are installed multiple times to their highest
+    /// version instead of the nearest one.
+    pub fn dedupe_packages(mut self, dedupe_packages: bool) -> Self {
+        self.dedupe_packages = dedupe_packages;
+        self
+    }
+
+    /// Reports import cycles in the module graph as issues.
+    pub fn circular_dependencies(mut self, options: CircularDependenciesOptions) -> Self {
+        self.circular_dependencies = Some(options);
+        self
+    }
+
+    /// Type checks the TypeScript modules of the module graph and reports the
+    /// diagnostics as issues.
+    pub fn type_check(mut self, options: TypeCheckOptions) -> Self {
+        self.type_check = Some(options);
+        self
+    }
+
+    /// Loads modules from module federation containers at runtime, or emits a
+    /// container of the exposed modules, see [FederationOptions].
+    pub fn federation(mut self, options: FederationOptions) -> Self {
+        self.federation = Some(options);
+        self
+    }
+
+    /// Prints the
+    /// [BuildTimings](turbopack_cli_utils::build_timings::BuildTimings)
+    /// recorded by the handle's layer after the build.
+    pub fn timings(mut self, timings: BuildTimingsHandle) -> Self {
+        self.timings = Some(timings);
+        self
+    }
+
+    /// Builds the application and writes the output assets to disk.
     pub async fn build(self) -> Result<()> {
+        self.run(BuildMode::Emit).await
+    }
+
+    /// Builds the application and prints a report of the output assets and
+    /// their sizes instead of writing them to disk.
+    pub async fn analyze(self, json: bool) -> Result<()> {
+        self.run(BuildMode::Analyze { json }).await
+    }
+
+    /// Builds the application and prints a report of the compile-time values
+    /// inlined into the output chunks, without their values. This allows to
+    /// verify that no private values are leaked into browser bundles.
+    pub async fn analyze_inlined_values(self, json: bool) -> Result<()> {
+        self.run(BuildMode::InlinedValues { json }).await
+    }
+
+    async fn run(self, mode: BuildMode) -> Result<()> {
+        let timings = self.timings.clone();
         let task = self.turbo_tasks.spawn_once_task::<(), _>(async move {
-            let build_result = build_internal(
+            let output_assets = build_output_assets(
                 self.project_dir.clone(),
                 self.root_dir,
                 EntryRequests(
@@ -118,12 +251,17 @@ impl TurbopackBuildBuilder {
                 )
                 .cell(),
                 self.browserslist_query,
+                self.legacy_browserslist_query,
                 self.minify_type,
+                self.runtime_chunk,
+                self.source_maps_type,
+                self.module_id_strategy,
+                self.dedupe_packages,
+                self.circular_dependencies.map(|options| options.cell()),
+                self.type_check.map(|options| options.cell()),
+                self.federation.map(|options| options.cell()),
             );
 
-            // Await the result to propagate any errors.
-            build_result.await?;
-
             let issue_reporter: Vc<Box<dyn IssueReporter>> =
                 Vc::upcast(ConsoleUi::new(TransientInstance::new(LogOptions {
                     project_dir: PathBuf::from(self.project_dir),
@@ -131,34 +269,181 @@ impl TurbopackBuildBuilder {
                     show_all: self.show_all,
                     log_detail: self.log_detail,
                     log_level: self.log_level,
+                    issue_policy: self.issue_policy,
                 })));
 
-            handle_issues(
-                build_result,
-                issue_reporter,
-                IssueSeverity::Error.into(),
-                None,
-                None,
-            )
-            .await?;
+            match mode {
+                BuildMode::Emit => {
+                    let build_result = emit_assets(output_assets);
+
+                    // Await the result to propagate any errors.
+                    build_result.await?;
+
+                    handle_issues(
+                        build_result,
+                        issue_reporter,
+                        IssueSeverity::Error.into(),
+                        None,
+                        None,
+                    )
+                    .await?;
+                }
+                BuildMode::Analyze { json } => {
+                    let sizes = output_asset_sizes(output_assets);
+
+                    // Await the result to propagate any errors.
+                    let report = sizes.await?;
+
+                    handle_issues(
+                        sizes,
+                        issue_reporter,
+                        IssueSeverity::Error.into(),
+                        None,
+                        None,
+                    )
+                    .await?;
+
+                    print_report(&report, json)?;
+                }
+                BuildMode::InlinedValues { json } => {
+                    let usages = inlined_value_usages(output_assets);
+
+                    // Await the result to propagate any errors.
+                    let report = usages.await?;
+
+                    handle_issues(
+                        usages,
+                        issue_reporter,
+                        IssueSeverity::Error.into(),
+                        None,
+                        None,
+                    )
+                    .await?;
+
+                    print_inlined_values_report(&report, json)?;
+                }
+            }
 
             Ok(Default::default())
         });
 
         self.turbo_tasks.wait_task_completion(task, true).await?;
+        // Stopping writes the persistent cache, if there is one.
+        self.turbo_tasks.stop_and_wait().await;
+
+        if let Some(timings) = timings {
+            // Keep stdout clean for reports, e.g. `analyze --json`.
+            eprint!("{}", timings.take());
+        }
 
         Ok(())
     }
 }
 
+enum BuildMode {
+    Emit,
+    Analyze { json: bool },
+    InlinedValues { json: bool },
+}
+
+/// Computes all output assets of the build, without writing them to disk.
 #[turbo_tasks::function]
-async fn build_internal(
+async fn build_output_assets(
     project_dir: String,
     root_dir: String,
     entry_requests: Vc<EntryRequests>,
     browserslist_query: String,
+    legacy_browserslist_query: Option<String>,
     minify_type: MinifyType,
-) -> Result<Vc<()>> {
+    runtime_chunk: bool,
+    source_maps_type: SourceMapsType,
+    module_id_strategy: ModuleIdStrategy,
+    dedupe_packages: bool,
+    circular_dependencies: Option<Vc<CircularDependenciesOptions>>,
+    type_check: Option<Vc<TypeCheckOptions>>,
+    federation: Option<Vc<FederationOptions>>,
+) -> Result<Vc<OutputAssets>> {
+    let output_fs = output_fs(project_dir.clone());
+    let build_output_root = output_fs.root().join("dist".to_string());
+
+    let modern = build_target_output(
+        project_dir.clone(),
+        root_dir.clone(),
+        entry_requests,
+        browserslist_query,
+        build_output_root,
+        minify_type,
+        runtime_chunk,
+        source_maps_type,
+        module_id_strategy,
+        dedupe_packages,
+        circular_dependencies,
+        type_check,
+        federation,
+    );
+    let Some(legacy_browserslist_query) = legacy_browserslist_query else {
+        return Ok(modern.await?.assets);
+    };
+
+    // Each target has its own asset context, so its module graph is resolved
+    // and transformed separately, and only reading the sources is shared.
+    // Type checking doesn't depend on the target, so it's only done once.
+    let legacy = build_target_output(
+        project_dir,
+        root_dir,
+        entry_requests,
+        legacy_browserslist_query,
+        build_output_root.join(LEGACY_OUTPUT_DIR.to_string()),
+        minify_type,
+        runtime_chunk,
+        source_maps_type,
+        module_id_strategy,
+        dedupe_packages,
+        circular_dependencies,
+        None,
+        federation,
+    );
+
+    let modern = modern.await?;
+    let legacy = legacy.await?;
+    let mut assets = modern.assets.await?.clone_value();
+    assets.extend(legacy.assets.await?.iter().copied());
+    assets.push(module_nomodule_manifest(
+        build_output_root.join("build-manifest.json".to_string()),
+        modern.entry_chunks,
+        legacy.entry_chunks,
+    ));
+
+    Ok(Vc::cell(assets))
+}
+
+/// The output of the build for one browserslist query.
+#[turbo_tasks::value]
+struct TargetOutput {
+    assets: Vc<OutputAssets>,
+    /// The entry chunk of each EcmaScript entry, by the file stem of the
+    /// entry module.
+    entry_chunks: Vc<EntryChunks>,
+}
+
+/// Builds the entries for the browsers matching `browserslist_query` into
+/// `build_output_root`.
+#[turbo_tasks::function]
+async fn build_target_output(
+    project_dir: String,
+    root_dir: String,
+    entry_requests: Vc<EntryRequests>,
+    browserslist_query: String,
+    build_output_root: Vc<FileSystemPath>,
+    minify_type: MinifyType,
+    runtime_chunk: bool,
+    source_maps_type: SourceMapsType,
+    module_id_strategy: ModuleIdStrategy,
+    dedupe_packages: bool,
+    circular_dependencies: Option<Vc<CircularDependenciesOptions>>,
+    type_check: Option<Vc<TypeCheckOptions>>,
+    federation: Option<Vc<FederationOptions>>,
+) -> Result<Vc<TargetOutput>> {
     let env = Environment::new(Value::new(ExecutionEnvironment::Browser(
         BrowserEnvironment {
             dom: true,
@@ -176,27 +461,44 @@ async fn build_internal(
         .unwrap_or(project_relative)
         .replace(MAIN_SEPARATOR, "/");
     let project_path = project_fs.root().join(project_relative);
-    let build_output_root = output_fs.root().join("dist".to_string());
 
-    let chunking_context = Vc::upcast(
-        BuildChunkingContext::builder(
-            project_path,
-            build_output_root,
-            build_output_root,
-            build_output_root,
-            build_output_root,
-            env,
-        )
-        .minify_type(minify_type)
-        .build(),
-    );
+    let build_chunking_context = BuildChunkingContext::builder(
+        project_path,
+        build_output_root,
+        build_output_root,
+        build_output_root,
+        build_output_root,
+        env,
+    )
+    .minify_type(minify_type)
+    .runtime_chunk(runtime_chunk)
+    .source_maps_type(source_maps_type)
+    .module_id_strategy(module_id_strategy)
+    .build();
+    let chunking_context = Vc::upcast(build_chunking_context);
 
     let node_env = NodeEnv::Production.cell();
-    let compile_time_info = get_client_compile_time_info(browserslist_query, node_env);
-    let execution_context =
-        ExecutionContext::new(project_path, chunking_context, load_env(project_path));
-    let asset_context =
-        get_client_asset_context(project_path, execution_context, compile_time_info, node_env);
+    let env = load_env_for_mode(project_path, NodeEnv::Production.to_string());
+    let compile_time_info = get_client_compile_time_info(browserslist_query, node_env, env);
+    let execution_context = ExecutionContext::new(project_path, chunking_context, env);
+    // Hosts load the modules of their remotes at runtime.
+    let remotes = match federation {
+        Some(options) if !options.await?.remotes.is_empty() => {
+            Some(federation_remotes(project_path, options))
+        }
+        _ => None,
+    };
+    let asset_context = get_client_asset_context(
+        project_path,
+        execution_context,
+        compile_time_info,
+        env,
+        node_env,
+        dedupe_packages,
+        type_check,
+        remotes,
+        None,
+    );
 
     let entry_requests = (*entry_requests
         .await?
@@ -238,75 +540,215 @@ async fn build_internal(
         .try_join()
         .await?;
 
+    // Reports packages which are included multiple times as issues.
+    detect_duplicate_packages(Vc::cell(entries.clone())).await?;
+    // Fails the build when short module ids collide.
+    detect_module_id_collisions(Vc::cell(entries.clone()), module_id_strategy).await?;
+    if let Some(options) = circular_dependencies {
+        detect_circular_dependencies(Vc::cell(entries.clone()), options).await?;
+    }
+    if type_check.is_some() {
+        let module_options_context = get_client_module_options_context(
+            project_path,
+            execution_context,
+            compile_time_info.environment(),
+            env,
+            node_env,
+            dedupe_packages,
+            type_check,
+        );
+        type_check_module_graph(Vc::cell(entries.clone()), module_options_context).await?;
+    }
+
     let entry_chunk_groups = entries
         .into_iter()
         .map(|entry_module| async move {
-            Ok(
-                if let Some(ecmascript) =
-                    Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(entry_module).await?
-                {
-                    Vc::cell(vec![Vc::try_resolve_downcast_type::<BuildChunkingContext>(
-                        chunking_context,
-                    )
-                    .await?
-                    .unwrap()
-                    .entry_chunk_group(
-                        build_output_root
-                            .join(
-                                ecmascript
-                                    .ident()
-                                    .path()
-                                    .file_stem()
-                                    .await?
-                                    .as_deref()
-                                    .unwrap()
-                                    .to_string(),
-                            )
-                            .with_extension("entry.js".to_string()),
-                        Vc::upcast(ecmascript),
-                        EvaluatableAssets::one(Vc::upcast(ecmascript)),
-                    )])
-                } else if let Some(chunkable) =
-                    Vc::try_resolve_sidecast::<Box<dyn ChunkableModule>>(entry_module).await?
-                {
-                    chunking_context.root_chunk_group(chunkable)
-                } else {
-                    // TODO convert into a serve-able asset
-                    bail!(
-                        "Entry module is not chunkable, so it can't be used to bootstrap the \
-                         application"
-                    )
-                },
-            )
+            let name = entry_module
+                .ident()
+                .path()
+                .file_stem()
+                .await?
+                .as_deref()
+                .unwrap_or_default()
+                .to_string();
+            let (chunk_group, entry_chunk) = if let Some(ecmascript) =
+                Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(entry_module).await?
+            {
+                let entry_chunk =
+                    Vc::try_resolve_downcast_type::<BuildChunkingContext>(chunking_context)
+                        .await?
+                        .unwrap()
+                        .entry_chunk_group(
+                            build_output_root
+                                .join(name.clone())
+                                .with_extension("entry.js".to_string()),
+                            Vc::upcast(ecmascript),
+                            EvaluatableAssets::one(Vc::upcast(ecmascript)),
+                        );
+                (Vc::cell(vec![entry_chunk]), Some(entry_chunk))
+            } else if let Some(chunkable) =
+                Vc::try_resolve_sidecast::<Box<dyn ChunkableModule>>(entry_module).await?
+            {
+                (chunking_context.root_chunk_group(chunkable), None)
+            } else {
+                // TODO convert into a serve-able asset
+                bail!(
+                    "Entry module is not chunkable, so it can't be used to bootstrap the \
+                     application"
+                )
+            };
+            Ok((name, chunk_group, entry_chunk))
         })
         .try_join()
         .await?;
 
     let mut chunks: HashSet<Vc<Box<dyn OutputAsset>>> = HashSet::new();
-    for chunk_group in entry_chunk_groups {
+    let mut entry_chunks = IndexMap::new();
+    for (name, chunk_group, entry_chunk) in entry_chunk_groups {
         chunks.extend(&*all_assets_from_entries(chunk_group).await?);
+        if let Some(entry_chunk) = entry_chunk {
+            entry_chunks.insert(name, entry_chunk);
+        }
     }
 
-    chunks
+    if let Some(options) = federation {
+        if !options.await?.exposes.is_empty() {
+            // The exposed modules and the fallbacks of the shared packages are
+            // bundled without the shared packages, which the container
+            // provides once the versions to use have been negotiated.
+            let container_context = get_client_asset_context(
+                project_path,
+                execution_context,
+                compile_time_info,
+                env,
+                node_env,
+                dedupe_packages,
+                None,
+                None,
+                Some(container_externals(options)),
+            );
+            let container = federation_container(
+                project_path,
+                container_context,
+                get_client_resolve_options_context(project_path, env, dedupe_packages, None, None),
+                build_chunking_context,
+                build_output_root,
+                options,
+            );
+            chunks.extend(&*all_assets_from_entries(Vc::cell(vec![container])).await?);
+        }
+    }
+
+    Ok(TargetOutput {
+        assets: Vc::cell(chunks.into_iter().collect()),
+        entry_chunks: Vc::cell(entry_chunks),
+    }
+    .cell())
+}
+
+#[turbo_tasks::function]
+async fn emit_assets(output_assets: Vc<OutputAssets>) -> Result<Vc<()>> {
+    output_assets
+        .await?
         .iter()
         .map(|c| c.content().write(c.ident().path()))
         .try_join()
+        .instrument(tracing::info_span!("emit"))
         .await?;
 
     Ok(Default::default())
 }
 
-pub async fn build(args: &BuildArguments) -> Result<()> {
-    let NormalizedDirs {
-        project_dir,
-        root_dir,
-    } = normalize_dirs(&args.common.dir, &args.common.root)?;
+pub async fn build(args: &BuildArguments, timings: Option<BuildTimingsHandle>) -> Result<()> {
+    match persisted_graph(args)? {
+        Some(graph) => {
+            let tt = TurboTasks::new(MemoryBackendWithPersistedGraph::new(graph));
+            builder(tt, args, timings)?.build().await
+        }
+        None => {
+            let tt = memory_turbo_tasks(args);
+            builder(tt, args, timings)?.build().await
+        }
+    }
+}
+
+/// Builds the application like `build`, but reports the output assets instead
+/// of writing them.
+pub async fn analyze(args: &AnalyzeArguments, timings: Option<BuildTimingsHandle>) -> Result<()> {
+    match persisted_graph(&args.build)? {
+        Some(graph) => {
+            let tt = TurboTasks::new(MemoryBackendWithPersistedGraph::new(graph));
+            analyze_with(builder(tt, &args.build, timings)?, args).await
+        }
+        None => {
+            let tt = memory_turbo_tasks(&args.build);
+            analyze_with(builder(tt, &args.build, timings)?, args).await
+        }
+    }
+}
 
-    let tt = TurboTasks::new(MemoryBackend::new(
+async fn analyze_with<B: Backend + 'static>(
+    builder: TurbopackBuildBuilder<B>,
+    args: &AnalyzeArguments,
+) -> Result<()> {
+    if args.inlined_values {
+        builder.analyze_inlined_values(args.json).await
+    } else {
+        builder.analyze(args.json).await
+    }
+}
+
+fn memory_turbo_tasks(args: &BuildArguments) -> Arc<TurboTasks<MemoryBackend>> {
+    TurboTasks::new(MemoryBackend::new(
         args.common
             .memory_limit
             .map_or(usize::MAX, |l| l * 1024 * 1024),
-    ));
+    ))
+}
+
+/// Opens the persistent cache of the project in the shared [ArtifactStore],
+/// when `--persistent-caching` is enabled.
+fn persisted_graph(args: &BuildArguments) -> Result<Option<DiskPersistedGraph>> {
+    if !args.persistent_caching {
+        return Ok(None);
+    }
+    let store = ArtifactStore::open(ArtifactStore::default_root()?)?;
+    let index = args
+        .common
+        .dir
+        .as_deref()
+        .unwrap_or_else(|| Path::new("."))
+        .join(".turbopack")
+        .join("cache");
+    let graph = DiskPersistedGraph::with_storage(
+        Box::new(PersistedGraphArtifact::new(store, index)),
+        format!(
+            "turbopack-cli {} {}",
+            env!("CARGO_PKG_VERSION"),
+            build_id()?
+        ),
+    )?
+    .with_volatile_functions(DiskFileSystem::volatile_functions());
+    Ok(Some(graph))
+}
+
+/// Identifies the binary, since a rebuilt binary can change the functions
+/// whose results the persistent cache contains without changing the version.
+fn build_id() -> Result<String> {
+    let path = current_exe().context("unable to locate the current executable")?;
+    let binary = fs::read(&path).with_context(|| format!("unable to read {}", path.display()))?;
+    Ok(encode_hex(hash_xxh3_hash64(binary.as_slice())))
+}
+
+fn builder<B: Backend + 'static>(
+    tt: Arc<TurboTasks<B>>,
+    args: &BuildArguments,
+    timings: Option<BuildTimingsHandle>,
+) -> Result<TurbopackBuildBuilder<B>> {
+    let NormalizedDirs {
+        project_dir,
+        root_dir,
+    } = normalize_dirs(&args.common.dir, &args.common.root)?;
 
     let mut builder = TurbopackBuildBuilder::new(tt, project_dir, root_dir)
         .log_detail(args.common.log_detail)
@@ -320,13 +762,61 @@ pub async fn build(args: &BuildArguments) -> Result<()> {
         } else {
             MinifyType::Minify
         })
-        .show_all(args.common.show_all);
+        .runtime_chunk(!args.no_runtime_chunk)
+        .source_maps_type(match args.source_maps.unwrap_or(SourceMapsOption::Full) {
+            SourceMapsOption::Full => SourceMapsType::Full,
+            SourceMapsOption::Cheap => SourceMapsType::Cheap,
+            SourceMapsOption::Inline => SourceMapsType::Inline,
+            SourceMapsOption::Hidden => SourceMapsType::Hidden,
+            SourceMapsOption::None => SourceMapsType::None,
+        })
+        .module_id_strategy(
+            match args.module_ids.unwrap_or(ModuleIdsOption::Deterministic) {
+                ModuleIdsOption::Path => ModuleIdStrategy::FullPath,
+                ModuleIdsOption::Hashed => ModuleIdStrategy::Hashed,
+                ModuleIdsOption::Deterministic => ModuleIdStrategy::Deterministic,
+            },
+        )
+        .show_all(args.common.show_all)
+        .issue_policy(IssuePolicy {
+            warnings_as_errors: args.warnings_as_errors,
+            error_codes: args.error_codes.clone(),
+            allowed_codes: args.allowed_codes.clone(),
+        })
+        .dedupe_packages(args.dedupe_packages);
+
+    if let Some(browserslist) = &args.legacy_browserslist {
+        builder = builder.legacy_browserslist_query(browserslist.clone());
+    }
+
+    if let Some(severity) = args.circular_dependencies {
+        builder = builder.circular_dependencies(CircularDependenciesOptions {
+            severity: severity.0,
+            allow: args.allowed_circular_dependencies.clone(),
+        });
+    }
+
+    if args.type_check {
+        builder = builder.type_check(TypeCheckOptions {
+            tsconfig: args.tsconfig.clone(),
+        });
+    }
+
+    if let Some(browserslist) = &args.common.browserslist {
+        builder = builder.browserslist_query(browserslist.clone());
+    }
+
+    if let Some(federation) = &args.federation {
+        builder = builder.federation(federation.clone());
+    }
+
+    if let Some(timings) = timings {
+        builder = builder.timings(timings);
+    }
 
     for entry in normalize_entries(&args.common.entries) {
         builder = builder.entry_request(EntryRequest::Relative(entry));
     }
 
-    builder.build().await?;
-
-    Ok(())
+    Ok(builder)
 }
@@ -0,0 +1,205 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use turbopack_cli_utils::issue::IssueSeverityCliOption;
+
+use crate::{
+    arguments::{Arguments, BuildArguments, CommonArguments, ModuleIdsOption, SourceMapsOption},
+    build::federation::FederationOptions,
+};
+
+/// The name of the config file which is loaded from the application directory
+/// when no `--config` is passed.
+pub const CONFIG_FILE_NAME: &str = "turbopack.config.json";
+
+/// Project configuration loaded from a JSON config file.
+///
+/// Every option is a default for the command line argument of the same name,
+/// i.e. arguments passed on the command line take precedence.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TurbopackConfig {
+    /// The entrypoints of the project. Resolved relative to the project's
+    /// directory.
+    pub entries: Option<Vec<String>>,
+
+    /// The root directory of the project. Resolved relative to the config
+    /// file.
+    pub root: Option<PathBuf>,
+
+    /// The browserslist query of the targeted browsers.
+    pub browserslist: Option<String>,
+
+    /// The browserslist query of the legacy browsers which builds
+    /// additionally target with `<script nomodule>` output.
+    pub legacy_browserslist: Option<String>,
+
+    /// The memory limit in MB for garbage collection.
+    pub memory_limit: Option<usize>,
+
+    /// Whether build output is minified.
+    pub minify: Option<bool>,
+
+    /// Whether the entry chunks of builds load the runtime from a shared
+    /// runtime chunk.
+    pub runtime_chunk: Option<bool>,
+
+    /// How source maps are emitted in builds.
+    pub source_maps: Option<SourceMapsOption>,
+
+    /// How module ids are generated in builds.
+    pub module_ids: Option<ModuleIdsOption>,
+
+    /// Whether builds report all warnings as errors.
+    pub warnings_as_errors: Option<bool>,
+
+    /// Codes of warnings which builds report as errors.
+    pub error_codes: Option<Vec<String>>,
+
+    /// Codes of warnings which builds keep reporting as warnings when
+    /// `warningsAsErrors` is set.
+    pub allowed_codes: Option<Vec<String>>,
+
+    /// Whether builds resolve packages which are installed multiple times to
+    /// their highest version.
+    pub dedupe_packages: Option<bool>,
+
+    /// The severity of the issues builds report for import cycles. Cycles
+    /// are not reported when unset.
+    pub circular_dependencies: Option<IssueSeverityCliOption>,
+
+    /// Globs of modules whose import cycles builds don't report.
+    pub allowed_circular_dependencies: Option<Vec<String>>,
+
+    /// Whether builds type check the TypeScript modules.
+    pub type_check: Option<bool>,
+
+    /// The `tsconfig.json` used to type check builds.
+    pub tsconfig: Option<String>,
+
+    /// The modules which builds expose to or load from other builds at
+    /// runtime with module federation.
+    pub federation: Option<FederationOptions>,
+}
+
+impl TurbopackConfig {
+    /// Loads the config file referenced by the arguments. A missing
+    /// `turbopack.config.json` results in an empty config, while a missing
+    /// file passed with `--config` is an error.
+    pub fn load(args: &Arguments) -> Result<Self> {
+        let Some(common) = args.common() else {
+            return Ok(Default::default());
+        };
+
+        let (path, required) = match &common.config {
+            Some(path) => (path.clone(), true),
+            None => (
+                common
+                    .dir
+                    .as_deref()
+                    .unwrap_or_else(|| Path::new("."))
+                    .join(CONFIG_FILE_NAME),
+                false,
+            ),
+        };
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if !required && err.kind() == ErrorKind::NotFound => {
+                return Ok(Default::default());
+            }
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("unable to read config file {}", path.display()));
+            }
+        };
+
+        let mut config: TurbopackConfig = serde_json::from_str(&content)
+            .with_context(|| format!("unable to parse config file {}", path.display()))?;
+
+        if let (Some(root), Some(config_dir)) = (&mut config.root, path.parent()) {
+            *root = config_dir.join(&*root);
+        }
+
+        Ok(config)
+    }
+
+    /// Fills in the arguments which were not passed on the command line.
+    pub fn apply(self, args: &mut Arguments) {
+        match args {
+            Arguments::Build(args) => self.apply_build(args),
+            Arguments::Dev(args) => self.apply_common(&mut args.common),
+            Arguments::Analyze(args) => self.apply_build(&mut args.build),
+            Arguments::Gc(_) => {}
+        }
+    }
+
+    fn apply_build(self, args: &mut BuildArguments) {
+        if self.minify == Some(false) {
+            args.no_minify = true;
+        }
+        if self.runtime_chunk == Some(false) {
+            args.no_runtime_chunk = true;
+        }
+        if args.source_maps.is_none() {
+            args.source_maps = self.source_maps;
+        }
+        if args.module_ids.is_none() {
+            args.module_ids = self.module_ids;
+        }
+        if self.warnings_as_errors == Some(true) {
+            args.warnings_as_errors = true;
+        }
+        if args.error_codes.is_empty() {
+            args.error_codes = self.error_codes.clone().unwrap_or_default();
+        }
+        if args.allowed_codes.is_empty() {
+            args.allowed_codes = self.allowed_codes.clone().unwrap_or_default();
+        }
+        if self.dedupe_packages == Some(true) {
+            args.dedupe_packages = true;
+        }
+        if args.legacy_browserslist.is_none() {
+            args.legacy_browserslist = self.legacy_browserslist.clone();
+        }
+        if args.circular_dependencies.is_none() {
+            args.circular_dependencies = self.circular_dependencies;
+        }
+        if args.allowed_circular_dependencies.is_empty() {
+            args.allowed_circular_dependencies = self
+                .allowed_circular_dependencies
+                .clone()
+                .unwrap_or_default();
+        }
+        if self.type_check == Some(true) {
+            args.type_check = true;
+        }
+        if args.tsconfig.is_none() {
+            args.tsconfig = self.tsconfig.clone();
+        }
+        if args.federation.is_none() {
+            args.federation = self.federation.clone();
+        }
+        self.apply_common(&mut args.common);
+    }
+
+    fn apply_common(self, common: &mut CommonArguments) {
+        if common.entries.is_none() {
+            common.entries = self.entries;
+        }
+        if common.root.is_none() {
+            common.root = self.root;
+        }
+        if common.browserslist.is_none() {
+            common.browserslist = self.browserslist;
+        }
+        if common.memory_limit.is_none() {
+            common.memory_limit = self.memory_limit;
+        }
+    }
+}
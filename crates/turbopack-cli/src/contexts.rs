@@ -2,19 +2,23 @@ use std::{collections::HashMap, fmt};
 
 use anyhow::Result;
 use turbo_tasks::{Value, Vc};
+use turbo_tasks_env::{FilterProcessEnv, ProcessEnv};
 use turbo_tasks_fs::{FileSystem, FileSystemPath};
 use turbopack::{
     condition::ContextCondition,
+    federation::FederationRemotes,
     module_options::{CustomEcmascriptTransformPlugins, JsxTransformOptions, ModuleOptionsContext},
     resolve_options_context::ResolveOptionsContext,
     ModuleAssetContext,
 };
 use turbopack_core::{
     compile_time_defines,
-    compile_time_info::{CompileTimeDefines, CompileTimeInfo},
+    compile_time_info::{
+        CompileTimeDefineValue, CompileTimeDefines, CompileTimeInfo, ImportMetaEnv, ImportMetaUrl,
+    },
     context::AssetContext,
     environment::{BrowserEnvironment, Environment, ExecutionEnvironment},
-    resolve::options::{ImportMap, ImportMapping},
+    resolve::options::{Externals, ImportMap, ImportMapping},
 };
 use turbopack_dev::react_refresh::assert_can_resolve_react_refresh;
 use turbopack_ecmascript_plugins::transform::{
@@ -22,7 +26,16 @@ use turbopack_ecmascript_plugins::transform::{
     styled_components::{StyledComponentsTransformConfig, StyledComponentsTransformer},
     styled_jsx::StyledJsxTransformer,
 };
-use turbopack_node::execution_context::ExecutionContext;
+use turbopack_env::{
+    defines::{import_meta_env, process_env_defines},
+    RuntimeConfigAsset,
+};
+use turbopack_node::{execution_context::ExecutionContext, type_check::TypeCheckOptions};
+
+/// Env variables with this prefix, e.g. from `.env` files, are exposed to
+/// client code through `process.env`, `import.meta.env` and the
+/// `runtime-config` module.
+pub const PUBLIC_ENV_PREFIX: &str = "TURBOPACK_PUBLIC_";
 
 #[turbo_tasks::value(shared)]
 pub enum NodeEnv {
@@ -43,8 +56,19 @@ async fn foreign_code_context_condition() -> Result<ContextCondition> {
     Ok(ContextCondition::InDirectory("node_modules".to_string()))
 }
 
+/// The env variables which are exposed to client code, see [PUBLIC_ENV_PREFIX].
+fn public_env(process_env: Vc<Box<dyn ProcessEnv>>) -> Vc<Box<dyn ProcessEnv>> {
+    Vc::upcast(FilterProcessEnv::new(
+        process_env,
+        vec![PUBLIC_ENV_PREFIX.to_string()],
+    ))
+}
+
 #[turbo_tasks::function]
-pub async fn get_client_import_map(project_path: Vc<FileSystemPath>) -> Result<Vc<ImportMap>> {
+pub async fn get_client_import_map(
+    project_path: Vc<FileSystemPath>,
+    process_env: Vc<Box<dyn ProcessEnv>>,
+) -> Result<Vc<ImportMap>> {
     let mut import_map = ImportMap::empty();
 
     import_map.insert_singleton_alias("@swc/helpers", project_path);
@@ -61,20 +85,40 @@ pub async fn get_client_import_map(project_path: Vc<FileSystemPath>) -> Result<V
         .cell(),
     );
 
+    // `import config from "runtime-config"` exposes the public env variables,
+    // which the server can override at render time.
+    let public_env = public_env(process_env);
+    let public_keys = public_env.read_all().await?.keys().cloned().collect();
+    import_map.insert_exact_alias(
+        "runtime-config",
+        RuntimeConfigAsset::new(project_path, "client".to_string(), public_keys, public_env)
+            .import_mapping(),
+    );
+
     Ok(import_map.cell())
 }
 
+/// The resolve options of client modules. `federation_remotes` are the module
+/// federation containers which a host loads modules from, and `externals` the
+/// shared packages which a container provides to its exposed modules.
 #[turbo_tasks::function]
 pub async fn get_client_resolve_options_context(
     project_path: Vc<FileSystemPath>,
+    process_env: Vc<Box<dyn ProcessEnv>>,
+    dedupe_packages: bool,
+    federation_remotes: Option<Vc<FederationRemotes>>,
+    externals: Option<Vc<Externals>>,
 ) -> Result<Vc<ResolveOptionsContext>> {
-    let next_client_import_map = get_client_import_map(project_path);
+    let next_client_import_map = get_client_import_map(project_path, process_env);
     let module_options_context = ResolveOptionsContext {
         enable_node_modules: Some(project_path.root().resolve().await?),
         custom_conditions: vec!["development".to_string()],
         import_map: Some(next_client_import_map),
         browser: true,
         module: true,
+        dedupe_packages,
+        federation_remotes,
+        externals,
         ..Default::default()
     };
     Ok(ResolveOptionsContext {
@@ -90,11 +134,14 @@ pub async fn get_client_resolve_options_context(
 }
 
 #[turbo_tasks::function]
-async fn get_client_module_options_context(
+pub async fn get_client_module_options_context(
     project_path: Vc<FileSystemPath>,
     execution_context: Vc<ExecutionContext>,
     env: Vc<Environment>,
+    process_env: Vc<Box<dyn ProcessEnv>>,
     node_env: Vc<NodeEnv>,
+    dedupe_packages: bool,
+    type_check: Option<Vc<TypeCheckOptions>>,
 ) -> Result<Vc<ModuleOptionsContext>> {
     let module_options_context = ModuleOptionsContext {
         preset_env_versions: Some(env),
@@ -102,7 +149,8 @@ async fn get_client_module_options_context(
         ..Default::default()
     };
 
-    let resolve_options_context = get_client_resolve_options_context(project_path);
+    let resolve_options_context =
+        get_client_resolve_options_context(project_path, process_env, dedupe_packages, None, None);
 
     let enable_react_refresh = matches!(*node_env.await?, NodeEnv::Development)
         && assert_can_resolve_react_refresh(project_path, resolve_options_context)
@@ -130,6 +178,7 @@ async fn get_client_module_options_context(
                 Vc::cell(Box::new(StyledJsxTransformer::new()) as _),
             ],
             output_transforms: vec![],
+            analyzers: vec![],
         },
     ));
 
@@ -142,6 +191,7 @@ async fn get_client_module_options_context(
             module_options_context.clone().cell(),
         )],
         custom_ecma_transform_plugins,
+        enable_type_checking: type_check,
         ..module_options_context
     }
     .cell();
@@ -154,14 +204,28 @@ pub fn get_client_asset_context(
     project_path: Vc<FileSystemPath>,
     execution_context: Vc<ExecutionContext>,
     compile_time_info: Vc<CompileTimeInfo>,
+    process_env: Vc<Box<dyn ProcessEnv>>,
     node_env: Vc<NodeEnv>,
+    dedupe_packages: bool,
+    type_check: Option<Vc<TypeCheckOptions>>,
+    federation_remotes: Option<Vc<FederationRemotes>>,
+    externals: Option<Vc<Externals>>,
 ) -> Vc<Box<dyn AssetContext>> {
-    let resolve_options_context = get_client_resolve_options_context(project_path);
+    let resolve_options_context = get_client_resolve_options_context(
+        project_path,
+        process_env,
+        dedupe_packages,
+        federation_remotes,
+        externals,
+    );
     let module_options_context = get_client_module_options_context(
         project_path,
         execution_context,
         compile_time_info.environment(),
+        process_env,
         node_env,
+        dedupe_packages,
+        type_check,
     );
 
     let asset_context: Vc<Box<dyn AssetContext>> = Vc::upcast(ModuleAssetContext::new(
@@ -175,20 +239,46 @@ pub fn get_client_asset_context(
     asset_context
 }
 
-fn client_defines(node_env: &NodeEnv) -> Vc<CompileTimeDefines> {
+fn client_defines(node_env: &NodeEnv) -> CompileTimeDefines {
     compile_time_defines!(
         process.turbopack = true,
         process.env.TURBOPACK = true,
         process.env.NODE_ENV = node_env.to_string()
     )
-    .cell()
+}
+
+/// The `import.meta.env` of client modules, like Vite's.
+fn client_import_meta_env(node_env: &NodeEnv) -> ImportMetaEnv {
+    let dev = matches!(node_env, NodeEnv::Development);
+    ImportMetaEnv(
+        [
+            ("MODE", CompileTimeDefineValue::from(node_env.to_string())),
+            ("DEV", dev.into()),
+            ("PROD", (!dev).into()),
+            ("SSR", false.into()),
+            ("BASE_URL", "/".into()),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect(),
+    )
 }
 
 #[turbo_tasks::function]
 pub async fn get_client_compile_time_info(
     browserslist_query: String,
     node_env: Vc<NodeEnv>,
+    env: Vc<Box<dyn ProcessEnv>>,
 ) -> Result<Vc<CompileTimeInfo>> {
+    let node_env = &*node_env.await?;
+    let public_env = public_env(env);
+
+    // The built-in values take precedence over the env variables.
+    let mut defines = process_env_defines(public_env).await?.clone_value();
+    defines.extend(client_defines(node_env).0);
+    let mut meta_env = import_meta_env(public_env).await?.clone_value();
+    meta_env.extend(client_import_meta_env(node_env).0);
+
     Ok(
         CompileTimeInfo::builder(Environment::new(Value::new(ExecutionEnvironment::Browser(
             BrowserEnvironment {
@@ -199,7 +289,9 @@ pub async fn get_client_compile_time_info(
             }
             .into(),
         ))))
-        .defines(client_defines(&*node_env.await?))
+        .defines(Vc::cell(defines))
+        .import_meta_url(ImportMetaUrl::Served)
+        .import_meta_env(Vc::cell(meta_env))
         .cell(),
     )
 }
@@ -35,7 +35,7 @@ use turbopack_dev_server::{
     },
     DevServer, DevServerBuilder,
 };
-use turbopack_env::dotenv::load_env;
+use turbopack_env::dotenv::load_env_for_mode;
 use turbopack_node::execution_context::ExecutionContext;
 
 use self::web_entry_source::create_web_entry_source;
@@ -56,6 +56,8 @@ pub struct TurbopackDevServerBuilder {
     root_dir: String,
     entry_requests: Vec<EntryRequest>,
     eager_compile: bool,
+    lazy_compilation: bool,
+    runtime_chunk: bool,
     hostname: Option<IpAddr>,
     issue_reporter: Option<Box<dyn IssueReporterProvider>>,
     port: Option<u16>,
@@ -78,6 +80,8 @@ impl TurbopackDevServerBuilder {
             root_dir,
             entry_requests: vec![],
             eager_compile: false,
+            lazy_compilation: false,
+            runtime_chunk: false,
             hostname: None,
             issue_reporter: None,
             port: None,
@@ -101,6 +105,18 @@ impl TurbopackDevServerBuilder {
         self
     }
 
+    pub fn lazy_compilation(mut self, lazy_compilation: bool) -> TurbopackDevServerBuilder {
+        self.lazy_compilation = lazy_compilation;
+        self
+    }
+
+    /// Serves the runtime as a separate chunk which is shared by all entries,
+    /// see [turbopack_dev::DevChunkingContextBuilder::runtime_chunk].
+    pub fn runtime_chunk(mut self, runtime_chunk: bool) -> TurbopackDevServerBuilder {
+        self.runtime_chunk = runtime_chunk;
+        self
+    }
+
     pub fn hostname(mut self, hostname: IpAddr) -> TurbopackDevServerBuilder {
         self.hostname = Some(hostname);
         self
@@ -194,6 +210,8 @@ impl TurbopackDevServerBuilder {
         let project_dir = self.project_dir;
         let root_dir = self.root_dir;
         let eager_compile = self.eager_compile;
+        let lazy_compilation = self.lazy_compilation;
+        let runtime_chunk = self.runtime_chunk;
         let show_all = self.show_all;
         let log_detail = self.log_detail;
         let browserslist_query = self.browserslist_query;
@@ -203,6 +221,7 @@ impl TurbopackDevServerBuilder {
             show_all,
             log_detail,
             log_level: self.log_level,
+            issue_policy: Default::default(),
         });
         let entry_requests = Arc::new(self.entry_requests);
         let tasks = turbo_tasks.clone();
@@ -217,6 +236,8 @@ impl TurbopackDevServerBuilder {
                 project_dir.clone(),
                 entry_requests.clone().into(),
                 eager_compile,
+                lazy_compilation,
+                runtime_chunk,
                 turbo_tasks.clone().into(),
                 browserslist_query.clone(),
             )
@@ -233,6 +254,8 @@ async fn source(
     project_dir: String,
     entry_requests: TransientInstance<Vec<EntryRequest>>,
     eager_compile: bool,
+    lazy_compilation: bool,
+    runtime_chunk: bool,
     turbo_tasks: TransientInstance<TurboTasks<MemoryBackend>>,
     browserslist_query: String,
 ) -> Result<Vc<Box<dyn ContentSource>>> {
@@ -246,7 +269,7 @@ async fn source(
     let fs = project_fs(root_dir);
     let project_path: Vc<turbo_tasks_fs::FileSystemPath> = fs.root().join(project_relative);
 
-    let env = load_env(project_path);
+    let env = load_env_for_mode(project_path, NodeEnv::Development.to_string());
     let build_output_root = output_fs.root().join(".turbopack/build".to_string());
 
     let build_chunking_context = DevChunkingContext::builder(
@@ -282,6 +305,8 @@ async fn source(
         server_root,
         env,
         eager_compile,
+        lazy_compilation,
+        runtime_chunk,
         NodeEnv::Development.cell(),
         browserslist_query,
     );
@@ -344,6 +369,8 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
 
     let mut server = TurbopackDevServerBuilder::new(tt, project_dir, root_dir)
         .eager_compile(args.eager_compile)
+        .lazy_compilation(args.lazy_compilation)
+        .runtime_chunk(args.runtime_chunk)
         .hostname(args.hostname)
         .port(args.port)
         .log_detail(args.common.log_detail)
@@ -354,6 +381,10 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
                 .map_or_else(|| IssueSeverity::Warning, |l| l.0),
         );
 
+    if let Some(browserslist) = &args.common.browserslist {
+        server = server.browserslist_query(browserslist.clone());
+    }
+
     for entry in normalize_entries(&args.common.entries) {
         server = server.entry_request(EntryRequest::Relative(entry))
     }
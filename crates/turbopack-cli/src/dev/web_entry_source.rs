@@ -34,6 +34,8 @@ pub fn get_client_chunking_context(
     project_path: Vc<FileSystemPath>,
     server_root: Vc<FileSystemPath>,
     environment: Vc<Environment>,
+    lazy_compilation: bool,
+    runtime_chunk: bool,
 ) -> Vc<Box<dyn ChunkingContext>> {
     Vc::upcast(
         DevChunkingContext::builder(
@@ -44,6 +46,8 @@ pub fn get_client_chunking_context(
             environment,
         )
         .hot_module_replacement()
+        .lazy_compilation(lazy_compilation)
+        .runtime_chunk(runtime_chunk)
         .build(),
     )
 }
@@ -51,8 +55,10 @@ pub fn get_client_chunking_context(
 #[turbo_tasks::function]
 pub async fn get_client_runtime_entries(
     project_path: Vc<FileSystemPath>,
+    env: Vc<Box<dyn ProcessEnv>>,
 ) -> Result<Vc<RuntimeEntries>> {
-    let resolve_options_context = get_client_resolve_options_context(project_path);
+    let resolve_options_context =
+        get_client_resolve_options_context(project_path, env, false, None, None);
 
     let mut runtime_entries = Vec::new();
 
@@ -84,17 +90,33 @@ pub async fn create_web_entry_source(
     execution_context: Vc<ExecutionContext>,
     entry_requests: Vec<Vc<Request>>,
     server_root: Vc<FileSystemPath>,
-    _env: Vc<Box<dyn ProcessEnv>>,
+    env: Vc<Box<dyn ProcessEnv>>,
     eager_compile: bool,
+    lazy_compilation: bool,
+    runtime_chunk: bool,
     node_env: Vc<NodeEnv>,
     browserslist_query: String,
 ) -> Result<Vc<Box<dyn ContentSource>>> {
-    let compile_time_info = get_client_compile_time_info(browserslist_query, node_env);
-    let asset_context =
-        get_client_asset_context(project_path, execution_context, compile_time_info, node_env);
-    let chunking_context =
-        get_client_chunking_context(project_path, server_root, compile_time_info.environment());
-    let entries = get_client_runtime_entries(project_path);
+    let compile_time_info = get_client_compile_time_info(browserslist_query, node_env, env);
+    let asset_context = get_client_asset_context(
+        project_path,
+        execution_context,
+        compile_time_info,
+        env,
+        node_env,
+        false,
+        None,
+        None,
+        None,
+    );
+    let chunking_context = get_client_chunking_context(
+        project_path,
+        server_root,
+        compile_time_info.environment(),
+        lazy_compilation,
+        runtime_chunk,
+    );
+    let entries = get_client_runtime_entries(project_path, env);
 
     let runtime_entries = entries.resolve_entries(asset_context);
 
@@ -4,7 +4,9 @@
 #![feature(async_fn_in_trait)]
 
 pub mod arguments;
+pub mod artifact_store;
 pub mod build;
+pub mod config;
 pub(crate) mod contexts;
 pub mod dev;
 pub(crate) mod embed_js;
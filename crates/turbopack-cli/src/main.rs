@@ -5,9 +5,13 @@ use std::path::Path;
 
 use anyhow::{Context, Result};
 use clap::Parser;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Registry};
-use turbopack_cli::{arguments::Arguments, register};
+use tracing_subscriber::{
+    layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer, Registry,
+};
+use turbopack_cli::{arguments::Arguments, config::TurbopackConfig, register};
 use turbopack_cli_utils::{
+    build_timings::{BuildTimingsHandle, BuildTimingsLayer},
+    chrome_trace::ChromeTraceLayer,
     exit::ExitGuard,
     raw_trace::RawTraceLayer,
     trace_writer::TraceWriter,
@@ -24,6 +28,10 @@ fn main() {
 
     let args = Arguments::parse();
 
+    let timings_layer = args.timings().then(BuildTimingsLayer::new);
+    let timings = timings_layer.as_ref().map(BuildTimingsLayer::handle);
+    let timings_layer = timings_layer.map(BuildTimingsLayer::filtered);
+
     let trace = std::env::var("TURBOPACK_TRACING").ok();
 
     let _guard = if let Some(mut trace) = trace {
@@ -41,9 +49,11 @@ fn main() {
             _ => {}
         }
 
-        let subscriber = Registry::default();
+        let subscriber = Registry::default().with(timings_layer);
 
-        let subscriber = subscriber.with(EnvFilter::builder().parse(trace).unwrap());
+        // The filter only applies to the trace, so it doesn't hide the spans of
+        // the build phases from the timings layer.
+        let filter = EnvFilter::builder().parse(trace).unwrap();
 
         let internal_dir = args
             .dir()
@@ -52,17 +62,34 @@ fn main() {
         std::fs::create_dir_all(&internal_dir)
             .context("Unable to create .turbopack directory")
             .unwrap();
-        let trace_file = internal_dir.join("trace.log");
+        // `TURBOPACK_TRACE_FORMAT=chrome` writes a Chrome trace event file
+        // instead of the raw trace, which can be opened without conversion.
+        let chrome_trace = std::env::var("TURBOPACK_TRACE_FORMAT").as_deref() == Ok("chrome");
+        let trace_file = internal_dir.join(if chrome_trace {
+            "trace.json"
+        } else {
+            "trace.log"
+        });
         let trace_writer = std::fs::File::create(trace_file).unwrap();
         let (trace_writer, guard) = TraceWriter::new(trace_writer);
-        let subscriber = subscriber.with(RawTraceLayer::new(trace_writer));
+        if chrome_trace {
+            subscriber
+                .with(ChromeTraceLayer::new(trace_writer).with_filter(filter))
+                .init();
+        } else {
+            subscriber
+                .with(RawTraceLayer::new(trace_writer).with_filter(filter))
+                .init();
+        }
 
         let guard = ExitGuard::new(guard).unwrap();
 
-        subscriber.init();
-
         Some(guard)
     } else {
+        if timings_layer.is_some() {
+            Registry::default().with(timings_layer).init();
+        }
+
         None
     };
 
@@ -73,15 +100,19 @@ fn main() {
         })
         .build()
         .unwrap()
-        .block_on(main_inner(args))
+        .block_on(main_inner(args, timings))
         .unwrap();
 }
 
-async fn main_inner(args: Arguments) -> Result<()> {
+async fn main_inner(mut args: Arguments, timings: Option<BuildTimingsHandle>) -> Result<()> {
     register();
 
+    TurbopackConfig::load(&args)?.apply(&mut args);
+
     match args {
-        Arguments::Build(args) => turbopack_cli::build::build(&args).await,
+        Arguments::Build(args) => turbopack_cli::build::build(&args, timings).await,
         Arguments::Dev(args) => turbopack_cli::dev::start_server(&args).await,
+        Arguments::Analyze(args) => turbopack_cli::build::analyze(&args, timings).await,
+        Arguments::Gc(args) => turbopack_cli::artifact_store::gc(&args),
     }
 }
@@ -1,37 +1,107 @@
-use std::collections::HashSet;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
 
 use anyhow::Result;
 use auto_hash_map::AutoSet;
 use indexmap::{IndexMap, IndexSet};
 use once_cell::unsync::Lazy;
-use turbo_tasks::{TryFlatJoinIterExt, TryJoinIterExt, Value, Vc};
+use turbo_tasks::{TryFlatJoinIterExt, TryJoinIterExt, Value, ValueToString, Vc};
+use turbo_tasks_fs::FileSystemPath;
 
 use super::{
-    availability_info::AvailabilityInfo, available_chunk_items::AvailableChunkItemInfo,
-    chunk_content, chunking::make_chunks, AsyncModuleInfo, Chunk, ChunkContentResult, ChunkItem,
-    ChunkingContext,
+    availability_info::AvailabilityInfo,
+    available_chunk_items::AvailableChunkItemInfo,
+    chunk_content,
+    chunking::{make_chunks, ReferencedOutputAssets},
+    ordering::order_chunk_items,
+    preload::AsyncChunkGroupHint,
+    AsyncModuleInfo, Chunk, ChunkContentResult, ChunkItem, ChunkableModule, ChunkingContext,
+};
+use crate::{
+    issue::{Issue, IssueExt, IssueSeverity},
+    module::Module,
+    reference::ModuleReference,
 };
-use crate::{module::Module, output::OutputAssets, reference::ModuleReference};
 
 pub struct MakeChunkGroupResult {
     pub chunks: Vec<Vc<Box<dyn Chunk>>>,
+    /// The async chunk groups referenced with [super::AsyncLoadingHints].
+    pub loading_hints: Vec<Vc<AsyncChunkGroupHint>>,
 }
 
-/// Creates a chunk group from a set of entries.
+/// Creates a chunk group from a set of entries. The chunks are named after
+/// `chunk_name` if given.
 pub async fn make_chunk_group(
     chunking_context: Vc<Box<dyn ChunkingContext>>,
     entries: impl IntoIterator<Item = Vc<Box<dyn Module>>>,
+    chunk_name: Option<String>,
+    availability_info: AvailabilityInfo,
+) -> Result<MakeChunkGroupResult> {
+    make_chunk_group_internal(
+        chunking_context,
+        entries,
+        chunk_name,
+        availability_info,
+        None,
+    )
+    .await
+}
+
+/// Like [make_chunk_group], but the entries are evaluated synchronously, e.g.
+/// in a service worker script. Async modules, like modules with top-level
+/// await, can't be evaluated there and are reported with an
+/// [AsyncModuleInSyncContextIssue]. `context` describes where the entries are
+/// evaluated.
+pub async fn make_sync_chunk_group(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    entries: impl IntoIterator<Item = Vc<Box<dyn Module>>>,
+    chunk_name: Option<String>,
+    availability_info: AvailabilityInfo,
+    context: &str,
+) -> Result<MakeChunkGroupResult> {
+    make_chunk_group_internal(
+        chunking_context,
+        entries,
+        chunk_name,
+        availability_info,
+        Some(context),
+    )
+    .await
+}
+
+async fn make_chunk_group_internal(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    entries: impl IntoIterator<Item = Vc<Box<dyn Module>>>,
+    chunk_name: Option<String>,
     availability_info: AvailabilityInfo,
+    sync_context: Option<&str>,
 ) -> Result<MakeChunkGroupResult> {
     let ChunkContentResult {
         chunk_items,
         async_modules,
+        async_module_hints,
+        async_module_names,
+        async_module_parent_counts,
         external_module_references,
+        external_module_reference_parents,
         forward_edges_inherit_async,
         local_back_edges_inherit_async,
         available_async_modules_back_edges_inherit_async,
+        split_point_back_edges_inherit_async,
+        chunk_item_children,
     } = chunk_content(chunking_context, entries, availability_info).await?;
 
+    // Order sensitive chunk items must not depend on the traversal order
+    let chunk_items = order_chunk_items(chunk_items, &chunk_item_children).await?;
+
+    // Chunk items of entry chunk groups are evaluated on startup, so expensive
+    // ones are reported
+    if matches!(availability_info, AvailabilityInfo::Root) {
+        report_startup_cost(chunking_context, &chunk_items).await?;
+    }
+
     // Find all local chunk items that are self async
     let self_async_children = chunk_items
         .iter()
@@ -43,10 +113,25 @@ pub async fn make_chunk_group(
         .try_flat_join()
         .await?;
 
-    // Get all available async modules and concatenate with local async modules
+    // Async modules can only be evaluated when the output format and the
+    // context the chunk group is evaluated in support them
+    let sync_context = match sync_context {
+        Some(sync_context) => Some(sync_context.to_string()),
+        None if !*chunking_context.supports_async_modules().await? => {
+            Some("an output format which doesn't support async modules".to_string())
+        }
+        None => None,
+    };
+    if let Some(sync_context) = sync_context {
+        report_async_modules(&self_async_children, &chunk_item_children, sync_context).await?;
+    }
+
+    // Get all available async modules and split points and concatenate with local
+    // async modules
     let mut async_chunk_items = available_async_modules_back_edges_inherit_async
         .keys()
         .copied()
+        .chain(split_point_back_edges_inherit_async.keys().copied())
         .chain(self_async_children.into_iter())
         .map(|chunk_item| (chunk_item, AutoSet::<Vc<Box<dyn ChunkItem>>>::new()))
         .collect::<IndexMap<_, _>>();
@@ -57,15 +142,13 @@ pub async fn make_chunk_group(
         let Some((&chunk_item, _)) = async_chunk_items.get_index(i) else {
             break;
         };
-        // The first few entries are from
-        // available_async_modules_back_edges_inherit_async and need to use that map,
-        // all other entries are local
-        let map = if i < available_async_modules_back_edges_inherit_async.len() {
-            &available_async_modules_back_edges_inherit_async
-        } else {
-            &local_back_edges_inherit_async
-        };
-        if let Some(parents) = map.get(&chunk_item) {
+        // Available async modules and split points aren't local, so only one of
+        // the maps contains parents of the chunk item
+        let parents = available_async_modules_back_edges_inherit_async
+            .get(&chunk_item)
+            .or_else(|| split_point_back_edges_inherit_async.get(&chunk_item))
+            .or_else(|| local_back_edges_inherit_async.get(&chunk_item));
+        if let Some(parents) = parents {
             for &parent in parents.iter() {
                 // Add item, it will be iterated by this loop too
                 async_chunk_items
@@ -83,8 +166,14 @@ pub async fn make_chunk_group(
         .map(|chunk_item| (chunk_item, None))
         .collect::<IndexMap<_, Option<Vc<AsyncModuleInfo>>>>();
 
-    // Insert AsyncModuleInfo for every async module
+    // Insert AsyncModuleInfo for every async module. Split points are placed in
+    // their own chunk groups.
     for (async_item, referenced_async_modules) in async_chunk_items {
+        if split_point_back_edges_inherit_async.contains_key(&async_item)
+            && !chunk_items.contains_key(&async_item)
+        {
+            continue;
+        }
         let referenced_async_modules =
             if let Some(references) = forward_edges_inherit_async.get(&async_item) {
                 references
@@ -101,8 +190,13 @@ pub async fn make_chunk_group(
         );
     }
 
-    // Insert async chunk loaders for every referenced async module
-    let async_loaders = {
+    // Insert async chunk loaders for every referenced async module. Async chunk
+    // groups with loading hints are exposed, so they can be loaded ahead of time.
+    // Small async chunk groups with a single parent are inlined, so loading them
+    // doesn't need another round trip.
+    let inline_async_chunk_size = *chunking_context.inline_async_chunk_size().await?;
+    let mut inlined_chunk_items = Vec::new();
+    let (async_loaders, loading_hints) = {
         // If necessary, compute new [AvailabilityInfo]
         let inner_availability_info = Lazy::new(|| {
             let map = chunk_items
@@ -120,15 +214,59 @@ pub async fn make_chunk_group(
             availability_info.with_chunk_items(map)
         });
 
-        async_modules
+        let mut async_loaders = Vec::new();
+        for module in async_modules {
+            if inline_async_chunk_size > 0
+                && async_module_parent_counts.get(&module) == Some(&1)
+                && !async_module_hints.contains_key(&module)
+                && !async_module_names.contains_key(&module)
+            {
+                let inner_availability_info = *inner_availability_info;
+                if let Some(chunk_items) = inlined_async_chunk_items(
+                    chunking_context,
+                    module,
+                    inner_availability_info,
+                    inline_async_chunk_size,
+                )
+                .await?
+                {
+                    inlined_chunk_items.extend(chunk_items);
+                    async_loaders.push(chunking_context.inlined_async_loader_chunk_item(module));
+                    continue;
+                }
+            }
+            async_loaders.push(chunking_context.async_loader_chunk_item(
+                module,
+                async_module_names.get(&module).cloned(),
+                Value::new(*inner_availability_info),
+            ));
+        }
+
+        let loading_hints = async_module_hints
             .into_iter()
-            .map(|module| {
-                chunking_context
-                    .async_loader_chunk_item(module, Value::new(*inner_availability_info))
+            .map(|(module, hints)| {
+                let availability_info = Value::new(*inner_availability_info);
+                AsyncChunkGroupHint {
+                    hints,
+                    chunks: match async_module_names.get(&module) {
+                        Some(chunk_name) => chunking_context.named_chunk_group(
+                            module,
+                            chunk_name.clone(),
+                            availability_info,
+                        ),
+                        None => chunking_context.chunk_group(module, availability_info),
+                    },
+                }
+                .cell()
             })
-            .collect::<Vec<_>>()
+            .collect::<Vec<_>>();
+
+        (async_loaders, loading_hints)
     };
     let async_loader_chunk_items = async_loaders.iter().map(|&chunk_item| (chunk_item, None));
+    for chunk_item in inlined_chunk_items {
+        chunk_items.entry(chunk_item).or_insert(None);
+    }
 
     // And also add output assets referenced by async chunk loaders
     let async_loader_references = async_loaders
@@ -140,13 +278,25 @@ pub async fn make_chunk_group(
         .iter()
         .flat_map(|references| references.iter().copied())
         .collect();
+    let async_loader_external_module_reference_parents = async_loaders
+        .iter()
+        .zip(async_loader_references.iter())
+        .flat_map(|(&loader, references)| {
+            references.iter().map(move |&reference| (reference, loader))
+        })
+        .collect();
 
     // Pass chunk items to chunking algorithm
     let mut chunks = make_chunks(
         chunking_context,
         chunk_items,
         "",
-        references_to_output_assets(external_module_references).await?,
+        chunk_name.as_deref(),
+        references_to_output_assets(
+            external_module_references,
+            &external_module_reference_parents,
+        )
+        .await?,
     )
     .await?;
 
@@ -157,30 +307,312 @@ pub async fn make_chunk_group(
         chunking_context,
         async_loader_chunk_items,
         "async-loader-",
-        references_to_output_assets(async_loader_external_module_references).await?,
+        None,
+        references_to_output_assets(
+            async_loader_external_module_references,
+            &async_loader_external_module_reference_parents,
+        )
+        .await?,
     )
     .await?;
 
     // concatenate chunks
     chunks.extend(async_loader_chunks);
 
-    Ok(MakeChunkGroupResult { chunks })
+    Ok(MakeChunkGroupResult {
+        chunks,
+        loading_hints,
+    })
+}
+
+/// Returns the chunk items of the chunk group of the async `module` if it's
+/// small enough to be inlined into the chunk group referencing it. Chunk groups
+/// which contain async modules, including split points, or referenced output
+/// assets are never inlined.
+async fn inlined_async_chunk_items(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    module: Vc<Box<dyn ChunkableModule>>,
+    availability_info: AvailabilityInfo,
+    max_size: usize,
+) -> Result<Option<IndexSet<Vc<Box<dyn ChunkItem>>>>> {
+    let ChunkContentResult {
+        chunk_items,
+        async_modules,
+        external_module_references,
+        available_async_modules_back_edges_inherit_async,
+        ..
+    } = chunk_content(chunking_context, [Vc::upcast(module)], availability_info).await?;
+    if !async_modules.is_empty()
+        || !external_module_references.is_empty()
+        || !available_async_modules_back_edges_inherit_async.is_empty()
+    {
+        return Ok(None);
+    }
+
+    let mut size = 0;
+    for &chunk_item in &chunk_items {
+        if *chunk_item.is_self_async().await? {
+            return Ok(None);
+        }
+        size += *chunk_item
+            .ty()
+            .chunk_item_size(chunking_context, chunk_item, None)
+            .await?;
+        if size > max_size {
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(chunk_items))
+}
+
+/// Reports chunk items whose [ChunkItem::startup_cost] exceeds the
+/// [ChunkingContext::startup_cost_budget] with a [StartupCostIssue].
+async fn report_startup_cost(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    chunk_items: &IndexSet<Vc<Box<dyn ChunkItem>>>,
+) -> Result<()> {
+    let budget = *chunking_context.startup_cost_budget().await?;
+    if budget == 0 {
+        return Ok(());
+    }
+    let costs = chunk_items
+        .iter()
+        .map(|&chunk_item| async move { Ok((chunk_item, *chunk_item.startup_cost().await?)) })
+        .try_join()
+        .await?;
+    for (chunk_item, cost) in costs {
+        if cost > budget {
+            StartupCostIssue {
+                file_path: chunk_item.asset_ident().path(),
+                cost,
+                budget,
+            }
+            .cell()
+            .emit();
+        }
+    }
+    Ok(())
+}
+
+/// Reports each of the self async `async_chunk_items` with an
+/// [AsyncModuleInSyncContextIssue], including a chain of imports which leads
+/// from an entry of the chunk group to it.
+async fn report_async_modules(
+    async_chunk_items: &[Vc<Box<dyn ChunkItem>>],
+    chunk_item_children: &IndexMap<Vc<Box<dyn ChunkItem>>, Vec<Vc<Box<dyn ChunkItem>>>>,
+    context: String,
+) -> Result<()> {
+    if async_chunk_items.is_empty() {
+        return Ok(());
+    }
+
+    for (&chunk_item, chain) in async_chunk_items
+        .iter()
+        .zip(import_chains(async_chunk_items, chunk_item_children))
+    {
+        let import_chain = chain
+            .iter()
+            .map(|chunk_item| chunk_item.asset_ident().to_string())
+            .try_join()
+            .await?;
+        AsyncModuleInSyncContextIssue {
+            file_path: chunk_item.asset_ident().path(),
+            context: context.clone(),
+            import_chain: import_chain.iter().map(|item| item.to_string()).collect(),
+        }
+        .cell()
+        .emit();
+    }
+    Ok(())
+}
+
+/// Returns the shortest chain of imports from an entry of the graph of
+/// `children` to each of the `targets`, starting with the entry.
+fn import_chains<T: Copy + Eq + Hash>(
+    targets: &[T],
+    children: &IndexMap<T, Vec<T>>,
+) -> Vec<Vec<T>> {
+    // Entries are traversed last, so searching breadth-first from the end finds
+    // the shortest import chains.
+    let mut parents = HashMap::new();
+    let mut visited = HashSet::new();
+    for &root in children.keys().rev() {
+        if !visited.insert(root) {
+            continue;
+        }
+        let mut queue = VecDeque::from([root]);
+        while let Some(item) = queue.pop_front() {
+            for &child in children.get(&item).into_iter().flatten() {
+                if visited.insert(child) {
+                    parents.insert(child, item);
+                    queue.push_back(child);
+                }
+            }
+        }
+    }
+
+    targets
+        .iter()
+        .map(|&target| {
+            let mut chain = vec![target];
+            while let Some(&parent) = parents.get(chain.last().unwrap()) {
+                chain.push(parent);
+            }
+            chain.reverse();
+            chain
+        })
+        .collect()
 }
 
+/// Resolves the output assets of the given references and keeps track of the
+/// chunk item referencing each of them.
 async fn references_to_output_assets(
     references: IndexSet<Vc<Box<dyn ModuleReference>>>,
-) -> Result<Vc<OutputAssets>> {
+    parents: &IndexMap<Vc<Box<dyn ModuleReference>>, Vc<Box<dyn ChunkItem>>>,
+) -> Result<ReferencedOutputAssets> {
     let output_assets = references
         .into_iter()
-        .map(|reference| reference.resolve_reference().primary_output_assets())
+        .map(|reference| async move {
+            let output_assets = reference
+                .resolve_reference()
+                .primary_output_assets()
+                .await?;
+            Ok((parents.get(&reference).copied(), output_assets))
+        })
         .try_join()
         .await?;
-    let mut set = HashSet::new();
-    let output_assets = output_assets
-        .iter()
-        .flatten()
-        .copied()
-        .filter(|&asset| set.insert(asset))
-        .collect::<Vec<_>>();
-    Ok(OutputAssets::new(output_assets))
+    let mut referenced_output_assets = ReferencedOutputAssets::new();
+    for (parent, output_assets) in output_assets {
+        for &asset in output_assets.iter() {
+            referenced_output_assets.entry(asset).or_insert(parent);
+        }
+    }
+    Ok(referenced_output_assets)
+}
+
+/// Reports a module in an entry chunk group which does more work on import
+/// than the startup cost budget allows.
+#[turbo_tasks::value(shared)]
+struct StartupCostIssue {
+    file_path: Vc<FileSystemPath>,
+    cost: usize,
+    budget: usize,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for StartupCostIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("chunking".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn code(&self) -> Vc<Option<String>> {
+        Vc::cell(Some("TP2001".to_string()))
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell("Module exceeds the startup cost budget".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "This module is loaded on startup and its top level code has an estimated cost of {} \
+             (budget: {}). Consider moving expensive initialization into a function, or loading \
+             the module lazily with a dynamic import().",
+            self.cost, self.budget
+        ))
+    }
+}
+
+/// Reports an async module, e.g. a module with top-level await, which is
+/// imported where it can't be evaluated.
+#[turbo_tasks::value(shared)]
+struct AsyncModuleInSyncContextIssue {
+    file_path: Vc<FileSystemPath>,
+    context: String,
+    import_chain: Vec<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for AsyncModuleInSyncContextIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("chunking".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn code(&self) -> Vc<Option<String>> {
+        Vc::cell(Some("TP2005".to_string()))
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell("Async module can't be evaluated synchronously".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "This module is async, e.g. because it uses top-level await, but it's evaluated in \
+             {}. Async modules and the modules importing them can only be evaluated \
+             asynchronously. Move the top-level await into a function, or load the module with a \
+             dynamic import().\n\nImport chain:\n{}",
+            self.context,
+            self.import_chain
+                .iter()
+                .map(|item| format!("- {}", item))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::indexmap;
+
+    use super::import_chains;
+
+    #[test]
+    fn finds_the_shortest_import_chains() {
+        // Entries are traversed last, so they come last.
+        let children = indexmap! {
+            "c" => vec!["tla"],
+            "b" => vec!["c"],
+            "a" => vec!["b", "c"],
+            "sw" => vec!["a"],
+        };
+        assert_eq!(
+            import_chains(&["tla", "b", "sw"], &children),
+            [
+                vec!["sw", "a", "c", "tla"],
+                vec!["sw", "a", "b"],
+                vec!["sw"]
+            ]
+        );
+    }
 }
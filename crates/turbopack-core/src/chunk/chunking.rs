@@ -1,79 +1,338 @@
-use std::{
-    borrow::Cow,
-    mem::{replace, take},
-    pin::Pin,
-};
+use std::{borrow::Cow, collections::HashSet, mem::take, pin::Pin};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use futures::Future;
 use indexmap::IndexMap;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use tracing::Level;
 use turbo_tasks::{ReadRef, TryJoinIterExt, ValueToString, Vc};
+use turbo_tasks_fs::FileSystemPath;
 
 use super::{AsyncModuleInfo, Chunk, ChunkItem, ChunkType, ChunkingContext};
-use crate::output::OutputAssets;
+use crate::{
+    issue::{Issue, IssueExt, IssueSeverity},
+    output::{OutputAsset, OutputAssets},
+};
+
+/// Turns a user provided chunk name into a single safe path segment. Path
+/// separators and other unusual characters are replaced with `_` and leading
+/// dots are removed, so names like `../../etc` stay within the chunk root.
+/// Returns `None` when nothing usable is left.
+pub fn sanitize_chunk_name(chunk_name: &str) -> Option<String> {
+    let sanitized: String = chunk_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let sanitized = sanitized.trim_start_matches('.');
+    sanitized
+        .chars()
+        .any(|c| c != '_')
+        .then(|| sanitized.to_string())
+}
+
+/// Controls how [make_chunks] distributes the chunk items of a chunk group
+/// over chunks.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(Debug, Default, Clone, Copy, Hash, PartialOrd, Ord)]
+pub enum ChunkingStrategy {
+    /// Splits chunk items into app and vendor code, and further by package
+    /// name and folder structure to get well-sized chunks. This is what
+    /// production builds should use.
+    #[default]
+    Split,
+    /// Only splits chunk items into app and vendor code without looking at
+    /// their sizes. This produces a few coarse chunks per chunk group, which
+    /// keeps changes in app code from affecting vendor chunks.
+    AppVendors,
+    /// Puts all chunk items of the same type into a single chunk. This is the
+    /// cheapest option to compute.
+    Single,
+}
+
+/// Controls to which chunk [make_chunks] attaches the output assets
+/// referenced by the chunk items.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(Debug, Default, Clone, Copy, Hash, PartialOrd, Ord)]
+pub enum ReferencedOutputAssetsPlacement {
+    /// Attaches all referenced output assets to the chunk that contains the
+    /// first chunk item, which is the entry of the chunk group.
+    #[default]
+    EntryChunk,
+    /// Attaches each referenced output asset to the chunk that contains the
+    /// chunk item referencing it. Output assets without a referencing chunk
+    /// item are attached to the entry chunk.
+    ReferencingChunk,
+    /// Attaches all referenced output assets to an additional chunk that
+    /// contains no chunk items.
+    DedicatedChunk,
+}
+
+/// Output assets referenced by chunk items, mapped to the chunk item that
+/// references them, if known.
+pub type ReferencedOutputAssets =
+    IndexMap<Vc<Box<dyn OutputAsset>>, Option<Vc<Box<dyn ChunkItem>>>>;
 
 /// Creates chunks based on heuristics for the passed `chunk_items`. Also
-/// attaches `referenced_output_assets` to the first chunk.
+/// attaches `referenced_output_assets` to chunks as configured by
+/// [ChunkingContext::referenced_output_assets_placement].
+///
+/// When a `chunk_name` is requested, all chunks are named after it. The name
+/// is sanitized with [sanitize_chunk_name] as it usually comes from a magic
+/// comment in user code and must not escape the output directory.
+///
+/// Chunks without any content are dropped when their [ChunkType] allows it,
+/// e.g. when tree shaking or deleted imports left only empty chunk items, and
+/// reported with a [DeadChunksIssue].
 #[tracing::instrument(level = Level::TRACE, skip_all)]
 pub async fn make_chunks(
     chunking_context: Vc<Box<dyn ChunkingContext>>,
     chunk_items: impl IntoIterator<Item = (Vc<Box<dyn ChunkItem>>, Option<Vc<AsyncModuleInfo>>)>,
     key_prefix: &str,
-    mut referenced_output_assets: Vc<OutputAssets>,
+    chunk_name: Option<&str>,
+    referenced_output_assets: ReferencedOutputAssets,
 ) -> Result<Vec<Vc<Box<dyn Chunk>>>> {
+    let chunk_name = chunk_name.and_then(sanitize_chunk_name);
+    let chunk_name = chunk_name.as_deref();
+    let key_prefix = match chunk_name {
+        Some(chunk_name) => Cow::Owned(format!("{key_prefix}{chunk_name}-")),
+        None => Cow::Borrowed(key_prefix),
+    };
+    let strategy = *chunking_context.chunking_strategy().await?;
+    // Sizes and idents of all chunk items are read at once instead of per chunk
+    // item type, as this dominates chunking time for large module graphs.
     let chunk_items = chunk_items
         .into_iter()
         .map(|(chunk_item, async_info)| async move {
             let ty = chunk_item.ty().resolve().await?;
-            Ok((ty, chunk_item, async_info))
+            let info = if strategy == ChunkingStrategy::Single {
+                None
+            } else {
+                Some(chunk_item_info(ty, chunking_context, chunk_item, async_info).await?)
+            };
+            Ok((ty, chunk_item, async_info, info))
         })
         .try_join()
         .await?;
+    let entry = chunk_items.first().map(|&(_, chunk_item, ..)| chunk_item);
     let mut map = IndexMap::<_, Vec<_>>::new();
-    for (ty, chunk_item, async_info) in chunk_items {
-        map.entry(ty).or_default().push((chunk_item, async_info));
+    for (ty, chunk_item, async_info, info) in chunk_items {
+        map.entry(ty)
+            .or_default()
+            .push((chunk_item, async_info, info));
     }
 
+    let mut output_assets_placer = OutputAssetsPlacer {
+        placement: *chunking_context
+            .referenced_output_assets_placement()
+            .await?,
+        entry,
+        remaining: referenced_output_assets,
+        empty: OutputAssets::empty().resolve().await?,
+    };
+    let first_ty = map.keys().next().copied();
+
     let mut chunks = Vec::new();
+    let mut dead_chunks = Vec::new();
     for (ty, chunk_items) in map {
         let ty_name = ty.to_string().await?;
+        let can_drop_empty_chunks = *ty.can_drop_empty_chunks().await?;
+
+        let coarse = match strategy {
+            ChunkingStrategy::Split => false,
+            ChunkingStrategy::AppVendors => true,
+            ChunkingStrategy::Single => {
+                // Sizes and idents are not needed when everything goes into a single chunk,
+                // except for finding out whether the chunk is empty.
+                let chunk_items = chunk_items
+                    .into_iter()
+                    .map(|(chunk_item, async_info, _)| (chunk_item, async_info))
+                    .collect::<Vec<_>>();
+                let referenced_output_assets = output_assets_placer
+                    .take_for(chunk_items.iter().map(|&(chunk_item, _)| chunk_item));
+                let is_dead = can_drop_empty_chunks
+                    && referenced_output_assets == output_assets_placer.empty
+                    && chunk_items
+                        .iter()
+                        .map(|&(chunk_item, async_info)| {
+                            ty.chunk_item_size(chunking_context, chunk_item, async_info)
+                        })
+                        .try_join()
+                        .await?
+                        .iter()
+                        .all(|size| **size == 0);
+                let chunk = ty.chunk(
+                    chunking_context,
+                    chunk_items,
+                    referenced_output_assets,
+                    chunk_name.map(ToString::to_string),
+                );
+                if is_dead {
+                    dead_chunks.push(chunk);
+                } else {
+                    chunks.push(chunk);
+                }
+                continue;
+            }
+        };
 
         let chunk_items = chunk_items
             .into_iter()
-            .map(|(chunk_item, async_info)| async move {
-                Ok((
-                    chunk_item,
-                    async_info,
-                    *ty.chunk_item_size(chunking_context, chunk_item, async_info)
-                        .await?,
-                    chunk_item.asset_ident().to_string().await?,
-                ))
+            .map(|(chunk_item, async_info, info)| {
+                let info = info.context("chunk item info is missing")?;
+                Ok((chunk_item, async_info, info.size, info.asset_ident.clone()))
             })
-            .try_join()
-            .await?;
+            .collect::<Result<Vec<_>>>()?;
 
         let mut split_context = SplitContext {
             ty,
             chunking_context,
             chunks: &mut chunks,
-            referenced_output_assets: &mut referenced_output_assets,
-            empty_referenced_output_assets: OutputAssets::empty().resolve().await?,
+            dead_chunks: can_drop_empty_chunks.then_some(&mut dead_chunks),
+            output_assets_placer: &mut output_assets_placer,
+            chunk_name,
         };
 
-        app_vendors_split(
-            chunk_items,
-            format!("{key_prefix}{ty_name}"),
-            &mut split_context,
-        )
-        .await?;
+        let name = format!("{key_prefix}{ty_name}");
+        if coarse {
+            app_vendors_coarse_split(chunk_items, name, &mut split_context).await?;
+        } else {
+            app_vendors_split(chunk_items, name, &mut split_context).await?;
+        }
+    }
+
+    // Output assets that are not attached to any chunk yet get a chunk of their
+    // own. That's always the case for
+    // [ReferencedOutputAssetsPlacement::DedicatedChunk].
+    if let Some(ty) = first_ty {
+        if !output_assets_placer.remaining.is_empty() {
+            chunks.push(ty.chunk(
+                chunking_context,
+                Vec::new(),
+                output_assets_placer.take_remaining(),
+                chunk_name.map(ToString::to_string),
+            ));
+        }
+    }
+
+    if !dead_chunks.is_empty() {
+        let chunk_paths = dead_chunks
+            .iter()
+            .map(|chunk| chunk.path())
+            .try_join()
+            .await?;
+        let output_root = chunking_context.output_root();
+        let output_root_value = output_root.await?;
+        DeadChunksIssue {
+            output_root,
+            chunks: chunk_paths
+                .iter()
+                .map(|path| {
+                    output_root_value
+                        .get_path_to(path)
+                        .unwrap_or(path.path.as_str())
+                        .to_string()
+                })
+                .collect(),
+        }
+        .cell()
+        .emit();
     }
 
     Ok(chunks)
 }
 
+/// The size and ident of a chunk item, as needed by the split heuristics.
+#[turbo_tasks::value(serialization = "none")]
+struct ChunkItemInfo {
+    size: usize,
+    asset_ident: ReadRef<String>,
+}
+
+/// Reads the [ChunkItemInfo] of a chunk item. A chunk item is usually part of
+/// many chunk groups, which all share the result of this task.
+#[turbo_tasks::function]
+async fn chunk_item_info(
+    ty: Vc<Box<dyn ChunkType>>,
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    chunk_item: Vc<Box<dyn ChunkItem>>,
+    async_info: Option<Vc<AsyncModuleInfo>>,
+) -> Result<Vc<ChunkItemInfo>> {
+    // Both tasks are scheduled before either is awaited.
+    let size = ty.chunk_item_size(chunking_context, chunk_item, async_info);
+    let asset_ident = chunk_item.asset_ident().to_string();
+    Ok(ChunkItemInfo {
+        size: *size.await?,
+        asset_ident: asset_ident.await?,
+    }
+    .cell())
+}
+
+/// Decides which referenced output assets are attached to a chunk, according
+/// to the [ReferencedOutputAssetsPlacement].
+struct OutputAssetsPlacer {
+    placement: ReferencedOutputAssetsPlacement,
+    entry: Option<Vc<Box<dyn ChunkItem>>>,
+    remaining: ReferencedOutputAssets,
+    empty: Vc<OutputAssets>,
+}
+
+impl OutputAssetsPlacer {
+    /// Takes the output assets that should be attached to a chunk containing
+    /// the given `chunk_items`.
+    fn take_for(
+        &mut self,
+        chunk_items: impl IntoIterator<Item = Vc<Box<dyn ChunkItem>>>,
+    ) -> Vc<OutputAssets> {
+        if self.remaining.is_empty() {
+            return self.empty;
+        }
+        let chunk_items = chunk_items.into_iter().collect::<HashSet<_>>();
+        let contains_entry = self
+            .entry
+            .map_or(false, |entry| chunk_items.contains(&entry));
+        let output_assets = match self.placement {
+            ReferencedOutputAssetsPlacement::EntryChunk => {
+                if !contains_entry {
+                    return self.empty;
+                }
+                take(&mut self.remaining).into_keys().collect::<Vec<_>>()
+            }
+            ReferencedOutputAssetsPlacement::ReferencingChunk => {
+                let mut output_assets = Vec::new();
+                self.remaining.retain(|&asset, parent| {
+                    let attach = match *parent {
+                        Some(parent) => chunk_items.contains(&parent),
+                        None => contains_entry,
+                    };
+                    if attach {
+                        output_assets.push(asset);
+                    }
+                    !attach
+                });
+                output_assets
+            }
+            ReferencedOutputAssetsPlacement::DedicatedChunk => return self.empty,
+        };
+        if output_assets.is_empty() {
+            self.empty
+        } else {
+            OutputAssets::new(output_assets)
+        }
+    }
+
+    /// Takes all output assets that are not attached to a chunk yet.
+    fn take_remaining(&mut self) -> Vc<OutputAssets> {
+        OutputAssets::new(take(&mut self.remaining).into_keys().collect())
+    }
+}
+
 type ChunkItemWithInfo = (
     Vc<Box<dyn ChunkItem>>,
     Option<Vc<AsyncModuleInfo>>,
@@ -85,8 +344,11 @@ struct SplitContext<'a> {
     ty: Vc<Box<dyn ChunkType>>,
     chunking_context: Vc<Box<dyn ChunkingContext>>,
     chunks: &'a mut Vec<Vc<Box<dyn Chunk>>>,
-    referenced_output_assets: &'a mut Vc<OutputAssets>,
-    empty_referenced_output_assets: Vc<OutputAssets>,
+    /// Where empty chunks are collected instead of `chunks`, if the chunk type
+    /// allows dropping them.
+    dead_chunks: Option<&'a mut Vec<Vc<Box<dyn Chunk>>>>,
+    output_assets_placer: &'a mut OutputAssetsPlacer,
+    chunk_name: Option<&'a str>,
 }
 
 /// Handle chunk items based on their total size. If the total size is too
@@ -120,19 +382,24 @@ async fn make_chunk(
     key: &mut String,
     split_context: &mut SplitContext<'_>,
 ) -> Result<()> {
-    split_context.chunks.push(
-        split_context.ty.chunk(
-            split_context.chunking_context,
-            chunk_items
-                .into_iter()
-                .map(|(chunk_item, async_info, ..)| (chunk_item, async_info))
-                .collect(),
-            replace(
-                split_context.referenced_output_assets,
-                split_context.empty_referenced_output_assets,
-            ),
-        ),
+    let referenced_output_assets = split_context
+        .output_assets_placer
+        .take_for(chunk_items.iter().map(|&(chunk_item, ..)| chunk_item));
+    let is_empty = referenced_output_assets == split_context.output_assets_placer.empty
+        && chunk_items.iter().all(|&(_, _, size, _)| size == 0);
+    let chunk = split_context.ty.chunk(
+        split_context.chunking_context,
+        chunk_items
+            .into_iter()
+            .map(|(chunk_item, async_info, ..)| (chunk_item, async_info))
+            .collect(),
+        referenced_output_assets,
+        split_context.chunk_name.map(ToString::to_string),
     );
+    match &mut split_context.dead_chunks {
+        Some(dead_chunks) if is_empty => dead_chunks.push(chunk),
+        _ => split_context.chunks.push(chunk),
+    }
     Ok(())
 }
 
@@ -185,6 +452,28 @@ async fn app_vendors_split(
     Ok(())
 }
 
+/// Split chunk items into one app code and one vendor code chunk, regardless
+/// of their sizes.
+#[tracing::instrument(level = Level::TRACE, skip(chunk_items, split_context))]
+async fn app_vendors_coarse_split(
+    chunk_items: Vec<ChunkItemWithInfo>,
+    name: String,
+    split_context: &mut SplitContext<'_>,
+) -> Result<()> {
+    let (app_chunk_items, vendors_chunk_items): (Vec<_>, Vec<_>) = chunk_items
+        .into_iter()
+        .partition(|(_, _, _, asset_ident)| is_app_code(asset_ident));
+    if !app_chunk_items.is_empty() {
+        let mut key = format!("{}-app", name);
+        make_chunk(app_chunk_items, &mut key, split_context).await?;
+    }
+    if !vendors_chunk_items.is_empty() {
+        let mut key = format!("{}-vendors", name);
+        make_chunk(vendors_chunk_items, &mut key, split_context).await?;
+    }
+    Ok(())
+}
+
 /// Split chunk items by node_modules package name. Continues splitting with
 /// [folder_split] if necessary.
 #[tracing::instrument(level = Level::TRACE, skip(chunk_items, split_context))]
@@ -336,3 +625,74 @@ fn chunk_size(chunk_items: &[ChunkItemWithInfo]) -> ChunkSize {
         ChunkSize::Small
     }
 }
+
+/// Reports chunks that were dropped by [make_chunks] because none of their
+/// chunk items had any content anymore.
+#[turbo_tasks::value(shared)]
+struct DeadChunksIssue {
+    output_root: Vc<FileSystemPath>,
+    /// The paths of the dropped chunks, relative to the output root.
+    chunks: Vec<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for DeadChunksIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Info.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("chunking".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell("Empty chunks were removed".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.output_root
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "The following chunks no longer contain any code and were removed from their chunk \
+             group:\n{}",
+            self.chunks
+                .iter()
+                .map(|chunk| format!("- {chunk}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sanitize_chunk_name;
+
+    #[test]
+    fn sanitizes_chunk_names() {
+        assert_eq!(sanitize_chunk_name("dialog").as_deref(), Some("dialog"));
+        assert_eq!(
+            sanitize_chunk_name("my-chunk_1.v2").as_deref(),
+            Some("my-chunk_1.v2")
+        );
+        assert_eq!(
+            sanitize_chunk_name("../../etc/passwd").as_deref(),
+            Some("_.._etc_passwd")
+        );
+        assert_eq!(
+            sanitize_chunk_name("pages\\admin").as_deref(),
+            Some("pages_admin")
+        );
+        assert_eq!(sanitize_chunk_name(".hidden").as_deref(), Some("hidden"));
+        assert_eq!(sanitize_chunk_name(".."), None);
+        assert_eq!(sanitize_chunk_name("/"), None);
+        assert_eq!(sanitize_chunk_name(""), None);
+    }
+}
@@ -1,10 +1,19 @@
 use anyhow::Result;
-use turbo_tasks::{Upcast, Value, ValueToString, Vc};
+use turbo_tasks::{Upcast, Value, Vc};
 use turbo_tasks_fs::FileSystemPath;
 
-use super::{availability_info::AvailabilityInfo, ChunkableModule, EvaluatableAssets};
+use super::{
+    availability_info::AvailabilityInfo,
+    chunking::{ChunkingStrategy, ReferencedOutputAssetsPlacement},
+    module_id_strategy::{module_id_from_ident, ModuleIdStrategy},
+    preload::OriginHints,
+    split_point::SplitPoints,
+    ChunkableModule, EvaluatableAssets,
+};
 use crate::{
+    asset::AssetContent,
     chunk::{ChunkItem, ModuleId},
+    content_hash::{content_hash, ContentHashAlgorithm},
     environment::Environment,
     ident::AssetIdent,
     module::Module,
@@ -47,34 +56,113 @@ pub trait ChunkingContext {
         original_asset_ident: Vc<AssetIdent>,
     ) -> Vc<FileSystemPath>;
 
+    /// Returns the content hash of `content` which is passed to
+    /// [ChunkingContext::asset_path].
+    fn content_hash(self: Vc<Self>, content: Vc<AssetContent>) -> Vc<String> {
+        content_hash(content, ContentHashAlgorithm::default())
+    }
+
     fn is_hot_module_replacement_enabled(self: Vc<Self>) -> Vc<bool> {
         Vc::cell(false)
     }
 
+    /// Returns how chunk items of a chunk group are split into chunks.
+    fn chunking_strategy(self: Vc<Self>) -> Vc<ChunkingStrategy> {
+        ChunkingStrategy::Split.cell()
+    }
+
+    /// Returns to which chunks the output assets referenced by chunk items
+    /// are attached.
+    fn referenced_output_assets_placement(self: Vc<Self>) -> Vc<ReferencedOutputAssetsPlacement> {
+        ReferencedOutputAssetsPlacement::default().cell()
+    }
+
+    /// Returns the cross-origin servers which chunks and assets are loaded
+    /// from.
+    fn origin_hints(self: Vc<Self>) -> Vc<OriginHints> {
+        OriginHints::empty()
+    }
+
+    /// Returns the estimated startup cost above which a chunk item evaluated in
+    /// an entry chunk group is reported, see [ChunkItem::startup_cost]. Zero
+    /// disables the check.
+    fn startup_cost_budget(self: Vc<Self>) -> Vc<usize> {
+        Vc::cell(0)
+    }
+
+    /// Whether the output format can evaluate async modules, e.g. modules with
+    /// top-level await. When it can't, async modules are reported.
+    fn supports_async_modules(self: Vc<Self>) -> Vc<bool> {
+        Vc::cell(true)
+    }
+
+    /// Returns the modules which are forced into separate chunks.
+    fn split_points(self: Vc<Self>) -> Vc<SplitPoints> {
+        SplitPoints::empty()
+    }
+
+    /// Creates the chunk item which loads the chunk group of `module`. The
+    /// chunks of the chunk group are named after `chunk_name` if given.
     fn async_loader_chunk_item(
         &self,
         module: Vc<Box<dyn ChunkableModule>>,
+        chunk_name: Option<String>,
         availability_info: Value<AvailabilityInfo>,
     ) -> Vc<Box<dyn ChunkItem>>;
     fn async_loader_chunk_item_id(&self, module: Vc<Box<dyn ChunkableModule>>) -> Vc<ModuleId>;
 
+    /// Returns the size in bytes up to which the chunk group of an async
+    /// module, which is only referenced by a single chunk item, is inlined
+    /// into the chunk group of that chunk item. Zero disables inlining.
+    fn inline_async_chunk_size(self: Vc<Self>) -> Vc<usize> {
+        Vc::cell(0)
+    }
+
+    /// Creates the chunk item which loads `module` after its chunk group was
+    /// inlined into the chunk group of the loader. It has the same id as the
+    /// [ChunkingContext::async_loader_chunk_item] of `module`.
+    fn inlined_async_loader_chunk_item(
+        &self,
+        module: Vc<Box<dyn ChunkableModule>>,
+    ) -> Vc<Box<dyn ChunkItem>>;
+
     fn chunk_group(
         self: Vc<Self>,
         module: Vc<Box<dyn ChunkableModule>>,
         availability_info: Value<AvailabilityInfo>,
     ) -> Vc<OutputAssets>;
 
+    /// Like [ChunkingContext::chunk_group], but the chunks are named after
+    /// `chunk_name` instead of their chunk items, e.g. for a
+    /// `webpackChunkName` comment on `import()`.
+    fn named_chunk_group(
+        self: Vc<Self>,
+        module: Vc<Box<dyn ChunkableModule>>,
+        _chunk_name: String,
+        availability_info: Value<AvailabilityInfo>,
+    ) -> Vc<OutputAssets> {
+        self.chunk_group(module, availability_info)
+    }
+
     fn evaluated_chunk_group(
         self: Vc<Self>,
         ident: Vc<AssetIdent>,
         evaluatable_assets: Vc<EvaluatableAssets>,
     ) -> Vc<OutputAssets>;
 
-    async fn chunk_item_id_from_ident(
+    /// Generates a single output asset at `path` that contains the runtime
+    /// and all chunk items needed to evaluate `evaluatable_assets`. It never
+    /// loads other chunks, which makes it suitable for service workers.
+    fn service_worker_entry_chunk(
         self: Vc<Self>,
-        ident: Vc<AssetIdent>,
-    ) -> Result<Vc<ModuleId>> {
-        Ok(ModuleId::String(ident.to_string().await?.clone_value()).cell())
+        path: Vc<FileSystemPath>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+    ) -> Vc<Box<dyn OutputAsset>>;
+
+    /// Returns the id of the chunk item with `ident`. Defaults to
+    /// [ModuleIdStrategy::FullPath].
+    fn chunk_item_id_from_ident(self: Vc<Self>, ident: Vc<AssetIdent>) -> Vc<ModuleId> {
+        module_id_from_ident(ident, ModuleIdStrategy::FullPath)
     }
 
     fn chunk_item_id(self: Vc<Self>, chunk_item: Vc<Box<dyn ChunkItem>>) -> Vc<ModuleId> {
@@ -2,12 +2,16 @@ pub mod availability_info;
 pub mod available_chunk_items;
 pub mod chunk_group;
 pub mod chunking;
+pub mod module_id_strategy;
 pub(crate) mod chunking_context;
 pub(crate) mod containment_tree;
 pub(crate) mod data;
 pub(crate) mod evaluate;
 pub mod optimize;
+pub(crate) mod ordering;
 pub(crate) mod passthrough_asset;
+pub mod preload;
+pub mod split_point;
 
 use std::{
     collections::HashSet,
@@ -20,7 +24,7 @@ use anyhow::Result;
 use auto_hash_map::AutoSet;
 use indexmap::{IndexMap, IndexSet};
 use serde::{Deserialize, Serialize};
-use tracing::{info_span, Span};
+use tracing::{info_span, Instrument, Span};
 use turbo_tasks::{
     debug::ValueDebugFormat,
     graph::{AdjacencyMap, GraphTraversal, GraphTraversalResult, Visit, VisitControlFlow},
@@ -30,7 +34,7 @@ use turbo_tasks::{
 use turbo_tasks_fs::FileSystemPath;
 use turbo_tasks_hash::DeterministicHash;
 
-use self::availability_info::AvailabilityInfo;
+use self::{availability_info::AvailabilityInfo, split_point::split_chunk_name};
 pub use self::{
     chunking_context::{ChunkingContext, ChunkingContextExt},
     data::{ChunkData, ChunkDataOption, ChunksData},
@@ -91,6 +95,14 @@ pub trait ChunkableModule: Module + Asset {
         self: Vc<Self>,
         chunking_context: Vc<Box<dyn ChunkingContext>>,
     ) -> Vc<Box<dyn ChunkItem>>;
+
+    /// The name of the async chunk group this module is split into when the
+    /// module requests to be a split point, e.g. with a
+    /// `/* @turbopack-chunk: "editor" */` comment. Modules only imported
+    /// through it are placed in that chunk group too.
+    fn split_chunk_name(self: Vc<Self>) -> Vc<Option<String>> {
+        Vc::cell(None)
+    }
 }
 
 #[turbo_tasks::value(transparent)]
@@ -165,6 +177,33 @@ pub enum ChunkingType {
 #[turbo_tasks::value(transparent)]
 pub struct ChunkingTypeOption(Option<ChunkingType>);
 
+/// Hints on how the chunk group of a [ChunkingType::Async] reference should be
+/// loaded ahead of time, e.g. from `webpackPrefetch` comments on `import()`.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Default, Clone, Copy, Hash)]
+pub struct AsyncLoadingHints {
+    /// The chunk group is likely needed for a future navigation and should be
+    /// loaded when the browser is idle.
+    pub prefetch: bool,
+    /// The chunk group is needed soon and should be loaded in parallel with
+    /// the referencing chunk group.
+    pub preload: bool,
+}
+
+impl AsyncLoadingHints {
+    pub fn is_empty(&self) -> bool {
+        !self.prefetch && !self.preload
+    }
+
+    /// Combines the hints of multiple references to the same chunk group.
+    pub fn union(self, other: AsyncLoadingHints) -> AsyncLoadingHints {
+        AsyncLoadingHints {
+            prefetch: self.prefetch || other.prefetch,
+            preload: self.preload || other.preload,
+        }
+    }
+}
+
 /// A [ModuleReference] implementing this trait and returning true for
 /// [ChunkableModuleReference::is_chunkable] are considered as potentially
 /// chunkable references. When all [Module]s of such a reference implement
@@ -176,6 +215,17 @@ pub trait ChunkableModuleReference: ModuleReference + ValueToString {
     fn chunking_type(self: Vc<Self>) -> Vc<ChunkingTypeOption> {
         Vc::cell(Some(ChunkingType::default()))
     }
+
+    /// Only used for [ChunkingType::Async] references.
+    fn async_loading_hints(self: Vc<Self>) -> Vc<AsyncLoadingHints> {
+        AsyncLoadingHints::default().cell()
+    }
+
+    /// The name of the chunks of the chunk group of a [ChunkingType::Async]
+    /// reference, e.g. from a `webpackChunkName` comment on `import()`.
+    fn async_chunk_name(self: Vc<Self>) -> Vc<Option<String>> {
+        Vc::cell(None)
+    }
 }
 
 type AsyncInfo = IndexMap<Vc<Box<dyn ChunkItem>>, Vec<Vc<Box<dyn ChunkItem>>>>;
@@ -183,7 +233,19 @@ type AsyncInfo = IndexMap<Vc<Box<dyn ChunkItem>>, Vec<Vc<Box<dyn ChunkItem>>>>;
 pub struct ChunkContentResult {
     pub chunk_items: IndexSet<Vc<Box<dyn ChunkItem>>>,
     pub async_modules: IndexSet<Vc<Box<dyn ChunkableModule>>>,
+    /// The combined [AsyncLoadingHints] of async modules which are referenced
+    /// with hints.
+    pub async_module_hints: IndexMap<Vc<Box<dyn ChunkableModule>>, AsyncLoadingHints>,
+    /// The requested chunk names of async modules. The first reference with a
+    /// name determines the name.
+    pub async_module_names: IndexMap<Vc<Box<dyn ChunkableModule>>, String>,
+    /// The number of chunk items which reference each async module.
+    pub async_module_parent_counts: IndexMap<Vc<Box<dyn ChunkableModule>>, usize>,
     pub external_module_references: IndexSet<Vc<Box<dyn ModuleReference>>>,
+    /// A map from external module references to the chunk item that first
+    /// referenced them. References from passthrough modules are not included.
+    pub external_module_reference_parents:
+        IndexMap<Vc<Box<dyn ModuleReference>>, Vc<Box<dyn ChunkItem>>>,
     /// A map from local module to all children from which the async module
     /// status is inherited
     pub forward_edges_inherit_async: AsyncInfo,
@@ -193,6 +255,12 @@ pub struct ChunkContentResult {
     /// A map from already available async modules to all local parents that
     /// inherit the async module status
     pub available_async_modules_back_edges_inherit_async: AsyncInfo,
+    /// A map from split points to all local parents that statically import
+    /// them and therefore inherit the async module status
+    pub split_point_back_edges_inherit_async: AsyncInfo,
+    /// A map from chunk items to the chunk items they reference, in the order
+    /// of their references.
+    pub chunk_item_children: IndexMap<Vc<Box<dyn ChunkItem>>, Vec<Vc<Box<dyn ChunkItem>>>>,
 }
 
 pub async fn chunk_content(
@@ -200,7 +268,9 @@ pub async fn chunk_content(
     entries: impl IntoIterator<Item = Vc<Box<dyn Module>>>,
     availability_info: AvailabilityInfo,
 ) -> Result<ChunkContentResult> {
-    chunk_content_internal_parallel(chunking_context, entries, availability_info).await
+    chunk_content_internal_parallel(chunking_context, entries, availability_info)
+        .instrument(info_span!("chunking"))
+        .await
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, TraceRawVcs, Debug)]
@@ -213,6 +283,9 @@ enum InheritAsyncEdge {
     /// not included in back edges at all since they don't influence the parent
     /// module in terms of being an async module.
     AvailableAsyncModule,
+    /// The chunk item is a split point, which is loaded in its own async chunk
+    /// group. The parent awaits that chunk group, so it's an async module.
+    SplitPoint,
 }
 
 #[derive(Eq, PartialEq, Clone, Hash, Serialize, Deserialize, TraceRawVcs, Debug)]
@@ -230,9 +303,15 @@ enum ChunkContentGraphNode {
     // Async module that is referenced from the chunk group
     AsyncModule {
         module: Vc<Box<dyn ChunkableModule>>,
+        hints: AsyncLoadingHints,
+        chunk_name: Option<String>,
+    },
+    // ModuleReferences that are not placed in the current chunk group, together
+    // with the chunk item that references them, if any
+    ExternalModuleReference {
+        reference: Vc<Box<dyn ModuleReference>>,
+        parent: Option<Vc<Box<dyn ChunkItem>>>,
     },
-    // ModuleReferences that are not placed in the current chunk group
-    ExternalModuleReference(Vc<Box<dyn ModuleReference>>),
     /// A list of directly referenced chunk items from which `is_async_module`
     /// will be inherited.
     InheritAsyncInfo {
@@ -285,14 +364,14 @@ async fn graph_node_to_referenced_nodes(
             else {
                 return Ok(vec![ChunkGraphEdge {
                     key: None,
-                    node: ChunkContentGraphNode::ExternalModuleReference(reference),
+                    node: ChunkContentGraphNode::ExternalModuleReference { reference, parent },
                 }]);
             };
 
             let Some(chunking_type) = *chunkable_module_reference.chunking_type().await? else {
                 return Ok(vec![ChunkGraphEdge {
                     key: None,
-                    node: ChunkContentGraphNode::ExternalModuleReference(reference),
+                    node: ChunkContentGraphNode::ExternalModuleReference { reference, parent },
                 }]);
             };
 
@@ -322,7 +401,10 @@ async fn graph_node_to_referenced_nodes(
                         return Ok((
                             Some(ChunkGraphEdge {
                                 key: None,
-                                node: ChunkContentGraphNode::ExternalModuleReference(reference),
+                                node: ChunkContentGraphNode::ExternalModuleReference {
+                                    reference,
+                                    parent,
+                                },
                             }),
                             None,
                         ));
@@ -359,6 +441,25 @@ async fn graph_node_to_referenced_nodes(
                                 .as_chunk_item(chunk_content_context.chunking_context)
                                 .resolve()
                                 .await?;
+                            // Static imports of split points load the split chunk group on
+                            // first use, like an async module.
+                            if let Some(chunk_name) =
+                                split_chunk_name(chunk_content_context.chunking_context, chunk_item)
+                                    .await?
+                                    .clone_value()
+                            {
+                                return Ok((
+                                    Some(ChunkGraphEdge {
+                                        key: None,
+                                        node: ChunkContentGraphNode::AsyncModule {
+                                            module: chunkable_module,
+                                            hints: AsyncLoadingHints::default(),
+                                            chunk_name: Some(chunk_name),
+                                        },
+                                    }),
+                                    Some((chunk_item, InheritAsyncEdge::SplitPoint)),
+                                ));
+                            }
                             if let Some(available_chunk_items) = chunk_content_context
                                 .availability_info
                                 .available_chunk_items()
@@ -392,6 +493,13 @@ async fn graph_node_to_referenced_nodes(
                                 key: None,
                                 node: ChunkContentGraphNode::AsyncModule {
                                     module: chunkable_module,
+                                    hints: *chunkable_module_reference
+                                        .async_loading_hints()
+                                        .await?,
+                                    chunk_name: chunkable_module_reference
+                                        .async_chunk_name()
+                                        .await?
+                                        .clone_value(),
                                 },
                             }),
                             None,
@@ -536,14 +644,21 @@ async fn chunk_content_internal_parallel(
         unreachable!();
     };
 
-    let graph_nodes: Vec<_> = traversal_result?.into_reverse_topological().collect();
+    let traversal_result = traversal_result?;
+    let async_module_parent_counts = async_module_parent_counts(&traversal_result).await?;
+    let chunk_item_children = chunk_item_children(&traversal_result);
+    let graph_nodes: Vec<_> = traversal_result.into_reverse_topological().collect();
 
     let mut chunk_items = IndexSet::new();
     let mut async_modules = IndexSet::new();
+    let mut async_module_hints = IndexMap::new();
+    let mut async_module_names = IndexMap::new();
     let mut external_module_references = IndexSet::new();
+    let mut external_module_reference_parents = IndexMap::new();
     let mut forward_edges_inherit_async = IndexMap::new();
     let mut local_back_edges_inherit_async = IndexMap::new();
     let mut available_async_modules_back_edges_inherit_async = IndexMap::new();
+    let mut split_point_back_edges_inherit_async = IndexMap::new();
 
     for graph_node in graph_nodes {
         match graph_node {
@@ -551,13 +666,31 @@ async fn chunk_content_internal_parallel(
             ChunkContentGraphNode::ChunkItem { item, .. } => {
                 chunk_items.insert(item);
             }
-            ChunkContentGraphNode::AsyncModule { module } => {
+            ChunkContentGraphNode::AsyncModule {
+                module,
+                hints,
+                chunk_name,
+            } => {
                 let module = module.resolve().await?;
                 async_modules.insert(module);
+                if !hints.is_empty() {
+                    let entry = async_module_hints
+                        .entry(module)
+                        .or_insert_with(AsyncLoadingHints::default);
+                    *entry = entry.union(hints);
+                }
+                if let Some(chunk_name) = chunk_name {
+                    async_module_names.entry(module).or_insert(chunk_name);
+                }
             }
-            ChunkContentGraphNode::ExternalModuleReference(reference) => {
+            ChunkContentGraphNode::ExternalModuleReference { reference, parent } => {
                 let reference = reference.resolve().await?;
                 external_module_references.insert(reference);
+                if let Some(parent) = parent {
+                    external_module_reference_parents
+                        .entry(reference)
+                        .or_insert(parent);
+                }
             }
             ChunkContentGraphNode::InheritAsyncInfo { item, references } => {
                 for &(reference, ty) in &references {
@@ -572,6 +705,10 @@ async fn chunk_content_internal_parallel(
                                 .or_insert_with(Vec::new)
                                 .push(item)
                         }
+                        InheritAsyncEdge::SplitPoint => split_point_back_edges_inherit_async
+                            .entry(reference)
+                            .or_insert_with(Vec::new)
+                            .push(item),
                     }
                 }
                 forward_edges_inherit_async
@@ -585,13 +722,69 @@ async fn chunk_content_internal_parallel(
     Ok(ChunkContentResult {
         chunk_items,
         async_modules,
+        async_module_hints,
+        async_module_names,
+        async_module_parent_counts,
         external_module_references,
+        external_module_reference_parents,
         forward_edges_inherit_async,
         local_back_edges_inherit_async,
         available_async_modules_back_edges_inherit_async,
+        split_point_back_edges_inherit_async,
+        chunk_item_children,
     })
 }
 
+/// Collects the chunk items which each chunk item of a chunk group references.
+fn chunk_item_children(
+    graph: &AdjacencyMap<ChunkContentGraphNode>,
+) -> IndexMap<Vc<Box<dyn ChunkItem>>, Vec<Vc<Box<dyn ChunkItem>>>> {
+    let mut children = IndexMap::new();
+    for node in graph.reverse_topological() {
+        let ChunkContentGraphNode::ChunkItem { item, .. } = node else {
+            continue;
+        };
+        let Some(edges) = graph.get(node) else {
+            continue;
+        };
+        children.insert(
+            *item,
+            edges
+                .filter_map(|child| match child {
+                    ChunkContentGraphNode::ChunkItem { item, .. } => Some(*item),
+                    _ => None,
+                })
+                .collect(),
+        );
+    }
+    children
+}
+
+/// Counts the chunk items which reference each async module of a chunk group.
+async fn async_module_parent_counts(
+    graph: &AdjacencyMap<ChunkContentGraphNode>,
+) -> Result<IndexMap<Vc<Box<dyn ChunkableModule>>, usize>> {
+    let mut counts = IndexMap::new();
+    for node in graph.reverse_topological() {
+        let ChunkContentGraphNode::ChunkItem { .. } = node else {
+            continue;
+        };
+        let Some(children) = graph.get(node) else {
+            continue;
+        };
+        let mut modules = HashSet::new();
+        for child in children {
+            if let ChunkContentGraphNode::AsyncModule { module, .. } = child {
+                modules.insert(module.resolve().await?);
+            }
+        }
+        for module in modules {
+            *counts.entry(module).or_insert(0) += 1;
+        }
+    }
+    Ok(counts)
+}
+
 #[turbo_tasks::value_trait]
 pub trait ChunkItem {
     /// The [AssetIdent] of the [Module] that this [ChunkItem] was created from.
@@ -623,16 +816,25 @@ pub trait ChunkItem {
     fn is_self_async(self: Vc<Self>) -> Vc<bool> {
         Vc::cell(false)
     }
+
+    /// An estimate of the work done when this chunk item is evaluated, e.g.
+    /// top level loops or large literals. Used to report expensive modules in
+    /// entry chunk groups, see [ChunkingContext::startup_cost_budget].
+    fn startup_cost(self: Vc<Self>) -> Vc<usize> {
+        Vc::cell(0)
+    }
 }
 
 #[turbo_tasks::value_trait]
 pub trait ChunkType: ValueToString {
-    /// Create a new chunk for the given chunk items
+    /// Create a new chunk for the given chunk items. Chunks of a named chunk
+    /// group are named after `chunk_name` instead of their chunk items.
     fn chunk(
         &self,
         chunking_context: Vc<Box<dyn ChunkingContext>>,
         chunk_items: Vec<ChunkItemWithAsyncModuleInfo>,
         referenced_output_assets: Vc<OutputAssets>,
+        chunk_name: Option<String>,
     ) -> Vc<Box<dyn Chunk>>;
 
     fn chunk_item_size(
@@ -641,6 +843,21 @@ pub trait ChunkType: ValueToString {
         chunk_item: Vc<Box<dyn ChunkItem>>,
         async_module_info: Option<Vc<AsyncModuleInfo>>,
     ) -> Vc<usize>;
+
+    /// Whether chunks of this type can be dropped when none of their chunk
+    /// items have any content. That's not the case when chunk items are
+    /// registered at runtime even if they are empty, e.g. module factories.
+    fn can_drop_empty_chunks(self: Vc<Self>) -> Vc<bool> {
+        Vc::cell(false)
+    }
+
+    /// Whether the order of chunk items of this type affects the result, e.g.
+    /// because later CSS declarations override earlier ones. Order sensitive
+    /// chunk items are ordered by the module graph, see
+    /// [ordering::order_chunk_items].
+    fn is_order_sensitive(self: Vc<Self>) -> Vc<bool> {
+        Vc::cell(false)
+    }
 }
 
 #[turbo_tasks::value(transparent)]
@@ -0,0 +1,245 @@
+use anyhow::Result;
+use indexmap::{IndexMap, IndexSet};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, TaskInput, TryJoinIterExt, ValueToString, Vc};
+use turbo_tasks_fs::FileSystemPath;
+use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64};
+
+use super::ModuleId;
+use crate::{
+    ident::AssetIdent,
+    issue::{Issue, IssueExt, IssueSeverity},
+    module::{Module, Modules},
+    reference::all_modules_from_entries,
+};
+
+/// How the ids of chunk items are derived from the idents of their modules.
+#[derive(
+    Debug,
+    Default,
+    TaskInput,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    TraceRawVcs,
+)]
+pub enum ModuleIdStrategy {
+    /// The ident itself, e.g. `[project]/src/index.js (ecmascript)`. It's
+    /// readable, but long and reveals the directory structure of the project.
+    #[default]
+    FullPath,
+    /// A 64 bit hash of the ident as hex string.
+    Hashed,
+    /// A 32 bit number derived from a hash of the ident. It's the shortest,
+    /// but ids of different modules may collide in very large module graphs,
+    /// see [detect_module_id_collisions].
+    Deterministic,
+}
+
+/// Computes the id of the chunk item with `ident` with `strategy`. The id
+/// only changes when the ident changes, e.g. when the module is moved.
+#[turbo_tasks::function]
+pub async fn module_id_from_ident(
+    ident: Vc<AssetIdent>,
+    strategy: ModuleIdStrategy,
+) -> Result<Vc<ModuleId>> {
+    let ident = ident.to_string().await?;
+    Ok(match strategy {
+        ModuleIdStrategy::FullPath => ModuleId::String(ident.clone_value()),
+        ModuleIdStrategy::Hashed => ModuleId::String(encode_hex(hash_xxh3_hash64(ident.as_str()))),
+        ModuleIdStrategy::Deterministic => {
+            ModuleId::Number(hash_xxh3_hash64(ident.as_str()) as u32)
+        }
+    }
+    .cell())
+}
+
+/// Groups the idents by their ids and returns the ids which are shared by
+/// more than one ident, along with the sorted idents.
+fn module_id_collisions(
+    ids: impl IntoIterator<Item = (ModuleId, String)>,
+) -> Vec<(ModuleId, Vec<String>)> {
+    let mut idents_by_id = IndexMap::<ModuleId, IndexSet<String>>::new();
+    for (id, ident) in ids {
+        idents_by_id.entry(id).or_default().insert(ident);
+    }
+    idents_by_id
+        .into_iter()
+        .filter(|(_, idents)| idents.len() > 1)
+        .map(|(id, idents)| {
+            let mut idents: Vec<_> = idents.into_iter().collect();
+            idents.sort();
+            (id, idents)
+        })
+        .collect()
+}
+
+/// Walks the module graph from `entries` and emits a
+/// [ModuleIdCollisionIssue] for each id which `strategy` derives for more than
+/// one module. The runtime registers the modules by their ids, so one of them
+/// would run in place of the other.
+#[turbo_tasks::function]
+pub async fn detect_module_id_collisions(
+    entries: Vc<Modules>,
+    strategy: ModuleIdStrategy,
+) -> Result<Vc<()>> {
+    // The ident itself is unique.
+    if strategy == ModuleIdStrategy::FullPath {
+        return Ok(Default::default());
+    }
+
+    let modules = all_modules_from_entries(entries).await?;
+    let ids = modules
+        .iter()
+        .map(|module| async move {
+            let ident = module.ident();
+            Ok((
+                module_id_from_ident(ident, strategy).await?.clone_value(),
+                (ident.to_string().await?.clone_value(), ident.path()),
+            ))
+        })
+        .try_join()
+        .await?;
+    let paths: IndexMap<_, _> = ids
+        .iter()
+        .map(|(_, (ident, path))| (ident.clone(), *path))
+        .collect();
+
+    for (id, idents) in module_id_collisions(ids.into_iter().map(|(id, (ident, _))| (id, ident))) {
+        ModuleIdCollisionIssue {
+            path: paths[&idents[0]],
+            id,
+            idents,
+        }
+        .cell()
+        .emit();
+    }
+
+    Ok(Default::default())
+}
+
+/// Reports modules which got the same id. The build would run the wrong code
+/// for one of them, so this is an error.
+#[turbo_tasks::value(shared)]
+pub struct ModuleIdCollisionIssue {
+    pub path: Vc<FileSystemPath>,
+    pub id: ModuleId,
+    pub idents: Vec<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for ModuleIdCollisionIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("chunking".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn code(&self) -> Vc<Option<String>> {
+        Vc::cell(Some("TP2006".to_string()))
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "{} modules have the same module id {}",
+            self.idents.len(),
+            self.id
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        let mut description = "The module ids are derived from hashes of the module paths, which \
+                               collided. Use hashed or full path module ids instead:"
+            .to_string();
+        for ident in &self.idents {
+            description.push_str(&format!("\n- {ident}"));
+        }
+        Vc::cell(description)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lazy_static::lazy_static;
+    use turbo_tasks::ValueToString;
+    use turbo_tasks_fs::VirtualFileSystem;
+    use turbo_tasks_hash::hash_xxh3_hash64;
+    use turbo_tasks_testing::run;
+
+    use super::{module_id_collisions, module_id_from_ident, ModuleIdStrategy};
+    use crate::{chunk::ModuleId, ident::AssetIdent};
+
+    lazy_static! {
+        static ref REGISTER: () = crate::register();
+    }
+
+    #[tokio::test]
+    async fn module_ids_are_derived_from_the_ident() {
+        run! {
+            let root = VirtualFileSystem::new().root();
+            let ident = AssetIdent::from_path(root.join("src/index.js".to_string()));
+            let other = AssetIdent::from_path(root.join("src/other.js".to_string()));
+            let ident_string = ident.to_string().await?.clone_value();
+
+            assert_eq!(
+                *module_id_from_ident(ident, ModuleIdStrategy::FullPath).await?,
+                ModuleId::String(ident_string.clone())
+            );
+
+            let hashed = module_id_from_ident(ident, ModuleIdStrategy::Hashed).await?;
+            let ModuleId::String(hash) = &*hashed else {
+                panic!("hashed ids should be strings");
+            };
+            assert_eq!(hash.len(), 16);
+            assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
+            assert_ne!(
+                *module_id_from_ident(other, ModuleIdStrategy::Hashed).await?,
+                *hashed
+            );
+
+            assert_eq!(
+                *module_id_from_ident(ident, ModuleIdStrategy::Deterministic).await?,
+                ModuleId::Number(hash_xxh3_hash64(ident_string.as_str()) as u32)
+            );
+            assert_ne!(
+                *module_id_from_ident(other, ModuleIdStrategy::Deterministic).await?,
+                *module_id_from_ident(ident, ModuleIdStrategy::Deterministic).await?
+            );
+        }
+    }
+
+    #[test]
+    fn finds_colliding_module_ids() {
+        let collisions = module_id_collisions([
+            (ModuleId::Number(1), "[project]/b.js".to_string()),
+            (ModuleId::Number(2), "[project]/c.js".to_string()),
+            (ModuleId::Number(1), "[project]/a.js".to_string()),
+            (ModuleId::Number(1), "[project]/b.js".to_string()),
+        ]);
+        assert_eq!(
+            collisions,
+            vec![(
+                ModuleId::Number(1),
+                vec!["[project]/a.js".to_string(), "[project]/b.js".to_string()]
+            )]
+        );
+    }
+}
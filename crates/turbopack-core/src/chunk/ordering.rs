@@ -0,0 +1,275 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashSet},
+    hash::Hash,
+};
+
+use anyhow::Result;
+use indexmap::{IndexMap, IndexSet};
+use turbo_tasks::{TryJoinIterExt, ValueToString, Vc};
+use turbo_tasks_fs::FileSystemPath;
+
+use super::ChunkItem;
+use crate::issue::{Issue, IssueExt, IssueSeverity};
+
+/// Orders the chunk items of order sensitive chunk types (see
+/// [super::ChunkType::is_order_sensitive]) deterministically by the module
+/// graph, independent of the order in which the graph was traversed.
+///
+/// Every chunk item requires the order sensitive chunk items it references,
+/// directly or through chunk items which are not order sensitive (e.g. CSS
+/// modules), to be ordered like the traversal orders them: later references
+/// first. When these requirements contradict each other, a
+/// [ChunkItemsOrderConflictIssue] is emitted and the chunk item which comes
+/// first in the original order wins.
+///
+/// Chunk items which are not order sensitive keep their positions.
+pub async fn order_chunk_items(
+    chunk_items: IndexSet<Vc<Box<dyn ChunkItem>>>,
+    chunk_item_children: &IndexMap<Vc<Box<dyn ChunkItem>>, Vec<Vc<Box<dyn ChunkItem>>>>,
+) -> Result<IndexSet<Vc<Box<dyn ChunkItem>>>> {
+    let order_sensitive = chunk_items
+        .iter()
+        .map(|&chunk_item| async move { Ok(*chunk_item.ty().is_order_sensitive().await?) })
+        .try_join()
+        .await?;
+    // Maps order sensitive chunk items to their index in this list.
+    let sensitive_items: IndexSet<Vc<Box<dyn ChunkItem>>> = chunk_items
+        .iter()
+        .zip(order_sensitive.iter())
+        .filter(|&(_, &is_order_sensitive)| is_order_sensitive)
+        .map(|(&chunk_item, _)| chunk_item)
+        .collect();
+    if sensitive_items.len() < 2 {
+        return Ok(chunk_items);
+    }
+
+    let successors = order_requirements(&sensitive_items, chunk_item_children);
+    let (ordered, conflicts) = toposort(&successors);
+    for (index, conflicting) in conflicts {
+        let mut items = vec![sensitive_items[index].asset_ident().to_string().await?];
+        for other in conflicting {
+            items.push(sensitive_items[other].asset_ident().to_string().await?);
+        }
+        ChunkItemsOrderConflictIssue {
+            file_path: sensitive_items[index].asset_ident().path(),
+            items: items.iter().map(|item| item.to_string()).collect(),
+        }
+        .cell()
+        .emit();
+    }
+
+    let mut ordered = ordered.into_iter().map(|index| sensitive_items[index]);
+    Ok(chunk_items
+        .into_iter()
+        .zip(order_sensitive)
+        .map(|(chunk_item, is_order_sensitive)| {
+            if is_order_sensitive {
+                ordered.next().unwrap()
+            } else {
+                chunk_item
+            }
+        })
+        .collect())
+}
+
+/// Returns the successors of every item of `sensitive_items` by its index:
+/// the items which are required to come after it.
+///
+/// The items which `children` references, directly or through items which
+/// aren't in `sensitive_items`, are required to be ordered like the
+/// traversal orders them: later references first.
+fn order_requirements<T: Copy + Eq + Hash>(
+    sensitive_items: &IndexSet<T>,
+    children: &IndexMap<T, Vec<T>>,
+) -> Vec<IndexSet<usize>> {
+    // The indices of the sensitive items which are reached from `child`
+    // without passing another sensitive item.
+    let group = |child: T| -> Vec<usize> {
+        let mut indices = Vec::new();
+        let mut visited = HashSet::new();
+        let mut stack = vec![child];
+        while let Some(item) = stack.pop() {
+            if !visited.insert(item) {
+                continue;
+            }
+            if let Some(index) = sensitive_items.get_index_of(&item) {
+                indices.push(index);
+            } else if let Some(grandchildren) = children.get(&item) {
+                stack.extend(grandchildren.iter().rev().copied());
+            }
+        }
+        indices
+    };
+
+    let mut successors = vec![IndexSet::new(); sensitive_items.len()];
+    for children in children.values() {
+        let mut previous = Vec::new();
+        // Later references are traversed first, so they come first.
+        for &child in children.iter().rev() {
+            let current = group(child);
+            if current.is_empty() {
+                continue;
+            }
+            for &before in &previous {
+                for &after in &current {
+                    if before != after {
+                        successors[before].insert(after);
+                    }
+                }
+            }
+            previous = current;
+        }
+    }
+    successors
+}
+
+/// Orders the indices of `successors` topologically, preferring lower indices
+/// when the order isn't constrained.
+///
+/// Cycles are broken at their lowest index, which is returned along with the
+/// indices which were required to come before it.
+fn toposort(successors: &[IndexSet<usize>]) -> (Vec<usize>, Vec<(usize, Vec<usize>)>) {
+    let len = successors.len();
+    let mut in_degrees = vec![0usize; len];
+    for successors in successors {
+        for &successor in successors {
+            in_degrees[successor] += 1;
+        }
+    }
+
+    let mut ready: BinaryHeap<Reverse<usize>> = in_degrees
+        .iter()
+        .enumerate()
+        .filter(|&(_, &in_degree)| in_degree == 0)
+        .map(|(index, _)| Reverse(index))
+        .collect();
+    let mut done = vec![false; len];
+    let mut ordered = Vec::with_capacity(len);
+    let mut conflicts = Vec::new();
+    while ordered.len() < len {
+        let index = match ready.pop() {
+            Some(Reverse(index)) => index,
+            None => {
+                // Every remaining index waits for another one, so there is a
+                // cycle of conflicting requirements.
+                let index = (0..len).find(|&index| !done[index]).unwrap();
+                let conflicting = (0..len)
+                    .filter(|&other| !done[other] && successors[other].contains(&index))
+                    .collect::<Vec<_>>();
+                conflicts.push((index, conflicting));
+                in_degrees[index] = 0;
+                index
+            }
+        };
+        if done[index] {
+            continue;
+        }
+        done[index] = true;
+        ordered.push(index);
+        for &successor in &successors[index] {
+            if done[successor] {
+                continue;
+            }
+            in_degrees[successor] -= 1;
+            if in_degrees[successor] == 0 {
+                ready.push(Reverse(successor));
+            }
+        }
+    }
+    (ordered, conflicts)
+}
+
+/// Reports order sensitive chunk items of a chunk group which are required to
+/// come both before and after each other.
+#[turbo_tasks::value(shared)]
+struct ChunkItemsOrderConflictIssue {
+    file_path: Vc<FileSystemPath>,
+    items: Vec<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for ChunkItemsOrderConflictIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("chunking".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn code(&self) -> Vc<Option<String>> {
+        Vc::cell(Some("TP2004".to_string()))
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell("Conflicting order of modules".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "The modules below are imported in different orders by modules of the same chunk \
+             group, so their order in the output is ambiguous. The first module is placed before \
+             the others. Import them in the same order everywhere to resolve this conflict.\n\n{}",
+            self.items
+                .iter()
+                .map(|item| format!("- {}", item))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::{indexmap, IndexSet};
+
+    use super::{order_requirements, toposort};
+
+    fn successors(edges: &[&[usize]]) -> Vec<IndexSet<usize>> {
+        edges
+            .iter()
+            .map(|successors| successors.iter().copied().collect())
+            .collect()
+    }
+
+    #[test]
+    fn sorts_topologically_preferring_lower_indices() {
+        let (ordered, conflicts) = toposort(&successors(&[&[], &[0], &[], &[2]]));
+        assert_eq!(ordered, [1, 0, 3, 2]);
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn breaks_cycles_at_the_lowest_index() {
+        let (ordered, conflicts) = toposort(&successors(&[&[1], &[2], &[0], &[]]));
+        assert_eq!(ordered, [3, 0, 1, 2]);
+        assert_eq!(conflicts, [(0, vec![2])]);
+    }
+
+    #[test]
+    fn requires_order_through_insensitive_items() {
+        // "page" imports "a.module.css" and then "b.css", where the CSS
+        // module is only reached through its JavaScript wrapper.
+        let sensitive: IndexSet<&str> = ["b.css", "a.css"].into_iter().collect();
+        let children = indexmap! {
+            "page" => vec!["a.module.css", "b.css"],
+            "a.module.css" => vec!["a.module.css.js"],
+            "a.module.css.js" => vec!["a.css"],
+        };
+        let successors = order_requirements(&sensitive, &children);
+        // Later references come first, so "b.css" comes before "a.css".
+        assert_eq!(successors[0].iter().copied().collect::<Vec<_>>(), [1]);
+        assert!(successors[1].is_empty());
+    }
+}
@@ -0,0 +1,203 @@
+use anyhow::Result;
+use indexmap::IndexSet;
+use turbo_tasks::{TryJoinIterExt, Value, Vc};
+
+use super::{
+    availability_info::AvailabilityInfo,
+    chunk_group::{make_chunk_group, MakeChunkGroupResult},
+    AsyncLoadingHints, ChunkingContext,
+};
+use crate::{
+    module::Modules,
+    output::{OutputAsset, OutputAssets},
+};
+
+/// Describes how an [OutputAsset] should be preloaded, i.e. the attributes of
+/// a `<link rel="preload">` tag for it.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct PreloadInfo {
+    /// The value of the `as` attribute, e.g. `font`.
+    pub as_type: String,
+    /// The value of the `type` attribute, e.g. `font/woff2`.
+    pub mime_type: Option<String>,
+    /// Whether the `crossorigin` attribute must be set. Fonts are always
+    /// fetched in CORS mode, so their preloads won't be used without it.
+    pub crossorigin: bool,
+}
+
+/// An [OutputAsset] which should be preloaded when a chunk group referencing
+/// it is loaded.
+#[turbo_tasks::value_trait]
+pub trait PreloadableAsset: OutputAsset {
+    fn preload_info(self: Vc<Self>) -> Vc<PreloadInfo>;
+}
+
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct PreloadAsset {
+    pub asset: Vc<Box<dyn OutputAsset>>,
+    pub info: Vc<PreloadInfo>,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct PreloadAssets(Vec<Vc<PreloadAsset>>);
+
+/// Collects the assets which should be preloaded along with a chunk group,
+/// i.e. [PreloadableAsset]s which are part of the chunk group or referenced by
+/// one of its chunks.
+#[turbo_tasks::function]
+pub async fn chunk_group_preloads(chunk_group: Vc<OutputAssets>) -> Result<Vc<PreloadAssets>> {
+    let chunk_group = chunk_group.await?;
+    let references = chunk_group
+        .iter()
+        .map(|chunk| chunk.references())
+        .try_join()
+        .await?;
+
+    let assets: IndexSet<Vc<Box<dyn OutputAsset>>> = chunk_group
+        .iter()
+        .chain(references.iter().flat_map(|references| references.iter()))
+        .map(|asset| asset.resolve())
+        .try_join()
+        .await?
+        .into_iter()
+        .collect();
+
+    let mut preloads = Vec::new();
+    for asset in assets {
+        if let Some(preloadable) =
+            Vc::try_resolve_sidecast::<Box<dyn PreloadableAsset>>(asset).await?
+        {
+            preloads.push(
+                PreloadAsset {
+                    asset,
+                    info: preloadable.preload_info(),
+                }
+                .cell(),
+            );
+        }
+    }
+
+    Ok(Vc::cell(preloads))
+}
+
+/// An async chunk group which is referenced with [AsyncLoadingHints], e.g. by
+/// `import(/* webpackPrefetch: true */ "./page")`.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct AsyncChunkGroupHint {
+    pub hints: AsyncLoadingHints,
+    pub chunks: Vc<OutputAssets>,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct AsyncChunkGroupHints(Vec<Vc<AsyncChunkGroupHint>>);
+
+/// Collects the async chunk groups which should be prefetched or preloaded
+/// when the chunk group of `entries` is loaded, so consumers can generate
+/// `<link rel="prefetch">` and `<link rel="preload">` tags for their chunks.
+#[turbo_tasks::function]
+pub async fn chunk_group_loading_hints(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    entries: Vc<Modules>,
+    availability_info: Value<AvailabilityInfo>,
+) -> Result<Vc<AsyncChunkGroupHints>> {
+    let MakeChunkGroupResult { loading_hints, .. } = make_chunk_group(
+        chunking_context,
+        entries.await?.iter().copied(),
+        None,
+        availability_info.into_value(),
+    )
+    .await?;
+    Ok(Vc::cell(loading_hints))
+}
+
+/// A cross-origin server which chunks or assets are loaded from, i.e. the
+/// `href` of a `<link rel="preconnect">` or `<link rel="dns-prefetch">` tag.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct OriginHint {
+    /// The origin of the server, e.g. `https://cdn.example.com`.
+    pub origin: String,
+    /// Whether the `crossorigin` attribute must be set, because the
+    /// resources are fetched in CORS mode.
+    pub crossorigin: bool,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OriginHints(Vec<OriginHint>);
+
+#[turbo_tasks::value_impl]
+impl OriginHints {
+    /// Creates a new empty [Vc<OriginHints>].
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        Vc::cell(Vec::new())
+    }
+}
+
+/// Collects the cross-origin servers which the chunks of a chunk group and the
+/// assets they reference are loaded from, so consumers can connect to them
+/// ahead of time.
+#[turbo_tasks::function]
+pub async fn chunk_group_origin_hints(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    chunk_group: Vc<OutputAssets>,
+) -> Result<Vc<OriginHints>> {
+    if chunk_group.await?.is_empty() {
+        return Ok(OriginHints::empty());
+    }
+    Ok(chunking_context.origin_hints())
+}
+
+/// Returns the origin of an absolute or protocol-relative url, e.g.
+/// `https://cdn.example.com` for `https://cdn.example.com/static/`. Relative
+/// urls have no origin of their own.
+pub fn url_origin(url: &str) -> Option<String> {
+    let (scheme, rest) = match url.strip_prefix("//") {
+        Some(rest) => (None, rest),
+        None => {
+            let (scheme, rest) = url.split_once("://")?;
+            if scheme.is_empty()
+                || !scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+            {
+                return None;
+            }
+            (Some(scheme), rest)
+        }
+    };
+    let host = rest.split(['/', '?', '#']).next()?;
+    if host.is_empty() {
+        return None;
+    }
+    Some(match scheme {
+        Some(scheme) => format!("{scheme}://{host}"),
+        None => format!("//{host}"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::url_origin;
+
+    #[test]
+    fn origin_of_urls() {
+        assert_eq!(
+            url_origin("https://cdn.example.com/static/").as_deref(),
+            Some("https://cdn.example.com")
+        );
+        assert_eq!(
+            url_origin("http://localhost:3000").as_deref(),
+            Some("http://localhost:3000")
+        );
+        assert_eq!(
+            url_origin("//cdn.example.com/static/").as_deref(),
+            Some("//cdn.example.com")
+        );
+        assert_eq!(url_origin("/static/"), None);
+        assert_eq!(url_origin("static/"), None);
+    }
+}
@@ -0,0 +1,68 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::glob::Glob;
+
+use super::{ChunkItem, ChunkableModule, ChunkingContext};
+
+/// Forces the modules matching `glob` into separate async chunk groups, even
+/// when they are imported statically. The chunks are named after
+/// `chunk_name`.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct SplitPoint {
+    /// Matched against the path of a module relative to the root of its file
+    /// system, e.g. `src/editor/**`.
+    pub glob: Vc<Glob>,
+    pub chunk_name: String,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct SplitPoints(Vec<SplitPoint>);
+
+#[turbo_tasks::value_impl]
+impl SplitPoints {
+    /// Creates a new empty [Vc<SplitPoints>].
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        Vc::cell(Vec::new())
+    }
+}
+
+/// Returns the name of the async chunk group `chunk_item` is split into, if
+/// its module is a split point. A split point requested by the module itself
+/// takes precedence over the [SplitPoints] of the chunking context.
+///
+/// Static imports of split points load the chunk group on first use, so the
+/// importing modules become async modules. Split points are therefore ignored
+/// when the chunking context doesn't support async modules.
+#[turbo_tasks::function]
+pub async fn split_chunk_name(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    chunk_item: Vc<Box<dyn ChunkItem>>,
+) -> Result<Vc<Option<String>>> {
+    if !*chunking_context.supports_async_modules().await? {
+        return Ok(Vc::cell(None));
+    }
+
+    let module = chunk_item.module();
+    if let Some(chunkable_module) =
+        Vc::try_resolve_sidecast::<Box<dyn ChunkableModule>>(module).await?
+    {
+        let chunk_name = chunkable_module.split_chunk_name();
+        if chunk_name.await?.is_some() {
+            return Ok(chunk_name);
+        }
+    }
+
+    let split_points = chunking_context.split_points().await?;
+    if !split_points.is_empty() {
+        let path = module.ident().path().await?;
+        for split_point in split_points.iter() {
+            if split_point.glob.await?.execute(&path.path) {
+                return Ok(Vc::cell(Some(split_point.chunk_name.clone())));
+            }
+        }
+    }
+
+    Ok(Vc::cell(None))
+}
@@ -0,0 +1,265 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use anyhow::Result;
+use turbo_tasks::{TryJoinIterExt, ValueToString, Vc};
+use turbo_tasks_fs::{glob::Glob, FileSystemPath};
+
+use crate::{
+    issue::{Issue, IssueExt, IssueSeverity},
+    module::{Module, Modules},
+    reference::primary_referenced_modules,
+};
+
+/// Configures [detect_circular_dependencies].
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug)]
+pub struct CircularDependenciesOptions {
+    /// The severity of the reported issues.
+    pub severity: IssueSeverity,
+    /// Cycles containing a module whose path matches one of these globs are
+    /// not reported, e.g. `**/node_modules/**`.
+    pub allow: Vec<String>,
+}
+
+/// Import cycles in the module graph. Each cycle lists the idents of its
+/// modules in import order, starting and ending with the same module.
+#[turbo_tasks::value(transparent)]
+pub struct CircularDependencies(Vec<Vec<String>>);
+
+/// Computes the strongly connected components of a graph given by its
+/// adjacency lists, using Tarjan's algorithm.
+fn strongly_connected_components(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    const UNVISITED: usize = usize::MAX;
+    let mut index = vec![UNVISITED; edges.len()];
+    let mut low_link = vec![0; edges.len()];
+    let mut on_stack = vec![false; edges.len()];
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+    let mut components = Vec::new();
+
+    for root in 0..edges.len() {
+        if index[root] != UNVISITED {
+            continue;
+        }
+        // The nodes being visited, with the position of the next edge to visit.
+        let mut visiting = vec![(root, 0)];
+        index[root] = next_index;
+        low_link[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some((node, edge)) = visiting.last_mut() {
+            let node = *node;
+            if let Some(&next) = edges[node].get(*edge) {
+                *edge += 1;
+                if index[next] == UNVISITED {
+                    index[next] = next_index;
+                    low_link[next] = next_index;
+                    next_index += 1;
+                    stack.push(next);
+                    on_stack[next] = true;
+                    visiting.push((next, 0));
+                } else if on_stack[next] {
+                    low_link[node] = low_link[node].min(index[next]);
+                }
+                continue;
+            }
+
+            visiting.pop();
+            if let Some(&(parent, _)) = visiting.last() {
+                low_link[parent] = low_link[parent].min(low_link[node]);
+            }
+            if low_link[node] == index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
+
+/// Finds the shortest cycle through `start` which only visits nodes of
+/// `component`. The cycle starts and ends with `start`.
+fn shortest_cycle(edges: &[Vec<usize>], component: &[usize], start: usize) -> Option<Vec<usize>> {
+    let component = component.iter().copied().collect::<HashSet<_>>();
+    let mut parents = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        for &next in &edges[node] {
+            if next == start {
+                let mut cycle = vec![start, node];
+                let mut current = node;
+                while let Some(&parent) = parents.get(&current) {
+                    cycle.push(parent);
+                    current = parent;
+                }
+                cycle.reverse();
+                return Some(cycle);
+            }
+            if component.contains(&next) && !parents.contains_key(&next) {
+                parents.insert(next, node);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Walks the module graph from `entries` and reports import cycles as
+/// [CircularDependencyIssue]s. One cycle is reported for each group of
+/// modules which (transitively) import each other.
+#[turbo_tasks::function]
+pub async fn detect_circular_dependencies(
+    entries: Vc<Modules>,
+    options: Vc<CircularDependenciesOptions>,
+) -> Result<Vc<CircularDependencies>> {
+    let options = options.await?;
+    let allow = options
+        .allow
+        .iter()
+        .map(|glob| Glob::new(glob.clone()))
+        .try_join()
+        .await?;
+
+    let mut modules = entries.await?.clone_value();
+    let mut indices = modules
+        .iter()
+        .enumerate()
+        .map(|(index, &module)| (module, index))
+        .collect::<HashMap<_, _>>();
+    let mut edges = Vec::new();
+    while edges.len() < modules.len() {
+        let references = primary_referenced_modules(modules[edges.len()]).await?;
+        let mut targets = Vec::new();
+        for &reference in references.iter() {
+            let index = *indices.entry(reference).or_insert_with(|| {
+                modules.push(reference);
+                modules.len() - 1
+            });
+            targets.push(index);
+        }
+        edges.push(targets);
+    }
+
+    let modules = &modules;
+    let mut cycles = Vec::new();
+    for component in strongly_connected_components(&edges) {
+        // Components are found in reverse topological order, and the last
+        // member is the first one reached from the entries.
+        let start = component[component.len() - 1];
+        let Some(cycle) = shortest_cycle(&edges, &component, start) else {
+            continue;
+        };
+
+        let paths = cycle
+            .iter()
+            .map(|&index| async move { Ok(modules[index].ident().path().await?.path.clone()) })
+            .try_join()
+            .await?;
+        if paths
+            .iter()
+            .any(|path| allow.iter().any(|glob| glob.execute(path)))
+        {
+            continue;
+        }
+
+        let idents = cycle
+            .iter()
+            .map(|&index| async move {
+                let ident = modules[index].ident().to_string().await?;
+                Ok(ident.clone_value())
+            })
+            .try_join()
+            .await?;
+        CircularDependencyIssue {
+            file_path: modules[start].ident().path(),
+            severity: options.severity,
+            cycle: idents.clone(),
+        }
+        .cell()
+        .emit();
+        cycles.push(idents);
+    }
+
+    Ok(Vc::cell(cycles))
+}
+
+/// Reports an import cycle. Modules in a cycle can observe each other's
+/// exports before they are initialized, e.g. as `undefined` when CommonJS
+/// and ESM modules are mixed.
+#[turbo_tasks::value(shared)]
+pub struct CircularDependencyIssue {
+    pub file_path: Vc<FileSystemPath>,
+    pub severity: IssueSeverity,
+    pub cycle: Vec<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for CircularDependencyIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        self.severity.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("module graph".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn code(&self) -> Vc<Option<String>> {
+        Vc::cell(Some("TP2003".to_string()))
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell("Circular dependency detected".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "These modules import each other, so some of them are evaluated before their \
+             dependencies. Their imports might be undefined at that time.\n{}",
+            self.cycle.join("\n  -> ")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_cycles() {
+        // 0 -> 1 -> 2 -> 0, 2 -> 3, 3 -> 3, 4 -> 1
+        let edges = vec![vec![1], vec![2], vec![0, 3], vec![3], vec![1]];
+        let mut components = strongly_connected_components(&edges);
+        for component in &mut components {
+            component.sort();
+        }
+        components.sort();
+        assert_eq!(components, vec![vec![0, 1, 2], vec![3], vec![4]]);
+
+        assert_eq!(
+            shortest_cycle(&edges, &[0, 1, 2], 0),
+            Some(vec![0, 1, 2, 0])
+        );
+        assert_eq!(shortest_cycle(&edges, &[3], 3), Some(vec![3, 3]));
+        assert_eq!(shortest_cycle(&edges, &[4], 4), None);
+    }
+}
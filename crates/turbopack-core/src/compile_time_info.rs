@@ -173,6 +173,79 @@ impl FreeVarReferences {
     pub fn empty() -> Vc<Self> {
         Vc::cell(IndexMap::new())
     }
+
+    /// Adds a reference to the provided module for every provided identifier
+    /// which has no free var reference yet.
+    #[turbo_tasks::function]
+    pub async fn with_provided_modules(
+        self: Vc<Self>,
+        provided_modules: Vc<ProvidedModules>,
+    ) -> Result<Vc<Self>> {
+        let mut free_var_references = self.await?.clone_value();
+        for (name, provided_module) in provided_modules.await?.iter() {
+            free_var_references
+                .entry(vec![name.clone()])
+                .or_insert_with(|| FreeVarReference::EcmaScriptModule {
+                    request: provided_module.request.clone(),
+                    lookup_path: None,
+                    export: Some(provided_module.export.clone()),
+                });
+        }
+        Ok(Vc::cell(free_var_references))
+    }
+}
+
+/// A module which is automatically imported when the identifier it's provided
+/// for is used as a free variable, like webpack's `ProvidePlugin`.
+#[turbo_tasks::value]
+#[derive(Debug, Clone)]
+pub struct ProvidedModule {
+    /// The request to import, resolved from the module using the identifier.
+    pub request: String,
+    /// The export to bind the identifier to. Use `default` to bind the
+    /// `module.exports` of a CommonJS module, e.g. `jquery`.
+    pub export: String,
+}
+
+/// Maps free identifiers (e.g. `Buffer`, `process`, `$`) to the modules which
+/// provide them.
+#[turbo_tasks::value(transparent)]
+#[derive(Debug, Clone)]
+pub struct ProvidedModules(pub IndexMap<String, ProvidedModule>);
+
+#[turbo_tasks::value_impl]
+impl ProvidedModules {
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        Vc::cell(IndexMap::new())
+    }
+}
+
+/// How the `import.meta.url` of modules is generated.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(Debug, Default, Clone, Copy, Hash, PartialOrd, Ord)]
+pub enum ImportMetaUrl {
+    /// A `file://` url of the module's path.
+    #[default]
+    File,
+    /// The url the module's path would be served from, i.e. relative to
+    /// `location.origin` on the client and to the server address when server
+    /// rendering. Environments without rendering use a `file://` url.
+    Served,
+}
+
+/// The values of `import.meta.env`, like Vite's. Accesses of known keys are
+/// replaced with their values at build time.
+#[turbo_tasks::value(transparent)]
+#[derive(Debug, Clone)]
+pub struct ImportMetaEnv(pub IndexMap<String, CompileTimeDefineValue>);
+
+#[turbo_tasks::value_impl]
+impl ImportMetaEnv {
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        Vc::cell(IndexMap::new())
+    }
 }
 
 #[turbo_tasks::value(shared)]
@@ -181,6 +254,9 @@ pub struct CompileTimeInfo {
     pub environment: Vc<Environment>,
     pub defines: Vc<CompileTimeDefines>,
     pub free_var_references: Vc<FreeVarReferences>,
+    pub import_meta_url: ImportMetaUrl,
+    /// `import.meta.env` is only defined when set.
+    pub import_meta_env: Option<Vc<ImportMetaEnv>>,
 }
 
 impl CompileTimeInfo {
@@ -189,6 +265,9 @@ impl CompileTimeInfo {
             environment,
             defines: None,
             free_var_references: None,
+            provided_modules: None,
+            import_meta_url: Default::default(),
+            import_meta_env: None,
         }
     }
 }
@@ -201,6 +280,8 @@ impl CompileTimeInfo {
             environment,
             defines: CompileTimeDefines::empty(),
             free_var_references: FreeVarReferences::empty(),
+            import_meta_url: Default::default(),
+            import_meta_env: None,
         }
         .cell()
     }
@@ -215,6 +296,9 @@ pub struct CompileTimeInfoBuilder {
     environment: Vc<Environment>,
     defines: Option<Vc<CompileTimeDefines>>,
     free_var_references: Option<Vc<FreeVarReferences>>,
+    provided_modules: Option<Vc<ProvidedModules>>,
+    import_meta_url: ImportMetaUrl,
+    import_meta_env: Option<Vc<ImportMetaEnv>>,
 }
 
 impl CompileTimeInfoBuilder {
@@ -228,13 +312,36 @@ impl CompileTimeInfoBuilder {
         self
     }
 
+    /// Injects imports of the provided modules for free uses of their
+    /// identifiers. Explicit [FreeVarReferences] take precedence.
+    pub fn provide(mut self, provided_modules: Vc<ProvidedModules>) -> Self {
+        self.provided_modules = Some(provided_modules);
+        self
+    }
+
+    pub fn import_meta_url(mut self, import_meta_url: ImportMetaUrl) -> Self {
+        self.import_meta_url = import_meta_url;
+        self
+    }
+
+    pub fn import_meta_env(mut self, import_meta_env: Vc<ImportMetaEnv>) -> Self {
+        self.import_meta_env = Some(import_meta_env);
+        self
+    }
+
     pub fn build(self) -> CompileTimeInfo {
+        let mut free_var_references = self
+            .free_var_references
+            .unwrap_or_else(FreeVarReferences::empty);
+        if let Some(provided_modules) = self.provided_modules {
+            free_var_references = free_var_references.with_provided_modules(provided_modules);
+        }
         CompileTimeInfo {
             environment: self.environment,
             defines: self.defines.unwrap_or_else(CompileTimeDefines::empty),
-            free_var_references: self
-                .free_var_references
-                .unwrap_or_else(FreeVarReferences::empty),
+            free_var_references,
+            import_meta_url: self.import_meta_url,
+            import_meta_env: self.import_meta_env,
         }
     }
 
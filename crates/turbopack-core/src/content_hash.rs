@@ -0,0 +1,60 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use turbo_tasks::{trace::TraceRawVcs, TaskInput, Vc};
+use turbo_tasks_fs::{rope::Rope, FileContent};
+use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64};
+
+use crate::asset::AssetContent;
+
+/// The algorithm used to compute content hashes, e.g. for the paths of static
+/// assets.
+#[derive(
+    Debug,
+    Default,
+    TaskInput,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    TraceRawVcs,
+)]
+pub enum ContentHashAlgorithm {
+    /// A fast non-cryptographic 64 bit hash.
+    #[default]
+    Xxh3,
+    /// A 256 bit hash which is suitable for integrity checks.
+    Sha256,
+}
+
+impl ContentHashAlgorithm {
+    /// Hashes `content` and returns the hash as lowercase hex string.
+    pub fn hash(self, content: &Rope) -> Result<String> {
+        Ok(match self {
+            ContentHashAlgorithm::Xxh3 => encode_hex(hash_xxh3_hash64(content)),
+            ContentHashAlgorithm::Sha256 => {
+                format!("{:x}", Sha256::digest(content.to_bytes()?))
+            }
+        })
+    }
+}
+
+/// Computes the content hash of the file `content` with `algorithm`.
+#[turbo_tasks::function]
+pub async fn content_hash(
+    content: Vc<AssetContent>,
+    algorithm: ContentHashAlgorithm,
+) -> Result<Vc<String>> {
+    let AssetContent::File(file) = &*content.await? else {
+        bail!("content hash: unsupported file content");
+    };
+    let FileContent::Content(file) = &*file.await? else {
+        bail!("content hash: not found");
+    };
+    Ok(Vc::cell(algorithm.hash(file.content())?))
+}
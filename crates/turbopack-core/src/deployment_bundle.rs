@@ -0,0 +1,215 @@
+use std::iter::once;
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use turbo_tasks::{TryJoinIterExt, Vc};
+use turbo_tasks_fs::{File, FileContent, FileSystemPath};
+
+use crate::{
+    asset::{Asset, AssetContent},
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets},
+    reference::all_assets_from_entries,
+};
+
+/// The path of the manifest inside a [DeploymentBundle].
+pub const DEPLOYMENT_MANIFEST: &str = "deployment-manifest.json";
+
+/// A reproducible tar archive, which can also be used as an OCI image layer,
+/// of output assets and all assets they reference, e.g. the chunks of a chunk
+/// group plus traced server files.
+///
+/// Entries are sorted by path and their timestamps, ownership and permissions
+/// are normalized, so the same outputs always produce the same archive. The
+/// archive contains a [DEPLOYMENT_MANIFEST] with the size and SHA-256 hash of
+/// every file.
+#[turbo_tasks::value]
+pub struct DeploymentBundle {
+    path: Vc<FileSystemPath>,
+    root: Vc<FileSystemPath>,
+    entries: Vc<OutputAssets>,
+}
+
+#[derive(Serialize)]
+struct DeploymentManifestEntry<'a> {
+    path: &'a str,
+    size: usize,
+    sha256: String,
+}
+
+#[turbo_tasks::value_impl]
+impl DeploymentBundle {
+    /// Creates an archive at `path` which contains `entries` and all assets
+    /// they reference. Paths in the archive are relative to `root`.
+    #[turbo_tasks::function]
+    pub fn new(
+        path: Vc<FileSystemPath>,
+        root: Vc<FileSystemPath>,
+        entries: Vc<OutputAssets>,
+    ) -> Vc<Self> {
+        DeploymentBundle {
+            path,
+            root,
+            entries,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for DeploymentBundle {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.path)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for DeploymentBundle {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<AssetContent>> {
+        let root = self.root.await?;
+        let assets = all_assets_from_entries(self.entries).await?;
+        let contents = assets
+            .iter()
+            .map(|asset| async move {
+                Ok((
+                    asset.ident().path().await?,
+                    asset.content().file_content().await?,
+                ))
+            })
+            .try_join()
+            .await?;
+
+        let mut files = Vec::new();
+        for (path, content) in &contents {
+            let FileContent::Content(file) = &**content else {
+                continue;
+            };
+            let Some(relative_path) = root.get_path_to(path) else {
+                bail!(
+                    "{} can't be added to the deployment bundle, it's not inside of {}",
+                    path.path,
+                    root.path
+                );
+            };
+            files.push((
+                relative_path.to_string(),
+                file.content().to_bytes()?.into_owned(),
+            ));
+        }
+        let archive = deployment_archive(files)?;
+
+        Ok(AssetContent::file(File::from(archive).into()))
+    }
+}
+
+/// Creates the tar archive of a [DeploymentBundle] from the relative paths and
+/// contents of its files. The order of `files` doesn't affect the archive.
+fn deployment_archive(mut files: Vec<(String, Vec<u8>)>) -> Result<Vec<u8>> {
+    files.sort();
+    // The same asset can be referenced multiple times.
+    files.dedup();
+    for window in files.windows(2) {
+        if window[0].0 == window[1].0 {
+            bail!(
+                "{} can't be added to the deployment bundle, there are multiple files with \
+                 different contents at this path",
+                window[0].0
+            );
+        }
+    }
+    if files.iter().any(|(path, _)| path == DEPLOYMENT_MANIFEST) {
+        bail!(
+            "{DEPLOYMENT_MANIFEST} can't be added to the deployment bundle, the path is reserved"
+        );
+    }
+
+    let manifest = files
+        .iter()
+        .map(|(path, content)| DeploymentManifestEntry {
+            path,
+            size: content.len(),
+            sha256: format!("{:x}", Sha256::digest(content)),
+        })
+        .collect::<Vec<_>>();
+    let manifest = serde_json::to_vec_pretty(&manifest)?;
+
+    let mut archive = tar::Builder::new(Vec::new());
+    for (path, content) in files
+        .iter()
+        .map(|(path, content)| (path.as_str(), content.as_slice()))
+        .chain(once((DEPLOYMENT_MANIFEST, manifest.as_slice())))
+    {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Regular);
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(0);
+        header.set_uid(0);
+        header.set_gid(0);
+        archive.append_data(&mut header, path, content)?;
+    }
+    Ok(archive.into_inner()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::{deployment_archive, DEPLOYMENT_MANIFEST};
+
+    fn file(path: &str, content: &str) -> (String, Vec<u8>) {
+        (path.to_string(), content.as_bytes().to_vec())
+    }
+
+    fn entries(archive: &[u8]) -> Vec<(String, String)> {
+        tar::Archive::new(archive)
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                assert_eq!(entry.header().mtime().unwrap(), 0);
+                assert_eq!(entry.header().mode().unwrap(), 0o644);
+                let path = entry.path().unwrap().to_string_lossy().into_owned();
+                let mut content = String::new();
+                entry.read_to_string(&mut content).unwrap();
+                (path, content)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn archive_is_deterministic() {
+        let a = deployment_archive(vec![
+            file("static/chunk.js", "chunk"),
+            file("index.js", "index"),
+            file("static/chunk.js", "chunk"),
+        ])
+        .unwrap();
+        let b = deployment_archive(vec![
+            file("index.js", "index"),
+            file("static/chunk.js", "chunk"),
+        ])
+        .unwrap();
+        assert_eq!(a, b);
+
+        let entries = entries(&a);
+        let paths = entries
+            .iter()
+            .map(|(path, _)| path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["index.js", "static/chunk.js", DEPLOYMENT_MANIFEST]);
+        let manifest: serde_json::Value = serde_json::from_str(&entries[2].1).unwrap();
+        assert_eq!(manifest.as_array().unwrap().len(), 2);
+        assert_eq!(manifest[1]["path"], "static/chunk.js");
+        assert_eq!(manifest[1]["size"], 5);
+    }
+
+    #[test]
+    fn archive_rejects_conflicting_files() {
+        assert!(deployment_archive(vec![file("a.js", "a"), file("a.js", "b")]).is_err());
+        assert!(deployment_archive(vec![file(DEPLOYMENT_MANIFEST, "[]")]).is_err());
+    }
+}
@@ -0,0 +1,292 @@
+use anyhow::Result;
+use indexmap::{IndexMap, IndexSet};
+use turbo_tasks::{TryJoinIterExt, ValueToString, Vc};
+use turbo_tasks_fs::FileSystemPath;
+
+use crate::{
+    issue::{Issue, IssueExt, IssueSeverity},
+    module::{Module, Modules},
+    package_json::read_package_json,
+    reference::{all_modules_from_entries, primary_referenced_modules},
+};
+
+/// An installation of a package in the module graph.
+#[turbo_tasks::value(shared)]
+#[derive(Clone)]
+pub struct PackageInstance {
+    /// The directory the package is installed in.
+    pub path: Vc<FileSystemPath>,
+    /// The version from the package's `package.json`, if any.
+    pub version: Option<String>,
+    /// The modules outside of the package which import it.
+    pub importers: Vec<String>,
+}
+
+/// A package which is part of the module graph more than once.
+#[turbo_tasks::value(shared)]
+#[derive(Clone)]
+pub struct DuplicatePackage {
+    pub name: String,
+    pub instances: Vec<PackageInstance>,
+    /// Whether the versions of all instances are semver compatible with each
+    /// other, so the package could be deduped to the highest version.
+    pub compatible: bool,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct DuplicatePackages(Vec<DuplicatePackage>);
+
+/// Returns the directory and the name of the package containing `path`, based
+/// on the last `node_modules` directory in the path.
+fn package_of(path: &str) -> Option<(&str, &str)> {
+    const NODE_MODULES: &str = "node_modules/";
+    let (index, _) = path
+        .match_indices(NODE_MODULES)
+        .filter(|&(index, _)| index == 0 || path.as_bytes()[index - 1] == b'/')
+        .last()?;
+    let start = index + NODE_MODULES.len();
+    let rest = &path[start..];
+    let mut segments = rest.split('/');
+    let first = segments.next()?;
+    let name_len = if first.starts_with('@') {
+        first.len() + 1 + segments.next()?.len()
+    } else {
+        first.len()
+    };
+    if name_len == 0 {
+        return None;
+    }
+    Some((&path[..start + name_len], &rest[..name_len]))
+}
+
+/// Returns whether all `versions` are semver compatible with the lowest of
+/// them, i.e. using the highest version everywhere doesn't cross a breaking
+/// change. Missing or invalid versions are never compatible.
+fn versions_compatible<'a>(versions: impl IntoIterator<Item = Option<&'a str>>) -> bool {
+    let Some(versions) = versions
+        .into_iter()
+        .map(|version| semver::Version::parse(version?).ok())
+        .collect::<Option<Vec<_>>>()
+    else {
+        return false;
+    };
+    let Some(lowest) = versions.iter().min() else {
+        return true;
+    };
+    let Ok(requirement) = semver::VersionReq::parse(&format!("^{lowest}")) else {
+        return false;
+    };
+    versions.iter().all(|version| requirement.matches(version))
+}
+
+type PackageImporters = (Vc<FileSystemPath>, IndexSet<Vc<Box<dyn Module>>>);
+
+async fn package_instance(
+    path: Vc<FileSystemPath>,
+    importers: IndexSet<Vc<Box<dyn Module>>>,
+) -> Result<PackageInstance> {
+    let read = read_package_json(path.join("package.json".to_string())).await?;
+    let version = match &*read {
+        Some(package_json) => package_json["version"].as_str().map(ToString::to_string),
+        None => None,
+    };
+    let importers = importers
+        .into_iter()
+        .map(|importer| async move { Ok(importer.ident().to_string().await?.clone_value()) })
+        .try_join()
+        .await?;
+    Ok(PackageInstance {
+        path,
+        version,
+        importers,
+    })
+}
+
+/// Walks the module graph from `entries` and finds packages which are
+/// installed in more than one directory, e.g. because different importers
+/// depend on incompatible versions. Emits a [DuplicatePackagesIssue] for each
+/// of them.
+#[turbo_tasks::function]
+pub async fn detect_duplicate_packages(entries: Vc<Modules>) -> Result<Vc<DuplicatePackages>> {
+    let modules = all_modules_from_entries(entries)
+        .await?
+        .iter()
+        .map(|&module| async move { Ok((module, primary_referenced_modules(module).await?)) })
+        .try_join()
+        .await?;
+
+    let paths = modules
+        .iter()
+        .map(|(module, _)| async move {
+            let path = module.ident().path();
+            Ok((*module, (path, path.await?)))
+        })
+        .try_join()
+        .await?
+        .into_iter()
+        .collect::<IndexMap<_, _>>();
+
+    // Package name -> package directory -> importers.
+    let mut packages = IndexMap::<&str, IndexMap<&str, PackageImporters>>::new();
+    for (module, references) in &modules {
+        let (path, path_value) = &paths[module];
+        let package = package_of(&path_value.path);
+        if let Some((dir, name)) = package {
+            packages
+                .entry(name)
+                .or_default()
+                .entry(dir)
+                .or_insert_with(|| (path.root().join(dir.to_string()), IndexSet::new()));
+        }
+        for reference in references.iter() {
+            let (reference_path, reference_path_value) = &paths[reference];
+            let Some((dir, name)) = package_of(&reference_path_value.path) else {
+                continue;
+            };
+            let (_, importers) = packages
+                .entry(name)
+                .or_default()
+                .entry(dir)
+                .or_insert_with(|| (reference_path.root().join(dir.to_string()), IndexSet::new()));
+            if package.map(|(importer_dir, _)| importer_dir) != Some(dir) {
+                importers.insert(*module);
+            }
+        }
+    }
+
+    let mut duplicates = Vec::new();
+    for (name, instances) in packages {
+        if instances.len() < 2 {
+            continue;
+        }
+        let instances = instances
+            .into_values()
+            .map(|(path, importers)| package_instance(path, importers))
+            .try_join()
+            .await?;
+        let compatible =
+            versions_compatible(instances.iter().map(|instance| instance.version.as_deref()));
+        let duplicate = DuplicatePackage {
+            name: name.to_string(),
+            instances,
+            compatible,
+        };
+        DuplicatePackagesIssue {
+            package: duplicate.clone(),
+        }
+        .cell()
+        .emit();
+        duplicates.push(duplicate);
+    }
+
+    Ok(Vc::cell(duplicates))
+}
+
+/// Reports a package which is part of the module graph more than once, which
+/// increases the bundle size and can break packages relying on module-level
+/// state, e.g. React. Duplicates with semver compatible versions are reported
+/// as warnings, as they can be deduped, while incompatible ones are only
+/// hints.
+#[turbo_tasks::value(shared)]
+pub struct DuplicatePackagesIssue {
+    pub package: DuplicatePackage,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for DuplicatePackagesIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        if self.package.compatible {
+            IssueSeverity::Warning.into()
+        } else {
+            IssueSeverity::Hint.into()
+        }
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("resolve".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn code(&self) -> Vc<Option<String>> {
+        Vc::cell(Some("TP2002".to_string()))
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "Package {} is included {} times",
+            self.package.name,
+            self.package.instances.len()
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.package.instances[0].path
+    }
+
+    #[turbo_tasks::function]
+    async fn description(&self) -> Result<Vc<String>> {
+        let mut description = if self.package.compatible {
+            "Multiple semver compatible versions of the package are part of the module graph. \
+             Consider aligning the dependency versions, or enable deduping packages to use the \
+             highest version everywhere:"
+        } else {
+            "Multiple incompatible versions of the package are part of the module graph. Consider \
+             aligning the dependency versions:"
+        }
+        .to_string();
+        for instance in &self.package.instances {
+            description.push_str(&format!(
+                "\n- {} ({})",
+                instance.path.await?.path,
+                instance.version.as_deref().unwrap_or("unknown version")
+            ));
+            for importer in &instance.importers {
+                description.push_str(&format!("\n    imported by {importer}"));
+            }
+        }
+        Ok(Vc::cell(description))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{package_of, versions_compatible};
+
+    #[test]
+    fn package_of_uses_last_node_modules() {
+        assert_eq!(
+            package_of("node_modules/react/index.js"),
+            Some(("node_modules/react", "react"))
+        );
+        assert_eq!(
+            package_of("app/node_modules/a/node_modules/b/lib/index.js"),
+            Some(("app/node_modules/a/node_modules/b", "b"))
+        );
+        assert_eq!(
+            package_of("node_modules/@scope/pkg/index.js"),
+            Some(("node_modules/@scope/pkg", "@scope/pkg"))
+        );
+    }
+
+    #[test]
+    fn package_of_ignores_non_packages() {
+        assert_eq!(package_of("src/index.js"), None);
+        assert_eq!(package_of("src/my_node_modules/a/index.js"), None);
+        assert_eq!(package_of("node_modules/@scope"), None);
+        assert_eq!(package_of("node_modules/"), None);
+    }
+
+    #[test]
+    fn compatible_versions() {
+        assert!(versions_compatible([Some("1.2.0"), Some("1.4.3")]));
+        assert!(versions_compatible([Some("0.3.1"), Some("0.3.0")]));
+        assert!(!versions_compatible([Some("1.2.0"), Some("2.0.0")]));
+        assert!(!versions_compatible([Some("0.3.1"), Some("0.4.0")]));
+        assert!(!versions_compatible([Some("1.2.0"), None]));
+        assert!(!versions_compatible([Some("1.2.0"), Some("latest")]));
+    }
+}
@@ -8,12 +8,15 @@ use std::{
 use anyhow::{anyhow, bail, Context, Result};
 use serde::{Deserialize, Serialize};
 use swc_core::ecma::preset_env::{Version, Versions};
-use turbo_tasks::{Value, Vc};
+use turbo_tasks::{trace::TraceRawVcs, Value, Vc};
 use turbo_tasks_env::ProcessEnv;
 
 use crate::target::CompileTarget;
 
 static DEFAULT_NODEJS_VERSION: &str = "16.0.0";
+/// The V8 of the edge runtimes. This is pinned, so that the output doesn't
+/// change when the browserslist database is updated.
+static EDGE_BROWSERSLIST_QUERY: &str = "chrome 118";
 
 #[turbo_tasks::value(shared)]
 #[derive(Default)]
@@ -144,6 +147,9 @@ impl Rendering {
 #[turbo_tasks::value]
 #[derive(Default)]
 pub enum ChunkLoading {
+    /// Chunks can't be loaded at runtime. All chunks of a chunk group are
+    /// statically imported or concatenated, e.g. in edge runtimes which can't
+    /// fetch or evaluate code.
     #[default]
     None,
     /// CommonJS in Node.js
@@ -156,6 +162,8 @@ pub enum ChunkLoading {
 pub struct Environment {
     // members must be private to avoid leaking non-custom types
     execution: ExecutionEnvironment,
+    /// Overrides the export conditions of the execution environment.
+    resolve_conditions: Option<Vec<String>>,
 }
 
 #[turbo_tasks::value_impl]
@@ -164,8 +172,38 @@ impl Environment {
     pub fn new(execution: Value<ExecutionEnvironment>) -> Vc<Self> {
         Self::cell(Environment {
             execution: execution.into_value(),
+            resolve_conditions: None,
         })
     }
+
+    /// Returns a copy of this environment which sets `conditions` as export
+    /// conditions instead of the ones of the execution environment, e.g.
+    /// `["worker", "browser"]` for a web worker or `["deno"]`.
+    #[turbo_tasks::function]
+    pub async fn with_resolve_conditions(
+        self: Vc<Self>,
+        conditions: Vec<String>,
+    ) -> Result<Vc<Self>> {
+        Ok(Environment {
+            execution: self.await?.execution,
+            resolve_conditions: Some(conditions),
+        }
+        .cell())
+    }
+
+    /// An environment for the edge `runtime`, e.g. Cloudflare Workers. Node.js
+    /// built-ins which the runtime doesn't provide are reported when
+    /// resolving them, and chunks are never loaded at runtime.
+    #[turbo_tasks::function]
+    pub fn edge(runtime: Value<EdgeRuntime>) -> Vc<Self> {
+        Environment::new(Value::new(ExecutionEnvironment::EdgeWorker(
+            EdgeWorkerEnvironment {
+                server_addr: ServerAddr::empty(),
+                runtime: runtime.into_value(),
+            }
+            .cell(),
+        )))
+    }
 }
 
 #[turbo_tasks::value(serialization = "auto_for_input")]
@@ -175,6 +213,8 @@ pub enum ExecutionEnvironment {
     NodeJsLambda(Vc<NodeJsEnvironment>),
     EdgeWorker(Vc<EdgeWorkerEnvironment>),
     Browser(Vc<BrowserEnvironment>),
+    Electron(Vc<ElectronEnvironment>),
+    ReactNative(Vc<ReactNativeEnvironment>),
     // TODO allow custom trait here
     Custom(u8),
 }
@@ -189,6 +229,16 @@ impl Environment {
             | ExecutionEnvironment::NodeJsLambda(node_env) => node_env.await?.compile_target,
             ExecutionEnvironment::Browser(_) => CompileTarget::unknown(),
             ExecutionEnvironment::EdgeWorker(_) => CompileTarget::unknown(),
+            ExecutionEnvironment::Electron(electron_env) => {
+                let electron_env = electron_env.await?;
+                match electron_env.process {
+                    ElectronProcess::Main | ElectronProcess::Preload => {
+                        electron_env.node.await?.compile_target
+                    }
+                    ElectronProcess::Renderer => CompileTarget::unknown(),
+                }
+            }
+            ExecutionEnvironment::ReactNative(_) => CompileTarget::unknown(),
             ExecutionEnvironment::Custom(_) => todo!(),
         })
     }
@@ -200,16 +250,58 @@ impl Environment {
             ExecutionEnvironment::NodeJsBuildTime(node_env, ..)
             | ExecutionEnvironment::NodeJsLambda(node_env) => node_env.runtime_versions(),
             ExecutionEnvironment::Browser(browser_env) => {
-                Vc::cell(Versions::parse_versions(browserslist::resolve(
-                    browser_env.await?.browserslist_query.split(','),
-                    &browserslist::Opts::new(),
-                )?)?)
+                browserslist_versions(&browser_env.await?.browserslist_query)?
+            }
+            ExecutionEnvironment::Electron(electron_env) => {
+                let electron_env = electron_env.await?;
+                match electron_env.process {
+                    ElectronProcess::Main => electron_env.node.runtime_versions(),
+                    ElectronProcess::Preload | ElectronProcess::Renderer => {
+                        browserslist_versions(&electron_env.browserslist_query)?
+                    }
+                }
             }
-            ExecutionEnvironment::EdgeWorker(_) => todo!(),
+            // Hermes and JavaScriptCore aren't known to browserslist, so
+            // everything is transpiled like Metro's Babel preset does.
+            ExecutionEnvironment::ReactNative(_) => Vc::cell(Versions::default()),
+            // Edge runtimes are based on a recent V8.
+            ExecutionEnvironment::EdgeWorker(_) => browserslist_versions(EDGE_BROWSERSLIST_QUERY)?,
             ExecutionEnvironment::Custom(_) => todo!(),
         })
     }
 
+    /// The edge runtime of edge worker environments, if any.
+    #[turbo_tasks::function]
+    pub async fn edge_runtime(self: Vc<Self>) -> Result<Vc<OptionEdgeRuntime>> {
+        let this = self.await?;
+        Ok(Vc::cell(match this.execution {
+            ExecutionEnvironment::EdgeWorker(edge_env) => Some(edge_env.await?.runtime),
+            _ => None,
+        }))
+    }
+
+    /// The browserslist query of environments which render in a browser, if
+    /// any.
+    #[turbo_tasks::function]
+    pub async fn browserslist_query(self: Vc<Self>) -> Result<Vc<Option<String>>> {
+        let this = self.await?;
+        Ok(Vc::cell(match this.execution {
+            ExecutionEnvironment::Browser(browser_env) => {
+                Some(browser_env.await?.browserslist_query.clone())
+            }
+            ExecutionEnvironment::Electron(electron_env) => {
+                let electron_env = electron_env.await?;
+                match electron_env.process {
+                    ElectronProcess::Main => None,
+                    ElectronProcess::Preload | ElectronProcess::Renderer => {
+                        Some(electron_env.browserslist_query.clone())
+                    }
+                }
+            }
+            _ => None,
+        }))
+    }
+
     #[turbo_tasks::function]
     pub async fn node_externals(self: Vc<Self>) -> Result<Vc<bool>> {
         let this = self.await?;
@@ -219,10 +311,32 @@ impl Environment {
             }
             ExecutionEnvironment::Browser(_) => Vc::cell(false),
             ExecutionEnvironment::EdgeWorker(_) => Vc::cell(false),
+            ExecutionEnvironment::Electron(electron_env) => {
+                Vc::cell(electron_env.await?.process.has_node_integration())
+            }
+            ExecutionEnvironment::ReactNative(_) => Vc::cell(false),
             ExecutionEnvironment::Custom(_) => todo!(),
         })
     }
 
+    /// Requests which are provided by the runtime and must not be bundled, in
+    /// addition to the Node.js built-ins when [Environment::node_externals]
+    /// is enabled.
+    #[turbo_tasks::function]
+    pub async fn runtime_externals(self: Vc<Self>) -> Result<Vc<Vec<String>>> {
+        let this = self.await?;
+        Ok(match this.execution {
+            ExecutionEnvironment::Electron(electron_env) => {
+                if electron_env.await?.process.has_node_integration() {
+                    Vc::cell(vec!["electron".to_string()])
+                } else {
+                    Vc::<Vec<String>>::default()
+                }
+            }
+            _ => Vc::<Vec<String>>::default(),
+        })
+    }
+
     #[turbo_tasks::function]
     pub async fn resolve_extensions(self: Vc<Self>) -> Result<Vc<Vec<String>>> {
         let env = self.await?;
@@ -237,6 +351,20 @@ impl Environment {
             ExecutionEnvironment::EdgeWorker(_) | ExecutionEnvironment::Browser(_) => {
                 Vc::<Vec<String>>::default()
             }
+            ExecutionEnvironment::Electron(electron_env) => {
+                if electron_env.await?.process.has_node_integration() {
+                    Vc::cell(vec![
+                        ".js".to_string(),
+                        ".node".to_string(),
+                        ".json".to_string(),
+                    ])
+                } else {
+                    Vc::<Vec<String>>::default()
+                }
+            }
+            ExecutionEnvironment::ReactNative(react_native_env) => {
+                Vc::cell(react_native_env.await?.platform.resolve_extensions())
+            }
             ExecutionEnvironment::Custom(_) => todo!(),
         })
     }
@@ -251,6 +379,9 @@ impl Environment {
             ExecutionEnvironment::EdgeWorker(_) | ExecutionEnvironment::Browser(_) => {
                 Vc::cell(false)
             }
+            ExecutionEnvironment::Electron(_) | ExecutionEnvironment::ReactNative(_) => {
+                Vc::cell(true)
+            }
             ExecutionEnvironment::Custom(_) => todo!(),
         })
     }
@@ -258,22 +389,62 @@ impl Environment {
     #[turbo_tasks::function]
     pub async fn resolve_conditions(self: Vc<Self>) -> Result<Vc<Vec<String>>> {
         let env = self.await?;
+        if let Some(conditions) = &env.resolve_conditions {
+            return Ok(Vc::cell(conditions.clone()));
+        }
         Ok(match env.execution {
             ExecutionEnvironment::NodeJsBuildTime(..) | ExecutionEnvironment::NodeJsLambda(_) => {
                 Vc::cell(vec!["node".to_string()])
             }
             ExecutionEnvironment::Browser(_) => Vc::<Vec<String>>::default(),
-            ExecutionEnvironment::EdgeWorker(_) => Vc::cell(vec!["edge-worker".to_string()]),
+            ExecutionEnvironment::EdgeWorker(edge_env) => Vc::cell(
+                edge_env
+                    .await?
+                    .runtime
+                    .resolve_conditions()
+                    .iter()
+                    .map(|condition| condition.to_string())
+                    .collect(),
+            ),
+            ExecutionEnvironment::Electron(electron_env) => match electron_env.await?.process {
+                ElectronProcess::Main | ElectronProcess::Preload => {
+                    Vc::cell(vec!["electron".to_string(), "node".to_string()])
+                }
+                ElectronProcess::Renderer => {
+                    Vc::cell(vec!["electron".to_string(), "browser".to_string()])
+                }
+            },
+            ExecutionEnvironment::ReactNative(_) => Vc::cell(vec!["react-native".to_string()]),
             ExecutionEnvironment::Custom(_) => todo!(),
         })
     }
 
+    /// Fields of `package.json` which point to the entry of a package and are
+    /// preferred over `main` when emulating this environment.
+    #[turbo_tasks::function]
+    pub async fn resolve_main_fields(self: Vc<Self>) -> Result<Vc<Vec<String>>> {
+        let env = self.await?;
+        Ok(match env.execution {
+            ExecutionEnvironment::ReactNative(_) => {
+                Vc::cell(vec!["react-native".to_string(), "browser".to_string()])
+            }
+            _ => Vc::<Vec<String>>::default(),
+        })
+    }
+
     #[turbo_tasks::function]
     pub async fn cwd(self: Vc<Self>) -> Result<Vc<Option<String>>> {
         let env = self.await?;
         Ok(match env.execution {
             ExecutionEnvironment::NodeJsBuildTime(env)
             | ExecutionEnvironment::NodeJsLambda(env) => env.await?.cwd,
+            ExecutionEnvironment::Electron(env) => {
+                let env = env.await?;
+                match env.process {
+                    ElectronProcess::Main | ElectronProcess::Preload => env.node.await?.cwd,
+                    ElectronProcess::Renderer => Vc::cell(None),
+                }
+            }
             _ => Vc::cell(None),
         })
     }
@@ -290,6 +461,11 @@ impl Environment {
                 Rendering::Server(env.await?.server_addr).cell()
             }
             ExecutionEnvironment::Browser(_) => Rendering::Client.cell(),
+            ExecutionEnvironment::Electron(env) => match env.await?.process {
+                ElectronProcess::Renderer => Rendering::Client.cell(),
+                ElectronProcess::Main | ElectronProcess::Preload => Rendering::None.cell(),
+            },
+            ExecutionEnvironment::ReactNative(_) => Rendering::Client.cell(),
             _ => Rendering::None.cell(),
         })
     }
@@ -303,9 +479,33 @@ impl Environment {
             }
             ExecutionEnvironment::EdgeWorker(_) => ChunkLoading::None.cell(),
             ExecutionEnvironment::Browser(_) => ChunkLoading::Dom.cell(),
+            // Preload scripts run before the page is loaded and must not
+            // insert tags into it, so they load chunks like the main process.
+            ExecutionEnvironment::Electron(env) => match env.await?.process {
+                ElectronProcess::Main | ElectronProcess::Preload => ChunkLoading::NodeJs.cell(),
+                ElectronProcess::Renderer => ChunkLoading::Dom.cell(),
+            },
+            // All chunks are part of a single bundle.
+            ExecutionEnvironment::ReactNative(_) => ChunkLoading::None.cell(),
             _ => ChunkLoading::None.cell(),
         })
     }
+
+    /// The url prefix static assets are requested from when the chunking
+    /// context doesn't specify one.
+    #[turbo_tasks::function]
+    pub async fn asset_prefix(self: Vc<Self>) -> Result<Vc<Option<String>>> {
+        let env = self.await?;
+        Ok(match env.execution {
+            ExecutionEnvironment::Electron(env) => Vc::cell(
+                env.await?
+                    .asset_scheme
+                    .as_ref()
+                    .map(|scheme| format!("{scheme}://./")),
+            ),
+            _ => Vc::cell(None),
+        })
+    }
 }
 
 pub enum NodeEnvironmentType {
@@ -381,14 +581,166 @@ pub struct BrowserEnvironment {
     pub browserslist_query: String,
 }
 
+/// The process type of an Electron app.
+#[derive(
+    Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TraceRawVcs,
+)]
+pub enum ElectronProcess {
+    /// The main process, which runs in Node.js.
+    Main,
+    /// A preload script, which runs in a renderer before the page is loaded
+    /// and has access to Node.js APIs.
+    Preload,
+    /// A renderer process, which runs a page without Node.js integration.
+    Renderer,
+}
+
+impl ElectronProcess {
+    /// Whether Node.js built-ins and the `electron` module can be required.
+    pub fn has_node_integration(&self) -> bool {
+        matches!(self, ElectronProcess::Main | ElectronProcess::Preload)
+    }
+}
+
+#[turbo_tasks::value(shared)]
+pub struct ElectronEnvironment {
+    pub process: ElectronProcess,
+    /// The Node.js version of the main process and preload scripts.
+    pub node: Vc<NodeJsEnvironment>,
+    /// The browserslist query for the Chromium version of preload scripts and
+    /// renderers, e.g. `electron >= 27`.
+    pub browserslist_query: String,
+    /// The scheme of a custom protocol which serves the output directory, e.g.
+    /// `app`. Static assets are requested from `app://./` instead of `/`, which
+    /// doesn't resolve for pages loaded from `file://` urls.
+    pub asset_scheme: Option<String>,
+}
+
+/// The platform a React Native app is built for.
+#[derive(
+    Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TraceRawVcs,
+)]
+pub enum ReactNativePlatform {
+    Ios,
+    Android,
+}
+
+impl ReactNativePlatform {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReactNativePlatform::Ios => "ios",
+            ReactNativePlatform::Android => "android",
+        }
+    }
+
+    /// The extensions which are tried when resolving a request, like Metro
+    /// does: `.ios.tsx` is preferred over `.native.tsx`, which is preferred
+    /// over `.tsx`.
+    pub fn resolve_extensions(&self) -> Vec<String> {
+        let mut extensions = Vec::new();
+        for extension in [".tsx", ".ts", ".jsx", ".js", ".json"] {
+            extensions.push(format!(".{}{extension}", self.as_str()));
+            extensions.push(format!(".native{extension}"));
+            extensions.push(extension.to_string());
+        }
+        extensions
+    }
+}
+
+#[turbo_tasks::value(shared)]
+pub struct ReactNativeEnvironment {
+    pub platform: ReactNativePlatform,
+}
+
+/// The edge runtime an [EdgeWorkerEnvironment] runs in.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    TraceRawVcs,
+)]
+pub enum EdgeRuntime {
+    /// An edge runtime which provides no Node.js built-ins, e.g. the sandbox of
+    /// a dev server.
+    #[default]
+    Generic,
+    /// Cloudflare Workers, which run on `workerd`.
+    Workerd,
+    /// Vercel Edge Functions, which run on `edge-light`.
+    VercelEdge,
+}
+
+impl EdgeRuntime {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdgeRuntime::Generic => "edge runtime",
+            EdgeRuntime::Workerd => "Cloudflare Workers",
+            EdgeRuntime::VercelEdge => "Vercel Edge Functions",
+        }
+    }
+
+    /// The export conditions of the runtime, like the ones Wrangler and the
+    /// Vercel CLI use. The generic runtime only uses `edge-worker`, which all
+    /// edge worker environments resolve with.
+    pub fn resolve_conditions(&self) -> &'static [&'static str] {
+        match self {
+            EdgeRuntime::Generic => &["edge-worker"],
+            EdgeRuntime::Workerd => &["edge-worker", "workerd", "worker", "browser"],
+            EdgeRuntime::VercelEdge => &["edge-worker", "edge-light", "worker", "browser"],
+        }
+    }
+
+    /// The Node.js built-in modules which the runtime provides as `node:`
+    /// imports. Cloudflare Workers require the `nodejs_compat` compatibility
+    /// flag for them.
+    pub fn node_builtins(&self) -> &'static [&'static str] {
+        match self {
+            EdgeRuntime::Generic => &[],
+            EdgeRuntime::Workerd => &[
+                "assert",
+                "async_hooks",
+                "buffer",
+                "crypto",
+                "diagnostics_channel",
+                "events",
+                "path",
+                "process",
+                "stream",
+                "string_decoder",
+                "util",
+            ],
+            EdgeRuntime::VercelEdge => &["assert", "async_hooks", "buffer", "events", "util"],
+        }
+    }
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionEdgeRuntime(Option<EdgeRuntime>);
+
 #[turbo_tasks::value(shared)]
 pub struct EdgeWorkerEnvironment {
     pub server_addr: Vc<ServerAddr>,
+    pub runtime: EdgeRuntime,
 }
 
 #[turbo_tasks::value(transparent)]
 pub struct RuntimeVersions(#[turbo_tasks(trace_ignore)] pub Versions);
 
+fn browserslist_versions(browserslist_query: &str) -> Result<Vc<RuntimeVersions>> {
+    Ok(Vc::cell(Versions::parse_versions(browserslist::resolve(
+        browserslist_query.split(','),
+        &browserslist::Opts::new(),
+    )?)?))
+}
+
 #[turbo_tasks::function]
 pub async fn get_current_nodejs_version(env: Vc<Box<dyn ProcessEnv>>) -> Result<Vc<String>> {
     let path_read = env.read("PATH".to_string()).await?;
@@ -409,3 +761,40 @@ pub async fn get_current_nodejs_version(env: Vc<Box<dyn ProcessEnv>>) -> Result<
             .to_owned(),
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use swc_core::ecma::preset_env::{Version, Versions};
+
+    use super::{EdgeRuntime, EDGE_BROWSERSLIST_QUERY};
+
+    #[test]
+    fn edge_browserslist_query_is_pinned() {
+        let versions = Versions::parse_versions(
+            browserslist::resolve([EDGE_BROWSERSLIST_QUERY], &browserslist::Opts::new()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            versions.chrome,
+            Some(Version {
+                major: 118,
+                minor: 0,
+                patch: 0,
+            })
+        );
+        assert_eq!(versions.firefox, None);
+    }
+
+    #[test]
+    fn generic_edge_runtime_keeps_the_edge_worker_condition() {
+        assert_eq!(EdgeRuntime::Generic.resolve_conditions(), ["edge-worker"]);
+        assert_eq!(
+            EdgeRuntime::Workerd.resolve_conditions(),
+            ["edge-worker", "workerd", "worker", "browser"]
+        );
+        assert_eq!(
+            EdgeRuntime::VercelEdge.resolve_conditions(),
+            ["edge-worker", "edge-light", "worker", "browser"]
+        );
+    }
+}
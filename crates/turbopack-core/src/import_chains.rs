@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use turbo_tasks::{TryJoinIterExt, ValueToString, Vc};
+use turbo_tasks_fs::FileSystemPath;
+
+use crate::{
+    module::{Module, Modules},
+    reference::ModuleReference,
+};
+
+/// A module in an import chain, see [ImportChains].
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct ImportChainStep {
+    /// The ident of the module.
+    pub module: String,
+    /// The description of the reference which imports the module from the
+    /// previous module of the chain, e.g. `import(./page)`. `None` for the
+    /// entry at the start of the chain.
+    pub reference: Option<String>,
+}
+
+/// Import chains from an entry to a module. Each chain lists the modules
+/// importing each other, starting with the entry.
+#[turbo_tasks::value(transparent)]
+pub struct ImportChains(Vec<Vec<ImportChainStep>>);
+
+type ReferencedModule = (Vc<Box<dyn ModuleReference>>, Vc<Box<dyn Module>>);
+
+/// A module and the reference importing it from the previous module of the
+/// chain.
+type ChainLink = (Vc<Box<dyn Module>>, Option<Vc<Box<dyn ModuleReference>>>);
+
+/// For each module, the modules and references importing it on a shortest
+/// path from the entries. Entries have no parents.
+type Parents =
+    IndexMap<Vc<Box<dyn Module>>, Vec<(Vc<Box<dyn Module>>, Vc<Box<dyn ModuleReference>>)>>;
+
+async fn referenced_modules(module: Vc<Box<dyn Module>>) -> Result<Vec<ReferencedModule>> {
+    Ok(module
+        .references()
+        .await?
+        .iter()
+        .map(|&reference| async move {
+            let modules = reference.resolve_reference().primary_modules().await?;
+            Ok(modules
+                .iter()
+                .map(|&module| (reference, module))
+                .collect::<Vec<_>>())
+        })
+        .try_join()
+        .await?
+        .into_iter()
+        .flatten()
+        .collect())
+}
+
+/// Collects up to `limit` chains from an entry to `module`, followed by the
+/// reversed `suffix`.
+fn collect_chains(
+    module: Vc<Box<dyn Module>>,
+    parents: &Parents,
+    limit: usize,
+    suffix: &mut Vec<ChainLink>,
+    chains: &mut Vec<Vec<ChainLink>>,
+) {
+    if chains.len() >= limit {
+        return;
+    }
+    let module_parents = &parents[&module];
+    if module_parents.is_empty() {
+        let mut chain = vec![(module, None)];
+        chain.extend(suffix.iter().rev().copied());
+        chains.push(chain);
+        return;
+    }
+    for &(parent, reference) in module_parents {
+        suffix.push((module, Some(reference)));
+        collect_chains(parent, parents, limit, suffix, chains);
+        suffix.pop();
+    }
+}
+
+/// Answers why the module at `path` is part of the module graph: returns up
+/// to `limit` of the shortest import chains from one of the `entries` to a
+/// module at `path`, including the references along the chains.
+///
+/// Returns no chains when `path` isn't reachable from the entries.
+#[turbo_tasks::function]
+pub async fn import_chains(
+    entries: Vc<Modules>,
+    path: Vc<FileSystemPath>,
+    limit: usize,
+) -> Result<Vc<ImportChains>> {
+    let path = path.await?;
+    let entries = entries.await?;
+
+    let mut parents = Parents::new();
+    let mut depths = HashMap::new();
+    for &entry in entries.iter() {
+        parents.entry(entry).or_default();
+        depths.insert(entry, 0);
+    }
+
+    // Visit the graph breadth-first, one level at a time, until a module at
+    // `path` is found. This records all shortest chains to each module.
+    let mut level = parents.keys().copied().collect::<Vec<_>>();
+    let mut targets = Vec::new();
+    let mut depth = 0;
+    while !level.is_empty() {
+        let paths = level
+            .iter()
+            .map(|module| async move { Ok(module.ident().path().await?) })
+            .try_join()
+            .await?;
+        targets.extend(
+            level
+                .iter()
+                .zip(paths.iter())
+                .filter(|(_, module_path)| ***module_path == *path)
+                .map(|(&module, _)| module),
+        );
+        if !targets.is_empty() {
+            break;
+        }
+
+        let references = level
+            .iter()
+            .map(|&module| referenced_modules(module))
+            .try_join()
+            .await?;
+        let mut next_level = Vec::new();
+        for (&module, references) in level.iter().zip(references) {
+            for (reference, referenced) in references {
+                match depths.get(&referenced) {
+                    None => {
+                        depths.insert(referenced, depth + 1);
+                        parents.insert(referenced, vec![(module, reference)]);
+                        next_level.push(referenced);
+                    }
+                    Some(&referenced_depth) if referenced_depth == depth + 1 => {
+                        parents[&referenced].push((module, reference));
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        level = next_level;
+        depth += 1;
+    }
+
+    let mut chains = Vec::new();
+    for target in targets {
+        collect_chains(target, &parents, limit, &mut Vec::new(), &mut chains);
+    }
+
+    let chains = chains
+        .into_iter()
+        .map(|chain| async move {
+            chain
+                .into_iter()
+                .map(|(module, reference)| async move {
+                    let reference = match reference {
+                        Some(reference) => Some(reference.to_string().await?.clone_value()),
+                        None => None,
+                    };
+                    Ok(ImportChainStep {
+                        module: module.ident().to_string().await?.clone_value(),
+                        reference,
+                    })
+                })
+                .try_join()
+                .await
+        })
+        .try_join()
+        .await?;
+
+    Ok(Vc::cell(chains))
+}
@@ -37,6 +37,11 @@ impl Issue for AnalyzeIssue {
         self.category
     }
 
+    #[turbo_tasks::function]
+    fn code(&self) -> Vc<Option<String>> {
+        Vc::cell(self.code.clone())
+    }
+
     #[turbo_tasks::function]
     fn file_path(&self) -> Vc<FileSystemPath> {
         self.source_ident.path()
@@ -91,6 +91,12 @@ pub trait Issue {
         Vc::<String>::default()
     }
 
+    /// A stable code identifying the kind of issue (eg "TP1001"), which allows
+    /// to configure how specific issues are treated.
+    fn code(self: Vc<Self>) -> Vc<Option<String>> {
+        Vc::cell(None)
+    }
+
     /// The issue title should be descriptive of the issue, but should be a
     /// single line. This is displayed to the user directly under the issue
     /// header.
@@ -134,6 +140,7 @@ pub trait Issue {
             severity: *self.severity().await?,
             file_path: self.file_path().to_string().await?.clone_value(),
             category: self.category().await?.clone_value(),
+            code: self.code().await?.clone_value(),
             title: self.title().await?.clone_value(),
             description: self.description().await?.clone_value(),
             detail: self.detail().await?.clone_value(),
@@ -455,6 +462,7 @@ pub struct PlainIssue {
     pub severity: IssueSeverity,
     pub file_path: String,
     pub category: String,
+    pub code: Option<String>,
 
     pub title: String,
     pub description: String,
@@ -589,6 +597,49 @@ pub struct PlainIssueProcessingPathItem {
     pub description: ReadRef<String>,
 }
 
+/// Promotes warnings to errors, so they fail the compilation. This allows CI
+/// builds to enforce standards while local development stays permissive.
+///
+/// Issues are identified by their [Issue::code]. [IssueReporter]s apply the
+/// policy to all issues they report.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone, Default)]
+pub struct IssuePolicy {
+    /// Promote all warnings to errors, except for `allowed_codes`.
+    pub warnings_as_errors: bool,
+    /// Codes of warnings which are always promoted to errors.
+    pub error_codes: Vec<String>,
+    /// Codes of warnings which are never promoted to errors.
+    pub allowed_codes: Vec<String>,
+}
+
+impl IssuePolicy {
+    /// Returns the severity the issue is reported with.
+    pub fn severity(&self, issue: &PlainIssue) -> IssueSeverity {
+        if issue.severity != IssueSeverity::Warning {
+            return issue.severity;
+        }
+        let code = issue.code.as_deref();
+        let has_code =
+            |codes: &[String]| code.map_or(false, |code| codes.iter().any(|c| c == code));
+        if has_code(&self.error_codes)
+            || (self.warnings_as_errors && !has_code(&self.allowed_codes))
+        {
+            IssueSeverity::Error
+        } else {
+            IssueSeverity::Warning
+        }
+    }
+
+    /// Returns a copy of `issue` with the severity it's reported with.
+    pub fn apply(&self, issue: &PlainIssue) -> PlainIssue {
+        PlainIssue {
+            severity: self.severity(issue),
+            ..issue.clone()
+        }
+    }
+}
+
 #[turbo_tasks::value_trait]
 pub trait IssueReporter {
     /// Reports issues to the user (e.g. to stdio). Returns whether fatal
@@ -608,6 +659,11 @@ pub trait IssueReporter {
         source: TransientValue<RawVc>,
         min_failing_severity: Vc<IssueSeverity>,
     ) -> Vc<bool>;
+
+    /// The policy which decides the severity of reported issues.
+    fn issue_policy(self: Vc<Self>) -> Vc<IssuePolicy> {
+        IssuePolicy::default().cell()
+    }
 }
 
 #[async_trait]
@@ -11,18 +11,24 @@
 pub mod asset;
 pub mod changed;
 pub mod chunk;
+pub mod circular_dependencies;
 pub mod code_builder;
 pub mod compile_time_info;
+pub mod content_hash;
 pub mod context;
+pub mod deployment_bundle;
 pub mod diagnostics;
+pub mod duplicate_packages;
 pub mod environment;
 pub mod error;
 pub mod file_source;
 pub mod ident;
+pub mod import_chains;
 pub mod introspect;
 pub mod issue;
 pub mod module;
 pub mod output;
+pub mod output_query;
 pub mod package_json;
 pub mod proxied_asset;
 pub mod raw_module;
@@ -30,12 +36,15 @@ pub mod raw_output;
 pub mod reference;
 pub mod reference_type;
 pub mod resolve;
+pub mod security;
 pub mod server_fs;
 pub mod source;
 pub mod source_map;
 pub mod source_pos;
 pub mod source_transform;
 pub mod target;
+pub mod unused_files;
+pub mod update_protocol;
 mod utils;
 pub mod version;
 pub mod virtual_output;
@@ -1,5 +1,7 @@
+use anyhow::Result;
 use indexmap::IndexSet;
 use turbo_tasks::Vc;
+use turbo_tasks_fs::{glob::Glob, FileSystemPath};
 
 use crate::{asset::Asset, ident::AssetIdent};
 
@@ -16,6 +18,12 @@ pub trait OutputAsset: Asset {
     fn references(self: Vc<Self>) -> Vc<OutputAssets> {
         OutputAssets::empty()
     }
+
+    /// Hints for serving the [OutputAsset]. By default, only the content type
+    /// is guessed from the extension.
+    fn metadata(self: Vc<Self>) -> Vc<OutputAssetMetadata> {
+        OutputAssetMetadata::from_ident(self.ident())
+    }
 }
 
 #[turbo_tasks::value(transparent)]
@@ -41,3 +49,102 @@ pub struct OutputAssetsSet(IndexSet<Vc<Box<dyn OutputAsset>>>);
 
 // TODO All Vc::try_resolve_downcast::<Box<dyn OutputAsset>> calls should be
 // removed
+
+/// Hints for serving an [OutputAsset], so serving layers and upload tooling
+/// don't have to derive them from the extension.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone, Default)]
+pub struct OutputAssetMetadata {
+    /// The `content-type` the asset should be served with.
+    pub content_type: Option<String>,
+    /// The `cache-control` the asset should be served with.
+    pub cache_control: Option<String>,
+    /// Whether the content at the asset's path never changes, e.g. because
+    /// the path contains a content hash.
+    pub immutable: bool,
+}
+
+/// The `cache-control` for [OutputAssetMetadata::immutable] assets.
+pub const IMMUTABLE_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+#[turbo_tasks::value_impl]
+impl OutputAssetMetadata {
+    /// Metadata with the content type guessed from the extension of the
+    /// ident's path.
+    #[turbo_tasks::function]
+    pub async fn from_ident(ident: Vc<AssetIdent>) -> Result<Vc<Self>> {
+        let path = ident.path().await?;
+        Ok(OutputAssetMetadata {
+            content_type: mime_guess::from_path(&path.path)
+                .first()
+                .map(|mime| mime.to_string()),
+            ..Default::default()
+        }
+        .cell())
+    }
+
+    /// Metadata for assets whose path contains a content hash, which can be
+    /// cached forever.
+    #[turbo_tasks::function]
+    pub async fn immutable(ident: Vc<AssetIdent>) -> Result<Vc<Self>> {
+        let metadata = Self::from_ident(ident).await?;
+        Ok(OutputAssetMetadata {
+            content_type: metadata.content_type.clone(),
+            cache_control: Some(IMMUTABLE_CACHE_CONTROL.to_string()),
+            immutable: true,
+        }
+        .cell())
+    }
+}
+
+/// Overrides the [OutputAssetMetadata] of assets whose path relative to the
+/// output root matches `glob`. Fields which are `None` are left unchanged.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct OutputAssetMetadataRule {
+    pub glob: Vc<Glob>,
+    pub content_type: Option<String>,
+    pub cache_control: Option<String>,
+    pub immutable: Option<bool>,
+}
+
+/// A list of [OutputAssetMetadataRule]s. All matching rules are applied in
+/// order, so later rules take precedence.
+#[turbo_tasks::value(transparent)]
+pub struct OutputAssetMetadataRules(Vec<OutputAssetMetadataRule>);
+
+/// Returns the [OutputAssetMetadata] of an asset with the matching `rules`
+/// applied, e.g. to serve manifests with `no-cache` although they are not
+/// hashed.
+#[turbo_tasks::function]
+pub async fn output_asset_metadata(
+    asset: Vc<Box<dyn OutputAsset>>,
+    output_root: Vc<FileSystemPath>,
+    rules: Vc<OutputAssetMetadataRules>,
+) -> Result<Vc<OutputAssetMetadata>> {
+    let rules = rules.await?;
+    if rules.is_empty() {
+        return Ok(asset.metadata());
+    }
+
+    let mut metadata = asset.metadata().await?.clone_value();
+    let path = asset.ident().path().await?;
+    let Some(path) = output_root.await?.get_path_to(&path).map(str::to_string) else {
+        return Ok(metadata.cell());
+    };
+    for rule in rules.iter() {
+        if !rule.glob.await?.execute(&path) {
+            continue;
+        }
+        if let Some(content_type) = &rule.content_type {
+            metadata.content_type = Some(content_type.clone());
+        }
+        if let Some(cache_control) = &rule.cache_control {
+            metadata.cache_control = Some(cache_control.clone());
+        }
+        if let Some(immutable) = rule.immutable {
+            metadata.immutable = immutable;
+        }
+    }
+    Ok(metadata.cell())
+}
@@ -0,0 +1,109 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use turbo_tasks::{TryJoinIterExt, Vc};
+use turbo_tasks_fs::{glob::Glob, FileContent, FileSystemPath};
+
+use crate::{
+    asset::Asset,
+    chunk::ChunkingContext,
+    output::{OutputAsset, OutputAssets},
+    reference::all_assets_from_entries,
+};
+
+/// Named chunk groups (or other entry output assets) of an app, e.g. one per
+/// page or entrypoint.
+#[turbo_tasks::value(transparent)]
+pub struct OutputAssetGroups(IndexMap<String, Vc<OutputAssets>>);
+
+/// An output asset matched by [query_output_assets].
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct OutputAssetInfo {
+    /// The path relative to the output root.
+    pub path: String,
+    /// The size in bytes, or `None` if the asset has no file content.
+    pub size: Option<usize>,
+    /// The content hash, or `None` if the asset has no file content.
+    pub hash: Option<String>,
+    /// The names of the [OutputAssetGroups] which (transitively) reference
+    /// the asset.
+    pub groups: Vec<String>,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OutputAssetInfos(Vec<OutputAssetInfo>);
+
+/// Returns all output assets of `groups` whose path relative to `root`
+/// matches `glob`, sorted by path. Hashes are computed with the content hash
+/// algorithm configured for `chunking_context`.
+///
+/// Like all turbo-tasks functions the result is kept up to date
+/// incrementally, so deployment tooling and test harnesses can use it instead
+/// of walking the output directory after every rebuild.
+#[turbo_tasks::function]
+pub async fn query_output_assets(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    root: Vc<FileSystemPath>,
+    groups: Vc<OutputAssetGroups>,
+    glob: Vc<Glob>,
+) -> Result<Vc<OutputAssetInfos>> {
+    let root = root.await?;
+    let glob = glob.await?;
+
+    let groups = groups
+        .await?
+        .iter()
+        .map(|(name, entries)| async move {
+            let assets = all_assets_from_entries(*entries)
+                .await?
+                .iter()
+                .map(|asset| async move { Ok((*asset, asset.ident().path().await?)) })
+                .try_join()
+                .await?;
+            Ok((name, assets))
+        })
+        .try_join()
+        .await?;
+
+    let mut matches: IndexMap<String, (Vc<Box<dyn OutputAsset>>, Vec<String>)> = IndexMap::new();
+    for (name, assets) in groups {
+        for (asset, path) in assets {
+            let Some(path) = root.get_path_to(&path) else {
+                continue;
+            };
+            if !glob.execute(path) {
+                continue;
+            }
+            let (_, groups) = matches
+                .entry(path.to_string())
+                .or_insert_with(|| (asset, Vec::new()));
+            if !groups.contains(name) {
+                groups.push(name.clone());
+            }
+        }
+    }
+    matches.sort_keys();
+
+    let infos = matches
+        .into_iter()
+        .map(|(path, (asset, groups))| async move {
+            let content = asset.content();
+            let (size, hash) = match &*content.file_content().await? {
+                FileContent::Content(file) => (
+                    Some(file.content().len()),
+                    Some(chunking_context.content_hash(content).await?.clone_value()),
+                ),
+                FileContent::NotFound => (None, None),
+            };
+            Ok(OutputAssetInfo {
+                path,
+                size,
+                hash,
+                groups,
+            })
+        })
+        .try_join()
+        .await?;
+
+    Ok(Vc::cell(infos))
+}
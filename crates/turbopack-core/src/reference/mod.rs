@@ -209,6 +209,36 @@ pub async fn all_modules(asset: Vc<Box<dyn Module>>) -> Result<Vc<Modules>> {
     Ok(Vc::cell(assets.into_iter().collect()))
 }
 
+/// Walks the module graph from multiple entries and collects all modules
+/// which are reachable via primary references, including the entries
+/// themselves.
+#[turbo_tasks::function]
+pub async fn all_modules_from_entries(entries: Vc<Modules>) -> Result<Vc<Modules>> {
+    Ok(Vc::cell(
+        AdjacencyMap::new()
+            .skip_duplicates()
+            .visit(
+                entries.await?.iter().copied(),
+                get_primary_referenced_modules,
+            )
+            .await
+            .completed()?
+            .into_inner()
+            .into_reverse_topological()
+            .collect(),
+    ))
+}
+
+/// Computes the list of all primary modules referenced by a given module.
+async fn get_primary_referenced_modules(
+    module: Vc<Box<dyn Module>>,
+) -> Result<impl Iterator<Item = Vc<Box<dyn Module>>> + Send> {
+    Ok(primary_referenced_modules(module)
+        .await?
+        .clone_value()
+        .into_iter())
+}
+
 /// Walks the asset graph from multiple assets and collect all referenced
 /// assets.
 #[turbo_tasks::function]
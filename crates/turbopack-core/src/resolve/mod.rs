@@ -1,6 +1,7 @@
 use std::{
     borrow::Cow,
     collections::{BTreeMap, HashMap, HashSet},
+    fmt::Display,
     future::Future,
     iter::once,
     pin::Pin,
@@ -8,7 +9,7 @@ use std::{
 
 use anyhow::{anyhow, bail, Result};
 use serde_json::Value as JsonValue;
-use tracing::Level;
+use tracing::{Instrument, Level};
 use turbo_tasks::{TryJoinIterExt, Value, ValueToString, Vc};
 use turbo_tasks_fs::{
     util::{normalize_path, normalize_request},
@@ -17,11 +18,12 @@ use turbo_tasks_fs::{
 
 use self::{
     options::{
-        resolve_modules_options, ConditionValue, ImportMapResult, ResolveInPackage,
+        resolve_modules_options, ConditionValue, Externals, ImportMapResult, ResolveInPackage,
         ResolveIntoPackage, ResolveModules, ResolveModulesOptions, ResolveOptions,
     },
     parse::Request,
     pattern::Pattern,
+    pnp::find_pnp_package,
     remap::{ExportsField, ImportsField},
 };
 use crate::{
@@ -35,7 +37,7 @@ use crate::{
     reference_type::ReferenceType,
     resolve::{
         pattern::{read_matches, PatternMatch},
-        plugin::ResolvePlugin,
+        plugin::{BeforeResolvePluginResult, ResolvePlugin},
     },
     source::{OptionSource, Source, Sources},
 };
@@ -47,6 +49,7 @@ pub mod origin;
 pub mod parse;
 pub mod pattern;
 pub mod plugin;
+mod pnp;
 pub(crate) mod remap;
 
 pub use alias_map::{
@@ -56,6 +59,28 @@ pub use remap::{ResolveAliasMap, SubpathValue};
 
 use crate::issue::{IssueSeverity, IssueSource};
 
+/// How an external module is loaded at runtime.
+#[derive(PartialOrd, Ord, Hash, Debug, Copy, Clone)]
+#[turbo_tasks::value(serialization = "auto_for_input")]
+pub enum ExternalType {
+    /// Loaded with `require()`.
+    CommonJs,
+    /// Loaded with `import()`, which makes the importing module async.
+    EcmaScriptModule,
+    /// Read from a global variable.
+    Global,
+}
+
+impl Display for ExternalType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExternalType::CommonJs => write!(f, "commonjs"),
+            ExternalType::EcmaScriptModule => write!(f, "esm"),
+            ExternalType::Global => write!(f, "global"),
+        }
+    }
+}
+
 #[turbo_tasks::value(shared)]
 #[derive(Clone, Debug)]
 pub enum ModuleResolveResultItem {
@@ -63,6 +88,7 @@ pub enum ModuleResolveResultItem {
     OutputAsset(Vc<Box<dyn OutputAsset>>),
     OriginalReferenceExternal,
     OriginalReferenceTypeExternal(String),
+    External(String, ExternalType),
     Ignore,
     Empty,
     Custom(u8),
@@ -343,6 +369,7 @@ pub enum ResolveResultItem {
     Source(Vc<Box<dyn Source>>),
     OriginalReferenceExternal,
     OriginalReferenceTypeExternal(String),
+    External(String, ExternalType),
     Ignore,
     Empty,
     Custom(u8),
@@ -521,6 +548,9 @@ impl ResolveResult {
                             ResolveResultItem::OriginalReferenceTypeExternal(s) => {
                                 ModuleResolveResultItem::OriginalReferenceTypeExternal(s)
                             }
+                            ResolveResultItem::External(name, ty) => {
+                                ModuleResolveResultItem::External(name, ty)
+                            }
                             ResolveResultItem::Ignore => ModuleResolveResultItem::Ignore,
                             ResolveResultItem::Empty => ModuleResolveResultItem::Empty,
                             ResolveResultItem::Custom(u8) => ModuleResolveResultItem::Custom(u8),
@@ -707,30 +737,36 @@ async fn exists(
     fs_path: Vc<FileSystemPath>,
     refs: &mut Vec<Vc<Box<dyn Source>>>,
 ) -> Result<Option<Vc<FileSystemPath>>> {
-    type_exists(fs_path, FileSystemEntryType::File, refs).await
+    type_exists(fs_path, FileSystemEntryType::File, false, refs).await
 }
 
 async fn dir_exists(
     fs_path: Vc<FileSystemPath>,
     refs: &mut Vec<Vc<Box<dyn Source>>>,
 ) -> Result<Option<Vc<FileSystemPath>>> {
-    type_exists(fs_path, FileSystemEntryType::Directory, refs).await
+    type_exists(fs_path, FileSystemEntryType::Directory, false, refs).await
 }
 
+/// Returns the canonical path of `fs_path` if it's of type `ty`, or `fs_path`
+/// itself when `preserve_symlinks` is set.
 async fn type_exists(
     fs_path: Vc<FileSystemPath>,
     ty: FileSystemEntryType,
+    preserve_symlinks: bool,
     refs: &mut Vec<Vc<Box<dyn Source>>>,
 ) -> Result<Option<Vc<FileSystemPath>>> {
-    let result = fs_path.resolve().await?.realpath_with_links().await?;
+    let fs_path = fs_path.resolve().await?;
+    let result = fs_path.realpath_with_links().await?;
     for path in result.symlinks.iter() {
         refs.push(Vc::upcast(FileSource::new(*path)));
     }
     let path = result.path.resolve().await?;
-    Ok(if *path.get_type().await? == ty {
-        Some(path)
-    } else {
+    Ok(if *path.get_type().await? != ty {
         None
+    } else if preserve_symlinks {
+        Some(fs_path)
+    } else {
+        Some(path)
     })
 }
 
@@ -869,6 +905,7 @@ async fn find_package(
     let mut packages = vec![];
     let mut affecting_sources = vec![];
     let options = options.await?;
+    let preserve_symlinks = options.preserve_symlinks;
     for resolve_modules in &options.modules {
         match resolve_modules {
             ResolveModules::Nested(root_vc, names) => {
@@ -879,10 +916,22 @@ async fn find_package(
                 while lookup_path_value.is_inside_ref(root) {
                     for name in names.iter() {
                         let fs_path = lookup_path.join(name.clone());
-                        if let Some(fs_path) = dir_exists(fs_path, &mut affecting_sources).await? {
+                        if let Some(fs_path) = type_exists(
+                            fs_path,
+                            FileSystemEntryType::Directory,
+                            preserve_symlinks,
+                            &mut affecting_sources,
+                        )
+                        .await?
+                        {
                             let fs_path = fs_path.join(package_name.clone());
-                            if let Some(fs_path) =
-                                dir_exists(fs_path, &mut affecting_sources).await?
+                            if let Some(fs_path) = type_exists(
+                                fs_path,
+                                FileSystemEntryType::Directory,
+                                preserve_symlinks,
+                                &mut affecting_sources,
+                            )
+                            .await?
                             {
                                 packages.push(fs_path);
                             }
@@ -897,6 +946,8 @@ async fn find_package(
                 }
             }
             ResolveModules::Path(context) => {
+                // The package directory is used as is, so its symlinks are
+                // always preserved.
                 let package_dir = context.join(package_name.clone());
                 if dir_exists(package_dir, &mut affecting_sources)
                     .await?
@@ -905,6 +956,14 @@ async fn find_package(
                     packages.push(package_dir.resolve().await?);
                 }
             }
+            ResolveModules::YarnPnp(root) => {
+                if let Some(package_dir) =
+                    find_pnp_package(*root, lookup_path, &package_name, &mut affecting_sources)
+                        .await?
+                {
+                    packages.push(package_dir);
+                }
+            }
             ResolveModules::Registry(_, _) => todo!(),
         }
     }
@@ -1011,12 +1070,45 @@ pub async fn resolve(
     request: Vc<Request>,
     options: Vc<ResolveOptions>,
 ) -> Result<Vc<ResolveResult>> {
-    let raw_result = resolve_internal_inline(lookup_path, request, options)
-        .await?
-        .resolve()
-        .await?;
-    let result = handle_resolve_plugins(lookup_path, request, options, raw_result).await?;
-    Ok(result)
+    async {
+        let (request, raw_result) =
+            match handle_before_resolve_plugins(lookup_path, request, options).await? {
+                BeforeResolvePluginResult::Unchanged => (
+                    request,
+                    resolve_internal_inline(lookup_path, request, options).await?,
+                ),
+                BeforeResolvePluginResult::Rewrite(new_request) => (
+                    new_request,
+                    resolve_internal_inline(lookup_path, new_request, options).await?,
+                ),
+                BeforeResolvePluginResult::External(name, ty) => (
+                    request,
+                    ResolveResult::primary(ResolveResultItem::External(name, ty)).cell(),
+                ),
+                BeforeResolvePluginResult::Result(result) => (request, result),
+            };
+        let raw_result = raw_result.resolve().await?;
+        let result = handle_resolve_plugins(lookup_path, request, options, raw_result).await?;
+        Ok(result)
+    }
+    .instrument(tracing::info_span!("resolve"))
+    .await
+}
+
+/// Calls the `before_resolve` hooks of the plugins in order, until one of them
+/// handles the request.
+async fn handle_before_resolve_plugins(
+    lookup_path: Vc<FileSystemPath>,
+    request: Vc<Request>,
+    options: Vc<ResolveOptions>,
+) -> Result<BeforeResolvePluginResult> {
+    for plugin in &options.await?.plugins {
+        let result = plugin.before_resolve(lookup_path, request).await?;
+        if !matches!(*result, BeforeResolvePluginResult::Unchanged) {
+            return Ok(result.clone_value());
+        }
+    }
+    Ok(BeforeResolvePluginResult::Unchanged)
 }
 
 async fn handle_resolve_plugins(
@@ -1107,6 +1199,13 @@ async fn resolve_internal_inline(
     #[allow(clippy::explicit_auto_deref)]
     let options_value: &ResolveOptions = &*options.await?;
 
+    // Leave externals unbundled
+    if let Some(externals) = options_value.externals {
+        if let Some(result) = resolve_external(request, externals).await? {
+            return Ok(result);
+        }
+    }
+
     // Apply import mappings if provided
     if let Some(import_map) = &options_value.import_map {
         let result = import_map.await?.lookup(lookup_path, request).await?;
@@ -1179,7 +1278,7 @@ async fn resolve_internal_inline(
             query,
             force_in_lookup_dir,
         } => {
-            resolve_relative_request(
+            let result = resolve_relative_request(
                 lookup_path,
                 request,
                 options,
@@ -1188,7 +1287,22 @@ async fn resolve_internal_inline(
                 *query,
                 *force_in_lookup_dir,
             )
-            .await?
+            .await?;
+            if !options_value.root_dirs.is_empty() && *result.is_unresolveable().await? {
+                resolve_relative_request_in_root_dirs(
+                    lookup_path,
+                    request,
+                    options,
+                    options_value,
+                    path,
+                    *query,
+                    *force_in_lookup_dir,
+                )
+                .await?
+                .unwrap_or(result)
+            } else {
+                result
+            }
         }
         Request::Module {
             module,
@@ -1430,6 +1544,69 @@ async fn resolve_relative_request(
     Ok(merge_results(results))
 }
 
+/// Resolves a relative request from the locations corresponding to
+/// `lookup_path` in the other [ResolveOptions::root_dirs], when `lookup_path` is
+/// inside one of them. Returns the first result which is resolveable.
+async fn resolve_relative_request_in_root_dirs(
+    lookup_path: Vc<FileSystemPath>,
+    request: Vc<Request>,
+    options: Vc<ResolveOptions>,
+    options_value: &ResolveOptions,
+    path: &Pattern,
+    query: Vc<String>,
+    force_in_lookup_dir: bool,
+) -> Result<Option<Vc<ResolveResult>>> {
+    let lookup_path_value = lookup_path.await?;
+    let root_dirs = options_value
+        .root_dirs
+        .iter()
+        .map(|&root_dir| async move { root_dir.await })
+        .try_join()
+        .await?;
+    // Like TypeScript, prefer the most specific root dir containing the lookup
+    // path.
+    let Some((root_dir_index, sub_path)) = root_dirs
+        .iter()
+        .enumerate()
+        .filter_map(|(i, root_dir)| {
+            let sub_path = if **root_dir == *lookup_path_value {
+                ""
+            } else {
+                root_dir.get_path_to(&lookup_path_value)?
+            };
+            Some((i, sub_path))
+        })
+        .min_by_key(|(_, sub_path)| sub_path.len())
+    else {
+        return Ok(None);
+    };
+
+    for (i, &root_dir) in options_value.root_dirs.iter().enumerate() {
+        if i == root_dir_index {
+            continue;
+        }
+        let lookup_path = if sub_path.is_empty() {
+            root_dir
+        } else {
+            root_dir.join(sub_path.to_string())
+        };
+        let result = resolve_relative_request(
+            lookup_path,
+            request,
+            options,
+            options_value,
+            path,
+            query,
+            force_in_lookup_dir,
+        )
+        .await?;
+        if !*result.is_unresolveable().await? {
+            return Ok(Some(result));
+        }
+    }
+    Ok(None)
+}
+
 #[tracing::instrument(level = Level::TRACE, skip_all)]
 async fn resolve_module_request(
     lookup_path: Vc<FileSystemPath>,
@@ -1501,6 +1678,12 @@ async fn resolve_module_request(
         .into());
     }
 
+    let packages = if options_value.dedupe_packages {
+        sort_packages_by_version(&result.packages).await?
+    } else {
+        result.packages.clone()
+    };
+
     let mut results = vec![];
 
     // There may be more than one package with the same name. For instance, in a
@@ -1508,7 +1691,7 @@ async fn resolve_module_request(
     // resolve packages. A request to "foo/bar" might resolve to either
     // "[baseUrl]/foo/bar" or "[baseUrl]/node_modules/foo/bar", and we'll need to
     // try both.
-    for &package_path in &result.packages {
+    for package_path in packages {
         results.push(resolve_into_package(
             Value::new(path.clone()),
             package_path.resolve().await?,
@@ -1523,6 +1706,33 @@ async fn resolve_module_request(
     ))
 }
 
+/// Sorts packages by their version, highest first. Packages without a valid
+/// version come last. The order of packages with the same version, which is
+/// nearest first, is kept.
+async fn sort_packages_by_version(
+    packages: &[Vc<FileSystemPath>],
+) -> Result<Vec<Vc<FileSystemPath>>> {
+    let mut packages = packages
+        .iter()
+        .map(|&package_path| async move {
+            let read = read_package_json(package_path.join("package.json".to_string())).await?;
+            let version = match &*read {
+                Some(package_json) => package_json["version"]
+                    .as_str()
+                    .and_then(|version| semver::Version::parse(version).ok()),
+                None => None,
+            };
+            Ok((version, package_path))
+        })
+        .try_join()
+        .await?;
+    packages.sort_by(|(a, _), (b, _)| b.cmp(a));
+    Ok(packages
+        .into_iter()
+        .map(|(_, package_path)| package_path)
+        .collect())
+}
+
 #[turbo_tasks::function]
 async fn resolve_into_package(
     path: Value<Pattern>,
@@ -1594,6 +1804,29 @@ async fn resolve_into_package(
     Ok(merge_results(results))
 }
 
+/// Returns the external the request matches, if it's a request to a module.
+async fn resolve_external(
+    request: Vc<Request>,
+    externals: Vc<Externals>,
+) -> Result<Option<Vc<ResolveResult>>> {
+    let request_value = request.await?;
+    if !matches!(*request_value, Request::Module { .. }) {
+        return Ok(None);
+    }
+    let Some(request) = request_value.request() else {
+        return Ok(None);
+    };
+    for external in externals.await?.iter() {
+        if external.request.matches(&request).await? {
+            let name = external.name.clone().unwrap_or(request);
+            return Ok(Some(
+                ResolveResult::primary(ResolveResultItem::External(name, external.ty)).cell(),
+            ));
+        }
+    }
+    Ok(None)
+}
+
 #[tracing::instrument(level = Level::TRACE, skip_all)]
 async fn resolve_import_map_result(
     result: &ImportMapResult,
@@ -1710,12 +1943,21 @@ async fn resolved(
     ResolveOptions {
         resolved_map,
         in_package,
+        preserve_symlinks,
         ..
     }: &ResolveOptions,
     options: Vc<ResolveOptions>,
     query: Vc<String>,
 ) -> Result<Vc<ResolveResult>> {
-    let RealPathResult { path, symlinks } = &*fs_path.realpath_with_links().await?;
+    let RealPathResult {
+        path: real_path,
+        symlinks,
+    } = &*fs_path.realpath_with_links().await?;
+    let path = if *preserve_symlinks {
+        &fs_path
+    } else {
+        real_path
+    };
 
     for in_package in in_package.iter() {
         // resolved is called when importing a relative path, not a
@@ -1996,3 +2238,90 @@ impl ModulePart {
         ModulePart::Internal(id).cell()
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{fs, os::unix::fs::symlink};
+
+    use anyhow::Result;
+    use lazy_static::lazy_static;
+    use tempfile::TempDir;
+    use turbo_tasks::{TryJoinIterExt, Vc};
+    use turbo_tasks_fs::{DiskFileSystem, FileSystem, FileSystemPath};
+    use turbo_tasks_testing::run;
+
+    use super::{
+        find_package,
+        options::{ResolveModules, ResolveModulesOptions},
+    };
+
+    lazy_static! {
+        static ref REGISTER: () = crate::register();
+    }
+
+    /// Creates a project whose `node_modules/foo` is a symlink to
+    /// `packages/foo`, like in a workspace.
+    fn symlinked_package() -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("packages/foo")).unwrap();
+        fs::create_dir_all(dir.path().join("node_modules")).unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        symlink("../packages/foo", dir.path().join("node_modules/foo")).unwrap();
+        dir
+    }
+
+    async fn find_foo(
+        modules: impl Fn(Vc<FileSystemPath>) -> ResolveModules,
+        preserve_symlinks: bool,
+    ) -> Result<Vec<String>> {
+        let dir = symlinked_package();
+        let root = DiskFileSystem::new(
+            "project".to_string(),
+            dir.path().to_str().unwrap().to_string(),
+        )
+        .root();
+        let options = ResolveModulesOptions {
+            modules: vec![modules(root)],
+            preserve_symlinks,
+        }
+        .cell();
+        let result = find_package(root.join("src".to_string()), "foo".to_string(), options);
+        result
+            .await?
+            .packages
+            .iter()
+            .map(|package| async move { Ok(package.await?.path.clone()) })
+            .try_join()
+            .await
+    }
+
+    fn nested(root: Vc<FileSystemPath>) -> ResolveModules {
+        ResolveModules::Nested(root, vec!["node_modules".to_string()])
+    }
+
+    fn path(root: Vc<FileSystemPath>) -> ResolveModules {
+        ResolveModules::Path(root.join("node_modules".to_string()))
+    }
+
+    #[tokio::test]
+    async fn nested_packages_are_canonicalized() {
+        run! {
+            assert_eq!(find_foo(nested, false).await?, ["packages/foo"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn nested_packages_preserve_symlinks() {
+        run! {
+            assert_eq!(find_foo(nested, true).await?, ["node_modules/foo"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn path_packages_are_used_as_is() {
+        run! {
+            assert_eq!(find_foo(path, false).await?, ["node_modules/foo"]);
+            assert_eq!(find_foo(path, true).await?, ["node_modules/foo"]);
+        }
+    }
+}
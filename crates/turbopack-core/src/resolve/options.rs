@@ -3,15 +3,19 @@ use std::{collections::BTreeMap, future::Future, pin::Pin};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use turbo_tasks::{
-    debug::ValueDebugFormat, trace::TraceRawVcs, TryJoinIterExt, Value, ValueToString, Vc,
+    debug::ValueDebugFormat, primitives::Regex, trace::TraceRawVcs, TryJoinIterExt, Value,
+    ValueToString, Vc,
 };
 use turbo_tasks_fs::{glob::Glob, FileSystemPath};
 
 use super::{
     alias_map::{AliasMap, AliasTemplate},
-    AliasPattern, ResolveResult, ResolveResultItem,
+    AliasPattern, ExternalType, ResolveResult, ResolveResultItem,
+};
+use crate::{
+    package_json::read_package_json,
+    resolve::{parse::Request, plugin::ResolvePlugin},
 };
-use crate::resolve::{parse::Request, plugin::ResolvePlugin};
 
 #[turbo_tasks::value(shared)]
 #[derive(Hash, Debug)]
@@ -31,6 +35,9 @@ pub enum ResolveModules {
     /// registry filesystem is assumed to have structure like
     /// @scope/module/version/<path-in-package>
     Registry(Vc<FileSystemPath>, Vc<LockedVersions>),
+    /// look up packages in the Yarn Plug'n'Play manifest (`.pnp.cjs` or
+    /// `.pnp.data.json`) in that directory
+    YarnPnp(Vc<FileSystemPath>),
 }
 
 #[derive(TraceRawVcs, Hash, PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
@@ -252,6 +259,78 @@ pub struct ResolvedMap {
     pub by_glob: Vec<(Vc<FileSystemPath>, Vc<Glob>, Vc<ImportMapping>)>,
 }
 
+/// Matches the requests which are handled by an [External].
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug)]
+pub enum ExternalRequest {
+    /// Matches the request exactly, e.g. `fs`.
+    Exact(String),
+    /// Matches requests starting with the prefix, e.g. `@aws-sdk/`.
+    Prefix(String),
+    /// Matches requests with a regular expression.
+    Regex(Vc<Regex>),
+}
+
+impl ExternalRequest {
+    pub async fn matches(&self, request: &str) -> Result<bool> {
+        Ok(match self {
+            ExternalRequest::Exact(name) => request == name,
+            ExternalRequest::Prefix(prefix) => request.starts_with(prefix),
+            ExternalRequest::Regex(regex) => regex.await?.is_match(request),
+        })
+    }
+}
+
+/// Requests which aren't bundled, but loaded at runtime instead.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug)]
+pub struct External {
+    pub request: ExternalRequest,
+    pub ty: ExternalType,
+    /// The name which is loaded at runtime, e.g. the name of the global
+    /// variable. Defaults to the request.
+    pub name: Option<String>,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct Externals(Vec<External>);
+
+#[turbo_tasks::value_impl]
+impl Externals {
+    /// Externalizes the `dependencies`, `peerDependencies` and
+    /// `optionalDependencies` of the package.json at `package_json_path` and
+    /// their subpaths, e.g. for library builds which leave installing them to
+    /// the consumers of the library.
+    #[turbo_tasks::function]
+    pub async fn package_dependencies(
+        package_json_path: Vc<FileSystemPath>,
+        ty: Value<ExternalType>,
+    ) -> Result<Vc<Self>> {
+        let ty = ty.into_value();
+        let mut externals = Vec::new();
+        if let Some(package_json) = &*read_package_json(package_json_path).await? {
+            for field in ["dependencies", "peerDependencies", "optionalDependencies"] {
+                let Some(dependencies) = package_json[field].as_object() else {
+                    continue;
+                };
+                for name in dependencies.keys() {
+                    externals.push(External {
+                        request: ExternalRequest::Exact(name.clone()),
+                        ty,
+                        name: None,
+                    });
+                    externals.push(External {
+                        request: ExternalRequest::Prefix(format!("{name}/")),
+                        ty,
+                        name: None,
+                    });
+                }
+            }
+        }
+        Ok(Vc::cell(externals))
+    }
+}
+
 #[turbo_tasks::value(shared)]
 #[derive(Clone, Debug)]
 pub enum ImportMapResult {
@@ -370,6 +449,21 @@ impl ImportMap {
 
 #[turbo_tasks::value_impl]
 impl ResolvedMap {
+    /// Keeps modules in `node_modules` directories below `root` external, so
+    /// they are loaded with their original request at runtime, e.g. for
+    /// Node.js bundles which are deployed together with their `node_modules`.
+    #[turbo_tasks::function]
+    pub fn external_node_modules(root: Vc<FileSystemPath>) -> Vc<Self> {
+        ResolvedMap {
+            by_glob: vec![(
+                root,
+                Glob::new("**/node_modules/**".to_string()),
+                ImportMapping::External(None).cell(),
+            )],
+        }
+        .cell()
+    }
+
     #[turbo_tasks::function]
     pub async fn lookup(
         self: Vc<Self>,
@@ -408,7 +502,23 @@ pub struct ResolveOptions {
     /// An import map to use when a request is otherwise unresolveable.
     pub fallback_import_map: Option<Vc<ImportMap>>,
     pub resolved_map: Option<Vc<ResolvedMap>>,
+    /// Requests which aren't bundled. They take precedence over the import
+    /// map.
+    pub externals: Option<Vc<Externals>>,
     pub plugins: Vec<Vc<Box<dyn ResolvePlugin>>>,
+    /// When a package is installed in multiple `node_modules` directories
+    /// along the lookup path, prefers the highest version instead of the
+    /// nearest one. This dedupes packages whose versions were not hoisted.
+    pub dedupe_packages: bool,
+    /// Directories whose contents are merged into a single virtual directory,
+    /// like `compilerOptions.rootDirs` in a `tsconfig.json`. A relative request
+    /// from inside one of them which can't be resolved is resolved from the
+    /// same location in the other directories, in order.
+    pub root_dirs: Vec<Vc<FileSystemPath>>,
+    /// Keeps the paths of resolved modules and packages as they are instead
+    /// of canonicalizing them through symlinks, like Node.js'
+    /// `--preserve-symlinks`. Symlinks are still tracked as affecting sources.
+    pub preserve_symlinks: bool,
     pub placeholder_for_future_extensions: (),
 }
 
@@ -416,8 +526,10 @@ pub struct ResolveOptions {
 impl ResolveOptions {
     #[turbo_tasks::function]
     pub async fn modules(self: Vc<Self>) -> Result<Vc<ResolveModulesOptions>> {
+        let options = self.await?;
         Ok(ResolveModulesOptions {
-            modules: self.await?.modules.clone(),
+            modules: options.modules.clone(),
+            preserve_symlinks: options.preserve_symlinks,
         }
         .into())
     }
@@ -461,14 +573,18 @@ impl ResolveOptions {
 #[derive(Hash, Clone, Debug)]
 pub struct ResolveModulesOptions {
     pub modules: Vec<ResolveModules>,
+    /// See [ResolveOptions::preserve_symlinks].
+    pub preserve_symlinks: bool,
 }
 
 #[turbo_tasks::function]
 pub async fn resolve_modules_options(
     options: Vc<ResolveOptions>,
 ) -> Result<Vc<ResolveModulesOptions>> {
+    let options = options.await?;
     Ok(ResolveModulesOptions {
-        modules: options.await?.modules.clone(),
+        modules: options.modules.clone(),
+        preserve_symlinks: options.preserve_symlinks,
     }
     .into())
 }
@@ -2,7 +2,7 @@ use anyhow::Result;
 use turbo_tasks::Vc;
 use turbo_tasks_fs::{glob::Glob, FileSystemPath};
 
-use crate::resolve::{parse::Request, ResolveResultOption};
+use crate::resolve::{parse::Request, ExternalType, ResolveResult, ResolveResultOption};
 
 /// A condition which determines if the hooks of a resolve plugin gets called.
 #[turbo_tasks::value]
@@ -36,8 +36,36 @@ impl ResolvePluginCondition {
     }
 }
 
+/// The result of the [ResolvePlugin::before_resolve] hook.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug)]
+pub enum BeforeResolvePluginResult {
+    /// The request is resolved as usual.
+    Unchanged,
+    /// This request is resolved instead, e.g. to map a custom scheme to a
+    /// module. It's not passed to the `before_resolve` hooks again.
+    Rewrite(Vc<Request>),
+    /// The request is not bundled, but loaded at runtime with this name.
+    External(String, ExternalType),
+    /// This result is used instead of resolving the request, e.g. to provide
+    /// a virtual source.
+    Result(Vc<ResolveResult>),
+}
+
 #[turbo_tasks::value_trait]
 pub trait ResolvePlugin {
+    /// This hook gets called before a request is resolved. The first plugin
+    /// which doesn't return [BeforeResolvePluginResult::Unchanged] determines
+    /// how the request is resolved. Unlike import maps, this allows to
+    /// intercept requests based on any of their properties.
+    fn before_resolve(
+        self: Vc<Self>,
+        _lookup_path: Vc<FileSystemPath>,
+        _request: Vc<Request>,
+    ) -> Vc<BeforeResolvePluginResult> {
+        BeforeResolvePluginResult::Unchanged.cell()
+    }
+
     /// A condition which determines if the hooks gets called.
     fn after_resolve_condition(self: Vc<Self>) -> Vc<ResolvePluginCondition>;
 
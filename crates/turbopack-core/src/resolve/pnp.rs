@@ -0,0 +1,387 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, Vc};
+use turbo_tasks_fs::{FileContent, FileSystem, FileSystemPath, ZipFileSystem};
+
+use super::dir_exists;
+use crate::{file_source::FileSource, source::Source};
+
+/// The name of the Yarn Plug'n'Play runtime, which contains the manifest
+/// unless Yarn is configured with `pnpEnableInlining: false`.
+const PNP_RUNTIME: &str = ".pnp.cjs";
+/// The name of the Yarn Plug'n'Play manifest when it's not inlined.
+const PNP_DATA: &str = ".pnp.data.json";
+
+/// A package in the dependency tree, e.g. `react` with the reference
+/// `npm:18.2.0`. The top-level package has neither name nor reference.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+struct PnpLocator {
+    name: Option<String>,
+    reference: Option<String>,
+}
+
+impl PnpLocator {
+    fn top_level() -> Self {
+        PnpLocator {
+            name: None,
+            reference: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+struct PnpPackage {
+    /// The location of the package relative to the directory of the manifest,
+    /// without leading `./` and trailing `/`.
+    location: String,
+    /// The dependencies by the name they are imported with. `None` is a peer
+    /// dependency which isn't provided by the dependent.
+    dependencies: HashMap<String, Option<PnpLocator>>,
+}
+
+/// The dependency tree of a Yarn Plug'n'Play install.
+#[turbo_tasks::value]
+struct PnpManifest {
+    packages: HashMap<PnpLocator, PnpPackage>,
+    /// The packages by their location, longest location first.
+    locations: Vec<(String, PnpLocator)>,
+    /// Whether packages can import the dependencies of the top-level package
+    /// and of the fallback pool, even if they don't depend on them.
+    enable_top_level_fallback: bool,
+    fallback_pool: HashMap<String, PnpLocator>,
+    /// Packages which are excluded from the fallback.
+    fallback_exclusions: HashSet<PnpLocator>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawPnpDependency {
+    Reference(String),
+    Alias(String, String),
+}
+
+impl RawPnpDependency {
+    fn into_locator(self, name: &str) -> PnpLocator {
+        let (name, reference) = match self {
+            RawPnpDependency::Reference(reference) => (name.to_string(), reference),
+            RawPnpDependency::Alias(name, reference) => (name, reference),
+        };
+        PnpLocator {
+            name: Some(name),
+            reference: Some(reference),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPnpPackage {
+    package_location: String,
+    #[serde(default)]
+    package_dependencies: Vec<(String, Option<RawPnpDependency>)>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPnpData {
+    #[serde(default)]
+    enable_top_level_fallback: bool,
+    #[serde(default)]
+    fallback_exclusion_list: Vec<(String, Vec<String>)>,
+    #[serde(default)]
+    fallback_pool: Vec<(String, Option<RawPnpDependency>)>,
+    package_registry_data: Vec<(Option<String>, Vec<(Option<String>, RawPnpPackage)>)>,
+}
+
+impl From<RawPnpData> for PnpManifest {
+    fn from(data: RawPnpData) -> Self {
+        let mut packages = HashMap::new();
+        for (name, references) in data.package_registry_data {
+            for (reference, package) in references {
+                let location = package.package_location;
+                let location = location.strip_prefix("./").unwrap_or(&location);
+                let dependencies = package
+                    .package_dependencies
+                    .into_iter()
+                    .map(|(name, dependency)| {
+                        let locator = dependency.map(|dependency| dependency.into_locator(&name));
+                        (name, locator)
+                    })
+                    .collect();
+                packages.insert(
+                    PnpLocator {
+                        name: name.clone(),
+                        reference,
+                    },
+                    PnpPackage {
+                        location: location.trim_end_matches('/').to_string(),
+                        dependencies,
+                    },
+                );
+            }
+        }
+
+        let mut locations = packages
+            .iter()
+            .map(|(locator, package)| (package.location.clone(), locator.clone()))
+            .collect::<Vec<_>>();
+        locations.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+
+        PnpManifest {
+            packages,
+            locations,
+            enable_top_level_fallback: data.enable_top_level_fallback,
+            fallback_pool: data
+                .fallback_pool
+                .into_iter()
+                .filter_map(|(name, dependency)| {
+                    let locator = dependency?.into_locator(&name);
+                    Some((name, locator))
+                })
+                .collect(),
+            fallback_exclusions: data
+                .fallback_exclusion_list
+                .into_iter()
+                .flat_map(|(name, references)| {
+                    references.into_iter().map(move |reference| PnpLocator {
+                        name: Some(name.clone()),
+                        reference: Some(reference),
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+impl PnpManifest {
+    /// Finds the package which contains `path`, which is relative to the
+    /// directory of the manifest.
+    fn find_locator(&self, path: &str) -> Option<&PnpLocator> {
+        self.locations.iter().find_map(|(location, locator)| {
+            let is_inside = location.is_empty()
+                || path
+                    .strip_prefix(location.as_str())
+                    .map_or(false, |rest| rest.is_empty() || rest.starts_with('/'));
+            is_inside.then_some(locator)
+        })
+    }
+
+    /// Finds the package which `issuer` gets when it imports `name`.
+    fn resolve_dependency(&self, issuer: &PnpLocator, name: &str) -> Option<&PnpPackage> {
+        let dependency = match self.packages.get(issuer)?.dependencies.get(name) {
+            Some(dependency) => dependency.as_ref(),
+            None if self.enable_top_level_fallback
+                && !self.fallback_exclusions.contains(issuer) =>
+            {
+                match self
+                    .packages
+                    .get(&PnpLocator::top_level())
+                    .and_then(|package| package.dependencies.get(name))
+                {
+                    Some(dependency) => dependency.as_ref(),
+                    None => self.fallback_pool.get(name),
+                }
+            }
+            None => None,
+        }?;
+        self.packages.get(dependency)
+    }
+}
+
+/// Extracts the manifest from the `RAW_RUNTIME_STATE` string literal of a
+/// `.pnp.cjs` file.
+fn extract_inlined_manifest(runtime: &str) -> Option<String> {
+    let start = runtime.find("RAW_RUNTIME_STATE")?;
+    let literal = &runtime[start..];
+    let literal = &literal[literal.find('\'')? + 1..];
+    let mut manifest = String::new();
+    let mut chars = literal.chars();
+    loop {
+        match chars.next()? {
+            '\'' => return Some(manifest),
+            '\\' => match chars.next()? {
+                // A line continuation.
+                '\n' => {}
+                c => manifest.push(c),
+            },
+            c => manifest.push(c),
+        }
+    }
+}
+
+/// The manifest and the file it was read from.
+#[turbo_tasks::value(transparent)]
+struct OptionPnpManifest(Option<(Vc<FileSystemPath>, Vc<PnpManifest>)>);
+
+/// Reads the Plug'n'Play manifest in `root`, from `.pnp.data.json` or from
+/// the `.pnp.cjs` runtime.
+#[turbo_tasks::function]
+async fn read_pnp_manifest(root: Vc<FileSystemPath>) -> Result<Vc<OptionPnpManifest>> {
+    let data_path = root.join(PNP_DATA.to_string());
+    let (path, data) = match &*data_path.read().await? {
+        FileContent::Content(file) => (data_path, file.content().to_str()?.into_owned()),
+        FileContent::NotFound => {
+            let runtime_path = root.join(PNP_RUNTIME.to_string());
+            let FileContent::Content(file) = &*runtime_path.read().await? else {
+                return Ok(Vc::cell(None));
+            };
+            let data = extract_inlined_manifest(&file.content().to_str()?).with_context(|| {
+                format!(
+                    "{PNP_RUNTIME} doesn't contain the manifest and there is no {PNP_DATA} next \
+                     to it"
+                )
+            })?;
+            (runtime_path, data)
+        }
+    };
+    let path_str = path.to_string().await?;
+    let data: RawPnpData = serde_json::from_str(&data)
+        .with_context(|| format!("unable to parse the Yarn Plug'n'Play manifest {path_str}"))?;
+    Ok(Vc::cell(Some((path, PnpManifest::from(data).cell()))))
+}
+
+/// Finds the directory of the package `package_name` for an import from
+/// `lookup_path` in a Yarn Plug'n'Play install whose manifest is in `root`.
+///
+/// Packages in zip archives and in `__virtual__` directories are returned as
+/// paths on the [ZipFileSystem] of `root`, other packages (e.g. workspaces and
+/// unplugged packages) as paths on the file system of `root`. Returns `None`
+/// when there is no manifest, when `lookup_path` isn't part of a package of
+/// the manifest or when the package doesn't depend on `package_name`.
+pub(super) async fn find_pnp_package(
+    root: Vc<FileSystemPath>,
+    lookup_path: Vc<FileSystemPath>,
+    package_name: &str,
+    affecting_sources: &mut Vec<Vc<Box<dyn Source>>>,
+) -> Result<Option<Vc<FileSystemPath>>> {
+    let Some((manifest_path, manifest)) = *read_pnp_manifest(root).await? else {
+        return Ok(None);
+    };
+    affecting_sources.push(Vc::upcast(FileSource::new(manifest_path)));
+    let manifest = manifest.await?;
+
+    let zip_fs = ZipFileSystem::new(root);
+    let zip_fs: Vc<Box<dyn FileSystem>> = Vc::upcast(zip_fs.resolve().await?);
+    let lookup_path = lookup_path.await?;
+    let root_value = root.await?;
+    let issuer_path = if lookup_path.fs == zip_fs {
+        lookup_path.path.as_str()
+    } else if *lookup_path == *root_value {
+        ""
+    } else if let Some(path) = root_value.get_path_to(&lookup_path) {
+        path
+    } else {
+        return Ok(None);
+    };
+
+    let Some(issuer) = manifest.find_locator(issuer_path) else {
+        return Ok(None);
+    };
+    let Some(package) = manifest.resolve_dependency(issuer, package_name) else {
+        return Ok(None);
+    };
+
+    let needs_zip_fs = package.location.split('/').any(|segment| {
+        segment.ends_with(".zip") || segment == "__virtual__" || segment == "$$virtual"
+    });
+    let package_dir = if needs_zip_fs {
+        zip_fs.root().join(package.location.clone())
+    } else {
+        root.join(package.location.clone())
+    };
+    dir_exists(package_dir, affecting_sources).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_inlined_manifest, PnpLocator, PnpManifest, RawPnpData};
+
+    fn manifest() -> PnpManifest {
+        let data: RawPnpData = serde_json::from_str(
+            r#"{
+                "enableTopLevelFallback": true,
+                "fallbackExclusionList": [["excluded", ["npm:1.0.0"]]],
+                "fallbackPool": [["pooled", "npm:1.0.0"]],
+                "packageRegistryData": [
+                    [null, [[null, {
+                        "packageLocation": "./",
+                        "packageDependencies": [["react", "npm:18.2.0"], ["alias", ["react", "npm:18.2.0"]]]
+                    }]]],
+                    ["react", [["npm:18.2.0", {
+                        "packageLocation": "./.yarn/cache/react-npm-18.2.0-abc.zip/node_modules/react/",
+                        "packageDependencies": [["react", "npm:18.2.0"], ["peer", null]]
+                    }]]],
+                    ["excluded", [["npm:1.0.0", {
+                        "packageLocation": "./.yarn/cache/excluded-npm-1.0.0-def.zip/node_modules/excluded/",
+                        "packageDependencies": []
+                    }]]],
+                    ["pooled", [["npm:1.0.0", {
+                        "packageLocation": "./.yarn/cache/pooled-npm-1.0.0-ghi.zip/node_modules/pooled/",
+                        "packageDependencies": []
+                    }]]]
+                ]
+            }"#,
+        )
+        .unwrap();
+        data.into()
+    }
+
+    fn locator(name: &str, reference: &str) -> PnpLocator {
+        PnpLocator {
+            name: Some(name.to_string()),
+            reference: Some(reference.to_string()),
+        }
+    }
+
+    #[test]
+    fn find_locator() {
+        let manifest = manifest();
+        assert_eq!(
+            manifest.find_locator("src/index.js"),
+            Some(&PnpLocator::top_level())
+        );
+        assert_eq!(
+            manifest
+                .find_locator(".yarn/cache/react-npm-18.2.0-abc.zip/node_modules/react/index.js"),
+            Some(&locator("react", "npm:18.2.0"))
+        );
+    }
+
+    #[test]
+    fn resolve_dependency() {
+        let manifest = manifest();
+        let react = ".yarn/cache/react-npm-18.2.0-abc.zip/node_modules/react";
+        let location = |issuer: &PnpLocator, name: &str| {
+            manifest
+                .resolve_dependency(issuer, name)
+                .map(|package| package.location.as_str())
+        };
+        assert_eq!(location(&PnpLocator::top_level(), "react"), Some(react));
+        assert_eq!(location(&PnpLocator::top_level(), "alias"), Some(react));
+        // Missing peer dependencies are not resolved.
+        assert_eq!(location(&locator("react", "npm:18.2.0"), "peer"), None);
+        // Falls back to the dependencies of the top-level package, then to the
+        // fallback pool.
+        assert_eq!(
+            location(&locator("react", "npm:18.2.0"), "alias"),
+            Some(react)
+        );
+        assert_eq!(
+            location(&locator("react", "npm:18.2.0"), "pooled"),
+            Some(".yarn/cache/pooled-npm-1.0.0-ghi.zip/node_modules/pooled")
+        );
+        assert_eq!(location(&locator("excluded", "npm:1.0.0"), "react"), None);
+    }
+
+    #[test]
+    fn inlined_manifest() {
+        let runtime = "#!/usr/bin/env node\n/* eslint-disable */\n\"use strict\";\n\nconst \
+                       RAW_RUNTIME_STATE =\n'{\\\n  \"__info\": [\"It\\'s a \\\\ test\"]\\\n}';\n";
+        assert_eq!(
+            extract_inlined_manifest(runtime).as_deref(),
+            Some("{  \"__info\": [\"It's a \\ test\"]}")
+        );
+    }
+}
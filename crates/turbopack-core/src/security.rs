@@ -0,0 +1,102 @@
+use anyhow::Result;
+use turbo_tasks::{Completion, TryJoinIterExt, Vc};
+use turbo_tasks_fs::FileSystemPath;
+
+use crate::{
+    issue::{Issue, IssueExt, IssueSeverity},
+    source::Source,
+};
+
+/// Inspects sources which are bundled, e.g. modules and the `package.json`
+/// manifests of their packages, for supply chain risks like malicious install
+/// scripts, embedded secrets or obfuscated `eval` chains.
+#[turbo_tasks::value_trait]
+pub trait SecurityScanner {
+    fn scan(self: Vc<Self>, source: Vc<Box<dyn Source>>) -> Vc<SecurityFindings>;
+}
+
+/// A risk found by a [SecurityScanner].
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct SecurityFinding {
+    pub severity: IssueSeverity,
+    pub title: String,
+    pub description: String,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct SecurityFindings(Vec<SecurityFinding>);
+
+#[turbo_tasks::value_impl]
+impl SecurityFindings {
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        Vc::cell(Vec::new())
+    }
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct SecurityScanners(Vec<Vc<Box<dyn SecurityScanner>>>);
+
+#[turbo_tasks::value_impl]
+impl SecurityScanners {
+    /// Runs all scanners on `source` and reports their findings as
+    /// [SecurityIssue]s.
+    #[turbo_tasks::function]
+    pub async fn scan(self: Vc<Self>, source: Vc<Box<dyn Source>>) -> Result<Vc<Completion>> {
+        let findings = self
+            .await?
+            .iter()
+            .map(|scanner| scanner.scan(source))
+            .try_join()
+            .await?;
+        let path = source.ident().path();
+        for finding in findings.iter().flat_map(|findings| findings.iter()) {
+            SecurityIssue {
+                path,
+                severity: finding.severity,
+                title: finding.title.clone(),
+                description: finding.description.clone(),
+            }
+            .cell()
+            .emit();
+        }
+        Ok(Completion::new())
+    }
+}
+
+#[turbo_tasks::value(shared)]
+pub struct SecurityIssue {
+    pub path: Vc<FileSystemPath>,
+    pub severity: IssueSeverity,
+    pub title: String,
+    pub description: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for SecurityIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        self.severity.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(self.title.clone())
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("security".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(self.description.clone())
+    }
+}
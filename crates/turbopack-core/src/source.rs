@@ -1,6 +1,6 @@
 use turbo_tasks::Vc;
 
-use crate::{asset::Asset, ident::AssetIdent};
+use crate::{asset::Asset, ident::AssetIdent, output::OutputAssets};
 
 /// (Unparsed) Source Code. Source Code is processed into [Module]s by the
 /// [AssetContext]. All [Source]s have content and an identifier.
@@ -11,6 +11,14 @@ pub trait Source: Asset {
     fn ident(&self) -> Vc<AssetIdent>;
 }
 
+/// A [Source] which emits files besides its content, e.g. one transformed by
+/// webpack loaders calling `this.emitFile`. Modules created from it reference
+/// the emitted files, so they are written along with the chunks.
+#[turbo_tasks::value_trait]
+pub trait EmittingSource: Source {
+    fn emitted_assets(self: Vc<Self>) -> Vc<OutputAssets>;
+}
+
 #[turbo_tasks::value(transparent)]
 pub struct OptionSource(Option<Vc<Box<dyn Source>>>);
 
@@ -5,7 +5,10 @@ use indexmap::IndexMap;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sourcemap::{SourceMap as CrateMap, SourceMapBuilder};
 use turbo_tasks::{TryJoinIterExt, Vc};
-use turbo_tasks_fs::rope::{Rope, RopeBuilder};
+use turbo_tasks_fs::{
+    glob::Glob,
+    rope::{Rope, RopeBuilder},
+};
 
 use crate::source_pos::SourcePos;
 
@@ -46,6 +49,40 @@ pub struct SectionMapping(IndexMap<String, Vc<Box<dyn GenerateSourceMap>>>);
 #[turbo_tasks::value(transparent)]
 pub struct OptionSourceMap(Option<Vc<SourceMap>>);
 
+/// Replaces the `from` prefix of `sources` entries in a source map with `to`,
+/// e.g. to strip absolute project paths or to add a `webpack://` style
+/// protocol, so maps match stack traces across machines.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct SourcePathRewrite {
+    pub from: String,
+    pub to: String,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct SourcePathRewrites(Vec<SourcePathRewrite>);
+
+#[turbo_tasks::value_impl]
+impl SourcePathRewrites {
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        Vc::cell(Vec::new())
+    }
+}
+
+impl SourcePathRewrites {
+    /// Applies the first rewrite whose `from` prefix matches `source`.
+    pub fn rewrite(&self, source: &str) -> String {
+        self.iter()
+            .find_map(|rewrite| {
+                source
+                    .strip_prefix(&rewrite.from)
+                    .map(|rest| format!("{}{}", rewrite.to, rest))
+            })
+            .unwrap_or_else(|| source.to_string())
+    }
+}
+
 #[turbo_tasks::value(transparent)]
 #[derive(Clone, Debug)]
 pub struct Tokens(Vec<Token>);
@@ -186,6 +223,57 @@ impl SourceMap {
             Self::Sectioned(m) => (*m.source_contents().await?).to_vec(),
         })
     }
+
+    /// Builds a regular map out of the tokens of this map. `map_token` maps
+    /// each token, or drops it by returning `None`. `map_source` maps the path
+    /// of each source which is still referenced to its path in the new map and
+    /// whether its contents are kept.
+    async fn rebuild(
+        &self,
+        source_root: Option<String>,
+        mut map_token: impl FnMut(Token) -> Option<Token>,
+        map_source: impl Fn(&str) -> (String, bool),
+    ) -> Result<Self> {
+        let mut builder = SourceMapBuilder::new(None);
+        builder.set_source_root(source_root);
+
+        let mut source_to_src_id = IndexMap::new();
+        for token in self.tokens().await? {
+            match map_token(token) {
+                None => {}
+                Some(Token::Synthetic(t)) => {
+                    builder.add(
+                        t.generated_line as u32,
+                        t.generated_column as u32,
+                        SOURCEMAP_CRATE_NONE_U32,
+                        SOURCEMAP_CRATE_NONE_U32,
+                        None,
+                        None,
+                    );
+                }
+                Some(Token::Original(t)) => {
+                    let (source, keep_contents) = map_source(&t.original_file);
+                    let token = builder.add(
+                        t.generated_line as u32,
+                        t.generated_column as u32,
+                        t.original_line as u32,
+                        t.original_column as u32,
+                        Some(&source),
+                        t.name.as_deref(),
+                    );
+                    source_to_src_id.insert(t.original_file, (token.src_id, keep_contents));
+                }
+            }
+        }
+
+        for (source, contents) in self.source_contents().await? {
+            if let Some(&(src_id, true)) = source_to_src_id.get(&source) {
+                builder.set_source_contents(src_id, contents.as_deref());
+            }
+        }
+
+        Ok(Self::new_regular(builder.into_sourcemap()))
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -387,6 +475,68 @@ impl SourceMap {
 
         Ok(Self::new_regular(builder.into_sourcemap()).into())
     }
+
+    /// Rewrites the `sources` of the map with `rewrites` and sets its
+    /// `sourceRoot`.
+    #[turbo_tasks::function]
+    pub async fn rewrite_sources(
+        self: Vc<Self>,
+        source_root: Option<String>,
+        rewrites: Vc<SourcePathRewrites>,
+    ) -> Result<Vc<SourceMap>> {
+        let rewrites = rewrites.await?;
+        Ok(self
+            .await?
+            .rebuild(source_root, Some, |source| (rewrites.rewrite(source), true))
+            .await?
+            .cell())
+    }
+
+    /// Drops the `sourcesContent` of all sources which match `sources`, e.g.
+    /// `**/node_modules/**` to keep the map small while still embedding the
+    /// contents of app code.
+    #[turbo_tasks::function]
+    pub async fn omit_source_contents(self: Vc<Self>, sources: Vc<Glob>) -> Result<Vc<SourceMap>> {
+        let sources = sources.await?;
+        Ok(self
+            .await?
+            .rebuild(None, Some, |source| {
+                (source.to_string(), !sources.execute(source))
+            })
+            .await?
+            .cell())
+    }
+
+    /// Reduces the map to a single mapping per generated line, which points to
+    /// the start of the original line. Such maps are much smaller, but only
+    /// resolve stack traces to lines.
+    #[turbo_tasks::function]
+    pub async fn lines_only(self: Vc<Self>) -> Result<Vc<SourceMap>> {
+        let mut last_line = None;
+        Ok(self
+            .await?
+            .rebuild(
+                None,
+                |token| {
+                    let Token::Original(t) = token else {
+                        return None;
+                    };
+                    if last_line == Some(t.generated_line) {
+                        return None;
+                    }
+                    last_line = Some(t.generated_line);
+                    Some(Token::Original(OriginalToken {
+                        generated_column: 0,
+                        original_column: 0,
+                        name: None,
+                        ..t
+                    }))
+                },
+                |source| (source.to_string(), true),
+            )
+            .await?
+            .cell())
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -1,11 +1,82 @@
 use anyhow::Result;
 use turbo_tasks::Vc;
+use turbo_tasks_env::{CommandLineProcessEnv, ProcessEnv};
+use turbo_tasks_fs::{FileContent, FileSystemPath};
+use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64, Xxh3Hash64Hasher};
 
 use crate::source::Source;
 
 #[turbo_tasks::value_trait]
 pub trait SourceTransform {
     fn transform(self: Vc<Self>, source: Vc<Box<dyn Source>>) -> Vc<Box<dyn Source>>;
+
+    /// Inputs which affect the result of the transform, but are not part of
+    /// the transform itself, e.g. the version of an external tool or a config
+    /// file it reads. Results are cached per state of these inputs.
+    fn cache_key_inputs(self: Vc<Self>) -> Vc<CacheKeyInputs> {
+        CacheKeyInputs::empty()
+    }
+}
+
+/// An input which is part of the cache key of a transform's results.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub enum CacheKeyInput {
+    /// The version of a tool, e.g. of the package implementing a transform.
+    Version { name: String, version: String },
+    /// The content of a file, e.g. a config file.
+    File(Vc<FileSystemPath>),
+    /// The value of an environment variable. Only a hash of the value is
+    /// stored.
+    EnvVar(String),
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct CacheKeyInputs(Vec<CacheKeyInput>);
+
+#[turbo_tasks::value_impl]
+impl CacheKeyInputs {
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        Vc::cell(Vec::new())
+    }
+
+    /// A hash of the current state of all inputs. Empty when there are no
+    /// inputs.
+    #[turbo_tasks::function]
+    pub async fn cache_key(self: Vc<Self>) -> Result<Vc<String>> {
+        let inputs = self.await?;
+        if inputs.is_empty() {
+            return Ok(Vc::cell(String::new()));
+        }
+
+        let mut hasher = Xxh3Hash64Hasher::new();
+        for input in inputs.iter() {
+            match input {
+                CacheKeyInput::Version { name, version } => {
+                    hasher.write_value(0_u8);
+                    hasher.write_ref(name);
+                    hasher.write_ref(version);
+                }
+                CacheKeyInput::File(path) => {
+                    hasher.write_value(1_u8);
+                    hasher.write_ref(&path.await?.path);
+                    let content = match &*path.read().await? {
+                        FileContent::Content(file) => Some(hash_xxh3_hash64(file.content())),
+                        FileContent::NotFound => None,
+                    };
+                    hasher.write_value(content);
+                }
+                CacheKeyInput::EnvVar(name) => {
+                    hasher.write_value(2_u8);
+                    hasher.write_ref(name);
+                    let value = CommandLineProcessEnv::new().read(name.clone()).await?;
+                    hasher.write_ref(&*value);
+                }
+            }
+        }
+        Ok(Vc::cell(encode_hex(hasher.finish())))
+    }
 }
 
 #[turbo_tasks::value(transparent)]
@@ -18,9 +89,23 @@ impl SourceTransforms {
         self: Vc<Self>,
         source: Vc<Box<dyn Source>>,
     ) -> Result<Vc<Box<dyn Source>>> {
-        Ok(self
-            .await?
-            .iter()
-            .fold(source, |source, transform| transform.transform(source)))
+        let mut source = source;
+        for transform in self.await?.iter() {
+            let cache_key = transform.cache_key_inputs().cache_key().await?;
+            source = transform_with_cache_key(*transform, source, cache_key.clone_value());
+        }
+        Ok(source)
     }
 }
+
+/// Applies a transform. The cache key is only an argument so it becomes part
+/// of the task's identity, which invalidates persisted results when the cache
+/// key inputs change.
+#[turbo_tasks::function]
+fn transform_with_cache_key(
+    transform: Vc<Box<dyn SourceTransform>>,
+    source: Vc<Box<dyn Source>>,
+    _cache_key: String,
+) -> Vc<Box<dyn Source>> {
+    transform.transform(source)
+}
@@ -0,0 +1,96 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use turbo_tasks::{TryJoinIterExt, Vc};
+use turbo_tasks_fs::{glob::Glob, DirectoryContent, DirectoryEntry, FileSystemPath};
+
+use crate::{
+    module::{Module, Modules},
+    reference::all_modules_from_entries,
+};
+
+/// Configures [detect_unused_files].
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug)]
+pub struct UnusedFilesOptions {
+    /// Files with a path matching one of these globs are considered source
+    /// files, e.g. `**/*.{js,jsx,ts,tsx}`.
+    pub include: Vec<String>,
+    /// Files and directories with a path matching one of these globs are
+    /// skipped, e.g. `**/*.test.ts` or `scripts`.
+    pub exclude: Vec<String>,
+}
+
+/// Source files which are not part of the module graph, relative to the
+/// project directory and sorted.
+#[turbo_tasks::value(transparent)]
+pub struct UnusedFiles(Vec<String>);
+
+/// Walks the module graph from `entries` and returns the source files in
+/// `dir` which are never reached, e.g. to delete dead code.
+///
+/// `node_modules` and dot files and directories are always skipped. Note that
+/// files which are only used by other entries, tests or tooling are reported
+/// as well, so they should either be part of `entries` or excluded.
+#[turbo_tasks::function]
+pub async fn detect_unused_files(
+    entries: Vc<Modules>,
+    dir: Vc<FileSystemPath>,
+    options: Vc<UnusedFilesOptions>,
+) -> Result<Vc<UnusedFiles>> {
+    let options = options.await?;
+    let include = options
+        .include
+        .iter()
+        .map(|glob| Glob::new(glob.clone()))
+        .try_join()
+        .await?;
+    let exclude = options
+        .exclude
+        .iter()
+        .map(|glob| Glob::new(glob.clone()))
+        .try_join()
+        .await?;
+    let dir_value = dir.await?;
+
+    let used = all_modules_from_entries(entries)
+        .await?
+        .iter()
+        .map(|module| async move { Ok(module.ident().path().await?) })
+        .try_join()
+        .await?
+        .into_iter()
+        .filter_map(|path| dir_value.get_path_to(&path).map(ToString::to_string))
+        .collect::<HashSet<_>>();
+
+    let mut unused = Vec::new();
+    let mut directories = vec![(String::new(), dir)];
+    while let Some((prefix, directory)) = directories.pop() {
+        let DirectoryContent::Entries(directory_entries) = &*directory.read_dir().await? else {
+            continue;
+        };
+        for (name, entry) in directory_entries.iter() {
+            if name.starts_with('.') || name == "node_modules" {
+                continue;
+            }
+            let path = format!("{prefix}{name}");
+            if exclude.iter().any(|glob| glob.execute(&path)) {
+                continue;
+            }
+            match entry {
+                DirectoryEntry::Directory(directory) => {
+                    directories.push((format!("{path}/"), *directory));
+                }
+                DirectoryEntry::File(_) => {
+                    if include.iter().any(|glob| glob.execute(&path)) && !used.contains(&path) {
+                        unused.push(path);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    unused.sort();
+
+    Ok(Vc::cell(unused))
+}
@@ -0,0 +1,261 @@
+//! The instructions of the partial HMR updates sent to clients, e.g. by
+//! `turbopack-dev-server`. These are the Rust counterpart of the protocol
+//! types of the development runtime (`protocol.d.ts`), so dev servers and
+//! other embedders can produce and consume updates without depending on the
+//! internals of the chunking contexts.
+//!
+//! The instruction of a chunk list's [PartialUpdate] is a [ChunkListUpdate].
+//! Producers which forward instructions computed elsewhere can use
+//! `serde_json::Value` for the generic instruction types.
+//!
+//! [PartialUpdate]: crate::version::PartialUpdate
+
+use indexmap::{IndexMap, IndexSet};
+use serde::{Deserialize, Deserializer, Serialize};
+use turbo_tasks_fs::rope::Rope;
+
+use crate::chunk::ModuleId;
+
+/// The version of the update protocol. It is increased for changes which
+/// existing clients can't handle.
+pub const UPDATE_PROTOCOL_VERSION: u32 = 1;
+
+/// An update of a chunk list, i.e. of the chunks loaded by a page or entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub struct ChunkListUpdate<I = PartialChunkUpdate, M = MergedChunkUpdate> {
+    /// The [UPDATE_PROTOCOL_VERSION] the update was produced with. Updates
+    /// without a version are from producers which predate versioning, i.e.
+    /// version 1.
+    #[serde(default = "initial_protocol_version")]
+    pub protocol_version: u32,
+    /// A map from chunk path to a corresponding update of that chunk.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub chunks: IndexMap<String, ChunkUpdate<I>>,
+    /// Updates of groups of chunks which are updated together, e.g. all
+    /// ecmascript chunks of the list.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub merged: Vec<M>,
+}
+
+fn initial_protocol_version() -> u32 {
+    1
+}
+
+impl<I, M> Default for ChunkListUpdate<I, M> {
+    fn default() -> Self {
+        ChunkListUpdate {
+            protocol_version: UPDATE_PROTOCOL_VERSION,
+            chunks: IndexMap::new(),
+            merged: Vec::new(),
+        }
+    }
+}
+
+impl<I, M> ChunkListUpdate<I, M> {
+    /// Returns `true` if this update is empty.
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty() && self.merged.is_empty()
+    }
+}
+
+/// An update of a single chunk from one version to another.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ChunkUpdate<I = PartialChunkUpdate> {
+    /// The chunk was updated and must be reloaded.
+    Total,
+    /// The chunk was updated and can be updated in place.
+    Partial { instruction: I },
+    /// The chunk was added.
+    Added,
+    /// The chunk was deleted.
+    Deleted,
+}
+
+/// The instruction of a [ChunkUpdate::Partial].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum PartialChunkUpdate {
+    /// Replaces the stylesheet of a CSS chunk with the given version, without
+    /// reloading the JS chunk it belongs to.
+    CssChunkUpdate {
+        /// The version id of the updated chunk.
+        version: String,
+    },
+}
+
+/// An update of a group of chunks, see [ChunkListUpdate::merged].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum MergedChunkUpdate {
+    EcmascriptMergedUpdate(EcmascriptMergedUpdate),
+}
+
+/// An update of the ecmascript chunks of a chunk list. Module factories are
+/// only sent once, even when a module is part of multiple chunks.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EcmascriptMergedUpdate {
+    /// A map from module id to the latest module entry, for added and
+    /// modified modules.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub entries: IndexMap<ModuleId, EcmascriptModuleEntry>,
+    /// A map from chunk path to the chunk update.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub chunks: IndexMap<String, EcmascriptMergedChunkUpdate>,
+}
+
+impl EcmascriptMergedUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.chunks.is_empty()
+    }
+}
+
+/// Which modules were added to or deleted from an ecmascript chunk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum EcmascriptMergedChunkUpdate {
+    /// The chunk was added, with the given modules.
+    Added {
+        #[serde(default, skip_serializing_if = "IndexSet::is_empty")]
+        modules: IndexSet<ModuleId>,
+    },
+    /// The chunk was deleted, with the given modules.
+    Deleted {
+        // Technically, this is redundant, since the client will already know
+        // all modules in the chunk from the previous version. However, it's
+        // useful for merging updates without access to an initial state.
+        #[serde(default, skip_serializing_if = "IndexSet::is_empty")]
+        modules: IndexSet<ModuleId>,
+    },
+    /// Modules were added to or deleted from the chunk. Modified modules are
+    /// only part of [EcmascriptMergedUpdate::entries].
+    Partial {
+        #[serde(default, skip_serializing_if = "IndexSet::is_empty")]
+        added: IndexSet<ModuleId>,
+        #[serde(default, skip_serializing_if = "IndexSet::is_empty")]
+        deleted: IndexSet<ModuleId>,
+    },
+}
+
+/// The module factory of an added or modified module.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EcmascriptModuleEntry {
+    /// The code of the module factory.
+    #[serde(deserialize_with = "deserialize_code")]
+    pub code: Rope,
+    /// The url of the module, used as the source url of the evaluated code.
+    pub url: String,
+    /// The url of the source map of the module, if any.
+    pub map: Option<String>,
+}
+
+/// Ropes deserialize from bytes, but the code is sent as a string.
+fn deserialize_code<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Rope, D::Error> {
+    Ok(Rope::from(String::deserialize(deserializer)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::{indexmap, indexset};
+    use serde::{de::DeserializeOwned, Serialize};
+    use serde_json::json;
+
+    use super::*;
+
+    fn round_trip<T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug>(
+        value: &T,
+    ) -> serde_json::Value {
+        let json = serde_json::to_value(value).unwrap();
+        let deserialized: T = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(&deserialized, value);
+        json
+    }
+
+    #[test]
+    fn chunk_list_update_round_trip() {
+        let module_id = ModuleId::String("[project]/index.js (ecmascript)".to_string());
+        let update: ChunkListUpdate = ChunkListUpdate {
+            chunks: indexmap! {
+                "styles.css".to_string() => ChunkUpdate::Partial {
+                    instruction: PartialChunkUpdate::CssChunkUpdate {
+                        version: "abc".to_string(),
+                    },
+                },
+                "deleted.js".to_string() => ChunkUpdate::Deleted,
+            },
+            merged: vec![MergedChunkUpdate::EcmascriptMergedUpdate(
+                EcmascriptMergedUpdate {
+                    entries: indexmap! {
+                        module_id.clone() => EcmascriptModuleEntry {
+                            code: Rope::from("(() => {})"),
+                            url: "/index.js".to_string(),
+                            map: None,
+                        },
+                    },
+                    chunks: indexmap! {
+                        "index.js".to_string() => EcmascriptMergedChunkUpdate::Partial {
+                            added: indexset! { module_id },
+                            deleted: indexset! {},
+                        },
+                    },
+                },
+            )],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            round_trip(&update),
+            json!({
+                "type": "ChunkListUpdate",
+                "protocolVersion": UPDATE_PROTOCOL_VERSION,
+                "chunks": {
+                    "styles.css": {
+                        "type": "partial",
+                        "instruction": { "type": "CssChunkUpdate", "version": "abc" },
+                    },
+                    "deleted.js": { "type": "deleted" },
+                },
+                "merged": [{
+                    "type": "EcmascriptMergedUpdate",
+                    "entries": {
+                        "[project]/index.js (ecmascript)": {
+                            "code": "(() => {})",
+                            "url": "/index.js",
+                            "map": null,
+                        },
+                    },
+                    "chunks": {
+                        "index.js": {
+                            "type": "partial",
+                            "added": ["[project]/index.js (ecmascript)"],
+                        },
+                    },
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn chunk_update_round_trip() {
+        assert_eq!(
+            round_trip(&ChunkUpdate::<PartialChunkUpdate>::Total),
+            json!({ "type": "total" })
+        );
+        assert_eq!(
+            round_trip(&EcmascriptMergedChunkUpdate::Added {
+                modules: indexset! { ModuleId::Number(1) },
+            }),
+            json!({ "type": "added", "modules": [1] })
+        );
+    }
+
+    #[test]
+    fn updates_without_protocol_version_are_version_1() {
+        let update: ChunkListUpdate =
+            serde_json::from_value(json!({ "type": "ChunkListUpdate" })).unwrap();
+        assert_eq!(update.protocol_version, 1);
+        assert!(update.is_empty());
+    }
+}
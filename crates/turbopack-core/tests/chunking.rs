@@ -0,0 +1,363 @@
+#![cfg(test)]
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use turbo_tasks::{TryJoinIterExt, Value, ValueToString, Vc};
+use turbo_tasks_fs::{FileSystem, FileSystemPath, VirtualFileSystem};
+use turbo_tasks_testing::{register, run};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{
+        availability_info::AvailabilityInfo,
+        chunking::{make_chunks, ChunkingStrategy},
+        AsyncModuleInfo, Chunk, ChunkItem, ChunkItemWithAsyncModuleInfo, ChunkType,
+        ChunkableModule, ChunkingContext, EvaluatableAssets, ModuleId,
+    },
+    environment::Environment,
+    ident::AssetIdent,
+    module::Module,
+    output::{OutputAsset, OutputAssets},
+    reference::ModuleReferences,
+    register,
+};
+
+register!();
+
+/// Only implements what [make_chunks] needs.
+#[turbo_tasks::value]
+struct TestChunkingContext {
+    strategy: ChunkingStrategy,
+    root: Vc<FileSystemPath>,
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkingContext for TestChunkingContext {
+    #[turbo_tasks::function]
+    fn context_path(&self) -> Vc<FileSystemPath> {
+        self.root
+    }
+
+    #[turbo_tasks::function]
+    fn output_root(&self) -> Vc<FileSystemPath> {
+        self.root
+    }
+
+    #[turbo_tasks::function]
+    fn environment(&self) -> Vc<Environment> {
+        unimplemented!()
+    }
+
+    #[turbo_tasks::function]
+    fn chunk_path(&self, _ident: Vc<AssetIdent>, _extension: String) -> Vc<FileSystemPath> {
+        unimplemented!()
+    }
+
+    #[turbo_tasks::function]
+    fn reference_chunk_source_maps(&self, _chunk: Vc<Box<dyn OutputAsset>>) -> Vc<bool> {
+        unimplemented!()
+    }
+
+    #[turbo_tasks::function]
+    fn can_be_in_same_chunk(
+        &self,
+        _asset_a: Vc<Box<dyn Module>>,
+        _asset_b: Vc<Box<dyn Module>>,
+    ) -> Vc<bool> {
+        unimplemented!()
+    }
+
+    #[turbo_tasks::function]
+    fn asset_url(&self, _ident: Vc<AssetIdent>) -> Result<Vc<String>> {
+        unimplemented!()
+    }
+
+    #[turbo_tasks::function]
+    fn asset_path(
+        &self,
+        _content_hash: String,
+        _original_asset_ident: Vc<AssetIdent>,
+    ) -> Vc<FileSystemPath> {
+        unimplemented!()
+    }
+
+    #[turbo_tasks::function]
+    fn chunking_strategy(&self) -> Vc<ChunkingStrategy> {
+        self.strategy.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn async_loader_chunk_item(
+        &self,
+        _module: Vc<Box<dyn ChunkableModule>>,
+        _chunk_name: Option<String>,
+        _availability_info: Value<AvailabilityInfo>,
+    ) -> Vc<Box<dyn ChunkItem>> {
+        unimplemented!()
+    }
+
+    #[turbo_tasks::function]
+    fn async_loader_chunk_item_id(&self, _module: Vc<Box<dyn ChunkableModule>>) -> Vc<ModuleId> {
+        unimplemented!()
+    }
+
+    #[turbo_tasks::function]
+    fn inlined_async_loader_chunk_item(
+        &self,
+        _module: Vc<Box<dyn ChunkableModule>>,
+    ) -> Vc<Box<dyn ChunkItem>> {
+        unimplemented!()
+    }
+
+    #[turbo_tasks::function]
+    fn chunk_group(
+        &self,
+        _module: Vc<Box<dyn ChunkableModule>>,
+        _availability_info: Value<AvailabilityInfo>,
+    ) -> Vc<OutputAssets> {
+        unimplemented!()
+    }
+
+    #[turbo_tasks::function]
+    fn evaluated_chunk_group(
+        &self,
+        _ident: Vc<AssetIdent>,
+        _evaluatable_assets: Vc<EvaluatableAssets>,
+    ) -> Vc<OutputAssets> {
+        unimplemented!()
+    }
+
+    #[turbo_tasks::function]
+    fn service_worker_entry_chunk(
+        &self,
+        _path: Vc<FileSystemPath>,
+        _evaluatable_assets: Vc<EvaluatableAssets>,
+    ) -> Vc<Box<dyn OutputAsset>> {
+        unimplemented!()
+    }
+}
+
+/// A chunk item of a fixed size.
+#[turbo_tasks::value]
+struct TestChunkItem {
+    path: Vc<FileSystemPath>,
+    size: usize,
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for TestChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<ModuleReferences> {
+        ModuleReferences::empty()
+    }
+
+    #[turbo_tasks::function]
+    async fn ty(&self) -> Result<Vc<Box<dyn ChunkType>>> {
+        Ok(Vc::upcast(Vc::<TestChunkType>::default().resolve().await?))
+    }
+
+    #[turbo_tasks::function]
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        unimplemented!()
+    }
+
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        unimplemented!()
+    }
+}
+
+#[derive(Default)]
+#[turbo_tasks::value]
+struct TestChunkType {}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for TestChunkType {
+    #[turbo_tasks::function]
+    fn to_string(&self) -> Vc<String> {
+        Vc::cell("test".to_string())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkType for TestChunkType {
+    #[turbo_tasks::function]
+    fn chunk(
+        &self,
+        _chunking_context: Vc<Box<dyn ChunkingContext>>,
+        chunk_items: Vec<ChunkItemWithAsyncModuleInfo>,
+        _referenced_output_assets: Vc<OutputAssets>,
+        _chunk_name: Option<String>,
+    ) -> Vc<Box<dyn Chunk>> {
+        Vc::upcast(
+            TestChunk {
+                chunk_items: chunk_items
+                    .into_iter()
+                    .map(|(chunk_item, _)| chunk_item)
+                    .collect(),
+            }
+            .cell(),
+        )
+    }
+
+    #[turbo_tasks::function]
+    async fn chunk_item_size(
+        &self,
+        _chunking_context: Vc<Box<dyn ChunkingContext>>,
+        chunk_item: Vc<Box<dyn ChunkItem>>,
+        _async_module_info: Option<Vc<AsyncModuleInfo>>,
+    ) -> Result<Vc<usize>> {
+        let chunk_item = Vc::try_resolve_downcast_type::<TestChunkItem>(chunk_item)
+            .await?
+            .context("chunk items of the test chunk type are test chunk items")?;
+        Ok(Vc::cell(chunk_item.await?.size))
+    }
+}
+
+/// Remembers its chunk items, so tests can compare them.
+#[turbo_tasks::value]
+struct TestChunk {
+    chunk_items: Vec<Vc<Box<dyn ChunkItem>>>,
+}
+
+#[turbo_tasks::value_impl]
+impl Chunk for TestChunk {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        unimplemented!()
+    }
+
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        unimplemented!()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for TestChunk {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        unimplemented!()
+    }
+}
+
+/// Chunks test chunk items with the paths and sizes of `items` using
+/// `strategy`, and returns the paths of the chunk items of each chunk.
+async fn chunk_paths(
+    strategy: ChunkingStrategy,
+    items: &[(&str, usize)],
+) -> Result<Vec<Vec<String>>> {
+    let root = VirtualFileSystem::new().root();
+    let chunking_context = TestChunkingContext { strategy, root }.cell();
+    let chunk_items = items.iter().map(|&(path, size)| {
+        let chunk_item: Vc<Box<dyn ChunkItem>> = Vc::upcast(
+            TestChunkItem {
+                path: root.join(path.to_string()),
+                size,
+            }
+            .cell(),
+        );
+        (chunk_item, None)
+    });
+
+    let chunks = make_chunks(
+        Vc::upcast(chunking_context),
+        chunk_items,
+        "",
+        None,
+        IndexMap::new(),
+    )
+    .await?;
+    chunks
+        .into_iter()
+        .map(|chunk| async move {
+            let chunk = Vc::try_resolve_downcast_type::<TestChunk>(chunk)
+                .await?
+                .context("the test chunk type creates test chunks")?;
+            chunk
+                .await?
+                .chunk_items
+                .iter()
+                .map(|chunk_item| async move {
+                    Ok(chunk_item.asset_ident().path().await?.path.clone())
+                })
+                .try_join()
+                .await
+        })
+        .try_join()
+        .await
+}
+
+#[tokio::test]
+async fn single_strategy_puts_all_chunk_items_into_one_chunk() {
+    run! {
+        let chunks = chunk_paths(
+            ChunkingStrategy::Single,
+            &[("src/index.js", 100_000), ("node_modules/a/index.js", 400_000)],
+        )
+        .await?;
+        assert_eq!(chunks, [["src/index.js", "node_modules/a/index.js"]]);
+    }
+}
+
+#[tokio::test]
+async fn app_vendors_strategy_splits_app_from_vendor_code() {
+    run! {
+        let chunks = chunk_paths(
+            ChunkingStrategy::AppVendors,
+            &[
+                ("src/index.js", 10),
+                ("node_modules/a/index.js", 400_000),
+                ("src/page.js", 10),
+                ("node_modules/b/index.js", 10),
+            ],
+        )
+        .await?;
+        assert_eq!(
+            chunks,
+            [
+                vec!["src/index.js", "src/page.js"],
+                vec!["node_modules/a/index.js", "node_modules/b/index.js"],
+            ]
+        );
+    }
+}
+
+#[tokio::test]
+async fn split_strategy_merges_small_groups() {
+    run! {
+        let chunks = chunk_paths(
+            ChunkingStrategy::Split,
+            &[("src/index.js", 10), ("node_modules/a/index.js", 10)],
+        )
+        .await?;
+        assert_eq!(chunks, [["src/index.js", "node_modules/a/index.js"]]);
+    }
+}
+
+#[tokio::test]
+async fn split_strategy_splits_large_vendor_code_by_package() {
+    run! {
+        let chunks = chunk_paths(
+            ChunkingStrategy::Split,
+            &[
+                ("src/index.js", 100_000),
+                ("node_modules/a/index.js", 200_000),
+                ("node_modules/b/index.js", 150_000),
+            ],
+        )
+        .await?;
+        assert_eq!(
+            chunks,
+            [
+                ["src/index.js"],
+                ["node_modules/a/index.js"],
+                ["node_modules/b/index.js"],
+            ]
+        );
+    }
+}
@@ -1,11 +1,14 @@
 #![cfg(test)]
 
 use sourcemap::SourceMapBuilder;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::glob::Glob;
 use turbo_tasks_testing::{register, run};
 use turbopack_core::{
     register,
     source_map::{
-        RegularSourceMap, SectionedSourceMap, SourceMap, SourceMapSection, SyntheticToken, Token,
+        RegularSourceMap, SectionedSourceMap, SourceMap, SourceMapSection, SourcePathRewrite,
+        SourcePathRewrites, SyntheticToken, Token,
     },
     source_pos::SourcePos,
 };
@@ -140,3 +143,77 @@ async fn sectioned_map_tokens_does_not_implement_nested_sections() {
         let _ = sectioned_map.tokens().await?;
     }
 }
+
+#[tokio::test]
+async fn rewrite_sources_replaces_prefixes_and_sets_source_root() {
+    run! {
+        register();
+
+        let map = {
+            let mut builder = SourceMapBuilder::new(None);
+            builder.add(0, 0, 0, 0, Some("/turbopack/[project]/src/index.js"), None);
+            builder.add(1, 0, 0, 0, Some("/turbopack/[externals]/fs"), None);
+            builder.into_sourcemap()
+        };
+        let rewrites: Vc<SourcePathRewrites> = Vc::cell(vec![SourcePathRewrite {
+            from: "/turbopack/[project]/".to_string(),
+            to: "webpack:///".to_string(),
+        }]);
+
+        let rewritten = SourceMap::new_regular(map)
+            .cell()
+            .rewrite_sources(Some("/app/".to_string()), rewrites)
+            .await?;
+        let SourceMap::Regular(rewritten) = &*rewritten else {
+            panic!("expected a regular source map");
+        };
+
+        assert_eq!(rewritten.get_source_root(), Some("/app/"));
+        assert_eq!(
+            rewritten.sources().collect::<Vec<_>>(),
+            vec!["webpack:///src/index.js", "/turbopack/[externals]/fs"]
+        );
+    }
+}
+
+#[tokio::test]
+async fn omit_source_contents_drops_matching_sources() {
+    run! {
+        register();
+
+        let map = {
+            let mut builder = SourceMapBuilder::new(None);
+            let app = builder.add(0, 0, 0, 0, Some("/turbopack/[project]/src/index.js"), None);
+            builder.set_source_contents(app.src_id, Some("app"));
+            let vendor = builder.add(
+                1,
+                0,
+                0,
+                0,
+                Some("/turbopack/[project]/node_modules/react/index.js"),
+                None,
+            );
+            builder.set_source_contents(vendor.src_id, Some("vendor"));
+            builder.into_sourcemap()
+        };
+
+        let omitted = SourceMap::new_regular(map)
+            .cell()
+            .omit_source_contents(Glob::new("**/node_modules/**".to_string()))
+            .await?;
+
+        assert_eq!(
+            omitted.source_contents().await?,
+            vec![
+                (
+                    "/turbopack/[project]/src/index.js".to_string(),
+                    Some("app".to_string())
+                ),
+                (
+                    "/turbopack/[project]/node_modules/react/index.js".to_string(),
+                    None
+                ),
+            ]
+        );
+    }
+}
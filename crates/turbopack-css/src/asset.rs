@@ -15,21 +15,27 @@ use turbopack_core::{
     context::AssetContext,
     ident::AssetIdent,
     module::Module,
-    reference::{ModuleReference, ModuleReferences},
+    reference::{ModuleReference, ModuleReferences, SingleOutputAssetReference},
     resolve::origin::ResolveOrigin,
     source::Source,
+    source_map::GenerateSourceMap,
 };
 
 use crate::{
     chunk::{CssChunkItem, CssChunkItemContent, CssChunkPlaceable, CssChunkType, CssImport},
     code_gen::CodeGenerateable,
+    css_modules::CssModulesOptions,
     parse::{parse_css, ParseCss, ParseCssResult, ParseCssResultSourceMap},
     path_visitor::ApplyVisitors,
     references::{
-        analyze_css_stylesheet, compose::CssModuleComposeReference, import::ImportAssetReference,
+        analyze_css_stylesheet,
+        compose::CssModuleComposeReference,
+        import::ImportAssetReference,
+        module_import::CssModuleImportReference,
+        url::{ReferencedAsset, UrlAssetReference},
     },
     transform::CssInputTransforms,
-    CssModuleAssetType,
+    CssModuleAssetType, ModuleCssAsset,
 };
 
 #[turbo_tasks::function]
@@ -44,6 +50,7 @@ pub struct CssModuleAsset {
     asset_context: Vc<Box<dyn AssetContext>>,
     transforms: Vc<CssInputTransforms>,
     ty: CssModuleAssetType,
+    css_modules: Vc<CssModulesOptions>,
 }
 
 #[turbo_tasks::value_impl]
@@ -55,12 +62,14 @@ impl CssModuleAsset {
         asset_context: Vc<Box<dyn AssetContext>>,
         transforms: Vc<CssInputTransforms>,
         ty: CssModuleAssetType,
+        css_modules: Vc<CssModulesOptions>,
     ) -> Vc<Self> {
         Self::cell(CssModuleAsset {
             source,
             asset_context,
             transforms,
             ty,
+            css_modules,
         })
     }
 
@@ -75,7 +84,7 @@ impl CssModuleAsset {
 impl ParseCss for CssModuleAsset {
     #[turbo_tasks::function]
     fn parse_css(&self) -> Vc<ParseCssResult> {
-        parse_css(self.source, self.ty, self.transforms)
+        parse_css(self.source, self.ty, self.transforms, self.css_modules)
     }
 }
 
@@ -98,6 +107,7 @@ impl Module for CssModuleAsset {
             Vc::upcast(self),
             this.ty,
             this.transforms,
+            this.css_modules,
         ))
     }
 }
@@ -154,8 +164,25 @@ impl ChunkItem for CssModuleChunkItem {
     }
 
     #[turbo_tasks::function]
-    fn references(&self) -> Vc<ModuleReferences> {
-        self.module.references()
+    async fn references(&self) -> Result<Vc<ModuleReferences>> {
+        let mut references = self.module.references().await?.clone_value();
+        // Assets embedded with `url()` are emitted along with the chunk, unless
+        // they are inlined.
+        for reference in references.clone() {
+            if let Some(url_ref) =
+                Vc::try_resolve_downcast_type::<UrlAssetReference>(reference).await?
+            {
+                if let ReferencedAsset::Some(asset) =
+                    &*url_ref.get_referenced_asset(self.chunking_context).await?
+                {
+                    references.push(Vc::upcast(SingleOutputAssetReference::new(
+                        *asset,
+                        Vc::cell(format!("url {}", asset.ident().to_string().await?)),
+                    )));
+                }
+            }
+        }
+        Ok(Vc::cell(references))
     }
 
     #[turbo_tasks::function]
@@ -203,25 +230,15 @@ impl CssChunkItem for CssModuleChunkItem {
                         }
                     }
                 }
-            } else if let Some(compose_ref) =
-                Vc::try_resolve_downcast_type::<CssModuleComposeReference>(*reference).await?
-            {
-                for &module in compose_ref
-                    .resolve_reference()
-                    .primary_modules()
+            } else if Vc::try_resolve_downcast_type::<CssModuleComposeReference>(*reference)
+                .await?
+                .is_some()
+                || Vc::try_resolve_downcast_type::<CssModuleImportReference>(*reference)
                     .await?
-                    .iter()
-                {
-                    if let Some(placeable) =
-                        Vc::try_resolve_downcast::<Box<dyn CssChunkPlaceable>>(module).await?
-                    {
-                        let item = placeable.as_chunk_item(chunking_context);
-                        if let Some(css_item) =
-                            Vc::try_resolve_downcast::<Box<dyn CssChunkItem>>(item).await?
-                        {
-                            imports.push(CssImport::Composes(css_item));
-                        }
-                    }
+                    .is_some()
+            {
+                for css_item in composed_chunk_items(*reference, chunking_context).await? {
+                    imports.push(CssImport::Composes(css_item));
                 }
             }
         }
@@ -298,7 +315,11 @@ impl CssChunkItem for CssModuleChunkItem {
 
             code_gen.emit(&stylesheet)?;
 
-            let srcmap = ParseCssResultSourceMap::new(source_map.clone(), srcmap).cell();
+            let input_source_map =
+                Vc::try_resolve_sidecast::<Box<dyn GenerateSourceMap>>(self.module.await?.source)
+                    .await?;
+            let srcmap =
+                ParseCssResultSourceMap::new(source_map.clone(), srcmap, input_source_map).cell();
 
             Ok(CssChunkItemContent {
                 inner_code: code_string.into(),
@@ -325,3 +346,33 @@ impl CssChunkItem for CssModuleChunkItem {
         self.chunking_context
     }
 }
+
+/// Returns the chunk items of the CSS modules which a `composes` or ICSS
+/// `:import` reference depends on.
+async fn composed_chunk_items(
+    reference: Vc<Box<dyn ModuleReference>>,
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+) -> Result<Vec<Vc<Box<dyn CssChunkItem>>>> {
+    let mut items = Vec::new();
+    for &module in reference
+        .resolve_reference()
+        .primary_modules()
+        .await?
+        .iter()
+    {
+        // The CSS of a CSS module is in its inner module.
+        let module = match Vc::try_resolve_downcast_type::<ModuleCssAsset>(module).await? {
+            Some(css_module) => css_module.inner(),
+            None => module,
+        };
+        if let Some(placeable) =
+            Vc::try_resolve_downcast::<Box<dyn CssChunkPlaceable>>(module).await?
+        {
+            let item = placeable.as_chunk_item(chunking_context);
+            if let Some(css_item) = Vc::try_resolve_downcast::<Box<dyn CssChunkItem>>(item).await? {
+                items.push(css_item);
+            }
+        }
+    }
+    Ok(items)
+}
@@ -1,5 +1,6 @@
 pub(crate) mod single_item_chunk;
 pub mod source_map;
+pub mod update;
 pub(crate) mod writer;
 
 use std::fmt::Write;
@@ -25,16 +26,23 @@ use turbopack_core::{
     output::{OutputAsset, OutputAssets},
     server_fs::ServerFileSystem,
     source_map::{GenerateSourceMap, OptionSourceMap},
+    version::VersionedContent,
 };
 use writer::expand_imports;
 
-use self::{single_item_chunk::chunk::SingleItemCssChunk, source_map::CssChunkSourceMapAsset};
+use self::{
+    single_item_chunk::chunk::SingleItemCssChunk, source_map::CssChunkSourceMapAsset,
+    update::CssChunkVersionedContent,
+};
 use crate::{parse::ParseCssResultSourceMap, util::stringify_js, ImportAssetReference};
 
 #[turbo_tasks::value]
 pub struct CssChunk {
     pub chunking_context: Vc<Box<dyn ChunkingContext>>,
     pub content: Vc<CssChunkContent>,
+    /// The requested name of the chunk, e.g. from a `webpackChunkName`
+    /// comment. It replaces the common path of the chunk items in the ident.
+    pub chunk_name: Option<String>,
 }
 
 #[turbo_tasks::value(transparent)]
@@ -46,10 +54,12 @@ impl CssChunk {
     pub fn new(
         chunking_context: Vc<Box<dyn ChunkingContext>>,
         content: Vc<CssChunkContent>,
+        chunk_name: Option<String>,
     ) -> Vc<Self> {
         CssChunk {
             chunking_context,
             content,
+            chunk_name,
         }
         .cell()
     }
@@ -231,7 +241,11 @@ impl OutputAsset for CssChunk {
         }
 
         let ident = AssetIdent {
-            path: if let Some((common_path, _)) = common_path {
+            path: if let Some(chunk_name) = &this.chunk_name {
+                this.chunking_context
+                    .context_path()
+                    .join(chunk_name.clone())
+            } else if let Some((common_path, _)) = common_path {
                 common_path
             } else {
                 ServerFileSystem::new().root()
@@ -278,6 +292,11 @@ impl Asset for CssChunk {
     fn content(self: Vc<Self>) -> Vc<AssetContent> {
         self.content()
     }
+
+    #[turbo_tasks::function]
+    fn versioned_content(self: Vc<Self>) -> Vc<Box<dyn VersionedContent>> {
+        Vc::upcast(CssChunkVersionedContent::new(self.content()))
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -305,7 +324,10 @@ impl CssChunkContext {
         self: Vc<Self>,
         chunk_item: Vc<Box<dyn CssChunkItem>>,
     ) -> Result<Vc<ModuleId>> {
-        Ok(ModuleId::String(chunk_item.asset_ident().to_string().await?.clone_value()).cell())
+        Ok(self
+            .await?
+            .chunking_context
+            .chunk_item_id_from_ident(chunk_item.asset_ident()))
     }
 }
 
@@ -412,6 +434,7 @@ impl ChunkType for CssChunkType {
         chunking_context: Vc<Box<dyn ChunkingContext>>,
         chunk_items: Vec<ChunkItemWithAsyncModuleInfo>,
         referenced_output_assets: Vc<OutputAssets>,
+        chunk_name: Option<String>,
     ) -> Result<Vc<Box<dyn Chunk>>> {
         let content = CssChunkContent {
             chunk_items: chunk_items
@@ -430,7 +453,11 @@ impl ChunkType for CssChunkType {
             referenced_output_assets,
         }
         .cell();
-        Ok(Vc::upcast(CssChunk::new(chunking_context, content)))
+        Ok(Vc::upcast(CssChunk::new(
+            chunking_context,
+            content,
+            chunk_name,
+        )))
     }
 
     #[turbo_tasks::function]
@@ -445,12 +472,26 @@ impl ChunkType for CssChunkType {
         else {
             bail!("Chunk item is not an css chunk item but reporting chunk type css");
         };
-        Ok(Vc::cell(
-            chunk_item
-                .content()
-                .await
-                .map_or(0, |content| content.inner_code.len()),
-        ))
+        // A chunk item which fails to parse or transform reports that as an
+        // issue of its module, instead of failing the whole chunk group.
+        Ok(Vc::cell(chunk_item.content().await.map_or(0, |content| {
+            // Imports are rendered into the chunk as well, so a chunk item with
+            // imports is never considered empty.
+            content.inner_code.len() + content.imports.len()
+        })))
+    }
+
+    #[turbo_tasks::function]
+    fn can_drop_empty_chunks(&self) -> Vc<bool> {
+        // CSS chunk items are not registered at runtime, so chunks which only
+        // contain empty chunk items have no effect.
+        Vc::cell(true)
+    }
+
+    #[turbo_tasks::function]
+    fn is_order_sensitive(&self) -> Vc<bool> {
+        // Later declarations override earlier ones with the same specificity.
+        Vc::cell(true)
     }
 }
 
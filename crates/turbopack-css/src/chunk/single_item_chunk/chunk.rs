@@ -11,10 +11,11 @@ use turbopack_core::{
     introspect::Introspectable,
     output::{OutputAsset, OutputAssets},
     source_map::{GenerateSourceMap, OptionSourceMap},
+    version::VersionedContent,
 };
 
 use super::source_map::SingleItemCssChunkSourceMapAsset;
-use crate::chunk::CssChunkItem;
+use crate::chunk::{update::CssChunkVersionedContent, CssChunkItem};
 
 /// A CSS chunk that only contains a single item. This is used for selectively
 /// loading CSS modules that are part of a larger chunk in development mode, and
@@ -132,6 +133,11 @@ impl Asset for SingleItemCssChunk {
             File::from(code.source_code().clone()).into(),
         ))
     }
+
+    #[turbo_tasks::function]
+    fn versioned_content(self: Vc<Self>) -> Vc<Box<dyn VersionedContent>> {
+        Vc::upcast(CssChunkVersionedContent::new(self.content()))
+    }
 }
 
 #[turbo_tasks::value_impl]
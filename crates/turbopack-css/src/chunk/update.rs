@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use turbo_tasks::{IntoTraitRef, Vc};
+use turbopack_core::{
+    asset::AssetContent,
+    update_protocol::PartialChunkUpdate,
+    version::{
+        FileHashVersion, PartialUpdate, TotalUpdate, Update, Version, VersionedAssetContent,
+        VersionedContent,
+    },
+};
+
+/// The content of a CSS chunk alongside its version. Unlike other file
+/// contents, it can be hot updated, see [PartialChunkUpdate::CssChunkUpdate].
+#[turbo_tasks::value]
+pub struct CssChunkVersionedContent {
+    inner: Vc<VersionedAssetContent>,
+}
+
+#[turbo_tasks::value_impl]
+impl CssChunkVersionedContent {
+    #[turbo_tasks::function]
+    pub fn new(content: Vc<AssetContent>) -> Vc<Self> {
+        CssChunkVersionedContent {
+            inner: VersionedAssetContent::new(content),
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl VersionedContent for CssChunkVersionedContent {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        self.inner.content()
+    }
+
+    #[turbo_tasks::function]
+    fn version(&self) -> Vc<Box<dyn Version>> {
+        self.inner.version()
+    }
+
+    #[turbo_tasks::function]
+    async fn update(&self, from: Vc<Box<dyn Version>>) -> Result<Vc<Update>> {
+        let to = self.inner.version();
+        let to_id = to.id().await?;
+
+        // It's likely `from` is `NotFoundVersion`.
+        if Vc::try_resolve_downcast_type::<FileHashVersion>(from)
+            .await?
+            .is_none()
+        {
+            return Ok(Update::Total(TotalUpdate {
+                to: to.into_trait_ref().await?,
+            })
+            .cell());
+        }
+
+        if *from.id().await? == *to_id {
+            return Ok(Update::None.cell());
+        }
+
+        let instruction = serde_json::to_value(PartialChunkUpdate::CssChunkUpdate {
+            version: to_id.clone_value(),
+        })?;
+        Ok(Update::Partial(PartialUpdate {
+            to: to.into_trait_ref().await?,
+            instruction: Arc::new(instruction),
+        })
+        .cell())
+    }
+}
@@ -0,0 +1,598 @@
+use std::{collections::HashSet, mem::take};
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use swc_core::{
+    common::errors::Handler,
+    css::{
+        ast::{
+            ClassSelector, ComplexSelector, ComplexSelectorChildren, ComponentValue, IdSelector,
+            PseudoClassSelector, PseudoClassSelectorChildren, QualifiedRule, QualifiedRulePrelude,
+            Rule, SimpleBlock, Stylesheet, SubclassSelector, Token, TokenAndSpan,
+        },
+        codegen::{writer::basic::BasicCssWriter, CodeGenerator, Emit},
+        modules::{CssClassName, TransformConfig},
+        visit::{Visit, VisitWith},
+    },
+    ecma::atoms::JsWord,
+};
+use turbo_tasks::{trace::TraceRawVcs, Vc};
+
+/// Options for the class names of CSS modules.
+#[turbo_tasks::value(shared)]
+#[derive(Default, Clone, Debug)]
+#[serde(default)]
+pub struct CssModulesOptions {
+    /// The pattern of scoped class names, e.g. `[local]_[hash:6]`. `[local]`
+    /// is the class name in the source, `[name]` the file name up to the
+    /// first `.`, and `[hash]` a hash of the module, optionally truncated to
+    /// a length with `[hash:<length>]`. Defaults to
+    /// `[local]__[name]__[hash]`.
+    pub pattern: Option<String>,
+    /// How the class names are exported to JavaScript.
+    pub exports_convention: CssModulesExportsConvention,
+    pub mode: CssModulesMode,
+}
+
+#[turbo_tasks::value_impl]
+impl CssModulesOptions {
+    #[turbo_tasks::function]
+    pub fn default() -> Vc<Self> {
+        Self::cell(Default::default())
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CssModulesExportsConvention {
+    /// Class names are exported as they are written.
+    #[default]
+    AsIs,
+    /// Class names are exported as written and in camelCase, e.g. `btn-primary`
+    /// is exported as `btn-primary` and `btnPrimary`.
+    CamelCase,
+    /// Class names are only exported in camelCase.
+    CamelCaseOnly,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CssModulesMode {
+    /// Class names are scoped unless they are wrapped in `:global(...)`.
+    #[default]
+    Local,
+    /// Class names are only scoped when they are wrapped in `:local(...)`.
+    Global,
+    /// Like `Local`, but every selector must contain a scoped class name or
+    /// id, so the module can't style elements outside of it.
+    Pure,
+}
+
+/// The [TransformConfig] of a CSS module, which renames its class names.
+struct CssModulesNaming {
+    pattern: Option<String>,
+    basename: String,
+    path_hash: u64,
+    /// The only names which are scoped, if any. See [CssModulesMode::Global].
+    locals: Option<HashSet<JsWord>>,
+}
+
+impl TransformConfig for CssModulesNaming {
+    fn new_name_for(&self, local: &JsWord) -> JsWord {
+        if let Some(locals) = &self.locals {
+            if !locals.contains(local) {
+                return local.clone();
+            }
+        }
+        let pattern = self.pattern.as_deref().unwrap_or(DEFAULT_PATTERN);
+        scoped_name(pattern, local, &self.basename, self.path_hash).into()
+    }
+}
+
+/// The [CssModulesOptions::pattern] which is used when none is configured.
+const DEFAULT_PATTERN: &str = "[local]__[name]__[hash]";
+
+/// Replaces the placeholders of a [CssModulesOptions::pattern].
+fn scoped_name(pattern: &str, local: &str, basename: &str, path_hash: u64) -> String {
+    // Truncated to a u32, so `[hash]` is at most 8 hex characters long.
+    let hash = format!("{:x}", path_hash as u32);
+    let mut name = String::with_capacity(pattern.len() + local.len());
+    let mut rest = pattern;
+    while let Some(start) = rest.find('[') {
+        name.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(']') else {
+            break;
+        };
+        match &rest[1..end] {
+            "local" => name.push_str(local),
+            "name" => name.push_str(basename),
+            "hash" => name.push_str(&hash),
+            placeholder => match placeholder
+                .strip_prefix("hash:")
+                .and_then(|len| len.parse::<usize>().ok())
+            {
+                Some(len) => name.push_str(&hash[..len.min(hash.len())]),
+                None => name.push_str(&rest[..=end]),
+            },
+        }
+        rest = &rest[end + 1..];
+    }
+    name.push_str(rest);
+    name
+}
+
+/// Converts `btn-primary` and `btn_primary` to `btnPrimary`.
+fn camel_case(name: &str) -> String {
+    let mut camel = String::with_capacity(name.len());
+    let mut upper = false;
+    for char in name.chars() {
+        match char {
+            '-' | '_' if !camel.is_empty() => upper = true,
+            _ if upper => {
+                camel.extend(char.to_uppercase());
+                upper = false;
+            }
+            _ => camel.push(char),
+        }
+    }
+    camel
+}
+
+/// Applies the [CssModulesExportsConvention] to the exports of a CSS module.
+fn convert_exports(
+    exports: IndexMap<JsWord, Vec<CssClassName>>,
+    convention: CssModulesExportsConvention,
+) -> IndexMap<JsWord, Vec<CssClassName>> {
+    match convention {
+        CssModulesExportsConvention::AsIs => exports,
+        CssModulesExportsConvention::CamelCase => {
+            let mut converted = exports.clone();
+            for (name, class_names) in exports {
+                converted
+                    .entry(camel_case(&name).into())
+                    .or_insert(class_names);
+            }
+            converted
+        }
+        CssModulesExportsConvention::CamelCaseOnly => exports
+            .into_iter()
+            .map(|(name, class_names)| (camel_case(&name).into(), class_names))
+            .collect(),
+    }
+}
+
+/// Scopes the class names and ids of a CSS module according to its
+/// [CssModulesOptions] and returns the exported names, sorted by name.
+///
+/// `basename` and `path_hash` fill the `[name]` and `[hash]` placeholders of
+/// the pattern, and impure selectors of [CssModulesMode::Pure] modules are
+/// reported to `handler`.
+pub(crate) fn compile(
+    stylesheet: &mut Stylesheet,
+    options: &CssModulesOptions,
+    basename: &str,
+    path_hash: u64,
+    handler: &Handler,
+) -> IndexMap<JsWord, Vec<CssClassName>> {
+    if options.mode == CssModulesMode::Pure {
+        stylesheet.visit_with(&mut PureSelectorsVisitor::new(handler));
+    }
+    let locals = (options.mode == CssModulesMode::Global).then(|| {
+        let mut visitor = LocalNamesVisitor::default();
+        stylesheet.visit_with(&mut visitor);
+        visitor.locals
+    });
+    let result = swc_core::css::modules::compile(
+        stylesheet,
+        // TODO swc_css_modules should take `impl TransformConfig + '_`
+        CssModulesNaming {
+            pattern: options.pattern.clone(),
+            basename: basename.to_string(),
+            path_hash,
+            locals: locals.clone(),
+        },
+    );
+    // Names which aren't scoped in a global mode module are global, and
+    // aren't exported.
+    let renamed = result
+        .renamed
+        .into_iter()
+        .filter(|(name, _)| locals.as_ref().map_or(true, |locals| locals.contains(name)))
+        .collect();
+    let mut exports = convert_exports(renamed, options.exports_convention);
+    // exports should be reported deterministically
+    // TODO(sokra) report in order of occurrence within swc_css_modules using an
+    // IndexMap
+    exports.sort_keys();
+    exports
+}
+
+/// Collects the class names and ids which are wrapped in `:local(...)` or
+/// follow a `:local` switch, see [CssModulesMode::Global].
+#[derive(Default)]
+struct LocalNamesVisitor {
+    locals: HashSet<JsWord>,
+    in_local: bool,
+}
+
+impl Visit for LocalNamesVisitor {
+    fn visit_complex_selector(&mut self, selector: &ComplexSelector) {
+        let in_local = self.in_local;
+        selector.visit_children_with(self);
+        self.in_local = in_local;
+    }
+
+    fn visit_pseudo_class_selector(&mut self, selector: &PseudoClassSelector) {
+        match (&*selector.name.value, &selector.children) {
+            ("local", None) => self.in_local = true,
+            ("global", None) => self.in_local = false,
+            ("local", Some(_)) => {
+                let in_local = self.in_local;
+                self.in_local = true;
+                selector.visit_children_with(self);
+                self.in_local = in_local;
+            }
+            ("global", Some(_)) => {}
+            _ => selector.visit_children_with(self),
+        }
+    }
+
+    fn visit_class_selector(&mut self, selector: &ClassSelector) {
+        if self.in_local {
+            self.locals.insert(selector.text.value.clone());
+        }
+    }
+
+    fn visit_id_selector(&mut self, selector: &IdSelector) {
+        if self.in_local {
+            self.locals.insert(selector.text.value.clone());
+        }
+    }
+}
+
+/// Reports selectors which don't contain a scoped class name or id, see
+/// [CssModulesMode::Pure].
+pub(crate) struct PureSelectorsVisitor<'a> {
+    handler: &'a Handler,
+    /// Whether the visited rules are nested in a rule, which scopes them.
+    nested: bool,
+}
+
+impl<'a> PureSelectorsVisitor<'a> {
+    pub fn new(handler: &'a Handler) -> Self {
+        PureSelectorsVisitor {
+            handler,
+            nested: false,
+        }
+    }
+}
+
+impl Visit for PureSelectorsVisitor<'_> {
+    fn visit_qualified_rule(&mut self, rule: &QualifiedRule) {
+        rule.prelude.visit_with(self);
+        let nested = self.nested;
+        self.nested = true;
+        rule.block.visit_with(self);
+        self.nested = nested;
+    }
+
+    fn visit_complex_selector(&mut self, selector: &ComplexSelector) {
+        // Nested selectors are scoped by their parent selector, with or
+        // without `&`.
+        if self.nested {
+            return;
+        }
+        let mut global = false;
+        let mut pure = false;
+        for child in &selector.children {
+            let ComplexSelectorChildren::CompoundSelector(compound) = child else {
+                continue;
+            };
+            for subclass in &compound.subclass_selectors {
+                match subclass {
+                    SubclassSelector::Class(_) | SubclassSelector::Id(_) if !global => pure = true,
+                    SubclassSelector::PseudoClass(pseudo) => {
+                        match (&*pseudo.name.value, &pseudo.children) {
+                            ("global", None) => global = true,
+                            ("local", None) => global = false,
+                            ("local", Some(_)) => pure = true,
+                            _ => {}
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if !pure {
+            self.handler
+                .struct_span_err(
+                    selector.span,
+                    "Selector is not pure, every selector of a pure CSS module must contain a \
+                     local class or id",
+                )
+                .emit();
+        }
+    }
+}
+
+/// A value which is imported with an ICSS `:import("./other.module.css") {
+/// alias: name; }` block.
+#[derive(Debug, Clone)]
+pub(crate) struct IcssImport {
+    pub request: String,
+    pub name: String,
+}
+
+/// The ICSS `:import` and `:export` blocks of a CSS module, which let
+/// modules share values with other modules and JavaScript, e.g. variables of
+/// Sass modules.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct IcssSymbols {
+    /// The imported values by their local alias.
+    pub imports: IndexMap<String, IcssImport>,
+    /// The exported values by name.
+    pub exports: IndexMap<String, String>,
+}
+
+/// Removes the ICSS `:import` and `:export` blocks from the stylesheet and
+/// returns their symbols.
+///
+/// Malformed blocks are reported to `handler` and their symbols skipped.
+pub(crate) fn extract_icss(stylesheet: &mut Stylesheet, handler: &Handler) -> IcssSymbols {
+    let mut symbols = IcssSymbols::default();
+    let mut rules = Vec::with_capacity(stylesheet.rules.len());
+    for rule in take(&mut stylesheet.rules) {
+        let icss = match &rule {
+            Rule::QualifiedRule(rule) => icss_pseudo_class(rule).map(|pseudo| (rule, pseudo)),
+            _ => None,
+        };
+        let Some((rule, pseudo)) = icss else {
+            rules.push(rule);
+            continue;
+        };
+        let declarations = icss_declarations(&rule.block, handler);
+        if &*pseudo.name.value == "export" {
+            symbols.exports.extend(declarations);
+        } else {
+            let Some(request) = icss_import_request(pseudo) else {
+                handler
+                    .struct_span_err(
+                        pseudo.span,
+                        "ICSS imports must have a request, e.g. `:import(\"./a.module.css\")`",
+                    )
+                    .emit();
+                continue;
+            };
+            for (alias, name) in declarations {
+                symbols.imports.insert(
+                    alias,
+                    IcssImport {
+                        request: request.clone(),
+                        name,
+                    },
+                );
+            }
+        }
+    }
+    stylesheet.rules = rules;
+    symbols
+}
+
+/// Returns the `:import(...)` or `:export` pseudo class if it's the only
+/// selector of the rule.
+fn icss_pseudo_class(rule: &QualifiedRule) -> Option<&PseudoClassSelector> {
+    let QualifiedRulePrelude::SelectorList(list) = &rule.prelude else {
+        return None;
+    };
+    let [selector] = &list.children[..] else {
+        return None;
+    };
+    let [ComplexSelectorChildren::CompoundSelector(compound)] = &selector.children[..] else {
+        return None;
+    };
+    if compound.nesting_selector.is_some() || compound.type_selector.is_some() {
+        return None;
+    }
+    let [SubclassSelector::PseudoClass(pseudo)] = &compound.subclass_selectors[..] else {
+        return None;
+    };
+    matches!(&*pseudo.name.value, "import" | "export").then_some(pseudo)
+}
+
+fn icss_import_request(pseudo: &PseudoClassSelector) -> Option<String> {
+    pseudo
+        .children
+        .as_ref()?
+        .iter()
+        .find_map(|child| match child {
+            PseudoClassSelectorChildren::Str(str) => Some(str.value.to_string()),
+            PseudoClassSelectorChildren::PreservedToken(TokenAndSpan {
+                token: Token::String { value, .. },
+                ..
+            }) => Some(value.to_string()),
+            _ => None,
+        })
+}
+
+/// Returns the names and values of the declarations of an ICSS block.
+fn icss_declarations(block: &SimpleBlock, handler: &Handler) -> Vec<(String, String)> {
+    block
+        .value
+        .iter()
+        .filter_map(|value| match value {
+            ComponentValue::Declaration(declaration) => Some(declaration),
+            _ => None,
+        })
+        .filter_map(|declaration| {
+            let mut code = String::new();
+            let mut code_gen = CodeGenerator::new(
+                BasicCssWriter::new(&mut code, None, Default::default()),
+                Default::default(),
+            );
+            let emitted = code_gen.emit(&**declaration).is_ok();
+            let Some((name, value)) = code.split_once(':').filter(|_| emitted) else {
+                handler
+                    .struct_span_err(declaration.span, "ICSS declarations must have a value")
+                    .emit();
+                return None;
+            };
+            Some((name.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use swc_core::{
+        common::{errors::Handler, FileName, SourceMap},
+        css::{
+            ast::Stylesheet,
+            modules::CssClassName,
+            parser::{parse_file, parser::ParserConfig},
+        },
+        ecma::atoms::JsWord,
+    };
+
+    use super::{
+        camel_case, compile, extract_icss, scoped_name, CssModulesMode, CssModulesOptions,
+        DEFAULT_PATTERN,
+    };
+
+    fn parse(code: &str) -> Stylesheet {
+        let source_map: Arc<SourceMap> = Default::default();
+        let fm = source_map.new_source_file(FileName::Anon, code.to_string());
+        let config = ParserConfig {
+            css_modules: true,
+            legacy_nesting: true,
+            ..Default::default()
+        };
+        let mut errors = Vec::new();
+        let stylesheet = parse_file::<Stylesheet>(&fm, None, config, &mut errors).unwrap();
+        assert!(errors.is_empty());
+        stylesheet
+    }
+
+    fn handler() -> Handler {
+        Handler::with_emitter_writer(Box::new(std::io::sink()), None)
+    }
+
+    fn is_pure(code: &str) -> bool {
+        let handler = handler();
+        let options = CssModulesOptions {
+            mode: CssModulesMode::Pure,
+            ..Default::default()
+        };
+        compile(&mut parse(code), &options, "button", 0, &handler);
+        !handler.has_errors()
+    }
+
+    #[test]
+    fn replaces_placeholders() {
+        let hash = 0x0123_4567_89ab_cdef;
+        assert_eq!(
+            scoped_name("[local]_[hash:6]", "btn", "button", hash),
+            "btn_89abcd"
+        );
+        assert_eq!(
+            scoped_name("[name]__[local]--[hash]", "btn", "button", hash),
+            "button__btn--89abcdef"
+        );
+        assert_eq!(
+            scoped_name("[local]-[unknown]", "btn", "button", hash),
+            "btn-[unknown]"
+        );
+        assert_eq!(
+            scoped_name(DEFAULT_PATTERN, "btn", "button", hash),
+            "btn__button__89abcdef"
+        );
+    }
+
+    #[test]
+    fn converts_to_camel_case() {
+        assert_eq!(camel_case("btn-primary"), "btnPrimary");
+        assert_eq!(camel_case("btn__primary_large"), "btnPrimaryLarge");
+        assert_eq!(camel_case("-webkit"), "-webkit");
+        assert_eq!(camel_case("btn"), "btn");
+    }
+
+    #[test]
+    fn checks_pure_selectors() {
+        assert!(is_pure(".a span {}"));
+        assert!(is_pure("#a {}"));
+        assert!(is_pure(":local(.a) {}"));
+        assert!(!is_pure("span {}"));
+        assert!(!is_pure(":global(.a) span {}"));
+        assert!(!is_pure(":global .a {}"));
+    }
+
+    #[test]
+    fn nested_selectors_are_pure() {
+        assert!(is_pure(".a { & span {} }"));
+        assert!(is_pure(".a { span {} }"));
+        assert!(is_pure(".a { :global(.b) {} }"));
+        assert!(is_pure(".a { @media (min-width: 100px) { span {} } }"));
+        assert!(!is_pure("@media (min-width: 100px) { span {} }"));
+    }
+
+    #[test]
+    fn global_mode_only_exports_local_names() {
+        let handler = handler();
+        let options = CssModulesOptions {
+            pattern: Some("[local]_[name]".to_string()),
+            mode: CssModulesMode::Global,
+            ..Default::default()
+        };
+        let exports = compile(
+            &mut parse(".a {} :local(.b) {} :local .c .d {}"),
+            &options,
+            "button",
+            0,
+            &handler,
+        );
+        assert_eq!(
+            exports.keys().map(|name| &**name).collect::<Vec<_>>(),
+            ["b", "c", "d"]
+        );
+        assert!(matches!(
+            &exports[&JsWord::from("b")][..],
+            [CssClassName::Local { name }] if &*name.value == "b_button"
+        ));
+    }
+
+    #[test]
+    fn extracts_icss_blocks() {
+        let mut stylesheet = parse(
+            ":import(\"./colors.module.css\") { primary: blue; }\n:export { text: primary; size: \
+             12px; }\n.a { color: primary; }",
+        );
+        let handler = handler();
+        let icss = extract_icss(&mut stylesheet, &handler);
+        assert!(!handler.has_errors());
+        assert_eq!(stylesheet.rules.len(), 1);
+
+        let import = &icss.imports["primary"];
+        assert_eq!(import.request, "./colors.module.css");
+        assert_eq!(import.name, "blue");
+        assert_eq!(
+            icss.exports
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .collect::<Vec<_>>(),
+            [("text", "primary"), ("size", "12px")]
+        );
+    }
+
+    #[test]
+    fn reports_icss_imports_without_request() {
+        let mut stylesheet = parse(":import { primary: blue; }\n:export { text: red; }");
+        let handler = handler();
+        let icss = extract_icss(&mut stylesheet, &handler);
+        assert!(handler.has_errors());
+        assert!(stylesheet.rules.is_empty());
+        assert!(icss.imports.is_empty());
+        assert_eq!(icss.exports["text"], "red");
+    }
+}
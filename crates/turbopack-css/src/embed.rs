@@ -22,4 +22,9 @@ pub trait CssEmbed {
     /// references.
     fn references(self: Vc<Self>) -> Vc<OutputAssets>;
     fn embeddable_asset(self: Vc<Self>) -> Vc<Box<dyn OutputAsset>>;
+    /// A `data:` url the asset is inlined as instead of referencing the
+    /// [CssEmbed::embeddable_asset], if any.
+    fn inline_url(self: Vc<Self>) -> Vc<Option<String>> {
+        Vc::cell(None)
+    }
 }
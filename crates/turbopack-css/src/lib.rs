@@ -8,8 +8,10 @@
 mod asset;
 pub mod chunk;
 mod code_gen;
+pub mod css_modules;
 pub mod embed;
 mod global_asset;
+pub mod lightningcss;
 mod module_asset;
 pub(crate) mod parse;
 mod path_visitor;
@@ -0,0 +1,333 @@
+use std::fmt::Display;
+
+use anyhow::{anyhow, Context, Result};
+use lightningcss::{
+    error::Error,
+    stylesheet::{MinifyOptions, ParserFlags, ParserOptions, PrinterOptions, StyleSheet},
+    targets::{Browsers, Targets},
+};
+use parcel_sourcemap::SourceMap as ParcelSourceMap;
+use sourcemap::SourceMap as CrateMap;
+use turbo_tasks::{ValueToString, Vc};
+use turbo_tasks_fs::{File, FileContent, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    environment::Environment,
+    ident::AssetIdent,
+    issue::{Issue, IssueExt, IssueSeverity, IssueSource, OptionIssueSource},
+    source::Source,
+    source_map::{GenerateSourceMap, OptionSourceMap, SourceMap},
+    source_pos::SourcePos,
+    source_transform::SourceTransform,
+    SOURCE_MAP_ROOT_NAME,
+};
+
+/// A [SourceTransform] which parses, lowers and optionally minifies CSS
+/// natively with Lightning CSS, instead of running a chain of transforms.
+///
+/// Nesting and `@custom-media` rules are compiled away, and vendor prefixes
+/// are added and removed, for the browsers of the `targets` environment.
+#[turbo_tasks::value]
+pub struct LightningCssTransform {
+    targets: Option<Vc<Environment>>,
+    minify: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl LightningCssTransform {
+    #[turbo_tasks::function]
+    pub fn new(targets: Option<Vc<Environment>>, minify: bool) -> Vc<Self> {
+        LightningCssTransform { targets, minify }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl SourceTransform for LightningCssTransform {
+    #[turbo_tasks::function]
+    fn transform(&self, source: Vc<Box<dyn Source>>) -> Vc<Box<dyn Source>> {
+        Vc::upcast(
+            LightningCssTransformedSource {
+                targets: self.targets,
+                minify: self.minify,
+                source,
+            }
+            .cell(),
+        )
+    }
+}
+
+#[turbo_tasks::value]
+struct LightningCssTransformedSource {
+    targets: Option<Vc<Environment>>,
+    minify: bool,
+    source: Vc<Box<dyn Source>>,
+}
+
+#[turbo_tasks::value]
+enum LightningCssResult {
+    Transformed {
+        code: String,
+        source_map: Vc<SourceMap>,
+    },
+    /// The source isn't CSS text, or Lightning CSS failed to process it and
+    /// reported an issue. The source is passed through as is.
+    Unchanged,
+}
+
+#[turbo_tasks::value_impl]
+impl LightningCssTransformedSource {
+    #[turbo_tasks::function]
+    async fn transformed(&self) -> Result<Vc<LightningCssResult>> {
+        let AssetContent::File(file) = *self.source.content().await? else {
+            return Ok(LightningCssResult::Unchanged.cell());
+        };
+        let FileContent::Content(file) = &*file.await? else {
+            return Ok(LightningCssResult::Unchanged.cell());
+        };
+        let Ok(code) = file.content().to_str() else {
+            return Ok(LightningCssResult::Unchanged.cell());
+        };
+
+        let browsers = match self.targets {
+            Some(targets) => match &*targets.browserslist_query().await? {
+                Some(query) => browsers_from_query(query)?,
+                None => None,
+            },
+            None => None,
+        };
+
+        let ident = self.source.ident().to_string().await?;
+        Ok(
+            match transform_css(&code, &ident, Targets::from(browsers), self.minify)? {
+                Ok(TransformedCss { code, source_map }) => LightningCssResult::Transformed {
+                    code,
+                    source_map: SourceMap::new_regular(source_map).cell(),
+                }
+                .cell(),
+                Err(error) => {
+                    LightningCssIssue {
+                        file_path: self.source.ident().path(),
+                        source: error.location.map(|pos| {
+                            IssueSource {
+                                source: self.source,
+                                start: pos,
+                                end: pos,
+                            }
+                            .cell()
+                        }),
+                        message: error.message,
+                    }
+                    .cell()
+                    .emit();
+                    LightningCssResult::Unchanged.cell()
+                }
+            },
+        )
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Source for LightningCssTransformedSource {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.source.ident()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for LightningCssTransformedSource {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        Ok(match &*self.transformed().await? {
+            LightningCssResult::Transformed { code, .. } => {
+                AssetContent::file(FileContent::Content(File::from(code.clone())).cell())
+            }
+            LightningCssResult::Unchanged => self.await?.source.content(),
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for LightningCssTransformedSource {
+    /// Maps the transformed CSS back to the original source, so that the
+    /// source maps of the CSS chunks can be traced through this transform.
+    #[turbo_tasks::function]
+    async fn generate_source_map(self: Vc<Self>) -> Result<Vc<OptionSourceMap>> {
+        Ok(Vc::cell(match &*self.transformed().await? {
+            LightningCssResult::Transformed { source_map, .. } => Some(*source_map),
+            LightningCssResult::Unchanged => None,
+        }))
+    }
+}
+
+/// Parses a comma separated browserslist query, e.g. `"chrome 64, edge 79"`.
+fn browsers_from_query(query: &str) -> Result<Option<Browsers>> {
+    Browsers::from_browserslist(query.split(',').map(str::trim))
+        .with_context(|| format!("invalid browserslist query {query:?}"))
+}
+
+struct TransformedCss {
+    code: String,
+    source_map: CrateMap,
+}
+
+/// An error reported by Lightning CSS while processing a stylesheet.
+#[derive(Debug)]
+struct CssError {
+    message: String,
+    location: Option<SourcePos>,
+}
+
+impl<T: Display> From<Error<T>> for CssError {
+    fn from(error: Error<T>) -> Self {
+        CssError {
+            message: error.kind.to_string(),
+            // Lightning CSS lines are 0-based but its columns are 1-based.
+            location: error.loc.map(|loc| SourcePos {
+                line: loc.line as usize,
+                column: (loc.column as usize).saturating_sub(1),
+            }),
+        }
+    }
+}
+
+/// Parses, lowers and prints `code`, along with a source map from the printed
+/// CSS to `code`, which is named after `ident` like the maps of the CSS
+/// parser.
+///
+/// Errors in the CSS are returned as [CssError]s, to be reported as issues.
+fn transform_css(
+    code: &str,
+    ident: &str,
+    targets: Targets,
+    minify: bool,
+) -> Result<Result<TransformedCss, CssError>> {
+    let mut stylesheet = match StyleSheet::parse(
+        code,
+        ParserOptions {
+            filename: ident.to_string(),
+            flags: ParserFlags::NESTING | ParserFlags::CUSTOM_MEDIA,
+            ..Default::default()
+        },
+    ) {
+        Ok(stylesheet) => stylesheet,
+        Err(error) => return Ok(Err(error.into())),
+    };
+    if let Err(error) = stylesheet.minify(MinifyOptions {
+        targets,
+        ..Default::default()
+    }) {
+        return Ok(Err(error.into()));
+    }
+
+    let mut parcel_map = ParcelSourceMap::new("/");
+    parcel_map.add_source(ident);
+    let result = match stylesheet.to_css(PrinterOptions {
+        minify,
+        targets,
+        source_map: Some(&mut parcel_map),
+        ..Default::default()
+    }) {
+        Ok(result) => result,
+        Err(error) => return Ok(Err(error.into())),
+    };
+
+    let json = parcel_map
+        .to_json(None)
+        .map_err(|err| anyhow!("{err}"))
+        .context("failed to serialize the Lightning CSS source map")?;
+    let mut source_map = CrateMap::from_slice(json.as_bytes())?;
+    source_map.set_source(0, &format!("/{SOURCE_MAP_ROOT_NAME}/{ident}"));
+    source_map.set_source_contents(0, Some(code));
+
+    Ok(Ok(TransformedCss {
+        code: result.code,
+        source_map,
+    }))
+}
+
+#[turbo_tasks::value(shared)]
+struct LightningCssIssue {
+    file_path: Vc<FileSystemPath>,
+    source: Option<Vc<IssueSource>>,
+    message: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for LightningCssIssue {
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell("Transforming css with Lightning CSS failed".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(self.message.clone())
+    }
+
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("parse".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> Vc<OptionIssueSource> {
+        Vc::cell(self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lightningcss::targets::Targets;
+
+    use super::{browsers_from_query, transform_css};
+
+    #[test]
+    fn trims_browserslist_entries() {
+        let browsers = browsers_from_query("chrome 64, edge 79").unwrap().unwrap();
+        assert_eq!(browsers.chrome, Some(64 << 16));
+        assert_eq!(browsers.edge, Some(79 << 16));
+    }
+
+    #[test]
+    fn lowers_nesting_with_a_source_map() {
+        let code = ".a {\n  color: red;\n\n  & .b {\n    color: blue;\n  }\n}\n";
+        let result = transform_css(code, "[project]/a.css", Targets::default(), true)
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.code, ".a{color:red}.a .b{color:#00f}");
+
+        let map = result.source_map;
+        assert_eq!(map.get_source(0), Some("/turbopack/[project]/a.css"));
+        assert_eq!(map.get_source_contents(0), Some(code));
+        let token = map
+            .lookup_token(0, result.code.find(".a .b").unwrap() as u32)
+            .unwrap();
+        assert_eq!(token.get_src_line(), 3);
+    }
+
+    #[test]
+    fn reports_parse_errors_with_their_location() {
+        let error = transform_css(
+            ".a {}\n..b { color: red }",
+            "a.css",
+            Targets::default(),
+            false,
+        )
+        .unwrap()
+        .err()
+        .unwrap();
+        assert!(!error.message.is_empty());
+        assert_eq!(error.location.unwrap().line, 1);
+    }
+}
@@ -110,6 +110,10 @@ enum ModuleCssClass {
         original: String,
         from: Vc<CssModuleComposeReference>,
     },
+    /// A value of an ICSS `:export` block.
+    Value {
+        value: String,
+    },
 }
 
 /// A map of CSS classes exported from a CSS module.
@@ -128,12 +132,17 @@ enum ModuleCssClass {
 /// .class3 {
 ///   composes: class4 from "./other.module.css";
 /// }
+///
+/// :export {
+///   primary: #f00;
+/// }
 /// ```
 ///
 /// The above CSS module would have the following exports:
 /// 1. class1: [Global("exported_class1")]
 /// 2. class2: [Local("exported_class2")]
 /// 3. class3: [Local("exported_class3), Import("class4", "./other.module.css")]
+/// 4. primary: [Value("#f00")]
 #[turbo_tasks::value(transparent)]
 #[derive(Debug, Clone)]
 struct ModuleCssClasses(IndexMap<String, Vec<ModuleCssClass>>);
@@ -141,7 +150,7 @@ struct ModuleCssClasses(IndexMap<String, Vec<ModuleCssClass>>);
 #[turbo_tasks::value_impl]
 impl ModuleCssAsset {
     #[turbo_tasks::function]
-    async fn inner(self: Vc<Self>) -> Result<Vc<Box<dyn Module>>> {
+    pub(crate) async fn inner(self: Vc<Self>) -> Result<Vc<Box<dyn Module>>> {
         let this = self.await?;
         Ok(this.asset_context.process(
             this.source,
@@ -161,7 +170,7 @@ impl ModuleCssAsset {
         let mut classes = IndexMap::default();
 
         // TODO(alexkirsz) Should we report an error on parse error here?
-        if let ParseCssResult::Ok { exports, .. } = &*parse_result {
+        if let ParseCssResult::Ok { exports, icss, .. } = &*parse_result {
             for (class_name, export_class_names) in exports {
                 let mut export = Vec::default();
 
@@ -185,6 +194,23 @@ impl ModuleCssAsset {
 
                 classes.insert(class_name.to_string(), export);
             }
+
+            for (name, value) in &icss.exports {
+                let export = match icss.imports.get(value) {
+                    // Re-exports a value which is imported from another module.
+                    Some(import) => ModuleCssClass::Import {
+                        original: import.name.clone(),
+                        from: CssModuleComposeReference::new(
+                            Vc::upcast(self),
+                            Request::parse(Value::new(import.request.clone().into())),
+                        ),
+                    },
+                    None => ModuleCssClass::Value {
+                        value: value.clone(),
+                    },
+                };
+                classes.insert(name.clone(), vec![export]);
+            }
         }
 
         Ok(Vc::cell(classes))
@@ -200,7 +226,9 @@ impl ModuleCssAsset {
                     ModuleCssClass::Import { from, .. } => {
                         references.push(Vc::upcast(*from));
                     }
-                    ModuleCssClass::Local { .. } | ModuleCssClass::Global { .. } => {}
+                    ModuleCssClass::Local { .. }
+                    | ModuleCssClass::Global { .. }
+                    | ModuleCssClass::Value { .. } => {}
                 }
             }
         }
@@ -343,8 +371,18 @@ impl EcmascriptChunkItem for ModuleChunkItem {
                             continue;
                         };
 
-                        // TODO(alexkirsz) We should also warn if `original_name` can't be found in
-                        // the target module.
+                        if !css_module.classes().await?.contains_key(original_name) {
+                            CssModuleComposesIssue {
+                                severity: IssueSeverity::Warning.cell(),
+                                source: self.module.ident(),
+                                message: Vc::cell(formatdoc! {
+                                    r#"
+                                        `{original_name}` referenced in `composes: ... from {from};` is not exported by {from}.
+                                    "#,
+                                    from = &*from.await?.request.to_string().await?
+                                }),
+                            }.cell().emit();
+                        }
 
                         let placeable: Vc<Box<dyn EcmascriptChunkPlaceable>> =
                             Vc::upcast(css_module);
@@ -360,7 +398,8 @@ impl EcmascriptChunkItem for ModuleChunkItem {
                         });
                     }
                     ModuleCssClass::Local { name: class_name }
-                    | ModuleCssClass::Global { name: class_name } => {
+                    | ModuleCssClass::Global { name: class_name }
+                    | ModuleCssClass::Value { value: class_name } => {
                         exported_class_names.push(StringifyJs(&class_name).to_string());
                     }
                 }
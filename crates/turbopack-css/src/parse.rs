@@ -10,7 +10,7 @@ use swc_core::{
     },
     css::{
         ast::Stylesheet,
-        modules::{CssClassName, TransformConfig},
+        modules::CssClassName,
         parser::{parse_file, parser::ParserConfig},
     },
     ecma::atoms::JsWord,
@@ -26,6 +26,7 @@ use turbopack_core::{
 use turbopack_swc_utils::emitter::IssueEmitter;
 
 use crate::{
+    css_modules::{compile, extract_icss, CssModulesOptions, IcssSymbols},
     transform::{CssInputTransform, CssInputTransforms, TransformContext},
     CssModuleAssetType,
 };
@@ -44,6 +45,8 @@ pub enum ParseCssResult {
         imports: Vec<JsWord>,
         #[turbo_tasks(debug_ignore, trace_ignore)]
         exports: IndexMap<JsWord, Vec<CssClassName>>,
+        #[turbo_tasks(debug_ignore, trace_ignore)]
+        icss: IcssSymbols,
     },
     Unparseable,
     NotFound,
@@ -67,19 +70,30 @@ pub struct ParseCssResultSourceMap {
     /// SourceMap.
     #[turbo_tasks(debug_ignore, trace_ignore)]
     mappings: Vec<(BytePos, LineCol)>,
+
+    /// The source map of the parsed source, when a source transform changed
+    /// it, which the generated source map is traced through.
+    input_source_map: Option<Vc<Box<dyn GenerateSourceMap>>>,
 }
 
 impl PartialEq for ParseCssResultSourceMap {
     fn eq(&self, other: &Self) -> bool {
-        Arc::ptr_eq(&self.source_map, &other.source_map) && self.mappings == other.mappings
+        Arc::ptr_eq(&self.source_map, &other.source_map)
+            && self.mappings == other.mappings
+            && self.input_source_map == other.input_source_map
     }
 }
 
 impl ParseCssResultSourceMap {
-    pub fn new(source_map: Arc<SourceMap>, mappings: Vec<(BytePos, LineCol)>) -> Self {
+    pub fn new(
+        source_map: Arc<SourceMap>,
+        mappings: Vec<(BytePos, LineCol)>,
+        input_source_map: Option<Vc<Box<dyn GenerateSourceMap>>>,
+    ) -> Self {
         ParseCssResultSourceMap {
             source_map,
             mappings,
+            input_source_map,
         }
     }
 }
@@ -87,15 +101,21 @@ impl ParseCssResultSourceMap {
 #[turbo_tasks::value_impl]
 impl GenerateSourceMap for ParseCssResultSourceMap {
     #[turbo_tasks::function]
-    fn generate_source_map(&self) -> Vc<OptionSourceMap> {
+    async fn generate_source_map(&self) -> Result<Vc<OptionSourceMap>> {
         let map = self.source_map.build_source_map_with_config(
             &self.mappings,
             None,
             InlineSourcesContentConfig {},
         );
-        Vc::cell(Some(
-            turbopack_core::source_map::SourceMap::new_regular(map).cell(),
-        ))
+        let map = turbopack_core::source_map::SourceMap::new_regular(map).cell();
+        let input_map = match self.input_source_map {
+            Some(input_source_map) => *input_source_map.generate_source_map().await?,
+            None => None,
+        };
+        Ok(Vc::cell(Some(match input_map {
+            Some(input_map) => input_map.trace(map),
+            None => map,
+        })))
     }
 }
 
@@ -122,6 +142,7 @@ pub async fn parse_css(
     source: Vc<Box<dyn Source>>,
     ty: CssModuleAssetType,
     transforms: Vc<CssInputTransforms>,
+    css_modules: Vc<CssModulesOptions>,
 ) -> Result<Vc<ParseCssResult>> {
     let content = source.content();
     let fs_path = &*source.ident().path().await?;
@@ -141,6 +162,7 @@ pub async fn parse_css(
                         source,
                         ty,
                         transforms,
+                        &*css_modules.await?,
                     )
                     .await?
                 }
@@ -156,6 +178,7 @@ async fn parse_content(
     source: Vc<Box<dyn Source>>,
     ty: CssModuleAssetType,
     transforms: &[CssInputTransform],
+    css_modules: &CssModulesOptions,
 ) -> Result<Vc<ParseCssResult>> {
     let source_map: Arc<SourceMap> = Default::default();
     let handler = Handler::with_emitter(
@@ -206,9 +229,10 @@ async fn parse_content(
             .await?;
     }
 
-    let (imports, exports) = match ty {
+    let (imports, exports, icss) = match ty {
         CssModuleAssetType::Default => Default::default(),
         CssModuleAssetType::Module => {
+            let icss = extract_icss(&mut parsed_stylesheet, &handler);
             let imports = swc_core::css::modules::imports::analyze_imports(&parsed_stylesheet);
             let basename = BASENAME_RE
                 .captures(fs_path.file_name())
@@ -216,21 +240,14 @@ async fn parse_content(
                 .get(0)
                 .context("Must include basename preceding .")?
                 .as_str();
-            // Truncate this as u32 so it's formated as 8-character hex in the suffic below
-            let path_hash = turbo_tasks_hash::hash_xxh3_hash64(ident_str) as u32;
-            let result = swc_core::css::modules::compile(
+            let exports = compile(
                 &mut parsed_stylesheet,
-                // TODO swc_css_modules should take `impl TransformConfig + '_`
-                ModuleTransformConfig {
-                    suffix: format!("__{}__{:x}", basename, path_hash),
-                },
+                css_modules,
+                basename,
+                turbo_tasks_hash::hash_xxh3_hash64(ident_str),
+                &handler,
             );
-            let mut exports = result.renamed.into_iter().collect::<IndexMap<_, _>>();
-            // exports should be reported deterministically
-            // TODO(sokra) report in order of occurrence within swc_css_modules using an
-            // IndexMap
-            exports.sort_keys();
-            (imports, exports)
+            (imports, exports, icss)
         }
     };
 
@@ -239,20 +256,11 @@ async fn parse_content(
         source_map,
         imports,
         exports,
+        icss,
     }
     .into())
 }
 
-struct ModuleTransformConfig {
-    suffix: String,
-}
-
-impl TransformConfig for ModuleTransformConfig {
-    fn new_name_for(&self, local: &JsWord) -> JsWord {
-        format!("{}{}", *local, self.suffix).into()
-    }
-}
-
 /// Trait to be implemented by assets which can be parsed as CSS.
 #[turbo_tasks::value_trait]
 pub trait ParseCss {
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use indexmap::IndexMap;
 use swc_core::{
     common::{
         errors::{Handler, HANDLER},
@@ -26,9 +27,11 @@ use turbopack_core::{
 use turbopack_swc_utils::emitter::IssueEmitter;
 
 use crate::{
+    css_modules::CssModulesOptions,
     parse::{parse_css, ParseCssResult},
     references::{
         import::{ImportAssetReference, ImportAttributes},
+        module_import::CssModuleImportReference,
         url::UrlAssetReference,
     },
     CssInputTransforms, CssModuleAssetType,
@@ -37,6 +40,7 @@ use crate::{
 pub(crate) mod compose;
 pub(crate) mod import;
 pub(crate) mod internal;
+pub(crate) mod module_import;
 pub(crate) mod url;
 
 #[turbo_tasks::function]
@@ -45,14 +49,17 @@ pub async fn analyze_css_stylesheet(
     origin: Vc<Box<dyn ResolveOrigin>>,
     ty: CssModuleAssetType,
     transforms: Vc<CssInputTransforms>,
+    css_modules: Vc<CssModulesOptions>,
 ) -> Result<Vc<ModuleReferences>> {
     let mut references = Vec::new();
 
-    let parsed = parse_css(source, ty, transforms).await?;
+    let parsed = parse_css(source, ty, transforms, css_modules).await?;
 
     if let ParseCssResult::Ok {
         stylesheet,
         source_map,
+        imports,
+        icss,
         ..
     } = &*parsed
     {
@@ -73,6 +80,25 @@ pub async fn analyze_css_stylesheet(
                 stylesheet.visit_with_path(&mut visitor, &mut Default::default());
             })
         });
+
+        // The other CSS modules of `composes: ... from "..."` and ICSS `:import`s.
+        let mut module_imports: IndexMap<String, Vec<(String, String)>> = imports
+            .iter()
+            .map(|request| (request.to_string(), Vec::new()))
+            .collect();
+        for (alias, import) in &icss.imports {
+            module_imports
+                .entry(import.request.clone())
+                .or_default()
+                .push((alias.clone(), import.name.clone()));
+        }
+        for (request, values) in module_imports {
+            references.push(Vc::upcast(CssModuleImportReference::new(
+                origin,
+                Request::parse(Value::new(request.into())),
+                values,
+            )));
+        }
     }
     Ok(Vc::cell(references))
 }
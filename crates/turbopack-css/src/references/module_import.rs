@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use swc_core::css::{
+    ast::{ComponentValue, Stylesheet},
+    visit::{VisitMut, VisitMutWith},
+};
+use turbo_tasks::{Value, ValueToString, Vc};
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::{
+    chunk::ChunkingContext,
+    issue::{Issue, IssueExt, IssueSeverity},
+    module::Module,
+    reference::ModuleReference,
+    reference_type::CssReferenceSubType,
+    resolve::{origin::ResolveOrigin, parse::Request, ModuleResolveResult},
+};
+
+use crate::{
+    code_gen::{CodeGenerateable, CodeGeneration},
+    create_visitor,
+    parse::{ParseCss, ParseCssResult},
+    references::css_resolve,
+    ModuleCssAsset,
+};
+
+/// A dependency of a CSS module on another CSS module, either from
+/// `composes: ... from "..."` or from an ICSS `:import("...")` block.
+///
+/// The CSS of the other module is included before the CSS of this module,
+/// and the imported ICSS values replace their aliases in this module.
+#[turbo_tasks::value]
+#[derive(Hash, Debug)]
+pub struct CssModuleImportReference {
+    pub origin: Vc<Box<dyn ResolveOrigin>>,
+    pub request: Vc<Request>,
+    /// The local aliases and the names of the imported ICSS values.
+    pub values: Vec<(String, String)>,
+}
+
+impl CssModuleImportReference {
+    /// Creates a new [`CssModuleImportReference`].
+    pub fn new(
+        origin: Vc<Box<dyn ResolveOrigin>>,
+        request: Vc<Request>,
+        values: Vec<(String, String)>,
+    ) -> Vc<Self> {
+        Self::cell(CssModuleImportReference {
+            origin,
+            request,
+            values,
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleReference for CssModuleImportReference {
+    #[turbo_tasks::function]
+    fn resolve_reference(&self) -> Vc<ModuleResolveResult> {
+        css_resolve(
+            self.origin,
+            self.request,
+            Value::new(CssReferenceSubType::Compose),
+            None,
+        )
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for CssModuleImportReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell(format!(
+            "css module import {}",
+            self.request.to_string().await?,
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for CssModuleImportReference {
+    #[turbo_tasks::function]
+    async fn code_generation(
+        self: Vc<Self>,
+        _chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<Vc<CodeGeneration>> {
+        let this = self.await?;
+        let mut visitors = Vec::new();
+
+        if !this.values.is_empty() {
+            let module = *self.resolve_reference().first_module().await?;
+            let mut values = HashMap::new();
+            if let Some(module) = module {
+                for (alias, name) in &this.values {
+                    match &*icss_export_value(module, name.clone()).await? {
+                        IcssExportValue::Value(value) => {
+                            values.insert(alias.clone(), value.clone());
+                        }
+                        IcssExportValue::Cycle => {}
+                        IcssExportValue::NotExported => IcssImportIssue {
+                            path: this.origin.origin_path(),
+                            message: format!(
+                                "The value `{}` is not exported by {}",
+                                name,
+                                this.request.to_string().await?
+                            ),
+                        }
+                        .cell()
+                        .emit(),
+                    }
+                }
+            }
+
+            visitors.push(
+                create_visitor!(visit_mut_stylesheet(stylesheet: &mut Stylesheet) {
+                    stylesheet.visit_mut_with(&mut IcssValuesReplacer { values: &values });
+                }),
+            );
+        }
+
+        Ok(CodeGeneration {
+            visitors,
+            imports: vec![],
+        }
+        .into())
+    }
+}
+
+/// Replaces the aliases of imported ICSS values with the values.
+struct IcssValuesReplacer<'a> {
+    values: &'a HashMap<String, String>,
+}
+
+impl VisitMut for IcssValuesReplacer<'_> {
+    fn visit_mut_component_value(&mut self, value: &mut ComponentValue) {
+        if let ComponentValue::Ident(ident) = value {
+            if let Some(replacement) = self.values.get(&*ident.value) {
+                ident.value = replacement.as_str().into();
+                ident.raw = Some(replacement.as_str().into());
+                return;
+            }
+        }
+        value.visit_mut_children_with(self);
+    }
+}
+
+#[turbo_tasks::value]
+enum IcssExportValue {
+    Value(String),
+    NotExported,
+    /// The value is imported in a cycle, which is already reported.
+    Cycle,
+}
+
+/// Returns the value of an ICSS `:export` of a CSS module, following values
+/// which the module imports from other modules itself.
+#[turbo_tasks::function]
+async fn icss_export_value(
+    module: Vc<Box<dyn Module>>,
+    name: String,
+) -> Result<Vc<IcssExportValue>> {
+    let mut module = module;
+    let mut name = name;
+    let mut visited = Vec::new();
+    loop {
+        let current = match Vc::try_resolve_downcast_type::<ModuleCssAsset>(module).await? {
+            Some(css_module) => css_module.inner(),
+            None => module,
+        }
+        .resolve()
+        .await?;
+        let cycle_start = visited.iter().position(|(visited_module, visited_name)| {
+            *visited_module == current && *visited_name == name
+        });
+        visited.push((current, name));
+        if let Some(start) = cycle_start {
+            let mut cycle = Vec::new();
+            for (module, name) in &visited[start..] {
+                cycle.push(format!(
+                    "`{}` of {}",
+                    name,
+                    module.ident().to_string().await?
+                ));
+            }
+            IcssImportIssue {
+                path: current.ident().path(),
+                message: format!(
+                    "ICSS values are imported in a cycle: {}",
+                    cycle.join(" -> ")
+                ),
+            }
+            .cell()
+            .emit();
+            return Ok(IcssExportValue::Cycle.cell());
+        }
+
+        let Some(parse_css) = Vc::try_resolve_sidecast::<Box<dyn ParseCss>>(current).await? else {
+            return Ok(IcssExportValue::NotExported.cell());
+        };
+        let ParseCssResult::Ok { icss, .. } = &*parse_css.parse_css().await? else {
+            return Ok(IcssExportValue::NotExported.cell());
+        };
+        let (_, current_name) = visited.last().unwrap();
+        let Some(value) = icss.exports.get(current_name) else {
+            return Ok(IcssExportValue::NotExported.cell());
+        };
+        let Some(import) = icss.imports.get(value) else {
+            return Ok(IcssExportValue::Value(value.clone()).cell());
+        };
+        let Some(origin) = Vc::try_resolve_sidecast::<Box<dyn ResolveOrigin>>(current).await?
+        else {
+            return Ok(IcssExportValue::NotExported.cell());
+        };
+        let imported = css_resolve(
+            origin,
+            Request::parse(Value::new(import.request.clone().into())),
+            Value::new(CssReferenceSubType::Compose),
+            None,
+        )
+        .first_module()
+        .await?;
+        let Some(imported) = *imported else {
+            return Ok(IcssExportValue::NotExported.cell());
+        };
+        module = imported;
+        name = import.name.clone();
+    }
+}
+
+#[turbo_tasks::value(shared)]
+struct IcssImportIssue {
+    path: Vc<FileSystemPath>,
+    message: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for IcssImportIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell("ICSS value can't be imported".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("css".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(self.message.clone())
+    }
+}
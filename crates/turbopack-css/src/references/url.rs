@@ -25,6 +25,8 @@ use crate::{
 #[turbo_tasks::value(into = "new")]
 pub enum ReferencedAsset {
     Some(Vc<Box<dyn OutputAsset>>),
+    /// The asset is inlined as a `data:` url.
+    Inline(String),
     None,
 }
 
@@ -55,7 +57,7 @@ impl UrlAssetReference {
     }
 
     #[turbo_tasks::function]
-    async fn get_referenced_asset(
+    pub async fn get_referenced_asset(
         self: Vc<Self>,
         chunking_context: Vc<Box<dyn ChunkingContext>>,
     ) -> Result<Vc<ReferencedAsset>> {
@@ -63,10 +65,11 @@ impl UrlAssetReference {
             if let Some(embeddable) =
                 Vc::try_resolve_sidecast::<Box<dyn CssEmbeddable>>(module).await?
             {
-                return Ok(ReferencedAsset::Some(
-                    embeddable.as_css_embed(chunking_context).embeddable_asset(),
-                )
-                .into());
+                let embed = embeddable.as_css_embed(chunking_context);
+                if let Some(url) = &*embed.inline_url().await? {
+                    return Ok(ReferencedAsset::Inline(url.clone()).into());
+                }
+                return Ok(ReferencedAsset::Some(embed.embeddable_asset()).into());
             }
         }
         Ok(ReferencedAsset::cell(ReferencedAsset::None))
@@ -115,19 +118,27 @@ impl CodeGenerateable for UrlAssetReference {
 
         let mut visitors = Vec::new();
 
-        if let ReferencedAsset::Some(asset) = &*self.get_referenced_asset(chunking_context).await? {
-            // TODO(WEB-662) This is not the correct way to get the path of the asset.
-            // `asset` is on module-level, but we need the output-level asset instead.
-            let path = asset.ident().path().await?;
-            let relative_path = context_path
-                .get_relative_path_to(&path)
-                .unwrap_or_else(|| format!("/{}", path.path));
+        let url = match &*self.get_referenced_asset(chunking_context).await? {
+            ReferencedAsset::Some(asset) => {
+                // TODO(WEB-662) This is not the correct way to get the path of the asset.
+                // `asset` is on module-level, but we need the output-level asset instead.
+                let path = asset.ident().path().await?;
+                Some(
+                    context_path
+                        .get_relative_path_to(&path)
+                        .unwrap_or_else(|| format!("/{}", path.path)),
+                )
+            }
+            ReferencedAsset::Inline(url) => Some(url.clone()),
+            ReferencedAsset::None => None,
+        };
 
+        if let Some(url) = url {
             visitors.push(
                 create_visitor!((&this.path.await?), visit_mut_url(u: &mut Url) {
                     u.value = Some(Box::new(UrlValue::Str(Str {
                         span: DUMMY_SP,
-                        value: relative_path.as_str().into(),
+                        value: url.as_str().into(),
                         raw: None,
                     })))
                 }),
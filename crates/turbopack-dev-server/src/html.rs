@@ -1,13 +1,16 @@
 use anyhow::{anyhow, Result};
 use mime_guess::mime::TEXT_HTML_UTF_8;
-use turbo_tasks::{ReadRef, TryJoinIterExt, Vc};
+use turbo_tasks::{ReadRef, TryJoinIterExt, Value, Vc};
 use turbo_tasks_fs::{File, FileSystemPath};
 use turbo_tasks_hash::{encode_hex, Xxh3Hash64Hasher};
 use turbopack_core::{
     asset::{Asset, AssetContent},
-    chunk::{ChunkableModule, ChunkingContext, ChunkingContextExt, EvaluatableAssets},
+    chunk::{
+        availability_info::AvailabilityInfo, preload::chunk_group_loading_hints, ChunkableModule,
+        ChunkingContext, ChunkingContextExt, EvaluatableAssets,
+    },
     ident::AssetIdent,
-    module::Module,
+    module::{Module, Modules},
     output::{OutputAsset, OutputAssets},
     version::{Version, VersionedContent},
 };
@@ -22,7 +25,9 @@ type DevHtmlEntry = (
 
 /// The HTML entry point of the dev server.
 ///
-/// Generates an HTML page that includes the ES and CSS chunks.
+/// Generates an HTML page that includes the ES and CSS chunks, and prefetches
+/// or preloads the chunks of async chunk groups which are imported with
+/// loading hints, e.g. `import(/* webpackPrefetch: true */ "./page")`.
 #[turbo_tasks::value(shared)]
 #[derive(Clone)]
 pub struct DevHtmlAsset {
@@ -119,7 +124,77 @@ impl DevHtmlAsset {
             }
         }
 
-        Ok(DevHtmlAssetContent::new(chunk_paths, this.body.clone()))
+        let mut prefetch_paths = vec![];
+        let mut preload_paths = vec![];
+        for (chunking_context, entries) in &*self.chunk_group_entries().await? {
+            let loading_hints = chunk_group_loading_hints(
+                *chunking_context,
+                *entries,
+                Value::new(AvailabilityInfo::Root),
+            )
+            .await?;
+            for loading_hint in loading_hints.iter() {
+                let loading_hint = loading_hint.await?;
+                for chunk in loading_hint.chunks.await?.iter() {
+                    let chunk_path = &*chunk.ident().path().await?;
+                    let Some(relative_path) = context_path.get_path_to(chunk_path) else {
+                        continue;
+                    };
+                    let path = format!("/{relative_path}");
+                    // Preloading takes precedence, the chunk is fetched right away anyway.
+                    if loading_hint.hints.preload {
+                        preload_paths.push(path);
+                    } else if loading_hint.hints.prefetch {
+                        prefetch_paths.push(path);
+                    }
+                }
+            }
+        }
+
+        Ok(DevHtmlAssetContent::new(
+            chunk_paths,
+            prefetch_paths,
+            preload_paths,
+            this.body.clone(),
+        ))
+    }
+
+    /// The chunking context and the entries of the chunk group of each entry,
+    /// which are the same as the ones [DevHtmlAsset::chunks] uses.
+    #[turbo_tasks::function]
+    async fn chunk_group_entries(self: Vc<Self>) -> Result<Vc<ChunkGroupEntries>> {
+        let this = self.await?;
+
+        let chunk_group_entries = this
+            .entries
+            .iter()
+            .map(|entry| async move {
+                let &(chunkable_module, chunking_context, runtime_entries) = entry;
+
+                let entries: Vec<Vc<Box<dyn Module>>> =
+                    if let Some(runtime_entries) = runtime_entries {
+                        let runtime_entries = if let Some(evaluatable) =
+                            Vc::try_resolve_downcast(chunkable_module).await?
+                        {
+                            runtime_entries.with_entry(evaluatable)
+                        } else {
+                            runtime_entries
+                        };
+                        runtime_entries
+                            .await?
+                            .iter()
+                            .map(|&evaluatable| Vc::upcast(evaluatable))
+                            .collect()
+                    } else {
+                        vec![Vc::upcast(chunkable_module)]
+                    };
+
+                Ok((chunking_context, Vc::cell(entries)))
+            })
+            .try_join()
+            .await?;
+
+        Ok(Vc::cell(chunk_group_entries))
     }
 
     #[turbo_tasks::function]
@@ -159,15 +234,42 @@ impl DevHtmlAsset {
     }
 }
 
+#[turbo_tasks::value(transparent)]
+struct ChunkGroupEntries(Vec<(Vc<Box<dyn ChunkingContext>>, Vc<Modules>)>);
+
 #[turbo_tasks::value]
 struct DevHtmlAssetContent {
     chunk_paths: Vec<String>,
+    prefetch_paths: Vec<String>,
+    preload_paths: Vec<String>,
     body: Option<String>,
 }
 
 impl DevHtmlAssetContent {
-    fn new(chunk_paths: Vec<String>, body: Option<String>) -> Vc<Self> {
-        DevHtmlAssetContent { chunk_paths, body }.cell()
+    fn new(
+        chunk_paths: Vec<String>,
+        prefetch_paths: Vec<String>,
+        preload_paths: Vec<String>,
+        body: Option<String>,
+    ) -> Vc<Self> {
+        DevHtmlAssetContent {
+            chunk_paths,
+            prefetch_paths,
+            preload_paths,
+            body,
+        }
+        .cell()
+    }
+}
+
+/// The value of the `as` attribute of a `<link rel="preload">` for a chunk.
+fn preload_as(relative_path: &str) -> Option<&'static str> {
+    if relative_path.ends_with(".js") {
+        Some("script")
+    } else if relative_path.ends_with(".css") {
+        Some("style")
+    } else {
+        None
     }
 }
 
@@ -179,6 +281,22 @@ impl DevHtmlAssetContent {
 
         let mut scripts = Vec::new();
         let mut stylesheets = Vec::new();
+        let mut links = Vec::new();
+
+        for relative_path in &*this.preload_paths {
+            if let Some(as_type) = preload_as(relative_path) {
+                links.push(format!(
+                    "<link rel=\"preload\" href=\"{}\" as=\"{}\">",
+                    relative_path, as_type
+                ));
+            }
+        }
+        for relative_path in &*this.prefetch_paths {
+            links.push(format!(
+                "<link rel=\"prefetch\" href=\"{}\">",
+                relative_path
+            ));
+        }
 
         for relative_path in &*this.chunk_paths {
             if relative_path.ends_with(".js") {
@@ -198,9 +316,14 @@ impl DevHtmlAssetContent {
             None => "",
         };
 
+        let head = stylesheets
+            .into_iter()
+            .chain(links)
+            .collect::<Vec<_>>()
+            .join("\n");
         let html = format!(
             "<!DOCTYPE html>\n<html>\n<head>\n{}\n</head>\n<body>\n{}\n{}\n</body>\n</html>",
-            stylesheets.join("\n"),
+            head,
             body,
             scripts.join("\n"),
         );
@@ -243,6 +366,8 @@ impl Version for DevHtmlAssetVersion {
         for relative_path in &*self.content.chunk_paths {
             hasher.write_ref(relative_path);
         }
+        hasher.write_ref(&self.content.prefetch_paths);
+        hasher.write_ref(&self.content.preload_paths);
         if let Some(body) = &self.content.body {
             hasher.write_ref(body);
         }
@@ -8,8 +8,10 @@ use hyper::{
     http::HeaderValue,
     Request, Response,
 };
+use indexmap::IndexMap;
 use mime::Mime;
 use mime_guess::mime;
+use parking_lot::Mutex;
 use tokio_util::io::{ReaderStream, StreamReader};
 use turbo_tasks::{util::SharedError, CollectiblesSource, ReadRef, TransientInstance, Vc};
 use turbo_tasks_bytes::Bytes;
@@ -26,6 +28,69 @@ use crate::source::{
     Body, ContentSource, ContentSourceSideEffect, HeaderList, ProxyResult,
 };
 
+/// The header which is set on responses that were served from the
+/// [StaleContentCache] while a rebuild was still in flight. The HMR client
+/// will receive the changes as an update once the rebuild has completed.
+pub const STALE_HEADER: &str = "x-turbopack-stale";
+
+/// The maximum number of request uris the [StaleContentCache] keeps content
+/// for. Uris with query strings are unbounded, so the least recently served
+/// ones are evicted.
+const STALE_CONTENT_CACHE_MAX_ENTRIES: usize = 1000;
+
+/// Keeps the last static content served for each request uri, so it can be
+/// served immediately while a rebuild for the same uri is in flight.
+#[derive(Default)]
+pub struct StaleContentCache {
+    /// Maps request uris to their path and the last content served for them,
+    /// ordered from the least to the most recently served.
+    entries: Mutex<IndexMap<String, (String, ReadRef<GetFromSourceResult>)>>,
+}
+
+impl StaleContentCache {
+    /// Returns the cache key for a request, or `None` if responses to the
+    /// request must not be served stale.
+    pub fn key(request: &Request<hyper::Body>) -> Option<String> {
+        // Only GET requests are idempotent, everything else has to wait for the
+        // rebuild.
+        (request.method() == hyper::Method::GET).then(|| request.uri().to_string())
+    }
+
+    /// Returns whether stale content is available for the given key.
+    pub fn contains(&self, key: &str) -> bool {
+        self.entries.lock().contains_key(key)
+    }
+
+    /// Builds a response from the last content served for the given key, if
+    /// any. The response is tagged with the [STALE_HEADER].
+    pub fn response(&self, key: &str) -> Result<Option<Response<hyper::Body>>> {
+        let Some((path, result)) = self.entries.lock().get(key).cloned() else {
+            return Ok(None);
+        };
+        let mut response = static_response(&result, &path)?;
+        if let Some(response) = &mut response {
+            response
+                .headers_mut()
+                .insert(STALE_HEADER, HeaderValue::from_static("1"));
+        }
+        Ok(response)
+    }
+
+    fn insert(&self, key: String, path: String, result: ReadRef<GetFromSourceResult>) {
+        let mut entries = self.entries.lock();
+        // Re-inserting moves the entry to the end, so it's evicted last.
+        entries.shift_remove(&key);
+        entries.insert(key, (path, result));
+        if entries.len() > STALE_CONTENT_CACHE_MAX_ENTRIES {
+            entries.shift_remove_index(0);
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        self.entries.lock().shift_remove(key);
+    }
+}
+
 #[turbo_tasks::value(serialization = "none")]
 enum GetFromSourceResult {
     Static {
@@ -73,11 +138,13 @@ pub async fn process_request_with_content_source(
     source: Vc<Box<dyn ContentSource>>,
     request: Request<hyper::Body>,
     issue_reporter: Vc<Box<dyn IssueReporter>>,
+    stale_content_cache: Option<&StaleContentCache>,
 ) -> Result<(
     Response<hyper::Body>,
     AutoSet<Vc<Box<dyn ContentSourceSideEffect>>>,
 )> {
     let original_path = request.uri().path().to_string();
+    let cache_key = StaleContentCache::key(&request);
     let request = http_request_to_source_request(request).await?;
     let result = get_from_source(source, TransientInstance::new(request));
     let resolved_result = result.resolve_strongly_consistent().await?;
@@ -90,134 +157,153 @@ pub async fn process_request_with_content_source(
         Some("get_from_source"),
     )
     .await?;
-    match &*resolved_result.await? {
-        GetFromSourceResult::Static {
-            content,
-            status_code,
-            headers,
-            header_overwrites,
-        } => {
-            if let FileContent::Content(file) = &**content {
-                let mut response = Response::builder().status(*status_code);
-
-                let header_map = response.headers_mut().expect("headers must be defined");
-
-                for (header_name, header_value) in headers {
-                    header_map.append(
-                        HeaderName::try_from(header_name.clone())?,
-                        hyper::header::HeaderValue::try_from(header_value.as_str())?,
-                    );
-                }
+    let resolved_result = resolved_result.await?;
+    if let (Some(cache), Some(key)) = (stale_content_cache, cache_key) {
+        match &*resolved_result {
+            GetFromSourceResult::Static { status_code, .. } if *status_code < 400 => {
+                cache.insert(key, original_path.clone(), resolved_result.clone());
+            }
+            _ => cache.remove(&key),
+        }
+    }
+    if let Some(response) = static_response(&resolved_result, &original_path)? {
+        return Ok((response, side_effects));
+    }
+    if let GetFromSourceResult::HttpProxy(proxy_result) = &*resolved_result {
+        let mut response = Response::builder().status(proxy_result.status);
+        let headers = response.headers_mut().expect("headers must be defined");
 
-                for (header_name, header_value) in header_overwrites.iter() {
-                    header_map.insert(
-                        HeaderName::try_from(header_name.clone())?,
-                        hyper::header::HeaderValue::try_from(header_value)?,
-                    );
-                }
+        for (name, value) in &proxy_result.headers {
+            headers.append(
+                HeaderName::from_bytes(name.as_bytes())?,
+                hyper::header::HeaderValue::from_str(value)?,
+            );
+        }
 
-                // naively checking if content is `compressible`.
-                let mut should_compress = false;
-                let should_compress_predicate = |mime: &Mime| {
-                    matches!(
-                        (mime.type_(), mime.subtype(), mime.suffix()),
-                        (_, mime::PLAIN, _)
-                            | (_, mime::JSON, _)
-                            | (mime::TEXT, _, _)
-                            | (mime::APPLICATION, mime::XML, _)
-                            | (mime::APPLICATION, mime::JAVASCRIPT, _)
-                            | (_, _, Some(mime::XML))
-                            | (_, _, Some(mime::JSON))
-                            | (_, _, Some(mime::TEXT))
-                    )
-                };
-
-                if let Some(content_type) = file.content_type() {
-                    header_map.append(
-                        "content-type",
-                        hyper::header::HeaderValue::try_from(content_type.to_string())?,
-                    );
-
-                    should_compress = should_compress_predicate(content_type);
-                } else if let hyper::header::Entry::Vacant(entry) = header_map.entry("content-type")
-                {
-                    let guess = mime_guess::from_path(&original_path).first_or_octet_stream();
-                    should_compress = should_compress_predicate(&guess);
-                    // If a text type, application/javascript, or application/json was
-                    // guessed, use a utf-8 charset as  we most likely generated it as
-                    // such.
-                    entry.insert(hyper::header::HeaderValue::try_from(
-                        if (guess.type_() == mime::TEXT
-                            || guess.subtype() == mime::JAVASCRIPT
-                            || guess.subtype() == mime::JSON)
-                            && guess.get_param("charset").is_none()
-                        {
-                            guess.to_string() + "; charset=utf-8"
-                        } else {
-                            guess.to_string()
-                        },
-                    )?);
-                }
+        return Ok((
+            response.body(hyper::Body::wrap_stream(proxy_result.body.read()))?,
+            side_effects,
+        ));
+    }
 
-                if !header_map.contains_key("cache-control") {
-                    // The dev server contents might change at any time, we can't cache them.
-                    header_map.append(
-                        "cache-control",
-                        hyper::header::HeaderValue::try_from("must-revalidate")?,
-                    );
-                }
+    Ok((
+        Response::builder().status(404).body(hyper::Body::empty())?,
+        side_effects,
+    ))
+}
 
-                let content = file.content();
-                let response = if should_compress {
-                    header_map.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+/// Builds the response for static content, or returns `None` if the result
+/// is not static file content.
+fn static_response(
+    result: &GetFromSourceResult,
+    original_path: &str,
+) -> Result<Option<Response<hyper::Body>>> {
+    let GetFromSourceResult::Static {
+        content,
+        status_code,
+        headers,
+        header_overwrites,
+    } = result
+    else {
+        return Ok(None);
+    };
+    if let FileContent::Content(file) = &**content {
+        let mut response = Response::builder().status(*status_code);
 
-                    // Grab ropereader stream, coerce anyhow::Error to std::io::Error
-                    let stream_ext = content
-                        .read()
-                        .into_stream()
-                        .map_err(|err| Error::new(ErrorKind::Other, err));
+        let header_map = response.headers_mut().expect("headers must be defined");
 
-                    let gzipped_stream =
-                        ReaderStream::new(async_compression::tokio::bufread::GzipEncoder::new(
-                            StreamReader::new(stream_ext),
-                        ));
+        for (header_name, header_value) in headers {
+            header_map.append(
+                HeaderName::try_from(header_name.clone())?,
+                hyper::header::HeaderValue::try_from(header_value.as_str())?,
+            );
+        }
 
-                    response.body(hyper::Body::wrap_stream(gzipped_stream))?
-                } else {
-                    header_map.insert(
-                        CONTENT_LENGTH,
-                        hyper::header::HeaderValue::try_from(content.len().to_string())?,
-                    );
+        for (header_name, header_value) in header_overwrites.iter() {
+            header_map.insert(
+                HeaderName::try_from(header_name.clone())?,
+                hyper::header::HeaderValue::try_from(header_value)?,
+            );
+        }
 
-                    response.body(hyper::Body::wrap_stream(content.read()))?
-                };
+        // naively checking if content is `compressible`.
+        let mut should_compress = false;
+        let should_compress_predicate = |mime: &Mime| {
+            matches!(
+                (mime.type_(), mime.subtype(), mime.suffix()),
+                (_, mime::PLAIN, _)
+                    | (_, mime::JSON, _)
+                    | (mime::TEXT, _, _)
+                    | (mime::APPLICATION, mime::XML, _)
+                    | (mime::APPLICATION, mime::JAVASCRIPT, _)
+                    | (_, _, Some(mime::XML))
+                    | (_, _, Some(mime::JSON))
+                    | (_, _, Some(mime::TEXT))
+            )
+        };
 
-                return Ok((response, side_effects));
-            }
+        if let Some(content_type) = file.content_type() {
+            header_map.append(
+                "content-type",
+                hyper::header::HeaderValue::try_from(content_type.to_string())?,
+            );
+
+            should_compress = should_compress_predicate(content_type);
+        } else if let hyper::header::Entry::Vacant(entry) = header_map.entry("content-type") {
+            let guess = mime_guess::from_path(original_path).first_or_octet_stream();
+            should_compress = should_compress_predicate(&guess);
+            // If a text type, application/javascript, or application/json was
+            // guessed, use a utf-8 charset as  we most likely generated it as
+            // such.
+            entry.insert(hyper::header::HeaderValue::try_from(
+                if (guess.type_() == mime::TEXT
+                    || guess.subtype() == mime::JAVASCRIPT
+                    || guess.subtype() == mime::JSON)
+                    && guess.get_param("charset").is_none()
+                {
+                    guess.to_string() + "; charset=utf-8"
+                } else {
+                    guess.to_string()
+                },
+            )?);
         }
-        GetFromSourceResult::HttpProxy(proxy_result) => {
-            let mut response = Response::builder().status(proxy_result.status);
-            let headers = response.headers_mut().expect("headers must be defined");
-
-            for (name, value) in &proxy_result.headers {
-                headers.append(
-                    HeaderName::from_bytes(name.as_bytes())?,
-                    hyper::header::HeaderValue::from_str(value)?,
-                );
-            }
 
-            return Ok((
-                response.body(hyper::Body::wrap_stream(proxy_result.body.read()))?,
-                side_effects,
-            ));
+        if !header_map.contains_key("cache-control") {
+            // The dev server contents might change at any time, we can't cache them.
+            header_map.append(
+                "cache-control",
+                hyper::header::HeaderValue::try_from("must-revalidate")?,
+            );
         }
-        GetFromSourceResult::NotFound => {}
+
+        let content = file.content();
+        let response = if should_compress {
+            header_map.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+
+            // Grab ropereader stream, coerce anyhow::Error to std::io::Error
+            let stream_ext = content
+                .read()
+                .into_stream()
+                .map_err(|err| Error::new(ErrorKind::Other, err));
+
+            let gzipped_stream = ReaderStream::new(
+                async_compression::tokio::bufread::GzipEncoder::new(StreamReader::new(stream_ext)),
+            );
+
+            response.body(hyper::Body::wrap_stream(gzipped_stream))?
+        } else {
+            header_map.insert(
+                CONTENT_LENGTH,
+                hyper::header::HeaderValue::try_from(content.len().to_string())?,
+            );
+
+            response.body(hyper::Body::wrap_stream(content.read()))?
+        };
+
+        return Ok(Some(response));
     }
 
-    Ok((
-        Response::builder().status(404).body(hyper::Body::empty())?,
-        side_effects,
-    ))
+    Ok(None)
 }
 
 async fn http_request_to_source_request(request: Request<hyper::Body>) -> Result<SourceRequest> {
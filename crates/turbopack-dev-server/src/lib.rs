@@ -40,8 +40,15 @@ use turbopack_core::{
     issue::{handle_issues, IssueReporter, IssueSeverity},
 };
 
-use self::{source::ContentSource, update::UpdateServer};
+use self::{
+    source::ContentSource,
+    update::{
+        encoding::{UpdateEncoding, UpdatePayloadMetrics},
+        UpdateServer,
+    },
+};
 use crate::{
+    http::StaleContentCache,
     invalidation::{ServerRequest, ServerRequestSideEffects},
     source::ContentSourceSideEffect,
 };
@@ -66,6 +73,8 @@ pub struct DevServerBuilder {
     pub addr: SocketAddr,
     #[turbo_tasks(trace_ignore)]
     server: Builder<AddrIncoming>,
+    #[turbo_tasks(trace_ignore)]
+    stale_while_revalidate: Option<Duration>,
 }
 
 #[derive(TraceRawVcs)]
@@ -74,6 +83,9 @@ pub struct DevServer {
     pub addr: SocketAddr,
     #[turbo_tasks(trace_ignore)]
     pub future: Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>>,
+    /// Sizes of the HMR update messages sent to clients.
+    #[turbo_tasks(trace_ignore)]
+    pub update_metrics: Arc<UpdatePayloadMetrics>,
 }
 
 impl DevServer {
@@ -107,11 +119,27 @@ impl DevServer {
             .local_addr()
             .context("not able to get bound address")?;
         let server = Server::from_tcp(listener).context("Not able to start server")?;
-        Ok(DevServerBuilder { addr, server })
+        Ok(DevServerBuilder {
+            addr,
+            server,
+            stale_while_revalidate: None,
+        })
     }
 }
 
 impl DevServerBuilder {
+    /// Serves the last compiled content for a path when a request takes longer
+    /// than `grace_period`, e.g. because a rebuild is in flight, instead of
+    /// blocking the request until the rebuild has completed.
+    ///
+    /// Such responses are tagged with an `x-turbopack-stale` header. The
+    /// rebuild continues in the background and the HMR client receives its
+    /// changes as an update.
+    pub fn stale_while_revalidate(mut self, grace_period: Duration) -> DevServerBuilder {
+        self.stale_while_revalidate = Some(grace_period);
+        self
+    }
+
     pub fn serve(
         self,
         turbo_tasks: Arc<dyn TurboTasksApi>,
@@ -121,8 +149,15 @@ impl DevServerBuilder {
         let ongoing_side_effects = Arc::new(Mutex::new(VecDeque::<
             Arc<tokio::sync::Mutex<Option<JoinHandle<Result<()>>>>>,
         >::with_capacity(16)));
+        let stale_while_revalidate = self
+            .stale_while_revalidate
+            .map(|grace_period| (grace_period, Arc::new(StaleContentCache::default())));
+        let update_metrics = Arc::new(UpdatePayloadMetrics::default());
+        let server_update_metrics = update_metrics.clone();
         let make_svc = make_service_fn(move |_| {
             let tt = turbo_tasks.clone();
+            let update_metrics = server_update_metrics.clone();
+            let stale_while_revalidate = stale_while_revalidate.clone();
             let source_provider = source_provider.clone();
             let get_issue_reporter = get_issue_reporter.clone();
             let ongoing_side_effects = ongoing_side_effects.clone();
@@ -131,9 +166,22 @@ impl DevServerBuilder {
                     let request_span = info_span!(parent: None, "request", name = ?request.uri());
                     let start = Instant::now();
                     let tt = tt.clone();
+                    let update_metrics = update_metrics.clone();
                     let get_issue_reporter = get_issue_reporter.clone();
                     let ongoing_side_effects = ongoing_side_effects.clone();
                     let source_provider = source_provider.clone();
+                    let stale_content_cache = stale_while_revalidate
+                        .as_ref()
+                        .map(|(_, cache)| cache.clone());
+                    // Only requests for which content has been served before can be
+                    // served stale.
+                    let stale_while_revalidate =
+                        stale_while_revalidate
+                            .clone()
+                            .and_then(|(grace_period, cache)| {
+                                let key = StaleContentCache::key(&request)?;
+                                cache.contains(&key).then_some((grace_period, cache, key))
+                            });
                     let future = async move {
                         event!(parent: Span::current(), Level::DEBUG, "request start");
                         // Wait until all ongoing side effects are completed
@@ -179,10 +227,15 @@ impl DevServerBuilder {
                                 let path = uri.path();
 
                                 if path == "/turbopack-hmr" {
+                                    let encoding = UpdateEncoding::from_query(uri.query());
                                     let (response, websocket) =
                                         hyper_tungstenite::upgrade(request, None)?;
-                                    let update_server =
-                                        UpdateServer::new(source_provider, issue_reporter);
+                                    let update_server = UpdateServer::new(
+                                        source_provider,
+                                        issue_reporter,
+                                        encoding,
+                                        update_metrics,
+                                    );
                                     update_server.run(&*tt, websocket);
                                     return Ok(response);
                                 }
@@ -224,6 +277,7 @@ impl DevServerBuilder {
                                     resolved_source,
                                     request,
                                     issue_reporter,
+                                    stale_content_cache.as_deref(),
                                 )
                                 .await?;
                             let status = response.status().as_u16();
@@ -259,7 +313,29 @@ impl DevServerBuilder {
                         .await
                     };
                     async move {
-                        match future.await {
+                        let result = match stale_while_revalidate {
+                            Some((grace_period, cache, key)) => {
+                                // The request keeps running in the background when the grace
+                                // period elapses, so the cache is refreshed once the rebuild
+                                // has completed.
+                                let mut join_handle = tokio::spawn(future.in_current_span());
+                                match tokio::time::timeout(grace_period, &mut join_handle).await {
+                                    Ok(result) => {
+                                        result.map_err(anyhow::Error::from).and_then(|r| r)
+                                    }
+                                    Err(_) => match cache.response(&key) {
+                                        Ok(Some(response)) => Ok(response),
+                                        Ok(None) => join_handle
+                                            .await
+                                            .map_err(anyhow::Error::from)
+                                            .and_then(|r| r),
+                                        Err(err) => Err(err),
+                                    },
+                                }
+                            }
+                            None => future.await,
+                        };
+                        match result {
                             Ok(r) => Ok::<_, hyper::http::Error>(r),
                             Err(e) => {
                                 println!(
@@ -286,6 +362,7 @@ impl DevServerBuilder {
                 server.await?;
                 Ok(())
             }),
+            update_metrics,
         }
     }
 }
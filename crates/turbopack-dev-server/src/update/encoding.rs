@@ -0,0 +1,103 @@
+use std::{
+    io::Write,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use anyhow::Result;
+use flate2::{write::GzEncoder, Compression};
+use hyper_tungstenite::tungstenite::Message;
+use serde::Serialize;
+use tracing::{event, Level};
+
+/// How update messages are encoded on the websocket. Clients select an
+/// encoding with the `encoding` query parameter of the HMR endpoint, e.g.
+/// `/turbopack-hmr?encoding=gzip`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateEncoding {
+    /// Every message is sent as a JSON text message.
+    #[default]
+    Json,
+    /// Every message is sent as a binary message containing gzip compressed
+    /// JSON.
+    Gzip,
+}
+
+impl UpdateEncoding {
+    /// Reads the encoding from the query string of the HMR endpoint. Unknown
+    /// encodings fall back to [UpdateEncoding::Json].
+    pub fn from_query(query: Option<&str>) -> Self {
+        let gzip = query
+            .into_iter()
+            .flat_map(|query| query.split('&'))
+            .any(|param| param == "encoding=gzip");
+        if gzip {
+            UpdateEncoding::Gzip
+        } else {
+            UpdateEncoding::Json
+        }
+    }
+
+    /// Serializes and encodes a message, and records its size in `metrics`.
+    pub(super) fn encode(
+        self,
+        item: &impl Serialize,
+        metrics: &UpdatePayloadMetrics,
+    ) -> Result<Message> {
+        let json = serde_json::to_string(item)?;
+        let json_size = json.len();
+        let message = match self {
+            UpdateEncoding::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+                encoder.write_all(json.as_bytes())?;
+                Message::binary(encoder.finish()?)
+            }
+            UpdateEncoding::Json => Message::text(json),
+        };
+        let sent_size = message.len();
+        metrics.record(json_size, sent_size);
+        event!(Level::DEBUG, json_size, sent_size, "hmr update message");
+        Ok(message)
+    }
+}
+
+/// Sizes of the update messages sent by the dev server, summed over all
+/// clients since the server started.
+#[derive(Debug, Default)]
+pub struct UpdatePayloadMetrics {
+    messages: AtomicU64,
+    json_bytes: AtomicU64,
+    sent_bytes: AtomicU64,
+    largest_message: AtomicU64,
+}
+
+impl UpdatePayloadMetrics {
+    fn record(&self, json_size: usize, sent_size: usize) {
+        self.messages.fetch_add(1, Ordering::Relaxed);
+        self.json_bytes
+            .fetch_add(json_size as u64, Ordering::Relaxed);
+        self.sent_bytes
+            .fetch_add(sent_size as u64, Ordering::Relaxed);
+        self.largest_message
+            .fetch_max(sent_size as u64, Ordering::Relaxed);
+    }
+
+    /// The number of messages sent.
+    pub fn messages(&self) -> u64 {
+        self.messages.load(Ordering::Relaxed)
+    }
+
+    /// The size of all messages as JSON, i.e. before encoding.
+    pub fn json_bytes(&self) -> u64 {
+        self.json_bytes.load(Ordering::Relaxed)
+    }
+
+    /// The size of all messages as sent over the websocket.
+    pub fn sent_bytes(&self) -> u64 {
+        self.sent_bytes.load(Ordering::Relaxed)
+    }
+
+    /// The size of the largest message as sent over the websocket.
+    pub fn largest_message(&self) -> u64 {
+        self.largest_message.load(Ordering::Relaxed)
+    }
+}
@@ -1,4 +1,6 @@
+pub mod encoding;
 pub mod server;
 pub mod stream;
+pub mod watch;
 
 pub(super) use server::UpdateServer;
@@ -1,5 +1,6 @@
 use std::{
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 
@@ -13,12 +14,19 @@ use tokio_stream::StreamMap;
 use tracing::{instrument, Level};
 use turbo_tasks::{TransientInstance, TurboTasksApi, Vc};
 use turbo_tasks_fs::json::parse_json_with_source_context;
-use turbopack_core::{error::PrettyPrintError, issue::IssueReporter, version::Update};
+use turbopack_core::{
+    error::PrettyPrintError,
+    issue::{IssuePolicy, IssueReporter},
+    version::Update,
+};
 use turbopack_ecmascript_hmr_protocol::{
     ClientMessage, ClientUpdateInstruction, Issue, ResourceIdentifier,
 };
 
-use super::stream::UpdateStream;
+use super::{
+    encoding::{UpdateEncoding, UpdatePayloadMetrics},
+    stream::UpdateStream,
+};
 use crate::{
     source::{request::SourceRequest, resolve::resolve_source_request, Body},
     update::stream::UpdateStreamItem,
@@ -28,16 +36,24 @@ use crate::{
 /// A server that listens for updates and sends them to connected clients.
 pub(crate) struct UpdateServer<P: SourceProvider> {
     source_provider: P,
-    #[allow(dead_code)]
     issue_reporter: Vc<Box<dyn IssueReporter>>,
+    encoding: UpdateEncoding,
+    metrics: Arc<UpdatePayloadMetrics>,
 }
 
 impl<P: SourceProvider + Clone + Send + Sync> UpdateServer<P> {
     /// Create a new update server with the given websocket and content source.
-    pub fn new(source_provider: P, issue_reporter: Vc<Box<dyn IssueReporter>>) -> Self {
+    pub fn new(
+        source_provider: P,
+        issue_reporter: Vc<Box<dyn IssueReporter>>,
+        encoding: UpdateEncoding,
+        metrics: Arc<UpdatePayloadMetrics>,
+    ) -> Self {
         Self {
             source_provider,
             issue_reporter,
+            encoding,
+            metrics,
         }
     }
 
@@ -53,7 +69,7 @@ impl<P: SourceProvider + Clone + Send + Sync> UpdateServer<P> {
 
     #[instrument(level = Level::TRACE, skip_all, name = "UpdateServer::run_internal")]
     async fn run_internal(self, ws: HyperWebsocket) -> Result<()> {
-        let mut client: UpdateClient = ws.await?.into();
+        let mut client = UpdateClient::new(ws.await?, self.encoding, self.metrics.clone());
 
         let mut streams = StreamMap::new();
 
@@ -98,7 +114,8 @@ impl<P: SourceProvider + Clone + Send + Sync> UpdateServer<P> {
                 Some((resource, update)) = streams.next() => {
                     match update {
                         Ok(update) => {
-                            Self::send_update(&mut client, &mut streams, resource, &update).await?;
+                            let issue_policy = self.issue_reporter.issue_policy().await?;
+                            Self::send_update(&mut client, &mut streams, resource, &update, &issue_policy).await?;
                         }
                         Err(err) => {
                             eprintln!("Failed to get update for {resource}: {}", PrettyPrintError(&err));
@@ -117,6 +134,7 @@ impl<P: SourceProvider + Clone + Send + Sync> UpdateServer<P> {
         streams: &mut StreamMap<ResourceIdentifier, UpdateStream>,
         resource: ResourceIdentifier,
         item: &UpdateStreamItem,
+        issue_policy: &IssuePolicy,
     ) -> Result<()> {
         match item {
             UpdateStreamItem::NotFound => {
@@ -130,8 +148,9 @@ impl<P: SourceProvider + Clone + Send + Sync> UpdateServer<P> {
             UpdateStreamItem::Found { update, issues } => {
                 let issues = issues
                     .iter()
-                    .map(|p| (&**p).into())
-                    .collect::<Vec<Issue<'_>>>();
+                    .map(|issue| issue_policy.apply(issue))
+                    .collect::<Vec<_>>();
+                let issues = issues.iter().map(|p| p.into()).collect::<Vec<Issue<'_>>>();
                 match &**update {
                     Update::Partial(partial) => {
                         let partial_instruction = &partial.instruction;
@@ -186,6 +205,23 @@ pin_project! {
         #[pin]
         ws: WebSocketStream<Upgraded>,
         ended: bool,
+        encoding: UpdateEncoding,
+        metrics: Arc<UpdatePayloadMetrics>,
+    }
+}
+
+impl UpdateClient {
+    fn new(
+        ws: WebSocketStream<Upgraded>,
+        encoding: UpdateEncoding,
+        metrics: Arc<UpdatePayloadMetrics>,
+    ) -> Self {
+        Self {
+            ws,
+            ended: false,
+            encoding,
+            metrics,
+        }
     }
 }
 
@@ -248,12 +284,10 @@ impl<'a> Sink<ClientUpdateInstruction<'a>> for UpdateClient {
         self: Pin<&mut Self>,
         item: ClientUpdateInstruction<'a>,
     ) -> std::result::Result<(), Self::Error> {
-        let msg = Message::text(serde_json::to_string(&item)?);
+        let this = self.project();
+        let msg = this.encoding.encode(&item, this.metrics)?;
 
-        self.project()
-            .ws
-            .start_send(msg)
-            .context("sending to WebSocket")
+        this.ws.start_send(msg).context("sending to WebSocket")
     }
 
     fn poll_flush(
@@ -276,9 +310,3 @@ impl<'a> Sink<ClientUpdateInstruction<'a>> for UpdateClient {
             .map(|res| res.context("closing WebSocket"))
     }
 }
-
-impl From<WebSocketStream<Upgraded>> for UpdateClient {
-    fn from(ws: WebSocketStream<Upgraded>) -> Self {
-        Self { ws, ended: false }
-    }
-}
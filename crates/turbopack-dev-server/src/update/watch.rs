@@ -0,0 +1,255 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    pin::Pin,
+};
+
+use anyhow::Result;
+use futures::{prelude::*, Stream};
+use indexmap::IndexMap;
+use tokio::sync::mpsc::Sender;
+use tokio_stream::wrappers::ReceiverStream;
+use turbo_tasks::{ReadRef, TransientInstance, TryJoinIterExt, Vc};
+use turbopack_core::{
+    asset::Asset,
+    issue::{IssueDescriptionExt, PlainIssue},
+    output::OutputAssets,
+    reference::all_assets_from_entries,
+    version::{Version, VersionedContent},
+};
+
+pub type GetWatchEntriesFn = Box<dyn Fn() -> Vc<WatchEntries> + Send + Sync>;
+
+/// The output assets of the entries observed by a [WatchSubscription], by
+/// entry name. Assets referenced by the output assets are observed as well.
+#[turbo_tasks::value(transparent)]
+pub struct WatchEntries(IndexMap<String, Vc<OutputAssets>>);
+
+/// Maps entry names to the version ids of all their output assets, by path.
+#[turbo_tasks::value(transparent)]
+struct EntryVersions(BTreeMap<String, BTreeMap<String, String>>);
+
+/// The state of all observed entries after a compilation.
+#[turbo_tasks::value(serialization = "none")]
+#[derive(Debug)]
+struct WatchSnapshot {
+    entries: ReadRef<EntryVersions>,
+    issues: Vec<ReadRef<PlainIssue>>,
+}
+
+#[turbo_tasks::function]
+async fn entry_versions(entries: Vc<WatchEntries>) -> Result<Vc<EntryVersions>> {
+    let versions = entries
+        .await?
+        .iter()
+        .map(|(name, &assets)| async move {
+            let versions = all_assets_from_entries(assets)
+                .await?
+                .iter()
+                .map(|asset| async move {
+                    let path = asset.ident().path().await?.path.clone();
+                    let version = asset.versioned_content().version().id().await?;
+                    Ok((path, version.clone_value()))
+                })
+                .try_join()
+                .await?;
+            Ok((name.clone(), versions.into_iter().collect()))
+        })
+        .try_join()
+        .await?;
+
+    Ok(Vc::cell(versions.into_iter().collect()))
+}
+
+#[turbo_tasks::function]
+async fn get_watch_snapshot(
+    get_entries: TransientInstance<GetWatchEntriesFn>,
+) -> Result<Vc<WatchSnapshot>> {
+    let versions = entry_versions(get_entries());
+    let _ = versions.resolve_strongly_consistent().await?;
+    let issues = versions
+        .peek_issues_with_path()
+        .await?
+        .get_plain_issues()
+        .await?;
+
+    Ok(WatchSnapshot {
+        entries: versions.await?,
+        issues,
+    }
+    .cell())
+}
+
+#[turbo_tasks::function]
+async fn compute_watch_snapshot(
+    get_entries: TransientInstance<GetWatchEntriesFn>,
+    sender: TransientInstance<Sender<Result<ReadRef<WatchSnapshot>>>>,
+) -> Result<Vc<()>> {
+    let snapshot = get_watch_snapshot(get_entries).strongly_consistent().await;
+
+    // Send snapshot. Ignore channel closed error.
+    let _ = sender.send(snapshot).await;
+
+    Ok(Default::default())
+}
+
+/// An output asset whose content changed in a compilation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedAsset {
+    pub path: String,
+    /// The version id before the compilation.
+    pub from_version: String,
+    /// The version id after the compilation. This is the id HMR clients are
+    /// updated to.
+    pub to_version: String,
+}
+
+/// The changes of the output assets in a compilation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AssetDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<ChangedAsset>,
+}
+
+impl AssetDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// An event emitted by a [WatchSubscription] for each compilation which
+/// changed the output assets or the issues.
+#[derive(Debug, Clone)]
+pub struct RebuildEvent {
+    /// The sequence number of the event. The initial compilation has id 0.
+    pub id: u64,
+    /// The names of the entries whose output assets changed.
+    pub affected_entries: Vec<String>,
+    pub assets: AssetDiff,
+    /// Issues which have not been reported by the previous event.
+    pub issues_added: Vec<ReadRef<PlainIssue>>,
+    /// Issues which were reported by the previous event, but are resolved now.
+    pub issues_resolved: Vec<ReadRef<PlainIssue>>,
+}
+
+impl RebuildEvent {
+    /// Computes the changes between two snapshots. Returns `None` when nothing
+    /// changed.
+    fn diff(id: u64, previous: Option<&WatchSnapshot>, current: &WatchSnapshot) -> Option<Self> {
+        let empty = BTreeMap::new();
+        let previous_entries = previous.map_or(&empty, |previous| &*previous.entries);
+        let previous_issues = previous.map_or(&[][..], |previous| &previous.issues[..]);
+
+        let mut affected_entries = Vec::new();
+        let mut previous_assets = BTreeMap::new();
+        let mut current_assets = BTreeMap::new();
+        let names = previous_entries
+            .keys()
+            .chain(current.entries.keys())
+            .collect::<BTreeSet<_>>();
+        for name in names {
+            let from = previous_entries.get(name);
+            let to = current.entries.get(name);
+            if from != to {
+                affected_entries.push(name.clone());
+            }
+            previous_assets.extend(from.into_iter().flatten());
+            current_assets.extend(to.into_iter().flatten());
+        }
+
+        let mut assets = AssetDiff::default();
+        for (&path, &to_version) in &current_assets {
+            match previous_assets.get(path) {
+                None => assets.added.push(path.clone()),
+                Some(&from_version) if from_version != to_version => {
+                    assets.changed.push(ChangedAsset {
+                        path: path.clone(),
+                        from_version: from_version.clone(),
+                        to_version: to_version.clone(),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        for &path in previous_assets.keys() {
+            if !current_assets.contains_key(path) {
+                assets.removed.push(path.clone());
+            }
+        }
+
+        let issues_added = current
+            .issues
+            .iter()
+            .filter(|issue| !previous_issues.contains(*issue))
+            .cloned()
+            .collect::<Vec<_>>();
+        let issues_resolved = previous_issues
+            .iter()
+            .filter(|issue| !current.issues.contains(*issue))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // The initial compilation is always reported.
+        if previous.is_some()
+            && affected_entries.is_empty()
+            && issues_added.is_empty()
+            && issues_resolved.is_empty()
+        {
+            return None;
+        }
+
+        Some(RebuildEvent {
+            id,
+            affected_entries,
+            assets,
+            issues_added,
+            issues_resolved,
+        })
+    }
+}
+
+/// A stream of [RebuildEvent]s for a set of entries. The entries are
+/// recomputed whenever their inputs change, so embedders can react to
+/// rebuilds without polling.
+///
+/// Must be created within a turbo-tasks context.
+pub struct WatchSubscription(Pin<Box<dyn Stream<Item = Result<RebuildEvent>> + Send + Sync>>);
+
+impl WatchSubscription {
+    pub fn new(get_entries: TransientInstance<GetWatchEntriesFn>) -> Self {
+        let (sx, rx) = tokio::sync::mpsc::channel(32);
+
+        let _ = compute_watch_snapshot(get_entries, TransientInstance::new(sx));
+
+        let mut previous: Option<ReadRef<WatchSnapshot>> = None;
+        let mut next_id = 0;
+
+        let stream = ReceiverStream::new(rx).filter_map(move |item| {
+            let event = match item {
+                Ok(snapshot) => {
+                    let event = RebuildEvent::diff(next_id, previous.as_deref(), &snapshot);
+                    previous = Some(snapshot);
+                    event.map(|event| {
+                        next_id += 1;
+                        Ok(event)
+                    })
+                }
+                Err(err) => Some(Err(err)),
+            };
+            future::ready(event)
+        });
+
+        WatchSubscription(Box::pin(stream))
+    }
+}
+
+impl Stream for WatchSubscription {
+    type Item = Result<RebuildEvent>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        Pin::new(&mut self.get_mut().0).poll_next(cx)
+    }
+}
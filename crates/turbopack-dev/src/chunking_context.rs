@@ -2,11 +2,17 @@ use anyhow::{bail, Context, Result};
 use turbo_tasks::{Value, Vc};
 use turbo_tasks_fs::FileSystemPath;
 use turbopack_core::{
+    asset::AssetContent,
     chunk::{
         availability_info::AvailabilityInfo,
-        chunk_group::{make_chunk_group, MakeChunkGroupResult},
+        chunk_group::{make_chunk_group, make_sync_chunk_group, MakeChunkGroupResult},
+        chunking::{ChunkingStrategy, ReferencedOutputAssetsPlacement},
+        module_id_strategy::{module_id_from_ident, ModuleIdStrategy},
+        preload::{url_origin, OriginHint, OriginHints},
+        split_point::SplitPoints,
         Chunk, ChunkItem, ChunkableModule, ChunkingContext, EvaluatableAssets, ModuleId,
     },
+    content_hash::{content_hash, ContentHashAlgorithm},
     environment::Environment,
     ident::AssetIdent,
     module::Module,
@@ -14,14 +20,19 @@ use turbopack_core::{
 };
 use turbopack_ecmascript::{
     chunk::{EcmascriptChunk, EcmascriptChunkingContext},
-    manifest::{chunk_asset::ManifestAsyncModule, loader_item::ManifestLoaderChunkItem},
+    manifest::{
+        chunk_asset::ManifestAsyncModule, inlined_loader_item::InlinedLoaderChunkItem,
+        lazy_loader_item::LazyLoaderChunkItem, loader_item::ManifestLoaderChunkItem,
+    },
 };
-use turbopack_ecmascript_runtime::RuntimeType;
+use turbopack_ecmascript_runtime::{ChunkLoadingAttributes, CrossOrigin, NonceSource, RuntimeType};
 
 use crate::ecmascript::{
     chunk::EcmascriptDevChunk,
-    evaluate::chunk::EcmascriptDevEvaluateChunk,
+    evaluate::{chunk::EcmascriptDevEvaluateChunk, runtime::EcmascriptDevRuntimeChunk},
+    lazy::chunk::EcmascriptDevLazyManifestChunk,
     list::asset::{EcmascriptDevChunkList, EcmascriptDevChunkListSource},
+    service_worker::chunk::EcmascriptDevServiceWorkerChunk,
 };
 
 pub struct DevChunkingContextBuilder {
@@ -44,6 +55,21 @@ impl DevChunkingContextBuilder {
         self
     }
 
+    pub fn deferred_public_path(mut self, deferred_public_path: bool) -> Self {
+        self.chunking_context.deferred_public_path = deferred_public_path;
+        self
+    }
+
+    pub fn cross_origin(mut self, cross_origin: CrossOrigin) -> Self {
+        self.chunking_context.cross_origin = Some(cross_origin);
+        self
+    }
+
+    pub fn nonce_source(mut self, nonce_source: NonceSource) -> Self {
+        self.chunking_context.nonce_source = Some(nonce_source);
+        self
+    }
+
     pub fn reference_chunk_source_maps(mut self, source_maps: bool) -> Self {
         self.chunking_context.reference_chunk_source_maps = source_maps;
         self
@@ -59,6 +85,65 @@ impl DevChunkingContextBuilder {
         self
     }
 
+    pub fn chunking_strategy(mut self, chunking_strategy: ChunkingStrategy) -> Self {
+        self.chunking_context.chunking_strategy = chunking_strategy;
+        self
+    }
+
+    pub fn referenced_output_assets_placement(
+        mut self,
+        placement: ReferencedOutputAssetsPlacement,
+    ) -> Self {
+        self.chunking_context.referenced_output_assets_placement = placement;
+        self
+    }
+
+    pub fn split_points(mut self, split_points: Vc<SplitPoints>) -> Self {
+        self.chunking_context.split_points = split_points;
+        self
+    }
+
+    pub fn inline_async_chunk_size(mut self, inline_async_chunk_size: usize) -> Self {
+        self.chunking_context.inline_async_chunk_size = inline_async_chunk_size;
+        self
+    }
+
+    pub fn startup_cost_budget(mut self, startup_cost_budget: usize) -> Self {
+        self.chunking_context.startup_cost_budget = startup_cost_budget;
+        self
+    }
+
+    pub fn lazy_compilation(mut self, lazy_compilation: bool) -> Self {
+        self.chunking_context.lazy_compilation = lazy_compilation;
+        self
+    }
+
+    /// Emits the runtime as a separate chunk which is shared by all evaluated
+    /// chunk groups, instead of including it in each of their evaluate
+    /// chunks. Changes to the runtime then don't invalidate the cached
+    /// evaluate chunks.
+    pub fn runtime_chunk(mut self, runtime_chunk: bool) -> Self {
+        self.chunking_context.runtime_chunk = runtime_chunk;
+        self
+    }
+
+    pub fn content_hash_algorithm(mut self, algorithm: ContentHashAlgorithm) -> Self {
+        self.chunking_context.content_hash_algorithm = algorithm;
+        self
+    }
+
+    pub fn content_hash_length(mut self, length: usize) -> Self {
+        self.chunking_context.content_hash_length = length;
+        self
+    }
+
+    /// How the ids of chunk items are derived from their modules, e.g.
+    /// [ModuleIdStrategy::Deterministic] for short ids in production.
+    pub fn module_id_strategy(mut self, module_id_strategy: ModuleIdStrategy) -> Self {
+        self.chunking_context.module_id_strategy = module_id_strategy;
+        self
+    }
+
     pub fn build(self) -> Vc<DevChunkingContext> {
         DevChunkingContext::new(Value::new(self.chunking_context))
     }
@@ -85,18 +170,49 @@ pub struct DevChunkingContext {
     reference_css_chunk_source_maps: bool,
     /// Static assets are placed at this path
     asset_root_path: Vc<FileSystemPath>,
+    /// The algorithm of the content hashes in static asset paths.
+    content_hash_algorithm: ContentHashAlgorithm,
+    /// The number of hex digits of content hashes in static asset paths.
+    content_hash_length: usize,
+    /// How the ids of chunk items are derived from their modules.
+    module_id_strategy: ModuleIdStrategy,
     /// Base path that will be prepended to all chunk URLs when loading them.
     /// This path will not appear in chunk paths or chunk data.
     chunk_base_path: Vc<Option<String>>,
     /// URL prefix that will be prepended to all static asset URLs when loading
     /// them.
     asset_base_path: Vc<Option<String>>,
+    /// Resolve chunk and asset URLs against `__turbopack_public_path__` at
+    /// runtime. `chunk_base_path` is used when the global isn't set, while
+    /// `asset_base_path` is ignored.
+    deferred_public_path: bool,
+    /// The `crossorigin` attribute of tags inserted to load chunks.
+    cross_origin: Option<CrossOrigin>,
+    /// Where the CSP nonce of tags inserted to load chunks is read from.
+    nonce_source: Option<NonceSource>,
     /// Enable HMR for this chunking
     enable_hot_module_replacement: bool,
     /// The environment chunks will be evaluated in.
     environment: Vc<Environment>,
     /// The kind of runtime to include in the output.
     runtime_type: RuntimeType,
+    /// How chunk items are split into chunks. Coarser strategies reduce the
+    /// work needed on every change.
+    chunking_strategy: ChunkingStrategy,
+    /// To which chunks output assets referenced by chunk items are attached.
+    referenced_output_assets_placement: ReferencedOutputAssetsPlacement,
+    /// Modules which are forced into separate chunks.
+    split_points: Vc<SplitPoints>,
+    /// Async chunk groups up to this size are inlined into their only parent.
+    inline_async_chunk_size: usize,
+    /// Modules in entry chunk groups with a higher estimated startup cost are
+    /// reported.
+    startup_cost_budget: usize,
+    /// Don't compile the chunk groups of dynamic imports until the runtime
+    /// requests them for the first time.
+    lazy_compilation: bool,
+    /// Whether the runtime is emitted as a separate chunk.
+    runtime_chunk: bool,
 }
 
 impl DevChunkingContext {
@@ -115,11 +231,24 @@ impl DevChunkingContext {
                 reference_chunk_source_maps: true,
                 reference_css_chunk_source_maps: true,
                 asset_root_path,
+                content_hash_algorithm: Default::default(),
+                content_hash_length: 8,
+                module_id_strategy: Default::default(),
                 chunk_base_path: Default::default(),
                 asset_base_path: Default::default(),
+                deferred_public_path: false,
+                cross_origin: None,
+                nonce_source: None,
                 enable_hot_module_replacement: false,
                 environment,
                 runtime_type: Default::default(),
+                chunking_strategy: Default::default(),
+                referenced_output_assets_placement: Default::default(),
+                split_points: SplitPoints::empty(),
+                inline_async_chunk_size: 0,
+                startup_cost_budget: 0,
+                lazy_compilation: false,
+                runtime_chunk: false,
             },
         }
     }
@@ -134,10 +263,30 @@ impl DevChunkingContext {
         self.runtime_type
     }
 
+    /// Returns whether the runtime is emitted as a separate chunk instead of
+    /// being included in evaluate chunks.
+    pub fn runtime_chunk(&self) -> bool {
+        self.runtime_chunk
+    }
+
     /// Returns the asset base path.
     pub fn chunk_base_path(&self) -> Vc<Option<String>> {
         self.chunk_base_path
     }
+
+    /// Returns whether the public path is read from a global at runtime.
+    pub fn deferred_public_path(&self) -> bool {
+        self.deferred_public_path
+    }
+
+    /// Returns the attributes of tags inserted to load chunks.
+    pub fn chunk_loading_attributes(&self) -> Vc<ChunkLoadingAttributes> {
+        ChunkLoadingAttributes {
+            cross_origin: self.cross_origin,
+            nonce_source: self.nonce_source.clone(),
+        }
+        .cell()
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -179,6 +328,46 @@ impl DevChunkingContext {
         ))
     }
 
+    #[turbo_tasks::function]
+    async fn chunk_group_with_name(
+        self: Vc<Self>,
+        module: Vc<Box<dyn ChunkableModule>>,
+        chunk_name: Option<String>,
+        availability_info: Value<AvailabilityInfo>,
+    ) -> Result<Vc<OutputAssets>> {
+        let mut ident = module.ident();
+        if let Some(chunk_name) = &chunk_name {
+            ident = ident.with_modifier(Vc::cell(chunk_name.clone()));
+        }
+
+        let MakeChunkGroupResult { chunks, .. } = make_chunk_group(
+            Vc::upcast(self),
+            [Vc::upcast(module)],
+            chunk_name,
+            availability_info.into_value(),
+        )
+        .await?;
+
+        let mut assets: Vec<Vc<Box<dyn OutputAsset>>> = chunks
+            .iter()
+            .map(|chunk| self.generate_chunk(*chunk))
+            .collect();
+
+        assets.push(self.generate_chunk_list_register_chunk(
+            ident,
+            EvaluatableAssets::empty(),
+            Vc::cell(assets.clone()),
+            Value::new(EcmascriptDevChunkListSource::Dynamic),
+        ));
+
+        // Resolve assets
+        for asset in assets.iter_mut() {
+            *asset = asset.resolve().await?;
+        }
+
+        Ok(Vc::cell(assets))
+    }
+
     #[turbo_tasks::function]
     async fn generate_chunk(
         self: Vc<Self>,
@@ -236,15 +425,21 @@ impl ChunkingContext for DevChunkingContext {
             .strip_prefix(&format!("{}/", this.output_root.await?.path))
             .context("expected output_root to contain asset path")?;
 
-        Ok(Vc::cell(format!(
-            "{}{}",
-            this.asset_base_path
+        if this.deferred_public_path {
+            return Ok(Vc::cell(asset_path.to_string()));
+        }
+
+        let asset_base_path = match &*this.asset_base_path.await? {
+            Some(asset_base_path) => asset_base_path.to_owned(),
+            None => this
+                .environment
+                .asset_prefix()
                 .await?
-                .as_ref()
-                .map(|s| s.as_str())
-                .unwrap_or("/"),
-            asset_path
-        )))
+                .clone()
+                .unwrap_or_else(|| "/".to_owned()),
+        };
+
+        Ok(Vc::cell(format!("{}{}", asset_base_path, asset_path)))
     }
 
     #[turbo_tasks::function]
@@ -283,6 +478,16 @@ impl ChunkingContext for DevChunkingContext {
         Ok(Vc::cell(false))
     }
 
+    #[turbo_tasks::function]
+    fn content_hash(&self, content: Vc<AssetContent>) -> Vc<String> {
+        content_hash(content, self.content_hash_algorithm)
+    }
+
+    #[turbo_tasks::function]
+    fn chunk_item_id_from_ident(&self, ident: Vc<AssetIdent>) -> Vc<ModuleId> {
+        module_id_from_ident(ident, self.module_id_strategy)
+    }
+
     #[turbo_tasks::function]
     async fn asset_path(
         &self,
@@ -291,16 +496,17 @@ impl ChunkingContext for DevChunkingContext {
     ) -> Result<Vc<FileSystemPath>> {
         let source_path = original_asset_ident.path().await?;
         let basename = source_path.file_name();
+        let content_hash = &content_hash[..self.content_hash_length.min(content_hash.len())];
+        // Without a hash, the asset keeps the name of its source
+        if content_hash.is_empty() {
+            return Ok(self.asset_root_path.join(basename.to_string()));
+        }
         let asset_path = match source_path.extension_ref() {
             Some(ext) => format!(
                 "{basename}.{content_hash}.{ext}",
                 basename = &basename[..basename.len() - ext.len() - 1],
-                content_hash = &content_hash[..8]
-            ),
-            None => format!(
-                "{basename}.{content_hash}",
-                content_hash = &content_hash[..8]
             ),
+            None => format!("{basename}.{content_hash}"),
         };
         Ok(self.asset_root_path.join(asset_path))
     }
@@ -311,36 +517,81 @@ impl ChunkingContext for DevChunkingContext {
     }
 
     #[turbo_tasks::function]
-    async fn chunk_group(
-        self: Vc<Self>,
-        module: Vc<Box<dyn ChunkableModule>>,
-        availability_info: Value<AvailabilityInfo>,
-    ) -> Result<Vc<OutputAssets>> {
-        let MakeChunkGroupResult { chunks } = make_chunk_group(
-            Vc::upcast(self),
-            [Vc::upcast(module)],
-            availability_info.into_value(),
-        )
-        .await?;
+    fn chunking_strategy(&self) -> Vc<ChunkingStrategy> {
+        self.chunking_strategy.cell()
+    }
 
-        let mut assets: Vec<Vc<Box<dyn OutputAsset>>> = chunks
-            .iter()
-            .map(|chunk| self.generate_chunk(*chunk))
-            .collect();
+    #[turbo_tasks::function]
+    fn referenced_output_assets_placement(&self) -> Vc<ReferencedOutputAssetsPlacement> {
+        self.referenced_output_assets_placement.cell()
+    }
 
-        assets.push(self.generate_chunk_list_register_chunk(
-            module.ident(),
-            EvaluatableAssets::empty(),
-            Vc::cell(assets.clone()),
-            Value::new(EcmascriptDevChunkListSource::Dynamic),
-        ));
+    #[turbo_tasks::function]
+    async fn origin_hints(&self) -> Result<Vc<OriginHints>> {
+        // The base paths are only fallbacks when the public path is deferred
+        if self.deferred_public_path {
+            return Ok(OriginHints::empty());
+        }
+        let mut hints = Vec::new();
+        if let Some(origin) = self.chunk_base_path.await?.as_deref().and_then(url_origin) {
+            hints.push(OriginHint {
+                origin,
+                crossorigin: self.cross_origin.is_some(),
+            });
+        }
+        let asset_base_path = match &*self.asset_base_path.await? {
+            Some(asset_base_path) => Some(asset_base_path.to_owned()),
+            None => self.environment.asset_prefix().await?.clone(),
+        };
+        if let Some(origin) = asset_base_path.as_deref().and_then(url_origin) {
+            let hint = OriginHint {
+                origin,
+                crossorigin: false,
+            };
+            if !hints.contains(&hint) {
+                hints.push(hint);
+            }
+        }
+        Ok(Vc::cell(hints))
+    }
 
-        // Resolve assets
-        for asset in assets.iter_mut() {
-            *asset = asset.resolve().await?;
+    #[turbo_tasks::function]
+    fn split_points(&self) -> Vc<SplitPoints> {
+        self.split_points
+    }
+
+    #[turbo_tasks::function]
+    fn inline_async_chunk_size(&self) -> Vc<usize> {
+        // Inlining requires the size of the async chunk group, which would
+        // defeat lazy compilation.
+        if self.lazy_compilation {
+            return Vc::cell(0);
         }
+        Vc::cell(self.inline_async_chunk_size)
+    }
 
-        Ok(Vc::cell(assets))
+    #[turbo_tasks::function]
+    fn startup_cost_budget(&self) -> Vc<usize> {
+        Vc::cell(self.startup_cost_budget)
+    }
+
+    #[turbo_tasks::function]
+    fn chunk_group(
+        self: Vc<Self>,
+        module: Vc<Box<dyn ChunkableModule>>,
+        availability_info: Value<AvailabilityInfo>,
+    ) -> Vc<OutputAssets> {
+        self.chunk_group_with_name(module, None, availability_info)
+    }
+
+    #[turbo_tasks::function]
+    fn named_chunk_group(
+        self: Vc<Self>,
+        module: Vc<Box<dyn ChunkableModule>>,
+        chunk_name: String,
+        availability_info: Value<AvailabilityInfo>,
+    ) -> Vc<OutputAssets> {
+        self.chunk_group_with_name(module, Some(chunk_name), availability_info)
     }
 
     #[turbo_tasks::function]
@@ -360,8 +611,8 @@ impl ChunkingContext for DevChunkingContext {
             .map(|&evaluatable| Vc::upcast(evaluatable))
             .collect::<Vec<_>>();
 
-        let MakeChunkGroupResult { chunks } =
-            make_chunk_group(Vc::upcast(self), entries, availability_info).await?;
+        let MakeChunkGroupResult { chunks, .. } =
+            make_chunk_group(Vc::upcast(self), entries, None, availability_info).await?;
 
         let mut assets: Vec<Vc<Box<dyn OutputAsset>>> = chunks
             .iter()
@@ -370,12 +621,30 @@ impl ChunkingContext for DevChunkingContext {
 
         let other_assets = Vc::cell(assets.clone());
 
+        // The runtime chunk isn't part of the chunks the evaluate chunk waits
+        // for, as it doesn't register itself. It's part of the chunk list, so
+        // that changes to the runtime are picked up by HMR.
+        let runtime_chunk: Option<Vc<Box<dyn OutputAsset>>> = if self.await?.runtime_chunk {
+            Some(Vc::upcast(EcmascriptDevRuntimeChunk::new(self)))
+        } else {
+            None
+        };
+        let chunk_list_assets = match runtime_chunk {
+            Some(runtime_chunk) => {
+                let mut chunk_list_assets = assets.clone();
+                chunk_list_assets.push(runtime_chunk);
+                Vc::cell(chunk_list_assets)
+            }
+            None => other_assets,
+        };
+
         assets.push(self.generate_chunk_list_register_chunk(
             ident,
             evaluatable_assets,
-            other_assets,
+            chunk_list_assets,
             Value::new(EcmascriptDevChunkListSource::Entry),
         ));
+        assets.extend(runtime_chunk);
 
         assets.push(self.generate_evaluate_chunk(ident, other_assets, evaluatable_assets));
 
@@ -388,16 +657,83 @@ impl ChunkingContext for DevChunkingContext {
     }
 
     #[turbo_tasks::function]
-    fn async_loader_chunk_item(
+    async fn service_worker_entry_chunk(
+        self: Vc<Self>,
+        path: Vc<FileSystemPath>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+    ) -> Result<Vc<Box<dyn OutputAsset>>> {
+        let entries = evaluatable_assets
+            .await?
+            .iter()
+            .map(|&evaluatable| Vc::upcast(evaluatable))
+            .collect::<Vec<_>>();
+
+        // Event handlers of service workers must be registered during the initial
+        // evaluation of the script, so it can't wait for async modules.
+        let MakeChunkGroupResult { chunks, .. } = make_sync_chunk_group(
+            Vc::upcast(self),
+            entries,
+            None,
+            AvailabilityInfo::Root,
+            "a service worker script",
+        )
+        .await?;
+
+        // Only ecmascript chunks can be inlined into the service worker script.
+        let mut assets: Vec<Vc<Box<dyn OutputAsset>>> = Vec::new();
+        for chunk in chunks {
+            if let Some(ecmascript_chunk) =
+                Vc::try_resolve_downcast_type::<EcmascriptChunk>(chunk).await?
+            {
+                assets.push(Vc::upcast(EcmascriptDevChunk::new(self, ecmascript_chunk)));
+            }
+        }
+        let chunks = Vc::cell(assets);
+
+        let evaluate_chunk = EcmascriptDevEvaluateChunk::new(
+            self,
+            AssetIdent::from_path(path),
+            chunks,
+            evaluatable_assets,
+        );
+
+        Ok(Vc::upcast(EcmascriptDevServiceWorkerChunk::new(
+            self,
+            path,
+            chunks,
+            evaluate_chunk,
+        )))
+    }
+
+    #[turbo_tasks::function]
+    async fn async_loader_chunk_item(
         self: Vc<Self>,
         module: Vc<Box<dyn ChunkableModule>>,
+        chunk_name: Option<String>,
         availability_info: Value<AvailabilityInfo>,
+    ) -> Result<Vc<Box<dyn ChunkItem>>> {
+        let manifest_asset =
+            ManifestAsyncModule::new(module, Vc::upcast(self), chunk_name, availability_info);
+        Ok(if self.await?.lazy_compilation {
+            Vc::upcast(LazyLoaderChunkItem::new(
+                manifest_asset,
+                Vc::upcast(EcmascriptDevLazyManifestChunk::new(self, manifest_asset)),
+                Vc::upcast(self),
+            ))
+        } else {
+            Vc::upcast(ManifestLoaderChunkItem::new(
+                manifest_asset,
+                Vc::upcast(self),
+            ))
+        })
+    }
+
+    #[turbo_tasks::function]
+    fn inlined_async_loader_chunk_item(
+        self: Vc<Self>,
+        module: Vc<Box<dyn ChunkableModule>>,
     ) -> Vc<Box<dyn ChunkItem>> {
-        let manifest_asset = ManifestAsyncModule::new(module, Vc::upcast(self), availability_info);
-        Vc::upcast(ManifestLoaderChunkItem::new(
-            manifest_asset,
-            Vc::upcast(self),
-        ))
+        Vc::upcast(InlinedLoaderChunkItem::new(module, Vc::upcast(self)))
     }
 
     #[turbo_tasks::function]
@@ -415,4 +751,8 @@ impl EcmascriptChunkingContext for DevChunkingContext {
     fn has_react_refresh(&self) -> Vc<bool> {
         Vc::cell(true)
     }
+    #[turbo_tasks::function]
+    fn is_public_path_deferred(&self) -> Vc<bool> {
+        Vc::cell(self.deferred_public_path)
+    }
 }
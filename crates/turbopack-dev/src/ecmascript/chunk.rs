@@ -10,7 +10,10 @@ use turbopack_core::{
     source_map::{GenerateSourceMap, OptionSourceMap, SourceMapAsset},
     version::VersionedContent,
 };
-use turbopack_ecmascript::chunk::EcmascriptChunk;
+use turbopack_ecmascript::{
+    chunk::{EcmascriptChunk, EcmascriptOutputChunk},
+    references::InlinedValues,
+};
 
 use crate::{ecmascript::content::EcmascriptDevChunkContent, DevChunkingContext};
 
@@ -62,7 +65,7 @@ fn modifier() -> Vc<String> {
 #[turbo_tasks::value_impl]
 impl EcmascriptDevChunk {
     #[turbo_tasks::function]
-    async fn own_content(self: Vc<Self>) -> Result<Vc<EcmascriptDevChunkContent>> {
+    pub(super) async fn own_content(self: Vc<Self>) -> Result<Vc<EcmascriptDevChunkContent>> {
         let this = self.await?;
         Ok(EcmascriptDevChunkContent::new(
             this.chunking_context,
@@ -72,6 +75,22 @@ impl EcmascriptDevChunk {
     }
 }
 
+impl EcmascriptDevChunk {
+    /// Returns the output assets referenced by the chunk items of this chunk,
+    /// which doesn't include the chunk's own source map.
+    pub(super) async fn chunk_references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        Ok(self.await?.chunk.references())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptOutputChunk for EcmascriptDevChunk {
+    #[turbo_tasks::function]
+    fn inlined_values(&self) -> Vc<InlinedValues> {
+        self.chunk.inlined_values()
+    }
+}
+
 #[turbo_tasks::value_impl]
 impl OutputAsset for EcmascriptDevChunk {
     #[turbo_tasks::function]
@@ -62,8 +62,26 @@ impl EcmascriptDevChunkContent {
     #[turbo_tasks::function]
     async fn code(self: Vc<Self>) -> Result<Vc<Code>> {
         let this = self.await?;
-        let output_root = this.chunking_context.output_root().await?;
-        let chunk_path = this.chunk.ident().path().await?;
+        let mut code = CodeBuilder::default();
+
+        this.write_registration(&mut code).await?;
+
+        if code.has_source_map() {
+            let chunk_path = this.chunk.ident().path().await?;
+            let filename = chunk_path.file_name();
+            write!(code, "\n\n//# sourceMappingURL={}.map", filename)?;
+        }
+
+        Ok(code.build().cell())
+    }
+}
+
+impl EcmascriptDevChunkContent {
+    /// Writes the code registering the chunk with the runtime, without a
+    /// source map comment.
+    pub(super) async fn write_registration(&self, code: &mut CodeBuilder) -> Result<()> {
+        let output_root = self.chunking_context.output_root().await?;
+        let chunk_path = self.chunk.ident().path().await?;
         let chunk_server_path = if let Some(path) = output_root.get_path_to(&chunk_path) {
             path
         } else {
@@ -73,7 +91,6 @@ impl EcmascriptDevChunkContent {
                 output_root.to_string()
             );
         };
-        let mut code = CodeBuilder::default();
 
         // When a chunk is executed, it will either register itself with the current
         // instance of the runtime, or it will push itself onto the list of pending
@@ -90,7 +107,7 @@ impl EcmascriptDevChunkContent {
             chunk_path = StringifyJs(chunk_server_path)
         )?;
 
-        for (id, entry) in this.entries.await?.iter() {
+        for (id, entry) in self.entries.await?.iter() {
             write!(code, "\n{}: ", StringifyJs(&id))?;
             code.push_code(&*entry.code.await?);
             write!(code, ",")?;
@@ -98,12 +115,7 @@ impl EcmascriptDevChunkContent {
 
         write!(code, "\n}}]);")?;
 
-        if code.has_source_map() {
-            let filename = chunk_path.file_name();
-            write!(code, "\n\n//# sourceMappingURL={}.map", filename)?;
-        }
-
-        Ok(code.build().cell())
+        Ok(())
     }
 }
 
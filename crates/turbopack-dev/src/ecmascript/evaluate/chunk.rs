@@ -21,8 +21,8 @@ use turbopack_ecmascript::{
     chunk::{EcmascriptChunkData, EcmascriptChunkPlaceable},
     utils::StringifyJs,
 };
-use turbopack_ecmascript_runtime::RuntimeType;
 
+use super::runtime::write_runtime;
 use crate::DevChunkingContext;
 
 /// An Ecmascript chunk that:
@@ -67,8 +67,30 @@ impl EcmascriptDevEvaluateChunk {
     #[turbo_tasks::function]
     async fn code(self: Vc<Self>) -> Result<Vc<Code>> {
         let this = self.await?;
-        let chunking_context = this.chunking_context.await?;
-        let environment = this.chunking_context.environment();
+        let mut code = CodeBuilder::default();
+
+        let include_runtime = !this.chunking_context.await?.runtime_chunk();
+        self.write_evaluation(&mut code, include_runtime).await?;
+
+        if code.has_source_map() {
+            let chunk_path = self.ident().path().await?;
+            let filename = chunk_path.file_name();
+            write!(code, "\n\n//# sourceMappingURL={}.map", filename)?;
+        }
+
+        Ok(Code::cell(code.build()))
+    }
+}
+
+impl EcmascriptDevEvaluateChunk {
+    /// Writes the code registering this chunk and, with `include_runtime`, the
+    /// runtime code, without a source map comment.
+    pub(crate) async fn write_evaluation(
+        self: Vc<Self>,
+        code: &mut CodeBuilder,
+        include_runtime: bool,
+    ) -> Result<()> {
+        let this = self.await?;
 
         let output_root = this.chunking_context.output_root().await?;
         let chunk_path = self.ident().path().await?;
@@ -122,8 +144,6 @@ impl EcmascriptDevEvaluateChunk {
             runtime_module_ids,
         };
 
-        let mut code = CodeBuilder::default();
-
         // We still use the `TURBOPACK` global variable to store the chunk here,
         // as there may be another runtime already loaded in the page.
         // This is the case in integration tests.
@@ -140,27 +160,11 @@ impl EcmascriptDevEvaluateChunk {
             StringifyJs(&params),
         )?;
 
-        match chunking_context.runtime_type() {
-            RuntimeType::Default => {
-                let runtime_code = turbopack_ecmascript_runtime::get_dev_runtime_code(
-                    environment,
-                    chunking_context.chunk_base_path(),
-                );
-                code.push_code(&*runtime_code.await?);
-            }
-            #[cfg(feature = "test")]
-            RuntimeType::Dummy => {
-                let runtime_code = turbopack_ecmascript_runtime::get_dummy_runtime_code();
-                code.push_code(&runtime_code);
-            }
+        if include_runtime {
+            write_runtime(this.chunking_context, code).await?;
         }
 
-        if code.has_source_map() {
-            let filename = chunk_path.file_name();
-            write!(code, "\n\n//# sourceMappingURL={}.map", filename)?;
-        }
-
-        Ok(Code::cell(code.build()))
+        Ok(())
     }
 }
 
@@ -0,0 +1,135 @@
+use std::io::Write;
+
+use anyhow::Result;
+use turbo_tasks::{ValueToString, Vc};
+use turbo_tasks_fs::{File, FileSystem};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::ChunkingContext,
+    code_builder::{Code, CodeBuilder},
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets},
+    source_map::{GenerateSourceMap, OptionSourceMap, SourceMapAsset},
+};
+use turbopack_ecmascript_runtime::RuntimeType;
+
+use crate::DevChunkingContext;
+
+/// An Ecmascript chunk that only contains the Turbopack dev runtime code. It's
+/// shared by all evaluate chunks of the chunking context, see
+/// [crate::DevChunkingContextBuilder::runtime_chunk].
+#[turbo_tasks::value(shared)]
+pub(crate) struct EcmascriptDevRuntimeChunk {
+    chunking_context: Vc<DevChunkingContext>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptDevRuntimeChunk {
+    /// Creates a new [`Vc<EcmascriptDevRuntimeChunk>`].
+    #[turbo_tasks::function]
+    pub fn new(chunking_context: Vc<DevChunkingContext>) -> Vc<Self> {
+        EcmascriptDevRuntimeChunk { chunking_context }.cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn code(self: Vc<Self>) -> Result<Vc<Code>> {
+        let this = self.await?;
+        let mut code = CodeBuilder::default();
+
+        // The runtime only takes over `globalThis.TURBOPACK` when it's an array
+        // of chunks to register, which it isn't when the runtime chunk is
+        // loaded first.
+        writeln!(code, "globalThis.TURBOPACK = globalThis.TURBOPACK || [];")?;
+        write_runtime(this.chunking_context, &mut code).await?;
+
+        if code.has_source_map() {
+            let chunk_path = self.ident().path().await?;
+            let filename = chunk_path.file_name();
+            write!(code, "\n\n//# sourceMappingURL={}.map", filename)?;
+        }
+
+        Ok(Code::cell(code.build()))
+    }
+}
+
+/// Writes the dev runtime code of the `chunking_context`.
+pub(crate) async fn write_runtime(
+    chunking_context: Vc<DevChunkingContext>,
+    code: &mut CodeBuilder,
+) -> Result<()> {
+    let chunking_context_ref = chunking_context.await?;
+    match chunking_context_ref.runtime_type() {
+        RuntimeType::Default => {
+            let runtime_code = turbopack_ecmascript_runtime::get_dev_runtime_code(
+                chunking_context.environment(),
+                chunking_context_ref.chunk_base_path(),
+                chunking_context_ref.deferred_public_path(),
+                chunking_context_ref.chunk_loading_attributes(),
+            );
+            code.push_code(&*runtime_code.await?);
+        }
+        #[cfg(feature = "test")]
+        RuntimeType::Dummy => {
+            let runtime_code = turbopack_ecmascript_runtime::get_dummy_runtime_code();
+            code.push_code(&runtime_code);
+        }
+    }
+    Ok(())
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for EcmascriptDevRuntimeChunk {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell("Ecmascript Dev Runtime Chunk".to_string()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for EcmascriptDevRuntimeChunk {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        let ident = AssetIdent::from_path(
+            turbopack_ecmascript_runtime::embed_fs()
+                .root()
+                .join("runtime.js".to_string()),
+        );
+
+        AssetIdent::from_path(self.chunking_context.chunk_path(ident, ".js".to_string()))
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        let this = self.await?;
+        let mut references = vec![];
+
+        if *this
+            .chunking_context
+            .reference_chunk_source_maps(Vc::upcast(self))
+            .await?
+        {
+            references.push(Vc::upcast(SourceMapAsset::new(Vc::upcast(self))))
+        }
+
+        Ok(Vc::cell(references))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for EcmascriptDevRuntimeChunk {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        let code = self.code().await?;
+        Ok(AssetContent::file(
+            File::from(code.source_code().clone()).into(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for EcmascriptDevRuntimeChunk {
+    #[turbo_tasks::function]
+    fn generate_source_map(self: Vc<Self>) -> Vc<OptionSourceMap> {
+        self.code().generate_source_map()
+    }
+}
@@ -0,0 +1,148 @@
+use std::io::Write;
+
+use anyhow::{bail, Context, Result};
+use indoc::writedoc;
+use turbo_tasks::{TryJoinIterExt, ValueToString, Vc};
+use turbo_tasks_fs::File;
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{ChunkItem, ChunkItemExt, ChunkableModule, ChunkingContext},
+    code_builder::{Code, CodeBuilder},
+    ident::AssetIdent,
+    module::Module,
+    output::{OutputAsset, OutputAssets},
+    reference::ModuleReference,
+};
+use turbopack_ecmascript::{
+    chunk::{EcmascriptChunkItem, EcmascriptChunkItemExt},
+    manifest::chunk_asset::ManifestAsyncModule,
+    utils::StringifyJs,
+};
+
+use crate::DevChunkingContext;
+
+#[turbo_tasks::function]
+fn modifier() -> Vc<String> {
+    Vc::cell("lazy manifest".to_string())
+}
+
+/// A chunk which only contains the manifest chunk item of a dynamic import.
+///
+/// Used in lazy compilation mode: unlike the chunk group of the manifest, the
+/// path of this chunk is known without compiling the dynamically imported
+/// module. Its content and references, which require the chunk group of the
+/// dynamic import, are only computed once the chunk is requested.
+#[turbo_tasks::value(shared)]
+pub(crate) struct EcmascriptDevLazyManifestChunk {
+    chunking_context: Vc<DevChunkingContext>,
+    manifest: Vc<ManifestAsyncModule>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptDevLazyManifestChunk {
+    /// Creates a new [`Vc<EcmascriptDevLazyManifestChunk>`].
+    #[turbo_tasks::function]
+    pub fn new(
+        chunking_context: Vc<DevChunkingContext>,
+        manifest: Vc<ManifestAsyncModule>,
+    ) -> Vc<Self> {
+        EcmascriptDevLazyManifestChunk {
+            chunking_context,
+            manifest,
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn chunk_item(self: Vc<Self>) -> Result<Vc<Box<dyn EcmascriptChunkItem>>> {
+        let this = self.await?;
+        let chunk_item = this
+            .manifest
+            .as_chunk_item(Vc::upcast(this.chunking_context));
+        Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkItem>>(chunk_item)
+            .await?
+            .context("manifest chunk item must be an ecmascript chunk item")
+    }
+
+    #[turbo_tasks::function]
+    async fn code(self: Vc<Self>) -> Result<Vc<Code>> {
+        let this = self.await?;
+        let mut code = CodeBuilder::default();
+
+        let output_root = this.chunking_context.output_root().await?;
+        let chunk_path = self.ident().path().await?;
+        let Some(chunk_server_path) = output_root.get_path_to(&chunk_path) else {
+            bail!(
+                "chunk path {} is not in output root {}",
+                chunk_path.to_string(),
+                output_root.to_string()
+            );
+        };
+
+        let chunk_item = self.chunk_item();
+
+        // Registers like a regular dev chunk, so loading it resolves the
+        // `__turbopack_load__` call of the lazy loader item.
+        writedoc!(
+            code,
+            r#"
+                (globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([{chunk_path}, {{
+            "#,
+            chunk_path = StringifyJs(chunk_server_path)
+        )?;
+        write!(code, "\n{}: ", StringifyJs(&*chunk_item.id().await?))?;
+        code.push_code(&*chunk_item.code(None).await?);
+        write!(code, ",\n}}]);")?;
+
+        Ok(code.build().cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for EcmascriptDevLazyManifestChunk {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell("Ecmascript Dev Lazy Manifest Chunk".to_string()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for EcmascriptDevLazyManifestChunk {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        let ident = self.manifest.ident().with_modifier(modifier());
+        AssetIdent::from_path(self.chunking_context.chunk_path(ident, ".js".to_string()))
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        // These are the chunks of the dynamic import, so requesting them is
+        // what triggers its compilation.
+        let references = self.chunk_item().references().await?;
+        let output_assets =
+            references
+                .iter()
+                .map(|reference| async move {
+                    reference.resolve_reference().primary_output_assets().await
+                })
+                .try_join()
+                .await?;
+        Ok(Vc::cell(
+            output_assets
+                .iter()
+                .flat_map(|assets| assets.iter().copied())
+                .collect(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for EcmascriptDevLazyManifestChunk {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        let code = self.code().await?;
+        Ok(AssetContent::file(
+            File::from(code.source_code().clone()).into(),
+        ))
+    }
+}
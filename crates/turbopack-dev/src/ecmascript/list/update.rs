@@ -2,50 +2,21 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use indexmap::IndexMap;
-use serde::Serialize;
 use turbo_tasks::{IntoTraitRef, TraitRef, Vc};
-use turbopack_core::version::{
-    MergeableVersionedContent, PartialUpdate, TotalUpdate, Update, Version, VersionedContent,
-    VersionedContentMerger,
+use turbopack_core::{
+    update_protocol::{self, ChunkUpdate},
+    version::{
+        MergeableVersionedContent, PartialUpdate, TotalUpdate, Update, Version, VersionedContent,
+        VersionedContentMerger,
+    },
 };
 
 use super::{content::EcmascriptDevChunkListContent, version::EcmascriptDevChunkListVersion};
 
-/// Update of a chunk list from one version to another.
-#[derive(Serialize)]
-#[serde(tag = "type")]
-#[serde(rename_all = "camelCase")]
-struct ChunkListUpdate<'a> {
-    /// A map from chunk path to a corresponding update of that chunk.
-    #[serde(skip_serializing_if = "IndexMap::is_empty")]
-    chunks: IndexMap<&'a str, ChunkUpdate>,
-    /// List of merged updates since the last version.
-    #[serde(skip_serializing_if = "Vec::is_empty")]
-    merged: Vec<Arc<serde_json::Value>>,
-}
-
-/// Update of a chunk from one version to another.
-#[derive(Serialize)]
-#[serde(tag = "type")]
-#[serde(rename_all = "camelCase")]
-enum ChunkUpdate {
-    /// The chunk was updated and must be reloaded.
-    Total,
-    /// The chunk was updated and can be merged with the previous version.
-    Partial { instruction: Arc<serde_json::Value> },
-    /// The chunk was added.
-    Added,
-    /// The chunk was deleted.
-    Deleted,
-}
-
-impl<'a> ChunkListUpdate<'a> {
-    /// Returns `true` if this update is empty.
-    fn is_empty(&self) -> bool {
-        let ChunkListUpdate { chunks, merged } = self;
-        chunks.is_empty() && merged.is_empty()
-    }
-}
+/// The update of a chunk list, with the instructions of partial and merged
+/// updates as computed by the chunks and mergers.
+type ChunkListUpdate =
+    update_protocol::ChunkListUpdate<Arc<serde_json::Value>, Arc<serde_json::Value>>;
 
 /// Computes the update of a chunk list from one version to another.
 #[turbo_tasks::function]
@@ -112,11 +83,11 @@ pub(super) async fn update_chunk_list(
 
             match &*chunk_update {
                 Update::Total(_) => {
-                    chunks.insert(chunk_path.as_ref(), ChunkUpdate::Total);
+                    chunks.insert(chunk_path.to_string(), ChunkUpdate::Total);
                 }
                 Update::Partial(partial) => {
                     chunks.insert(
-                        chunk_path.as_ref(),
+                        chunk_path.to_string(),
                         ChunkUpdate::Partial {
                             instruction: partial.instruction.clone(),
                         },
@@ -125,12 +96,12 @@ pub(super) async fn update_chunk_list(
                 Update::None => {}
             }
         } else {
-            chunks.insert(chunk_path.as_ref(), ChunkUpdate::Deleted);
+            chunks.insert(chunk_path.to_string(), ChunkUpdate::Deleted);
         }
     }
 
     for chunk_path in by_path.keys() {
-        chunks.insert(chunk_path.as_ref(), ChunkUpdate::Added);
+        chunks.insert(chunk_path.to_string(), ChunkUpdate::Added);
     }
 
     let mut merged = vec![];
@@ -160,7 +131,11 @@ pub(super) async fn update_chunk_list(
             }
         }
     }
-    let update = ChunkListUpdate { chunks, merged };
+    let update = ChunkListUpdate {
+        chunks,
+        merged,
+        ..Default::default()
+    };
 
     let update = if update.is_empty() {
         Update::None
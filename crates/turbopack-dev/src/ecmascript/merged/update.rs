@@ -4,11 +4,14 @@ use anyhow::Result;
 use indexmap::{IndexMap, IndexSet};
 use serde::Serialize;
 use turbo_tasks::{IntoTraitRef, ReadRef, TryJoinIterExt, Vc};
-use turbo_tasks_fs::rope::Rope;
 use turbopack_core::{
     chunk::{ChunkingContext, ModuleId},
     code_builder::Code,
     output::OutputAsset,
+    update_protocol::{
+        EcmascriptMergedChunkUpdate, EcmascriptMergedUpdate, EcmascriptModuleEntry,
+        MergedChunkUpdate,
+    },
     version::{PartialUpdate, TotalUpdate, Update, Version},
 };
 
@@ -21,80 +24,20 @@ use super::{
     version::EcmascriptDevMergedChunkVersion,
 };
 
-#[derive(Serialize, Default)]
-#[serde(tag = "type", rename_all = "camelCase")]
-struct EcmascriptMergedUpdate<'a> {
-    /// A map from module id to latest module entry.
-    #[serde(skip_serializing_if = "IndexMap::is_empty")]
-    entries: IndexMap<ReadRef<ModuleId>, EcmascriptModuleEntry>,
-    /// A map from chunk path to the chunk update.
-    #[serde(skip_serializing_if = "IndexMap::is_empty")]
-    chunks: IndexMap<&'a str, EcmascriptMergedChunkUpdate>,
-}
-
-impl EcmascriptMergedUpdate<'_> {
-    fn is_empty(&self) -> bool {
-        self.entries.is_empty() && self.chunks.is_empty()
+fn module_entry(id: &ModuleId, code: ReadRef<Code>, chunk_path: &str) -> EcmascriptModuleEntry {
+    /// serde_qs can't serialize a lone enum when it's [serde::untagged].
+    #[derive(Serialize)]
+    struct Id<'a> {
+        id: &'a ModuleId,
     }
-}
-
-#[derive(Serialize)]
-#[serde(tag = "type", rename_all = "camelCase")]
-enum EcmascriptMergedChunkUpdate {
-    Added(EcmascriptMergedChunkAdded),
-    Deleted(EcmascriptMergedChunkDeleted),
-    Partial(EcmascriptMergedChunkPartial),
-}
-
-#[derive(Serialize, Default)]
-#[serde(rename_all = "camelCase")]
-struct EcmascriptMergedChunkAdded {
-    #[serde(skip_serializing_if = "IndexSet::is_empty")]
-    modules: IndexSet<ReadRef<ModuleId>>,
-}
-
-#[derive(Serialize, Default)]
-#[serde(rename_all = "camelCase")]
-struct EcmascriptMergedChunkDeleted {
-    // Technically, this is redundant, since the client will already know all
-    // modules in the chunk from the previous version. However, it's useful for
-    // merging updates without access to an initial state.
-    #[serde(skip_serializing_if = "IndexSet::is_empty")]
-    modules: IndexSet<ReadRef<ModuleId>>,
-}
-
-#[derive(Serialize, Default)]
-#[serde(rename_all = "camelCase")]
-struct EcmascriptMergedChunkPartial {
-    #[serde(skip_serializing_if = "IndexSet::is_empty")]
-    added: IndexSet<ReadRef<ModuleId>>,
-    #[serde(skip_serializing_if = "IndexSet::is_empty")]
-    deleted: IndexSet<ReadRef<ModuleId>>,
-}
-
-#[derive(Serialize)]
-struct EcmascriptModuleEntry {
-    code: Rope,
-    url: String,
-    map: Option<String>,
-}
-
-impl EcmascriptModuleEntry {
-    fn new(id: &ModuleId, code: ReadRef<Code>, chunk_path: &str) -> Self {
-        /// serde_qs can't serialize a lone enum when it's [serde::untagged].
-        #[derive(Serialize)]
-        struct Id<'a> {
-            id: &'a ModuleId,
-        }
-        let id = serde_qs::to_string(&Id { id }).unwrap();
-        EcmascriptModuleEntry {
-            // Cloning a rope is cheap.
-            code: code.source_code().clone(),
-            url: format!("{}?{}", chunk_path, &id),
-            map: code
-                .has_source_map()
-                .then(|| format!("{}.map?{}", chunk_path, &id)),
-        }
+    let id = serde_qs::to_string(&Id { id }).unwrap();
+    EcmascriptModuleEntry {
+        // Cloning a rope is cheap.
+        code: code.source_code().clone(),
+        url: format!("{}?{}", chunk_path, &id),
+        map: code
+            .has_source_map()
+            .then(|| format!("{}.map?{}", chunk_path, &id)),
     }
 }
 
@@ -193,60 +136,65 @@ pub(super) async fn update_ecmascript_merged_chunk(
                 }
                 EcmascriptChunkUpdate::Partial(chunk_partial) => {
                     // The chunk was updated.
-                    let mut partial = EcmascriptMergedChunkPartial::default();
+                    let mut added = IndexSet::new();
 
                     for (module_id, (module_hash, module_code)) in chunk_partial.added {
-                        partial.added.insert(module_id.clone());
+                        added.insert((*module_id).clone());
 
                         if merged_module_map.get(&module_id) != Some(module_hash) {
-                            let entry = EcmascriptModuleEntry::new(
-                                &module_id,
-                                module_code.clone(),
-                                chunk_path,
-                            );
-                            merged_update.entries.insert(module_id, entry);
+                            let entry = module_entry(&module_id, module_code.clone(), chunk_path);
+                            merged_update.entries.insert((*module_id).clone(), entry);
                         }
                     }
 
-                    partial.deleted.extend(chunk_partial.deleted.into_keys());
+                    let deleted = chunk_partial
+                        .deleted
+                        .into_keys()
+                        .map(|module_id| (*module_id).clone())
+                        .collect();
 
                     for (module_id, module_code) in chunk_partial.modified {
-                        let entry = EcmascriptModuleEntry::new(&module_id, module_code, chunk_path);
-                        merged_update.entries.insert(module_id, entry);
+                        let entry = module_entry(&module_id, module_code, chunk_path);
+                        merged_update.entries.insert((*module_id).clone(), entry);
                     }
 
-                    EcmascriptMergedChunkUpdate::Partial(partial)
+                    EcmascriptMergedChunkUpdate::Partial { added, deleted }
                 }
             }
         } else {
             // The chunk was added in this version.
-            let mut added = EcmascriptMergedChunkAdded::default();
+            let mut modules = IndexSet::new();
 
             for (id, entry) in &content_ref.entries.await? {
                 let hash = *entry.hash.await?;
                 let code = entry.code.await?;
-                added.modules.insert(id.clone());
+                modules.insert((**id).clone());
 
                 if merged_module_map.get(id) != Some(hash) {
-                    let entry = EcmascriptModuleEntry::new(id, code, chunk_path);
-                    merged_update.entries.insert(id.clone(), entry);
+                    let entry = module_entry(id, code, chunk_path);
+                    merged_update.entries.insert((**id).clone(), entry);
                 }
             }
 
-            EcmascriptMergedChunkUpdate::Added(added)
+            EcmascriptMergedChunkUpdate::Added { modules }
         };
 
-        merged_update.chunks.insert(chunk_path, chunk_update);
+        merged_update
+            .chunks
+            .insert(chunk_path.to_string(), chunk_update);
     }
 
     // Deleted chunks.
     for (chunk_path, chunk_version) in from_versions_by_chunk_path {
         let hashes = &chunk_version.entries_hashes;
         merged_update.chunks.insert(
-            chunk_path,
-            EcmascriptMergedChunkUpdate::Deleted(EcmascriptMergedChunkDeleted {
-                modules: hashes.keys().cloned().collect(),
-            }),
+            chunk_path.to_string(),
+            EcmascriptMergedChunkUpdate::Deleted {
+                modules: hashes
+                    .keys()
+                    .map(|module_id| (**module_id).clone())
+                    .collect(),
+            },
         );
     }
 
@@ -257,7 +205,9 @@ pub(super) async fn update_ecmascript_merged_chunk(
             to: Vc::upcast::<Box<dyn Version>>(to_merged_version)
                 .into_trait_ref()
                 .await?,
-            instruction: Arc::new(serde_json::to_value(&merged_update)?),
+            instruction: Arc::new(serde_json::to_value(
+                MergedChunkUpdate::EcmascriptMergedUpdate(merged_update),
+            )?),
         })
     };
 
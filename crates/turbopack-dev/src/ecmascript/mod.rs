@@ -2,7 +2,9 @@ pub(crate) mod chunk;
 pub(crate) mod content;
 pub(crate) mod content_entry;
 pub(crate) mod evaluate;
+pub(crate) mod lazy;
 pub(crate) mod list;
 pub(crate) mod merged;
+pub(crate) mod service_worker;
 pub(crate) mod update;
 pub(crate) mod version;
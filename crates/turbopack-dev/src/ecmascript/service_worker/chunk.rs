@@ -0,0 +1,146 @@
+use std::io::Write;
+
+use anyhow::Result;
+use turbo_tasks::{ValueToString, Vc};
+use turbo_tasks_fs::{File, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::ChunkingContext,
+    code_builder::{Code, CodeBuilder},
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets},
+    source_map::{GenerateSourceMap, OptionSourceMap, SourceMapAsset},
+};
+
+use crate::{
+    ecmascript::{chunk::EcmascriptDevChunk, evaluate::chunk::EcmascriptDevEvaluateChunk},
+    DevChunkingContext,
+};
+
+/// A single classic script that contains all chunks of an evaluated chunk
+/// group, followed by the Turbopack dev runtime code.
+///
+/// Service workers need to be registered with a stable URL and can't load
+/// additional chunks on demand, so everything they need has to be in a single
+/// file.
+#[turbo_tasks::value(shared)]
+pub(crate) struct EcmascriptDevServiceWorkerChunk {
+    chunking_context: Vc<DevChunkingContext>,
+    path: Vc<FileSystemPath>,
+    chunks: Vc<OutputAssets>,
+    evaluate_chunk: Vc<EcmascriptDevEvaluateChunk>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptDevServiceWorkerChunk {
+    /// Creates a new [`Vc<EcmascriptDevServiceWorkerChunk>`].
+    ///
+    /// `chunks` must only contain [`EcmascriptDevChunk`]s.
+    #[turbo_tasks::function]
+    pub fn new(
+        chunking_context: Vc<DevChunkingContext>,
+        path: Vc<FileSystemPath>,
+        chunks: Vc<OutputAssets>,
+        evaluate_chunk: Vc<EcmascriptDevEvaluateChunk>,
+    ) -> Vc<Self> {
+        EcmascriptDevServiceWorkerChunk {
+            chunking_context,
+            path,
+            chunks,
+            evaluate_chunk,
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn code(self: Vc<Self>) -> Result<Vc<Code>> {
+        let this = self.await?;
+        let mut code = CodeBuilder::default();
+
+        // All chunks push themselves onto the list of pending chunks, which the
+        // runtime registers in order once it executes at the end of the file.
+        for &chunk in this.chunks.await?.iter() {
+            let Some(chunk) = Vc::try_resolve_downcast_type::<EcmascriptDevChunk>(chunk).await?
+            else {
+                continue;
+            };
+            chunk
+                .own_content()
+                .await?
+                .write_registration(&mut code)
+                .await?;
+            writeln!(code)?;
+        }
+
+        this.evaluate_chunk
+            .write_evaluation(&mut code, true)
+            .await?;
+
+        if code.has_source_map() {
+            let filename = this.path.await?.file_name().to_string();
+            write!(code, "\n\n//# sourceMappingURL={}.map", filename)?;
+        }
+
+        Ok(Code::cell(code.build()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for EcmascriptDevServiceWorkerChunk {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell("Ecmascript Dev Service Worker Chunk".to_string()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for EcmascriptDevServiceWorkerChunk {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        let this = self.await?;
+        let mut references = Vec::new();
+
+        let include_source_map = *this
+            .chunking_context
+            .reference_chunk_source_maps(Vc::upcast(self))
+            .await?;
+
+        if include_source_map {
+            references.push(Vc::upcast(SourceMapAsset::new(Vc::upcast(self))));
+        }
+
+        // The chunks themselves are inlined, but the assets they reference still
+        // need to be emitted.
+        for &chunk in this.chunks.await?.iter() {
+            if let Some(chunk) = Vc::try_resolve_downcast_type::<EcmascriptDevChunk>(chunk).await? {
+                references.extend(chunk.chunk_references().await?.await?.iter().copied());
+            }
+        }
+
+        Ok(Vc::cell(references))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for EcmascriptDevServiceWorkerChunk {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        let code = self.code().await?;
+        Ok(AssetContent::file(
+            File::from(code.source_code().clone()).into(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for EcmascriptDevServiceWorkerChunk {
+    #[turbo_tasks::function]
+    fn generate_source_map(self: Vc<Self>) -> Vc<OptionSourceMap> {
+        self.code().generate_source_map()
+    }
+}
@@ -177,6 +177,7 @@ impl<'a> From<&'a PlainIssue> for Issue<'a> {
                     show_all: true,
                     log_detail: true,
                     log_level: IssueSeverity::Info,
+                    issue_policy: Default::default(),
                 },
             ),
         }
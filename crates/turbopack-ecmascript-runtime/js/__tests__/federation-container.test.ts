@@ -0,0 +1,201 @@
+import { readFileSync } from "fs";
+import { join } from "path";
+import * as ts from "typescript";
+
+type Runtime = {
+  satisfies: (version: string, range?: string) => boolean;
+  compareVersions: (a: string, b: string) => number;
+  createFederationContainer: (
+    name: string,
+    exposes: Record<string, any>,
+    shared: Record<string, any>
+  ) => {
+    init: (shareScope: Record<string, any>) => void;
+    get: (name: string) => Promise<() => any>;
+  };
+};
+
+// The runtime is a script which is embedded into `remoteEntry.mjs`, so it is
+// evaluated the same way, with a fresh module registry for each container.
+function loadRuntime(): Runtime {
+  const code = [
+    "shared/runtime-utils.ts",
+    "library/runtime.ts",
+    "federation/container.ts",
+  ]
+    .map(
+      (file) =>
+        ts.transpileModule(
+          readFileSync(join(__dirname, "../src", file), "utf-8"),
+          { compilerOptions: { target: ts.ScriptTarget.ES2020 } }
+        ).outputText
+    )
+    .join("\n");
+  return new Function(
+    "CHUNK_PUBLIC_PATH",
+    "LIBRARY_EXTERNALS",
+    `${code}\nreturn { satisfies, compareVersions, createFederationContainer };`
+  )("remoteEntry.mjs", {});
+}
+
+const chunk = (factories: Record<string, Function>) => () =>
+  Promise.resolve({
+    default: (register: (factories: Record<string, Function>) => void) =>
+      register(factories),
+  });
+
+function createApp2(runtime: Runtime) {
+  const instantiated: string[] = [];
+  const utils = chunk({
+    utils({ s }: any) {
+      instantiated.push("utils");
+      s({ label: () => (name: string) => `app2 ${name}` });
+    },
+  });
+  const react = chunk({
+    react({ m }: any) {
+      instantiated.push("react");
+      m.exports = { version: "18.2.0" };
+    },
+  });
+  const container = runtime.createFederationContainer(
+    "app2",
+    {
+      "./Button": {
+        id: "button",
+        chunks: [
+          chunk({
+            button({ s, i, x }: any) {
+              const { label } = i("utils");
+              const { version } = x("react", true);
+              s({
+                Button: () => () => `${label("button")} with React ${version}`,
+              });
+            },
+          }),
+          utils,
+        ],
+      },
+      "./Header": {
+        id: "header",
+        chunks: [
+          chunk({
+            header({ s, i }: any) {
+              const { label } = i("utils");
+              s({ default: () => () => label("header") });
+            },
+          }),
+          utils,
+        ],
+      },
+    },
+    {
+      react: {
+        version: "18.2.0",
+        requiredVersion: ">= 16.8.0",
+        singleton: true,
+        id: "react",
+        chunks: [react],
+      },
+    }
+  );
+  return { container, instantiated };
+}
+
+describe("satisfies", () => {
+  const { satisfies } = loadRuntime();
+
+  it("checks comparison ranges", () => {
+    expect(satisfies("18.2.0", ">=16.8.0")).toBe(true);
+    expect(satisfies("16.8.0", ">= 16.8.0")).toBe(true);
+    expect(satisfies("16.7.9", ">=16.8.0")).toBe(false);
+    expect(satisfies("18.2.0", ">=16.8.0 <18")).toBe(false);
+    expect(satisfies("17.0.2", ">16.8.0 <=17.0.2")).toBe(true);
+  });
+
+  it("checks caret and tilde ranges", () => {
+    expect(satisfies("18.3.1", "^18.2.0")).toBe(true);
+    expect(satisfies("19.0.0", "^18.2.0")).toBe(false);
+    expect(satisfies("0.2.5", "^0.2.3")).toBe(true);
+    expect(satisfies("0.3.0", "^0.2.3")).toBe(false);
+    expect(satisfies("1.2.9", "~1.2.3")).toBe(true);
+    expect(satisfies("1.3.0", "~1.2.3")).toBe(false);
+  });
+
+  it("checks exact versions, wildcards and alternatives", () => {
+    expect(satisfies("18.2.0", "18.2.0")).toBe(true);
+    expect(satisfies("18.2.1", "=18.2.0")).toBe(false);
+    expect(satisfies("1.0.0", "*")).toBe(true);
+    expect(satisfies("1.0.0", undefined)).toBe(true);
+    expect(satisfies("19.0.0", "^17.0.0 || ^19.0.0")).toBe(true);
+    expect(satisfies("18.0.0", "^17.0.0 || ^19.0.0")).toBe(false);
+  });
+});
+
+describe("compareVersions", () => {
+  const { compareVersions } = loadRuntime();
+
+  it("compares versions numerically", () => {
+    expect(compareVersions("18.10.0", "18.9.0")).toBeGreaterThan(0);
+    expect(compareVersions("v1.2.3", "1.2.3")).toBe(0);
+    expect(compareVersions("1.2", "1.2.1")).toBeLessThan(0);
+  });
+});
+
+describe("createFederationContainer", () => {
+  it("instantiates the modules which exposed modules share once", async () => {
+    const { container, instantiated } = createApp2(loadRuntime());
+    container.init({});
+
+    const button = (await container.get("./Button"))();
+    const header = (await container.get("./Header"))();
+
+    expect(button.Button()).toBe("app2 button with React 18.2.0");
+    expect(header.default()).toBe("app2 header");
+    expect(instantiated).toEqual(["react", "utils"]);
+  });
+
+  it("uses the singleton version which was loaded already", async () => {
+    const { container, instantiated } = createApp2(loadRuntime());
+    const hostReact = { version: "18.3.0" };
+    const shareScope = {
+      react: {
+        "17.0.2": { get: () => Promise.resolve({ version: "17.0.2" }) },
+        "18.3.0": {
+          get: () => Promise.resolve(hostReact),
+          loaded: Promise.resolve(hostReact),
+        },
+      },
+    };
+    container.init(shareScope);
+
+    const button = (await container.get("./Button"))();
+
+    expect(button.Button()).toBe("app2 button with React 18.3.0");
+    expect(instantiated).toEqual(["utils"]);
+    expect(Object.keys(shareScope.react)).toEqual([
+      "17.0.2",
+      "18.3.0",
+      "18.2.0",
+    ]);
+  });
+
+  it("provides its fallback to other containers", async () => {
+    const { container, instantiated } = createApp2(loadRuntime());
+    const shareScope: Record<string, any> = {};
+    container.init(shareScope);
+
+    const react = await shareScope.react["18.2.0"].get();
+
+    expect(react).toEqual({ version: "18.2.0" });
+    expect(instantiated).toEqual(["react"]);
+  });
+
+  it("rejects names which are not exposed", async () => {
+    const { container } = createApp2(loadRuntime());
+
+    await expect(container.get("./Footer")).rejects.toThrow(
+      "app2 does not expose ./Footer, it exposes ./Button, ./Header"
+    );
+  });
+});
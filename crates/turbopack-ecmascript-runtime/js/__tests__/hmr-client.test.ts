@@ -0,0 +1,44 @@
+/// <reference path="../src/dev/runtime/base/protocol.d.ts" />
+
+import { mergeChunkUpdates } from "../src/dev/client/hmr-client";
+
+const added: ChunkUpdate = { type: "added" };
+const deleted: ChunkUpdate = { type: "deleted" };
+const total: ChunkUpdate = { type: "total" };
+const css = (version: string): ChunkUpdate => ({
+  type: "partial",
+  instruction: { type: "CssChunkUpdate", version },
+});
+
+describe("mergeChunkUpdates", () => {
+  it("drops chunks which were added and deleted again", () => {
+    expect(mergeChunkUpdates(added, deleted)).toBeUndefined();
+  });
+
+  it("keeps adding chunks which were updated after being added", () => {
+    expect(mergeChunkUpdates(added, added)).toEqual(added);
+    expect(mergeChunkUpdates(added, total)).toEqual(added);
+    expect(mergeChunkUpdates(added, css("1"))).toEqual(added);
+  });
+
+  it("reloads chunks which were deleted and came back", () => {
+    expect(mergeChunkUpdates(deleted, added)).toEqual(total);
+    expect(mergeChunkUpdates(deleted, total)).toEqual(total);
+    expect(mergeChunkUpdates(deleted, css("1"))).toEqual(total);
+    expect(mergeChunkUpdates(deleted, deleted)).toEqual(deleted);
+  });
+
+  it("keeps total updates unless the chunk is deleted", () => {
+    expect(mergeChunkUpdates(total, added)).toEqual(total);
+    expect(mergeChunkUpdates(total, total)).toEqual(total);
+    expect(mergeChunkUpdates(total, css("1"))).toEqual(total);
+    expect(mergeChunkUpdates(total, deleted)).toEqual(deleted);
+  });
+
+  it("uses the latest CSS chunk update", () => {
+    expect(mergeChunkUpdates(css("1"), css("2"))).toEqual(css("2"));
+    expect(mergeChunkUpdates(css("1"), total)).toEqual(total);
+    expect(mergeChunkUpdates(css("1"), added)).toEqual(total);
+    expect(mergeChunkUpdates(css("1"), deleted)).toEqual(deleted);
+  });
+});
@@ -0,0 +1,12 @@
+/** @type {import('ts-jest/dist/types').InitialOptionsTsJest} */
+module.exports = {
+  preset: "ts-jest",
+  testEnvironment: "node",
+  globals: {
+    "ts-jest": {
+      // The runtime relies on ambient declarations which are only visible
+      // when type checking a whole project with `tsc`.
+      isolatedModules: true,
+    },
+  },
+};
@@ -2,6 +2,7 @@
 /// <reference path="../shared-node/node-utils.ts" />
 
 declare var RUNTIME_PUBLIC_PATH: string;
+declare var RUNTIME_CHUNK_LOADING: "require" | "import";
 
 enum SourceType {
   /**
@@ -27,10 +28,12 @@ type SourceInfo =
 
 type ExternalRequire = (id: ModuleId) => Exports | EsmNamespaceObject;
 type ExternalImport = (id: ModuleId) => Promise<Exports | EsmNamespaceObject>;
+type GetWorkerURL = (chunks: ChunkPath[], module: boolean) => string;
 
 interface TurbopackNodeBuildContext extends TurbopackBaseContext {
   x: ExternalRequire;
   y: ExternalImport;
+  b: GetWorkerURL;
 }
 
 type ModuleFactory = (
@@ -44,15 +47,47 @@ const RUNTIME_ROOT = path.resolve(__filename, relativePathToRuntimeRoot);
 
 const moduleFactories: ModuleFactories = Object.create(null);
 const moduleCache: ModuleCache = Object.create(null);
+/**
+ * Maps chunk paths to the paths of the emitted files, both relative to the
+ * output root. Chunks which are not in the map are loaded from their chunk
+ * path.
+ */
+const chunkPathMapping: Record<ChunkPath, string> = Object.create(null);
+const chunkLoads: Map<ChunkPath, Promise<void>> = new Map();
 
-function loadChunk(chunkData: ChunkData): void {
-  if (typeof chunkData === "string") {
-    return loadChunkPath(chunkData);
-  } else {
-    return loadChunkPath(chunkData.path);
+/**
+ * Registers the chunk path mapping of the manifest at `manifestPath`,
+ * relative to the output root. Deployment tooling can rewrite the manifest
+ * when it moves emitted chunks.
+ */
+function loadChunkManifest(manifestPath: string): void {
+  const manifest: Record<ChunkPath, string> = require(path.resolve(
+    RUNTIME_ROOT,
+    manifestPath
+  ));
+  Object.assign(chunkPathMapping, manifest);
+}
+
+function resolveChunkPath(chunkPath: ChunkPath): string {
+  return path.resolve(RUNTIME_ROOT, chunkPathMapping[chunkPath] ?? chunkPath);
+}
+
+function getChunkPath(chunkData: ChunkData): ChunkPath {
+  return typeof chunkData === "string" ? chunkData : chunkData.path;
+}
+
+function registerChunkModules(chunkModules: ModuleFactories): void {
+  for (const [moduleId, moduleFactory] of Object.entries(chunkModules)) {
+    if (!moduleFactories[moduleId]) {
+      moduleFactories[moduleId] = moduleFactory;
+    }
   }
 }
 
+function loadChunk(chunkData: ChunkData): void {
+  return loadChunkPath(getChunkPath(chunkData));
+}
+
 function loadChunkPath(chunkPath: ChunkPath): void {
   if (!chunkPath.endsWith(".js")) {
     // We only support loading JS chunks in Node.js.
@@ -60,29 +95,56 @@ function loadChunkPath(chunkPath: ChunkPath): void {
     return;
   }
 
-  const resolved = path.resolve(RUNTIME_ROOT, chunkPath);
-  const chunkModules: ModuleFactories = require(resolved);
-
-  for (const [moduleId, moduleFactory] of Object.entries(chunkModules)) {
-    if (!moduleFactories[moduleId]) {
-      moduleFactories[moduleId] = moduleFactory;
-    }
-  }
+  const chunkModules: ModuleFactories = require(resolveChunkPath(chunkPath));
+  registerChunkModules(chunkModules);
 }
 
 async function loadChunkAsync(
   source: SourceInfo,
   chunkData: ChunkData
 ): Promise<any> {
-  return new Promise<void>((resolve, reject) => {
-    try {
-      loadChunk(chunkData);
-    } catch (err) {
-      reject(err);
-      return;
-    }
-    resolve();
-  });
+  const chunkPath = getChunkPath(chunkData);
+  if (RUNTIME_CHUNK_LOADING === "require") {
+    return new Promise<void>((resolve, reject) => {
+      try {
+        loadChunkPath(chunkPath);
+      } catch (err) {
+        reject(err);
+        return;
+      }
+      resolve();
+    });
+  }
+
+  if (!chunkPath.endsWith(".js")) {
+    return;
+  }
+
+  let load = chunkLoads.get(chunkPath);
+  if (!load) {
+    const url = require("url").pathToFileURL(resolveChunkPath(chunkPath));
+    // Chunks are CommonJS modules, so their `module.exports` is the default
+    // export.
+    load = import(url.href).then(
+      ({ default: chunkModules }: { default: ModuleFactories }) => {
+        registerChunkModules(chunkModules);
+      }
+    );
+    // Allow retrying failed loads.
+    load.catch(() => chunkLoads.delete(chunkPath));
+    chunkLoads.set(chunkPath, load);
+  }
+  return load;
+}
+
+/**
+ * Returns the URL of the script of a worker, e.g. for `worker_threads`. The
+ * chunk group of a worker is a single entry chunk, which loads the runtime and
+ * the other chunks itself.
+ */
+function getWorkerURL(chunks: ChunkPath[], _module: boolean): string {
+  const entryChunk = chunks[chunks.length - 1];
+  return require("url").pathToFileURL(resolveChunkPath(entryChunk)).href;
 }
 
 function loadWebAssembly(chunkPath: ChunkPath, imports: WebAssembly.Imports) {
@@ -160,6 +222,8 @@ function instantiateModule(id: ModuleId, source: SourceInfo): Module {
       l: loadChunkAsync.bind(null, { type: SourceType.Parent, parentId: id }),
       w: loadWebAssembly,
       u: loadWebAssemblyModule,
+      b: getWorkerURL,
+      p: createHotState,
       g: globalThis,
       __dirname: module.id.replace(/(^|\/)[\/]+$/, ""),
     });
@@ -173,6 +237,9 @@ function instantiateModule(id: ModuleId, source: SourceInfo): Module {
     // in case of a circular dependency: cjs1 -> esm2 -> cjs1
     interopEsm(module.exports, module.namespaceObject);
   }
+  if (module.nodeNamespaceObject) {
+    interopEsm(module.exports, module.nodeNamespaceObject);
+  }
 
   return module;
 }
@@ -235,4 +302,5 @@ function getOrInstantiateRuntimeModule(
 module.exports = {
   getOrInstantiateRuntimeModule,
   loadChunk,
+  loadChunkManifest,
 };
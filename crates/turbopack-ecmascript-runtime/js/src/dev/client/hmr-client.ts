@@ -175,6 +175,10 @@ function mergeChunkListUpdates(
 
   return {
     type: "ChunkListUpdate",
+    protocolVersion: Math.max(
+      updateA.protocolVersion ?? 1,
+      updateB.protocolVersion ?? 1
+    ),
     chunks,
     merged,
   };
@@ -199,7 +203,8 @@ function mergeChunkListChunks(
   }
 
   for (const [chunkPath, chunkUpdateB] of Object.entries(chunksB)) {
-    if (chunks[chunkPath] == null) {
+    // Updates which cancelled each other out were removed above.
+    if (chunksA[chunkPath] == null) {
       chunks[chunkPath] = chunkUpdateB;
     }
   }
@@ -207,26 +212,52 @@ function mergeChunkListChunks(
   return chunks;
 }
 
-function mergeChunkUpdates(
+/**
+ * Merges two consecutive updates of the same chunk into one, or returns
+ * `undefined` when they cancel each other out.
+ */
+export function mergeChunkUpdates(
   updateA: ChunkUpdate,
   updateB: ChunkUpdate
 ): ChunkUpdate | undefined {
-  if (
-    (updateA.type === "added" && updateB.type === "deleted") ||
-    (updateA.type === "deleted" && updateB.type === "added")
-  ) {
-    return undefined;
-  }
-
-  if (updateA.type === "partial") {
-    invariant(updateA.instruction, "Partial updates are unsupported");
+  assertSupportedChunkUpdate(updateA);
+  assertSupportedChunkUpdate(updateB);
+
+  switch (updateB.type) {
+    case "deleted":
+      // A chunk which was added and deleted again was never loaded.
+      return updateA.type === "added" ? undefined : updateB;
+    case "added":
+    case "total":
+    case "partial":
+      switch (updateA.type) {
+        case "added":
+          // Loading the added chunk picks up the latest version anyway.
+          return updateA;
+        case "deleted":
+          // The chunk is still loaded, but its contents might have changed.
+          return { type: "total" };
+        case "total":
+          return updateA;
+        case "partial":
+          // CSS chunk updates replace the whole stylesheet, so the later one
+          // wins.
+          return updateB.type === "added" ? { type: "total" } : updateB;
+        default:
+          invariant(updateA, "Unknown chunk update type");
+      }
+    default:
+      invariant(updateB, "Unknown chunk update type");
   }
+}
 
-  if (updateB.type === "partial") {
-    invariant(updateB.instruction, "Partial updates are unsupported");
+function assertSupportedChunkUpdate(update: ChunkUpdate) {
+  if (
+    update.type === "partial" &&
+    update.instruction.type !== "CssChunkUpdate"
+  ) {
+    invariant(update.instruction, "Partial updates are unsupported");
   }
-
-  return undefined;
 }
 
 function mergeChunkListEcmascriptMergedUpdates(
@@ -280,7 +311,8 @@ function mergeEcmascriptChunksUpdates(
   }
 
   for (const [chunkPath, chunkUpdateB] of Object.entries(chunksB)) {
-    if (chunks[chunkPath] == null) {
+    // Updates which cancelled each other out were removed above.
+    if (chunksA[chunkPath] == null) {
       chunks[chunkPath] = chunkUpdateB;
     }
   }
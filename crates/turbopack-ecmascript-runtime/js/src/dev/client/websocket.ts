@@ -40,8 +40,21 @@ export type HMROptions = {
   assetPrefix: string;
   timeout?: number;
   log?: boolean;
+  /**
+   * Requests updates as gzip compressed binary messages, if the browser
+   * supports decompressing them.
+   */
+  compression?: boolean;
 };
 
+async function decodeMessage(data: string | Blob): Promise<string> {
+  if (typeof data === "string") {
+    return data;
+  }
+  const stream = data.stream().pipeThrough(new DecompressionStream("gzip"));
+  return new Response(stream).text();
+}
+
 export function connectHMR(options: HMROptions) {
   const { timeout = 5 * 1000 } = options;
 
@@ -60,16 +73,27 @@ export function connectHMR(options: HMROptions) {
       // lastActivity = Date.now()
     }
 
+    // Binary messages are decoded asynchronously, so messages are chained to
+    // be handled in the order they were received.
+    let pendingMessages = Promise.resolve();
+
     function handleMessage(event: MessageEvent) {
       // lastActivity = Date.now()
 
-      const message = {
-        type: "turbopack-message" as const,
-        data: JSON.parse(event.data),
-      };
-      eventCallbacks.forEach((cb) => {
-        cb(message);
-      });
+      pendingMessages = pendingMessages
+        .then(() => decodeMessage(event.data))
+        .then((data) => {
+          const message = {
+            type: "turbopack-message" as const,
+            data: JSON.parse(data),
+          };
+          eventCallbacks.forEach((cb) => {
+            cb(message);
+          });
+        })
+        .catch((err) => {
+          console.error("[HMR] failed to handle message", err);
+        });
     }
 
     // let timer: NodeJS.Timeout
@@ -91,7 +115,11 @@ export function connectHMR(options: HMROptions) {
       url = `${protocol}://${assetPrefix.split("://")[1]}`;
     }
 
-    source = new window.WebSocket(`${url}${options.path}`);
+    const compression =
+      options.compression && typeof DecompressionStream !== "undefined";
+    const query = compression ? "?encoding=gzip" : "";
+
+    source = new window.WebSocket(`${url}${options.path}${query}`);
     source.onopen = handleOnline;
     source.onerror = handleDisconnect;
     source.onmessage = handleMessage;
@@ -18,8 +18,35 @@ interface HotState {
   selfDeclined: boolean;
   selfInvalidated: boolean;
   disposeHandlers: ((data: object) => void)[];
+  /**
+   * Called with the exports of the new module instance after a self-accepted
+   * update (`import.meta.hot.accept(callback)`).
+   */
+  acceptCallbacks: ((exports: Exports) => void)[];
+  /**
+   * Called when the module is removed by an update
+   * (`import.meta.hot.prune(callback)`).
+   */
+  pruneHandlers: ((data: HotData) => void)[];
+  /**
+   * Handlers for updates of dependencies
+   * (`import.meta.hot.accept(deps, callback)`).
+   */
+  dependencyAcceptHandlers: DependencyAcceptHandler[];
+  /**
+   * Event listeners registered with `import.meta.hot.on`, which are removed
+   * when the module is disposed.
+   */
+  eventListeners: [string, HotEventListener][];
 }
 
+type DependencyAcceptHandler = {
+  dependencies: ModuleId[];
+  callback?: (outdatedDependencies: ModuleId[]) => void;
+};
+
+type HotEventListener = (payload: any) => void;
+
 type AcceptErrorHandler = (
   err: Error,
   context: { moduleId: ModuleId; dependencyId: string | number }
@@ -63,3 +90,40 @@ interface Hot {
 interface Module {
   hot: Hot;
 }
+
+/**
+ * The `import.meta.hot` API, following the semantics of Vite's HMR API.
+ */
+interface ImportMetaHot {
+  readonly data: HotData;
+
+  /**
+   * Accepts updates of the module itself, or of the given dependencies. The
+   * specifiers of the dependencies are replaced with their module ids at
+   * compile time.
+   */
+  accept: {
+    (callback?: (exports: Exports) => void): void;
+    (dep: ModuleId, callback?: (exports: Exports | undefined) => void): void;
+    (
+      deps: ModuleId[],
+      callback?: (exports: (Exports | undefined)[]) => void
+    ): void;
+  };
+
+  dispose: (callback: (data: HotData) => void) => void;
+
+  prune: (callback: (data: HotData) => void) => void;
+
+  decline: () => void;
+
+  invalidate: (message?: string) => void;
+
+  /**
+   * Listens to HMR events of the runtime: `vite:beforeUpdate`,
+   * `vite:afterUpdate`, `vite:beforeFullReload`, `vite:invalidate` and
+   * `vite:error`.
+   */
+  on: (event: string, callback: HotEventListener) => void;
+  off: (event: string, callback: HotEventListener) => void;
+}
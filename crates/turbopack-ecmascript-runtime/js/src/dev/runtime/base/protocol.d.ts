@@ -38,6 +38,11 @@ type PartialUpdate =
 
 type ChunkListUpdate = {
   type: "ChunkListUpdate";
+  /**
+   * The version of the update protocol, see `UPDATE_PROTOCOL_VERSION`.
+   * Missing in updates of servers which predate versioning.
+   */
+  protocolVersion?: number;
   chunks?: Record<ChunkPath, ChunkUpdate>;
   merged?: MergedChunkUpdate[];
 };
@@ -48,9 +53,22 @@ type ChunkUpdate =
     }
   | { type: "deleted" }
   | { type: "total" }
-  // We currently don't have any chunks that can be updated partially that can't
-  // be merged either. So these updates would go into `MergedChunkUpdate` instead.
-  | { type: "partial"; instruction: never };
+  | { type: "partial"; instruction: PartialChunkUpdate };
+
+type PartialChunkUpdate =
+  | CssChunkUpdate
+  | {
+      type: never;
+    };
+
+/**
+ * Replaces the stylesheet of a CSS chunk with the given version, without
+ * reloading the JS chunk it belongs to.
+ */
+type CssChunkUpdate = {
+  type: "CssChunkUpdate";
+  version: string;
+};
 
 type MergedChunkUpdate =
   | EcmascriptMergedUpdate
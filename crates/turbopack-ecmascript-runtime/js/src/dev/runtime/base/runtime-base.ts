@@ -20,6 +20,8 @@ type RefreshRuntimeGlobals =
   import("@next/react-refresh-utils/dist/runtime").RefreshRuntimeGlobals;
 
 declare var CHUNK_BASE_PATH: string;
+declare var CHUNK_CROSS_ORIGIN: string | null;
+declare var CHUNK_NONCE: () => string | undefined;
 declare var $RefreshHelpers$: RefreshRuntimeGlobals["$RefreshHelpers$"];
 declare var $RefreshReg$: RefreshRuntimeGlobals["$RefreshReg$"];
 declare var $RefreshSig$: RefreshRuntimeGlobals["$RefreshSig$"];
@@ -35,6 +37,8 @@ type RefreshHelpers = RefreshRuntimeGlobals["$RefreshHelpers$"];
 
 interface TurbopackDevBaseContext extends TurbopackBaseContext {
   k: RefreshContext;
+  p: PreserveHotState;
+  h: ImportMetaHot;
 }
 
 interface TurbopackDevContext extends TurbopackDevBaseContext {}
@@ -97,12 +101,18 @@ type SourceInfo =
 interface RuntimeBackend {
   registerChunk: (chunkPath: ChunkPath, params?: DevRuntimeParams) => void;
   loadChunk: (chunkPath: ChunkPath, source: SourceInfo) => Promise<void>;
-  reloadChunk?: (chunkPath: ChunkPath) => Promise<void>;
+  reloadChunk?: (chunkPath: ChunkPath, version?: string) => Promise<void>;
   unloadChunk?: (chunkPath: ChunkPath) => void;
 
   restart: () => void;
 }
 
+/**
+ * The newest version of the update protocol this runtime understands, see
+ * `turbopack_core::update_protocol::UPDATE_PROTOCOL_VERSION`.
+ */
+const UPDATE_PROTOCOL_VERSION = 1;
+
 const moduleFactories: ModuleFactories = Object.create(null);
 const moduleCache: ModuleCache = Object.create(null);
 /**
@@ -118,6 +128,10 @@ const moduleHotState: Map<Module, HotState> = new Map();
  * Modules that call `module.hot.invalidate()` (while being updated).
  */
 const queuedInvalidatedModules: Set<ModuleId> = new Set();
+/**
+ * Listeners of HMR events, see `import.meta.hot.on`.
+ */
+const hotEventListeners: Map<string, Set<HotEventListener>> = new Map();
 /**
  * Module IDs that are instantiated as part of the runtime of a chunk.
  */
@@ -313,6 +327,7 @@ function instantiateModule(id: ModuleId, source: SourceInfo): Module {
 
   moduleCache[id] = module;
   moduleHotState.set(module, hotState);
+  const importMetaHot = createImportMetaHot(module, hotState);
 
   // NOTE(alexkirsz) This can fail when the module encounters a runtime error.
   try {
@@ -339,6 +354,8 @@ function instantiateModule(id: ModuleId, source: SourceInfo): Module {
           u: loadWebAssemblyModule.bind(null, sourceInfo),
           g: globalThis,
           k: refresh,
+          p: preserveHotState.bind(null, module),
+          h: importMetaHot,
           __dirname: module.id.replace(/(^|\/)\/+$/, ""),
         })
       );
@@ -353,10 +370,32 @@ function instantiateModule(id: ModuleId, source: SourceInfo): Module {
     // in case of a circular dependency: cjs1 -> esm2 -> cjs1
     interopEsm(module.exports, module.namespaceObject);
   }
+  if (module.nodeNamespaceObject) {
+    interopEsm(module.exports, module.nodeNamespaceObject);
+  }
 
   return module;
 }
 
+/**
+ * Returns the value which the previous version of `module` preserved under
+ * `key` before it was disposed by an HMR update, or creates it. The value is
+ * preserved again when the module is disposed, so state like stores declared
+ * at the top level of a module survives updates.
+ */
+function preserveHotState<T>(
+  module: Module,
+  key: string,
+  create: () => T
+): T {
+  const data = module.hot.data as Record<string, unknown>;
+  const value = (key in data ? data[key] : create()) as T;
+  module.hot.dispose((data) => {
+    (data as Record<string, unknown>)[key] = value;
+  });
+  return value;
+}
+
 /**
  * NOTE(alexkirsz) Webpack has a "module execution" interception hook that
  * Next.js' React Refresh runtime hooks into to add module context to the
@@ -493,6 +532,7 @@ function computeOutdatedModules(
   modified: Map<ModuleId, EcmascriptModuleEntry>
 ): {
   outdatedModules: Set<ModuleId>;
+  outdatedDependencies: OutdatedDependencies;
   newModuleFactories: Map<ModuleId, ModuleFactory>;
 } {
   const newModuleFactories = new Map<ModuleId, ModuleFactory>();
@@ -503,19 +543,45 @@ function computeOutdatedModules(
     }
   }
 
-  const outdatedModules = computedInvalidatedModules(modified.keys());
+  const { outdatedModules, outdatedDependencies } = computedInvalidatedModules(
+    modified.keys()
+  );
 
   for (const [moduleId, entry] of modified) {
     newModuleFactories.set(moduleId, _eval(entry));
   }
 
-  return { outdatedModules, newModuleFactories };
+  return { outdatedModules, outdatedDependencies, newModuleFactories };
 }
 
-function computedInvalidatedModules(
-  invalidated: Iterable<ModuleId>
-): Set<ModuleId> {
+/**
+ * Maps modules to their dependencies which are outdated and whose updates
+ * the modules accepted.
+ */
+type OutdatedDependencies = Map<ModuleId, Set<ModuleId>>;
+
+function mergeOutdatedDependencies(
+  target: OutdatedDependencies,
+  source: OutdatedDependencies
+) {
+  for (const [moduleId, dependencies] of source) {
+    let targetDependencies = target.get(moduleId);
+    if (!targetDependencies) {
+      targetDependencies = new Set();
+      target.set(moduleId, targetDependencies);
+    }
+    for (const dependency of dependencies) {
+      targetDependencies.add(dependency);
+    }
+  }
+}
+
+function computedInvalidatedModules(invalidated: Iterable<ModuleId>): {
+  outdatedModules: Set<ModuleId>;
+  outdatedDependencies: OutdatedDependencies;
+} {
   const outdatedModules = new Set<ModuleId>();
+  const outdatedDependencies: OutdatedDependencies = new Map();
 
   for (const moduleId of invalidated) {
     const effect = getAffectedModuleEffects(moduleId);
@@ -537,17 +603,26 @@ function computedInvalidatedModules(
         for (const outdatedModuleId of effect.outdatedModules) {
           outdatedModules.add(outdatedModuleId);
         }
+        mergeOutdatedDependencies(
+          outdatedDependencies,
+          effect.outdatedDependencies
+        );
         break;
-      // TODO(alexkirsz) Dependencies: handle dependencies effects.
     }
   }
 
-  return outdatedModules;
+  return { outdatedModules, outdatedDependencies };
 }
 
+type OutdatedSelfAcceptedModule = {
+  moduleId: ModuleId;
+  errorHandler: true | Function;
+  acceptCallbacks: ((exports: Exports) => void)[];
+};
+
 function computeOutdatedSelfAcceptedModules(
   outdatedModules: Iterable<ModuleId>
-): { moduleId: ModuleId; errorHandler: true | Function }[] {
+): OutdatedSelfAcceptedModule[] {
   const outdatedSelfAcceptedModules = [];
   for (const moduleId of outdatedModules) {
     const module = moduleCache[moduleId];
@@ -556,6 +631,7 @@ function computeOutdatedSelfAcceptedModules(
       outdatedSelfAcceptedModules.push({
         moduleId,
         errorHandler: hotState.selfAccepted,
+        acceptCallbacks: hotState.acceptCallbacks,
       });
     }
   }
@@ -644,6 +720,16 @@ function disposeModule(moduleId: ModuleId, mode: "clear" | "replace") {
     disposeHandler(data);
   }
 
+  if (mode === "clear") {
+    for (const pruneHandler of hotState.pruneHandlers) {
+      pruneHandler(data);
+    }
+  }
+
+  for (const [event, listener] of hotState.eventListeners) {
+    hotEventListeners.get(event)?.delete(listener);
+  }
+
   // This used to warn in `getOrInstantiateModuleFromParent` when a disposed
   // module is still importing other modules.
   module.hot.active = false;
@@ -681,10 +767,8 @@ function disposeModule(moduleId: ModuleId, mode: "clear" | "replace") {
 }
 
 function applyPhase(
-  outdatedSelfAcceptedModules: {
-    moduleId: ModuleId;
-    errorHandler: true | Function;
-  }[],
+  outdatedSelfAcceptedModules: OutdatedSelfAcceptedModule[],
+  outdatedDependencies: OutdatedDependencies,
   newModuleFactories: Map<ModuleId, ModuleFactory>,
   outdatedModuleParents: Map<ModuleId, Array<ModuleId>>,
   reportError: (err: any) => void
@@ -696,15 +780,46 @@ function applyPhase(
 
   // TODO(alexkirsz) Run new runtime entries here.
 
-  // TODO(alexkirsz) Dependencies: call accept handlers for outdated deps.
+  // Call the accept handlers of modules for their outdated dependencies.
+  for (const [moduleId, dependencies] of outdatedDependencies) {
+    const module = moduleCache[moduleId];
+    const hotState = module && moduleHotState.get(module);
+    if (!hotState) {
+      continue;
+    }
+
+    for (const {
+      dependencies: accepted,
+      callback,
+    } of hotState.dependencyAcceptHandlers) {
+      const outdated = accepted.filter((dependency) =>
+        dependencies.has(dependency)
+      );
+      if (outdated.length === 0 || callback === undefined) {
+        continue;
+      }
+      try {
+        callback(outdated);
+      } catch (err) {
+        reportError(err);
+      }
+    }
+  }
 
   // Re-instantiate all outdated self-accepted modules.
-  for (const { moduleId, errorHandler } of outdatedSelfAcceptedModules) {
+  for (const {
+    moduleId,
+    errorHandler,
+    acceptCallbacks,
+  } of outdatedSelfAcceptedModules) {
     try {
-      instantiateModule(moduleId, {
+      const module = instantiateModule(moduleId, {
         type: SourceType.Update,
         parents: outdatedModuleParents.get(moduleId),
       });
+      for (const acceptCallback of acceptCallbacks) {
+        acceptCallback(module.exports);
+      }
     } catch (err) {
       if (typeof errorHandler === "function") {
         try {
@@ -741,6 +856,13 @@ function applyChunkListUpdate(
   chunkListPath: ChunkPath,
   update: ChunkListUpdate
 ) {
+  if ((update.protocolVersion ?? 1) > UPDATE_PROTOCOL_VERSION) {
+    // The update can't be understood by this runtime, a new one is loaded by
+    // restarting.
+    BACKEND.restart();
+    return;
+  }
+
   if (update.merged != null) {
     for (const merged of update.merged) {
       switch (merged.type) {
@@ -766,11 +888,21 @@ function applyChunkListUpdate(
           BACKEND.unloadChunk?.(chunkPath);
           break;
         case "partial":
-          invariant(
-            chunkUpdate.instruction,
-            (instruction) =>
-              `Unknown partial instruction: ${JSON.stringify(instruction)}.`
-          );
+          switch (chunkUpdate.instruction.type) {
+            case "CssChunkUpdate":
+              BACKEND.reloadChunk?.(
+                chunkPath,
+                chunkUpdate.instruction.version
+              );
+              break;
+            default:
+              invariant(
+                chunkUpdate.instruction,
+                (instruction) =>
+                  `Unknown partial instruction: ${JSON.stringify(instruction)}.`
+              );
+          }
+          break;
         default:
           invariant(
             chunkUpdate,
@@ -790,20 +922,31 @@ function applyEcmascriptMergedUpdate(
     entries,
     chunks
   );
-  const { outdatedModules, newModuleFactories } = computeOutdatedModules(
-    added,
-    modified
-  );
+  const { outdatedModules, outdatedDependencies, newModuleFactories } =
+    computeOutdatedModules(added, modified);
   const { disposedModules } = updateChunksPhase(chunksAdded, chunksDeleted);
 
-  applyInternal(outdatedModules, disposedModules, newModuleFactories);
+  applyInternal(
+    outdatedModules,
+    outdatedDependencies,
+    disposedModules,
+    newModuleFactories
+  );
 }
 
-function applyInvalidatedModules(outdatedModules: Set<ModuleId>) {
+function applyInvalidatedModules(
+  outdatedModules: Set<ModuleId>,
+  outdatedDependencies: OutdatedDependencies
+) {
   if (queuedInvalidatedModules.size > 0) {
-    computedInvalidatedModules(queuedInvalidatedModules).forEach((moduleId) => {
+    const invalidated = computedInvalidatedModules(queuedInvalidatedModules);
+    invalidated.outdatedModules.forEach((moduleId) => {
       outdatedModules.add(moduleId);
     });
+    mergeOutdatedDependencies(
+      outdatedDependencies,
+      invalidated.outdatedDependencies
+    );
 
     queuedInvalidatedModules.clear();
   }
@@ -813,10 +956,14 @@ function applyInvalidatedModules(outdatedModules: Set<ModuleId>) {
 
 function applyInternal(
   outdatedModules: Set<ModuleId>,
+  outdatedDependencies: OutdatedDependencies,
   disposedModules: Iterable<ModuleId>,
   newModuleFactories: Map<ModuleId, ModuleFactory>
 ) {
-  outdatedModules = applyInvalidatedModules(outdatedModules);
+  outdatedModules = applyInvalidatedModules(
+    outdatedModules,
+    outdatedDependencies
+  );
 
   const outdatedSelfAcceptedModules =
     computeOutdatedSelfAcceptedModules(outdatedModules);
@@ -834,6 +981,7 @@ function applyInternal(
 
   applyPhase(
     outdatedSelfAcceptedModules,
+    outdatedDependencies,
     newModuleFactories,
     outdatedModuleParents,
     reportError
@@ -844,7 +992,7 @@ function applyInternal(
   }
 
   if (queuedInvalidatedModules.size > 0) {
-    applyInternal(new Set(), [], new Map());
+    applyInternal(new Set(), new Map(), [], new Map());
   }
 }
 
@@ -942,10 +1090,12 @@ type ModuleEffect =
       type: "accepted";
       moduleId: ModuleId;
       outdatedModules: Set<ModuleId>;
+      outdatedDependencies: OutdatedDependencies;
     };
 
 function getAffectedModuleEffects(moduleId: ModuleId): ModuleEffect {
   const outdatedModules: Set<ModuleId> = new Set();
+  const outdatedDependencies: OutdatedDependencies = new Map();
 
   type QueueItem = { moduleId?: ModuleId; dependencyChain: ModuleId[] };
 
@@ -1010,8 +1160,20 @@ function getAffectedModuleEffects(moduleId: ModuleId): ModuleEffect {
         continue;
       }
 
-      // TODO(alexkirsz) Dependencies: check accepted and declined
-      // dependencies here.
+      const parentHotState = moduleHotState.get(parent)!;
+      if (
+        parentHotState.dependencyAcceptHandlers.some(({ dependencies }) =>
+          dependencies.includes(moduleId)
+        )
+      ) {
+        let parentOutdatedDependencies = outdatedDependencies.get(parentId);
+        if (!parentOutdatedDependencies) {
+          parentOutdatedDependencies = new Set();
+          outdatedDependencies.set(parentId, parentOutdatedDependencies);
+        }
+        parentOutdatedDependencies.add(moduleId);
+        continue;
+      }
 
       queue.push({
         moduleId: parentId,
@@ -1024,6 +1186,7 @@ function getAffectedModuleEffects(moduleId: ModuleId): ModuleEffect {
     type: "accepted",
     moduleId,
     outdatedModules,
+    outdatedDependencies,
   };
 }
 
@@ -1031,13 +1194,21 @@ function handleApply(chunkListPath: ChunkPath, update: ServerMessage) {
   switch (update.type) {
     case "partial": {
       // This indicates that the update is can be applied to the current state of the application.
-      applyUpdate(chunkListPath, update.instruction);
+      emitHotEvent("vite:beforeUpdate", update);
+      try {
+        applyUpdate(chunkListPath, update.instruction);
+      } catch (err) {
+        emitHotEvent("vite:error", { err });
+        throw err;
+      }
+      emitHotEvent("vite:afterUpdate", update);
       break;
     }
     case "restart": {
       // This indicates that there is no way to apply the update to the
       // current state of the application, and that the application must be
       // restarted.
+      emitHotEvent("vite:beforeFullReload", {});
       BACKEND.restart();
       break;
     }
@@ -1047,6 +1218,7 @@ function handleApply(chunkListPath: ChunkPath, update: ServerMessage) {
       // If it is a dynamic import, we simply discard all modules that the chunk has exclusive access to.
       // If it is a runtime chunk list, we restart the application.
       if (runtimeChunkLists.has(chunkListPath)) {
+        emitHotEvent("vite:beforeFullReload", {});
         BACKEND.restart();
       } else {
         disposeChunkList(chunkListPath);
@@ -1067,6 +1239,10 @@ function createModuleHot(
     selfDeclined: false,
     selfInvalidated: false,
     disposeHandlers: [],
+    acceptCallbacks: [],
+    pruneHandlers: [],
+    dependencyAcceptHandlers: [],
+    eventListeners: [],
   };
 
   const hot: Hot = {
@@ -1138,6 +1314,120 @@ function createModuleHot(
   return { hot, hotState };
 }
 
+/**
+ * Creates the `import.meta.hot` object of a module, which implements Vite's
+ * HMR API on top of the module's `module.hot` state.
+ */
+function createImportMetaHot(
+  module: Module,
+  hotState: HotState
+): ImportMetaHot {
+  const hot = module.hot;
+  let persistsData = false;
+
+  return {
+    // Unlike `module.hot.data`, which only contains what the dispose handlers
+    // of the previous instance stored, `import.meta.hot.data` is kept across
+    // updates.
+    get data() {
+      if (!persistsData) {
+        persistsData = true;
+        hot.dispose((data) => {
+          const nextData = data as Record<string, unknown>;
+          for (const [key, value] of Object.entries(hot.data)) {
+            if (!(key in nextData)) {
+              nextData[key] = value;
+            }
+          }
+        });
+      }
+      return hot.data;
+    },
+
+    accept: (
+      deps?: ModuleId | ModuleId[] | ((exports: Exports) => void),
+      callback?: (exports: any) => void
+    ) => {
+      if (deps === undefined || typeof deps === "function") {
+        hotState.selfAccepted = true;
+        if (deps !== undefined) {
+          hotState.acceptCallbacks.push(deps);
+        }
+        return;
+      }
+
+      // The specifiers were replaced with module ids at compile time.
+      const dependencies = Array.isArray(deps) ? deps : [deps];
+      hotState.dependencyAcceptHandlers.push({
+        dependencies,
+        callback:
+          callback &&
+          ((outdatedDependencies) => {
+            // Like in Vite, the callback receives the updated modules, and
+            // `undefined` for the dependencies which weren't updated.
+            const modules = dependencies.map((dependency) =>
+              outdatedDependencies.includes(dependency)
+                ? esmImport(module, dependency)
+                : undefined
+            );
+            callback(Array.isArray(deps) ? modules : modules[0]);
+          }),
+      });
+    },
+
+    dispose: (callback) => {
+      hotState.disposeHandlers.push(callback);
+    },
+
+    prune: (callback) => {
+      hotState.pruneHandlers.push(callback);
+    },
+
+    decline: () => {
+      hot.decline();
+    },
+
+    invalidate: (message) => {
+      emitHotEvent("vite:invalidate", { path: module.id, message });
+      hot.invalidate();
+    },
+
+    on: (event, callback) => {
+      let listeners = hotEventListeners.get(event);
+      if (!listeners) {
+        listeners = new Set();
+        hotEventListeners.set(event, listeners);
+      }
+      listeners.add(callback);
+      hotState.eventListeners.push([event, callback]);
+    },
+
+    off: (event, callback) => {
+      hotEventListeners.get(event)?.delete(callback);
+      hotState.eventListeners = hotState.eventListeners.filter(
+        ([e, c]) => e !== event || c !== callback
+      );
+    },
+  };
+}
+
+/**
+ * Calls the listeners registered for an HMR event with `import.meta.hot.on`.
+ */
+function emitHotEvent(event: string, payload: any) {
+  const listeners = hotEventListeners.get(event);
+  if (!listeners) {
+    return;
+  }
+  for (const listener of [...listeners]) {
+    try {
+      listener(payload);
+    } catch (err) {
+      console.error(`[turbopack] error in ${event} listener`, err);
+    }
+  }
+}
+
 /**
  * Adds a module to a chunk.
  */
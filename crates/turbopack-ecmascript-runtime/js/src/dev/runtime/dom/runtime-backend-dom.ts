@@ -14,10 +14,48 @@ type ChunkResolver = {
   promise: Promise<void>;
 };
 
+type GetWorkerBlobURL = (chunks: ChunkPath[], module: boolean) => string;
+
+interface TurbopackDevContext extends TurbopackDevBaseContext {
+  b: GetWorkerBlobURL;
+}
+
 let BACKEND: RuntimeBackend;
 
 function augmentContext(context: TurbopackDevBaseContext): TurbopackDevContext {
-  return context;
+  const domContext = context as TurbopackDevContext;
+  domContext.b = getWorkerBlobURL;
+  return domContext;
+}
+
+/**
+ * The blob URLs of worker scripts, by the chunks they load. They are reused so
+ * that all `SharedWorker`s created for the same entry share one worker, as
+ * workers are shared by their URL.
+ */
+const workerBlobURLs: Map<string, string> = new Map();
+
+/**
+ * Returns a blob URL for a worker script which loads all the given chunks.
+ *
+ * Classic workers load the chunks synchronously with `importScripts`, while
+ * module workers can't use `importScripts` and import them in order instead.
+ */
+function getWorkerBlobURL(chunks: ChunkPath[], module: boolean): string {
+  const key = JSON.stringify([module, chunks]);
+  let blobURL = workerBlobURLs.get(key);
+  if (blobURL == null) {
+    const chunkUrls = chunks.map(
+      (chunk) => new URL(getChunkRelativeUrl(chunk), location.origin).href
+    );
+    const bootstrap = module
+      ? `for (const url of ${JSON.stringify(chunkUrls)}) await import(url);`
+      : `importScripts(...${JSON.stringify(chunkUrls)});`;
+    const blob = new Blob([bootstrap], { type: "text/javascript" });
+    blobURL = URL.createObjectURL(blob);
+    workerBlobURLs.set(key, blobURL);
+  }
+  return blobURL;
 }
 
 function commonJsRequireContext(
@@ -110,7 +148,7 @@ async function loadWebAssemblyModule(
       }
     },
 
-    reloadChunk(chunkPath) {
+    reloadChunk(chunkPath, version) {
       return new Promise<void>((resolve, reject) => {
         if (!chunkPath.endsWith(".css")) {
           reject(new Error("The DOM backend can only reload CSS chunks"));
@@ -135,7 +173,12 @@ async function loadWebAssemblyModule(
 
         const link = document.createElement("link");
         link.rel = "stylesheet";
-        link.href = chunkUrl;
+        // The version makes sure the browser doesn't serve a cached stylesheet.
+        link.href =
+          version != null
+            ? `${chunkUrl}?v=${encodeURIComponent(version)}`
+            : chunkUrl;
+        setLoadingAttributes(link);
         link.onerror = () => {
           reject();
         };
@@ -228,6 +271,7 @@ async function loadWebAssemblyModule(
       const link = document.createElement("link");
       link.rel = "stylesheet";
       link.href = chunkUrl;
+      setLoadingAttributes(link);
       link.onerror = () => {
         resolver.reject();
       };
@@ -240,6 +284,7 @@ async function loadWebAssemblyModule(
     } else if (chunkPath.endsWith(".js")) {
       const script = document.createElement("script");
       script.src = chunkUrl;
+      setLoadingAttributes(script);
       // We'll only mark the chunk as loaded once the script has been executed,
       // which happens in `registerChunk`. Hence the absence of `resolve()` in
       // this branch.
@@ -255,6 +300,20 @@ async function loadWebAssemblyModule(
   }
 })();
 
+/**
+ * Sets the `crossorigin` and `nonce` attributes configured in the chunking
+ * context on a tag which loads a chunk.
+ */
+function setLoadingAttributes(element: HTMLScriptElement | HTMLLinkElement) {
+  if (CHUNK_CROSS_ORIGIN != null) {
+    element.crossOrigin = CHUNK_CROSS_ORIGIN;
+  }
+  const nonce = CHUNK_NONCE();
+  if (nonce != null) {
+    element.nonce = nonce;
+  }
+}
+
 function _eval({ code, url, map }: EcmascriptModuleEntry): ModuleFactory {
   code += `\n\n//# sourceURL=${location.origin}/${CHUNK_BASE_PATH}${url}`;
   if (map)
@@ -15,16 +15,22 @@ interface RequireContextEntry {
 
 type ExternalRequire = (id: ModuleId) => Exports | EsmNamespaceObject;
 type ExternalImport = (id: ModuleId) => Promise<Exports | EsmNamespaceObject>;
+type GetWorkerURL = (chunks: ChunkPath[], module: boolean) => string;
 
 interface TurbopackDevContext extends TurbopackDevBaseContext {
   x: ExternalRequire;
   y: ExternalImport;
+  b: GetWorkerURL;
 }
 
 function augmentContext(context: TurbopackDevBaseContext): TurbopackDevContext {
   const nodejsContext = context as TurbopackDevContext;
   nodejsContext.x = externalRequire;
   nodejsContext.y = externalImport;
+  nodejsContext.b = getWorkerURL.bind(null, {
+    type: SourceType.Parent,
+    parentId: context.m.id,
+  });
   return nodejsContext;
 }
 
@@ -48,6 +54,20 @@ function resolveChunkPath(chunkPath: ChunkPath, source: SourceInfo) {
   );
 }
 
+/**
+ * Returns the file URL of the chunk which evaluates the entry of a worker, e.g.
+ * for `worker_threads`. It loads the other chunks of the worker itself.
+ */
+function getWorkerURL(
+  source: SourceInfo,
+  chunks: ChunkPath[],
+  _module: boolean
+): string {
+  const entryChunk = chunks[chunks.length - 1];
+  return require("node:url").pathToFileURL(resolveChunkPath(entryChunk, source))
+    .href;
+}
+
 function loadWebAssembly(
   source: SourceInfo,
   chunkPath: ChunkPath,
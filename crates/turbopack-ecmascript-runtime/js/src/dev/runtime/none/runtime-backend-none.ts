@@ -13,10 +13,28 @@ type ChunkRunner = {
   runtimeModuleIds: ModuleId[];
 };
 
+type GetWorkerURL = (chunks: ChunkPath[], module: boolean) => string;
+
+interface TurbopackDevContext extends TurbopackDevBaseContext {
+  b: GetWorkerURL;
+}
+
 let BACKEND: RuntimeBackend;
 
 function augmentContext(context: TurbopackDevBaseContext): TurbopackDevContext {
-  return context;
+  const noneContext = context as TurbopackDevContext;
+  noneContext.b = getWorkerURL;
+  return noneContext;
+}
+
+// Code can't be loaded at runtime, e.g. in edge runtimes, so there is no
+// script a worker could be started with.
+function getWorkerURL(chunks: ChunkPath[], _module: boolean): string {
+  throw new Error(
+    `workers are not supported in this environment, the worker ${
+      chunks[chunks.length - 1]
+    } can't be started`
+  );
 }
 
 function commonJsRequireContext(
@@ -75,8 +93,16 @@ async function loadWebAssemblyModule(
       }
     },
 
-    loadChunk(chunkPath, fromChunkPath) {
-      throw new Error("chunk loading is not supported");
+    // Code can't be fetched or evaluated at runtime, e.g. in edge runtimes,
+    // so chunks can only be "loaded" when they were statically imported.
+    async loadChunk(chunkPath, _source) {
+      if (registeredChunks.has(chunkPath)) {
+        return;
+      }
+
+      throw new Error(
+        `chunk ${chunkPath} is not available, chunks can't be loaded at runtime in this environment and must be imported statically`
+      );
     },
 
     restart: () => {
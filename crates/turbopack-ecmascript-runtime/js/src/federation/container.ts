@@ -0,0 +1,243 @@
+/// <reference path="../library/runtime.ts" />
+
+/**
+ * The runtime of a module federation container, i.e. a `remoteEntry.mjs`.
+ *
+ * Hosts call `init` with their share scope, which maps the requests of shared
+ * packages to the versions provided by the host and all containers, and then
+ * `get` to load an exposed module.
+ *
+ * The container includes the library runtime, so all exposed modules and the
+ * fallbacks of the shared packages are instantiated by a single runtime, and
+ * the modules they have in common are only instantiated once. Each of them is
+ * a chunk group whose chunks are loaded on demand. The shared packages are
+ * external modules of the chunks, which are provided through
+ * `LIBRARY_EXTERNALS` once the versions to use have been negotiated.
+ */
+
+declare var CHUNK_PUBLIC_PATH: ChunkPath;
+
+/** A chunk of a chunk group, which registers its modules when called. */
+type FederationChunk = () => Promise<{
+  default: (register: typeof registerChunk) => void;
+}>;
+
+type FederationChunkGroup = {
+  /** The id of the module which is instantiated once the chunks are loaded. */
+  id: ModuleId;
+  chunks: FederationChunk[];
+};
+
+type ModuleNamespace = Record<string, any>;
+
+type SharedVersion = {
+  get: () => Promise<ModuleNamespace>;
+  /** The name of the container or host which provides the version. */
+  from: string;
+  loaded?: Promise<ModuleNamespace>;
+  /** The exports once the version was loaded, which hosts use synchronously. */
+  exports?: ModuleNamespace;
+};
+
+type ShareScope = Record<string, Record<string, SharedVersion>>;
+
+type SharedConfig = FederationChunkGroup & {
+  version: string;
+  requiredVersion?: string;
+  singleton?: boolean;
+};
+
+type Container = {
+  init: (shareScope: ShareScope) => void;
+  get: (name: string) => Promise<() => any>;
+};
+
+function parseVersion(version: string): number[] {
+  return version
+    .replace(/^[^\d]*/, "")
+    .split(/[.+-]/)
+    .slice(0, 3)
+    .map((part) => parseInt(part, 10) || 0);
+}
+
+function compareVersions(a: string, b: string): number {
+  const partsA = parseVersion(a);
+  const partsB = parseVersion(b);
+  for (let i = 0; i < 3; i++) {
+    const diff = (partsA[i] ?? 0) - (partsB[i] ?? 0);
+    if (diff !== 0) {
+      return diff;
+    }
+  }
+  return 0;
+}
+
+function satisfiesComparator(version: string, comparator: string): boolean {
+  if (comparator === "" || comparator === "*" || comparator === "x") {
+    return true;
+  }
+
+  const [, operator, expected] = /^(\^|~|>=|<=|>|<|=)?(.*)$/.exec(comparator)!;
+  const diff = compareVersions(version, expected);
+  switch (operator) {
+    case ">=":
+      return diff >= 0;
+    case ">":
+      return diff > 0;
+    case "<=":
+      return diff <= 0;
+    case "<":
+      return diff < 0;
+    case "^": {
+      // `^0.2.3` only allows patch updates, like npm does.
+      const actual = parseVersion(version);
+      const parts = parseVersion(expected);
+      const fixed = parts[0] !== 0 ? 1 : parts[1] !== 0 ? 2 : 3;
+      return (
+        diff >= 0 &&
+        actual.slice(0, fixed).join(".") === parts.slice(0, fixed).join(".")
+      );
+    }
+    case "~": {
+      const actual = parseVersion(version);
+      const parts = parseVersion(expected);
+      return diff >= 0 && actual[0] === parts[0] && actual[1] === parts[1];
+    }
+    default:
+      return diff === 0;
+  }
+}
+
+/**
+ * Checks `version` against a range of `^`, `~`, comparison, exact and `*`
+ * comparators, which may be combined with spaces and `||`, e.g.
+ * `>=16.8.0 <19 || ^19.0.0`.
+ */
+function satisfies(version: string, range: string | undefined): boolean {
+  if (range == null) {
+    return true;
+  }
+
+  return range.split("||").some((alternative) =>
+    alternative
+      .trim()
+      // `>= 16.8.0` is a single comparator
+      .replace(/([<>=^~]+)\s+/g, "$1")
+      .split(/\s+/)
+      .every((comparator) => satisfiesComparator(version, comparator))
+  );
+}
+
+async function loadChunkGroup(group: FederationChunkGroup): Promise<any> {
+  await Promise.all(
+    group.chunks.map(async (load) => (await load()).default(registerChunk))
+  );
+  // Async modules export a promise of their exports.
+  return await getOrInstantiateRuntimeModule(group.id, CHUNK_PUBLIC_PATH)
+    .exports;
+}
+
+function createFederationContainer(
+  name: string,
+  exposes: Record<string, FederationChunkGroup>,
+  shared: Record<string, SharedConfig>
+): Container {
+  let shareScope: ShareScope | undefined;
+  const resolvedShared: Map<string, Promise<ModuleNamespace>> = new Map();
+  const exposedExports: Map<string, Promise<any>> = new Map();
+
+  function init(scope: ShareScope) {
+    if (shareScope != null) {
+      return;
+    }
+    shareScope = scope;
+    for (const [request, config] of Object.entries(shared)) {
+      const versions = (scope[request] ??= {});
+      versions[config.version] ??= {
+        get: async () => {
+          // The fallback may depend on the shared packages before it, e.g.
+          // `react-dom` on `react`.
+          await provideExternals(request);
+          return loadChunkGroup(config);
+        },
+        from: name,
+      };
+    }
+  }
+
+  function selectVersion(request: string, config: SharedConfig): SharedVersion {
+    const versions = shareScope![request];
+    const available = Object.keys(versions).sort(compareVersions).reverse();
+
+    if (config.singleton) {
+      // All containers and the host must use the same version, which is the
+      // one that was loaded already or the highest one.
+      const version =
+        available.find((version) => versions[version].loaded != null) ??
+        available[0];
+      if (!satisfies(version, config.requiredVersion)) {
+        console.warn(
+          `${name}: the shared singleton ${request}@${version} does not satisfy the required version ${config.requiredVersion}`
+        );
+      }
+      return versions[version];
+    }
+
+    const version = available.find((version) =>
+      satisfies(version, config.requiredVersion ?? `^${config.version}`)
+    );
+    return versions[version ?? config.version];
+  }
+
+  function loadShared(request: string): Promise<ModuleNamespace> {
+    let promise = resolvedShared.get(request);
+    if (promise == null) {
+      const selected = selectVersion(request, shared[request]);
+      promise = selected.loaded ??= selected.get().then((exports) => {
+        selected.exports = exports;
+        return exports;
+      });
+      resolvedShared.set(request, promise);
+    }
+    return promise;
+  }
+
+  /**
+   * Provides the shared packages as external modules, in the order they are
+   * declared in, until `until` is reached.
+   */
+  async function provideExternals(until?: string) {
+    for (const request of Object.keys(shared)) {
+      if (request === until) {
+        return;
+      }
+      LIBRARY_EXTERNALS[request] ??= await loadShared(request);
+    }
+  }
+
+  async function get(exposeName: string): Promise<() => any> {
+    if (shareScope == null) {
+      init({});
+    }
+
+    const group = exposes[exposeName];
+    if (group == null) {
+      throw new Error(
+        `${name} does not expose ${exposeName}, it exposes ${Object.keys(
+          exposes
+        ).join(", ")}`
+      );
+    }
+
+    let promise = exposedExports.get(exposeName);
+    if (promise == null) {
+      promise = provideExternals().then(() => loadChunkGroup(group));
+      exposedExports.set(exposeName, promise);
+    }
+
+    const exports = await promise;
+    return () => exports;
+  }
+
+  return { init, get };
+}
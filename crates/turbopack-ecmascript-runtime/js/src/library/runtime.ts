@@ -0,0 +1,259 @@
+/// <reference path="../shared/runtime-utils.ts" />
+
+/**
+ * The runtime of a single-file library bundle, i.e. an IIFE or UMD bundle.
+ *
+ * All chunks are part of the bundle and register their modules with
+ * `registerChunk` before the entry is instantiated, so loading a chunk never
+ * has to fetch anything. Externals are provided by the wrapper of the bundle
+ * through `LIBRARY_EXTERNALS`, e.g. from globals, from `require()` calls or
+ * as the namespace objects of static imports in ES module bundles.
+ */
+
+declare var LIBRARY_EXTERNALS: Record<ModuleId, any>;
+
+enum SourceType {
+  /**
+   * The module was instantiated because it is the entry of the bundle.
+   */
+  Runtime = 0,
+  /**
+   * The module was instantiated because a parent module imported it.
+   */
+  Parent = 1,
+}
+
+type SourceInfo =
+  | {
+      type: SourceType.Runtime;
+      chunkPath: ChunkPath;
+    }
+  | {
+      type: SourceType.Parent;
+      parentId: ModuleId;
+    };
+
+interface TurbopackLibraryContext extends TurbopackBaseContext {
+  x: (
+    id: ModuleId,
+    esm?: boolean,
+    nodeInterop?: boolean
+  ) => Exports | EsmNamespaceObject;
+  y: (id: ModuleId) => Promise<Exports | EsmNamespaceObject>;
+}
+
+type ModuleFactory = (
+  this: Module["exports"],
+  context: TurbopackLibraryContext
+) => undefined;
+
+const moduleFactories: ModuleFactories = Object.create(null);
+const moduleCache: ModuleCache = Object.create(null);
+
+/**
+ * Called after a module was instantiated. ES module bundles update their
+ * exports with it, since the module may have changed the exports of the
+ * entry.
+ */
+let onModuleInstantiated = () => {};
+
+function registerChunk(chunkModules: ModuleFactories): void {
+  for (const [moduleId, moduleFactory] of Object.entries(chunkModules)) {
+    if (!moduleFactories[moduleId]) {
+      moduleFactories[moduleId] = moduleFactory;
+    }
+  }
+}
+
+async function loadChunkAsync(
+  _source: SourceInfo,
+  _chunkData: ChunkData
+): Promise<any> {
+  // All chunks have been registered when the bundle was evaluated.
+}
+
+function commonJsRequireContext(
+  entry: RequireContextEntry,
+  sourceModule: Module
+): Exports {
+  return commonJsRequire(sourceModule, entry.id());
+}
+
+function externalRequire(
+  id: ModuleId,
+  esm: boolean = false,
+  nodeInterop: boolean = false
+): Exports | EsmNamespaceObject {
+  if (!Object.prototype.hasOwnProperty.call(LIBRARY_EXTERNALS, id)) {
+    throw new Error(
+      `External module ${id} is not provided to the library, add it to its external globals`
+    );
+  }
+
+  const raw = LIBRARY_EXTERNALS[id];
+  // namespace objects of static imports are already ES modules
+  const isNamespace = raw != null && raw[Symbol.toStringTag] === "Module";
+  if (!esm) {
+    // `require()` of a CommonJS module returns its `module.exports`, which is
+    // the default export of its namespace object
+    return isNamespace && isCommonJsNamespace(raw) ? raw.default : raw;
+  }
+  if (isNamespace || (raw.__esModule && !nodeInterop)) {
+    return raw;
+  }
+
+  return interopEsm(raw, {}, !nodeInterop);
+}
+
+/**
+ * Whether the namespace object of a static import is the namespace object of
+ * a CommonJS module, i.e. all its named exports are properties of its default
+ * export.
+ */
+function isCommonJsNamespace(namespace: EsmNamespaceObject): boolean {
+  if (!("default" in namespace)) {
+    return false;
+  }
+  const exports = namespace.default;
+  return Object.keys(namespace).every(
+    (key) =>
+      key === "default" || (exports != null && exports[key] === namespace[key])
+  );
+}
+
+async function externalImport(
+  id: ModuleId
+): Promise<Exports | EsmNamespaceObject> {
+  return externalRequire(id, true);
+}
+
+function unsupportedWebAssembly(chunkPath: ChunkPath): never {
+  throw new Error(
+    `WebAssembly chunk ${chunkPath} can't be loaded in a library bundle`
+  );
+}
+
+function instantiateModule(id: ModuleId, source: SourceInfo): Module {
+  const moduleFactory = moduleFactories[id];
+  if (typeof moduleFactory !== "function") {
+    let instantiationReason;
+    switch (source.type) {
+      case SourceType.Runtime:
+        instantiationReason = `as the entry of chunk ${source.chunkPath}`;
+        break;
+      case SourceType.Parent:
+        instantiationReason = `because it was required from module ${source.parentId}`;
+        break;
+    }
+    throw new Error(
+      `Module ${id} was instantiated ${instantiationReason}, but the module factory is not part of the bundle.`
+    );
+  }
+
+  let parents: ModuleId[];
+  switch (source.type) {
+    case SourceType.Runtime:
+      parents = [];
+      break;
+    case SourceType.Parent:
+      parents = [source.parentId];
+      break;
+  }
+
+  const module: Module = {
+    exports: {},
+    error: undefined,
+    loaded: false,
+    id,
+    parents,
+    children: [],
+    namespaceObject: undefined,
+  };
+  moduleCache[id] = module;
+
+  try {
+    moduleFactory.call(module.exports, {
+      a: asyncModule.bind(null, module),
+      e: module.exports,
+      r: commonJsRequire.bind(null, module),
+      t: runtimeRequire,
+      x: externalRequire,
+      y: externalImport,
+      f: requireContext.bind(null, module),
+      i: esmImport.bind(null, module),
+      s: esmExport.bind(null, module, module.exports),
+      j: dynamicExport.bind(null, module, module.exports),
+      v: exportValue.bind(null, module),
+      n: exportNamespace.bind(null, module),
+      m: module,
+      c: moduleCache,
+      l: loadChunkAsync.bind(null, { type: SourceType.Parent, parentId: id }),
+      w: unsupportedWebAssembly,
+      u: unsupportedWebAssembly,
+      p: createHotState,
+      g: globalThis,
+      __dirname: "",
+    });
+  } catch (error) {
+    module.error = error as any;
+    throw error;
+  }
+
+  module.loaded = true;
+  if (module.namespaceObject && module.exports !== module.namespaceObject) {
+    // in case of a circular dependency: cjs1 -> esm2 -> cjs1
+    interopEsm(module.exports, module.namespaceObject);
+  }
+  if (module.nodeNamespaceObject) {
+    interopEsm(module.exports, module.nodeNamespaceObject);
+  }
+
+  onModuleInstantiated();
+
+  return module;
+}
+
+/**
+ * Retrieves a module from the cache, or instantiate it if it is not cached.
+ */
+function getOrInstantiateModuleFromParent(
+  id: ModuleId,
+  sourceModule: Module
+): Module {
+  const module = moduleCache[id];
+
+  if (sourceModule.children.indexOf(id) === -1) {
+    sourceModule.children.push(id);
+  }
+
+  if (module) {
+    if (module.parents.indexOf(sourceModule.id) === -1) {
+      module.parents.push(sourceModule.id);
+    }
+
+    return module;
+  }
+
+  return instantiateModule(id, {
+    type: SourceType.Parent,
+    parentId: sourceModule.id,
+  });
+}
+
+/**
+ * Retrieves a module from the cache, or instantiate it as a runtime module if it is not cached.
+ */
+function getOrInstantiateRuntimeModule(
+  moduleId: ModuleId,
+  chunkPath: ChunkPath
+): Module {
+  const module = moduleCache[moduleId];
+  if (module) {
+    if (module.error) {
+      throw module.error;
+    }
+    return module;
+  }
+
+  return instantiateModule(moduleId, { type: SourceType.Runtime, chunkPath });
+}
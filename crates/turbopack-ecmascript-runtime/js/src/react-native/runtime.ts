@@ -0,0 +1,203 @@
+/// <reference path="../shared/runtime-utils.ts" />
+
+/**
+ * The runtime of a single-file React Native bundle.
+ *
+ * All chunks are part of the bundle and register their modules with
+ * `registerChunk` before the entry is instantiated, so loading a chunk never
+ * has to fetch anything. There is no `require` and no file system, so
+ * externals and WebAssembly are not supported.
+ */
+
+enum SourceType {
+  /**
+   * The module was instantiated because it is the entry of the bundle.
+   */
+  Runtime = 0,
+  /**
+   * The module was instantiated because a parent module imported it.
+   */
+  Parent = 1,
+}
+
+type SourceInfo =
+  | {
+      type: SourceType.Runtime;
+      chunkPath: ChunkPath;
+    }
+  | {
+      type: SourceType.Parent;
+      parentId: ModuleId;
+    };
+
+interface TurbopackReactNativeContext extends TurbopackBaseContext {
+  x: (id: ModuleId) => never;
+  y: (id: ModuleId) => Promise<never>;
+}
+
+type ModuleFactory = (
+  this: Module["exports"],
+  context: TurbopackReactNativeContext
+) => undefined;
+
+const moduleFactories: ModuleFactories = Object.create(null);
+const moduleCache: ModuleCache = Object.create(null);
+
+function registerChunk(chunkModules: ModuleFactories): void {
+  for (const [moduleId, moduleFactory] of Object.entries(chunkModules)) {
+    if (!moduleFactories[moduleId]) {
+      moduleFactories[moduleId] = moduleFactory;
+    }
+  }
+}
+
+async function loadChunkAsync(
+  _source: SourceInfo,
+  _chunkData: ChunkData
+): Promise<any> {
+  // All chunks have been registered when the bundle was evaluated.
+}
+
+function commonJsRequireContext(
+  entry: RequireContextEntry,
+  sourceModule: Module
+): Exports {
+  return commonJsRequire(sourceModule, entry.id());
+}
+
+function externalRequire(id: ModuleId): never {
+  throw new Error(`External module ${id} can't be loaded in React Native`);
+}
+
+async function externalImport(id: ModuleId): Promise<never> {
+  return externalRequire(id);
+}
+
+function unsupportedWebAssembly(chunkPath: ChunkPath): never {
+  throw new Error(
+    `WebAssembly chunk ${chunkPath} can't be loaded in React Native`
+  );
+}
+
+function instantiateModule(id: ModuleId, source: SourceInfo): Module {
+  const moduleFactory = moduleFactories[id];
+  if (typeof moduleFactory !== "function") {
+    let instantiationReason;
+    switch (source.type) {
+      case SourceType.Runtime:
+        instantiationReason = `as the entry of chunk ${source.chunkPath}`;
+        break;
+      case SourceType.Parent:
+        instantiationReason = `because it was required from module ${source.parentId}`;
+        break;
+    }
+    throw new Error(
+      `Module ${id} was instantiated ${instantiationReason}, but the module factory is not part of the bundle.`
+    );
+  }
+
+  let parents: ModuleId[];
+  switch (source.type) {
+    case SourceType.Runtime:
+      parents = [];
+      break;
+    case SourceType.Parent:
+      parents = [source.parentId];
+      break;
+  }
+
+  const module: Module = {
+    exports: {},
+    error: undefined,
+    loaded: false,
+    id,
+    parents,
+    children: [],
+    namespaceObject: undefined,
+  };
+  moduleCache[id] = module;
+
+  try {
+    moduleFactory.call(module.exports, {
+      a: asyncModule.bind(null, module),
+      e: module.exports,
+      r: commonJsRequire.bind(null, module),
+      t: runtimeRequire,
+      x: externalRequire,
+      y: externalImport,
+      f: requireContext.bind(null, module),
+      i: esmImport.bind(null, module),
+      s: esmExport.bind(null, module, module.exports),
+      j: dynamicExport.bind(null, module, module.exports),
+      v: exportValue.bind(null, module),
+      n: exportNamespace.bind(null, module),
+      m: module,
+      c: moduleCache,
+      l: loadChunkAsync.bind(null, { type: SourceType.Parent, parentId: id }),
+      w: unsupportedWebAssembly,
+      u: unsupportedWebAssembly,
+      p: createHotState,
+      g: globalThis,
+      __dirname: "",
+    });
+  } catch (error) {
+    module.error = error as any;
+    throw error;
+  }
+
+  module.loaded = true;
+  if (module.namespaceObject && module.exports !== module.namespaceObject) {
+    // in case of a circular dependency: cjs1 -> esm2 -> cjs1
+    interopEsm(module.exports, module.namespaceObject);
+  }
+  if (module.nodeNamespaceObject) {
+    interopEsm(module.exports, module.nodeNamespaceObject);
+  }
+
+  return module;
+}
+
+/**
+ * Retrieves a module from the cache, or instantiate it if it is not cached.
+ */
+function getOrInstantiateModuleFromParent(
+  id: ModuleId,
+  sourceModule: Module
+): Module {
+  const module = moduleCache[id];
+
+  if (sourceModule.children.indexOf(id) === -1) {
+    sourceModule.children.push(id);
+  }
+
+  if (module) {
+    if (module.parents.indexOf(sourceModule.id) === -1) {
+      module.parents.push(sourceModule.id);
+    }
+
+    return module;
+  }
+
+  return instantiateModule(id, {
+    type: SourceType.Parent,
+    parentId: sourceModule.id,
+  });
+}
+
+/**
+ * Retrieves a module from the cache, or instantiate it as a runtime module if it is not cached.
+ */
+function getOrInstantiateRuntimeModule(
+  moduleId: ModuleId,
+  chunkPath: ChunkPath
+): Module {
+  const module = moduleCache[moduleId];
+  if (module) {
+    if (module.error) {
+      throw module.error;
+    }
+    return module;
+  }
+
+  return instantiateModule(moduleId, { type: SourceType.Runtime, chunkPath });
+}
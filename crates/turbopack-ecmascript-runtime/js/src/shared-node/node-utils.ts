@@ -19,7 +19,8 @@ function externalImport(id: ModuleId) {
 
 function externalRequire(
   id: ModuleId,
-  esm: boolean = false
+  esm: boolean = false,
+  nodeInterop: boolean = false
 ): Exports | EsmNamespaceObject {
   let raw;
   try {
@@ -32,11 +33,11 @@ function externalRequire(
     throw new Error(`Failed to load external module ${id}: ${err}`);
   }
 
-  if (!esm || raw.__esModule) {
+  if (!esm || (raw.__esModule && !nodeInterop)) {
     return raw;
   }
 
-  return interopEsm(raw, {}, true);
+  return interopEsm(raw, {}, !nodeInterop);
 }
 
 externalRequire.resolve = (
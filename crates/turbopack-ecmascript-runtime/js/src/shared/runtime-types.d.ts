@@ -22,7 +22,7 @@ type ChunkData =
 type CommonJsRequire = (moduleId: ModuleId) => Exports;
 type EsmImport = (
   moduleId: ModuleId,
-  allowExportDefault: boolean
+  nodeInterop?: boolean
 ) => EsmNamespaceObject | Promise<EsmNamespaceObject>;
 type EsmExport = (exportGetters: Record<string, () => any>) => void;
 type ExportValue = (value: any) => void;
@@ -35,6 +35,7 @@ type LoadWebAssembly = (
   imports: WebAssembly.Imports
 ) => Exports;
 type LoadWebAssemblyModule = (wasmChunkPath: ChunkPath) => WebAssembly.Module;
+type PreserveHotState = <T>(key: string, create: () => T) => T;
 
 type ModuleCache = Record<ModuleId, Module>;
 type ModuleFactories = Record<ModuleId, ModuleFactory>;
@@ -65,6 +66,7 @@ interface TurbopackBaseContext {
   l: LoadChunk;
   w: LoadWebAssembly;
   u: LoadWebAssemblyModule;
+  p: PreserveHotState;
   g: typeof globalThis;
   __dirname: string;
 }
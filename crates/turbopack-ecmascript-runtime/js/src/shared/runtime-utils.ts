@@ -30,6 +30,8 @@ interface BaseModule {
     | EsmNamespaceObject
     | Promise<EsmNamespaceObject>
     | AsyncModulePromise<EsmNamespaceObject>;
+  /** The namespace object of a CommonJS module with Node.js interop. */
+  nodeNamespaceObject?: EsmNamespaceObject;
   [REEXPORTED_OBJECTS]?: any[];
 }
 
@@ -196,18 +198,36 @@ function interopEsm(
   return ns;
 }
 
+/**
+ * @param nodeInterop
+ *   * `false`: CommonJS modules marked with `__esModule` have their `default`
+ *     property as default export, like Babel's interop
+ *   * `true`: CommonJS modules always have `module.exports` as default export,
+ *     like Node.js
+ */
 function esmImport(
   sourceModule: Module,
-  id: ModuleId
+  id: ModuleId,
+  nodeInterop?: boolean
 ): Exclude<Module["namespaceObject"], undefined> {
   const module = getOrInstantiateModuleFromParent(id, sourceModule);
   if (module.error) throw module.error;
 
-  // any ES module has to have `module.namespaceObject` defined.
-  if (module.namespaceObject) return module.namespaceObject;
+  // any ES module has `module.exports` as `module.namespaceObject`.
+  if (module.namespaceObject && module.namespaceObject === module.exports) {
+    return module.namespaceObject;
+  }
 
   // only ESM can be an async module, so we don't need to worry about exports being a promise here.
   const raw = module.exports;
+  if (nodeInterop) {
+    if (!module.nodeNamespaceObject) {
+      module.nodeNamespaceObject = interopEsm(raw, {}, false);
+    }
+    return module.nodeNamespaceObject;
+  }
+
+  if (module.namespaceObject) return module.namespaceObject;
   return (module.namespaceObject = interopEsm(
     raw,
     {},
@@ -267,6 +287,14 @@ function requireContext(
   return requireContext;
 }
 
+/**
+ * Creates the value of a top level variable which is preserved across HMR
+ * updates. Runtimes without HMR support have nothing to preserve.
+ */
+function createHotState<T>(_key: string, create: () => T): T {
+  return create();
+}
+
 /**
  * Returns the path of a chunk defined by its data.
  */
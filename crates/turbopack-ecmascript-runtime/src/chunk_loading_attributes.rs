@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use turbo_tasks::trace::TraceRawVcs;
+
+/// The `crossorigin` attribute of `<script>` and `<link>` tags which are
+/// inserted to load chunks.
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, TraceRawVcs,
+)]
+pub enum CrossOrigin {
+    Anonymous,
+    UseCredentials,
+}
+
+impl CrossOrigin {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CrossOrigin::Anonymous => "anonymous",
+            CrossOrigin::UseCredentials => "use-credentials",
+        }
+    }
+}
+
+/// Where the runtime reads the CSP nonce of inserted `<script>` and `<link>`
+/// tags from.
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, TraceRawVcs,
+)]
+pub enum NonceSource {
+    /// The global variable with this name, read whenever a chunk is loaded.
+    Global(String),
+    /// The `nonce` of the `<script>` tag which loaded the runtime.
+    CurrentScript,
+}
+
+/// Attributes which are set on `<script>` and `<link>` tags inserted by the
+/// runtime to load chunks.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Default, Clone, Hash)]
+pub struct ChunkLoadingAttributes {
+    pub cross_origin: Option<CrossOrigin>,
+    pub nonce_source: Option<NonceSource>,
+}
@@ -8,15 +8,25 @@ use turbopack_core::{
     context::AssetContext,
     environment::{ChunkLoading, Environment},
 };
-use turbopack_ecmascript::utils::StringifyJs;
+use turbopack_ecmascript::{chunk::PUBLIC_PATH_GLOBAL, utils::StringifyJs};
 
-use crate::{asset_context::get_runtime_asset_context, embed_js::embed_static_code};
+use crate::{
+    asset_context::get_runtime_asset_context,
+    chunk_loading_attributes::{ChunkLoadingAttributes, NonceSource},
+    embed_js::embed_static_code,
+};
 
 /// Returns the code for the development ECMAScript runtime.
+///
+/// With `deferred_public_path`, chunks are loaded relative to the
+/// `__turbopack_public_path__` global, falling back to `chunk_base_path`.
+/// `chunk_loading_attributes` only affect the DOM runtime.
 #[turbo_tasks::function]
 pub async fn get_dev_runtime_code(
     environment: Vc<Environment>,
     chunk_base_path: Vc<Option<String>>,
+    deferred_public_path: bool,
+    chunk_loading_attributes: Vc<ChunkLoadingAttributes>,
 ) -> Result<Vc<Code>> {
     let asset_context = get_runtime_asset_context(environment);
 
@@ -42,6 +52,33 @@ pub async fn get_dev_runtime_code(
         },
     );
 
+    let chunk_base_path = chunk_base_path.await?;
+    let chunk_base_path = StringifyJs(if let Some(chunk_base_path) = &*chunk_base_path {
+        chunk_base_path.as_str()
+    } else {
+        ""
+    });
+    let chunk_base_path = if deferred_public_path {
+        format!("globalThis.{PUBLIC_PATH_GLOBAL} ?? {chunk_base_path}")
+    } else {
+        chunk_base_path.to_string()
+    };
+
+    let chunk_loading_attributes = chunk_loading_attributes.await?;
+    let chunk_cross_origin = match chunk_loading_attributes.cross_origin {
+        Some(cross_origin) => StringifyJs(cross_origin.as_str()).to_string(),
+        None => "null".to_string(),
+    };
+    let chunk_nonce = match &chunk_loading_attributes.nonce_source {
+        Some(NonceSource::Global(name)) => format!("() => globalThis[{}]", StringifyJs(name)),
+        // `document.currentScript` is only set while the runtime is evaluated.
+        Some(NonceSource::CurrentScript) => "((nonce) => () => nonce)(typeof document === \
+                                             \"object\" ? document.currentScript?.nonce : \
+                                             undefined)"
+            .to_string(),
+        None => "() => undefined".to_string(),
+    };
+
     let mut code: CodeBuilder = CodeBuilder::default();
 
     writedoc!(
@@ -53,12 +90,12 @@ pub async fn get_dev_runtime_code(
             }}
 
             const CHUNK_BASE_PATH = {};
+            const CHUNK_CROSS_ORIGIN = {};
+            const CHUNK_NONCE = {};
         "#,
-        StringifyJs(if let Some(chunk_base_path) = &*chunk_base_path.await? {
-            chunk_base_path.as_str()
-        } else {
-            ""
-        })
+        chunk_base_path,
+        chunk_cross_origin,
+        chunk_nonce
     )?;
 
     code.push_code(&*shared_runtime_utils_code.await?);
@@ -0,0 +1,28 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbopack_core::{
+    code_builder::{Code, CodeBuilder},
+    environment::Environment,
+};
+
+use crate::{
+    asset_context::get_runtime_asset_context, embed_js::embed_static_code,
+    library_runtime::get_library_runtime_code,
+};
+
+/// Returns the code for the runtime of module federation containers, which
+/// defines `createFederationContainer` on top of the library runtime.
+#[turbo_tasks::function]
+pub async fn get_federation_container_runtime_code(
+    environment: Vc<Environment>,
+) -> Result<Vc<Code>> {
+    let asset_context = get_runtime_asset_context(environment);
+
+    let runtime_code = embed_static_code(asset_context, "federation/container.ts".to_string());
+
+    let mut code = CodeBuilder::default();
+    code.push_code(&*get_library_runtime_code(environment).await?);
+    code.push_code(&*runtime_code.await?);
+
+    Ok(Code::cell(code.build()))
+}
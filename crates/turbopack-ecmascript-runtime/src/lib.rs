@@ -3,17 +3,25 @@
 
 pub(crate) mod asset_context;
 pub(crate) mod build_runtime;
+pub(crate) mod chunk_loading_attributes;
 pub(crate) mod dev_runtime;
 #[cfg(feature = "test")]
 pub(crate) mod dummy_runtime;
 pub(crate) mod embed_js;
+pub(crate) mod federation_runtime;
+pub(crate) mod library_runtime;
+pub(crate) mod react_native_runtime;
 pub(crate) mod runtime_type;
 
 pub use build_runtime::get_build_runtime_code;
+pub use chunk_loading_attributes::{ChunkLoadingAttributes, CrossOrigin, NonceSource};
 pub use dev_runtime::get_dev_runtime_code;
 #[cfg(feature = "test")]
 pub use dummy_runtime::get_dummy_runtime_code;
 pub use embed_js::{embed_file, embed_file_path, embed_fs};
+pub use federation_runtime::get_federation_container_runtime_code;
+pub use library_runtime::get_library_runtime_code;
+pub use react_native_runtime::get_react_native_runtime_code;
 pub use runtime_type::RuntimeType;
 
 pub fn register() {
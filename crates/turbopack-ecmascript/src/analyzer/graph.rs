@@ -163,6 +163,33 @@ pub enum Effect {
         span: Span,
         in_try: bool,
     },
+    /// A reference to `import.meta.resolve(...)`.
+    ImportMetaResolve {
+        input: JsValue,
+        /// The ast path to the `import.meta.resolve(...)` call expression.
+        ast_path: Vec<AstParentKind>,
+        span: Span,
+        in_try: bool,
+    },
+    /// A reference to `import.meta.hot.accept(deps, ...)` with static
+    /// dependency specifiers.
+    ImportMetaHotAccept {
+        /// The specifiers of the dependencies. `None` for a single specifier
+        /// instead of an array, otherwise the index in the array.
+        deps: Vec<(String, Option<usize>)>,
+        /// The ast path to the `import.meta.hot.accept(...)` call expression.
+        ast_path: Vec<AstParentKind>,
+        span: Span,
+        in_try: bool,
+    },
+    /// A reference to `import.meta.env.KEY`.
+    ImportMetaEnv {
+        key: String,
+        /// The ast path to the `import.meta.env.KEY` member expression.
+        ast_path: Vec<AstParentKind>,
+        span: Span,
+        in_try: bool,
+    },
     /// A reference to `new URL(..., import.meta.url)`.
     Url {
         input: JsValue,
@@ -170,6 +197,28 @@ pub enum Effect {
         span: Span,
         in_try: bool,
     },
+    /// A reference to `new Worker(new URL(..., import.meta.url), options)` or
+    /// `new SharedWorker(...)`.
+    Worker {
+        input: JsValue,
+        /// Whether this is a `SharedWorker` instead of a dedicated `Worker`.
+        shared: bool,
+        /// Whether `{ type: "module" }` was passed in the worker options.
+        module: bool,
+        /// The ast path to the `new Worker(...)` expression.
+        ast_path: Vec<AstParentKind>,
+        span: Span,
+        in_try: bool,
+    },
+    /// A reference to
+    /// `navigator.serviceWorker.register(new URL(..., import.meta.url))`.
+    ServiceWorker {
+        input: JsValue,
+        /// The ast path to the `register(...)` call expression.
+        ast_path: Vec<AstParentKind>,
+        span: Span,
+        in_try: bool,
+    },
 }
 
 impl Effect {
@@ -206,7 +255,12 @@ impl Effect {
             }
             Effect::ImportedBinding { .. } => {}
             Effect::ImportMeta { .. } => {}
-            Effect::Url { input, .. } => {
+            Effect::ImportMetaEnv { .. } => {}
+            Effect::ImportMetaHotAccept { .. } => {}
+            Effect::ImportMetaResolve { input, .. }
+            | Effect::Url { input, .. }
+            | Effect::Worker { input, .. }
+            | Effect::ServiceWorker { input, .. } => {
                 input.normalize();
             }
         }
@@ -248,6 +302,7 @@ pub fn create_graph(m: &Program, eval_context: &EvalContext) -> VarGraph {
             current_value: Default::default(),
             cur_fn_return_values: Default::default(),
             cur_fn_ident: Default::default(),
+            worker_url_span: Default::default(),
         },
         &mut Default::default(),
     );
@@ -625,6 +680,11 @@ struct Analyzer<'a> {
     cur_fn_return_values: Option<Vec<JsValue>>,
 
     cur_fn_ident: u32,
+
+    /// The span of a `new URL(...)` expression which has already been handled
+    /// as part of a `new Worker(...)` expression or a service worker
+    /// registration.
+    worker_url_span: Option<Span>,
 }
 
 pub fn as_parent_path(ast_path: &AstNodePath<AstParentNodeRef<'_>>) -> Vec<AstParentKind> {
@@ -655,6 +715,166 @@ pub fn is_in_try(ast_path: &AstNodePath<AstParentNodeRef<'_>>) -> bool {
         .unwrap_or(false)
 }
 
+/// Returns the input of a `new URL(input, import.meta.url)` expression.
+fn import_meta_url_input(new_expr: &NewExpr, unresolved_mark: Mark) -> Option<&Expr> {
+    let box Expr::Ident(callee) = &new_expr.callee else {
+        return None;
+    };
+    if &*callee.sym != "URL" || !is_unresolved(callee, unresolved_mark) {
+        return None;
+    }
+    let args = new_expr.args.as_ref()?;
+    if args.len() != 2 {
+        return None;
+    }
+    if let Expr::Member(MemberExpr {
+        obj:
+            box Expr::MetaProp(MetaPropExpr {
+                kind: MetaPropKind::ImportMeta,
+                ..
+            }),
+        prop: MemberProp::Ident(prop),
+        ..
+    }) = &*args[1].expr
+    {
+        if &*prop.sym == "url" {
+            return Some(&args[0].expr);
+        }
+    }
+    None
+}
+
+/// Whether the expression is `import.meta.<name>`.
+fn is_import_meta_member(expr: &Expr, name: &str) -> bool {
+    matches!(
+        expr,
+        Expr::Member(MemberExpr {
+            obj: box Expr::MetaProp(MetaPropExpr {
+                kind: MetaPropKind::ImportMeta,
+                ..
+            }),
+            prop: MemberProp::Ident(prop),
+            ..
+        }) if &*prop.sym == name
+    )
+}
+
+/// Returns the argument of an `import.meta.resolve(specifier)` call.
+fn import_meta_resolve_arg(call_expr: &CallExpr) -> Option<&Expr> {
+    let Callee::Expr(callee) = &call_expr.callee else {
+        return None;
+    };
+    if !is_import_meta_member(callee, "resolve") {
+        return None;
+    }
+    match &*call_expr.args {
+        [ExprOrSpread { spread: None, expr }] => Some(expr),
+        _ => None,
+    }
+}
+
+/// Returns the static dependency specifiers of an
+/// `import.meta.hot.accept(deps, ...)` call, see [Effect::ImportMetaHotAccept].
+fn import_meta_hot_accept_deps(call_expr: &CallExpr) -> Option<Vec<(String, Option<usize>)>> {
+    let Callee::Expr(box Expr::Member(MemberExpr {
+        obj,
+        prop: MemberProp::Ident(accept),
+        ..
+    })) = &call_expr.callee
+    else {
+        return None;
+    };
+    if &*accept.sym != "accept" || !is_import_meta_member(obj, "hot") {
+        return None;
+    }
+    match call_expr.args.first() {
+        Some(ExprOrSpread {
+            spread: None,
+            expr: box Expr::Lit(Lit::Str(str)),
+        }) => Some(vec![(str.value.to_string(), None)]),
+        Some(ExprOrSpread {
+            spread: None,
+            expr: box Expr::Array(array),
+        }) => array
+            .elems
+            .iter()
+            .enumerate()
+            .map(|(i, elem)| match elem {
+                Some(ExprOrSpread {
+                    spread: None,
+                    expr: box Expr::Lit(Lit::Str(str)),
+                }) => Some((str.value.to_string(), Some(i))),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Returns the key of an `import.meta.env.KEY` or `import.meta.env["KEY"]`
+/// member expression.
+fn import_meta_env_key(member_expr: &MemberExpr) -> Option<&str> {
+    if !is_import_meta_member(&member_expr.obj, "env") {
+        return None;
+    }
+    match &member_expr.prop {
+        MemberProp::Ident(ident) => Some(&*ident.sym),
+        MemberProp::Computed(ComputedPropName {
+            expr: box Expr::Lit(Lit::Str(str)),
+            ..
+        }) => Some(&*str.value),
+        _ => None,
+    }
+}
+
+/// Returns the first argument of a `navigator.serviceWorker.register(...)`
+/// call.
+fn service_worker_register_arg(call_expr: &CallExpr, unresolved_mark: Mark) -> Option<&Expr> {
+    let Callee::Expr(box Expr::Member(MemberExpr {
+        obj:
+            box Expr::Member(MemberExpr {
+                obj: box Expr::Ident(navigator),
+                prop: MemberProp::Ident(service_worker),
+                ..
+            }),
+        prop: MemberProp::Ident(register),
+        ..
+    })) = &call_expr.callee
+    else {
+        return None;
+    };
+    if &*navigator.sym != "navigator"
+        || !is_unresolved(navigator, unresolved_mark)
+        || &*service_worker.sym != "serviceWorker"
+        || &*register.sym != "register"
+    {
+        return None;
+    }
+    match call_expr.args.first() {
+        Some(ExprOrSpread { spread: None, expr }) => Some(expr),
+        _ => None,
+    }
+}
+
+/// Checks if the options passed to a `Worker` constructor contain
+/// `type: "module"`.
+fn is_module_worker_options(options: &Expr) -> bool {
+    let Expr::Object(ObjectLit { props, .. }) = options else {
+        return false;
+    };
+    props.iter().any(|prop| {
+        let PropOrSpread::Prop(box Prop::KeyValue(KeyValueProp { key, value })) = prop else {
+            return false;
+        };
+        let is_type_key = match key {
+            PropName::Ident(ident) => &*ident.sym == "type",
+            PropName::Str(str) => &*str.value == "type",
+            _ => false,
+        };
+        is_type_key && matches!(&**value, Expr::Lit(Lit::Str(str)) if &*str.value == "module")
+    })
+}
+
 impl Analyzer<'_> {
     fn add_value(&mut self, id: Id, value: JsValue) {
         if let Some(prev) = self.data.values.get_mut(&id) {
@@ -1047,6 +1267,40 @@ impl VisitAstPath for Analyzer<'_> {
         n: &'ast CallExpr,
         ast_path: &mut AstNodePath<AstParentNodeRef<'r>>,
     ) {
+        // navigator.serviceWorker.register(new URL("path", import.meta.url))
+        if let Some(Expr::New(url_expr)) =
+            service_worker_register_arg(n, self.eval_context.unresolved_mark)
+        {
+            if let Some(input) = import_meta_url_input(url_expr, self.eval_context.unresolved_mark)
+            {
+                self.worker_url_span = Some(url_expr.span);
+                self.add_effect(Effect::ServiceWorker {
+                    input: self.eval_context.eval(input),
+                    ast_path: as_parent_path(ast_path),
+                    span: n.span(),
+                    in_try: is_in_try(ast_path),
+                });
+            }
+        }
+
+        if let Some(deps) = import_meta_hot_accept_deps(n) {
+            self.add_effect(Effect::ImportMetaHotAccept {
+                deps,
+                ast_path: as_parent_path(ast_path),
+                span: n.span(),
+                in_try: is_in_try(ast_path),
+            });
+        }
+
+        if let Some(input) = import_meta_resolve_arg(n) {
+            self.add_effect(Effect::ImportMetaResolve {
+                input: self.eval_context.eval(input),
+                ast_path: as_parent_path(ast_path),
+                span: n.span(),
+                in_try: is_in_try(ast_path),
+            });
+        }
+
         // We handle `define(function (require) {})` here.
         if let Callee::Expr(callee) = &n.callee {
             if n.args.len() == 1 {
@@ -1143,24 +1397,17 @@ impl VisitAstPath for Analyzer<'_> {
         new_expr: &'ast NewExpr,
         ast_path: &mut AstNodePath<AstParentNodeRef<'r>>,
     ) {
-        // new URL("path", import.meta.url)
         if let box Expr::Ident(ref callee) = &new_expr.callee {
-            if &*callee.sym == "URL" && is_unresolved(callee, self.eval_context.unresolved_mark) {
-                if let Some(args) = &new_expr.args {
-                    if args.len() == 2 {
-                        if let Expr::Member(MemberExpr {
-                            obj:
-                                box Expr::MetaProp(MetaPropExpr {
-                                    kind: MetaPropKind::ImportMeta,
-                                    ..
-                                }),
-                            prop: MemberProp::Ident(prop),
-                            ..
-                        }) = &*args[1].expr
+            if is_unresolved(callee, self.eval_context.unresolved_mark) {
+                match &*callee.sym {
+                    // new URL("path", import.meta.url)
+                    "URL" => {
+                        if let Some(input) =
+                            import_meta_url_input(new_expr, self.eval_context.unresolved_mark)
                         {
-                            if &*prop.sym == "url" {
+                            if self.worker_url_span != Some(new_expr.span) {
                                 self.add_effect(Effect::Url {
-                                    input: self.eval_context.eval(&args[0].expr),
+                                    input: self.eval_context.eval(input),
                                     ast_path: as_parent_path(ast_path),
                                     span: new_expr.span(),
                                     in_try: is_in_try(ast_path),
@@ -1168,6 +1415,34 @@ impl VisitAstPath for Analyzer<'_> {
                             }
                         }
                     }
+                    // new Worker(new URL("path", import.meta.url), { type: "module" })
+                    "Worker" | "SharedWorker" => {
+                        if let Some(args) = &new_expr.args {
+                            if let Some(ExprOrSpread {
+                                spread: None,
+                                expr: box Expr::New(url_expr),
+                            }) = args.first()
+                            {
+                                if let Some(input) = import_meta_url_input(
+                                    url_expr,
+                                    self.eval_context.unresolved_mark,
+                                ) {
+                                    self.worker_url_span = Some(url_expr.span);
+                                    self.add_effect(Effect::Worker {
+                                        input: self.eval_context.eval(input),
+                                        shared: &*callee.sym == "SharedWorker",
+                                        module: args.get(1).map_or(false, |options| {
+                                            is_module_worker_options(&options.expr)
+                                        }),
+                                        ast_path: as_parent_path(ast_path),
+                                        span: new_expr.span(),
+                                        in_try: is_in_try(ast_path),
+                                    });
+                                }
+                            }
+                        }
+                    }
+                    _ => {}
                 }
             }
         }
@@ -1179,6 +1454,14 @@ impl VisitAstPath for Analyzer<'_> {
         member_expr: &'ast MemberExpr,
         ast_path: &mut AstNodePath<AstParentNodeRef<'r>>,
     ) {
+        if let Some(key) = import_meta_env_key(member_expr) {
+            self.add_effect(Effect::ImportMetaEnv {
+                key: key.to_string(),
+                ast_path: as_parent_path(ast_path),
+                span: member_expr.span(),
+                in_try: is_in_try(ast_path),
+            });
+        }
         self.check_member_expr_for_effects(member_expr, ast_path);
         member_expr.visit_children_with_path(self, ast_path);
     }
@@ -36,6 +36,7 @@ pub mod builtin;
 pub mod graph;
 pub mod imports;
 pub mod linker;
+pub(crate) mod startup_cost;
 pub mod top_level_await;
 pub mod well_known;
 
@@ -3248,7 +3249,7 @@ mod tests {
     use std::{mem::take, path::PathBuf, time::Instant};
 
     use swc_core::{
-        common::Mark,
+        common::{FileName, Mark},
         ecma::{
             ast::EsVersion, parser::parse_file_as_program, transforms::base::resolver,
             visit::VisitMutWith,
@@ -3549,4 +3550,68 @@ mod tests {
         .await
         .unwrap()
     }
+
+    /// Returns the effects of the module `code`.
+    fn effects_of(code: &str) -> Vec<Effect> {
+        run_test(false, |cm, handler| {
+            let fm = cm.new_source_file(FileName::Anon, code.to_string());
+            let mut m = parse_file_as_program(
+                &fm,
+                Default::default(),
+                EsVersion::latest(),
+                None,
+                &mut vec![],
+            )
+            .map_err(|err| err.into_diagnostic(handler).emit())?;
+
+            let unresolved_mark = Mark::new();
+            let top_level_mark = Mark::new();
+            m.visit_mut_with(&mut resolver(unresolved_mark, top_level_mark, false));
+
+            let eval_context = EvalContext::new(&m, unresolved_mark);
+            Ok(create_graph(&m, &eval_context).effects)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn worker_effects() {
+        let effects = effects_of(
+            r#"
+            new Worker(new URL("./worker.js", import.meta.url));
+            new SharedWorker(new URL("./shared.js", import.meta.url), { type: "module" });
+            new URL("./asset.png", import.meta.url);
+            "#,
+        );
+
+        let workers = effects
+            .iter()
+            .filter_map(|effect| match effect {
+                Effect::Worker {
+                    input,
+                    shared,
+                    module,
+                    ..
+                } => Some((input.as_str().map(str::to_string), *shared, *module)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            workers,
+            vec![
+                (Some("./worker.js".to_string()), false, false),
+                (Some("./shared.js".to_string()), true, true),
+            ]
+        );
+
+        // The `new URL()` of a worker is not referenced as an asset on its own.
+        let urls = effects
+            .iter()
+            .filter_map(|effect| match effect {
+                Effect::Url { input, .. } => input.as_str().map(str::to_string),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(urls, vec!["./asset.png".to_string()]);
+    }
 }
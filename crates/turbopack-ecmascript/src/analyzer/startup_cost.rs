@@ -0,0 +1,190 @@
+use swc_core::ecma::{
+    ast::*,
+    visit::{noop_visit_type, Visit, VisitWith},
+};
+
+/// The assumed number of iterations of a loop, as the real number is unknown
+/// at compile time.
+const LOOP_ITERATIONS: usize = 10;
+
+/// Estimates the work a module does when it's evaluated, in abstract units.
+///
+/// Only code that runs at import time is counted: top level statements,
+/// immediately invoked function expressions and static class members. Loops
+/// multiply the cost of their body and large literals cost one unit per
+/// element.
+pub(crate) fn estimate_startup_cost(m: &Program) -> usize {
+    let mut visitor = StartupCostVisitor {
+        cost: 0,
+        multiplier: 1,
+    };
+
+    m.visit_with(&mut visitor);
+
+    visitor.cost
+}
+
+struct StartupCostVisitor {
+    cost: usize,
+    multiplier: usize,
+}
+
+impl StartupCostVisitor {
+    fn add(&mut self, units: usize) {
+        self.cost = self
+            .cost
+            .saturating_add(units.saturating_mul(self.multiplier));
+    }
+
+    fn visit_loop_body(&mut self, body: &Stmt) {
+        let multiplier = self.multiplier;
+        self.multiplier = multiplier.saturating_mul(LOOP_ITERATIONS);
+        body.visit_with(self);
+        self.multiplier = multiplier;
+    }
+}
+
+fn unparen(mut expr: &Expr) -> &Expr {
+    while let Expr::Paren(ParenExpr { expr: inner, .. }) = expr {
+        expr = inner;
+    }
+    expr
+}
+
+macro_rules! noop {
+    ($name:ident, $T:path) => {
+        fn $name(&mut self, _: &$T) {}
+    };
+}
+
+impl Visit for StartupCostVisitor {
+    fn visit_stmt(&mut self, n: &Stmt) {
+        self.add(1);
+        n.visit_children_with(self);
+    }
+
+    fn visit_call_expr(&mut self, n: &CallExpr) {
+        self.add(1);
+        match &n.callee {
+            // Immediately invoked function expressions run their body right
+            // away.
+            Callee::Expr(callee) => match unparen(callee) {
+                Expr::Fn(FnExpr { function, .. }) => function.body.visit_with(self),
+                Expr::Arrow(ArrowExpr { body, .. }) => body.visit_with(self),
+                callee => callee.visit_with(self),
+            },
+            callee => callee.visit_with(self),
+        }
+        n.args.visit_with(self);
+    }
+
+    fn visit_new_expr(&mut self, n: &NewExpr) {
+        self.add(1);
+        n.visit_children_with(self);
+    }
+
+    fn visit_array_lit(&mut self, n: &ArrayLit) {
+        self.add(n.elems.len());
+        n.visit_children_with(self);
+    }
+
+    fn visit_object_lit(&mut self, n: &ObjectLit) {
+        self.add(n.props.len());
+        n.visit_children_with(self);
+    }
+
+    fn visit_for_stmt(&mut self, n: &ForStmt) {
+        n.init.visit_with(self);
+        n.test.visit_with(self);
+        n.update.visit_with(self);
+        self.visit_loop_body(&n.body);
+    }
+
+    fn visit_for_in_stmt(&mut self, n: &ForInStmt) {
+        n.right.visit_with(self);
+        self.visit_loop_body(&n.body);
+    }
+
+    fn visit_for_of_stmt(&mut self, n: &ForOfStmt) {
+        n.right.visit_with(self);
+        self.visit_loop_body(&n.body);
+    }
+
+    fn visit_while_stmt(&mut self, n: &WhileStmt) {
+        n.test.visit_with(self);
+        self.visit_loop_body(&n.body);
+    }
+
+    fn visit_do_while_stmt(&mut self, n: &DoWhileStmt) {
+        n.test.visit_with(self);
+        self.visit_loop_body(&n.body);
+    }
+
+    // prevent code which doesn't run at import time from being counted
+    noop_visit_type!();
+    noop!(visit_arrow_expr, ArrowExpr);
+    noop!(visit_constructor, Constructor);
+    noop!(visit_function, Function);
+    noop!(visit_getter_prop, GetterProp);
+    noop!(visit_setter_prop, SetterProp);
+
+    fn visit_class_prop(&mut self, n: &ClassProp) {
+        if n.is_static {
+            n.value.visit_with(self);
+        }
+    }
+
+    fn visit_private_prop(&mut self, n: &PrivateProp) {
+        if n.is_static {
+            n.value.visit_with(self);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::{
+        common::{FileName, FilePathMapping, SourceMap},
+        ecma::{ast::EsVersion, parser::parse_file_as_program},
+    };
+
+    use super::estimate_startup_cost;
+
+    fn cost(code: &str) -> usize {
+        let cm = SourceMap::new(FilePathMapping::empty());
+        let fm = cm.new_source_file(FileName::Anon, code.to_string());
+        let program = parse_file_as_program(
+            &fm,
+            Default::default(),
+            EsVersion::latest(),
+            None,
+            &mut vec![],
+        )
+        .unwrap();
+        estimate_startup_cost(&program)
+    }
+
+    #[test]
+    fn functions_are_not_counted() {
+        assert_eq!(
+            cost("function a() { for (;;) { a(); } }"),
+            cost("function a() {}")
+        );
+        assert_eq!(
+            cost("const a = () => [1, 2, 3];"),
+            cost("const a = () => 1;")
+        );
+    }
+
+    #[test]
+    fn iifes_are_counted() {
+        assert!(cost("(function() { a(); b(); c(); })();") > cost("(function() {});"));
+        assert!(cost("(() => { a(); b(); c(); })();") > cost("(() => {});"));
+    }
+
+    #[test]
+    fn loops_multiply_their_body() {
+        assert!(cost("for (const x of y) { a(); }") >= 10 * cost("a();"));
+        assert!(cost("while (x) { while (y) { a(); } }") >= 100 * cost("a();"));
+    }
+}
@@ -31,6 +31,7 @@ impl ChunkType for EcmascriptChunkType {
         chunking_context: Vc<Box<dyn ChunkingContext>>,
         chunk_items: Vec<ChunkItemWithAsyncModuleInfo>,
         referenced_output_assets: Vc<OutputAssets>,
+        chunk_name: Option<String>,
     ) -> Result<Vc<Box<dyn Chunk>>> {
         let Some(chunking_context) =
             Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkingContext>>(chunking_context)
@@ -58,7 +59,11 @@ impl ChunkType for EcmascriptChunkType {
             referenced_output_assets: referenced_output_assets.await?.clone_value(),
         }
         .cell();
-        Ok(Vc::upcast(EcmascriptChunk::new(chunking_context, content)))
+        Ok(Vc::upcast(EcmascriptChunk::new(
+            chunking_context,
+            content,
+            chunk_name,
+        )))
     }
 
     #[turbo_tasks::function]
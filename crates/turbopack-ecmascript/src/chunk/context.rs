@@ -1,5 +1,13 @@
+use anyhow::Result;
 use turbo_tasks::Vc;
-use turbopack_core::chunk::ChunkingContext;
+use turbopack_core::{chunk::ChunkingContext, ident::AssetIdent};
+
+use crate::utils::StringifyJs;
+
+/// The global variable from which chunks and assets read their base URL at
+/// runtime when the public path is deferred. It must be set before the first
+/// chunk is loaded, and defaults to `/` for assets.
+pub const PUBLIC_PATH_GLOBAL: &str = "__turbopack_public_path__";
 
 /// [`EcmascriptChunkingContext`] must be implemented by [`ChunkingContext`]
 /// implementors that want to operate on [`EcmascriptChunk`]s.
@@ -10,4 +18,31 @@ pub trait EcmascriptChunkingContext: ChunkingContext {
     fn has_react_refresh(self: Vc<Self>) -> Vc<bool> {
         Vc::cell(false)
     }
+
+    /// Whether asset URLs are resolved against [`PUBLIC_PATH_GLOBAL`] at
+    /// runtime instead of a prefix known at build time. When deferred,
+    /// [`ChunkingContext::asset_url`] returns URLs without a prefix.
+    fn is_public_path_deferred(self: Vc<Self>) -> Vc<bool> {
+        Vc::cell(false)
+    }
+}
+
+/// Returns a JavaScript expression which evaluates to the URL of the asset
+/// with the given ident.
+#[turbo_tasks::function]
+pub async fn asset_url_code(
+    chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    ident: Vc<AssetIdent>,
+) -> Result<Vc<String>> {
+    let url = chunking_context.asset_url(ident).await?;
+    Ok(Vc::cell(
+        if *chunking_context.is_public_path_deferred().await? {
+            format!(
+                "(globalThis.{PUBLIC_PATH_GLOBAL} ?? \"/\") + {}",
+                StringifyJs(&*url)
+            )
+        } else {
+            StringifyJs(&*url).to_string()
+        },
+    ))
 }
@@ -13,7 +13,10 @@ use turbopack_core::{
 
 use super::EcmascriptChunkingContext;
 use crate::{
-    references::async_module::{AsyncModuleOptions, OptionAsyncModuleOptions},
+    references::{
+        async_module::{AsyncModuleOptions, OptionAsyncModuleOptions},
+        InlinedValues,
+    },
     utils::FormatIter,
     EcmascriptModuleContent, ParseResultSourceMap,
 };
@@ -36,6 +39,7 @@ impl EcmascriptChunkItemContent {
         async_module_options: Vc<OptionAsyncModuleOptions>,
     ) -> Result<Vc<Self>> {
         let refresh = *chunking_context.has_react_refresh().await?;
+        let hot = *chunking_context.is_hot_module_replacement_enabled().await?;
         let externals = *chunking_context.environment().node_externals().await?;
 
         let content = content.await?;
@@ -48,6 +52,7 @@ impl EcmascriptChunkItemContent {
                 EcmascriptChunkItemOptions {
                     strict: true,
                     refresh,
+                    hot,
                     externals,
                     async_module,
                     ..Default::default()
@@ -55,6 +60,7 @@ impl EcmascriptChunkItemContent {
             } else {
                 EcmascriptChunkItemOptions {
                     refresh,
+                    hot,
                     externals,
                     // These things are not available in ESM
                     module: true,
@@ -83,6 +89,9 @@ impl EcmascriptChunkItemContent {
             "l: __turbopack_load__",
             "j: __turbopack_dynamic__",
             "g: global",
+            // Every runtime provides it, the ones without HMR support just
+            // create the value.
+            "p: __turbopack_preserve_hot_state__",
             // HACK
             "__dirname",
         ];
@@ -96,6 +105,9 @@ impl EcmascriptChunkItemContent {
         if this.options.refresh {
             args.push("k: __turbopack_refresh__");
         }
+        if this.options.hot {
+            args.push("h: __turbopack_hot__");
+        }
         if this.options.module {
             args.push("m: module");
         }
@@ -109,6 +121,9 @@ impl EcmascriptChunkItemContent {
             args.push("w: __turbopack_wasm__");
             args.push("u: __turbopack_wasm_module__");
         }
+        if this.options.worker {
+            args.push("b: __turbopack_worker_blob_url__");
+        }
         let mut code = CodeBuilder::default();
         let args = FormatIter(|| args.iter().copied().intersperse(", "));
         if this.options.this {
@@ -155,6 +170,9 @@ pub struct EcmascriptChunkItemOptions {
     /// Whether this chunk item's module factory should include a
     /// `__turbopack_refresh__` argument.
     pub refresh: bool,
+    /// Whether this chunk item's module factory should include a
+    /// `__turbopack_hot__` argument, which is exposed as `import.meta.hot`.
+    pub hot: bool,
     /// Whether this chunk item's module factory should include a `module`
     /// argument.
     pub module: bool,
@@ -174,6 +192,9 @@ pub struct EcmascriptChunkItemOptions {
     /// Whether this chunk item's module factory should include
     /// `__turbopack_wasm__` to load WebAssembly.
     pub wasm: bool,
+    /// Whether this chunk item's module factory should include
+    /// `__turbopack_worker_blob_url__` to create worker script URLs.
+    pub worker: bool,
     pub placeholder_for_future_extensions: (),
 }
 
@@ -193,6 +214,11 @@ pub trait EcmascriptChunkItem: ChunkItem {
     fn need_async_module_info(self: Vc<Self>) -> Vc<bool> {
         Vc::cell(false)
     }
+
+    /// The compile-time values inlined into the chunk item's code.
+    fn inlined_values(self: Vc<Self>) -> Vc<InlinedValues> {
+        InlinedValues::empty()
+    }
 }
 
 pub trait EcmascriptChunkItemExt: Send {
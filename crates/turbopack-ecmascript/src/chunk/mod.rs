@@ -8,7 +8,8 @@ pub(crate) mod placeable;
 use std::fmt::Write;
 
 use anyhow::{bail, Result};
-use turbo_tasks::{Value, ValueToString, Vc};
+use indexmap::IndexMap;
+use turbo_tasks::{TryJoinIterExt, Value, ValueToString, Vc};
 use turbo_tasks_fs::FileSystem;
 use turbopack_core::{
     asset::{Asset, AssetContent},
@@ -19,14 +20,16 @@ use turbopack_core::{
         utils::{children_from_output_assets, content_to_details},
         Introspectable, IntrospectableChildren,
     },
-    output::OutputAssets,
+    output::{OutputAsset, OutputAssets},
     server_fs::ServerFileSystem,
 };
 
+use crate::references::InlinedValues;
+
 pub use self::{
     chunk_type::EcmascriptChunkType,
     content::EcmascriptChunkContent,
-    context::EcmascriptChunkingContext,
+    context::{asset_url_code, EcmascriptChunkingContext, PUBLIC_PATH_GLOBAL},
     data::EcmascriptChunkData,
     item::{
         EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkItemExt,
@@ -39,6 +42,9 @@ pub use self::{
 pub struct EcmascriptChunk {
     pub chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
     pub content: Vc<EcmascriptChunkContent>,
+    /// The requested name of the chunk, e.g. from a `webpackChunkName`
+    /// comment. It replaces the common path of the chunk items in the ident.
+    pub chunk_name: Option<String>,
 }
 
 #[turbo_tasks::value(transparent)]
@@ -50,10 +56,12 @@ impl EcmascriptChunk {
     pub async fn new(
         chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
         content: Vc<EcmascriptChunkContent>,
+        chunk_name: Option<String>,
     ) -> Result<Vc<Self>> {
         Ok(EcmascriptChunk {
             chunking_context,
             content,
+            chunk_name,
         }
         .cell())
     }
@@ -115,7 +123,11 @@ impl Chunk for EcmascriptChunk {
         }
 
         let ident = AssetIdent {
-            path: if let Some((common_path, _)) = common_path {
+            path: if let Some(chunk_name) = &this.chunk_name {
+                this.chunking_context
+                    .context_path()
+                    .join(chunk_name.clone())
+            } else if let Some((common_path, _)) = common_path {
                 common_path
             } else {
                 ServerFileSystem::new().root()
@@ -166,6 +178,31 @@ impl EcmascriptChunk {
     pub async fn chunk_items_count(&self) -> Result<Vc<usize>> {
         Ok(Vc::cell(self.content.await?.chunk_items.len()))
     }
+
+    /// The compile-time values inlined into the chunk items of this chunk.
+    #[turbo_tasks::function]
+    pub async fn inlined_values(&self) -> Result<Vc<InlinedValues>> {
+        let values = self
+            .content
+            .await?
+            .chunk_items
+            .iter()
+            .map(|&(chunk_item, _)| chunk_item.inlined_values())
+            .try_join()
+            .await?;
+        let mut inlined_values = IndexMap::new();
+        for (name, &count) in values.iter().flat_map(|values| values.iter()) {
+            *inlined_values.entry(name.clone()).or_default() += count;
+        }
+        Ok(Vc::cell(inlined_values))
+    }
+}
+
+/// An output asset which contains the chunk items of [EcmascriptChunk]s.
+#[turbo_tasks::value_trait]
+pub trait EcmascriptOutputChunk: OutputAsset {
+    /// The compile-time values inlined into the output asset.
+    fn inlined_values(self: Vc<Self>) -> Vc<InlinedValues>;
 }
 
 #[turbo_tasks::value_impl]
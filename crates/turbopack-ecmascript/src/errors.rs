@@ -16,5 +16,8 @@ pub mod failed_to_analyse {
         pub const AMD_DEFINE: &str = "TP1200";
         pub const NEW_URL_IMPORT_META: &str = "TP1201";
         pub const FREE_VAR_REFERENCE: &str = "TP1202";
+        pub const NEW_WORKER: &str = "TP1203";
+        pub const SERVICE_WORKER: &str = "TP1204";
+        pub const IMPORT_META_RESOLVE: &str = "TP1205";
     }
 }
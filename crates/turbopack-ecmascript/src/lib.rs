@@ -18,8 +18,10 @@ pub mod parse;
 mod path_visitor;
 pub mod references;
 pub mod resolve;
+pub mod service_worker_chunk;
 pub(crate) mod special_cases;
 pub(crate) mod static_code;
+pub mod stories;
 mod swc_comments;
 pub mod text;
 pub(crate) mod transform;
@@ -27,6 +29,7 @@ pub mod tree_shake;
 pub mod typescript;
 pub mod utils;
 pub mod webpack;
+pub mod worker_chunk;
 
 use anyhow::{Context, Result};
 use chunk::{EcmascriptChunkItem, EcmascriptChunkingContext};
@@ -37,18 +40,19 @@ use path_visitor::ApplyVisitors;
 pub use references::{AnalyzeEcmascriptModuleResult, TURBOPACK_HELPER};
 pub use static_code::StaticEcmascriptCode;
 use swc_core::{
-    common::GLOBALS,
+    common::{comments::Comments, Spanned, GLOBALS},
     ecma::{
         codegen::{text_writer::JsWriter, Emitter},
         visit::{VisitMutWith, VisitMutWithPath},
     },
 };
 pub use transform::{
-    CustomTransformer, EcmascriptInputTransform, EcmascriptInputTransforms, OptionTransformPlugin,
-    TransformContext, TransformPlugin, UnsupportedServerActionIssue,
+    AnalyzerDiagnostic, AnalyzerPlugin, CustomAnalyzer, CustomTransformer,
+    EcmascriptInputTransform, EcmascriptInputTransforms, HotStatePattern, HotStatePatterns,
+    OptionTransformPlugin, TransformContext, TransformPlugin, UnsupportedServerActionIssue,
 };
 use turbo_tasks::{trace::TraceRawVcs, ReadRef, TryJoinIterExt, Value, ValueToString, Vc};
-use turbo_tasks_fs::{rope::Rope, FileSystemPath};
+use turbo_tasks_fs::{glob::Glob, rope::Rope, FileSystemPath};
 use turbopack_core::{
     asset::{Asset, AssetContent},
     chunk::{
@@ -70,8 +74,12 @@ use self::{
     tree_shake::asset::EcmascriptModulePartAsset,
 };
 use crate::{
+    analyzer::startup_cost::estimate_startup_cost,
     chunk::EcmascriptChunkPlaceable,
-    references::{analyze_ecmascript_module, async_module::OptionAsyncModule},
+    references::{
+        analyze_ecmascript_module, async_module::OptionAsyncModule, esm::dynamic::parse_string,
+        InlinedValues,
+    },
     transform::remove_shebang,
 };
 
@@ -84,6 +92,21 @@ pub enum SpecifiedModuleType {
     EcmaScript,
 }
 
+/// How the namespace of a CommonJS module is synthesized when it's imported
+/// from an ES module, in particular its default export.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(PartialOrd, Ord, Hash, Debug, Clone, Copy, Default)]
+pub enum CjsInterop {
+    /// The `default` export is `module.exports.default` when the module is
+    /// marked with `__esModule` and `module.exports` otherwise, like Babel and
+    /// TypeScript's `esModuleInterop` do.
+    #[default]
+    Babel,
+    /// The `default` export is always `module.exports`, like Node.js does when
+    /// importing CommonJS modules from ES modules.
+    Node,
+}
+
 #[turbo_tasks::value(serialization = "auto_for_input")]
 #[derive(PartialOrd, Ord, Hash, Debug, Default, Copy, Clone)]
 pub struct EcmascriptOptions {
@@ -94,6 +117,12 @@ pub struct EcmascriptOptions {
     pub import_parts: bool,
     /// module is forced to a specific type (happens e. g. for .cjs and .mjs)
     pub specified_module_type: SpecifiedModuleType,
+    /// only files matching this glob are included in context modules
+    pub context_include: Option<Vc<Glob>>,
+    /// files matching this glob are excluded from context modules
+    pub context_exclude: Option<Vc<Glob>>,
+    /// how default imports of CommonJS modules are synthesized
+    pub cjs_interop: CjsInterop,
 }
 
 #[turbo_tasks::value(serialization = "auto_for_input")]
@@ -300,6 +329,7 @@ impl EcmascriptModuleAsset {
                 exports: ReadRef::cell(exports.clone()),
                 code_generation: result_value.code_generation,
                 async_module: ReadRef::cell(async_module.clone()),
+                inlined_values: result_value.inlined_values,
                 successful: false,
             }
             .cell());
@@ -405,6 +435,28 @@ impl ChunkableModule for EcmascriptModuleAsset {
             chunking_context,
         })))
     }
+
+    /// Modules request to be split points with a
+    /// `/* @turbopack-chunk: "editor" */` comment at their start.
+    #[turbo_tasks::function]
+    async fn split_chunk_name(self: Vc<Self>) -> Result<Vc<Option<String>>> {
+        let ParseResult::Ok {
+            program, comments, ..
+        } = &*self.parse().await?
+        else {
+            return Ok(Vc::cell(None));
+        };
+        let chunk_name = comments
+            .get_leading(program.span().lo)
+            .and_then(|comments| {
+                comments.iter().find_map(|comment| {
+                    let value = comment.text.trim().strip_prefix("@turbopack-chunk:")?;
+                    parse_string(value.trim())
+                })
+            })
+            .map(ToString::to_string);
+        Ok(Vc::cell(chunk_name))
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -492,6 +544,14 @@ impl ChunkItem for ModuleChunkItem {
             Ok(Vc::cell(false))
         }
     }
+
+    #[turbo_tasks::function]
+    async fn startup_cost(&self) -> Result<Vc<usize>> {
+        let ParseResult::Ok { program, .. } = &*self.module.parse().await? else {
+            return Ok(Vc::cell(0));
+        };
+        Ok(Vc::cell(estimate_startup_cost(program)))
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -528,6 +588,11 @@ impl EcmascriptChunkItem for ModuleChunkItem {
             async_module_options,
         ))
     }
+
+    #[turbo_tasks::function]
+    async fn inlined_values(&self) -> Result<Vc<InlinedValues>> {
+        Ok(self.module.analyze().await?.inlined_values)
+    }
 }
 
 /// The transformed contents of an Ecmascript module.
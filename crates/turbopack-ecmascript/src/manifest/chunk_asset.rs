@@ -31,6 +31,9 @@ fn modifier() -> Vc<String> {
 pub struct ManifestAsyncModule {
     pub inner: Vc<Box<dyn ChunkableModule>>,
     pub chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    /// The name of the chunks of the chunk group, e.g. from a
+    /// `webpackChunkName` comment.
+    pub chunk_name: Option<String>,
     pub availability_info: AvailabilityInfo,
 }
 
@@ -40,11 +43,13 @@ impl ManifestAsyncModule {
     pub fn new(
         module: Vc<Box<dyn ChunkableModule>>,
         chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+        chunk_name: Option<String>,
         availability_info: Value<AvailabilityInfo>,
     ) -> Vc<Self> {
         Self::cell(ManifestAsyncModule {
             inner: module,
             chunking_context,
+            chunk_name,
             availability_info: availability_info.into_value(),
         })
     }
@@ -52,9 +57,17 @@ impl ManifestAsyncModule {
     #[turbo_tasks::function]
     pub(super) async fn chunks(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
         let this = self.await?;
-        Ok(this
-            .chunking_context
-            .chunk_group(Vc::upcast(this.inner), Value::new(this.availability_info)))
+        let availability_info = Value::new(this.availability_info);
+        Ok(match &this.chunk_name {
+            Some(chunk_name) => this.chunking_context.named_chunk_group(
+                Vc::upcast(this.inner),
+                chunk_name.clone(),
+                availability_info,
+            ),
+            None => this
+                .chunking_context
+                .chunk_group(Vc::upcast(this.inner), availability_info),
+        })
     }
 
     #[turbo_tasks::function]
@@ -73,6 +86,9 @@ impl ManifestAsyncModule {
     #[turbo_tasks::function]
     pub async fn content_ident(&self) -> Result<Vc<AssetIdent>> {
         let mut ident = self.inner.ident();
+        if let Some(chunk_name) = &self.chunk_name {
+            ident = ident.with_modifier(Vc::cell(chunk_name.clone()));
+        }
         if let Some(available_modules) = self.availability_info.available_chunk_items() {
             ident = ident.with_modifier(Vc::cell(available_modules.hash().await?.to_string()));
         }
@@ -89,7 +105,11 @@ fn manifest_chunk_reference_description() -> Vc<String> {
 impl Module for ManifestAsyncModule {
     #[turbo_tasks::function]
     fn ident(&self) -> Vc<AssetIdent> {
-        self.inner.ident().with_modifier(modifier())
+        let ident = self.inner.ident().with_modifier(modifier());
+        match &self.chunk_name {
+            Some(chunk_name) => ident.with_modifier(Vc::cell(chunk_name.clone())),
+            None => ident,
+        }
     }
 
     #[turbo_tasks::function]
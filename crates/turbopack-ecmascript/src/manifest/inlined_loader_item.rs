@@ -0,0 +1,114 @@
+use std::io::Write as _;
+
+use anyhow::{anyhow, Result};
+use indoc::writedoc;
+use turbo_tasks::Vc;
+use turbopack_core::{
+    chunk::{ChunkItem, ChunkItemExt, ChunkType, ChunkableModule, ChunkingContext},
+    ident::AssetIdent,
+    module::Module,
+    reference::ModuleReferences,
+};
+
+use super::loader_item::ManifestLoaderChunkItem;
+use crate::{
+    chunk::{
+        EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkPlaceable,
+        EcmascriptChunkType, EcmascriptChunkingContext,
+    },
+    utils::StringifyJs,
+};
+
+/// Replaces the [ManifestLoaderChunkItem] of a dynamic `import()` when the
+/// chunk group of the imported module was inlined into the chunk group of the
+/// importing module. Nothing needs to be loaded, so the module is imported
+/// directly, but still asynchronously to keep the semantics of `import()`.
+#[turbo_tasks::value]
+pub struct InlinedLoaderChunkItem {
+    module: Vc<Box<dyn ChunkableModule>>,
+    chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+}
+
+#[turbo_tasks::value_impl]
+impl InlinedLoaderChunkItem {
+    #[turbo_tasks::function]
+    pub fn new(
+        module: Vc<Box<dyn ChunkableModule>>,
+        chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    ) -> Vc<Self> {
+        Self::cell(InlinedLoaderChunkItem {
+            module,
+            chunking_context,
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for InlinedLoaderChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> Vc<AssetIdent> {
+        // The id must match the one of the manifest loader, which is used by the
+        // importing module.
+        ManifestLoaderChunkItem::asset_ident_for(self.module)
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<ModuleReferences> {
+        ModuleReferences::empty()
+    }
+
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        Vc::upcast(self.chunking_context)
+    }
+
+    #[turbo_tasks::function]
+    async fn ty(&self) -> Result<Vc<Box<dyn ChunkType>>> {
+        Ok(Vc::upcast(
+            Vc::<EcmascriptChunkType>::default().resolve().await?,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        Vc::upcast(self.module)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for InlinedLoaderChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn EcmascriptChunkingContext>> {
+        self.chunking_context
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<EcmascriptChunkItemContent>> {
+        let placeable = Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkPlaceable>>(self.module)
+            .await?
+            .ok_or_else(|| anyhow!("asset is not placeable in ecmascript chunk"))?;
+        let dynamic_id = &*placeable
+            .as_chunk_item(Vc::upcast(self.chunking_context))
+            .id()
+            .await?;
+
+        let mut code = Vec::new();
+        writedoc!(
+            code,
+            r#"
+                __turbopack_export_value__((__turbopack_import__) => {{
+                    return Promise.resolve().then(() => {{
+                        return __turbopack_import__({dynamic_id});
+                    }});
+                }});
+            "#,
+            dynamic_id = StringifyJs(dynamic_id),
+        )?;
+
+        Ok(EcmascriptChunkItemContent {
+            inner_code: code.into(),
+            ..Default::default()
+        }
+        .into())
+    }
+}
@@ -0,0 +1,169 @@
+use std::io::Write as _;
+
+use anyhow::{anyhow, Result};
+use indoc::writedoc;
+use turbo_tasks::Vc;
+use turbopack_core::{
+    chunk::{ChunkData, ChunkItem, ChunkItemExt, ChunkType, ChunkableModule, ChunkingContext},
+    ident::AssetIdent,
+    module::Module,
+    output::OutputAsset,
+    reference::{ModuleReferences, SingleOutputAssetReference},
+};
+
+use super::{chunk_asset::ManifestAsyncModule, loader_item::ManifestLoaderChunkItem};
+use crate::{
+    chunk::{
+        data::EcmascriptChunkData, EcmascriptChunkItem, EcmascriptChunkItemContent,
+        EcmascriptChunkPlaceable, EcmascriptChunkType, EcmascriptChunkingContext,
+    },
+    utils::StringifyJs,
+};
+
+#[turbo_tasks::function]
+fn modifier() -> Vc<String> {
+    Vc::cell("lazy loader".to_string())
+}
+
+/// The lazy loader item replaces the [ManifestLoaderChunkItem] when lazy
+/// compilation is enabled.
+///
+/// The manifest loader item references the manifest chunks, and computing
+/// those already requires the chunk group of the dynamically imported module.
+/// Instead, the lazy loader item only references `lazy_chunk`, a placeholder
+/// chunk whose path is known without compiling anything and which contains
+/// the manifest chunk item. The chunk group of the dynamic import is only
+/// compiled when the runtime requests the placeholder chunk from the server,
+/// i.e. when the `import()` expression is reached for the first time.
+#[turbo_tasks::value]
+pub struct LazyLoaderChunkItem {
+    manifest: Vc<ManifestAsyncModule>,
+    lazy_chunk: Vc<Box<dyn OutputAsset>>,
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+}
+
+#[turbo_tasks::value_impl]
+impl LazyLoaderChunkItem {
+    #[turbo_tasks::function]
+    pub fn new(
+        manifest: Vc<ManifestAsyncModule>,
+        lazy_chunk: Vc<Box<dyn OutputAsset>>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Vc<Self> {
+        Self::cell(LazyLoaderChunkItem {
+            manifest,
+            lazy_chunk,
+            chunking_context,
+        })
+    }
+}
+
+#[turbo_tasks::function]
+fn lazy_chunk_reference_description() -> Vc<String> {
+    Vc::cell("lazy manifest chunk".to_string())
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for LazyLoaderChunkItem {
+    #[turbo_tasks::function]
+    async fn asset_ident(&self) -> Result<Vc<AssetIdent>> {
+        // Shares the id of the manifest loader item, which is what
+        // `async_loader_chunk_item_id` refers to.
+        Ok(ManifestLoaderChunkItem::asset_ident_for(
+            self.manifest.await?.inner,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn content_ident(&self) -> Vc<AssetIdent> {
+        self.manifest.content_ident().with_modifier(modifier())
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<ModuleReferences> {
+        Vc::cell(vec![Vc::upcast(SingleOutputAssetReference::new(
+            self.lazy_chunk,
+            lazy_chunk_reference_description(),
+        ))])
+    }
+
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        self.chunking_context
+    }
+
+    #[turbo_tasks::function]
+    async fn ty(&self) -> Result<Vc<Box<dyn ChunkType>>> {
+        Ok(Vc::upcast(
+            Vc::<EcmascriptChunkType>::default().resolve().await?,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        Vc::upcast(self.manifest)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for LazyLoaderChunkItem {
+    #[turbo_tasks::function]
+    async fn chunking_context(&self) -> Result<Vc<Box<dyn EcmascriptChunkingContext>>> {
+        Ok(self.manifest.await?.chunking_context)
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<EcmascriptChunkItemContent>> {
+        let mut code = Vec::new();
+
+        let manifest = self.manifest.await?;
+
+        let Some(lazy_chunk_data) =
+            &*ChunkData::from_asset(self.chunking_context.output_root(), self.lazy_chunk).await?
+        else {
+            return Err(anyhow!("lazy manifest chunk is not in the output root"));
+        };
+        let lazy_chunk_data = lazy_chunk_data.await?;
+
+        // The lazy chunk contains the manifest chunk item, which exports the
+        // chunks of the dynamic import.
+        let item_id = &*self
+            .manifest
+            .as_chunk_item(Vc::upcast(manifest.chunking_context))
+            .id()
+            .await?;
+
+        let placeable =
+            Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkPlaceable>>(manifest.inner)
+                .await?
+                .ok_or_else(|| anyhow!("asset is not placeable in ecmascript chunk"))?;
+        let dynamic_id = &*placeable
+            .as_chunk_item(Vc::upcast(manifest.chunking_context))
+            .id()
+            .await?;
+
+        writedoc!(
+            code,
+            r#"
+                __turbopack_export_value__((__turbopack_import__) => {{
+                    return __turbopack_load__({lazy_chunk_data}).then(() => {{
+                        return __turbopack_require__({item_id});
+                    }}).then((chunks) => {{
+                        return Promise.all(chunks.map((chunk) => __turbopack_load__(chunk)));
+                    }}).then(() => {{
+                        return __turbopack_import__({dynamic_id});
+                    }});
+                }});
+            "#,
+            lazy_chunk_data = StringifyJs(&EcmascriptChunkData::new(&lazy_chunk_data)),
+            item_id = StringifyJs(item_id),
+            dynamic_id = StringifyJs(dynamic_id),
+        )?;
+
+        Ok(EcmascriptChunkItemContent {
+            inner_code: code.into(),
+            ..Default::default()
+        }
+        .into())
+    }
+}
@@ -1,3 +1,5 @@
 pub mod chunk_asset;
 pub mod chunk_item;
+pub mod inlined_loader_item;
+pub mod lazy_loader_item;
 pub mod loader_item;
@@ -19,6 +19,7 @@ use swc_core::{
         visit::VisitMutWith,
     },
 };
+use tracing::Instrument;
 use turbo_tasks::{util::WrapFuture, Value, ValueToString, Vc};
 use turbo_tasks_fs::{FileContent, FileSystemPath};
 use turbo_tasks_hash::hash_xxh3_hash64;
@@ -140,6 +141,25 @@ pub async fn parse(
     source: Vc<Box<dyn Source>>,
     ty: Value<EcmascriptModuleAssetType>,
     transforms: Vc<EcmascriptInputTransforms>,
+) -> Result<Vc<ParseResult>> {
+    let cache_key = transforms.cache_key_inputs().cache_key().await?;
+    Ok(parse_with_cache_key(
+        source,
+        ty,
+        transforms,
+        cache_key.clone_value(),
+    ))
+}
+
+/// The cache key of the plugin transforms is only an argument so it becomes
+/// part of the task's identity, which invalidates persisted parse results
+/// when the cache key inputs change.
+#[turbo_tasks::function]
+async fn parse_with_cache_key(
+    source: Vc<Box<dyn Source>>,
+    ty: Value<EcmascriptModuleAssetType>,
+    transforms: Vc<EcmascriptInputTransforms>,
+    _cache_key: String,
 ) -> Result<Vc<ParseResult>> {
     match parse_internal(source, ty, transforms).await {
         Ok(result) => Ok(result),
@@ -246,6 +266,7 @@ async fn parse_content(
             let comments = SwcComments::default();
 
             let mut parsed_program = {
+                let _span = tracing::info_span!("parse", name = ident).entered();
                 let lexer = Lexer::new(
                     match ty {
                         EcmascriptModuleAssetType::Ecmascript => Syntax::Es(EsConfig {
@@ -331,11 +352,16 @@ async fn parse_content(
                 file_name_hash: file_path_hash,
                 file_path: fs_path_vc,
             };
-            for transform in transforms.iter() {
-                transform
-                    .apply(&mut parsed_program, &transform_context)
-                    .await?;
+            async {
+                for transform in transforms.iter() {
+                    transform
+                        .apply(&mut parsed_program, &transform_context)
+                        .await?;
+                }
+                anyhow::Ok(())
             }
+            .instrument(tracing::info_span!("transform", name = ident))
+            .await?;
 
             parsed_program.visit_mut_with(
                 &mut swc_core::ecma::transforms::base::helpers::inject_helpers(unresolved_mark),
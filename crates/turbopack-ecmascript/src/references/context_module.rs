@@ -0,0 +1,380 @@
+//! Context modules for partially dynamic requests like
+//! `import("./locales/" + lang + ".json")`.
+//!
+//! The files which could match such a request are listed at build time and
+//! included in a [RequireContextAsset], which maps their requests to module
+//! ids. The request is resolved through that map at runtime, like a
+//! `require.context()` call. For `import()`, the map points to the loaders of
+//! the modules, so every module is placed in its own async chunk group,
+//! unless `webpackMode: "eager"` is given.
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use swc_core::{
+    ecma::ast::{CallExpr, Callee, Expr},
+    quote_expr,
+};
+use turbo_tasks::{primitives::Regex, Value, ValueToString, Vc};
+use turbopack_core::{
+    chunk::{ChunkItemExt, ChunkableModule, ChunkableModuleReference},
+    issue::{IssueSeverity, IssueSource},
+    reference::ModuleReference,
+    resolve::{origin::ResolveOrigin, pattern::Pattern, ModuleResolveResult},
+    source::Source,
+};
+
+use crate::{
+    chunk::EcmascriptChunkingContext,
+    code_gen::CodeGeneration,
+    create_visitor,
+    references::{
+        esm::dynamic::ImportMagicComments,
+        pattern_mapping::ResolveType,
+        require_context::{
+            FlatDirList, RequireContextAsset, RequireContextMap, RequireContextMapEntry,
+        },
+        AstPath,
+    },
+    resolve::try_to_severity,
+    utils::module_id_to_lit,
+    CodeGenerateable, EcmascriptOptions,
+};
+
+/// Splits a partially dynamic request into the directory of its context
+/// module and the pattern of the files in that directory, e.g.
+/// `"./locales/" + <dynamic> + ".json"` into `./locales/` and
+/// `<dynamic> + ".json"`.
+///
+/// Returns `None` if the request is constant, not relative or the directory
+/// isn't known.
+pub(crate) fn context_request(pattern: &Pattern) -> Option<(String, Pattern)> {
+    let Pattern::Concatenation(parts) = pattern else {
+        return None;
+    };
+    let (Pattern::Constant(prefix), rest) = parts.split_first()? else {
+        return None;
+    };
+    if !prefix.starts_with("./") && !prefix.starts_with("../") {
+        return None;
+    }
+    let (dir, file_prefix) = prefix.split_at(prefix.rfind('/')? + 1);
+
+    let file_pattern = Pattern::concat(
+        std::iter::once(Pattern::Constant(file_prefix.to_string())).chain(rest.iter().cloned()),
+    );
+    Some((dir.to_string(), file_pattern))
+}
+
+/// Converts a pattern into an equivalent regular expression. Dynamic parts
+/// match any string, including `/`.
+fn pattern_to_regex(pattern: &Pattern) -> String {
+    match pattern {
+        Pattern::Constant(value) => regex::escape(value),
+        Pattern::Dynamic => ".*".to_string(),
+        Pattern::Alternatives(list) => format!(
+            "(?:{})",
+            list.iter()
+                .map(pattern_to_regex)
+                .collect::<Vec<_>>()
+                .join("|")
+        ),
+        Pattern::Concatenation(list) => list.iter().map(pattern_to_regex).collect(),
+    }
+}
+
+/// Lists the files in `dir` (relative to the origin) which match
+/// `file_pattern` and resolves them.
+///
+/// Files are also included when only their path without the extension
+/// matches, so `require("./pages/" + name)` can be resolved at runtime
+/// without an extension. Both requests are keys of the map then.
+///
+/// The files are filtered by the configured include and exclude globs and by
+/// the `webpackInclude` and `webpackExclude` magic comments. Like in webpack,
+/// the regular expressions of the comments are matched against the request
+/// relative to `dir`, e.g. `./en.json`.
+#[turbo_tasks::function]
+async fn context_module_map(
+    origin: Vc<Box<dyn ResolveOrigin>>,
+    dir: String,
+    file_pattern: Value<Pattern>,
+    options: Value<EcmascriptOptions>,
+    magic_comments: Vc<ImportMagicComments>,
+    resolve_type: Value<ResolveType>,
+    issue_source: Vc<IssueSource>,
+    issue_severity: Vc<IssueSeverity>,
+) -> Result<Vc<RequireContextMap>> {
+    let origin_path = &*origin.origin_path().parent().await?;
+    let file_regex = pattern_to_regex(&file_pattern);
+    let exact = regex::Regex::new(&format!("^{file_regex}$"))?;
+    let filter: Vc<Regex> = Vc::cell(regex::Regex::new(&format!("^{file_regex}(?:\\.[^/]+)?$"))?);
+
+    let include = match options.context_include {
+        Some(glob) => Some(glob.await?),
+        None => None,
+    };
+    let exclude = match options.context_exclude {
+        Some(glob) => Some(glob.await?),
+        None => None,
+    };
+    let magic_comments = magic_comments.await?;
+    // The patterns were validated when parsing the magic comments.
+    let include_regex = magic_comments
+        .include
+        .as_deref()
+        .map(regex::Regex::new)
+        .transpose()?;
+    let exclude_regex = magic_comments
+        .exclude
+        .as_deref()
+        .map(regex::Regex::new)
+        .transpose()?;
+
+    let list = &*FlatDirList::read(
+        origin.origin_path().parent().join(dir.clone()),
+        true,
+        filter,
+    )
+    .await?;
+
+    let mut files = Vec::new();
+    for (dir_relative, path) in list {
+        let request = format!("./{dir_relative}");
+        if include
+            .as_ref()
+            .is_some_and(|glob| !glob.execute(dir_relative))
+            || exclude
+                .as_ref()
+                .is_some_and(|glob| glob.execute(dir_relative))
+            || include_regex
+                .as_ref()
+                .is_some_and(|regex| !regex.is_match(&request))
+            || exclude_regex
+                .as_ref()
+                .is_some_and(|regex| regex.is_match(&request))
+        {
+            continue;
+        }
+        let origin_relative = origin_path
+            .get_relative_path_to(&*path.await?)
+            .context("invariant error: the context directory is relative to the origin")?;
+        files.push((dir_relative, origin_relative));
+    }
+
+    let mut map = IndexMap::new();
+    let mut extensionless = Vec::new();
+    for (dir_relative, origin_relative) in files {
+        let entry = || {
+            RequireContextMapEntry::resolve(
+                origin,
+                origin_relative.clone(),
+                *resolve_type,
+                Some(issue_source),
+                issue_severity,
+            )
+        };
+
+        if exact.is_match(dir_relative) {
+            map.insert(format!("{dir}{dir_relative}"), entry());
+        }
+        if let Some((stem, extension)) = dir_relative.rsplit_once('.') {
+            if !extension.contains('/')
+                && !stem.is_empty()
+                && !stem.ends_with('/')
+                && exact.is_match(stem)
+            {
+                extensionless.push((format!("{dir}{stem}"), entry()));
+            }
+        }
+    }
+    // Files which match with their extension take precedence.
+    for (key, entry) in extensionless {
+        map.entry(key).or_insert(entry);
+    }
+
+    Ok(Vc::cell(map))
+}
+
+/// A reference for a partially dynamic `require()` or `import()`, which is
+/// resolved through a [RequireContextAsset] at runtime.
+#[turbo_tasks::value]
+#[derive(Hash, Debug)]
+pub struct ContextModuleAssetReference {
+    inner: Vc<RequireContextAsset>,
+    description: String,
+    resolve_type: ResolveType,
+    eager: bool,
+
+    path: Vc<AstPath>,
+}
+
+#[turbo_tasks::value_impl]
+impl ContextModuleAssetReference {
+    #[turbo_tasks::function]
+    pub(crate) async fn new(
+        source: Vc<Box<dyn Source>>,
+        origin: Vc<Box<dyn ResolveOrigin>>,
+        dir: String,
+        file_pattern: Value<Pattern>,
+        options: Value<EcmascriptOptions>,
+        magic_comments: Vc<ImportMagicComments>,
+        resolve_type: Value<ResolveType>,
+        path: Vc<AstPath>,
+        issue_source: Vc<IssueSource>,
+        in_try: bool,
+    ) -> Result<Vc<Self>> {
+        let request = Pattern::concat([Pattern::Constant(dir.clone()), file_pattern.clone_value()])
+            .to_string();
+        let comments = magic_comments.await?;
+        let eager = *resolve_type == ResolveType::EsmAsync && comments.eager;
+        let description = description(*resolve_type, &request, &comments);
+
+        let map = context_module_map(
+            origin,
+            dir,
+            file_pattern,
+            options,
+            magic_comments,
+            resolve_type,
+            issue_source,
+            try_to_severity(in_try),
+        );
+        // The modules of an eager `import()` are bundled with the context
+        // module, so they are referenced like the ones of a `require()`.
+        let inner = RequireContextAsset::new(
+            source,
+            origin,
+            map,
+            description.clone(),
+            Value::new(if eager {
+                ResolveType::Cjs
+            } else {
+                *resolve_type
+            }),
+            magic_comments,
+        );
+
+        Ok(Self::cell(ContextModuleAssetReference {
+            inner,
+            description,
+            resolve_type: *resolve_type,
+            eager,
+            path,
+        }))
+    }
+}
+
+/// Describes a context module, including the magic comments which change its
+/// content, e.g. `import context ./locales/<dynamic>.json (eager)`.
+fn description(
+    resolve_type: ResolveType,
+    request: &str,
+    magic_comments: &ImportMagicComments,
+) -> String {
+    let mut description = format!(
+        "{} context {}",
+        match resolve_type {
+            ResolveType::Cjs => "require",
+            ResolveType::EsmAsync => "import",
+        },
+        request
+    );
+    if let Some(include) = &magic_comments.include {
+        description.push_str(&format!(" include /{include}/"));
+    }
+    if let Some(exclude) = &magic_comments.exclude {
+        description.push_str(&format!(" exclude /{exclude}/"));
+    }
+    if resolve_type == ResolveType::EsmAsync && magic_comments.eager {
+        description.push_str(" (eager)");
+    }
+    description
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleReference for ContextModuleAssetReference {
+    #[turbo_tasks::function]
+    fn resolve_reference(&self) -> Vc<ModuleResolveResult> {
+        ModuleResolveResult::module(Vc::upcast(self.inner)).cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ContextModuleAssetReference {
+    #[turbo_tasks::function]
+    fn to_string(&self) -> Vc<String> {
+        Vc::cell(self.description.clone())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModuleReference for ContextModuleAssetReference {}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for ContextModuleAssetReference {
+    #[turbo_tasks::function]
+    async fn code_generation(
+        &self,
+        chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    ) -> Result<Vc<CodeGeneration>> {
+        let chunk_item = self.inner.as_chunk_item(Vc::upcast(chunking_context));
+        let module_id = chunk_item.id().await?.clone_value();
+        let resolve_type = self.resolve_type;
+        let eager = self.eager;
+
+        let mut visitors = Vec::new();
+
+        let path = &self.path.await?;
+        visitors.push(
+            create_visitor!(exact path, visit_mut_call_expr(call_expr: &mut CallExpr) {
+                let context = quote_expr!(
+                    "__turbopack_require_context__(__turbopack_require__($id))",
+                    id: Expr = module_id_to_lit(&module_id)
+                );
+                call_expr.callee = Callee::Expr(match resolve_type {
+                    ResolveType::Cjs => context,
+                    // The request is evaluated eagerly, but a request which
+                    // isn't in the context rejects the returned promise.
+                    ResolveType::EsmAsync if eager => quote_expr!(
+                        "((request) => Promise.resolve().then(() => \
+                         __turbopack_import__($context.resolve(request))))",
+                        context: Expr = *context
+                    ),
+                    ResolveType::EsmAsync => quote_expr!(
+                        "((request) => Promise.resolve().then(() => \
+                         $context(request)(__turbopack_import__)))",
+                        context: Expr = *context
+                    ),
+                });
+            }),
+        );
+
+        Ok(CodeGeneration { visitors }.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use turbopack_core::resolve::pattern::Pattern;
+
+    use super::{context_request, pattern_to_regex};
+
+    #[test]
+    fn splits_context_requests() {
+        let pattern = Pattern::Concatenation(vec![
+            Pattern::Constant("./locales/messages.".to_string()),
+            Pattern::Dynamic,
+            Pattern::Constant(".json".to_string()),
+        ]);
+        let (dir, file_pattern) = context_request(&pattern).unwrap();
+        assert_eq!(dir, "./locales/");
+        assert_eq!(pattern_to_regex(&file_pattern), r"messages\..*\.json");
+
+        assert!(context_request(&Pattern::Constant("./a.js".to_string())).is_none());
+        assert!(context_request(&Pattern::Concatenation(vec![
+            Pattern::Constant("lodash/".to_string()),
+            Pattern::Dynamic,
+        ]))
+        .is_none());
+    }
+}
@@ -8,8 +8,8 @@ use swc_core::{
 use turbo_tasks::{Value, ValueToString, Vc};
 use turbopack_core::{
     chunk::{
-        ChunkItemExt, ChunkableModule, ChunkableModuleReference, ChunkingContext, ChunkingType,
-        ChunkingTypeOption, ModuleId,
+        split_point::split_chunk_name, ChunkItemExt, ChunkableModule, ChunkableModuleReference,
+        ChunkingContext, ChunkingType, ChunkingTypeOption, ModuleId,
     },
     issue::IssueSeverity,
     module::Module,
@@ -29,6 +29,7 @@ use crate::{
     create_visitor, magic_identifier,
     references::util::{request_to_string, throw_module_not_found_expr},
     resolve::esm_resolve,
+    CjsInterop,
 };
 
 #[turbo_tasks::value]
@@ -105,6 +106,7 @@ pub struct EsmAssetReference {
     pub annotations: ImportAnnotations,
 
     pub export_name: Option<Vc<ModulePart>>,
+    pub cjs_interop: CjsInterop,
 }
 
 /// A list of [EsmAssetReference]s
@@ -129,12 +131,14 @@ impl EsmAssetReference {
         request: Vc<Request>,
         annotations: Value<ImportAnnotations>,
         export_name: Option<Vc<ModulePart>>,
+        cjs_interop: Value<CjsInterop>,
     ) -> Vc<Self> {
         Self::cell(EsmAssetReference {
             origin,
             request,
             annotations: annotations.into_value(),
             export_name,
+            cjs_interop: cjs_interop.into_value(),
         })
     }
 
@@ -229,23 +233,56 @@ impl CodeGenerateable for EsmAssetReference {
 
         // only chunked references can be imported
         if chunking_type.is_some() {
+            let node_interop = matches!(self.await?.cjs_interop, CjsInterop::Node);
             let referenced_asset = self.get_referenced_asset().await?;
             if let Some(ident) = referenced_asset.get_ident().await? {
                 match &*referenced_asset {
                     ReferencedAsset::Some(asset) => {
-                        let id = asset
-                            .as_chunk_item(Vc::upcast(chunking_context))
-                            .id()
-                            .await?;
+                        let chunk_item = asset.as_chunk_item(Vc::upcast(chunking_context));
+                        // Split points are loaded in their own async chunk group. The
+                        // importing module awaits the promise of the loader like an async
+                        // module.
+                        let is_split_point =
+                            matches!(*chunking_type, Some(ChunkingType::ParallelInheritAsync))
+                                && split_chunk_name(Vc::upcast(chunking_context), chunk_item)
+                                    .await?
+                                    .is_some();
+                        let id = if is_split_point {
+                            chunking_context
+                                .async_loader_chunk_item_id(Vc::upcast(*asset))
+                                .await?
+                        } else {
+                            chunk_item.id().await?
+                        };
                         visitors.push(create_visitor!(visit_mut_program(program: &mut Program) {
-                            let stmt = quote!(
-                                "var $name = __turbopack_import__($id);" as Stmt,
-                                name = Ident::new(ident.clone().into(), DUMMY_SP),
-                                id: Expr = Expr::Lit(match &*id {
-                                    ModuleId::String(s) => s.clone().into(),
-                                    ModuleId::Number(n) => (*n as f64).into(),
-                                })
-                            );
+                            let id = Expr::Lit(match &*id {
+                                ModuleId::String(s) => s.clone().into(),
+                                ModuleId::Number(n) => (*n as f64).into(),
+                            });
+                            let stmt = match (is_split_point, node_interop) {
+                                (true, true) => quote!(
+                                    "var $name = __turbopack_require__($id)((id) => \
+                                     __turbopack_import__(id, true));" as Stmt,
+                                    name = Ident::new(ident.clone().into(), DUMMY_SP),
+                                    id: Expr = id
+                                ),
+                                (true, false) => quote!(
+                                    "var $name = __turbopack_require__($id)(__turbopack_import__);"
+                                        as Stmt,
+                                    name = Ident::new(ident.clone().into(), DUMMY_SP),
+                                    id: Expr = id
+                                ),
+                                (false, true) => quote!(
+                                    "var $name = __turbopack_import__($id, true);" as Stmt,
+                                    name = Ident::new(ident.clone().into(), DUMMY_SP),
+                                    id: Expr = id
+                                ),
+                                (false, false) => quote!(
+                                    "var $name = __turbopack_import__($id);" as Stmt,
+                                    name = Ident::new(ident.clone().into(), DUMMY_SP),
+                                    id: Expr = id
+                                ),
+                            };
                             insert_hoisted_stmt(program, stmt);
                         }));
                     }
@@ -260,11 +297,19 @@ impl CodeGenerateable for EsmAssetReference {
                         let request = request.clone();
                         visitors.push(create_visitor!(visit_mut_program(program: &mut Program) {
                             // TODO Technically this should insert a ESM external, but we don't support that yet
-                            let stmt = quote!(
-                                "var $name = __turbopack_external_require__($id, true);" as Stmt,
-                                name = Ident::new(ident.clone().into(), DUMMY_SP),
-                                id: Expr = Expr::Lit(request.clone().into())
-                            );
+                            let stmt = if node_interop {
+                                quote!(
+                                    "var $name = __turbopack_external_require__($id, true, true);" as Stmt,
+                                    name = Ident::new(ident.clone().into(), DUMMY_SP),
+                                    id: Expr = Expr::Lit(request.clone().into())
+                                )
+                            } else {
+                                quote!(
+                                    "var $name = __turbopack_external_require__($id, true);" as Stmt,
+                                    name = Ident::new(ident.clone().into(), DUMMY_SP),
+                                    id: Expr = Expr::Lit(request.clone().into())
+                                )
+                            };
                             insert_hoisted_stmt(program, stmt);
                         }));
                     }
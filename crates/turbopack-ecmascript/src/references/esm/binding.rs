@@ -11,7 +11,7 @@ use swc_core::{
 };
 use turbo_tasks::Vc;
 
-use super::EsmAssetReference;
+use super::{export::validate_import, EsmAssetReference};
 use crate::{
     chunk::EcmascriptChunkingContext,
     code_gen::{CodeGenerateable, CodeGeneration},
@@ -55,6 +55,10 @@ impl CodeGenerateable for EsmBinding {
         let mut visitors = Vec::new();
         let imported_module = this.reference.get_referenced_asset();
 
+        if let Some(export) = &this.export {
+            validate_import(this.reference, export.clone()).await?;
+        }
+
         fn make_expr(imported_module: &str, export: Option<&str>) -> Expr {
             if let Some(export) = export {
                 Expr::Member(MemberExpr {
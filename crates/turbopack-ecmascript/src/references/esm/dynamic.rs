@@ -1,11 +1,17 @@
+use std::collections::HashMap;
+
 use anyhow::Result;
 use swc_core::{
-    ecma::ast::{Callee, ExprOrSpread},
+    common::{comments::Comments, Span, Spanned},
+    ecma::{
+        ast::{CallExpr, Callee, ExprOrSpread, Program},
+        visit::{Visit, VisitWith},
+    },
     quote_expr,
 };
 use turbo_tasks::{Value, ValueToString, Vc};
 use turbopack_core::{
-    chunk::{ChunkableModuleReference, ChunkingType, ChunkingTypeOption},
+    chunk::{AsyncLoadingHints, ChunkableModuleReference, ChunkingType, ChunkingTypeOption},
     issue::IssueSource,
     reference::ModuleReference,
     reference_type::EcmaScriptModulesReferenceSubType,
@@ -19,6 +25,7 @@ use crate::{
     create_visitor,
     references::AstPath,
     resolve::{esm_resolve, try_to_severity},
+    CjsInterop,
 };
 
 #[turbo_tasks::value]
@@ -29,6 +36,9 @@ pub struct EsmAsyncAssetReference {
     pub path: Vc<AstPath>,
     pub issue_source: Vc<IssueSource>,
     pub in_try: bool,
+    pub loading_hints: Vc<AsyncLoadingHints>,
+    pub chunk_name: Option<String>,
+    pub cjs_interop: CjsInterop,
 }
 
 #[turbo_tasks::value_impl]
@@ -40,6 +50,9 @@ impl EsmAsyncAssetReference {
         path: Vc<AstPath>,
         issue_source: Vc<IssueSource>,
         in_try: bool,
+        loading_hints: Vc<AsyncLoadingHints>,
+        chunk_name: Option<String>,
+        cjs_interop: Value<CjsInterop>,
     ) -> Vc<Self> {
         Self::cell(EsmAsyncAssetReference {
             origin,
@@ -47,6 +60,9 @@ impl EsmAsyncAssetReference {
             path,
             issue_source,
             in_try,
+            loading_hints,
+            chunk_name,
+            cjs_interop: cjs_interop.into_value(),
         })
     }
 }
@@ -82,6 +98,16 @@ impl ChunkableModuleReference for EsmAsyncAssetReference {
     fn chunking_type(&self) -> Vc<ChunkingTypeOption> {
         Vc::cell(Some(ChunkingType::Async))
     }
+
+    #[turbo_tasks::function]
+    fn async_loading_hints(&self) -> Vc<AsyncLoadingHints> {
+        self.loading_hints
+    }
+
+    #[turbo_tasks::function]
+    fn async_chunk_name(&self) -> Vc<Option<String>> {
+        Vc::cell(self.chunk_name.clone())
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -107,6 +133,7 @@ impl CodeGenerateable for EsmAsyncAssetReference {
         .await?;
 
         let path = &self.path.await?;
+        let node_interop = matches!(self.cjs_interop, CjsInterop::Node);
 
         let visitor = match &*pm {
             PatternMapping::Invalid => {
@@ -148,8 +175,13 @@ impl CodeGenerateable for EsmAsyncAssetReference {
                         "__turbopack_require__($arg)",
                         arg: Expr = expr
                     ));
+                    let import = if node_interop {
+                        quote_expr!("(id) => __turbopack_import__(id, true)")
+                    } else {
+                        quote_expr!("__turbopack_import__")
+                    };
                     call_expr.args = vec![
-                        ExprOrSpread { spread: None, expr: quote_expr!("__turbopack_import__") },
+                        ExprOrSpread { spread: None, expr: import },
                     ];
                 })
             }
@@ -185,3 +217,229 @@ impl CodeGenerateable for EsmAsyncAssetReference {
         .into())
     }
 }
+
+/// The magic comments in front of the first argument of an `import()`
+/// expression, e.g. `import(/* webpackPrefetch: true */ "./page")` or
+/// `import(/* webpackChunkName: "dialog" */ "./dialog")`.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Default, Clone, Hash)]
+pub(crate) struct ImportMagicComments {
+    pub loading_hints: AsyncLoadingHints,
+    pub chunk_name: Option<String>,
+    /// Set by `webpackIgnore: true`. The import is not analyzed and is left
+    /// untouched in the output.
+    pub ignore: bool,
+    /// Set by `webpackMode: "eager"`. The modules of a context module are
+    /// placed in the chunk of the import instead of their own async chunk
+    /// groups.
+    pub eager: bool,
+    /// The regular expressions of `webpackInclude` and `webpackExclude`, which
+    /// filter the files of a context module.
+    pub include: Option<String>,
+    pub exclude: Option<String>,
+}
+
+impl ImportMagicComments {
+    fn is_empty(&self) -> bool {
+        self.loading_hints.is_empty()
+            && self.chunk_name.is_none()
+            && !self.ignore
+            && !self.eager
+            && self.include.is_none()
+            && self.exclude.is_none()
+    }
+}
+
+/// Collects the [ImportMagicComments] of all `import()` expressions in the
+/// program, keyed by the span of the call. Both the `webpack` and the
+/// `turbopack` prefix are accepted for the keys.
+pub(crate) fn collect_import_magic_comments(
+    program: &Program,
+    comments: &dyn Comments,
+) -> HashMap<Span, ImportMagicComments> {
+    let mut visitor = ImportMagicCommentsVisitor {
+        comments,
+        magic_comments: HashMap::new(),
+    };
+    program.visit_with(&mut visitor);
+    visitor.magic_comments
+}
+
+struct ImportMagicCommentsVisitor<'a> {
+    comments: &'a dyn Comments,
+    magic_comments: HashMap<Span, ImportMagicComments>,
+}
+
+impl Visit for ImportMagicCommentsVisitor<'_> {
+    fn visit_call_expr(&mut self, call_expr: &CallExpr) {
+        if let (Callee::Import(_), Some(arg)) = (&call_expr.callee, call_expr.args.first()) {
+            if let Some(comments) = self.comments.get_leading(arg.span_lo()) {
+                let mut magic_comments = ImportMagicComments::default();
+                for comment in comments.iter() {
+                    parse_magic_comment(&comment.text, &mut magic_comments);
+                }
+                if !magic_comments.is_empty() {
+                    self.magic_comments.insert(call_expr.span, magic_comments);
+                }
+            }
+        }
+        call_expr.visit_children_with(self);
+    }
+}
+
+/// Parses a comment like `webpackPrefetch: true, webpackChunkName: "page"`.
+/// Unknown keys and invalid values are ignored.
+fn parse_magic_comment(comment: &str, magic_comments: &mut ImportMagicComments) {
+    for part in split_magic_comment(comment) {
+        let Some((key, value)) = part.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "webpackPrefetch" | "turbopackPrefetch" => {
+                if let Some(value) = parse_bool(value) {
+                    magic_comments.loading_hints.prefetch = value;
+                }
+            }
+            "webpackPreload" | "turbopackPreload" => {
+                if let Some(value) = parse_bool(value) {
+                    magic_comments.loading_hints.preload = value;
+                }
+            }
+            "webpackIgnore" | "turbopackIgnore" => {
+                if let Some(value) = parse_bool(value) {
+                    magic_comments.ignore = value;
+                }
+            }
+            "webpackChunkName" | "turbopackChunkName" => {
+                if let Some(value) = parse_string(value) {
+                    magic_comments.chunk_name = Some(value.to_string());
+                }
+            }
+            "webpackMode" | "turbopackMode" => match parse_string(value) {
+                Some("eager") => magic_comments.eager = true,
+                Some("lazy") => magic_comments.eager = false,
+                _ => {}
+            },
+            "webpackInclude" | "turbopackInclude" => {
+                if let Some(value) = parse_regex(value) {
+                    magic_comments.include = Some(value);
+                }
+            }
+            "webpackExclude" | "turbopackExclude" => {
+                if let Some(value) = parse_regex(value) {
+                    magic_comments.exclude = Some(value);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Splits a magic comment at the commas between its entries. Commas in
+/// strings and regular expression literals, e.g. `/a{1,2}/`, are kept.
+fn split_magic_comment(comment: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut delimiter = None;
+    let mut escaped = false;
+    let mut after_colon = false;
+    for (i, c) in comment.char_indices() {
+        if let Some(d) = delimiter {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == d {
+                delimiter = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => delimiter = Some(c),
+            '/' if after_colon => delimiter = Some(c),
+            ',' => {
+                parts.push(&comment[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        if !c.is_whitespace() {
+            after_colon = c == ':';
+        }
+    }
+    parts.push(&comment[start..]);
+    parts
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parses a single or double quoted string. Empty strings are ignored.
+pub(crate) fn parse_string(value: &str) -> Option<&str> {
+    let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .or_else(|| {
+            value
+                .strip_prefix('\'')
+                .and_then(|value| value.strip_suffix('\''))
+        })?;
+    (!value.is_empty()).then_some(value)
+}
+
+/// Parses a regular expression literal like `/\.json$/i` into a pattern for
+/// the `regex` crate. Only the `i` flag is supported.
+fn parse_regex(value: &str) -> Option<String> {
+    let (source, flags) = value.strip_prefix('/')?.rsplit_once('/')?;
+    let pattern = match flags {
+        "" => source.to_string(),
+        "i" => format!("(?i){source}"),
+        _ => return None,
+    };
+    regex::Regex::new(&pattern).ok()?;
+    Some(pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_magic_comment, ImportMagicComments};
+
+    fn parse(comment: &str) -> ImportMagicComments {
+        let mut magic_comments = ImportMagicComments::default();
+        parse_magic_comment(comment, &mut magic_comments);
+        magic_comments
+    }
+
+    #[test]
+    fn parses_magic_comments() {
+        let magic_comments = parse(r#" webpackPrefetch: true, webpackChunkName: "page" "#);
+        assert!(magic_comments.loading_hints.prefetch);
+        assert!(!magic_comments.loading_hints.preload);
+        assert_eq!(magic_comments.chunk_name.as_deref(), Some("page"));
+
+        let magic_comments = parse(r#" webpackMode: "eager", turbopackIgnore: false "#);
+        assert!(magic_comments.eager);
+        assert!(!magic_comments.ignore);
+
+        let magic_comments = parse(" webpackPreload: yes, webpackUnknown: 1 ");
+        assert!(magic_comments.is_empty());
+    }
+
+    #[test]
+    fn parses_regex_magic_comments() {
+        let magic_comments =
+            parse(r#" webpackInclude: /\.(json|ya?ml)$/, webpackExclude: /draft{1,2}/i "#);
+        assert_eq!(magic_comments.include.as_deref(), Some(r"\.(json|ya?ml)$"));
+        assert_eq!(magic_comments.exclude.as_deref(), Some("(?i)draft{1,2}"));
+
+        let magic_comments = parse(" webpackInclude: /[/, webpackExclude: /a/g ");
+        assert_eq!(magic_comments.include, None);
+        assert_eq!(magic_comments.exclude, None);
+    }
+}
@@ -1,9 +1,7 @@
-use std::{
-    borrow::Cow,
-    collections::{BTreeMap, HashSet},
-};
+use std::collections::{btree_map::Entry, BTreeMap, BTreeSet, HashSet};
 
 use anyhow::Result;
+use futures::{future::BoxFuture, FutureExt};
 use serde::{Deserialize, Serialize};
 use swc_core::{
     common::DUMMY_SP,
@@ -13,10 +11,12 @@ use swc_core::{
     },
     quote, quote_expr,
 };
-use turbo_tasks::{trace::TraceRawVcs, ValueToString, Vc};
+use turbo_tasks::{trace::TraceRawVcs, Completion, ValueToString, Vc};
 use turbopack_core::{
+    ident::AssetIdent,
     issue::{analyze::AnalyzeIssue, IssueExt, IssueSeverity},
     module::Module,
+    resolve::origin::ResolveOrigin,
 };
 
 use super::{base::ReferencedAsset, EsmAssetReference};
@@ -35,33 +35,148 @@ pub enum EsmExport {
     Error,
 }
 
+/// The binding an export name refers to, after following explicit re-exports
+/// (`export { a } from "..."`). Names with the same origin refer to the same
+/// binding, even when they are exported by different modules.
+type ExportOrigin = (Vc<Box<dyn EcmascriptChunkPlaceable>>, String);
+
+async fn explicit_export_origin(
+    mut module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+    name: &str,
+) -> Result<ExportOrigin> {
+    let mut name = name.to_string();
+    let mut visited = HashSet::new();
+    while visited.insert((module, name.clone())) {
+        let exports = module.get_exports().await?;
+        let EcmascriptExports::EsmExports(exports) = &*exports else {
+            break;
+        };
+        let exports = exports.await?;
+        let Some(EsmExport::ImportedBinding(esm_ref, imported)) = exports.exports.get(&name) else {
+            break;
+        };
+        let ReferencedAsset::Some(asset) = &*esm_ref.get_referenced_asset().await? else {
+            break;
+        };
+        module = *asset;
+        name = imported.clone();
+    }
+    Ok((module, name))
+}
+
+/// Adds a name exported through `export *`. A name which is exported by
+/// multiple `export *` with different origins is ambiguous and marked as
+/// `None`, as it is in the ECMAScript spec.
+fn insert_star_export<T, O: PartialEq>(
+    names: &mut BTreeMap<String, Option<(T, O)>>,
+    name: String,
+    entry: Option<(T, O)>,
+) {
+    match names.entry(name) {
+        Entry::Vacant(vacant) => {
+            vacant.insert(entry);
+        }
+        Entry::Occupied(mut occupied) => {
+            let existing = occupied.get().as_ref().map(|(_, origin)| origin);
+            if existing != entry.as_ref().map(|(_, origin)| origin) {
+                occupied.insert(None);
+            }
+        }
+    }
+}
+
+/// The names a module exports to an `export *`, i.e. all names except
+/// `default`.
 #[turbo_tasks::value]
-struct ExpandResults {
-    star_exports: Vec<String>,
-    has_dynamic_exports: bool,
+pub struct AllExportNames {
+    /// Maps each name to the module which explicitly exports it.
+    pub esm_exports: BTreeMap<String, Vc<Box<dyn EcmascriptChunkPlaceable>>>,
+    /// Names which are not exported, because they are ambiguous.
+    pub ambiguous_exports: BTreeSet<String>,
+    /// Modules reached through `export *` whose exports are only known at
+    /// runtime.
+    pub dynamic_exporting_modules: Vec<Vc<Box<dyn EcmascriptChunkPlaceable>>>,
 }
 
 #[turbo_tasks::function]
-async fn expand_star_exports(
-    root_asset: Vc<Box<dyn EcmascriptChunkPlaceable>>,
-) -> Result<Vc<ExpandResults>> {
-    let mut set = HashSet::new();
-    let mut has_dynamic_exports = false;
-    let mut checked_assets = HashSet::new();
-    checked_assets.insert(root_asset);
-    let mut queue = vec![(root_asset, root_asset.get_exports())];
-    while let Some((asset, exports)) = queue.pop() {
-        match &*exports.await? {
+pub async fn get_all_export_names(
+    module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+) -> Result<Vc<AllExportNames>> {
+    let mut visited = HashSet::new();
+    let mut dynamic_exporting_modules = Vec::new();
+    let names = collect_export_names(module, &mut visited, &mut dynamic_exporting_modules).await?;
+
+    let mut esm_exports = BTreeMap::new();
+    let mut ambiguous_exports = BTreeSet::new();
+    for (name, exporter) in names {
+        match exporter {
+            Some(exporter) => {
+                esm_exports.insert(name, exporter);
+            }
+            None => {
+                ambiguous_exports.insert(name);
+            }
+        }
+    }
+
+    Ok(AllExportNames {
+        esm_exports,
+        ambiguous_exports,
+        dynamic_exporting_modules,
+    }
+    .cell())
+}
+
+/// Collects the names exported by `module` to an `export *`, mapped to the
+/// module which explicitly exports them, or `None` when they are ambiguous.
+///
+/// `export *` cycles are valid, so this recurses within a single task and
+/// skips modules which are already on the current path. Modules which are
+/// reached through multiple paths are visited for each of them, as the names
+/// they export can be shadowed on one path but not on another.
+fn collect_export_names<'a>(
+    module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+    visited: &'a mut HashSet<Vc<Box<dyn EcmascriptChunkPlaceable>>>,
+    dynamic_exporting_modules: &'a mut Vec<Vc<Box<dyn EcmascriptChunkPlaceable>>>,
+) -> BoxFuture<'a, Result<BTreeMap<String, Option<Vc<Box<dyn EcmascriptChunkPlaceable>>>>>> {
+    async move {
+        let mut names = BTreeMap::new();
+        if !visited.insert(module) {
+            return Ok(names);
+        }
+        match &*module.get_exports().await? {
             EcmascriptExports::EsmExports(exports) => {
                 let exports = exports.await?;
-                set.extend(exports.exports.keys().filter(|n| *n != "default").cloned());
+                let mut star_names = BTreeMap::new();
                 for esm_ref in exports.star_exports.iter() {
                     if let ReferencedAsset::Some(asset) = &*esm_ref.get_referenced_asset().await? {
-                        if checked_assets.insert(*asset) {
-                            queue.push((*asset, asset.get_exports()));
+                        let child_names =
+                            collect_export_names(*asset, visited, dynamic_exporting_modules)
+                                .await?;
+                        for (name, exporter) in child_names {
+                            let entry = match exporter {
+                                Some(exporter) => {
+                                    Some((exporter, explicit_export_origin(exporter, &name).await?))
+                                }
+                                None => None,
+                            };
+                            insert_star_export(&mut star_names, name, entry);
                         }
                     }
                 }
+                // Explicit exports shadow the names exported through `export *`.
+                names.extend(
+                    star_names
+                        .into_iter()
+                        .map(|(name, entry)| (name, entry.map(|(exporter, _)| exporter))),
+                );
+                names.extend(
+                    exports
+                        .exports
+                        .keys()
+                        .filter(|name| *name != "default")
+                        .map(|name| (name.clone(), Some(module))),
+                );
             }
             EcmascriptExports::None => AnalyzeIssue {
                 code: None,
@@ -71,9 +186,9 @@ async fn expand_star_exports(
                      want to export only types with `export type * from \"...\"`?\nNote: Using \
                      `export type` is more efficient than `export *` as it won't emit any runtime \
                      code.",
-                    asset.ident().to_string().await?
+                    module.ident().to_string().await?
                 )),
-                source_ident: asset.ident(),
+                source_ident: module.ident(),
                 severity: IssueSeverity::Warning.into(),
                 source: None,
                 title: Vc::cell("unexpected export *".to_string()),
@@ -87,9 +202,9 @@ async fn expand_star_exports(
                     "export * used with module {} which only has a default export (default export \
                      is not exported with export *)\nDid you want to use `export {{ default }} \
                      from \"...\";` instead?",
-                    asset.ident().to_string().await?
+                    module.ident().to_string().await?
                 )),
-                source_ident: asset.ident(),
+                source_ident: module.ident(),
                 severity: IssueSeverity::Warning.into(),
                 source: None,
                 title: Vc::cell("unexpected export *".to_string()),
@@ -97,7 +212,9 @@ async fn expand_star_exports(
             .cell()
             .emit(),
             EcmascriptExports::CommonJs => {
-                has_dynamic_exports = true;
+                if !dynamic_exporting_modules.contains(&module) {
+                    dynamic_exporting_modules.push(module);
+                }
                 AnalyzeIssue {
                     code: None,
                     category: Vc::cell("analyze".to_string()),
@@ -106,9 +223,9 @@ async fn expand_star_exports(
                          only available at runtime\nList all export names manually (`export {{ a, \
                          b, c }} from \"...\") or rewrite the module to ESM, to avoid the \
                          additional runtime code.`",
-                        asset.ident().to_string().await?
+                        module.ident().to_string().await?
                     )),
-                    source_ident: asset.ident(),
+                    source_ident: module.ident(),
                     severity: IssueSeverity::Warning.into(),
                     source: None,
                     title: Vc::cell("unexpected export *".to_string()),
@@ -117,14 +234,149 @@ async fn expand_star_exports(
                 .emit()
             }
             EcmascriptExports::DynamicNamespace => {
-                has_dynamic_exports = true;
+                if !dynamic_exporting_modules.contains(&module) {
+                    dynamic_exporting_modules.push(module);
+                }
+            }
+        }
+        visited.remove(&module);
+        Ok(names)
+    }
+    .boxed()
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash, TraceRawVcs)]
+pub enum FoundExportType {
+    /// The export is a binding of the module.
+    Found,
+    /// The module has exports which are only known at runtime.
+    Dynamic,
+    /// The module doesn't export the name.
+    NotFound,
+    /// The name is exported by multiple `export *` with different bindings.
+    Ambiguous,
+    /// The module has exports which can't be analyzed, e.g. a single value.
+    Unknown,
+}
+
+#[turbo_tasks::value]
+pub struct FollowExportsResult {
+    /// The last module that was followed to.
+    pub module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+    /// The name of the export in `module`.
+    pub export_name: String,
+    pub ty: FoundExportType,
+}
+
+/// Follows re-exports of `export_name`, both explicit ones and the ones from
+/// `export *`, to the module which declares the binding.
+#[turbo_tasks::function]
+pub async fn follow_reexports(
+    module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+    export_name: String,
+) -> Result<Vc<FollowExportsResult>> {
+    let mut module = module;
+    let mut export_name = export_name;
+    let mut visited = HashSet::new();
+    loop {
+        let ty = if !visited.insert((module, export_name.clone())) {
+            // Circular re-exports never resolve to a binding.
+            FoundExportType::NotFound
+        } else {
+            match &*module.get_exports().await? {
+                EcmascriptExports::EsmExports(exports) => {
+                    let expanded = exports.expand_exports().await?;
+                    match expanded.exports.get(&export_name) {
+                        Some(EsmExport::ImportedBinding(esm_ref, name)) => {
+                            if let ReferencedAsset::Some(asset) =
+                                &*esm_ref.get_referenced_asset().await?
+                            {
+                                module = *asset;
+                                export_name = name.clone();
+                                continue;
+                            }
+                            FoundExportType::Unknown
+                        }
+                        Some(EsmExport::LocalBinding(_) | EsmExport::ImportedNamespace(_)) => {
+                            FoundExportType::Found
+                        }
+                        Some(EsmExport::Error) => FoundExportType::Unknown,
+                        None if expanded.ambiguous_exports.contains(&export_name) => {
+                            FoundExportType::Ambiguous
+                        }
+                        None if !expanded.dynamic_exports.is_empty() => FoundExportType::Dynamic,
+                        None => FoundExportType::NotFound,
+                    }
+                }
+                EcmascriptExports::CommonJs | EcmascriptExports::DynamicNamespace => {
+                    FoundExportType::Dynamic
+                }
+                EcmascriptExports::Value => FoundExportType::Unknown,
+                EcmascriptExports::None => FoundExportType::NotFound,
             }
+        };
+        return Ok(FollowExportsResult {
+            module,
+            export_name,
+            ty,
         }
+        .cell());
     }
-    Ok(ExpandResults::cell(ExpandResults {
-        star_exports: set.into_iter().collect(),
-        has_dynamic_exports,
-    }))
+}
+
+/// Emits a warning when the module referenced by `esm_ref` doesn't export
+/// `export_name`, as the import evaluates to `undefined` at runtime.
+#[turbo_tasks::function]
+pub async fn validate_import(
+    esm_ref: Vc<EsmAssetReference>,
+    export_name: String,
+) -> Result<Vc<Completion>> {
+    let esm_ref_value = esm_ref.await?;
+    // Module parts only contain the exports they were split for.
+    if esm_ref_value.export_name.is_some() {
+        return Ok(Completion::immutable());
+    }
+    let ReferencedAsset::Some(asset) = &*esm_ref.get_referenced_asset().await? else {
+        return Ok(Completion::immutable());
+    };
+    let result = follow_reexports(*asset, export_name.clone()).await?;
+    let reason = match result.ty {
+        FoundExportType::NotFound => "was not found",
+        FoundExportType::Ambiguous => "is ambiguous, as multiple `export *` export it",
+        FoundExportType::Found | FoundExportType::Dynamic | FoundExportType::Unknown => {
+            return Ok(Completion::immutable());
+        }
+    };
+    let origin_path = esm_ref_value.origin.origin_path();
+    AnalyzeIssue {
+        code: None,
+        category: Vc::cell("analyze".to_string()),
+        message: Vc::cell(format!(
+            "Export `{}` {} in module {}.",
+            export_name,
+            reason,
+            asset.ident().to_string().await?
+        )),
+        source_ident: AssetIdent::from_path(origin_path),
+        severity: IssueSeverity::Warning.into(),
+        source: None,
+        title: Vc::cell("export not found".to_string()),
+    }
+    .cell()
+    .emit();
+    Ok(Completion::new())
+}
+
+/// The exports of a module with the names exported through `export *`
+/// resolved.
+#[turbo_tasks::value]
+pub struct ExpandedExports {
+    pub exports: BTreeMap<String, EsmExport>,
+    /// Names which are not exported, because they are ambiguous.
+    pub ambiguous_exports: BTreeSet<String>,
+    /// Modules referenced by `export *` which have exports only known at
+    /// runtime.
+    pub dynamic_exports: Vec<Vc<Box<dyn EcmascriptChunkPlaceable>>>,
 }
 
 #[turbo_tasks::value(shared)]
@@ -134,6 +386,58 @@ pub struct EsmExports {
     pub star_exports: Vec<Vc<EsmAssetReference>>,
 }
 
+#[turbo_tasks::value_impl]
+impl EsmExports {
+    /// Resolves the names exported through `export *`. Explicit exports shadow
+    /// them, and names which are exported by multiple `export *` with
+    /// different bindings are ambiguous and not exported.
+    #[turbo_tasks::function]
+    pub async fn expand_exports(&self) -> Result<Vc<ExpandedExports>> {
+        let mut exports = self.exports.clone();
+        let mut star_names = BTreeMap::new();
+        let mut dynamic_exports = Vec::new();
+
+        for esm_ref in self.star_exports.iter() {
+            let ReferencedAsset::Some(asset) = &*esm_ref.get_referenced_asset().await? else {
+                continue;
+            };
+            let names = get_all_export_names(*asset).await?;
+            for (name, &exporter) in names.esm_exports.iter() {
+                let origin = explicit_export_origin(exporter, name).await?;
+                insert_star_export(&mut star_names, name.clone(), Some((*esm_ref, origin)));
+            }
+            for name in names.ambiguous_exports.iter() {
+                insert_star_export(&mut star_names, name.clone(), None);
+            }
+            if !names.dynamic_exporting_modules.is_empty() {
+                dynamic_exports.push(*asset);
+            }
+        }
+
+        let mut ambiguous_exports = BTreeSet::new();
+        for (name, entry) in star_names {
+            if exports.contains_key(&name) {
+                continue;
+            }
+            match entry {
+                Some((esm_ref, _)) => {
+                    exports.insert(name.clone(), EsmExport::ImportedBinding(esm_ref, name));
+                }
+                None => {
+                    ambiguous_exports.insert(name);
+                }
+            }
+        }
+
+        Ok(ExpandedExports {
+            exports,
+            ambiguous_exports,
+            dynamic_exports,
+        }
+        .cell())
+    }
+}
+
 #[turbo_tasks::value_impl]
 impl CodeGenerateable for EsmExports {
     #[turbo_tasks::function]
@@ -144,39 +448,25 @@ impl CodeGenerateable for EsmExports {
         let this = self.await?;
         let mut visitors = Vec::new();
 
-        let mut all_exports: BTreeMap<Cow<str>, Cow<EsmExport>> = this
-            .exports
-            .iter()
-            .map(|(k, v)| (Cow::<str>::Borrowed(k), Cow::Borrowed(v)))
-            .collect();
+        let expanded = self.expand_exports().await?;
         let mut props = Vec::new();
         let mut dynamic_exports = Vec::<Box<Expr>>::new();
 
-        for esm_ref in this.star_exports.iter() {
-            if let ReferencedAsset::Some(asset) = &*esm_ref.get_referenced_asset().await? {
-                let export_info = expand_star_exports(*asset).await?;
-                let export_names = &export_info.star_exports;
-                for export in export_names.iter() {
-                    if !all_exports.contains_key(&Cow::<str>::Borrowed(export)) {
-                        all_exports.insert(
-                            Cow::Owned(export.clone()),
-                            Cow::Owned(EsmExport::ImportedBinding(*esm_ref, export.to_string())),
-                        );
-                    }
-                }
+        for asset in expanded.dynamic_exports.iter() {
+            let ident = ReferencedAsset::get_ident_from_placeable(asset).await?;
 
-                if export_info.has_dynamic_exports {
-                    let ident = ReferencedAsset::get_ident_from_placeable(asset).await?;
-
-                    dynamic_exports.push(quote_expr!(
-                        "__turbopack_dynamic__($arg)",
-                        arg: Expr = Ident::new(ident.into(), DUMMY_SP).into()
-                    ));
+            dynamic_exports.push(quote_expr!(
+                "__turbopack_dynamic__($arg)",
+                arg: Expr = Ident::new(ident.into(), DUMMY_SP).into()
+            ));
+        }
+        for (exported, local) in expanded.exports.iter() {
+            if let EsmExport::ImportedBinding(esm_ref, name) = local {
+                if this.exports.contains_key(exported) {
+                    validate_import(*esm_ref, name.clone()).await?;
                 }
             }
-        }
-        for (exported, local) in all_exports.into_iter() {
-            let expr = match local.as_ref() {
+            let expr = match local {
                 EsmExport::Error => Some(quote!(
                     "(() => { throw new Error(\"Failed binding. See build errors!\"); })" as Expr,
                 )),
@@ -218,7 +508,7 @@ impl CodeGenerateable for EsmExports {
                 props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
                     key: PropName::Str(Str {
                         span: DUMMY_SP,
-                        value: exported.as_ref().into(),
+                        value: exported.as_str().into(),
                         raw: None,
                     }),
                     value: Box::new(expr),
@@ -258,3 +548,40 @@ impl CodeGenerateable for EsmExports {
         Ok(CodeGeneration { visitors }.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::insert_star_export;
+
+    #[test]
+    fn star_exports_with_the_same_origin_are_not_ambiguous() {
+        let mut names = BTreeMap::new();
+        insert_star_export(&mut names, "a".to_string(), Some((1, "origin")));
+        insert_star_export(&mut names, "a".to_string(), Some((2, "origin")));
+        assert_eq!(names.get("a"), Some(&Some((1, "origin"))));
+    }
+
+    #[test]
+    fn star_exports_with_different_origins_are_ambiguous() {
+        let mut names = BTreeMap::new();
+        insert_star_export(&mut names, "a".to_string(), Some((1, "first")));
+        insert_star_export(&mut names, "a".to_string(), Some((2, "second")));
+        assert_eq!(names.get("a"), Some(&None));
+
+        // Once a name is ambiguous, it stays ambiguous.
+        insert_star_export(&mut names, "a".to_string(), Some((3, "first")));
+        assert_eq!(names.get("a"), Some(&None));
+    }
+
+    #[test]
+    fn ambiguous_star_exports_are_propagated() {
+        let mut names = BTreeMap::<_, Option<(i32, &str)>>::new();
+        insert_star_export(&mut names, "a".to_string(), None);
+        insert_star_export(&mut names, "b".to_string(), Some((1, "origin")));
+        insert_star_export(&mut names, "b".to_string(), None);
+        assert_eq!(names.get("a"), Some(&None));
+        assert_eq!(names.get("b"), Some(&None));
+    }
+}
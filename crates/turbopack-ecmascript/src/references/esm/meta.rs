@@ -3,17 +3,41 @@ use std::borrow::Cow;
 use anyhow::Result;
 use swc_core::{
     common::DUMMY_SP,
-    ecma::ast::{Expr, Ident},
+    ecma::ast::{
+        ArrayLit, CallExpr, Expr, ExprOrSpread, Ident, KeyValueProp, ObjectLit, Prop, PropName,
+        PropOrSpread, Str,
+    },
     quote,
 };
-use turbo_tasks::Vc;
+use turbo_tasks::{Value, ValueToString, Vc};
 use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::{
+    chunk::{
+        ChunkItemExt, ChunkableModule, ChunkableModuleReference, ChunkingContext, ChunkingType,
+        ChunkingTypeOption,
+    },
+    compile_time_info::{CompileTimeDefineValue, CompileTimeInfo, ImportMetaUrl},
+    environment::Rendering,
+    issue::IssueSource,
+    module::Module,
+    reference::ModuleReference,
+    reference_type::{EcmaScriptModulesReferenceSubType, UrlReferenceSubType},
+    resolve::{origin::ResolveOrigin, parse::Request, ModuleResolveResult},
+};
 
 use crate::{
     chunk::EcmascriptChunkingContext,
     code_gen::{CodeGenerateable, CodeGeneration},
     create_visitor, magic_identifier,
-    references::{as_abs_path, esm::base::insert_hoisted_stmt, AstPath},
+    references::{
+        as_abs_path,
+        esm::base::{insert_hoisted_stmt, ReferencedAsset},
+        pattern_mapping::{PatternMapping, ResolveType},
+        util::{request_to_string, throw_module_not_found_expr},
+        AstPath,
+    },
+    resolve::{esm_resolve, try_to_severity, url_resolve},
+    utils::module_id_to_lit,
 };
 
 /// Responsible for initializing the `import.meta` object binding, so that it
@@ -25,13 +49,18 @@ use crate::{
 #[derive(Hash, Debug)]
 pub struct ImportMetaBinding {
     path: Vc<FileSystemPath>,
+    compile_time_info: Vc<CompileTimeInfo>,
 }
 
 #[turbo_tasks::value_impl]
 impl ImportMetaBinding {
     #[turbo_tasks::function]
-    pub fn new(path: Vc<FileSystemPath>) -> Vc<Self> {
-        ImportMetaBinding { path }.cell()
+    pub fn new(path: Vc<FileSystemPath>, compile_time_info: Vc<CompileTimeInfo>) -> Vc<Self> {
+        ImportMetaBinding {
+            path,
+            compile_time_info,
+        }
+        .cell()
     }
 }
 
@@ -40,23 +69,39 @@ impl CodeGenerateable for ImportMetaBinding {
     #[turbo_tasks::function]
     async fn code_generation(
         &self,
-        _context: Vc<Box<dyn EcmascriptChunkingContext>>,
+        context: Vc<Box<dyn EcmascriptChunkingContext>>,
     ) -> Result<Vc<CodeGeneration>> {
-        let path = as_abs_path(self.path).await?.as_str().map_or_else(
-            || {
-                quote!(
-                    "(() => { throw new Error('could not convert import.meta.url to filepath') })()"
-                        as Expr
-                )
-            },
-            |path| format!("file://{}", encode_path(path)).into(),
-        );
+        let hot = *context.is_hot_module_replacement_enabled().await?;
+        let url = import_meta_url(self.path, self.compile_time_info).await?;
+        let env = match self.compile_time_info.await?.import_meta_env {
+            Some(import_meta_env) => Some(Expr::Object(ObjectLit {
+                span: DUMMY_SP,
+                props: import_meta_env
+                    .await?
+                    .iter()
+                    .map(|(key, value)| {
+                        prop(
+                            PropName::Str(Str::from(key.as_str())),
+                            define_value_to_expr(value),
+                        )
+                    })
+                    .collect(),
+            })),
+            None => None,
+        };
 
         let visitor = create_visitor!(visit_mut_program(program: &mut Program) {
+            let mut props = vec![prop(ident_prop_name("url"), url.clone())];
+            if hot {
+                props.push(prop(ident_prop_name("hot"), quote!("__turbopack_hot__" as Expr)));
+            }
+            if let Some(env) = &env {
+                props.push(prop(ident_prop_name("env"), env.clone()));
+            }
             let meta = quote!(
-                "const $name = { url: $path };" as Stmt,
+                "const $name = $meta;" as Stmt,
                 name = meta_ident(),
-                path: Expr = path.clone(),
+                meta: Expr = Expr::Object(ObjectLit { span: DUMMY_SP, props }),
             );
             insert_hoisted_stmt(program, meta);
         });
@@ -106,6 +151,370 @@ impl CodeGenerateable for ImportMetaRef {
     }
 }
 
+/// Replaces `import.meta.resolve(specifier)` with the url of the module
+/// `specifier` resolves to.
+///
+/// The module is resolved like the url of a `new URL(specifier,
+/// import.meta.url)`, so it is emitted as an asset and its url points at the
+/// output asset. In a non-rendering env the `file://` url of the module is
+/// used instead, see [ImportMetaUrl].
+#[turbo_tasks::value]
+#[derive(Hash, Debug)]
+pub struct ImportMetaResolve {
+    origin: Vc<Box<dyn ResolveOrigin>>,
+    request: Vc<Request>,
+    compile_time_info: Vc<CompileTimeInfo>,
+    ast_path: Vc<AstPath>,
+    issue_source: Vc<IssueSource>,
+    in_try: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMetaResolve {
+    #[turbo_tasks::function]
+    pub fn new(
+        origin: Vc<Box<dyn ResolveOrigin>>,
+        request: Vc<Request>,
+        compile_time_info: Vc<CompileTimeInfo>,
+        ast_path: Vc<AstPath>,
+        issue_source: Vc<IssueSource>,
+        in_try: bool,
+    ) -> Vc<Self> {
+        ImportMetaResolve {
+            origin,
+            request,
+            compile_time_info,
+            ast_path,
+            issue_source,
+            in_try,
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn get_referenced_asset(self: Vc<Self>) -> Result<Vc<ReferencedAsset>> {
+        let this = self.await?;
+        Ok(ReferencedAsset::from_resolve_result(
+            self.resolve_reference(),
+            this.request,
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleReference for ImportMetaResolve {
+    #[turbo_tasks::function]
+    fn resolve_reference(&self) -> Vc<ModuleResolveResult> {
+        url_resolve(
+            self.origin,
+            self.request,
+            Value::new(UrlReferenceSubType::EcmaScriptNewUrl),
+            self.issue_source,
+            try_to_severity(self.in_try),
+        )
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ImportMetaResolve {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell(format!(
+            "import.meta.resolve({})",
+            self.request.to_string().await?,
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModuleReference for ImportMetaResolve {
+    #[turbo_tasks::function]
+    fn chunking_type(&self) -> Vc<ChunkingTypeOption> {
+        Vc::cell(Some(ChunkingType::Parallel))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for ImportMetaResolve {
+    #[turbo_tasks::function]
+    async fn code_generation(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    ) -> Result<Vc<CodeGeneration>> {
+        let this = self.await?;
+
+        // Like `new URL(…)`, the url of the output asset is relative to the
+        // origin of the server in rendering environments.
+        let origin = match &*this.compile_time_info.environment().rendering().await? {
+            Rendering::Client => Some(quote!("location.origin" as Expr)),
+            Rendering::Server(server_addr) => Some(server_addr.await?.to_string()?.into()),
+            Rendering::None => None,
+        };
+
+        let url = match &*self.get_referenced_asset().await? {
+            ReferencedAsset::Some(asset) => match origin {
+                Some(origin) => {
+                    // The chunk item of the asset exports the path of the
+                    // output asset.
+                    let id = asset
+                        .as_chunk_item(Vc::upcast(chunking_context))
+                        .id()
+                        .await?;
+                    url_href(
+                        quote!(
+                            "__turbopack_require__($id)" as Expr,
+                            id: Expr = module_id_to_lit(&id),
+                        ),
+                        origin,
+                    )
+                }
+                None => import_meta_url(asset.ident().path(), this.compile_time_info).await?,
+            },
+            ReferencedAsset::OriginalReferenceTypeExternal(request) => {
+                url_href(request.as_str().into(), origin.unwrap_or_else(meta_url))
+            }
+            ReferencedAsset::None => {
+                throw_module_not_found_expr(&request_to_string(this.request).await?)
+            }
+        };
+
+        let ast_path = &this.ast_path.await?;
+        let visitor = create_visitor!(ast_path, visit_mut_expr(expr: &mut Expr) {
+            *expr = url.clone();
+        });
+
+        Ok(CodeGeneration {
+            visitors: vec![visitor],
+        }
+        .into())
+    }
+}
+
+/// Replaces an `import.meta.resolve(specifier)` whose specifier isn't static
+/// with `specifier` resolved relative to `import.meta.url` at runtime.
+#[turbo_tasks::value(shared)]
+#[derive(Hash, Debug)]
+pub struct ImportMetaResolveAtRuntime {
+    ast_path: Vc<AstPath>,
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMetaResolveAtRuntime {
+    #[turbo_tasks::function]
+    pub fn new(ast_path: Vc<AstPath>) -> Vc<Self> {
+        ImportMetaResolveAtRuntime { ast_path }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for ImportMetaResolveAtRuntime {
+    #[turbo_tasks::function]
+    async fn code_generation(
+        &self,
+        _context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    ) -> Result<Vc<CodeGeneration>> {
+        let ast_path = &self.ast_path.await?;
+        let visitor = create_visitor!(ast_path, visit_mut_expr(expr: &mut Expr) {
+            resolve_at_runtime(expr);
+        });
+
+        Ok(CodeGeneration {
+            visitors: vec![visitor],
+        }
+        .into())
+    }
+}
+
+fn resolve_at_runtime(expr: &mut Expr) {
+    if let Expr::Call(CallExpr { args, .. }) = expr {
+        if let Some(ExprOrSpread {
+            expr: specifier, ..
+        }) = args.first()
+        {
+            *expr = url_href(*specifier.clone(), meta_url());
+        }
+    }
+}
+
+fn url_href(input: Expr, base: Expr) -> Expr {
+    quote!(
+        "new URL($input, $base).href" as Expr,
+        input: Expr = input,
+        base: Expr = base,
+    )
+}
+
+fn meta_url() -> Expr {
+    quote!("$meta.url" as Expr, meta = meta_ident())
+}
+
+/// A dependency of an `import.meta.hot.accept(deps, callback)` call. The
+/// specifier is replaced with the module id of the dependency, which the
+/// runtime uses to call `callback` when the dependency is updated.
+#[turbo_tasks::value]
+#[derive(Hash, Debug)]
+pub struct ImportMetaHotAcceptReference {
+    origin: Vc<Box<dyn ResolveOrigin>>,
+    request: Vc<Request>,
+    /// The index of the specifier in the array of dependencies, or `None` when
+    /// a single specifier was passed.
+    index: Option<usize>,
+    ast_path: Vc<AstPath>,
+    issue_source: Vc<IssueSource>,
+    in_try: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMetaHotAcceptReference {
+    #[turbo_tasks::function]
+    pub fn new(
+        origin: Vc<Box<dyn ResolveOrigin>>,
+        request: Vc<Request>,
+        index: Option<usize>,
+        ast_path: Vc<AstPath>,
+        issue_source: Vc<IssueSource>,
+        in_try: bool,
+    ) -> Vc<Self> {
+        ImportMetaHotAcceptReference {
+            origin,
+            request,
+            index,
+            ast_path,
+            issue_source,
+            in_try,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleReference for ImportMetaHotAcceptReference {
+    #[turbo_tasks::function]
+    fn resolve_reference(&self) -> Vc<ModuleResolveResult> {
+        esm_resolve(
+            self.origin,
+            self.request,
+            Value::new(EcmaScriptModulesReferenceSubType::Undefined),
+            Some(self.issue_source),
+            try_to_severity(self.in_try),
+        )
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ImportMetaHotAcceptReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell(format!(
+            "import.meta.hot.accept {}",
+            self.request.to_string().await?,
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModuleReference for ImportMetaHotAcceptReference {}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for ImportMetaHotAcceptReference {
+    #[turbo_tasks::function]
+    async fn code_generation(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    ) -> Result<Vc<CodeGeneration>> {
+        let this = self.await?;
+        let pm = PatternMapping::resolve_request(
+            this.request,
+            this.origin,
+            Vc::upcast(chunking_context),
+            self.resolve_reference(),
+            Value::new(ResolveType::Cjs),
+        )
+        .await?;
+        let id = pm.create();
+        let index = this.index;
+
+        let ast_path = &this.ast_path.await?;
+        let visitor = create_visitor!(ast_path, visit_mut_expr(expr: &mut Expr) {
+            let Expr::Call(CallExpr { args, .. }) = expr else {
+                return;
+            };
+            let Some(ExprOrSpread { expr: deps, .. }) = args.first_mut() else {
+                return;
+            };
+            match (index, &mut **deps) {
+                (None, deps) => *deps = id.clone(),
+                (Some(index), Expr::Array(ArrayLit { elems, .. })) => {
+                    if let Some(Some(ExprOrSpread { expr: dep, .. })) = elems.get_mut(index) {
+                        **dep = id.clone();
+                    }
+                }
+                _ => {}
+            }
+        });
+
+        Ok(CodeGeneration {
+            visitors: vec![visitor],
+        }
+        .into())
+    }
+}
+
+/// Generates the `import.meta.url` of the module at `path`, see
+/// [ImportMetaUrl].
+async fn import_meta_url(
+    path: Vc<FileSystemPath>,
+    compile_time_info: Vc<CompileTimeInfo>,
+) -> Result<Expr> {
+    let compile_time_info = compile_time_info.await?;
+    if let ImportMetaUrl::Served = compile_time_info.import_meta_url {
+        let served_path = format!("/{}", encode_path(&path.await?.path));
+        match &*compile_time_info.environment.rendering().await? {
+            Rendering::Client => {
+                return Ok(quote!(
+                    "new URL($path, location.origin).href" as Expr,
+                    path: Expr = served_path.into(),
+                ));
+            }
+            Rendering::Server(server_addr) => {
+                let origin = server_addr.await?.to_string()?;
+                return Ok(format!("{}{}", origin.trim_end_matches('/'), served_path).into());
+            }
+            Rendering::None => {}
+        }
+    }
+    Ok(as_abs_path(path).await?.as_str().map_or_else(
+        || {
+            quote!(
+                "(() => { throw new Error('could not convert import.meta.url to filepath') })()"
+                    as Expr
+            )
+        },
+        |path| format!("file://{}", encode_path(path)).into(),
+    ))
+}
+
+fn define_value_to_expr(value: &CompileTimeDefineValue) -> Expr {
+    match value {
+        CompileTimeDefineValue::Bool(value) => (*value).into(),
+        CompileTimeDefineValue::String(value) => value.clone().into(),
+        CompileTimeDefineValue::JSON(value) => {
+            quote!("JSON.parse($value)" as Expr, value: Expr = value.clone().into())
+        }
+    }
+}
+
+fn ident_prop_name(name: &str) -> PropName {
+    PropName::Ident(Ident::new(name.into(), DUMMY_SP))
+}
+
+fn prop(key: PropName, value: Expr) -> PropOrSpread {
+    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+        key,
+        value: Box::new(value),
+    })))
+}
+
 /// URL encodes special chars that would appear in the "pathname" portion.
 /// https://github.com/nodejs/node/blob/3bed5f11e039153eff5cbfd9513b8f55fd53fc43/lib/internal/url.js#L1513-L1526
 fn encode_path(path: &'_ str) -> Cow<'_, str> {
@@ -143,7 +552,33 @@ fn meta_ident() -> Ident {
 
 #[cfg(test)]
 mod test {
-    use super::encode_path;
+    use swc_core::{ecma::ast::Expr, quote};
+
+    use super::{encode_path, meta_ident, resolve_at_runtime, url_href};
+
+    #[test]
+    fn test_url_href() {
+        assert_eq!(
+            url_href(
+                quote!("__turbopack_require__(\"[project]/a.png\")" as Expr),
+                quote!("location.origin" as Expr),
+            ),
+            quote!(
+                "new URL(__turbopack_require__(\"[project]/a.png\"), location.origin).href" as Expr
+            )
+        );
+    }
+
+    #[test]
+    fn test_resolve_at_runtime() {
+        // `import.meta` is only parsed in modules.
+        let mut expr = quote!("resolve(name)" as Expr);
+        resolve_at_runtime(&mut expr);
+        assert_eq!(
+            expr,
+            quote!("new URL(name, $meta.url).href" as Expr, meta = meta_ident())
+        );
+    }
 
     #[test]
     fn test_encode_path_regular() {
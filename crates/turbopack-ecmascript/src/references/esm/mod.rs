@@ -11,8 +11,14 @@ pub use self::{
     base::EsmAssetReference,
     binding::EsmBinding,
     dynamic::EsmAsyncAssetReference,
-    export::{EsmExport, EsmExports},
-    meta::{ImportMetaBinding, ImportMetaRef},
+    export::{
+        follow_reexports, get_all_export_names, AllExportNames, EsmExport, EsmExports,
+        ExpandedExports, FollowExportsResult, FoundExportType,
+    },
+    meta::{
+        ImportMetaBinding, ImportMetaHotAcceptReference, ImportMetaRef, ImportMetaResolve,
+        ImportMetaResolveAtRuntime,
+    },
     module_item::EsmModuleItem,
     url::UrlAssetReference,
 };
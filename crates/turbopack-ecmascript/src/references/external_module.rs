@@ -0,0 +1,247 @@
+//! Modules which aren't bundled, but loaded at runtime.
+//!
+//! Requests matching the `externals` of the resolve options are resolved to an
+//! [ExternalModule]. Its chunk item only contains the code which loads the
+//! external with `require()`, `import()` or from a global variable, depending
+//! on its [ExternalType].
+
+use std::fmt::Write;
+
+use anyhow::{Context, Result};
+use turbo_tasks::{Value, Vc};
+use turbo_tasks_fs::{FileSystem, VirtualFileSystem};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{ChunkItem, ChunkType, ChunkableModule, ChunkingContext},
+    ident::AssetIdent,
+    issue::{code_gen::CodeGenerationIssue, IssueExt, IssueSeverity},
+    module::Module,
+    reference::ModuleReferences,
+    resolve::ExternalType,
+};
+
+use super::async_module::AsyncModuleOptions;
+use crate::{
+    chunk::{
+        EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkItemOptions,
+        EcmascriptChunkPlaceable, EcmascriptChunkType, EcmascriptChunkingContext,
+        EcmascriptExports,
+    },
+    utils::StringifyJs,
+};
+
+#[turbo_tasks::function]
+fn modifier(name: String, ty: Value<ExternalType>) -> Vc<String> {
+    Vc::cell(format!("{} external {}", *ty, name))
+}
+
+#[turbo_tasks::value]
+pub struct ExternalModule {
+    /// The request passed to `require()` or `import()`, or the name of the
+    /// global variable.
+    name: String,
+    ty: ExternalType,
+}
+
+#[turbo_tasks::value_impl]
+impl ExternalModule {
+    #[turbo_tasks::function]
+    pub fn new(name: String, ty: Value<ExternalType>) -> Vc<Self> {
+        Self::cell(ExternalModule {
+            name,
+            ty: ty.into_value(),
+        })
+    }
+
+    /// The request or the name of the global variable which is loaded.
+    #[turbo_tasks::function]
+    pub fn name(&self) -> Vc<String> {
+        Vc::cell(self.name.clone())
+    }
+
+    #[turbo_tasks::function]
+    pub fn ty(&self) -> Vc<ExternalType> {
+        self.ty.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Module for ExternalModule {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(VirtualFileSystem::new().root())
+            .with_modifier(modifier(self.name.clone(), Value::new(self.ty)))
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<ModuleReferences> {
+        ModuleReferences::empty()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for ExternalModule {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        unimplemented!()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModule for ExternalModule {
+    #[turbo_tasks::function]
+    async fn as_chunk_item(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<Vc<Box<dyn ChunkItem>>> {
+        let chunking_context =
+            Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkingContext>>(chunking_context)
+                .await?
+                .context(
+                    "chunking context must impl EcmascriptChunkingContext to use ExternalModule",
+                )?;
+        Ok(Vc::upcast(
+            ExternalModuleChunkItem {
+                module: self,
+                chunking_context,
+            }
+            .cell(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for ExternalModule {
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> Vc<EcmascriptExports> {
+        match self.ty {
+            ExternalType::EcmaScriptModule => EcmascriptExports::DynamicNamespace,
+            ExternalType::CommonJs | ExternalType::Global => EcmascriptExports::Value,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value]
+pub struct ExternalModuleChunkItem {
+    module: Vc<ExternalModule>,
+    chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for ExternalModuleChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> Vc<AssetIdent> {
+        self.module.ident()
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<ModuleReferences> {
+        self.module.references()
+    }
+
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        Vc::upcast(self.chunking_context)
+    }
+
+    #[turbo_tasks::function]
+    async fn ty(&self) -> Result<Vc<Box<dyn ChunkType>>> {
+        Ok(Vc::upcast(
+            Vc::<EcmascriptChunkType>::default().resolve().await?,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        Vc::upcast(self.module)
+    }
+
+    #[turbo_tasks::function]
+    async fn is_self_async(&self) -> Result<Vc<bool>> {
+        Ok(Vc::cell(
+            self.module.await?.ty == ExternalType::EcmaScriptModule,
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for ExternalModuleChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn EcmascriptChunkingContext>> {
+        self.chunking_context
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<EcmascriptChunkItemContent>> {
+        let module = self.module.await?;
+        let name = StringifyJs(&module.name);
+
+        let mut code = String::new();
+        if module.ty != ExternalType::Global
+            && !*self.chunking_context.environment().node_externals().await?
+        {
+            CodeGenerationIssue {
+                severity: IssueSeverity::Error.into(),
+                path: self.module.ident().path(),
+                title: Vc::cell("external module can't be loaded".to_string()),
+                message: Vc::cell(format!(
+                    "The request {} is configured as {} external, but the chunking context \
+                     doesn't support loading external modules. Configure it as a global external \
+                     or bundle it instead.",
+                    name, module.ty
+                )),
+            }
+            .cell()
+            .emit();
+            writeln!(
+                code,
+                "throw new Error({});",
+                StringifyJs(&format!(
+                    "external module {} can't be loaded in this environment",
+                    module.name
+                ))
+            )?;
+            return Ok(EcmascriptChunkItemContent {
+                inner_code: code.into(),
+                ..Default::default()
+            }
+            .cell());
+        }
+
+        let mut async_module = None;
+        match module.ty {
+            ExternalType::CommonJs => {
+                writeln!(
+                    code,
+                    "__turbopack_export_value__(__turbopack_external_require__({name}));"
+                )?;
+            }
+            ExternalType::EcmaScriptModule => {
+                writeln!(
+                    code,
+                    "const mod = await __turbopack_external_import__({name});"
+                )?;
+                writeln!(code, "__turbopack_export_namespace__(mod);")?;
+                async_module = Some(AsyncModuleOptions {
+                    has_top_level_await: true,
+                });
+            }
+            ExternalType::Global => {
+                writeln!(code, "__turbopack_export_value__(globalThis[{name}]);")?;
+            }
+        }
+
+        Ok(EcmascriptChunkItemContent {
+            inner_code: code.into(),
+            options: EcmascriptChunkItemOptions {
+                strict: true,
+                externals: module.ty != ExternalType::Global,
+                async_module,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+        .cell())
+    }
+}
@@ -3,7 +3,9 @@ pub mod async_module;
 pub mod cjs;
 pub mod constant_condition;
 pub mod constant_value;
+pub mod context_module;
 pub mod esm;
+pub mod external_module;
 pub mod node;
 pub mod pattern_mapping;
 pub mod raw;
@@ -12,6 +14,7 @@ pub mod type_issue;
 pub mod typescript;
 pub mod unreachable;
 pub mod util;
+pub mod worker;
 
 use std::{
     borrow::Cow,
@@ -53,7 +56,9 @@ use turbopack_core::{
     error::PrettyPrintError,
     issue::{analyze::AnalyzeIssue, IssueExt, IssueSeverity, IssueSource},
     module::Module,
-    reference::{ModuleReference, ModuleReferences, SourceMapReference},
+    reference::{
+        ModuleReference, ModuleReferences, SingleOutputAssetReference, SourceMapReference,
+    },
     reference_type::{CommonJsReferenceSubType, ReferenceType},
     resolve::{
         find_context_file,
@@ -63,7 +68,7 @@ use turbopack_core::{
         pattern::Pattern,
         resolve, FindContextFileResult, ModulePart,
     },
-    source::Source,
+    source::{EmittingSource, Source},
 };
 use turbopack_swc_utils::emitter::IssueEmitter;
 use unreachable::Unreachable;
@@ -76,11 +81,13 @@ use self::{
     cjs::CjsAssetReference,
     esm::{
         export::EsmExport, EsmAssetReference, EsmAsyncAssetReference, EsmExports, EsmModuleItem,
-        ImportMetaBinding, ImportMetaRef, UrlAssetReference,
+        ImportMetaBinding, ImportMetaHotAcceptReference, ImportMetaRef, ImportMetaResolve,
+        ImportMetaResolveAtRuntime, UrlAssetReference,
     },
     node::{DirAssetReference, PackageJsonReference},
     raw::FileSourceReference,
     typescript::{TsConfigReference, TsReferencePathAssetReference, TsReferenceTypeAssetReference},
+    worker::{ServiceWorkerAssetReference, WorkerAssetReference},
 };
 use super::{
     analyzer::{
@@ -117,14 +124,20 @@ use crate::{
     references::{
         async_module::{AsyncModule, OptionAsyncModule},
         cjs::{CjsRequireAssetReference, CjsRequireCacheAccess, CjsRequireResolveAssetReference},
-        esm::{module_id::EsmModuleIdAssetReference, EsmBinding},
+        context_module::{context_request, ContextModuleAssetReference},
+        esm::{
+            dynamic::{collect_import_magic_comments, ImportMagicComments},
+            module_id::EsmModuleIdAssetReference,
+            EsmBinding,
+        },
+        pattern_mapping::ResolveType,
         require_context::{RequireContextAssetReference, RequireContextMap},
         type_issue::SpecifiedModuleTypeIssue,
     },
     resolve::try_to_severity,
     tree_shake::{part_of_module, split},
     typescript::resolve::tsconfig,
-    EcmascriptInputTransforms, EcmascriptModuleAsset, SpecifiedModuleType,
+    EcmascriptInputTransforms, EcmascriptModuleAsset, EcmascriptOptions, SpecifiedModuleType,
 };
 
 #[turbo_tasks::value(shared)]
@@ -133,10 +146,26 @@ pub struct AnalyzeEcmascriptModuleResult {
     pub code_generation: Vc<CodeGenerateables>,
     pub exports: Vc<EcmascriptExports>,
     pub async_module: Vc<OptionAsyncModule>,
+    /// The compile-time values inlined into the module.
+    pub inlined_values: Vc<InlinedValues>,
     /// `true` when the analysis was successful.
     pub successful: bool,
 }
 
+/// The number of places each compile-time value is inlined at, by the
+/// dot-separated name of the value (eg `process.env.API_URL`). The values
+/// themselves are not included, as they might be secrets.
+#[turbo_tasks::value(transparent)]
+pub struct InlinedValues(IndexMap<String, usize>);
+
+#[turbo_tasks::value_impl]
+impl InlinedValues {
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        Vc::cell(IndexMap::new())
+    }
+}
+
 /// A temporary analysis result builder to pass around, to be turned into an
 /// `Vc<AnalyzeEcmascriptModuleResult>` eventually.
 pub(crate) struct AnalyzeEcmascriptModuleResultBuilder {
@@ -144,6 +173,7 @@ pub(crate) struct AnalyzeEcmascriptModuleResultBuilder {
     code_gens: Vec<CodeGen>,
     exports: EcmascriptExports,
     async_module: Vc<OptionAsyncModule>,
+    inlined_values: IndexMap<String, usize>,
     successful: bool,
 }
 
@@ -154,6 +184,7 @@ impl AnalyzeEcmascriptModuleResultBuilder {
             code_gens: Vec::new(),
             exports: EcmascriptExports::None,
             async_module: Vc::cell(None),
+            inlined_values: IndexMap::new(),
             successful: false,
         }
     }
@@ -187,6 +218,12 @@ impl AnalyzeEcmascriptModuleResultBuilder {
             )));
     }
 
+    /// Records that the compile-time value with the given name has been
+    /// inlined.
+    pub fn add_inlined_value(&mut self, name: &[String]) {
+        *self.inlined_values.entry(name.join(".")).or_default() += 1;
+    }
+
     /// Sets the analysis result ES export.
     pub fn set_exports(&mut self, exports: EcmascriptExports) {
         self.exports = exports;
@@ -225,6 +262,7 @@ impl AnalyzeEcmascriptModuleResultBuilder {
                 code_generation: Vc::cell(self.code_gens),
                 exports: self.exports.into(),
                 async_module: self.async_module,
+                inlined_values: Vc::cell(self.inlined_values),
                 successful: self.successful,
             },
         ))
@@ -265,7 +303,9 @@ struct AnalysisState<'a> {
     // There can be many references to import.meta, but only the first should hoist
     // the object allocation.
     first_import_meta: bool,
-    import_parts: bool,
+    options: EcmascriptOptions,
+    /// Magic comments of `import()` expressions by the span of the call.
+    import_magic_comments: HashMap<Span, ImportMagicComments>,
 }
 
 impl<'a> AnalysisState<'a> {
@@ -307,6 +347,15 @@ pub(crate) async fn analyze_ecmascript_module(
     let mut analysis = AnalyzeEcmascriptModuleResultBuilder::new();
     let path = origin.origin_path();
 
+    if let Some(source) = Vc::try_resolve_sidecast::<Box<dyn EmittingSource>>(source).await? {
+        for &asset in source.emitted_assets().await?.iter() {
+            analysis.add_reference(SingleOutputAssetReference::new(
+                asset,
+                Vc::cell("emitted file".to_string()),
+            ));
+        }
+    }
+
     // Is this a typescript file that requires analzying type references?
     let analyze_types = match &*ty {
         EcmascriptModuleAssetType::TypescriptWithTypes
@@ -445,6 +494,7 @@ pub(crate) async fn analyze_ecmascript_module(
             } else {
                 None
             },
+            Value::new(options.cjs_interop),
         );
         import_references.push(r);
     }
@@ -666,7 +716,8 @@ pub(crate) async fn analyze_ecmascript_module(
         var_graph: &var_graph,
         fun_args_values: Mutex::new(HashMap::<u32, Vec<JsValue>>::new()),
         first_import_meta: true,
-        import_parts: options.import_parts,
+        options,
+        import_magic_comments: collect_import_magic_comments(program, &**comments),
     };
 
     enum Action {
@@ -936,11 +987,86 @@ pub(crate) async fn analyze_ecmascript_module(
             } => {
                 if analysis_state.first_import_meta {
                     analysis_state.first_import_meta = false;
-                    analysis.add_code_gen(ImportMetaBinding::new(source.ident().path()));
+                    analysis.add_code_gen(ImportMetaBinding::new(
+                        source.ident().path(),
+                        compile_time_info,
+                    ));
                 }
 
                 analysis.add_code_gen(ImportMetaRef::new(Vc::cell(ast_path)));
             }
+            Effect::ImportMetaResolve {
+                input,
+                ast_path,
+                span,
+                in_try,
+            } => {
+                let pat = js_value_to_pattern(&input);
+                match pat.into_string() {
+                    Some(request) => analysis.add_reference(ImportMetaResolve::new(
+                        origin,
+                        Request::parse(Value::new(request.into())),
+                        compile_time_info,
+                        Vc::cell(ast_path),
+                        IssueSource::from_byte_offset(
+                            source,
+                            span.lo.to_usize(),
+                            span.hi.to_usize(),
+                        ),
+                        in_try,
+                    )),
+                    None => {
+                        handler.span_warn_with_code(
+                            span,
+                            &format!(
+                                "import.meta.resolve({input}) is very dynamic, it's resolved \
+                                 relative to import.meta.url at runtime"
+                            ),
+                            DiagnosticId::Lint(
+                                errors::failed_to_analyse::ecmascript::IMPORT_META_RESOLVE
+                                    .to_string(),
+                            ),
+                        );
+                        analysis.add_code_gen(ImportMetaResolveAtRuntime::new(Vc::cell(ast_path)));
+                    }
+                }
+            }
+            Effect::ImportMetaHotAccept {
+                deps,
+                ast_path,
+                span,
+                in_try,
+            } => {
+                let ast_path = Vc::cell(ast_path);
+                let issue_source =
+                    IssueSource::from_byte_offset(source, span.lo.to_usize(), span.hi.to_usize());
+                for (dep, index) in deps {
+                    analysis.add_reference(ImportMetaHotAcceptReference::new(
+                        origin,
+                        Request::parse(Value::new(dep.into())),
+                        index,
+                        ast_path,
+                        issue_source,
+                        in_try,
+                    ));
+                }
+            }
+            Effect::ImportMetaEnv { key, ast_path, .. } => {
+                if let Some(import_meta_env) = compile_time_info.await?.import_meta_env {
+                    if let Some(value) = import_meta_env.await?.get(&key) {
+                        analysis.add_inlined_value(&[
+                            "import".to_string(),
+                            "meta".to_string(),
+                            "env".to_string(),
+                            key,
+                        ]);
+                        analysis.add_code_gen(ConstantValue::new(
+                            Value::new(value.clone()),
+                            Vc::cell(ast_path),
+                        ));
+                    }
+                }
+            }
             Effect::Url {
                 input,
                 ast_path,
@@ -966,6 +1092,64 @@ pub(crate) async fn analyze_ecmascript_module(
                     in_try,
                 ));
             }
+            Effect::Worker {
+                input,
+                shared,
+                module,
+                ast_path,
+                span,
+                in_try,
+            } => {
+                let pat = js_value_to_pattern(&input);
+                if !pat.has_constant_parts() {
+                    handler.span_warn_with_code(
+                        span,
+                        &format!(
+                            "new {}(new URL({input}, import.meta.url)) is very dynamic",
+                            if shared { "SharedWorker" } else { "Worker" }
+                        ),
+                        DiagnosticId::Lint(
+                            errors::failed_to_analyse::ecmascript::NEW_WORKER.to_string(),
+                        ),
+                    )
+                }
+                analysis.add_reference(WorkerAssetReference::new(
+                    origin,
+                    Request::parse(Value::new(pat)),
+                    Vc::cell(ast_path),
+                    IssueSource::from_byte_offset(source, span.lo.to_usize(), span.hi.to_usize()),
+                    in_try,
+                    shared,
+                    module,
+                ));
+            }
+            Effect::ServiceWorker {
+                input,
+                ast_path,
+                span,
+                in_try,
+            } => {
+                let pat = js_value_to_pattern(&input);
+                if !pat.has_constant_parts() {
+                    handler.span_warn_with_code(
+                        span,
+                        &format!(
+                            "navigator.serviceWorker.register(new URL({input}, import.meta.url)) \
+                             is very dynamic"
+                        ),
+                        DiagnosticId::Lint(
+                            errors::failed_to_analyse::ecmascript::SERVICE_WORKER.to_string(),
+                        ),
+                    )
+                }
+                analysis.add_reference(ServiceWorkerAssetReference::new(
+                    origin,
+                    Request::parse(Value::new(pat)),
+                    Vc::cell(ast_path),
+                    IssueSource::from_byte_offset(source, span.lo.to_usize(), span.hi.to_usize()),
+                    in_try,
+                ));
+            }
         }
     }
 
@@ -1014,6 +1198,7 @@ async fn handle_call<G: Fn(Vec<Effect>) + Send + Sync>(
         origin,
         source,
         compile_time_info,
+        options,
         ..
     } = state;
     fn explain_args(args: &[JsValue]) -> (String, String) {
@@ -1056,6 +1241,15 @@ async fn handle_call<G: Fn(Vec<Effect>) + Send + Sync>(
             }
         }
         JsValue::WellKnownFunction(WellKnownFunctionKind::Import) => {
+            let magic_comments = state
+                .import_magic_comments
+                .get(&span)
+                .cloned()
+                .unwrap_or_default();
+            if magic_comments.ignore {
+                // The import is left to the runtime's native `import()`.
+                return Ok(());
+            }
             let args = linked_args(args).await?;
             if args.len() == 1 {
                 let pat = js_value_to_pattern(&args[0]);
@@ -1069,12 +1263,30 @@ async fn handle_call<G: Fn(Vec<Effect>) + Send + Sync>(
                         ),
                     )
                 }
+                if let Some((dir, file_pattern)) = context_request(&pat) {
+                    analysis.add_reference(ContextModuleAssetReference::new(
+                        source,
+                        origin,
+                        dir,
+                        Value::new(file_pattern),
+                        Value::new(options),
+                        magic_comments.clone().cell(),
+                        Value::new(ResolveType::EsmAsync),
+                        Vc::cell(ast_path.to_vec()),
+                        issue_source(source, span),
+                        in_try,
+                    ));
+                    return Ok(());
+                }
                 analysis.add_reference(EsmAsyncAssetReference::new(
                     origin,
                     Request::parse(Value::new(pat)),
                     Vc::cell(ast_path.to_vec()),
                     issue_source(source, span),
                     in_try,
+                    magic_comments.loading_hints.cell(),
+                    magic_comments.chunk_name,
+                    Value::new(options.cjs_interop),
                 ));
                 return Ok(());
             }
@@ -1101,6 +1313,21 @@ async fn handle_call<G: Fn(Vec<Effect>) + Send + Sync>(
                         ),
                     )
                 }
+                if let Some((dir, file_pattern)) = context_request(&pat) {
+                    analysis.add_reference(ContextModuleAssetReference::new(
+                        source,
+                        origin,
+                        dir,
+                        Value::new(file_pattern),
+                        Value::new(options),
+                        ImportMagicComments::default().cell(),
+                        Value::new(ResolveType::Cjs),
+                        Vc::cell(ast_path.to_vec()),
+                        issue_source(source, span),
+                        in_try,
+                    ));
+                    return Ok(());
+                }
                 analysis.add_reference(CjsRequireAssetReference::new(
                     origin,
                     Request::parse(Value::new(pat)),
@@ -1654,7 +1881,8 @@ async fn handle_member(
                     continue;
                 }
                 if obj.iter_defineable_name_rev().eq(it)
-                    && handle_free_var_reference(ast_path, value, span, state, analysis).await?
+                    && handle_free_var_reference(ast_path, name, value, span, state, analysis)
+                        .await?
                 {
                     return Ok(());
                 }
@@ -1696,7 +1924,7 @@ async fn handle_free_var(
             if var
                 .iter_defineable_name_rev()
                 .eq(name.iter().map(Cow::Borrowed).rev())
-                && handle_free_var_reference(ast_path, value, span, state, analysis).await?
+                && handle_free_var_reference(ast_path, name, value, span, state, analysis).await?
             {
                 return Ok(());
             }
@@ -1708,6 +1936,7 @@ async fn handle_free_var(
 
 async fn handle_free_var_reference(
     ast_path: &[AstParentKind],
+    name: &[String],
     value: &FreeVarReference,
     span: Span,
     state: &AnalysisState<'_>,
@@ -1737,6 +1966,7 @@ async fn handle_free_var_reference(
         ),
 
         FreeVarReference::Value(value) => {
+            analysis.add_inlined_value(name);
             analysis.add_code_gen(ConstantValue::new(
                 Value::new(value.clone()),
                 Vc::cell(ast_path.to_vec()),
@@ -1757,6 +1987,7 @@ async fn handle_free_var_reference(
                 Request::parse(Value::new(request.clone().into())),
                 Default::default(),
                 state
+                    .options
                     .import_parts
                     .then(|| {
                         export
@@ -1764,6 +1995,7 @@ async fn handle_free_var_reference(
                             .map(|export| ModulePart::export(export.to_string()))
                     })
                     .flatten(),
+                Value::new(state.options.cjs_interop),
             )
             .resolve()
             .await?;
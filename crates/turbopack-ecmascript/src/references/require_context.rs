@@ -18,8 +18,8 @@ use turbo_tasks_fs::{DirectoryContent, DirectoryEntry, FileSystemPath};
 use turbopack_core::{
     asset::{Asset, AssetContent},
     chunk::{
-        ChunkItem, ChunkItemExt, ChunkType, ChunkableModule, ChunkableModuleReference,
-        ChunkingContext,
+        AsyncLoadingHints, ChunkItem, ChunkItemExt, ChunkType, ChunkableModule,
+        ChunkableModuleReference, ChunkingContext, ChunkingType, ChunkingTypeOption,
     },
     ident::AssetIdent,
     issue::{IssueSeverity, IssueSource},
@@ -37,10 +37,11 @@ use crate::{
     code_gen::CodeGeneration,
     create_visitor,
     references::{
-        pattern_mapping::{PatternMapping, ResolveType::Cjs},
+        esm::dynamic::ImportMagicComments,
+        pattern_mapping::{PatternMapping, ResolveType},
         AstPath,
     },
-    resolve::{cjs_resolve, try_to_severity},
+    resolve::{cjs_resolve, esm_resolve, try_to_severity},
     utils::module_id_to_lit,
     CodeGenerateable, EcmascriptChunkPlaceable,
 };
@@ -153,6 +154,34 @@ pub struct RequireContextMapEntry {
     pub result: Vc<ModuleResolveResult>,
 }
 
+impl RequireContextMapEntry {
+    /// Resolves a file of a context by its path relative to the origin.
+    pub(crate) fn resolve(
+        origin: Vc<Box<dyn ResolveOrigin>>,
+        origin_relative: String,
+        resolve_type: ResolveType,
+        issue_source: Option<Vc<IssueSource>>,
+        issue_severity: Vc<IssueSeverity>,
+    ) -> Self {
+        let request = Request::parse(Value::new(origin_relative.clone().into()));
+        let result = match resolve_type {
+            ResolveType::Cjs => cjs_resolve(origin, request, issue_source, issue_severity),
+            ResolveType::EsmAsync => esm_resolve(
+                origin,
+                request,
+                Default::default(),
+                issue_source,
+                issue_severity,
+            ),
+        };
+        RequireContextMapEntry {
+            origin_relative,
+            request,
+            result,
+        }
+    }
+}
+
 /// The resolved context map for a `require.context(..)` call.
 #[turbo_tasks::value(transparent)]
 pub struct RequireContextMap(IndexMap<String, RequireContextMapEntry>);
@@ -176,16 +205,15 @@ impl RequireContextMap {
 
         for (context_relative, path) in list {
             if let Some(origin_relative) = origin_path.get_relative_path_to(&*path.await?) {
-                let request = Request::parse(Value::new(origin_relative.clone().into()));
-                let result = cjs_resolve(origin, request, issue_source, issue_severity);
-
                 map.insert(
                     context_relative.clone(),
-                    RequireContextMapEntry {
+                    RequireContextMapEntry::resolve(
+                        origin,
                         origin_relative,
-                        request,
-                        result,
-                    },
+                        ResolveType::Cjs,
+                        issue_source,
+                        issue_severity,
+                    ),
                 );
             } else {
                 bail!("invariant error: this was already checked in `list_dir`");
@@ -231,15 +259,14 @@ impl RequireContextAssetReference {
             issue_source,
             try_to_severity(in_try),
         );
-        let inner = RequireContextAsset {
+        let inner = RequireContextAsset::new(
             source,
             origin,
             map,
-
-            dir: dir.clone(),
-            include_subdirs,
-        }
-        .cell();
+            description(&dir, include_subdirs),
+            Value::new(ResolveType::Cjs),
+            ImportMagicComments::default().cell(),
+        );
 
         Self::cell(RequireContextAssetReference {
             inner,
@@ -264,14 +291,18 @@ impl ModuleReference for RequireContextAssetReference {
 impl ValueToString for RequireContextAssetReference {
     #[turbo_tasks::function]
     async fn to_string(&self) -> Result<Vc<String>> {
-        Ok(Vc::cell(format!(
-            "require.context {}/{}",
-            self.dir,
-            if self.include_subdirs { "**" } else { "*" },
-        )))
+        Ok(Vc::cell(description(&self.dir, self.include_subdirs)))
     }
 }
 
+fn description(dir: &str, include_subdirs: bool) -> String {
+    format!(
+        "require.context {}/{}",
+        dir,
+        if include_subdirs { "**" } else { "*" },
+    )
+}
+
 #[turbo_tasks::value_impl]
 impl ChunkableModuleReference for RequireContextAssetReference {}
 
@@ -323,6 +354,51 @@ impl ValueToString for ResolvedModuleReference {
 #[turbo_tasks::value_impl]
 impl ChunkableModuleReference for ResolvedModuleReference {}
 
+/// A reference from an `import()` context module to one of its modules, which
+/// is placed in its own async chunk group.
+#[turbo_tasks::value]
+pub struct AsyncResolvedModuleReference {
+    result: Vc<ModuleResolveResult>,
+    magic_comments: Vc<ImportMagicComments>,
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleReference for AsyncResolvedModuleReference {
+    #[turbo_tasks::function]
+    fn resolve_reference(&self) -> Vc<ModuleResolveResult> {
+        self.result
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for AsyncResolvedModuleReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell("resolved async reference".to_string()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModuleReference for AsyncResolvedModuleReference {
+    #[turbo_tasks::function]
+    fn chunking_type(&self) -> Vc<ChunkingTypeOption> {
+        Vc::cell(Some(ChunkingType::Async))
+    }
+
+    #[turbo_tasks::function]
+    async fn async_loading_hints(&self) -> Result<Vc<AsyncLoadingHints>> {
+        Ok(self.magic_comments.await?.loading_hints.cell())
+    }
+
+    #[turbo_tasks::function]
+    async fn async_chunk_name(&self) -> Result<Vc<Option<String>>> {
+        Ok(Vc::cell(self.magic_comments.await?.chunk_name.clone()))
+    }
+}
+
+/// The module of a context, which maps the requests of the context to the
+/// ids of their modules. It is used for `require.context()` calls and for
+/// partially dynamic `require()` and `import()` requests.
 #[turbo_tasks::value]
 pub struct RequireContextAsset {
     source: Vc<Box<dyn Source>>,
@@ -330,17 +406,35 @@ pub struct RequireContextAsset {
     origin: Vc<Box<dyn ResolveOrigin>>,
     map: Vc<RequireContextMap>,
 
-    dir: String,
-    include_subdirs: bool,
+    /// Describes the context in the ident, e.g. `require.context ./pages/**`.
+    description: String,
+    /// With [ResolveType::EsmAsync], every module of the map is placed in its
+    /// own async chunk group and the map points to their loaders.
+    resolve_type: ResolveType,
+    /// The loading hints and chunk name of those async chunk groups.
+    magic_comments: Vc<ImportMagicComments>,
 }
 
-#[turbo_tasks::function]
-fn modifier(dir: String, include_subdirs: bool) -> Vc<String> {
-    Vc::cell(format!(
-        "require.context {}/{}",
-        dir,
-        if include_subdirs { "**" } else { "*" },
-    ))
+#[turbo_tasks::value_impl]
+impl RequireContextAsset {
+    #[turbo_tasks::function]
+    pub(crate) fn new(
+        source: Vc<Box<dyn Source>>,
+        origin: Vc<Box<dyn ResolveOrigin>>,
+        map: Vc<RequireContextMap>,
+        description: String,
+        resolve_type: Value<ResolveType>,
+        magic_comments: Vc<ImportMagicComments>,
+    ) -> Vc<Self> {
+        Self::cell(RequireContextAsset {
+            source,
+            origin,
+            map,
+            description,
+            resolve_type: resolve_type.into_value(),
+            magic_comments,
+        })
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -349,7 +443,7 @@ impl Module for RequireContextAsset {
     fn ident(&self) -> Vc<AssetIdent> {
         self.source
             .ident()
-            .with_modifier(modifier(self.dir.clone(), self.include_subdirs))
+            .with_modifier(Vc::cell(self.description.clone()))
     }
 
     #[turbo_tasks::function]
@@ -358,7 +452,18 @@ impl Module for RequireContextAsset {
 
         Ok(Vc::cell(
             map.iter()
-                .map(|(_, entry)| Vc::upcast(Vc::<ResolvedModuleReference>::cell(entry.result)))
+                .map(|(_, entry)| match self.resolve_type {
+                    ResolveType::Cjs => {
+                        Vc::upcast(Vc::<ResolvedModuleReference>::cell(entry.result))
+                    }
+                    ResolveType::EsmAsync => Vc::upcast(
+                        AsyncResolvedModuleReference {
+                            result: entry.result,
+                            magic_comments: self.magic_comments,
+                        }
+                        .cell(),
+                    ),
+                })
                 .collect(),
         ))
     }
@@ -394,6 +499,7 @@ impl ChunkableModule for RequireContextAsset {
 
                 origin: this.origin,
                 map: this.map,
+                resolve_type: this.resolve_type,
             }
             .cell(),
         ))
@@ -415,6 +521,7 @@ pub struct RequireContextChunkItem {
 
     origin: Vc<Box<dyn ResolveOrigin>>,
     map: Vc<RequireContextMap>,
+    resolve_type: ResolveType,
 }
 
 #[turbo_tasks::value_impl]
@@ -439,7 +546,7 @@ impl EcmascriptChunkItem for RequireContextChunkItem {
                 self.origin,
                 Vc::upcast(self.chunking_context),
                 entry.result,
-                Value::new(Cjs),
+                Value::new(self.resolve_type),
             )
             .await?;
 
@@ -0,0 +1,283 @@
+use anyhow::Result;
+use swc_core::{
+    ecma::ast::{CallExpr, Expr, ExprOrSpread, NewExpr},
+    quote,
+};
+use turbo_tasks::{Value, ValueToString, Vc};
+use turbopack_core::{
+    chunk::{
+        ChunkItemExt, ChunkableModule, ChunkableModuleReference, ChunkingType, ChunkingTypeOption,
+    },
+    issue::IssueSource,
+    reference::ModuleReference,
+    reference_type::EcmaScriptModulesReferenceSubType,
+    resolve::{origin::ResolveOrigin, parse::Request, ModuleResolveResult},
+};
+
+use crate::{
+    chunk::EcmascriptChunkingContext,
+    code_gen::{CodeGenerateable, CodeGeneration},
+    create_visitor,
+    references::AstPath,
+    resolve::{esm_resolve, try_to_severity},
+    service_worker_chunk::ServiceWorkerLoaderModule,
+    utils::module_id_to_lit,
+    worker_chunk::WorkerLoaderModule,
+};
+
+/// Worker Asset References are injected during code analysis when we find a
+/// (staticly analyzable) `new Worker(new URL("path", import.meta.url))` or
+/// `new SharedWorker(new URL("path", import.meta.url))`.
+///
+/// The worker entry is put into its own chunk group with its own runtime, and
+/// the `URL` argument is rewritten to the URL of a script loading these chunks.
+#[turbo_tasks::value]
+pub struct WorkerAssetReference {
+    pub origin: Vc<Box<dyn ResolveOrigin>>,
+    pub request: Vc<Request>,
+    pub ast_path: Vc<AstPath>,
+    pub issue_source: Vc<IssueSource>,
+    pub in_try: bool,
+    /// Whether this is a `SharedWorker` instead of a dedicated `Worker`.
+    pub shared: bool,
+    /// Whether the worker is created with `{ type: "module" }`.
+    pub module: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl WorkerAssetReference {
+    #[turbo_tasks::function]
+    pub fn new(
+        origin: Vc<Box<dyn ResolveOrigin>>,
+        request: Vc<Request>,
+        ast_path: Vc<AstPath>,
+        issue_source: Vc<IssueSource>,
+        in_try: bool,
+        shared: bool,
+        module: bool,
+    ) -> Vc<Self> {
+        Self::cell(WorkerAssetReference {
+            origin,
+            request,
+            ast_path,
+            issue_source,
+            in_try,
+            shared,
+            module,
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleReference for WorkerAssetReference {
+    #[turbo_tasks::function]
+    async fn resolve_reference(&self) -> Result<Vc<ModuleResolveResult>> {
+        let result = esm_resolve(
+            self.origin,
+            self.request,
+            Value::new(EcmaScriptModulesReferenceSubType::Undefined),
+            Some(self.issue_source),
+            try_to_severity(self.in_try),
+        );
+        let Some(module) = *result.first_module().await? else {
+            return Ok(result);
+        };
+        let Some(chunkable) = Vc::try_resolve_sidecast::<Box<dyn ChunkableModule>>(module).await?
+        else {
+            return Ok(result);
+        };
+        Ok(ModuleResolveResult::module_with_references(
+            Vc::upcast(WorkerLoaderModule::new(chunkable, self.module)),
+            result.await?.get_references().clone(),
+        )
+        .cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for WorkerAssetReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell(format!(
+            "new {}(new URL({}))",
+            if self.shared {
+                "SharedWorker"
+            } else {
+                "Worker"
+            },
+            self.request.to_string().await?,
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModuleReference for WorkerAssetReference {
+    #[turbo_tasks::function]
+    fn chunking_type(&self) -> Vc<ChunkingTypeOption> {
+        Vc::cell(Some(ChunkingType::Parallel))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for WorkerAssetReference {
+    #[turbo_tasks::function]
+    async fn code_generation(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    ) -> Result<Vc<CodeGeneration>> {
+        let this = self.await?;
+        let mut visitors = vec![];
+
+        if let Some(module) = *self.resolve_reference().first_module().await? {
+            if let Some(loader) =
+                Vc::try_resolve_downcast_type::<WorkerLoaderModule>(module).await?
+            {
+                let id = loader
+                    .as_chunk_item(Vc::upcast(chunking_context))
+                    .id()
+                    .await?;
+
+                // We rewrite the `new URL()` argument of the worker constructor to be a
+                // require() of the worker loader, which exports the URL of a script
+                // loading the worker's chunks.
+                let ast_path = this.ast_path.await?;
+                visitors.push(
+                    create_visitor!(ast_path, visit_mut_expr(new_expr: &mut Expr) {
+                        if let Expr::New(NewExpr { args: Some(args), .. }) = new_expr {
+                            if let Some(ExprOrSpread { box expr, spread: None }) = args.get_mut(0) {
+                                *expr = quote!(
+                                    "__turbopack_require__($id)" as Expr,
+                                    id: Expr = module_id_to_lit(&id),
+                                );
+                            }
+                        }
+                    }),
+                );
+            }
+        }
+
+        Ok(CodeGeneration { visitors }.into())
+    }
+}
+
+/// Service Worker Asset References are injected during code analysis when we
+/// find a (staticly analyzable)
+/// `navigator.serviceWorker.register(new URL("path", import.meta.url))`.
+///
+/// The service worker entry is bundled into a single script at a stable URL,
+/// which replaces the `URL` argument of the `register()` call.
+#[turbo_tasks::value]
+pub struct ServiceWorkerAssetReference {
+    pub origin: Vc<Box<dyn ResolveOrigin>>,
+    pub request: Vc<Request>,
+    pub ast_path: Vc<AstPath>,
+    pub issue_source: Vc<IssueSource>,
+    pub in_try: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl ServiceWorkerAssetReference {
+    #[turbo_tasks::function]
+    pub fn new(
+        origin: Vc<Box<dyn ResolveOrigin>>,
+        request: Vc<Request>,
+        ast_path: Vc<AstPath>,
+        issue_source: Vc<IssueSource>,
+        in_try: bool,
+    ) -> Vc<Self> {
+        Self::cell(ServiceWorkerAssetReference {
+            origin,
+            request,
+            ast_path,
+            issue_source,
+            in_try,
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleReference for ServiceWorkerAssetReference {
+    #[turbo_tasks::function]
+    async fn resolve_reference(&self) -> Result<Vc<ModuleResolveResult>> {
+        let result = esm_resolve(
+            self.origin,
+            self.request,
+            Value::new(EcmaScriptModulesReferenceSubType::Undefined),
+            Some(self.issue_source),
+            try_to_severity(self.in_try),
+        );
+        let Some(module) = *result.first_module().await? else {
+            return Ok(result);
+        };
+        let Some(chunkable) = Vc::try_resolve_sidecast::<Box<dyn ChunkableModule>>(module).await?
+        else {
+            return Ok(result);
+        };
+        Ok(ModuleResolveResult::module_with_references(
+            Vc::upcast(ServiceWorkerLoaderModule::new(chunkable)),
+            result.await?.get_references().clone(),
+        )
+        .cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ServiceWorkerAssetReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell(format!(
+            "navigator.serviceWorker.register(new URL({}))",
+            self.request.to_string().await?,
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModuleReference for ServiceWorkerAssetReference {
+    #[turbo_tasks::function]
+    fn chunking_type(&self) -> Vc<ChunkingTypeOption> {
+        Vc::cell(Some(ChunkingType::Parallel))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for ServiceWorkerAssetReference {
+    #[turbo_tasks::function]
+    async fn code_generation(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    ) -> Result<Vc<CodeGeneration>> {
+        let this = self.await?;
+        let mut visitors = vec![];
+
+        if let Some(module) = *self.resolve_reference().first_module().await? {
+            if let Some(loader) =
+                Vc::try_resolve_downcast_type::<ServiceWorkerLoaderModule>(module).await?
+            {
+                let id = loader
+                    .as_chunk_item(Vc::upcast(chunking_context))
+                    .id()
+                    .await?;
+
+                // We rewrite the `new URL()` argument of the `register()` call to be a
+                // require() of the service worker loader, which exports the URL of the
+                // service worker script.
+                let ast_path = this.ast_path.await?;
+                visitors.push(
+                    create_visitor!(ast_path, visit_mut_expr(call_expr: &mut Expr) {
+                        if let Expr::Call(CallExpr { args, .. }) = call_expr {
+                            if let Some(ExprOrSpread { box expr, spread: None }) = args.get_mut(0) {
+                                *expr = quote!(
+                                    "__turbopack_require__($id)" as Expr,
+                                    id: Expr = module_id_to_lit(&id),
+                                );
+                            }
+                        }
+                    }),
+                );
+            }
+        }
+
+        Ok(CodeGeneration { visitors }.into())
+    }
+}
@@ -0,0 +1,115 @@
+use anyhow::{Context, Result};
+use turbo_tasks::Vc;
+use turbo_tasks_hash::hash_xxh3_hash64;
+use turbopack_core::{
+    chunk::{ChunkItem, ChunkType, ChunkingContext, EvaluatableAsset, EvaluatableAssets},
+    ident::AssetIdent,
+    module::Module,
+    output::OutputAsset,
+    reference::{ModuleReferences, SingleOutputAssetReference},
+};
+
+use super::module::ServiceWorkerLoaderModule;
+use crate::chunk::{
+    asset_url_code, EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkType,
+    EcmascriptChunkingContext,
+};
+
+#[turbo_tasks::value(shared)]
+pub struct ServiceWorkerLoaderChunkItem {
+    pub module: Vc<ServiceWorkerLoaderModule>,
+    pub chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+}
+
+#[turbo_tasks::value_impl]
+impl ServiceWorkerLoaderChunkItem {
+    /// Returns the single script containing the service worker entry and the
+    /// runtime. It is placed at the root of the output directory, so the
+    /// service worker's default scope covers the whole app. The hash of the
+    /// path of the entry keeps it from colliding with service workers in
+    /// other directories, and the `.sw.js` suffix with an entry of the same
+    /// name, while the URL stays stable across builds.
+    #[turbo_tasks::function]
+    async fn service_worker_chunk(self: Vc<Self>) -> Result<Vc<Box<dyn OutputAsset>>> {
+        let this = self.await?;
+        let inner = this.module.await?.inner;
+        let evaluatable = Vc::try_resolve_sidecast::<Box<dyn EvaluatableAsset>>(inner)
+            .await?
+            .context("service worker entry must be an evaluatable asset")?;
+
+        let entry_path = inner.ident().path().await?;
+        let file_name = entry_path.file_name();
+        let file_stem = match entry_path.extension_ref() {
+            Some(ext) => &file_name[..file_name.len() - ext.len() - 1],
+            None => file_name,
+        };
+        let path_hash = hash_xxh3_hash64(entry_path.path.as_str()) as u32;
+        let path = this
+            .chunking_context
+            .output_root()
+            .join(format!("{file_stem}-{path_hash:08x}.sw.js"));
+
+        Ok(this
+            .chunking_context
+            .service_worker_entry_chunk(path, EvaluatableAssets::one(evaluatable)))
+    }
+}
+
+#[turbo_tasks::function]
+fn service_worker_chunk_reference_description() -> Vc<String> {
+    Vc::cell("service worker chunk".to_string())
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for ServiceWorkerLoaderChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn EcmascriptChunkingContext>> {
+        self.chunking_context
+    }
+
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<EcmascriptChunkItemContent>> {
+        let this = self.await?;
+        let url =
+            asset_url_code(this.chunking_context, self.service_worker_chunk().ident()).await?;
+
+        Ok(EcmascriptChunkItemContent {
+            inner_code: format!("__turbopack_export_value__({});\n", url).into(),
+            ..Default::default()
+        }
+        .cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for ServiceWorkerLoaderChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> Vc<AssetIdent> {
+        self.module.ident()
+    }
+
+    #[turbo_tasks::function]
+    fn references(self: Vc<Self>) -> Vc<ModuleReferences> {
+        Vc::cell(vec![Vc::upcast(SingleOutputAssetReference::new(
+            self.service_worker_chunk(),
+            service_worker_chunk_reference_description(),
+        ))])
+    }
+
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        Vc::upcast(self.chunking_context)
+    }
+
+    #[turbo_tasks::function]
+    async fn ty(&self) -> Result<Vc<Box<dyn ChunkType>>> {
+        Ok(Vc::upcast(
+            Vc::<EcmascriptChunkType>::default().resolve().await?,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        Vc::upcast(self.module)
+    }
+}
@@ -0,0 +1,94 @@
+use anyhow::{Context, Result};
+use turbo_tasks::Vc;
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{ChunkableModule, ChunkingContext},
+    ident::AssetIdent,
+    module::Module,
+    reference::{ModuleReferences, SingleModuleReference},
+};
+
+use super::chunk_item::ServiceWorkerLoaderChunkItem;
+use crate::chunk::{EcmascriptChunkPlaceable, EcmascriptChunkingContext, EcmascriptExports};
+
+#[turbo_tasks::function]
+fn modifier() -> Vc<String> {
+    Vc::cell("service worker loader".to_string())
+}
+
+/// The service worker loader module is placed in the chunk that contains the
+/// `navigator.serviceWorker.register(new URL(...))` call. It bundles the
+/// service worker entry into a single script and exports its URL.
+#[turbo_tasks::value]
+pub struct ServiceWorkerLoaderModule {
+    pub inner: Vc<Box<dyn ChunkableModule>>,
+}
+
+#[turbo_tasks::value_impl]
+impl ServiceWorkerLoaderModule {
+    #[turbo_tasks::function]
+    pub fn new(inner: Vc<Box<dyn ChunkableModule>>) -> Vc<Self> {
+        Self::cell(ServiceWorkerLoaderModule { inner })
+    }
+}
+
+#[turbo_tasks::function]
+fn service_worker_entry_reference_description() -> Vc<String> {
+    Vc::cell("service worker entry".to_string())
+}
+
+#[turbo_tasks::value_impl]
+impl Module for ServiceWorkerLoaderModule {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.inner.ident().with_modifier(modifier())
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<ModuleReferences> {
+        Vc::cell(vec![Vc::upcast(SingleModuleReference::new(
+            Vc::upcast(self.inner),
+            service_worker_entry_reference_description(),
+        ))])
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for ServiceWorkerLoaderModule {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        todo!()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModule for ServiceWorkerLoaderModule {
+    #[turbo_tasks::function]
+    async fn as_chunk_item(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<Vc<Box<dyn turbopack_core::chunk::ChunkItem>>> {
+        let chunking_context =
+            Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkingContext>>(chunking_context)
+                .await?
+                .context(
+                    "chunking context must impl EcmascriptChunkingContext to use \
+                     ServiceWorkerLoaderModule",
+                )?;
+        Ok(Vc::upcast(
+            ServiceWorkerLoaderChunkItem {
+                module: self,
+                chunking_context,
+            }
+            .cell(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for ServiceWorkerLoaderModule {
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> Vc<EcmascriptExports> {
+        EcmascriptExports::Value.cell()
+    }
+}
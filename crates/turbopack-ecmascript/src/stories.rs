@@ -0,0 +1,275 @@
+//! Indexes of "stories" modules for component explorers like Storybook.
+//!
+//! A [StoriesIndex] generates a module which default exports an object with an
+//! entry for each module matching a glob. Each entry lazily imports its module,
+//! so every story is placed in its own async chunk, and contains metadata that
+//! is extracted at build time: the named exports of the module and its leading
+//! JSDoc comment.
+//!
+//! Import maps resolve the request of an index to a [StoriesIndexSource],
+//! which the asset context processing it replaces with the index module.
+
+use std::fmt::Write;
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use indoc::writedoc;
+use swc_core::common::{
+    comments::{CommentKind, Comments},
+    Spanned,
+};
+use turbo_tasks::{TryJoinIterExt, Value, Vc};
+use turbo_tasks_fs::{glob::Glob, DirectoryEntry, File, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    context::AssetContext,
+    file_source::FileSource,
+    ident::AssetIdent,
+    module::Module,
+    reference_type::{EcmaScriptModulesReferenceSubType, ReferenceType},
+    source::Source,
+    virtual_source::VirtualSource,
+};
+
+use crate::{
+    chunk::{EcmascriptChunkPlaceable, EcmascriptExports},
+    parse::ParseResult,
+    utils::StringifyJs,
+    EcmascriptModuleAsset,
+};
+
+/// A module matching the glob of a [StoriesIndex].
+#[turbo_tasks::value(shared)]
+pub struct Story {
+    /// The path of the module relative to the directory of the index.
+    pub path: String,
+    pub module: Vc<Box<dyn Module>>,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct Stories(Vec<Vc<Story>>);
+
+/// Metadata of a story module which is known at build time.
+#[turbo_tasks::value(shared)]
+pub struct StoryMetadata {
+    /// The named exports of the module, i.e. its stories.
+    pub exports: Vec<String>,
+    /// The text of the JSDoc comment at the start of the module.
+    pub description: Option<String>,
+}
+
+#[turbo_tasks::value]
+pub struct StoriesIndex {
+    dir: Vc<FileSystemPath>,
+    glob: String,
+    asset_context: Vc<Box<dyn AssetContext>>,
+}
+
+#[turbo_tasks::value_impl]
+impl StoriesIndex {
+    #[turbo_tasks::function]
+    pub fn new(
+        dir: Vc<FileSystemPath>,
+        glob: String,
+        asset_context: Vc<Box<dyn AssetContext>>,
+    ) -> Vc<Self> {
+        StoriesIndex {
+            dir,
+            glob,
+            asset_context,
+        }
+        .cell()
+    }
+
+    /// The modules matching the glob, sorted by their path.
+    #[turbo_tasks::function]
+    pub async fn stories(&self) -> Result<Vc<Stories>> {
+        let mut files = Vec::new();
+        let mut queue = vec![self.dir.read_glob(Glob::new(self.glob.clone()), false)];
+        while let Some(result) = queue.pop() {
+            let result = result.await?;
+            for (path, entry) in result.results.iter() {
+                if let DirectoryEntry::File(file) = entry {
+                    files.push((path.clone(), *file));
+                }
+            }
+            queue.extend(result.inner.values().copied());
+        }
+        files.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        Ok(Vc::cell(
+            files
+                .into_iter()
+                .map(|(path, file)| {
+                    Story {
+                        path,
+                        module: self.asset_context.process(
+                            Vc::upcast(FileSource::new(file)),
+                            Value::new(ReferenceType::EcmaScriptModules(
+                                EcmaScriptModulesReferenceSubType::Undefined,
+                            )),
+                        ),
+                    }
+                    .cell()
+                })
+                .collect(),
+        ))
+    }
+
+    /// The generated index module.
+    #[turbo_tasks::function]
+    pub async fn module(self: Vc<Self>) -> Result<Vc<Box<dyn Module>>> {
+        let this = self.await?;
+        let stories = self.stories().await?;
+        let stories = stories
+            .iter()
+            .map(|story| async move {
+                let story = story.await?;
+                let metadata = story_metadata(story.module).await?;
+                Ok((story, metadata))
+            })
+            .try_join()
+            .await?;
+
+        let mut inner_assets = IndexMap::new();
+        let mut code = String::new();
+        writeln!(code, "const stories = {{")?;
+        for (index, (story, metadata)) in stories.iter().enumerate() {
+            let inner_asset = format!("STORY_{index}");
+            writedoc!(
+                code,
+                r#"
+                    {path}: {{
+                        load: () => import({inner_asset}),
+                        exports: {exports},
+                        description: {description},
+                    }},
+                "#,
+                path = StringifyJs(&format!("./{}", story.path)),
+                inner_asset = StringifyJs(&inner_asset),
+                exports = StringifyJs(&metadata.exports),
+                description = StringifyJs(&metadata.description),
+            )?;
+            inner_assets.insert(inner_asset, story.module);
+        }
+        writeln!(code, "}};")?;
+        writeln!(code, "export default stories;")?;
+
+        let source = VirtualSource::new(
+            index_path(this.dir, &this.glob),
+            AssetContent::file(File::from(code).into()),
+        );
+
+        Ok(this.asset_context.process(
+            Vc::upcast(source),
+            Value::new(ReferenceType::Internal(Vc::cell(inner_assets))),
+        ))
+    }
+}
+
+/// A placeholder for a [StoriesIndex], which is what import maps resolve the
+/// request of an index to. The asset context which processes it generates the
+/// index module in its place, see [StoriesIndexSource::module].
+#[turbo_tasks::value]
+pub struct StoriesIndexSource {
+    dir: Vc<FileSystemPath>,
+    glob: String,
+}
+
+#[turbo_tasks::value_impl]
+impl StoriesIndexSource {
+    #[turbo_tasks::function]
+    pub fn new(dir: Vc<FileSystemPath>, glob: String) -> Vc<Self> {
+        StoriesIndexSource { dir, glob }.cell()
+    }
+
+    /// The generated index module, whose stories are processed in
+    /// `asset_context`.
+    #[turbo_tasks::function]
+    pub fn module(&self, asset_context: Vc<Box<dyn AssetContext>>) -> Vc<Box<dyn Module>> {
+        StoriesIndex::new(self.dir, self.glob.clone(), asset_context).module()
+    }
+}
+
+#[turbo_tasks::function]
+fn stories_index_modifier() -> Vc<String> {
+    Vc::cell("stories index".to_string())
+}
+
+#[turbo_tasks::value_impl]
+impl Source for StoriesIndexSource {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(index_path(self.dir, &self.glob))
+            .with_modifier(stories_index_modifier())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for StoriesIndexSource {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        AssetContent::file(File::from("").into())
+    }
+}
+
+/// The path of the generated index module of `glob` in `dir`.
+fn index_path(dir: Vc<FileSystemPath>, glob: &str) -> Vc<FileSystemPath> {
+    dir.join(format!("__stories__/{}.js", glob.replace('/', "_")))
+}
+
+/// Extracts the named exports and the leading JSDoc comment of a story
+/// module. Modules which aren't ECMAScript have no metadata.
+#[turbo_tasks::function]
+pub async fn story_metadata(module: Vc<Box<dyn Module>>) -> Result<Vc<StoryMetadata>> {
+    let mut exports = Vec::new();
+    if let Some(placeable) =
+        Vc::try_resolve_sidecast::<Box<dyn EcmascriptChunkPlaceable>>(module).await?
+    {
+        if let EcmascriptExports::EsmExports(esm_exports) = &*placeable.get_exports().await? {
+            exports.extend(
+                esm_exports
+                    .expand_exports()
+                    .await?
+                    .exports
+                    .keys()
+                    .filter(|name| *name != "default")
+                    .cloned(),
+            );
+        }
+    }
+
+    let mut description = None;
+    if let Some(module) = Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(module).await? {
+        if let ParseResult::Ok {
+            program, comments, ..
+        } = &*module.parse().await?
+        {
+            description = comments
+                .get_leading(program.span().lo)
+                .and_then(|comments| {
+                    comments.into_iter().rev().find(|comment| {
+                        comment.kind == CommentKind::Block && comment.text.starts_with('*')
+                    })
+                })
+                .map(|comment| jsdoc_text(&comment.text));
+        }
+    }
+
+    Ok(StoryMetadata {
+        exports,
+        description,
+    }
+    .cell())
+}
+
+/// Returns the text of a JSDoc comment without the leading `*` of each line.
+fn jsdoc_text(comment: &str) -> String {
+    comment
+        .lines()
+        .map(|line| line.trim().trim_start_matches('*').trim())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use swc_core::{
+    common::{Span, DUMMY_SP},
+    ecma::{
+        ast::{
+            ComputedPropName, Decl, ExportDecl, Expr, Id, ImportSpecifier, Lit, MemberExpr,
+            MemberProp, ModuleDecl, ModuleExportName, ModuleItem, ParenExpr, Program, TsEnumDecl,
+            TsEnumMemberId, UnaryExpr, UnaryOp,
+        },
+        visit::{VisitMut, VisitMutWith},
+    },
+};
+use turbo_tasks::{Value, Vc};
+use turbo_tasks_fs::{FileSystemEntryType, FileSystemPath};
+use turbopack_core::file_source::FileSource;
+
+use super::EcmascriptInputTransforms;
+use crate::{
+    parse::{parse, ParseResult},
+    EcmascriptModuleAssetType,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum ConstEnumValue {
+    Number(f64),
+    String(String),
+}
+
+impl ConstEnumValue {
+    fn to_expr(&self, span: Span) -> Expr {
+        match self {
+            // Negative literals would be printed without parentheses, which
+            // breaks e.g. `a - -1`.
+            ConstEnumValue::Number(value) if value.is_sign_negative() => Expr::Unary(UnaryExpr {
+                span,
+                op: UnaryOp::Minus,
+                arg: Box::new(ConstEnumValue::Number(-value).to_expr(DUMMY_SP)),
+            }),
+            ConstEnumValue::Number(value) => Expr::Lit(Lit::Num((*value).into())),
+            ConstEnumValue::String(value) => Expr::Lit(Lit::Str(value.as_str().into())),
+        }
+    }
+}
+
+type ConstEnumMembers = IndexMap<String, ConstEnumValue>;
+
+/// The exported `const enum`s of a module and the values of their members.
+#[turbo_tasks::value(serialization = "none", eq = "manual")]
+#[derive(PartialEq)]
+struct ConstEnums {
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    enums: IndexMap<String, ConstEnumMembers>,
+}
+
+/// Evaluates the members of a `const enum` whose values are literals, negated
+/// numbers, references to previous members or implicit. Other members are
+/// skipped, as are implicit members following them.
+fn evaluate_members(decl: &TsEnumDecl) -> ConstEnumMembers {
+    fn evaluate(expr: &Expr, members: &ConstEnumMembers) -> Option<ConstEnumValue> {
+        match expr {
+            Expr::Lit(Lit::Num(num)) => Some(ConstEnumValue::Number(num.value)),
+            Expr::Lit(Lit::Str(str)) => Some(ConstEnumValue::String(str.value.to_string())),
+            Expr::Paren(ParenExpr { expr, .. }) => evaluate(expr, members),
+            Expr::Unary(UnaryExpr {
+                op: UnaryOp::Minus,
+                arg,
+                ..
+            }) => match evaluate(arg, members)? {
+                ConstEnumValue::Number(value) => Some(ConstEnumValue::Number(-value)),
+                ConstEnumValue::String(_) => None,
+            },
+            Expr::Ident(ident) => members.get(&*ident.sym).cloned(),
+            _ => None,
+        }
+    }
+
+    let mut members = ConstEnumMembers::new();
+    let mut next = Some(0.0);
+    for member in decl.members.iter() {
+        let name = match &member.id {
+            TsEnumMemberId::Ident(ident) => ident.sym.to_string(),
+            TsEnumMemberId::Str(str) => str.value.to_string(),
+        };
+        let value = match &member.init {
+            Some(init) => evaluate(init, &members),
+            None => next.map(ConstEnumValue::Number),
+        };
+        next = match value {
+            Some(ConstEnumValue::Number(value)) => Some(value + 1.0),
+            _ => None,
+        };
+        if let Some(value) = value {
+            members.insert(name, value);
+        }
+    }
+    members
+}
+
+#[turbo_tasks::function]
+async fn exported_const_enums(path: Vc<FileSystemPath>) -> Result<Vc<ConstEnums>> {
+    let ty = if path.await?.path.ends_with(".d.ts") {
+        EcmascriptModuleAssetType::TypescriptDeclaration
+    } else {
+        EcmascriptModuleAssetType::Typescript
+    };
+    let parsed = parse(
+        Vc::upcast(FileSource::new(path)),
+        Value::new(ty),
+        EcmascriptInputTransforms::empty(),
+    )
+    .await?;
+
+    let mut enums = IndexMap::new();
+    if let ParseResult::Ok {
+        program: Program::Module(module),
+        ..
+    } = &*parsed
+    {
+        for item in module.body.iter() {
+            if let ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                decl: Decl::TsEnum(decl),
+                ..
+            })) = item
+            {
+                if decl.is_const {
+                    enums.insert(decl.id.sym.to_string(), evaluate_members(decl));
+                }
+            }
+        }
+    }
+    Ok(ConstEnums { enums }.cell())
+}
+
+/// Finds the TypeScript module a relative import points to, the way
+/// TypeScript does it for extensionless and `.js` specifiers.
+async fn find_typescript_module(
+    dir: Vc<FileSystemPath>,
+    specifier: &str,
+) -> Result<Option<Vc<FileSystemPath>>> {
+    let base = specifier.strip_suffix(".js").unwrap_or(specifier);
+    for candidate in [
+        format!("{base}.ts"),
+        format!("{base}.tsx"),
+        format!("{base}.d.ts"),
+        format!("{base}/index.ts"),
+        format!("{base}/index.tsx"),
+    ] {
+        let Some(path) = *dir.try_join(candidate).await? else {
+            continue;
+        };
+        if matches!(&*path.get_type().await?, FileSystemEntryType::File) {
+            return Ok(Some(path));
+        }
+    }
+    Ok(None)
+}
+
+/// Replaces accesses to members of `const enum`s, which are imported from
+/// relative TypeScript modules, with the values of the members. This allows to
+/// use `const enum`s across modules like `tsc` does without `isolatedModules`,
+/// even when the declarations aren't emitted.
+///
+/// Only members with constant values are inlined, see [evaluate_members].
+pub(crate) async fn inline_imported_const_enums(
+    program: &mut Program,
+    file_path: Vc<FileSystemPath>,
+) -> Result<()> {
+    let Program::Module(module) = &*program else {
+        return Ok(());
+    };
+
+    let mut enums = HashMap::new();
+    for item in module.body.iter() {
+        let ModuleItem::ModuleDecl(ModuleDecl::Import(import)) = item else {
+            continue;
+        };
+        if !import.src.value.starts_with('.') {
+            continue;
+        }
+        let Some(path) = find_typescript_module(file_path.parent(), &import.src.value).await?
+        else {
+            continue;
+        };
+        let exported = exported_const_enums(path).await?;
+        for specifier in import.specifiers.iter() {
+            let ImportSpecifier::Named(named) = specifier else {
+                continue;
+            };
+            let imported = match &named.imported {
+                Some(ModuleExportName::Ident(ident)) => ident.sym.to_string(),
+                Some(ModuleExportName::Str(str)) => str.value.to_string(),
+                None => named.local.sym.to_string(),
+            };
+            if let Some(members) = exported.enums.get(&imported) {
+                enums.insert(named.local.to_id(), members.clone());
+            }
+        }
+    }
+
+    if !enums.is_empty() {
+        program.visit_mut_with(&mut ConstEnumInliner { enums });
+    }
+    Ok(())
+}
+
+struct ConstEnumInliner {
+    enums: HashMap<Id, ConstEnumMembers>,
+}
+
+impl ConstEnumInliner {
+    fn member_value(&self, member: &MemberExpr) -> Option<&ConstEnumValue> {
+        let Expr::Ident(obj) = &*member.obj else {
+            return None;
+        };
+        let members = self.enums.get(&obj.to_id())?;
+        match &member.prop {
+            MemberProp::Ident(prop) => members.get(&*prop.sym),
+            MemberProp::Computed(ComputedPropName { expr, .. }) => match &**expr {
+                Expr::Lit(Lit::Str(prop)) => members.get(&*prop.value),
+                _ => None,
+            },
+            MemberProp::PrivateName(_) => None,
+        }
+    }
+}
+
+impl VisitMut for ConstEnumInliner {
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        if let Expr::Member(member) = expr {
+            if let Some(value) = self.member_value(member) {
+                *expr = value.to_expr(member.span);
+                return;
+            }
+        }
+        expr.visit_mut_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use swc_core::{
+        common::{FileName, FilePathMapping, SourceMap},
+        ecma::{
+            ast::{Decl, EsVersion, Module, ModuleItem, Stmt},
+            codegen::{text_writer::JsWriter, Emitter},
+            parser::{parse_file_as_module, Syntax, TsConfig},
+            visit::VisitMutWith,
+        },
+    };
+
+    use super::{evaluate_members, ConstEnumInliner, ConstEnumMembers, ConstEnumValue};
+
+    fn parse(code: &str) -> Module {
+        let cm = SourceMap::new(FilePathMapping::empty());
+        let fm = cm.new_source_file(FileName::Anon, code.to_string());
+        parse_file_as_module(
+            &fm,
+            Syntax::Typescript(TsConfig::default()),
+            EsVersion::latest(),
+            None,
+            &mut vec![],
+        )
+        .unwrap()
+    }
+
+    fn members(code: &str) -> ConstEnumMembers {
+        let module = parse(code);
+        let Some(ModuleItem::Stmt(Stmt::Decl(Decl::TsEnum(decl)))) = module.body.first() else {
+            panic!("expected an enum declaration");
+        };
+        evaluate_members(decl)
+    }
+
+    fn inline(code: &str, name: &str, members: ConstEnumMembers) -> String {
+        let mut module = parse(code);
+        // Without a resolver pass all identifiers share the empty context.
+        let id = (name.into(), Default::default());
+        module.visit_mut_with(&mut ConstEnumInliner {
+            enums: HashMap::from([(id, members)]),
+        });
+
+        let source_map: Arc<SourceMap> = Default::default();
+        let mut bytes: Vec<u8> = vec![];
+        let mut emitter = Emitter {
+            cfg: swc_core::ecma::codegen::Config::default(),
+            cm: source_map.clone(),
+            comments: None,
+            wr: JsWriter::new(source_map, "\n", &mut bytes, None),
+        };
+        emitter.emit_module(&module).unwrap();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn implicit_members_count_up() {
+        let members = members("const enum E { A, B = 10, C, D = -1, E }");
+        assert_eq!(
+            members.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("A".to_string(), ConstEnumValue::Number(0.0)),
+                ("B".to_string(), ConstEnumValue::Number(10.0)),
+                ("C".to_string(), ConstEnumValue::Number(11.0)),
+                ("D".to_string(), ConstEnumValue::Number(-1.0)),
+                ("E".to_string(), ConstEnumValue::Number(0.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn strings_and_references() {
+        let members = members(r#"const enum E { A = "a", "b-c" = "bc", D = (A), F = -(2) }"#);
+        assert_eq!(members["A"], ConstEnumValue::String("a".to_string()));
+        assert_eq!(members["b-c"], ConstEnumValue::String("bc".to_string()));
+        assert_eq!(members["D"], ConstEnumValue::String("a".to_string()));
+        assert_eq!(members["F"], ConstEnumValue::Number(-2.0));
+    }
+
+    #[test]
+    fn computed_members_are_skipped() {
+        let members = members(r#"const enum E { A = f(), B, C = 1 << 2, D = 3, E = -"x" }"#);
+        assert_eq!(
+            members.into_iter().collect::<Vec<_>>(),
+            vec![("D".to_string(), ConstEnumValue::Number(3.0))]
+        );
+    }
+
+    #[test]
+    fn inlines_member_accesses() {
+        let members = ConstEnumMembers::from([
+            ("A".to_string(), ConstEnumValue::Number(1.0)),
+            ("B".to_string(), ConstEnumValue::Number(-1.0)),
+            ("C".to_string(), ConstEnumValue::String("c".to_string())),
+        ]);
+        assert_eq!(
+            inline(
+                r#"x = E.A + E["C"] - E.B + E.D + F.A + E[key];"#,
+                "E",
+                members
+            ),
+            "x = 1 + \"c\" - -1 + E.D + F.A + E[key];\n"
+        );
+    }
+}
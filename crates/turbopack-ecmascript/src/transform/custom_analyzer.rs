@@ -0,0 +1,144 @@
+use std::fmt::Debug;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use swc_core::{common::Span, ecma::ast::Program};
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::{
+    file_source::FileSource,
+    issue::{Issue, IssueExt, IssueSeverity, IssueSource, OptionIssueSource},
+    source::Source,
+    source_pos::SourcePos,
+};
+
+use super::TransformContext;
+
+/// A finding of a [CustomAnalyzer], e.g. a lint error.
+#[derive(Debug, Clone)]
+pub struct AnalyzerDiagnostic {
+    /// The location of the finding in the analyzed module.
+    pub span: Span,
+    pub severity: IssueSeverity,
+    /// A short summary, e.g. `no-console: Unexpected console statement`.
+    pub title: String,
+    pub message: String,
+}
+
+/// The CustomAnalyzer trait allows you to inspect the program of every
+/// ECMAScript module in the graph after all transforms have been applied,
+/// e.g. to lint the modules during the build without parsing them again.
+///
+/// The returned diagnostics are reported as issues of the module. Analyzers
+/// can also emit any other issues themselves.
+#[async_trait]
+pub trait CustomAnalyzer: Debug {
+    /// The category of the issues of the analyzer, e.g. `lint`.
+    fn category(&self) -> String {
+        "analysis".to_string()
+    }
+
+    async fn analyze(
+        &self,
+        program: &Program,
+        ctx: &TransformContext<'_>,
+    ) -> Result<Vec<AnalyzerDiagnostic>>;
+}
+
+/// A wrapper around a CustomAnalyzer instance, allowing it to operate with
+/// the turbo_task caching requirements.
+#[turbo_tasks::value(
+    transparent,
+    serialization = "none",
+    eq = "manual",
+    into = "new",
+    cell = "new"
+)]
+#[derive(Debug)]
+pub struct AnalyzerPlugin(#[turbo_tasks(trace_ignore)] Box<dyn CustomAnalyzer + Send + Sync>);
+
+/// Runs the analyzer and emits its diagnostics as issues.
+pub(crate) async fn run_analyzer(
+    analyzer: &(dyn CustomAnalyzer + Send + Sync),
+    program: &Program,
+    ctx: &TransformContext<'_>,
+) -> Result<()> {
+    let diagnostics = analyzer.analyze(program, ctx).await?;
+    if diagnostics.is_empty() {
+        return Ok(());
+    }
+    let category = analyzer.category();
+    let source: Vc<Box<dyn Source>> = Vc::upcast(FileSource::new(ctx.file_path));
+    for diagnostic in diagnostics {
+        let issue_source = (!diagnostic.span.is_dummy()).then(|| {
+            let start = ctx.source_map.lookup_char_pos(diagnostic.span.lo);
+            let end = ctx.source_map.lookup_char_pos(diagnostic.span.hi);
+            IssueSource {
+                source,
+                start: SourcePos {
+                    line: start.line - 1,
+                    column: start.col.0,
+                },
+                end: SourcePos {
+                    line: end.line - 1,
+                    column: end.col.0,
+                },
+            }
+            .cell()
+        });
+        AnalyzerIssue {
+            file_path: ctx.file_path,
+            source: issue_source,
+            severity: diagnostic.severity.cell(),
+            category: category.clone(),
+            title: diagnostic.title,
+            message: diagnostic.message,
+        }
+        .cell()
+        .emit();
+    }
+    Ok(())
+}
+
+#[turbo_tasks::value(shared)]
+struct AnalyzerIssue {
+    file_path: Vc<FileSystemPath>,
+    source: Option<Vc<IssueSource>>,
+    severity: Vc<IssueSeverity>,
+    category: String,
+    title: String,
+    message: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for AnalyzerIssue {
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(self.title.clone())
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(self.message.clone())
+    }
+
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        self.severity
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell(self.category.clone())
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> Vc<OptionIssueSource> {
+        Vc::cell(self.source)
+    }
+}
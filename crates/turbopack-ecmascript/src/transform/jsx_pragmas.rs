@@ -0,0 +1,103 @@
+use swc_core::base::SwcComments;
+
+/// Pragma comments like `/** @jsxImportSource theme-ui */`, which override
+/// the options of the React transform for a single module.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct JsxPragmas {
+    /// `@jsxRuntime classic` or `@jsxRuntime automatic`.
+    pub runtime: Option<String>,
+    /// `@jsxImportSource <package>`. Like in TypeScript, it implies the
+    /// automatic runtime, unless a runtime is set by a pragma as well.
+    pub import_source: Option<String>,
+}
+
+impl JsxPragmas {
+    /// Reads the pragmas from the leading comments of a module. When a pragma
+    /// occurs multiple times, the first one is used.
+    pub fn from_comments(comments: &SwcComments) -> Self {
+        let mut texts = comments
+            .leading
+            .iter()
+            .flat_map(|entry| {
+                entry
+                    .value()
+                    .iter()
+                    .map(|comment| (comment.span.lo, comment.text.to_string()))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        texts.sort_by_key(|(pos, _)| *pos);
+
+        let mut pragmas = JsxPragmas::default();
+        for (_, text) in texts {
+            pragmas.parse_comment(&text);
+        }
+        pragmas
+    }
+
+    fn parse_comment(&mut self, text: &str) {
+        let mut words = text
+            .split(|c: char| c.is_whitespace() || c == '*')
+            .filter(|word| !word.is_empty());
+        while let Some(word) = words.next() {
+            let pragma = match word {
+                "@jsxRuntime" => &mut self.runtime,
+                "@jsxImportSource" => &mut self.import_source,
+                _ => continue,
+            };
+            if let Some(value) = words.next() {
+                pragma.get_or_insert_with(|| value.to_string());
+            }
+        }
+    }
+
+    /// The runtime to use, if it's overridden by the pragmas.
+    pub fn runtime(&self) -> Option<&str> {
+        self.runtime
+            .as_deref()
+            .or_else(|| self.import_source.as_ref().map(|_| "automatic"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JsxPragmas;
+
+    fn parse(comments: &[&str]) -> JsxPragmas {
+        let mut pragmas = JsxPragmas::default();
+        for comment in comments {
+            pragmas.parse_comment(comment);
+        }
+        pragmas
+    }
+
+    #[test]
+    fn import_source() {
+        let pragmas = parse(&["* @jsxImportSource theme-ui "]);
+        assert_eq!(pragmas.import_source.as_deref(), Some("theme-ui"));
+        assert_eq!(pragmas.runtime(), Some("automatic"));
+    }
+
+    #[test]
+    fn classic_runtime() {
+        let pragmas = parse(&["*\n * @jsxRuntime classic\n * @jsx h\n "]);
+        assert_eq!(pragmas.import_source, None);
+        assert_eq!(pragmas.runtime(), Some("classic"));
+    }
+
+    #[test]
+    fn first_pragma_wins() {
+        let pragmas = parse(&[
+            " @jsxImportSource @emotion/react ",
+            " @jsxImportSource preact ",
+            " @jsxRuntime automatic @jsxRuntime classic ",
+        ]);
+        assert_eq!(pragmas.import_source.as_deref(), Some("@emotion/react"));
+        assert_eq!(pragmas.runtime(), Some("automatic"));
+    }
+
+    #[test]
+    fn no_pragmas() {
+        assert_eq!(parse(&[" eslint-disable ", "@jsx"]), JsxPragmas::default());
+    }
+}
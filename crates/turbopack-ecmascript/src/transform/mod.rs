@@ -1,3 +1,8 @@
+mod const_enums;
+mod custom_analyzer;
+mod jsx_pragmas;
+mod preserve_hot_state;
+
 use std::{fmt::Debug, hash::Hash, sync::Arc};
 
 use anyhow::Result;
@@ -6,7 +11,10 @@ use swc_core::{
     base::SwcComments,
     common::{chain, comments::Comments, util::take::Take, Mark, SourceMap},
     ecma::{
-        ast::{Module, ModuleItem, Program, Script},
+        ast::{
+            ExportNamedSpecifier, ExportSpecifier, ImportNamedSpecifier, ImportSpecifier, Module,
+            ModuleDecl, ModuleItem, Program, Script,
+        },
         preset_env::{
             Targets, {self},
         },
@@ -14,7 +22,7 @@ use swc_core::{
             base::{feature::FeatureFlag, helpers::inject_helpers, Assumptions},
             react::react,
         },
-        visit::{FoldWith, VisitMutWith},
+        visit::{FoldWith, VisitMut, VisitMutWith},
     },
 };
 use turbo_tasks::{ValueDefault, Vc};
@@ -22,6 +30,16 @@ use turbo_tasks_fs::FileSystemPath;
 use turbopack_core::{
     environment::Environment,
     issue::{Issue, IssueSeverity},
+    source_transform::{CacheKeyInput, CacheKeyInputs},
+};
+
+use self::{
+    const_enums::inline_imported_const_enums, custom_analyzer::run_analyzer,
+    jsx_pragmas::JsxPragmas, preserve_hot_state::PreserveHotState,
+};
+pub use self::{
+    custom_analyzer::{AnalyzerDiagnostic, AnalyzerPlugin, CustomAnalyzer},
+    preserve_hot_state::{HotStatePattern, HotStatePatterns},
 };
 
 #[turbo_tasks::value(serialization = "auto_for_input")]
@@ -45,6 +63,15 @@ pub enum EcmascriptInputTransform {
     TypeScript {
         #[serde(default)]
         use_define_for_class_fields: bool,
+        /// Keeps imports and exports which aren't marked with `type` exactly
+        /// as written, even if they are only used as types, like
+        /// `verbatimModuleSyntax` in a tsconfig.json.
+        #[serde(default)]
+        verbatim_module_syntax: bool,
+        /// Inlines the members of `const enum`s imported from relative
+        /// TypeScript modules.
+        #[serde(default)]
+        inline_const_enums: bool,
     },
     Decorators {
         #[serde(default)]
@@ -56,6 +83,14 @@ pub enum EcmascriptInputTransform {
         #[serde(default)]
         use_define_for_class_fields: bool,
     },
+    /// Removes type-only imports and re-exports, so they don't create
+    /// references in the module graph.
+    StripTypeOnlyImports,
+    /// Keeps the values of top level variables matching the patterns, e.g.
+    /// stores, across HMR updates. Requires a runtime with HMR support.
+    PreserveHotState(Vc<HotStatePatterns>),
+    /// Inspects the program without changing it, see [CustomAnalyzer].
+    Analyzer(Vc<AnalyzerPlugin>),
 }
 
 /// The CustomTransformer trait allows you to implement your own custom SWC
@@ -63,6 +98,13 @@ pub enum EcmascriptInputTransform {
 #[async_trait]
 pub trait CustomTransformer: Debug {
     async fn transform(&self, program: &mut Program, ctx: &TransformContext<'_>) -> Result<()>;
+
+    /// Inputs which affect the result of the transformer, but are not part of
+    /// its configuration, e.g. the version of a wasm plugin or a config file
+    /// it reads. Parse results are cached per state of these inputs.
+    fn cache_key_inputs(&self) -> Vec<CacheKeyInput> {
+        Vec::new()
+    }
 }
 
 /// A wrapper around a TransformPlugin instance, allowing it to operate with
@@ -93,6 +135,10 @@ impl CustomTransformer for TransformPlugin {
     async fn transform(&self, program: &mut Program, ctx: &TransformContext<'_>) -> Result<()> {
         self.0.transform(program, ctx).await
     }
+
+    fn cache_key_inputs(&self) -> Vec<CacheKeyInput> {
+        self.0.cache_key_inputs()
+    }
 }
 
 #[turbo_tasks::value(transparent, serialization = "auto_for_input")]
@@ -112,6 +158,18 @@ impl EcmascriptInputTransforms {
         transforms.extend(other.await?.clone_value());
         Ok(Vc::cell(transforms))
     }
+
+    /// The cache key inputs declared by the plugin transforms.
+    #[turbo_tasks::function]
+    pub async fn cache_key_inputs(self: Vc<Self>) -> Result<Vc<CacheKeyInputs>> {
+        let mut inputs = Vec::new();
+        for transform in self.await?.iter() {
+            if let EcmascriptInputTransform::Plugin(plugin) = transform {
+                inputs.extend(plugin.await?.cache_key_inputs());
+            }
+        }
+        Ok(Vc::cell(inputs))
+    }
 }
 
 pub struct TransformContext<'a> {
@@ -142,8 +200,11 @@ impl EcmascriptInputTransform {
                 runtime,
             } => {
                 use swc_core::ecma::transforms::react::{Options, Runtime};
-                let runtime = if let Some(runtime) = &*runtime.await? {
-                    match runtime.as_str() {
+                // Pragmas in the module take precedence over the configured options.
+                let pragmas = JsxPragmas::from_comments(comments);
+                let runtime = runtime.await?;
+                let runtime = if let Some(runtime) = pragmas.runtime().or(runtime.as_deref()) {
+                    match runtime {
                         "classic" => Runtime::Classic,
                         "automatic" => Runtime::Automatic,
                         _ => {
@@ -160,7 +221,10 @@ impl EcmascriptInputTransform {
                 let config = Options {
                     runtime: Some(runtime),
                     development: Some(*development),
-                    import_source: import_source.await?.clone_value(),
+                    import_source: match pragmas.import_source {
+                        Some(import_source) => Some(import_source),
+                        None => import_source.await?.clone_value(),
+                    },
                     refresh: if *refresh {
                         Some(swc_core::ecma::transforms::react::RefreshOptions {
                             refresh_reg: "__turbopack_refresh__.register".to_string(),
@@ -242,9 +306,19 @@ impl EcmascriptInputTransform {
             EcmascriptInputTransform::TypeScript {
                 // TODO(WEB-1213)
                 use_define_for_class_fields: _use_define_for_class_fields,
+                verbatim_module_syntax,
+                inline_const_enums,
             } => {
-                use swc_core::ecma::transforms::typescript::typescript;
-                let config = Default::default();
+                use swc_core::ecma::transforms::typescript::{typescript, Config};
+                // Inlining has to happen before the imports of the enums are
+                // removed as unused.
+                if *inline_const_enums {
+                    inline_imported_const_enums(program, ctx.file_path).await?;
+                }
+                let config = Config {
+                    verbatim_module_syntax: *verbatim_module_syntax,
+                    ..Default::default()
+                };
                 program.visit_mut_with(&mut typescript(config, unresolved_mark));
             }
             EcmascriptInputTransform::Decorators {
@@ -267,14 +341,72 @@ impl EcmascriptInputTransform {
                     inject_helpers(unresolved_mark)
                 ));
             }
+            EcmascriptInputTransform::StripTypeOnlyImports => {
+                program.visit_mut_with(&mut StripTypeOnlyImports);
+            }
+            EcmascriptInputTransform::PreserveHotState(patterns) => {
+                program.visit_mut_with(&mut PreserveHotState::new(
+                    &patterns.await?,
+                    unresolved_mark,
+                ));
+            }
             EcmascriptInputTransform::Plugin(transform) => {
                 transform.await?.transform(program, ctx).await?
             }
+            EcmascriptInputTransform::Analyzer(analyzer) => {
+                run_analyzer(&**analyzer.await?, program, ctx).await?
+            }
         }
         Ok(())
     }
 }
 
+/// Removes `import type`, `export type { .. } from` and `export type * from`
+/// declarations and type-only specifiers. Declarations which only had type-only
+/// specifiers are removed entirely, instead of becoming side effect imports.
+struct StripTypeOnlyImports;
+
+impl VisitMut for StripTypeOnlyImports {
+    fn visit_mut_module_items(&mut self, items: &mut Vec<ModuleItem>) {
+        items.retain_mut(|item| match item {
+            ModuleItem::ModuleDecl(ModuleDecl::Import(import)) => {
+                if import.type_only {
+                    return false;
+                }
+                let had_specifiers = !import.specifiers.is_empty();
+                import.specifiers.retain(|specifier| {
+                    !matches!(
+                        specifier,
+                        ImportSpecifier::Named(ImportNamedSpecifier {
+                            is_type_only: true,
+                            ..
+                        })
+                    )
+                });
+                !had_specifiers || !import.specifiers.is_empty()
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(export)) => {
+                if export.type_only {
+                    return false;
+                }
+                let had_specifiers = !export.specifiers.is_empty();
+                export.specifiers.retain(|specifier| {
+                    !matches!(
+                        specifier,
+                        ExportSpecifier::Named(ExportNamedSpecifier {
+                            is_type_only: true,
+                            ..
+                        })
+                    )
+                });
+                !had_specifiers || !export.specifiers.is_empty()
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportAll(export)) => !export.type_only,
+            _ => true,
+        });
+    }
+}
+
 pub fn remove_shebang(program: &mut Program) {
     match program {
         Program::Module(m) => {
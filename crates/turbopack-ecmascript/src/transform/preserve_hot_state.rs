@@ -0,0 +1,173 @@
+use std::collections::HashSet;
+
+use swc_core::{
+    common::{util::take::Take, Mark},
+    ecma::{ast::*, visit::VisitMut},
+    quote,
+};
+use turbo_tasks::Vc;
+
+/// A kind of value which is kept across HMR updates when it's created at the
+/// top level of a module.
+#[turbo_tasks::value(shared, serialization = "auto_for_input")]
+#[derive(Debug, Clone, PartialOrd, Ord, Hash)]
+pub enum HotStatePattern {
+    /// A call of (or `new` with) an export of a module, e.g. `create` from
+    /// `zustand`.
+    Import { source: String, export: String },
+    /// A call of (or `new` with) a global, e.g. `Map` for singleton caches.
+    Global { name: String },
+}
+
+#[turbo_tasks::value(transparent, serialization = "auto_for_input")]
+#[derive(Debug, Clone, PartialOrd, Ord, Hash)]
+pub struct HotStatePatterns(Vec<HotStatePattern>);
+
+#[turbo_tasks::value_impl]
+impl HotStatePatterns {
+    /// The stores of common state management libraries.
+    #[turbo_tasks::function]
+    pub fn defaults() -> Vc<Self> {
+        let import = |source: &str, export: &str| HotStatePattern::Import {
+            source: source.to_string(),
+            export: export.to_string(),
+        };
+        Vc::cell(vec![
+            import("zustand", "create"),
+            import("zustand", "default"),
+            import("zustand/vanilla", "createStore"),
+            import("redux", "createStore"),
+            import("redux", "legacy_createStore"),
+            import("@reduxjs/toolkit", "configureStore"),
+        ])
+    }
+}
+
+/// Wraps the initializers of top level variables which match a
+/// [HotStatePattern] with `__turbopack_preserve_hot_state__`, so the value of
+/// the previous version of the module is reused after an HMR update.
+pub(super) struct PreserveHotState<'a> {
+    patterns: &'a [HotStatePattern],
+    unresolved_mark: Mark,
+    matching_imports: HashSet<Id>,
+}
+
+impl<'a> PreserveHotState<'a> {
+    pub(super) fn new(patterns: &'a [HotStatePattern], unresolved_mark: Mark) -> Self {
+        Self {
+            patterns,
+            unresolved_mark,
+            matching_imports: HashSet::new(),
+        }
+    }
+
+    fn collect_imports(&mut self, import: &ImportDecl) {
+        for specifier in &import.specifiers {
+            let (local, export) = match specifier {
+                ImportSpecifier::Named(ImportNamedSpecifier {
+                    local, imported, ..
+                }) => (
+                    local,
+                    match imported {
+                        Some(ModuleExportName::Ident(ident)) => &*ident.sym,
+                        Some(ModuleExportName::Str(str)) => &*str.value,
+                        None => &*local.sym,
+                    },
+                ),
+                ImportSpecifier::Default(ImportDefaultSpecifier { local, .. }) => {
+                    (local, "default")
+                }
+                ImportSpecifier::Namespace(_) => continue,
+            };
+            let matches = self.patterns.iter().any(|pattern| {
+                matches!(
+                    pattern,
+                    HotStatePattern::Import { source, export: e }
+                        if *source == *import.src.value && e == export
+                )
+            });
+            if matches {
+                self.matching_imports.insert(local.to_id());
+            }
+        }
+    }
+
+    fn is_matching_callee(&self, ident: &Ident) -> bool {
+        if self.matching_imports.contains(&ident.to_id()) {
+            return true;
+        }
+        ident.span.ctxt.outer() == self.unresolved_mark
+            && self.patterns.iter().any(|pattern| {
+                matches!(pattern, HotStatePattern::Global { name } if *name == *ident.sym)
+            })
+    }
+
+    /// Whether `expr` is a call of a matching callee, including curried calls
+    /// like zustand's `create()(...)`.
+    fn is_matching_init(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Paren(ParenExpr { expr, .. }) => self.is_matching_init(expr),
+            Expr::Call(CallExpr {
+                callee: Callee::Expr(callee),
+                ..
+            })
+            | Expr::New(NewExpr { callee, .. }) => match &**callee {
+                Expr::Ident(ident) => self.is_matching_callee(ident),
+                callee => self.is_matching_init(callee),
+            },
+            _ => false,
+        }
+    }
+
+    fn wrap_decl(&self, decl: &mut VarDecl) {
+        for declarator in &mut decl.decls {
+            let (Pat::Ident(BindingIdent { id, .. }), Some(init)) =
+                (&declarator.name, &mut declarator.init)
+            else {
+                continue;
+            };
+            if !self.is_matching_init(init) {
+                continue;
+            }
+            let key = id.sym.to_string();
+            let value = init.take();
+            *init = Box::new(quote!(
+                "__turbopack_preserve_hot_state__($key, () => $value)" as Expr,
+                key: Expr = key.into(),
+                value: Expr = *value,
+            ));
+        }
+    }
+
+    fn wrap_stmt(&self, stmt: &mut Stmt) {
+        if let Stmt::Decl(Decl::Var(decl)) = stmt {
+            self.wrap_decl(decl);
+        }
+    }
+}
+
+impl VisitMut for PreserveHotState<'_> {
+    fn visit_mut_module(&mut self, module: &mut Module) {
+        for item in &module.body {
+            if let ModuleItem::ModuleDecl(ModuleDecl::Import(import)) = item {
+                self.collect_imports(import);
+            }
+        }
+        for item in &mut module.body {
+            match item {
+                ModuleItem::Stmt(stmt) => self.wrap_stmt(stmt),
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                    decl: Decl::Var(decl),
+                    ..
+                })) => self.wrap_decl(decl),
+                _ => {}
+            }
+        }
+    }
+
+    fn visit_mut_script(&mut self, script: &mut Script) {
+        for stmt in &mut script.body {
+            self.wrap_stmt(stmt);
+        }
+    }
+}
@@ -13,6 +13,7 @@ use crate::{
         placeable::EcmascriptChunkPlaceable, EcmascriptChunkItem, EcmascriptChunkItemContent,
         EcmascriptChunkType, EcmascriptChunkingContext,
     },
+    references::InlinedValues,
     EcmascriptModuleContent,
 };
 
@@ -67,6 +68,11 @@ impl EcmascriptChunkItem for EcmascriptModulePartChunkItem {
     fn chunking_context(&self) -> Vc<Box<dyn EcmascriptChunkingContext>> {
         self.chunking_context
     }
+
+    #[turbo_tasks::function]
+    async fn inlined_values(&self) -> Result<Vc<InlinedValues>> {
+        Ok(self.module.analyze().await?.inlined_values)
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -186,11 +186,21 @@ pub async fn read_from_tsconfigs<T>(
 }
 
 /// Resolve options specific to tsconfig.json.
+///
+/// These are usually read from a tsconfig.json with
+/// [tsconfig_resolve_options], but can also be created directly, e.g. for
+/// projects without one.
 #[turbo_tasks::value]
 #[derive(Default)]
 pub struct TsConfigResolveOptions {
-    base_url: Option<Vc<FileSystemPath>>,
-    import_map: Option<Vc<ImportMap>>,
+    /// `compilerOptions.baseUrl`. Modules are resolved in it before they are
+    /// resolved in `node_modules`.
+    pub base_url: Option<Vc<FileSystemPath>>,
+    /// The aliases of `compilerOptions.paths`. Requests which match an alias,
+    /// but don't resolve to any of its targets, are resolved normally.
+    pub import_map: Option<Vc<ImportMap>>,
+    /// `compilerOptions.rootDirs`, see [ResolveOptions::root_dirs].
+    pub root_dirs: Vec<Vc<FileSystemPath>>,
 }
 
 #[turbo_tasks::value_impl]
@@ -272,6 +282,31 @@ pub async fn tsconfig_resolve_options(
         }
     }
 
+    let root_dirs = if let Some(root_dirs) = read_from_tsconfigs(&configs, |json, source| {
+        json["compilerOptions"]["rootDirs"]
+            .as_array()
+            .map(|root_dirs| {
+                let context_dir = source.ident().path().parent();
+                root_dirs
+                    .iter()
+                    .filter_map(|root_dir| root_dir.as_str())
+                    .map(|root_dir| context_dir.try_join(root_dir.to_string()))
+                    .collect::<Vec<_>>()
+            })
+    })
+    .await?
+    {
+        let mut resolved_root_dirs = Vec::new();
+        for root_dir in root_dirs {
+            if let Some(root_dir) = *root_dir.await? {
+                resolved_root_dirs.push(root_dir);
+            }
+        }
+        resolved_root_dirs
+    } else {
+        Vec::new()
+    };
+
     let import_map = if !all_paths.is_empty() {
         let mut import_map = ImportMap::empty();
         for (key, value) in all_paths {
@@ -285,6 +320,7 @@ pub async fn tsconfig_resolve_options(
     Ok(TsConfigResolveOptions {
         base_url,
         import_map,
+        root_dirs,
     }
     .cell())
 }
@@ -319,6 +355,9 @@ pub async fn apply_tsconfig_resolve_options(
                 .unwrap_or(tsconfig_import_map),
         );
     }
+    resolve_options
+        .root_dirs
+        .extend(tsconfig_resolve_options.root_dirs.iter().copied());
     Ok(resolve_options.cell())
 }
 
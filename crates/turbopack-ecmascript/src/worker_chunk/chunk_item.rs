@@ -0,0 +1,135 @@
+use anyhow::{Context, Result};
+use turbo_tasks::{TryJoinIterExt, Vc};
+use turbopack_core::{
+    chunk::{ChunkItem, ChunkType, ChunkingContext, EvaluatableAsset, EvaluatableAssets},
+    ident::AssetIdent,
+    module::Module,
+    output::OutputAssets,
+    reference::{ModuleReferences, SingleOutputAssetReference},
+};
+
+use super::module::WorkerLoaderModule;
+use crate::{
+    chunk::{
+        EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkItemOptions,
+        EcmascriptChunkType, EcmascriptChunkingContext,
+    },
+    utils::StringifyJs,
+};
+
+#[turbo_tasks::function]
+fn worker_modifier() -> Vc<String> {
+    Vc::cell("worker".to_string())
+}
+
+#[turbo_tasks::value(shared)]
+pub struct WorkerLoaderChunkItem {
+    pub module: Vc<WorkerLoaderModule>,
+    pub chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+}
+
+#[turbo_tasks::value_impl]
+impl WorkerLoaderChunkItem {
+    /// Returns the chunks of the worker's own evaluated chunk group, including
+    /// the runtime.
+    #[turbo_tasks::function]
+    async fn chunks(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        let this = self.await?;
+        let module = this.module.await?;
+        let evaluatable = Vc::try_resolve_sidecast::<Box<dyn EvaluatableAsset>>(module.inner)
+            .await?
+            .context("worker entry must be an evaluatable asset")?;
+        Ok(this.chunking_context.evaluated_chunk_group(
+            module.inner.ident().with_modifier(worker_modifier()),
+            EvaluatableAssets::one(evaluatable),
+        ))
+    }
+}
+
+#[turbo_tasks::function]
+fn worker_chunk_reference_description() -> Vc<String> {
+    Vc::cell("worker chunk".to_string())
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for WorkerLoaderChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn EcmascriptChunkingContext>> {
+        self.chunking_context
+    }
+
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<EcmascriptChunkItemContent>> {
+        let this = self.await?;
+        let output_root = this.chunking_context.output_root().await?;
+        let chunks_paths = self
+            .chunks()
+            .await?
+            .iter()
+            .map(|chunk| chunk.ident().path())
+            .try_join()
+            .await?;
+        // Only JavaScript chunks can be loaded by a worker.
+        let chunks_paths: Vec<_> = chunks_paths
+            .iter()
+            .filter(|path| path.path.ends_with(".js"))
+            .filter_map(|path| output_root.get_path_to(path))
+            .collect();
+
+        Ok(EcmascriptChunkItemContent {
+            inner_code: format!(
+                "__turbopack_export_value__(__turbopack_worker_blob_url__({}, {}));\n",
+                StringifyJs(&chunks_paths),
+                this.module.await?.module,
+            )
+            .into(),
+            options: EcmascriptChunkItemOptions {
+                worker: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+        .cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for WorkerLoaderChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> Vc<AssetIdent> {
+        self.module.ident()
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self: Vc<Self>) -> Result<Vc<ModuleReferences>> {
+        Ok(Vc::cell(
+            self.chunks()
+                .await?
+                .iter()
+                .map(|&chunk| {
+                    Vc::upcast(SingleOutputAssetReference::new(
+                        chunk,
+                        worker_chunk_reference_description(),
+                    ))
+                })
+                .collect(),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        Vc::upcast(self.chunking_context)
+    }
+
+    #[turbo_tasks::function]
+    async fn ty(&self) -> Result<Vc<Box<dyn ChunkType>>> {
+        Ok(Vc::upcast(
+            Vc::<EcmascriptChunkType>::default().resolve().await?,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        Vc::upcast(self.module)
+    }
+}
@@ -0,0 +1,4 @@
+pub(crate) mod chunk_item;
+pub(crate) mod module;
+
+pub use module::WorkerLoaderModule;
@@ -0,0 +1,106 @@
+use anyhow::{Context, Result};
+use turbo_tasks::Vc;
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{ChunkableModule, ChunkingContext},
+    ident::AssetIdent,
+    module::Module,
+    reference::{ModuleReferences, SingleModuleReference},
+};
+
+use super::chunk_item::WorkerLoaderChunkItem;
+use crate::chunk::{EcmascriptChunkPlaceable, EcmascriptChunkingContext, EcmascriptExports};
+
+#[turbo_tasks::function]
+fn modifier() -> Vc<String> {
+    Vc::cell("worker loader".to_string())
+}
+
+#[turbo_tasks::function]
+fn module_modifier() -> Vc<String> {
+    Vc::cell("module worker loader".to_string())
+}
+
+/// The worker loader module is placed in the chunk that contains the
+/// `new Worker(new URL(...))` expression. It puts the worker entry into its own
+/// evaluated chunk group, which comes with its own runtime, and exports a URL
+/// that can be passed to the `Worker` constructor to load all of these chunks.
+#[turbo_tasks::value]
+pub struct WorkerLoaderModule {
+    pub inner: Vc<Box<dyn ChunkableModule>>,
+    /// Whether the worker is created with `{ type: "module" }`.
+    pub module: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl WorkerLoaderModule {
+    #[turbo_tasks::function]
+    pub fn new(inner: Vc<Box<dyn ChunkableModule>>, module: bool) -> Vc<Self> {
+        Self::cell(WorkerLoaderModule { inner, module })
+    }
+}
+
+#[turbo_tasks::function]
+fn worker_entry_reference_description() -> Vc<String> {
+    Vc::cell("worker entry".to_string())
+}
+
+#[turbo_tasks::value_impl]
+impl Module for WorkerLoaderModule {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.inner.ident().with_modifier(if self.module {
+            module_modifier()
+        } else {
+            modifier()
+        })
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<ModuleReferences> {
+        Vc::cell(vec![Vc::upcast(SingleModuleReference::new(
+            Vc::upcast(self.inner),
+            worker_entry_reference_description(),
+        ))])
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for WorkerLoaderModule {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        todo!()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModule for WorkerLoaderModule {
+    #[turbo_tasks::function]
+    async fn as_chunk_item(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<Vc<Box<dyn turbopack_core::chunk::ChunkItem>>> {
+        let chunking_context =
+            Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkingContext>>(chunking_context)
+                .await?
+                .context(
+                    "chunking context must impl EcmascriptChunkingContext to use \
+                     WorkerLoaderModule",
+                )?;
+        Ok(Vc::upcast(
+            WorkerLoaderChunkItem {
+                module: self,
+                chunking_context,
+            }
+            .cell(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for WorkerLoaderModule {
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> Vc<EcmascriptExports> {
+        EcmascriptExports::Value.cell()
+    }
+}
@@ -0,0 +1,37 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_env::ProcessEnv;
+use turbopack_core::compile_time_info::{CompileTimeDefines, ImportMetaEnv};
+
+/// Defines `process.env.KEY` for every variable of `env`, so accesses are
+/// replaced with their values at build time. Wrap `env` in a
+/// [turbo_tasks_env::FilterProcessEnv] to only expose public variables.
+#[turbo_tasks::function]
+pub async fn process_env_defines(env: Vc<Box<dyn ProcessEnv>>) -> Result<Vc<CompileTimeDefines>> {
+    Ok(Vc::cell(
+        env.read_all()
+            .await?
+            .iter()
+            .map(|(key, value)| {
+                (
+                    vec!["process".to_string(), "env".to_string(), key.clone()],
+                    value.clone().into(),
+                )
+            })
+            .collect(),
+    ))
+}
+
+/// Exposes every variable of `env` as `import.meta.env.KEY`, like Vite does.
+/// Wrap `env` in a [turbo_tasks_env::FilterProcessEnv] to only expose public
+/// variables.
+#[turbo_tasks::function]
+pub async fn import_meta_env(env: Vc<Box<dyn ProcessEnv>>) -> Result<Vc<ImportMetaEnv>> {
+    Ok(Vc::cell(
+        env.read_all()
+            .await?
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone().into()))
+            .collect(),
+    ))
+}
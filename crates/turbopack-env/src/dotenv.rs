@@ -15,10 +15,23 @@ pub async fn load_env(project_path: Vc<FileSystemPath>) -> Result<Vc<Box<dyn Pro
     let node_env = env.read("NODE_ENV".to_string()).await?;
     let node_env = node_env.as_deref().unwrap_or("development");
 
+    Ok(load_env_for_mode(project_path, node_env.to_string()))
+}
+
+/// Like [load_env], but `node_env` selects the dotenv files (e.g.
+/// `.env.production`) and overrides the `NODE_ENV` of the process, e.g. for
+/// production builds.
+#[turbo_tasks::function]
+pub fn load_env_for_mode(
+    project_path: Vc<FileSystemPath>,
+    node_env: String,
+) -> Vc<Box<dyn ProcessEnv>> {
+    let env: Vc<Box<dyn ProcessEnv>> = Vc::upcast(CommandLineProcessEnv::new());
+
     let env = Vc::upcast(CustomProcessEnv::new(
         env,
         Vc::cell(indexmap! {
-            "NODE_ENV".to_string() => node_env.to_string(),
+            "NODE_ENV".to_string() => node_env.clone(),
         }),
     ));
 
@@ -35,10 +48,8 @@ pub async fn load_env(project_path: Vc<FileSystemPath>) -> Result<Vc<Box<dyn Pro
     .into_iter()
     .flatten();
 
-    let env = files.fold(env, |prior, f| {
+    files.fold(env, |prior, f| {
         let path = project_path.join(f);
         Vc::upcast(TryDotenvProcessEnv::new(prior, path))
-    });
-
-    Ok(env)
+    })
 }
@@ -8,6 +8,10 @@
 //! have higher priority to define a environment variable (later dotenv files
 //! cannot override it). Later dotenv files can reference variables prior
 //! defined variables.
+//!
+//! Public config keys can also be exposed through a virtual `runtime-config`
+//! module, whose values can be overridden by the server at render time, or be
+//! replaced at build time through compile time defines (see [defines]).
 
 #![feature(async_closure)]
 #![feature(min_specialization)]
@@ -15,14 +19,17 @@
 #![feature(async_fn_in_trait)]
 
 mod asset;
+pub mod defines;
 pub mod dotenv;
 mod embeddable;
 mod issue;
+mod runtime_config;
 mod try_env;
 
 pub use asset::ProcessEnvAsset;
 pub use embeddable::EmbeddableProcessEnv;
 pub use issue::ProcessEnvIssue;
+pub use runtime_config::{RuntimeConfigAsset, RUNTIME_CONFIG_GLOBAL};
 pub use try_env::TryDotenvProcessEnv;
 
 pub fn register() {
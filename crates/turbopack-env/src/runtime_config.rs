@@ -0,0 +1,123 @@
+use std::io::Write;
+
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_env::ProcessEnv;
+use turbo_tasks_fs::{rope::RopeBuilder, File, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    ident::AssetIdent,
+    resolve::{options::ImportMapping, ResolveResult},
+    source::Source,
+};
+use turbopack_ecmascript::utils::StringifyJs;
+
+/// The global the server can assign an object to in order to override values
+/// of the runtime config at render time, e.g.
+/// `globalThis.__turbopack_runtime_config__ = { API_URL: "..." }`.
+///
+/// On the client, the server is expected to inline a script setting this
+/// global before any chunks are loaded. Keys that are not present in the
+/// global fall back to the values that were known at build time.
+pub const RUNTIME_CONFIG_GLOBAL: &str = "__turbopack_runtime_config__";
+
+/// A virtual `runtime-config` module exporting the declared public config
+/// keys. Values are read from the env at build time and can be overridden at
+/// render time through [RUNTIME_CONFIG_GLOBAL].
+///
+/// One module is created per build variant (e.g. `client`, `server` or
+/// `edge`), so that each can be populated from its own env.
+#[turbo_tasks::value]
+pub struct RuntimeConfigAsset {
+    /// The root path which we can construct our runtime config asset path.
+    root: Vc<FileSystemPath>,
+
+    /// The name of the build variant, which is part of the asset path.
+    variant: String,
+
+    /// The public config keys exported by the module.
+    keys: Vec<String>,
+
+    /// The env the build time values are read from.
+    env: Vc<Box<dyn ProcessEnv>>,
+}
+
+#[turbo_tasks::value_impl]
+impl RuntimeConfigAsset {
+    #[turbo_tasks::function]
+    pub fn new(
+        root: Vc<FileSystemPath>,
+        variant: String,
+        keys: Vec<String>,
+        env: Vc<Box<dyn ProcessEnv>>,
+    ) -> Vc<Self> {
+        RuntimeConfigAsset {
+            root,
+            variant,
+            keys,
+            env,
+        }
+        .cell()
+    }
+
+    /// An import mapping which resolves directly to this module, e.g. to alias
+    /// `runtime-config` in an import map.
+    #[turbo_tasks::function]
+    pub fn import_mapping(self: Vc<Self>) -> Vc<ImportMapping> {
+        ImportMapping::Direct(ResolveResult::source(Vc::upcast(self)).cell()).cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Source for RuntimeConfigAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(
+            self.root
+                .join(format!("runtime-config.{}.js", self.variant)),
+        )
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for RuntimeConfigAsset {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<AssetContent>> {
+        let mut code = RopeBuilder::default();
+        code += "const defaults = {\n";
+        for key in &self.keys {
+            let value = self.env.read(key.clone()).await?;
+            writeln!(
+                code,
+                "  {}: {},",
+                StringifyJs(key),
+                StringifyJs(&value.as_deref())
+            )?;
+        }
+        code += "};\n\n";
+
+        // Values are looked up on every access, so overrides which are assigned
+        // after this module has been evaluated (e.g. per request on the server)
+        // are picked up as well.
+        writeln!(
+            code,
+            "const config = {{}};
+for (const key of Object.keys(defaults)) {{
+  Object.defineProperty(config, key, {{
+    enumerable: true,
+    get() {{
+      const overrides = globalThis[{global}];
+      return overrides != null && Object.prototype.hasOwnProperty.call(overrides, key)
+        ? overrides[key]
+        : defaults[key];
+    }},
+  }});
+}}
+
+export default Object.freeze(config);",
+            global = StringifyJs(RUNTIME_CONFIG_GLOBAL),
+        )?;
+
+        Ok(AssetContent::file(File::from(code.build()).into()))
+    }
+}
@@ -414,6 +414,68 @@ pub async fn get_meta_data(
     }
 }
 
+/// Reads the dimensions of an image from its header without decoding the
+/// image data. Optionally computes a blur placeholder, which requires decoding.
+#[turbo_tasks::function]
+pub async fn sniff_meta_data(
+    ident: Vc<AssetIdent>,
+    content: Vc<FileContent>,
+    blur_placeholder: Option<Vc<BlurPlaceholderOptions>>,
+) -> Result<Vc<ImageMetaData>> {
+    let path = ident.path().await?;
+    if path.extension_ref() == Some("svg") {
+        return Ok(get_meta_data(ident, content, None));
+    }
+    let FileContent::Content(file) = &*content.await? else {
+        bail!("Input image not found");
+    };
+    let bytes = file.content().to_bytes()?;
+    let reader = image::io::Reader::new(Cursor::new(&bytes));
+    let dimensions = result_to_issue(
+        ident,
+        reader
+            .with_guessed_format()
+            .context("unable to determine image format from file content")
+            .and_then(|reader| {
+                let format = reader.format();
+                let dimensions = reader
+                    .into_dimensions()
+                    .context("unable to read image dimensions")?;
+                Ok((dimensions, format))
+            }),
+    );
+    let Some(((width, height), format)) = dimensions else {
+        return Ok(ImageMetaData::fallback_value(None).cell());
+    };
+
+    let blur_placeholder = if blur_placeholder.is_some() {
+        get_meta_data(ident, content, blur_placeholder)
+            .await?
+            .blur_placeholder
+            .as_ref()
+            .map(|blur| BlurPlaceholder {
+                data_url: blur.data_url.clone(),
+                width: blur.width,
+                height: blur.height,
+            })
+    } else {
+        None
+    };
+
+    Ok(ImageMetaData {
+        width,
+        height,
+        mime_type: if let Some(format) = format {
+            image_format_to_mime_type(format)?
+        } else {
+            None
+        },
+        blur_placeholder,
+        placeholder_for_future_extensions: (),
+    }
+    .cell())
+}
+
 #[turbo_tasks::function]
 pub async fn optimize(
     ident: Vc<AssetIdent>,
@@ -478,6 +540,46 @@ pub async fn optimize(
     }
 }
 
+/// Resizes an image to fit into `max_width` x `max_height` and encodes it in
+/// the format given by `extension`, e.g. `webp`. Keeps the original format
+/// when no `extension` is passed.
+#[turbo_tasks::function]
+pub async fn optimize_as(
+    ident: Vc<AssetIdent>,
+    content: Vc<FileContent>,
+    max_width: u32,
+    max_height: u32,
+    quality: u8,
+    extension: Option<String>,
+) -> Result<Vc<FileContent>> {
+    let Some(extension) = extension else {
+        return Ok(optimize(ident, content, max_width, max_height, quality));
+    };
+    let Some(target_format) = extension_to_image_format(&extension) else {
+        bail!("Unknown image format {extension}");
+    };
+    let FileContent::Content(content) = &*content.await? else {
+        return Ok(FileContent::NotFound.cell());
+    };
+    let bytes = content.content().to_bytes()?;
+
+    let Some((image, _)) = load_image(ident, &bytes, ident.path().await?.extension_ref()) else {
+        return Ok(FileContent::NotFound.cell());
+    };
+    let ImageBuffer::Decoded(image) = image else {
+        bail!("Converting {extension} images requires decoding support for the input format");
+    };
+    let (width, height) = image.dimensions();
+    let image = if width > max_width || height > max_height {
+        image.resize(max_width, max_height, FilterType::Lanczos3)
+    } else {
+        image
+    };
+    let (data, mime_type) = encode_image(image, target_format, quality)?;
+
+    Ok(FileContent::Content(File::from(data).with_content_type(mime_type)).cell())
+}
+
 #[turbo_tasks::value]
 struct ImageProcessingIssue {
     path: Vc<FileSystemPath>,
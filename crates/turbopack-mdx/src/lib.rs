@@ -2,8 +2,13 @@
 #![feature(arbitrary_self_types)]
 #![feature(async_fn_in_trait)]
 
+mod markdown;
+
+use std::{borrow::Cow, fmt::Write};
+
 use anyhow::{anyhow, Context, Result};
 use mdxjs::{compile, Options};
+use serde_json::Value as JsonValue;
 use turbo_tasks::{Value, ValueDefault, Vc};
 use turbo_tasks_fs::{rope::Rope, File, FileContent, FileSystemPath};
 use turbopack_core::{
@@ -11,6 +16,7 @@ use turbopack_core::{
     chunk::{AsyncModuleInfo, ChunkItem, ChunkType, ChunkableModule, ChunkingContext},
     context::AssetContext,
     ident::AssetIdent,
+    issue::{Issue, IssueExt, IssueSeverity, IssueSource, OptionIssueSource},
     module::Module,
     reference::ModuleReferences,
     resolve::origin::ResolveOrigin,
@@ -26,6 +32,8 @@ use turbopack_ecmascript::{
     EcmascriptModuleAssetType,
 };
 
+pub use self::markdown::MarkdownModuleAsset;
+
 #[turbo_tasks::function]
 fn modifier() -> Vc<String> {
     Vc::cell("mdx".to_string())
@@ -41,6 +49,9 @@ pub struct MdxTransformOptions {
     pub jsx_runtime: Option<String>,
     pub jsx_import_source: Option<String>,
     pub provider_import_source: Option<String>,
+    /// Parses YAML frontmatter at the start of a file and exports it as
+    /// `frontmatter`, instead of rendering it as markdown.
+    pub frontmatter: bool,
 }
 
 impl Default for MdxTransformOptions {
@@ -51,6 +62,7 @@ impl Default for MdxTransformOptions {
             jsx_runtime: None,
             jsx_import_source: None,
             provider_import_source: None,
+            frontmatter: false,
         }
     }
 }
@@ -69,6 +81,33 @@ impl ValueDefault for MdxTransformOptions {
     }
 }
 
+/// The YAML frontmatter of an MDX file as JSON, see
+/// [MdxModuleAsset::frontmatter].
+#[turbo_tasks::value(transparent)]
+pub struct MdxFrontmatter(#[turbo_tasks(trace_ignore)] Option<JsonValue>);
+
+/// Splits a file into its YAML frontmatter, which is delimited by `---` lines
+/// at the very start, and the rest of the file.
+fn split_frontmatter(content: &str) -> Option<(&str, &str)> {
+    let rest = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))?;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Parses YAML frontmatter as JSON. Empty frontmatter is an empty object.
+fn parse_frontmatter(yaml: &str) -> Result<JsonValue, serde_yaml::Error> {
+    serde_yaml::from_str::<Option<JsonValue>>(yaml)
+        .map(|value| value.unwrap_or_else(|| JsonValue::Object(Default::default())))
+}
+
 #[turbo_tasks::value]
 #[derive(Clone, Copy)]
 pub struct MdxModuleAsset {
@@ -120,9 +159,27 @@ async fn into_ecmascript_module_asset(
         filepath: Some(this.source.ident().path().await?.to_string()),
         ..Default::default()
     };
+    let mut content = file.content().to_str()?;
+    if transform_options.frontmatter {
+        if let Some((_, rest)) = split_frontmatter(&content) {
+            // Blank lines keep the line numbers of errors and source maps.
+            let lines = content[..content.len() - rest.len()].matches('\n').count();
+            content = Cow::Owned("\n".repeat(lines) + rest);
+        }
+    }
     // TODO: upstream mdx currently bubbles error as string
-    let mdx_jsx_component =
-        compile(&file.content().to_str()?, &options).map_err(|e| anyhow!("{}", e))?;
+    let mut mdx_jsx_component = compile(&content, &options).map_err(|e| anyhow!("{}", e))?;
+    if transform_options.frontmatter {
+        let frontmatter = match &*current_context.frontmatter().await? {
+            Some(frontmatter) => serde_json::to_string(frontmatter)?,
+            None => "{}".to_string(),
+        };
+        write!(
+            mdx_jsx_component,
+            "\nexport const frontmatter = {};\n",
+            frontmatter
+        )?;
+    }
 
     let source = VirtualSource::new_with_ident(
         this.source.ident(),
@@ -155,6 +212,41 @@ impl MdxModuleAsset {
         })
     }
 
+    /// The YAML frontmatter of the file as JSON, e.g. for static site
+    /// generators which list pages by their metadata. It's available even when
+    /// [MdxTransformOptions::frontmatter] is disabled.
+    #[turbo_tasks::function]
+    pub async fn frontmatter(&self) -> Result<Vc<MdxFrontmatter>> {
+        let AssetContent::File(file) = &*self.source.content().await? else {
+            return Ok(Vc::cell(None));
+        };
+        let FileContent::Content(file) = &*file.await? else {
+            return Ok(Vc::cell(None));
+        };
+        let content = file.content().to_str()?;
+        let Some((yaml, _)) = split_frontmatter(&content) else {
+            return Ok(Vc::cell(None));
+        };
+        match parse_frontmatter(yaml) {
+            Ok(frontmatter) => Ok(Vc::cell(Some(frontmatter))),
+            Err(err) => {
+                // The frontmatter starts after the opening `---` line.
+                let start = content.find('\n').map_or(0, |index| index + 1);
+                FrontmatterIssue {
+                    file_path: self.source.ident().path(),
+                    source: err.location().map(|location| {
+                        let offset = start + location.index();
+                        IssueSource::from_byte_offset(self.source, offset, offset)
+                    }),
+                    message: err.to_string(),
+                }
+                .cell()
+                .emit();
+                Ok(Vc::cell(None))
+            }
+        }
+    }
+
     #[turbo_tasks::function]
     async fn failsafe_analyze(self: Vc<Self>) -> Result<Vc<AnalyzeEcmascriptModuleResult>> {
         Ok(into_ecmascript_module_asset(&self)
@@ -293,6 +385,46 @@ impl EcmascriptChunkItem for MdxChunkItem {
     }
 }
 
+#[turbo_tasks::value(shared)]
+struct FrontmatterIssue {
+    file_path: Vc<FileSystemPath>,
+    source: Option<Vc<IssueSource>>,
+    message: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for FrontmatterIssue {
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell("Unable to parse the YAML frontmatter".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(self.message.clone())
+    }
+
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("parse".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> Vc<OptionIssueSource> {
+        Vc::cell(self.source)
+    }
+}
+
 pub fn register() {
     turbo_tasks::register();
     turbo_tasks_fs::register();
@@ -300,3 +432,45 @@ pub fn register() {
     turbopack_ecmascript::register();
     include!(concat!(env!("OUT_DIR"), "/register.rs"));
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{parse_frontmatter, split_frontmatter};
+
+    #[test]
+    fn splits_frontmatter() {
+        assert_eq!(
+            split_frontmatter("---\ntitle: Hello\n---\n# Hello\n"),
+            Some(("title: Hello\n", "# Hello\n"))
+        );
+        assert_eq!(
+            split_frontmatter("---\r\ntitle: Hello\r\n---\r\n# Hello"),
+            Some(("title: Hello\r\n", "# Hello"))
+        );
+        assert_eq!(split_frontmatter("---\n---\n"), Some(("", "")));
+    }
+
+    #[test]
+    fn requires_frontmatter_at_the_start() {
+        assert_eq!(split_frontmatter("# Hello\n---\ntitle: Hello\n---\n"), None);
+        assert_eq!(split_frontmatter("---\ntitle: Hello\n"), None);
+        assert_eq!(split_frontmatter("----\ntitle: Hello\n----\n"), None);
+    }
+
+    #[test]
+    fn parses_frontmatter() {
+        assert_eq!(
+            parse_frontmatter("title: Hello\ntags: [a, b]\n").unwrap(),
+            json!({ "title": "Hello", "tags": ["a", "b"] })
+        );
+        assert_eq!(parse_frontmatter("").unwrap(), json!({}));
+    }
+
+    #[test]
+    fn reports_invalid_frontmatter() {
+        let err = parse_frontmatter("title: Hello\ntags: [a, b\n").unwrap_err();
+        assert!(err.location().is_some());
+    }
+}
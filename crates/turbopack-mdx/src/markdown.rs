@@ -0,0 +1,162 @@
+use anyhow::{anyhow, Context, Result};
+use turbo_tasks::{ValueToString, Vc};
+use turbo_tasks_fs::FileContent;
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{ChunkItem, ChunkType, ChunkableModule, ChunkingContext},
+    ident::AssetIdent,
+    module::Module,
+    reference::ModuleReferences,
+    source::Source,
+};
+use turbopack_ecmascript::chunk::{
+    EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkPlaceable, EcmascriptChunkType,
+    EcmascriptChunkingContext, EcmascriptExports,
+};
+
+#[turbo_tasks::function]
+fn modifier() -> Vc<String> {
+    Vc::cell("markdown".to_string())
+}
+
+/// A markdown file which is compiled to HTML and exported as a string, for
+/// importing content without the JSX runtime which [super::MdxModuleAsset]
+/// requires.
+///
+/// GitHub flavored markdown is supported, and YAML frontmatter is left out of
+/// the HTML.
+#[turbo_tasks::value]
+pub struct MarkdownModuleAsset {
+    source: Vc<Box<dyn Source>>,
+}
+
+#[turbo_tasks::value_impl]
+impl MarkdownModuleAsset {
+    #[turbo_tasks::function]
+    pub fn new(source: Vc<Box<dyn Source>>) -> Vc<Self> {
+        Self::cell(MarkdownModuleAsset { source })
+    }
+
+    /// The HTML of the markdown file.
+    #[turbo_tasks::function]
+    pub async fn html(self: Vc<Self>) -> Result<Vc<String>> {
+        let AssetContent::File(file) = &*self.content().await? else {
+            anyhow::bail!("Unexpected markdown asset content");
+        };
+        let FileContent::Content(file) = &*file.await? else {
+            anyhow::bail!(
+                "Markdown file not found: {}",
+                self.ident().to_string().await?
+            );
+        };
+        let mut options = markdown::Options::gfm();
+        options.parse.constructs.frontmatter = true;
+        let html = markdown::to_html_with_options(&file.content().to_str()?, &options)
+            .map_err(|e| anyhow!("{}", e))
+            .context("Unable to compile markdown")?;
+        Ok(Vc::cell(html))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Module for MarkdownModuleAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.source.ident().with_modifier(modifier())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for MarkdownModuleAsset {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        self.source.content()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModule for MarkdownModuleAsset {
+    #[turbo_tasks::function]
+    async fn as_chunk_item(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<Vc<Box<dyn ChunkItem>>> {
+        let chunking_context =
+            Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkingContext>>(chunking_context)
+                .await?
+                .context(
+                    "chunking context must impl EcmascriptChunkingContext to use \
+                     MarkdownModuleAsset",
+                )?;
+        Ok(Vc::upcast(MarkdownChunkItem::cell(MarkdownChunkItem {
+            module: self,
+            chunking_context,
+        })))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for MarkdownModuleAsset {
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> Vc<EcmascriptExports> {
+        EcmascriptExports::Value.cell()
+    }
+}
+
+#[turbo_tasks::value]
+struct MarkdownChunkItem {
+    module: Vc<MarkdownModuleAsset>,
+    chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for MarkdownChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> Vc<AssetIdent> {
+        self.module.ident()
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<ModuleReferences> {
+        self.module.references()
+    }
+
+    #[turbo_tasks::function]
+    async fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        Vc::upcast(self.chunking_context)
+    }
+
+    #[turbo_tasks::function]
+    async fn ty(&self) -> Result<Vc<Box<dyn ChunkType>>> {
+        Ok(Vc::upcast(
+            Vc::<EcmascriptChunkType>::default().resolve().await?,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        Vc::upcast(self.module)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for MarkdownChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn EcmascriptChunkingContext>> {
+        self.chunking_context
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<EcmascriptChunkItemContent>> {
+        let html = self.module.html().await?;
+        let inner_code = format!(
+            "__turbopack_export_value__({});",
+            serde_json::to_string(&*html)?
+        );
+        Ok(EcmascriptChunkItemContent {
+            inner_code: inner_code.into(),
+            ..Default::default()
+        }
+        .into())
+    }
+}
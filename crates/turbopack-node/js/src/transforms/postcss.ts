@@ -18,7 +18,12 @@ const toPath = (file: string) => {
   return sep !== "/" ? relPath.replaceAll(sep, "/") : relPath;
 };
 
-const transform = async (ipc: Ipc, cssContent: string, name: string) => {
+const transform = async (
+  ipc: Ipc,
+  cssContent: string,
+  name: string,
+  pluginOptions: Record<string, unknown>
+) => {
   let config = importedConfig;
   if (typeof config === "function") {
     config = await config({ env: "development" });
@@ -39,6 +44,20 @@ const transform = async (ipc: Ipc, cssContent: string, name: string) => {
   } else {
     plugins = [];
   }
+  // Options passed by turbopack take precedence over the config. `false`
+  // disables a plugin.
+  for (const [pluginName, options] of Object.entries(pluginOptions)) {
+    const index = plugins.findIndex(
+      (plugin) => Array.isArray(plugin) && plugin[0] === pluginName
+    );
+    if (options === false) {
+      if (index !== -1) plugins.splice(index, 1);
+    } else if (index !== -1) {
+      plugins[index] = [pluginName, options];
+    } else {
+      plugins.push([pluginName, options]);
+    }
+  }
   const loadedPlugins = plugins.map((plugin) => {
     if (Array.isArray(plugin)) {
       const [arg, options] = plugin;
@@ -80,6 +99,7 @@ const transform = async (ipc: Ipc, cssContent: string, name: string) => {
           // There is also an info field, which we currently ignore
         });
         break;
+      case "dependency":
       case "file-dependency":
       case "missing-dependency":
         ipc.send({
@@ -97,7 +117,7 @@ const transform = async (ipc: Ipc, cssContent: string, name: string) => {
         ipc.send({
           type: "dirDependency",
           path: toPath(msg.dir),
-          glob: msg.glob,
+          glob: msg.glob ?? "**",
         });
         break;
       case "context-dependency":
@@ -0,0 +1,121 @@
+declare const __turbopack_external_require__: (id: string) => any;
+
+// @ts-ignore
+import { compile } from "@vercel/turbopack/tailwindcss";
+import { readFile } from "fs/promises";
+import { createRequire } from "module";
+import { dirname, isAbsolute, join, relative, resolve, sep } from "path";
+import type { Ipc } from "../ipc/evaluate";
+
+type Compiler = { build(candidates: string[]): string };
+
+type CachedCompiler = {
+  cssContent: string;
+  // The stylesheets the compiler loaded, with their content.
+  stylesheets: Map<string, string>;
+  compiler: Promise<Compiler>;
+};
+
+const contextDir = process.cwd();
+const toPath = (file: string) => {
+  const relPath = relative(contextDir, file);
+  if (isAbsolute(relPath)) {
+    throw new Error(
+      `Cannot depend on path (${file}) outside of root directory (${contextDir})`
+    );
+  }
+  return sep !== "/" ? relPath.replaceAll(sep, "/") : relPath;
+};
+
+const resolveModule = (id: string, base: string) =>
+  createRequire(join(base, "index.js")).resolve(id);
+
+// `@import "tailwindcss"` imports the stylesheet of a package, which Tailwind
+// CSS packages export as `index.css`.
+const resolveStylesheet = (id: string, base: string) => {
+  if (id.startsWith(".") || isAbsolute(id)) {
+    return resolve(base, id);
+  }
+  return resolveModule(id.endsWith(".css") ? id : `${id}/index.css`, base);
+};
+
+const createCompiler = (
+  ipc: Ipc,
+  cssContent: string,
+  name: string
+): CachedCompiler => {
+  const stylesheets = new Map<string, string>();
+  const compiler = compile(cssContent, {
+    base: dirname(name),
+    async loadStylesheet(id: string, base: string) {
+      const path = resolveStylesheet(id, base);
+      const content = await readFile(path, "utf8");
+      stylesheets.set(path, content);
+      return { base: dirname(path), content };
+    },
+    // Loads `@plugin` and `@config` modules.
+    async loadModule(id: string, base: string) {
+      const path = resolveModule(id, base);
+      // Modules are cached by `require`, so the process must not be reused
+      // after they changed.
+      ipc.send({
+        type: "buildDependency",
+        path: toPath(path),
+      });
+      const module = __turbopack_external_require__(path);
+      return { base: dirname(path), module: module.default ?? module };
+    },
+  });
+  return { cssContent, stylesheets, compiler };
+};
+
+const isFresh = async ({ stylesheets }: CachedCompiler) => {
+  for (const [path, content] of stylesheets) {
+    const current = await readFile(path, "utf8").catch(() => undefined);
+    if (current !== content) {
+      return false;
+    }
+  }
+  return true;
+};
+
+// Compilers are kept per stylesheet, so only the utilities of new candidates
+// are generated when the content files change.
+const compilers = new Map<string, CachedCompiler>();
+
+const transform = async (
+  ipc: Ipc,
+  cssContent: string,
+  name: string,
+  candidates: string[]
+) => {
+  let cached = compilers.get(name);
+  if (
+    !cached ||
+    cached.cssContent !== cssContent ||
+    !(await isFresh(cached))
+  ) {
+    const created = createCompiler(ipc, cssContent, name);
+    created.compiler.catch(() => {
+      if (compilers.get(name) === created) {
+        compilers.delete(name);
+      }
+    });
+    compilers.set(name, created);
+    cached = created;
+  }
+  const compiler = await cached.compiler;
+  // Turbopack only tracks the dependencies reported by this evaluation, so
+  // they are reported for cached compilers as well.
+  for (const path of cached.stylesheets.keys()) {
+    ipc.send({
+      type: "fileDependency",
+      path: toPath(path),
+    });
+  }
+  return {
+    css: compiler.build(candidates),
+  };
+};
+
+export { transform as default };
@@ -10,6 +10,7 @@ import {
   dirname,
   resolve as pathResolve,
 } from "path";
+import { parse as parseQuery } from "querystring";
 import {
   StackFrame,
   parse as parseStackTrace,
@@ -99,11 +100,21 @@ const transform = (
   ipc: Ipc,
   content: string,
   name: string,
+  query: string,
   loaders: LoaderConfig[]
 ) => {
   return new Promise((resolve, reject) => {
-    const resource = pathResolve(contextDir, name);
-    const resourceDir = dirname(resource);
+    const resourcePath = pathResolve(contextDir, name);
+    const resourceDir = dirname(resourcePath);
+    // loader-runner splits the query off the resource, pitching loaders
+    // often depend on it
+    const resource = resourcePath + query;
+    const assets: {
+      file: string;
+      content: string;
+      encoding: "utf-8" | "base64";
+      sourceMap?: string;
+    }[] = [];
 
     const loadersWithOptions = loaders.map((loader) =>
       typeof loader === "string" ? { loader, options: {} } : loader
@@ -117,9 +128,37 @@ const transform = (
           rootContext: contextDir,
           getOptions() {
             const entry = this.loaders[this.loaderIndex];
-            return entry.options && typeof entry.options === "object"
-              ? entry.options
-              : {};
+            const options = entry.options;
+            if (typeof options === "string") {
+              // Options of inline loaders are passed as a query string or JSON
+              return options.startsWith("{")
+                ? JSON.parse(options)
+                : parseQuery(options.replace(/^\?/, ""));
+            }
+            return options && typeof options === "object" ? options : {};
+          },
+          emitFile(
+            file: string,
+            content: string | Buffer,
+            sourceMap?: string | object
+          ) {
+            const isBuffer = Buffer.isBuffer(content);
+            assets.push({
+              file,
+              // Buffers are often binary, e.g. images
+              content: isBuffer ? content.toString("base64") : content,
+              encoding: isBuffer ? "base64" : "utf-8",
+              sourceMap:
+                typeof sourceMap === "object"
+                  ? JSON.stringify(sourceMap)
+                  : sourceMap,
+            });
+          },
+          addBuildDependency(file: string) {
+            ipc.send({
+              type: "buildDependency",
+              path: toPath(file),
+            });
           },
           getResolve: () => ({
             // [TODO] this is incomplete
@@ -162,23 +201,32 @@ const transform = (
         },
       },
       (err, result) => {
-        if (err) return reject(err);
-        for (const dep of result.contextDependencies) {
+        // Dependencies are reported for failed runs as well, so that fixing
+        // them invalidates the result.
+        for (const dep of result?.contextDependencies ?? []) {
           ipc.send({
             type: "dirDependency",
             path: toPath(dep),
             glob: "**",
           });
         }
-        for (const dep of result.fileDependencies) {
+        for (const dep of [
+          ...(result?.fileDependencies ?? []),
+          ...(result?.missingDependencies ?? []),
+        ]) {
           ipc.send({
             type: "fileDependency",
             path: toPath(dep),
           });
         }
+        if (err) return reject(err);
         if (!result.result) return reject(new Error("No result from loaders"));
         const [source, map] = result.result;
-        resolve({ source, map });
+        resolve({
+          source,
+          map: typeof map === "object" ? JSON.stringify(map) : map,
+          assets,
+        });
       }
     );
   });
@@ -0,0 +1,108 @@
+// @ts-ignore
+import ts from "@vercel/turbopack/typescript";
+import { relative, isAbsolute, sep, resolve, dirname } from "path";
+import type { Ipc } from "./ipc/evaluate";
+
+type Category = "warning" | "error" | "suggestion" | "message";
+
+type TypeCheckDiagnostic = {
+  file?: string;
+  line?: number;
+  column?: number;
+  endLine?: number;
+  endColumn?: number;
+  code: number;
+  message: string;
+  category: Category;
+};
+
+// Indexed by `ts.DiagnosticCategory`.
+const CATEGORIES: Category[] = ["warning", "error", "suggestion", "message"];
+
+const contextDir = process.cwd();
+const toPath = (file: string) => {
+  const relPath = relative(contextDir, file);
+  if (isAbsolute(relPath) || relPath.startsWith("..")) {
+    return undefined;
+  }
+  return sep !== "/" ? relPath.replaceAll(sep, "/") : relPath;
+};
+
+function toDiagnostic(diagnostic: any): TypeCheckDiagnostic {
+  const result: TypeCheckDiagnostic = {
+    code: diagnostic.code,
+    message: ts.flattenDiagnosticMessageText(diagnostic.messageText, "\n"),
+    category: CATEGORIES[diagnostic.category] ?? "error",
+  };
+  const file = diagnostic.file && toPath(diagnostic.file.fileName);
+  if (file === undefined) {
+    return result;
+  }
+  result.file = file;
+  if (diagnostic.start !== undefined) {
+    const start = diagnostic.file.getLineAndCharacterOfPosition(
+      diagnostic.start
+    );
+    const end = diagnostic.file.getLineAndCharacterOfPosition(
+      diagnostic.start + (diagnostic.length ?? 0)
+    );
+    result.line = start.line;
+    result.column = start.character;
+    result.endLine = end.line;
+    result.endColumn = end.character;
+  }
+  return result;
+}
+
+const typeCheck = async (
+  ipc: Ipc,
+  rootFiles: string[],
+  tsconfig: string | undefined
+): Promise<TypeCheckDiagnostic[]> => {
+  const configPath = tsconfig
+    ? resolve(contextDir, tsconfig)
+    : ts.findConfigFile(contextDir, ts.sys.fileExists);
+
+  const diagnostics: any[] = [];
+  let options = {};
+  if (configPath) {
+    const path = toPath(configPath);
+    if (path !== undefined) {
+      ipc.send({ type: "fileDependency", path });
+    }
+    const { config, error } = ts.readConfigFile(configPath, ts.sys.readFile);
+    if (error) {
+      diagnostics.push(error);
+    } else {
+      const parsed = ts.parseJsonConfigFileContent(
+        config,
+        ts.sys,
+        dirname(configPath)
+      );
+      options = parsed.options;
+      diagnostics.push(...parsed.errors);
+    }
+  }
+
+  const program = ts.createProgram({
+    rootNames: rootFiles.map((file) => resolve(contextDir, file)),
+    options: { ...options, noEmit: true },
+  });
+
+  // The diagnostics depend on all files of the program, including type
+  // declarations which are not part of the module graph.
+  for (const sourceFile of program.getSourceFiles()) {
+    if (program.isSourceFileDefaultLibrary(sourceFile)) {
+      continue;
+    }
+    const path = toPath(sourceFile.fileName);
+    if (path !== undefined) {
+      ipc.send({ type: "fileDependency", path });
+    }
+  }
+
+  diagnostics.push(...ts.getPreEmitDiagnostics(program));
+  return diagnostics.map(toDiagnostic);
+};
+
+export { typeCheck as default };
@@ -12,8 +12,7 @@ use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use turbo_tasks::{
-    duration_span, mark_finished, util::SharedError, Completion, RawVc, TryJoinIterExt, Value,
-    ValueToString, Vc,
+    duration_span, mark_finished, util::SharedError, Completion, RawVc, TryJoinIterExt, Value, Vc,
 };
 use turbo_tasks_bytes::{Bytes, Stream};
 use turbo_tasks_env::ProcessEnv;
@@ -434,12 +433,10 @@ async fn pull_operation(
             }
             EvalJavaScriptIncomingMessage::BuildDependency { path } => {
                 // TODO We might miss some changes that happened during execution
-                BuildDependencyIssue {
-                    context_ident: context_ident_for_issue,
-                    path: cwd.join(path),
-                }
-                .cell()
-                .emit();
+                file_dependencies.push(cwd.join(path).read());
+                // Build dependencies, like loaders or their config, are cached
+                // by the process, so it must not be reused after they changed.
+                operation.disallow_reuse();
             }
             EvalJavaScriptIncomingMessage::DirDependency { path, glob } => {
                 // TODO We might miss some changes that happened during execution
@@ -517,44 +514,6 @@ impl Issue for EvaluationIssue {
     }
 }
 
-/// An issue that occurred while evaluating node code.
-#[turbo_tasks::value(shared)]
-pub struct BuildDependencyIssue {
-    pub context_ident: Vc<AssetIdent>,
-    pub path: Vc<FileSystemPath>,
-}
-
-#[turbo_tasks::value_impl]
-impl Issue for BuildDependencyIssue {
-    #[turbo_tasks::function]
-    fn severity(&self) -> Vc<IssueSeverity> {
-        IssueSeverity::Warning.into()
-    }
-
-    #[turbo_tasks::function]
-    fn title(&self) -> Vc<String> {
-        Vc::cell("Build dependencies are not yet supported".to_string())
-    }
-
-    #[turbo_tasks::function]
-    fn category(&self) -> Vc<String> {
-        Vc::cell("build".to_string())
-    }
-
-    #[turbo_tasks::function]
-    fn file_path(&self) -> Vc<FileSystemPath> {
-        self.context_ident.path()
-    }
-
-    #[turbo_tasks::function]
-    async fn description(&self) -> Result<Vc<String>> {
-        Ok(Vc::cell(
-            format!("The file at {} is a build dependency, which is not yet implemented.
-Changing this file or any dependency will not be recognized and might require restarting the server", self.path.to_string().await?)
-        ))
-    }
-}
-
 /// A hack to invalidate when any file in a directory changes. Need to be
 /// awaited before files are accessed.
 #[turbo_tasks::function]
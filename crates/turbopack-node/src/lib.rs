@@ -37,6 +37,7 @@ pub mod render;
 pub mod route_matcher;
 pub mod source_map;
 pub mod transforms;
+pub mod type_check;
 
 #[turbo_tasks::function]
 async fn emit(
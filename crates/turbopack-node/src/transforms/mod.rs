@@ -1,3 +1,4 @@
 pub mod postcss;
+pub mod tailwind;
 mod util;
 pub mod webpack;
@@ -1,6 +1,7 @@
 use anyhow::{bail, Context, Result};
 use indexmap::indexmap;
 use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
 use turbo_tasks::{Completion, Completions, TryJoinIterExt, Value, Vc};
 use turbo_tasks_bytes::stream::SingleValue;
 use turbo_tasks_fs::{
@@ -69,18 +70,23 @@ fn postcss_configs() -> Vc<Vec<String>> {
 pub struct PostCssTransform {
     evaluate_context: Vc<Box<dyn AssetContext>>,
     execution_context: Vc<ExecutionContext>,
+    plugin_options: Vc<JsonValue>,
 }
 
 #[turbo_tasks::value_impl]
 impl PostCssTransform {
+    /// `plugin_options` is an object of options by plugin name, which take
+    /// precedence over the options in the PostCSS config.
     #[turbo_tasks::function]
     pub fn new(
         evaluate_context: Vc<Box<dyn AssetContext>>,
         execution_context: Vc<ExecutionContext>,
+        plugin_options: Vc<JsonValue>,
     ) -> Vc<Self> {
         PostCssTransform {
             evaluate_context,
             execution_context,
+            plugin_options,
         }
         .cell()
     }
@@ -94,6 +100,7 @@ impl SourceTransform for PostCssTransform {
             PostCssTransformedAsset {
                 evaluate_context: self.evaluate_context,
                 execution_context: self.execution_context,
+                plugin_options: self.plugin_options,
                 source,
             }
             .cell(),
@@ -105,6 +112,7 @@ impl SourceTransform for PostCssTransform {
 struct PostCssTransformedAsset {
     evaluate_context: Vc<Box<dyn AssetContext>>,
     execution_context: Vc<ExecutionContext>,
+    plugin_options: Vc<JsonValue>,
     source: Vc<Box<dyn Source>>,
 }
 
@@ -141,26 +149,30 @@ async fn extra_configs(
     asset_context: Vc<Box<dyn AssetContext>>,
     postcss_config_path: Vc<FileSystemPath>,
 ) -> Result<Vc<Completion>> {
-    let config_paths = [postcss_config_path
-        .parent()
-        .join("tailwind.config.js".to_string())];
-    let configs = config_paths
-        .into_iter()
-        .map(|path| async move {
-            Ok(
-                matches!(&*path.get_type().await?, FileSystemEntryType::File).then(|| {
-                    any_content_changed_of_module(asset_context.process(
-                        Vc::upcast(FileSource::new(path)),
-                        Value::new(ReferenceType::Internal(InnerAssets::empty())),
-                    ))
-                }),
-            )
-        })
-        .try_join()
-        .await?
-        .into_iter()
-        .flatten()
-        .collect::<Vec<_>>();
+    let config_dir = postcss_config_path.parent();
+    let configs = [
+        "tailwind.config.js",
+        "tailwind.config.cjs",
+        "tailwind.config.mjs",
+        "tailwind.config.ts",
+    ]
+    .into_iter()
+    .map(|config| config_dir.join(config.to_string()))
+    .map(|path| async move {
+        Ok(
+            matches!(&*path.get_type().await?, FileSystemEntryType::File).then(|| {
+                any_content_changed_of_module(asset_context.process(
+                    Vc::upcast(FileSource::new(path)),
+                    Value::new(ReferenceType::Internal(InnerAssets::empty())),
+                ))
+            }),
+        )
+    })
+    .try_join()
+    .await?
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
 
     Ok(Vc::<Completions>::cell(configs).completed())
 }
@@ -264,7 +276,11 @@ impl PostCssTransformedAsset {
             evaluate_context,
             chunking_context,
             None,
-            vec![Vc::cell(content.into()), Vc::cell(css_path.into())],
+            vec![
+                Vc::cell(content.into()),
+                Vc::cell(css_path.into()),
+                this.plugin_options,
+            ],
             extra_configs_changed,
             should_debug("postcss_transform"),
         )
@@ -283,7 +299,7 @@ impl PostCssTransformedAsset {
 
         // TODO handle SourceMap
         let file = File::from(processed_css.css);
-        let assets = emitted_assets_to_virtual_sources(processed_css.assets);
+        let assets = emitted_assets_to_virtual_sources(processed_css.assets)?;
         let content = AssetContent::File(FileContent::Content(file).cell()).cell();
         Ok(ProcessPostCssResult { content, assets }.cell())
     }
@@ -0,0 +1,371 @@
+use std::collections::BTreeSet;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{Completion, TryJoinIterExt, Value, Vc};
+use turbo_tasks_bytes::stream::SingleValue;
+use turbo_tasks_fs::{
+    glob::Glob, json::parse_json_with_source_context, DirectoryEntry, File, FileContent,
+    FileSystemPath,
+};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    context::AssetContext,
+    ident::AssetIdent,
+    issue::{Issue, IssueDescriptionExt, IssueExt, IssueSeverity},
+    module::Module,
+    reference_type::{InnerAssets, ReferenceType},
+    source::Source,
+    source_transform::SourceTransform,
+    virtual_source::VirtualSource,
+};
+
+use crate::{
+    debug::should_debug, embed_js::embed_file, evaluate::evaluate,
+    execution_context::ExecutionContext,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[turbo_tasks::value(transparent, serialization = "custom")]
+struct TailwindProcessingResult {
+    css: String,
+}
+
+/// A [SourceTransform] which generates the utility CSS of Tailwind CSS v4
+/// directly with its engine, without a PostCSS round-trip.
+///
+/// Candidate class names are extracted by turbopack from the files matching
+/// the `content` globs, each file on its own, so only changed files are
+/// scanned again. Added and removed files are picked up by watching the
+/// globs. The engine is loaded from the `tailwindcss` package, which can be
+/// aliased to another engine, e.g. one based on oxide.
+#[turbo_tasks::value]
+pub struct TailwindTransform {
+    evaluate_context: Vc<Box<dyn AssetContext>>,
+    execution_context: Vc<ExecutionContext>,
+    content: Vc<Vec<String>>,
+}
+
+#[turbo_tasks::value_impl]
+impl TailwindTransform {
+    /// `content` are globs relative to the project path which select the
+    /// files to extract candidates from, like Tailwind's `content` config.
+    #[turbo_tasks::function]
+    pub fn new(
+        evaluate_context: Vc<Box<dyn AssetContext>>,
+        execution_context: Vc<ExecutionContext>,
+        content: Vc<Vec<String>>,
+    ) -> Vc<Self> {
+        TailwindTransform {
+            evaluate_context,
+            execution_context,
+            content,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl SourceTransform for TailwindTransform {
+    #[turbo_tasks::function]
+    fn transform(&self, source: Vc<Box<dyn Source>>) -> Vc<Box<dyn Source>> {
+        Vc::upcast(
+            TailwindTransformedAsset {
+                evaluate_context: self.evaluate_context,
+                execution_context: self.execution_context,
+                content: self.content,
+                source,
+            }
+            .cell(),
+        )
+    }
+}
+
+#[turbo_tasks::value]
+struct TailwindTransformedAsset {
+    evaluate_context: Vc<Box<dyn AssetContext>>,
+    execution_context: Vc<ExecutionContext>,
+    content: Vc<Vec<String>>,
+    source: Vc<Box<dyn Source>>,
+}
+
+#[turbo_tasks::value_impl]
+impl Source for TailwindTransformedAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.source.ident()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for TailwindTransformedAsset {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        let this = self.await?;
+        Ok(self
+            .process()
+            .issue_file_path(this.source.ident().path(), "Tailwind CSS processing")
+            .await?)
+    }
+}
+
+#[turbo_tasks::function]
+fn tailwind_executor(
+    asset_context: Vc<Box<dyn AssetContext>>,
+    project_path: Vc<FileSystemPath>,
+) -> Vc<Box<dyn Module>> {
+    asset_context.process(
+        Vc::upcast(VirtualSource::new(
+            project_path.join("transform.ts".to_string()),
+            AssetContent::File(embed_file("transforms/tailwind.ts".to_string())).cell(),
+        )),
+        Value::new(ReferenceType::Internal(InnerAssets::empty())),
+    )
+}
+
+/// The layers of the `@tailwind` directives of the CSS, e.g. `utilities`.
+fn tailwind_directives(css: &str) -> impl Iterator<Item = &str> {
+    css.split("@tailwind").skip(1).map(|rest| {
+        let rest = rest.trim_start();
+        let end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+            .unwrap_or(rest.len());
+        &rest[..end]
+    })
+}
+
+/// Whether the CSS uses Tailwind CSS, i.e. imports the `tailwindcss`
+/// stylesheet or contains a `@tailwind utilities` directive.
+fn uses_tailwind(css: &str) -> bool {
+    css.contains("\"tailwindcss")
+        || css.contains("'tailwindcss")
+        || tailwind_directives(css).any(|layer| layer == "utilities")
+}
+
+#[turbo_tasks::value_impl]
+impl TailwindTransformedAsset {
+    #[turbo_tasks::function]
+    async fn process(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        let this = self.await?;
+        let ExecutionContext {
+            project_path,
+            chunking_context,
+            env,
+        } = *this.execution_context.await?;
+
+        let source_content = this.source.content();
+        let AssetContent::File(file) = *source_content.await? else {
+            bail!("Tailwind CSS transform only support transforming files");
+        };
+        let FileContent::Content(content) = &*file.await? else {
+            return Ok(AssetContent::File(FileContent::NotFound.cell()).cell());
+        };
+        let content = content.content().to_str()?;
+        let v3_directives = tailwind_directives(&content)
+            .filter(|&layer| layer != "utilities")
+            .map(|layer| format!("@tailwind {layer}"))
+            .collect::<Vec<_>>();
+        if !v3_directives.is_empty() {
+            TailwindV3DirectivesIssue {
+                source: this.source.ident().path(),
+                directives: v3_directives,
+            }
+            .cell()
+            .emit();
+            return Ok(source_content);
+        }
+        if !uses_tailwind(&content) {
+            return Ok(source_content);
+        }
+
+        let candidates = tailwind_candidates(project_path, this.content).await?;
+        let css_path = this.source.ident().path().await?;
+
+        let result = evaluate(
+            tailwind_executor(this.evaluate_context, project_path),
+            project_path,
+            env,
+            this.source.ident(),
+            this.evaluate_context,
+            chunking_context,
+            None,
+            vec![
+                Vc::cell(content.into()),
+                Vc::cell(css_path.path.as_str().into()),
+                Vc::cell(serde_json::to_value(&*candidates)?),
+            ],
+            // Stylesheets, plugins and configs which Tailwind CSS loads are
+            // reported as dependencies by the transform.
+            Completion::immutable(),
+            should_debug("tailwind_transform"),
+        )
+        .await?;
+
+        let SingleValue::Single(val) = result.try_into_single().await? else {
+            // An error happened, which has already been converted into an issue.
+            return Ok(AssetContent::File(FileContent::NotFound.cell()).cell());
+        };
+        let processed_css: TailwindProcessingResult = parse_json_with_source_context(val.to_str()?)
+            .context("Unable to deserializate response from Tailwind CSS transform")?;
+
+        Ok(AssetContent::File(FileContent::Content(File::from(processed_css.css)).cell()).cell())
+    }
+}
+
+/// The sorted candidate class names of all files matching the `content` globs.
+#[turbo_tasks::function]
+async fn tailwind_candidates(
+    project_path: Vc<FileSystemPath>,
+    content: Vc<Vec<String>>,
+) -> Result<Vc<Vec<String>>> {
+    let mut files = Vec::new();
+    let mut queue = content
+        .await?
+        .iter()
+        .map(|glob| project_path.read_glob(Glob::new(glob.clone()), false))
+        .collect::<Vec<_>>();
+    while let Some(result) = queue.pop() {
+        let result = result.await?;
+        for entry in result.results.values() {
+            if let DirectoryEntry::File(path) = entry {
+                files.push(*path);
+            }
+        }
+        queue.extend(result.inner.values().copied());
+    }
+    let candidates = files.into_iter().map(file_candidates).try_join().await?;
+    Ok(Vc::cell(
+        candidates
+            .iter()
+            .flat_map(|candidates| candidates.iter().cloned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect(),
+    ))
+}
+
+/// The candidate class names of a single file.
+#[turbo_tasks::function]
+async fn file_candidates(path: Vc<FileSystemPath>) -> Result<Vc<Vec<String>>> {
+    let FileContent::Content(file) = &*path.read().await? else {
+        return Ok(Vc::cell(Vec::new()));
+    };
+    let Ok(content) = file.content().to_str() else {
+        return Ok(Vc::cell(Vec::new()));
+    };
+    Ok(Vc::cell(
+        extract_candidates(&content)
+            .map(ToOwned::to_owned)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect(),
+    ))
+}
+
+/// Splits source code into strings which could be Tailwind CSS class names,
+/// e.g. `md:hover:bg-[#fff]/50`. Tailwind CSS ignores candidates which don't
+/// match any utility, so false positives are harmless.
+fn extract_candidates(content: &str) -> impl Iterator<Item = &str> {
+    content
+        .split(|c: char| {
+            c.is_whitespace() || matches!(c, '"' | '\'' | '`' | '<' | '>' | '{' | '}' | '=' | ';')
+        })
+        .map(|candidate| candidate.trim_end_matches([',', ':', '.']))
+        .filter(|candidate| {
+            candidate.len() < 256
+                && candidate.starts_with(|c: char| {
+                    c.is_ascii_lowercase() || matches!(c, '-' | '!' | '@' | '[' | '*')
+                })
+                && candidate.contains(|c: char| c.is_ascii_alphabetic())
+        })
+}
+
+/// Reports the `@tailwind` directives of Tailwind CSS v3, which Tailwind CSS v4
+/// doesn't support.
+#[turbo_tasks::value(shared)]
+struct TailwindV3DirectivesIssue {
+    source: Vc<FileSystemPath>,
+    directives: Vec<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for TailwindV3DirectivesIssue {
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.source
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell("Unsupported Tailwind CSS directives".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "{} are directives of Tailwind CSS v3. Replace them with `@import \"tailwindcss\";` \
+             to generate the styles with Tailwind CSS v4.",
+            self.directives.join(", ")
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("transform".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_candidates, tailwind_directives, uses_tailwind};
+
+    #[test]
+    fn extracts_candidates() {
+        assert_eq!(
+            extract_candidates(r#"<div className="md:hover:bg-[#fff]/50 p-4 {x}" data-a='!mt-2'>"#)
+                .collect::<Vec<_>>(),
+            [
+                "div",
+                "className",
+                "md:hover:bg-[#fff]/50",
+                "p-4",
+                "x",
+                "data-a",
+                "!mt-2"
+            ]
+        );
+    }
+
+    #[test]
+    fn trims_trailing_punctuation() {
+        assert_eq!(
+            extract_candidates("text-sm, underline. flex:").collect::<Vec<_>>(),
+            ["text-sm", "underline", "flex"]
+        );
+    }
+
+    #[test]
+    fn skips_non_candidates() {
+        assert_eq!(extract_candidates("42 Upper -- #id 3px").count(), 0);
+    }
+
+    #[test]
+    fn detects_tailwind() {
+        assert!(uses_tailwind("@import \"tailwindcss\";"));
+        assert!(uses_tailwind(
+            "@import 'tailwindcss/theme.css' layer(theme);"
+        ));
+        assert!(uses_tailwind("@tailwind utilities;"));
+        assert!(!uses_tailwind(".a { color: red; }"));
+        assert_eq!(
+            tailwind_directives("@tailwind base;\n@tailwind  components;@tailwind utilities")
+                .collect::<Vec<_>>(),
+            ["base", "components", "utilities"]
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use std::collections::BTreeMap;
 
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use turbo_tasks::Vc;
@@ -8,36 +10,87 @@ use turbopack_core::{
     asset::AssetContent, server_fs::ServerFileSystem, virtual_source::VirtualSource,
 };
 
+/// How the content of an [EmittedAsset] is encoded.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+pub enum EmittedAssetEncoding {
+    #[default]
+    #[serde(rename = "utf-8")]
+    Utf8,
+    /// Binary content, e.g. a `Buffer` passed to webpack's `emitFile`.
+    #[serde(rename = "base64")]
+    Base64,
+}
+
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct EmittedAsset {
     file: String,
     content: String,
+    #[serde(default)]
+    encoding: EmittedAssetEncoding,
     source_map: Option<JsonValue>,
 }
 
+impl EmittedAsset {
+    fn into_file(self) -> Result<File> {
+        Ok(match self.encoding {
+            EmittedAssetEncoding::Utf8 => File::from(self.content),
+            EmittedAssetEncoding::Base64 => File::from(
+                STANDARD
+                    .decode(&self.content)
+                    .with_context(|| format!("Invalid base64 content of emitted {}", self.file))?,
+            ),
+        })
+    }
+}
+
 pub fn emitted_assets_to_virtual_sources(
     assets: Option<Vec<EmittedAsset>>,
-) -> Vec<Vc<VirtualSource>> {
+) -> Result<Vec<Vc<VirtualSource>>> {
     assets
         .into_iter()
         .flatten()
-        .map(
-            |EmittedAsset {
-                 file,
-                 content,
-                 source_map,
-             }| (file, (content, source_map)),
-        )
+        .map(|asset| (asset.file.clone(), asset))
         // Sort it to make it determinstic
         .collect::<BTreeMap<_, _>>()
         .into_iter()
-        .map(|(file, (content, _source_map))| {
+        .map(|(file, asset)| {
             // TODO handle SourceMap
-            VirtualSource::new(
+            Ok(VirtualSource::new(
                 ServerFileSystem::new().root().join(file),
-                AssetContent::File(FileContent::Content(File::from(content)).cell()).cell(),
-            )
+                AssetContent::File(FileContent::Content(asset.into_file()?).cell()).cell(),
+            ))
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{EmittedAsset, EmittedAssetEncoding};
+
+    #[test]
+    fn encodings() {
+        let asset: EmittedAsset =
+            serde_json::from_str(r#"{ "file": "a.txt", "content": "a" }"#).unwrap();
+        assert_eq!(asset.encoding, EmittedAssetEncoding::Utf8);
+        assert_eq!(
+            asset.into_file().unwrap().content().to_bytes().unwrap(),
+            &b"a"[..]
+        );
+
+        let asset: EmittedAsset = serde_json::from_str(
+            r#"{ "file": "a.png", "content": "iVBORw0KGgo=", "encoding": "base64" }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            asset.into_file().unwrap().content().to_bytes().unwrap(),
+            &b"\x89PNG\r\n\x1a\n"[..]
+        );
+
+        let asset: EmittedAsset = serde_json::from_str(
+            r#"{ "file": "a.png", "content": "not base64!", "encoding": "base64" }"#,
+        )
+        .unwrap();
+        assert!(asset.into_file().is_err());
+    }
+}
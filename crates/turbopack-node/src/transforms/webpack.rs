@@ -3,17 +3,18 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use turbo_tasks::{trace::TraceRawVcs, Completion, Value, Vc};
 use turbo_tasks_bytes::stream::SingleValue;
-use turbo_tasks_fs::{json::parse_json_with_source_context, File, FileContent};
+use turbo_tasks_fs::{json::parse_json_with_source_context, File, FileContent, FileSystemPath};
 use turbopack_core::{
     asset::{Asset, AssetContent},
     context::AssetContext,
     file_source::FileSource,
     ident::AssetIdent,
     module::Module,
+    output::{OutputAsset, OutputAssets},
     reference_type::{InnerAssets, ReferenceType},
-    source::Source,
+    source::{EmittingSource, Source},
     source_transform::SourceTransform,
-    virtual_source::VirtualSource,
+    virtual_output::VirtualOutputAsset,
 };
 
 use super::util::{emitted_assets_to_virtual_sources, EmittedAsset};
@@ -39,16 +40,87 @@ pub struct WebpackLoaderItem {
     pub options: serde_json::Map<String, serde_json::Value>,
 }
 
+impl WebpackLoaderItem {
+    /// Parses a loader of an inline request, e.g. `css-loader?modules` or
+    /// `css-loader?{"modules":true}`.
+    fn parse_inline(loader: &str) -> Result<Self> {
+        let Some((loader, query)) = loader.split_once('?') else {
+            return Ok(WebpackLoaderItem {
+                loader: loader.to_string(),
+                options: Default::default(),
+            });
+        };
+        let options = if query.starts_with('{') {
+            serde_json::from_str(query)
+                .with_context(|| format!("Invalid options of inline loader {loader}"))?
+        } else {
+            url::form_urlencoded::parse(query.as_bytes())
+                .map(|(key, value)| (key.into_owned(), value.into_owned().into()))
+                .collect()
+        };
+        Ok(WebpackLoaderItem {
+            loader: loader.to_string(),
+            options,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 #[turbo_tasks::value(shared, transparent)]
 pub struct WebpackLoaderItems(pub Vec<WebpackLoaderItem>);
 
+/// The loaders of an inline request like `!style-loader!css-loader!./a.css`.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct InlineLoaders {
+    pub loaders: Vec<WebpackLoaderItem>,
+    /// A `!`, `-!` or `!!` prefix of the request disables the loaders
+    /// configured in rules. There are only normal loaders in rules, so all of
+    /// the prefixes have the same effect.
+    pub disable_configured_loaders: bool,
+    /// The request of the resource, without the loaders.
+    pub request: String,
+}
+
+impl InlineLoaders {
+    /// Splits the loaders off a request. Returns `None` for requests without
+    /// loaders.
+    pub fn parse(request: &str) -> Result<Option<InlineLoaders>> {
+        if !request.contains('!') {
+            return Ok(None);
+        }
+        let (disable_configured_loaders, request) = match ["-!", "!!", "!"]
+            .into_iter()
+            .find_map(|prefix| request.strip_prefix(prefix))
+        {
+            Some(request) => (true, request),
+            None => (false, request),
+        };
+        let (loaders, request) = request.rsplit_once('!').unwrap_or(("", request));
+        let loaders = loaders
+            .split('!')
+            .filter(|loader| !loader.is_empty())
+            .map(WebpackLoaderItem::parse_inline)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Some(InlineLoaders {
+            loaders,
+            disable_configured_loaders,
+            request: request.to_string(),
+        }))
+    }
+}
+
 #[turbo_tasks::value]
 pub struct WebpackLoaders {
     evaluate_context: Vc<Box<dyn AssetContext>>,
     execution_context: Vc<ExecutionContext>,
     loaders: Vc<WebpackLoaderItems>,
     rename_as: Option<String>,
+    /// The directory of the files which loaders emit with `this.emitFile`.
+    emit_path: Option<Vc<FileSystemPath>>,
+    /// Whether the loaders are specified inline in a request, see
+    /// [WebpackLoaders::inline].
+    inline: bool,
 }
 
 #[turbo_tasks::value_impl]
@@ -59,12 +131,37 @@ impl WebpackLoaders {
         execution_context: Vc<ExecutionContext>,
         loaders: Vc<WebpackLoaderItems>,
         rename_as: Option<String>,
+        emit_path: Option<Vc<FileSystemPath>>,
     ) -> Vc<Self> {
         WebpackLoaders {
             evaluate_context,
             execution_context,
             loaders,
             rename_as,
+            emit_path,
+            inline: false,
+        }
+        .cell()
+    }
+
+    /// Creates the transform for the loaders of an inline request, see
+    /// [InlineLoaders]. Like in webpack, the loaders are expected to return
+    /// ecmascript code. The transformed sources are distinct for each list of
+    /// loaders, as the same resource can be requested with different loaders.
+    #[turbo_tasks::function]
+    pub fn inline(
+        evaluate_context: Vc<Box<dyn AssetContext>>,
+        execution_context: Vc<ExecutionContext>,
+        loaders: Vc<WebpackLoaderItems>,
+        emit_path: Option<Vc<FileSystemPath>>,
+    ) -> Vc<Self> {
+        WebpackLoaders {
+            evaluate_context,
+            execution_context,
+            loaders,
+            rename_as: Some("*.js".to_string()),
+            emit_path,
+            inline: true,
         }
         .cell()
     }
@@ -94,13 +191,29 @@ struct WebpackLoadersProcessedAsset {
 impl Source for WebpackLoadersProcessedAsset {
     #[turbo_tasks::function]
     async fn ident(&self) -> Result<Vc<AssetIdent>> {
-        Ok(
-            if let Some(rename_as) = self.transform.await?.rename_as.as_deref() {
-                self.source.ident().rename_as(rename_as.to_string())
-            } else {
-                self.source.ident()
-            },
-        )
+        let transform = self.transform.await?;
+        let mut ident = if let Some(rename_as) = transform.rename_as.as_deref() {
+            self.source.ident().rename_as(rename_as.to_string())
+        } else {
+            self.source.ident()
+        };
+        if transform.inline {
+            let loaders = transform
+                .loaders
+                .await?
+                .iter()
+                .map(|item| {
+                    if item.options.is_empty() {
+                        item.loader.clone()
+                    } else {
+                        format!("{}?{}", item.loader, json!(item.options))
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("!");
+            ident = ident.with_modifier(Vc::cell(format!("webpack loaders {loaders}")));
+        }
+        Ok(ident)
     }
 }
 
@@ -112,10 +225,19 @@ impl Asset for WebpackLoadersProcessedAsset {
     }
 }
 
+#[turbo_tasks::value_impl]
+impl EmittingSource for WebpackLoadersProcessedAsset {
+    #[turbo_tasks::function]
+    async fn emitted_assets(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        Ok(self.process().await?.assets)
+    }
+}
+
 #[turbo_tasks::value]
 struct ProcessWebpackLoadersResult {
     content: Vc<AssetContent>,
-    assets: Vec<Vc<VirtualSource>>,
+    /// The files emitted by the loaders, in the emit path.
+    assets: Vc<OutputAssets>,
 }
 
 #[turbo_tasks::function]
@@ -147,7 +269,7 @@ impl WebpackLoadersProcessedAsset {
         let FileContent::Content(content) = &*file.await? else {
             return Ok(ProcessWebpackLoadersResult {
                 content: AssetContent::File(FileContent::NotFound.cell()).cell(),
-                assets: Vec::new(),
+                assets: OutputAssets::empty(),
             }
             .cell());
         };
@@ -157,6 +279,8 @@ impl WebpackLoadersProcessedAsset {
         let webpack_loaders_executor = webpack_loaders_executor(evaluate_context);
         let resource_fs_path = this.source.ident().path().await?;
         let resource_path = resource_fs_path.path.as_str();
+        // Pitching loaders often depend on the query of the resource
+        let resource_query = this.source.ident().query().await?;
         let loaders = transform.loaders.await?;
         let config_value = evaluate(
             webpack_loaders_executor,
@@ -169,6 +293,7 @@ impl WebpackLoadersProcessedAsset {
             vec![
                 Vc::cell(content.into()),
                 Vc::cell(resource_path.into()),
+                Vc::cell(resource_query.as_str().into()),
                 Vc::cell(json!(*loaders)),
             ],
             Completion::immutable(),
@@ -180,7 +305,7 @@ impl WebpackLoadersProcessedAsset {
             // An error happened, which has already been converted into an issue.
             return Ok(ProcessWebpackLoadersResult {
                 content: AssetContent::File(FileContent::NotFound.cell()).cell(),
-                assets: Vec::new(),
+                assets: OutputAssets::empty(),
             }
             .cell());
         };
@@ -191,8 +316,105 @@ impl WebpackLoadersProcessedAsset {
 
         // TODO handle SourceMap
         let file = File::from(processed.source);
-        let assets = emitted_assets_to_virtual_sources(processed.assets);
+        let mut assets = Vec::new();
+        if let Some(emit_path) = transform.emit_path {
+            for source in emitted_assets_to_virtual_sources(processed.assets)? {
+                let path = source.ident().path().await?;
+                let Some(target) = *emit_path.try_join(path.path.clone()).await? else {
+                    bail!(
+                        "Webpack loaders can't emit {} outside of the output directory",
+                        path.path
+                    );
+                };
+                assets.push(Vc::upcast::<Box<dyn OutputAsset>>(VirtualOutputAsset::new(
+                    target,
+                    source.content(),
+                )));
+            }
+        }
         let content = AssetContent::File(FileContent::Content(file).cell()).cell();
-        Ok(ProcessWebpackLoadersResult { content, assets }.cell())
+        Ok(ProcessWebpackLoadersResult {
+            content,
+            assets: Vc::cell(assets),
+        }
+        .cell())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{InlineLoaders, WebpackLoaderItem};
+
+    fn loader(loader: &str, options: serde_json::Value) -> WebpackLoaderItem {
+        let serde_json::Value::Object(options) = options else {
+            panic!("options must be an object");
+        };
+        WebpackLoaderItem {
+            loader: loader.to_string(),
+            options,
+        }
+    }
+
+    #[test]
+    fn requests_without_loaders() {
+        assert!(InlineLoaders::parse("./a.css").unwrap().is_none());
+        assert!(InlineLoaders::parse("@scope/pkg/a.js?query")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn loaders_and_request() {
+        let inline = InlineLoaders::parse("style-loader!css-loader!./a.css?inline")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            inline.loaders,
+            [
+                loader("style-loader", json!({})),
+                loader("css-loader", json!({}))
+            ]
+        );
+        assert!(!inline.disable_configured_loaders);
+        assert_eq!(inline.request, "./a.css?inline");
+    }
+
+    #[test]
+    fn prefixes_disable_configured_loaders() {
+        for prefix in ["!", "-!", "!!"] {
+            let inline = InlineLoaders::parse(&format!("{prefix}raw-loader!./a.txt"))
+                .unwrap()
+                .unwrap();
+            assert_eq!(inline.loaders, [loader("raw-loader", json!({}))]);
+            assert!(inline.disable_configured_loaders);
+            assert_eq!(inline.request, "./a.txt");
+        }
+
+        // Only disables the configured loaders.
+        let inline = InlineLoaders::parse("!./a.txt").unwrap().unwrap();
+        assert!(inline.loaders.is_empty());
+        assert!(inline.disable_configured_loaders);
+        assert_eq!(inline.request, "./a.txt");
+    }
+
+    #[test]
+    fn loader_options() {
+        let inline = InlineLoaders::parse(
+            r#"css-loader?modules=true&url=false!sass-loader?{"sourceMap":true}!./a.scss"#,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            inline.loaders,
+            [
+                loader("css-loader", json!({ "modules": "true", "url": "false" })),
+                loader("sass-loader", json!({ "sourceMap": true })),
+            ]
+        );
+        assert_eq!(inline.request, "./a.scss");
+
+        assert!(InlineLoaders::parse("sass-loader?{invalid}!./a.scss").is_err());
     }
 }
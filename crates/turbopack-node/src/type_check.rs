@@ -0,0 +1,184 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{Completion, Value, Vc};
+use turbo_tasks_bytes::stream::SingleValue;
+use turbo_tasks_fs::{json::parse_json_with_source_context, FileSystemPath};
+use turbopack_core::{
+    context::AssetContext,
+    file_source::FileSource,
+    ident::AssetIdent,
+    issue::{Issue, IssueExt, IssueSeverity, IssueSource, OptionIssueSource},
+    module::Module,
+    reference_type::{InnerAssets, ReferenceType},
+    source_pos::SourcePos,
+};
+
+use crate::{
+    debug::should_debug, embed_js::embed_file_path, evaluate::evaluate,
+    execution_context::ExecutionContext,
+};
+
+/// Configures [type_check].
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug, Default)]
+pub struct TypeCheckOptions {
+    /// The path of the `tsconfig.json` to use, relative to the project path.
+    /// Defaults to the nearest `tsconfig.json` in the project path or its
+    /// parents.
+    pub tsconfig: Option<String>,
+}
+
+/// A diagnostic reported by the TypeScript compiler. Lines and columns are
+/// zero-based.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct TypeCheckDiagnostic {
+    file: Option<String>,
+    line: Option<usize>,
+    column: Option<usize>,
+    end_line: Option<usize>,
+    end_column: Option<usize>,
+    code: u32,
+    message: String,
+    category: String,
+}
+
+#[turbo_tasks::function]
+fn type_check_executor(evaluate_context: Vc<Box<dyn AssetContext>>) -> Vc<Box<dyn Module>> {
+    evaluate_context.process(
+        Vc::upcast(FileSource::new(embed_file_path(
+            "type-check.ts".to_string(),
+        ))),
+        Value::new(ReferenceType::Internal(InnerAssets::empty())),
+    )
+}
+
+/// Type checks `files`, which are relative to the project path, like
+/// `tsc --noEmit` and emits the diagnostics as issues.
+///
+/// The `typescript` package is resolved with the `@vercel/turbopack/typescript`
+/// request from `evaluate_context`. The check is repeated when one of the
+/// files of the TypeScript program or the `tsconfig.json` changes.
+#[turbo_tasks::function]
+pub async fn type_check(
+    evaluate_context: Vc<Box<dyn AssetContext>>,
+    execution_context: Vc<ExecutionContext>,
+    files: Vc<Vec<String>>,
+    options: Vc<TypeCheckOptions>,
+) -> Result<Vc<Completion>> {
+    let ExecutionContext {
+        project_path,
+        chunking_context,
+        env,
+    } = *execution_context.await?;
+    let files = files.await?;
+    if files.is_empty() {
+        return Ok(Completion::immutable());
+    }
+    let options = options.await?;
+
+    let result = evaluate(
+        type_check_executor(evaluate_context),
+        project_path,
+        env,
+        AssetIdent::from_path(project_path),
+        evaluate_context,
+        chunking_context,
+        None,
+        vec![
+            Vc::cell(files.clone_value().into()),
+            Vc::cell(options.tsconfig.clone().into()),
+        ],
+        Completion::immutable(),
+        should_debug("type_check"),
+    )
+    .await?;
+
+    let SingleValue::Single(val) = result.try_into_single().await? else {
+        // An error happened, which has already been converted into an issue.
+        return Ok(Completion::new());
+    };
+    let diagnostics: Vec<TypeCheckDiagnostic> = parse_json_with_source_context(val.to_str()?)
+        .context("Unable to deserialize response from the type checker")?;
+
+    for diagnostic in diagnostics {
+        let file_path = match &diagnostic.file {
+            Some(file) => project_path.join(file.clone()),
+            None => project_path,
+        };
+        let source = match (diagnostic.line, diagnostic.column) {
+            (Some(line), Some(column)) if diagnostic.file.is_some() => Some(
+                IssueSource {
+                    source: Vc::upcast(FileSource::new(file_path)),
+                    start: SourcePos { line, column },
+                    end: SourcePos {
+                        line: diagnostic.end_line.unwrap_or(line),
+                        column: diagnostic.end_column.unwrap_or(column),
+                    },
+                }
+                .cell(),
+            ),
+            _ => None,
+        };
+        let severity = match diagnostic.category.as_str() {
+            "error" => IssueSeverity::Error,
+            "warning" => IssueSeverity::Warning,
+            "suggestion" => IssueSeverity::Suggestion,
+            _ => IssueSeverity::Info,
+        };
+        TypeCheckIssue {
+            file_path,
+            source,
+            code: diagnostic.code,
+            message: diagnostic.message,
+            severity: severity.cell(),
+        }
+        .cell()
+        .emit();
+    }
+
+    Ok(Completion::new())
+}
+
+#[turbo_tasks::value(shared)]
+struct TypeCheckIssue {
+    file_path: Vc<FileSystemPath>,
+    source: Option<Vc<IssueSource>>,
+    code: u32,
+    message: String,
+    severity: Vc<IssueSeverity>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for TypeCheckIssue {
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        let summary = self.message.lines().next().unwrap_or_default();
+        Vc::cell(format!("TS{}: {}", self.code, summary))
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(self.message.clone())
+    }
+
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        self.severity
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("type check".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> Vc<OptionIssueSource> {
+        Vc::cell(self.source)
+    }
+}
@@ -0,0 +1,78 @@
+//! Directories whose contents are copied verbatim into the output, e.g. a
+//! `public/` directory.
+//!
+//! The directory is read through turbo-tasks-fs, so adding, changing or
+//! removing files only invalidates the affected directories when the file
+//! system is watched.
+
+use anyhow::Result;
+use turbo_tasks::{TryJoinIterExt, Vc};
+use turbo_tasks_fs::{DirectoryContent, DirectoryEntry, FileSystemPath};
+use turbopack_core::{file_source::FileSource, output::OutputAssets};
+
+use crate::fixed::FixedStaticAsset;
+
+/// A directory whose files are emitted at the same relative paths below
+/// `output_dir`. Files are not content hashed.
+#[turbo_tasks::value]
+pub struct StaticDirectory {
+    source_dir: Vc<FileSystemPath>,
+    output_dir: Vc<FileSystemPath>,
+}
+
+#[turbo_tasks::value_impl]
+impl StaticDirectory {
+    #[turbo_tasks::function]
+    pub fn new(source_dir: Vc<FileSystemPath>, output_dir: Vc<FileSystemPath>) -> Vc<Self> {
+        StaticDirectory {
+            source_dir,
+            output_dir,
+        }
+        .cell()
+    }
+
+    /// All files of the directory as [FixedStaticAsset]s, including files in
+    /// subdirectories. A missing directory has no files.
+    #[turbo_tasks::function]
+    pub fn output_assets(&self) -> Vc<OutputAssets> {
+        directory_output_assets(self.source_dir, self.output_dir)
+    }
+}
+
+/// Reads a single directory, so changes only invalidate the directory they
+/// happened in.
+#[turbo_tasks::function]
+async fn directory_output_assets(
+    source_dir: Vc<FileSystemPath>,
+    output_dir: Vc<FileSystemPath>,
+) -> Result<Vc<OutputAssets>> {
+    let DirectoryContent::Entries(entries) = &*source_dir.read_dir().await? else {
+        return Ok(OutputAssets::empty());
+    };
+
+    let mut assets = Vec::new();
+    let mut directories = Vec::new();
+    for (name, entry) in entries.iter() {
+        match entry {
+            DirectoryEntry::File(path) | DirectoryEntry::Symlink(path) => {
+                assets.push(Vc::upcast(FixedStaticAsset::new(
+                    output_dir.join(name.clone()),
+                    Vc::upcast(FileSource::new(*path)),
+                )));
+            }
+            DirectoryEntry::Directory(path) => {
+                directories.push(directory_output_assets(
+                    *path,
+                    output_dir.join(name.clone()),
+                ));
+            }
+            DirectoryEntry::Other(_) | DirectoryEntry::Error => {}
+        }
+    }
+
+    for directory_assets in directories.into_iter().try_join().await? {
+        assets.extend(directory_assets.iter().copied());
+    }
+
+    Ok(Vc::cell(assets))
+}
@@ -0,0 +1,224 @@
+//! Fonts referenced from CSS `@font-face` rules.
+//!
+//! Small fonts can be inlined as `data:` urls. Fonts which are emitted as
+//! separate files can be marked for preloading, so consumers can generate
+//! `<link rel="preload">` tags from
+//! [turbopack_core::chunk::preload::chunk_group_preloads].
+
+use anyhow::Result;
+use base64::{display::Base64Display, engine::general_purpose::STANDARD};
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileContent;
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{
+        preload::{PreloadInfo, PreloadableAsset},
+        ChunkItem, ChunkableModule, ChunkingContext,
+    },
+    context::AssetContext,
+    ident::AssetIdent,
+    module::Module,
+    output::{OutputAsset, OutputAssetMetadata, OutputAssets},
+    source::Source,
+};
+use turbopack_css::embed::{CssEmbed, CssEmbeddable};
+
+use crate::{StaticAsset, StaticModuleAsset};
+
+#[turbo_tasks::function]
+fn modifier() -> Vc<String> {
+    Vc::cell("font".to_string())
+}
+
+/// Returns the mime type of a font file based on its extension.
+fn font_mime_type(extension: Option<&str>) -> Option<&'static str> {
+    Some(match extension? {
+        "woff2" => "font/woff2",
+        "woff" => "font/woff",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        _ => return None,
+    })
+}
+
+/// A font which is referenced from CSS. It is either inlined as a `data:` url
+/// when it is at most `inline_limit` bytes large, or emitted as a static asset.
+#[turbo_tasks::value]
+#[derive(Clone)]
+pub struct FontModuleAsset {
+    pub source: Vc<Box<dyn Source>>,
+    pub asset_context: Vc<Box<dyn AssetContext>>,
+    pub inline_limit: Option<u64>,
+    pub preload: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl FontModuleAsset {
+    #[turbo_tasks::function]
+    pub fn new(
+        source: Vc<Box<dyn Source>>,
+        asset_context: Vc<Box<dyn AssetContext>>,
+        inline_limit: Option<u64>,
+        preload: bool,
+    ) -> Vc<Self> {
+        Self::cell(FontModuleAsset {
+            source,
+            asset_context,
+            inline_limit,
+            preload,
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn static_module(self: Vc<Self>) -> Result<Vc<StaticModuleAsset>> {
+        let this = self.await?;
+        Ok(StaticModuleAsset::new(this.source, this.asset_context))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Module for FontModuleAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.source
+            .ident()
+            .with_modifier(modifier())
+            .with_layer(self.asset_context.layer())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for FontModuleAsset {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        self.source.content()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModule for FontModuleAsset {
+    #[turbo_tasks::function]
+    fn as_chunk_item(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Vc<Box<dyn ChunkItem>> {
+        // When imported from ES modules, fonts export their url like any other
+        // static asset.
+        self.static_module().as_chunk_item(chunking_context)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CssEmbeddable for FontModuleAsset {
+    #[turbo_tasks::function]
+    async fn as_css_embed(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<Vc<Box<dyn CssEmbed>>> {
+        let this = self.await?;
+        let static_asset = StaticAsset::cell(StaticAsset {
+            chunking_context,
+            source: this.source,
+        });
+        let asset = if this.preload {
+            Vc::upcast(FontAsset::cell(FontAsset {
+                static_asset,
+                source: this.source,
+            }))
+        } else {
+            Vc::upcast(static_asset)
+        };
+        Ok(Vc::upcast(FontCssEmbed::cell(FontCssEmbed {
+            asset,
+            source: this.source,
+            inline_limit: this.inline_limit,
+        })))
+    }
+}
+
+/// An emitted font which is marked for preloading.
+#[turbo_tasks::value]
+struct FontAsset {
+    static_asset: Vc<StaticAsset>,
+    source: Vc<Box<dyn Source>>,
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for FontAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.static_asset.ident()
+    }
+
+    #[turbo_tasks::function]
+    fn metadata(&self) -> Vc<OutputAssetMetadata> {
+        self.static_asset.metadata()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for FontAsset {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        self.source.content()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl PreloadableAsset for FontAsset {
+    #[turbo_tasks::function]
+    async fn preload_info(&self) -> Result<Vc<PreloadInfo>> {
+        let path = self.source.ident().path().await?;
+        Ok(PreloadInfo {
+            as_type: "font".to_string(),
+            mime_type: font_mime_type(path.extension_ref()).map(ToString::to_string),
+            crossorigin: true,
+        }
+        .cell())
+    }
+}
+
+#[turbo_tasks::value]
+struct FontCssEmbed {
+    asset: Vc<Box<dyn OutputAsset>>,
+    source: Vc<Box<dyn Source>>,
+    inline_limit: Option<u64>,
+}
+
+#[turbo_tasks::value_impl]
+impl CssEmbed for FontCssEmbed {
+    #[turbo_tasks::function]
+    async fn references(&self) -> Result<Vc<OutputAssets>> {
+        Ok(Vc::cell(vec![self.asset]))
+    }
+
+    #[turbo_tasks::function]
+    fn embeddable_asset(&self) -> Vc<Box<dyn OutputAsset>> {
+        self.asset
+    }
+
+    #[turbo_tasks::function]
+    async fn inline_url(&self) -> Result<Vc<Option<String>>> {
+        let Some(inline_limit) = self.inline_limit else {
+            return Ok(Vc::cell(None));
+        };
+        let path = self.source.ident().path().await?;
+        let Some(mime) = font_mime_type(path.extension_ref()) else {
+            return Ok(Vc::cell(None));
+        };
+        let AssetContent::File(file) = &*self.source.content().await? else {
+            return Ok(Vc::cell(None));
+        };
+        let FileContent::Content(file) = &*file.await? else {
+            return Ok(Vc::cell(None));
+        };
+        let content = file.content();
+        if content.len() as u64 > inline_limit {
+            return Ok(Vc::cell(None));
+        }
+        Ok(Vc::cell(Some(format!(
+            "data:{mime};base64,{}",
+            Base64Display::new(&content.to_bytes()?, &STANDARD)
+        ))))
+    }
+}
@@ -0,0 +1,400 @@
+//! Image optimization for static image assets.
+//!
+//! Images imported with a resource query like `?w=640&format=webp` are resized
+//! and converted at build time. A comma separated list of widths
+//! (`?w=640,1280`) emits one output asset per width.
+//!
+//! The module exports the url of the (largest) processed image together with
+//! its dimensions, a `srcSet` and a blur placeholder.
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use turbo_tasks::{ValueToString, Vc};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{ChunkItem, ChunkType, ChunkableModule, ChunkingContext},
+    context::AssetContext,
+    ident::AssetIdent,
+    module::Module,
+    output::{OutputAsset, OutputAssetMetadata},
+    reference::{ModuleReference, ModuleReferences, SingleOutputAssetReference},
+    source::Source,
+};
+use turbopack_ecmascript::{
+    chunk::{
+        asset_url_code, EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkPlaceable,
+        EcmascriptChunkType, EcmascriptChunkingContext, EcmascriptExports,
+    },
+    utils::StringifyJs,
+};
+use turbopack_image::process::{get_meta_data, optimize_as, BlurPlaceholderOptions};
+
+#[turbo_tasks::function]
+fn modifier() -> Vc<String> {
+    Vc::cell("processed image".to_string())
+}
+
+const DEFAULT_QUALITY: u8 = 75;
+
+/// Processing options parsed from the resource query of an image.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Default, Clone)]
+pub struct ImageQuery {
+    /// The widths to resize the image to. Keeps the original size when empty.
+    pub widths: Vec<u32>,
+    /// The extension of the format to convert the image to, e.g. `webp`.
+    pub format: Option<String>,
+    pub quality: Option<u8>,
+}
+
+impl ImageQuery {
+    /// Parses a resource query like `?w=640,1280&format=webp&q=80`. Unknown
+    /// parameters are ignored.
+    pub fn parse(query: &str) -> Result<Self> {
+        let mut image_query = ImageQuery::default();
+        for param in query.trim_start_matches('?').split('&') {
+            let (key, value) = param.split_once('=').unwrap_or((param, ""));
+            match key {
+                "w" | "width" => {
+                    for width in value.split(',') {
+                        image_query.widths.push(
+                            width
+                                .parse()
+                                .with_context(|| format!("invalid image width {width:?}"))?,
+                        );
+                    }
+                }
+                "format" => match value {
+                    "avif" | "gif" | "jpeg" | "png" | "webp" => {
+                        image_query.format = Some(value.to_string())
+                    }
+                    "jpg" => image_query.format = Some("jpeg".to_string()),
+                    _ => bail!("unsupported image format {value:?}"),
+                },
+                "q" | "quality" => {
+                    image_query.quality = Some(
+                        value
+                            .parse()
+                            .with_context(|| format!("invalid image quality {value:?}"))?,
+                    )
+                }
+                _ => {}
+            }
+        }
+        Ok(image_query)
+    }
+}
+
+/// A static image module that resizes and converts the image according to its
+/// resource query.
+#[turbo_tasks::value]
+#[derive(Clone)]
+pub struct ProcessedImageModule {
+    pub source: Vc<Box<dyn Source>>,
+    pub asset_context: Vc<Box<dyn AssetContext>>,
+    pub blur_placeholder: Option<Vc<BlurPlaceholderOptions>>,
+}
+
+#[turbo_tasks::value_impl]
+impl ProcessedImageModule {
+    #[turbo_tasks::function]
+    pub fn new(
+        source: Vc<Box<dyn Source>>,
+        asset_context: Vc<Box<dyn AssetContext>>,
+        blur_placeholder: Option<Vc<BlurPlaceholderOptions>>,
+    ) -> Vc<Self> {
+        Self::cell(ProcessedImageModule {
+            source,
+            asset_context,
+            blur_placeholder,
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn query(&self) -> Result<Vc<ImageQuery>> {
+        let query = self.source.ident().query().await?;
+        Ok(ImageQuery::parse(&query)?.cell())
+    }
+
+    /// Returns one processed image per requested width.
+    #[turbo_tasks::function]
+    async fn processed_images(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<Vc<ProcessedImages>> {
+        let source = self.await?.source;
+        let query = self.query().await?;
+        let quality = query.quality.unwrap_or(DEFAULT_QUALITY);
+        let widths = if query.widths.is_empty() {
+            vec![None]
+        } else {
+            query.widths.iter().copied().map(Some).collect()
+        };
+        Ok(Vc::cell(
+            widths
+                .into_iter()
+                .map(|width| {
+                    ProcessedImageAsset {
+                        chunking_context,
+                        source,
+                        width,
+                        format: query.format.clone(),
+                        quality,
+                    }
+                    .cell()
+                })
+                .collect(),
+        ))
+    }
+}
+
+#[turbo_tasks::value(transparent)]
+struct ProcessedImages(Vec<Vc<ProcessedImageAsset>>);
+
+#[turbo_tasks::value_impl]
+impl Module for ProcessedImageModule {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.source
+            .ident()
+            .with_modifier(modifier())
+            .with_layer(self.asset_context.layer())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for ProcessedImageModule {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        self.source.content()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModule for ProcessedImageModule {
+    #[turbo_tasks::function]
+    async fn as_chunk_item(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<Vc<Box<dyn ChunkItem>>> {
+        let chunking_context =
+            Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkingContext>>(chunking_context)
+                .await?
+                .context(
+                    "chunking context must impl EcmascriptChunkingContext to use \
+                     ProcessedImageModule",
+                )?;
+        Ok(Vc::upcast(
+            ProcessedImageChunkItem {
+                module: self,
+                chunking_context,
+                images: self.processed_images(Vc::upcast(chunking_context)),
+            }
+            .cell(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for ProcessedImageModule {
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> Vc<EcmascriptExports> {
+        EcmascriptExports::Value.into()
+    }
+}
+
+/// An image resized to `width` and encoded as `format`.
+#[turbo_tasks::value]
+struct ProcessedImageAsset {
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    source: Vc<Box<dyn Source>>,
+    width: Option<u32>,
+    format: Option<String>,
+    quality: u8,
+}
+
+#[turbo_tasks::value_impl]
+impl ProcessedImageAsset {
+    /// The ident of the source with the extension changed to the target
+    /// format.
+    #[turbo_tasks::function]
+    async fn output_ident(&self) -> Result<Vc<AssetIdent>> {
+        let ident = self.source.ident();
+        let Some(format) = &self.format else {
+            return Ok(ident);
+        };
+        let path = ident.path();
+        let path_value = path.await?;
+        let file_name = path_value.file_name();
+        let file_stem = match path_value.extension_ref() {
+            Some(ext) => &file_name[..file_name.len() - ext.len() - 1],
+            None => file_name,
+        };
+        Ok(AssetIdent::from_path(
+            path.parent().join(format!("{file_stem}.{format}")),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for ProcessedImageAsset {
+    #[turbo_tasks::function]
+    async fn ident(self: Vc<Self>) -> Result<Vc<AssetIdent>> {
+        let this = self.await?;
+        let content_hash = this.chunking_context.content_hash(self.content()).await?;
+        let asset_path = this
+            .chunking_context
+            .asset_path(content_hash.clone_value(), self.output_ident());
+        Ok(AssetIdent::from_path(asset_path))
+    }
+
+    #[turbo_tasks::function]
+    fn metadata(self: Vc<Self>) -> Vc<OutputAssetMetadata> {
+        // The path contains a content hash.
+        OutputAssetMetadata::immutable(self.ident())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for ProcessedImageAsset {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<AssetContent>> {
+        let ident = self.source.ident();
+        let content = self.source.content().file_content();
+        Ok(AssetContent::file(optimize_as(
+            ident,
+            content,
+            self.width.unwrap_or(u32::MAX),
+            u32::MAX,
+            self.quality,
+            self.format.clone(),
+        )))
+    }
+}
+
+#[turbo_tasks::value]
+struct ProcessedImageChunkItem {
+    module: Vc<ProcessedImageModule>,
+    chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    images: Vc<ProcessedImages>,
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for ProcessedImageChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> Vc<AssetIdent> {
+        self.module.ident()
+    }
+
+    #[turbo_tasks::function]
+    async fn references(&self) -> Result<Vc<ModuleReferences>> {
+        let mut references: Vec<Vc<Box<dyn ModuleReference>>> = Vec::new();
+        for &image in self.images.await?.iter() {
+            references.push(Vc::upcast(SingleOutputAssetReference::new(
+                Vc::upcast(image),
+                Vc::cell(format!(
+                    "processed image(url) {}",
+                    image.ident().to_string().await?
+                )),
+            )));
+        }
+        Ok(Vc::cell(references))
+    }
+
+    #[turbo_tasks::function]
+    async fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        Vc::upcast(self.chunking_context)
+    }
+
+    #[turbo_tasks::function]
+    async fn ty(&self) -> Result<Vc<Box<dyn ChunkType>>> {
+        Ok(Vc::upcast(
+            Vc::<EcmascriptChunkType>::default().resolve().await?,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        Vc::upcast(self.module)
+    }
+}
+
+/// The value exported by a [ProcessedImageModule], without its `src` and
+/// `srcSet`. Urls are added as code, as they may be resolved at runtime.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProcessedImageExport {
+    width: u32,
+    height: u32,
+    #[serde(rename = "blurDataURL", skip_serializing_if = "Option::is_none")]
+    blur_data_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    blur_width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    blur_height: Option<u32>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for ProcessedImageChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn EcmascriptChunkingContext>> {
+        self.chunking_context
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<EcmascriptChunkItemContent>> {
+        let module = self.module.await?;
+
+        let mut src_set = Vec::new();
+        let mut largest = None;
+        for &image in self.images.await?.iter() {
+            let url = asset_url_code(self.chunking_context, image.ident()).await?;
+            let meta = get_meta_data(image.ident(), image.content().file_content(), None).await?;
+            src_set.push(format!(
+                "{} + {}",
+                &*url,
+                StringifyJs(&format!(" {}w", meta.width))
+            ));
+            if largest
+                .as_ref()
+                .map_or(true, |(_, width, _)| meta.width > *width)
+            {
+                largest = Some((url.clone_value(), meta.width, meta.height));
+            }
+        }
+        let (src, width, height) = largest.context("processed image has no outputs")?;
+
+        let blur_placeholder = if let Some(blur_placeholder) = module.blur_placeholder {
+            get_meta_data(
+                module.source.ident(),
+                module.source.content().file_content(),
+                Some(blur_placeholder),
+            )
+            .await?
+            .blur_placeholder
+            .as_ref()
+            .map(|blur| (blur.data_url.clone(), blur.width, blur.height))
+        } else {
+            None
+        };
+
+        let export = ProcessedImageExport {
+            width,
+            height,
+            blur_data_url: blur_placeholder.as_ref().map(|(url, ..)| url.clone()),
+            blur_width: blur_placeholder.as_ref().map(|&(_, width, _)| width),
+            blur_height: blur_placeholder.as_ref().map(|&(.., height)| height),
+        };
+
+        Ok(EcmascriptChunkItemContent {
+            inner_code: format!(
+                "__turbopack_export_value__({{ src: {src}, srcSet: [{}].join(\", \"), ...{} }});",
+                src_set.join(", "),
+                StringifyJs(&export)
+            )
+            .into(),
+            ..Default::default()
+        }
+        .into())
+    }
+}
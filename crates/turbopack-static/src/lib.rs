@@ -1,39 +1,45 @@
 //! Static asset support for turbopack.
 //!
-//! Static assets are copied directly to the output folder.
+//! Static assets are copied directly to the output folder. Whole directories
+//! can be copied as well, see [directory].
 //!
 //! When imported from ES modules, they produce a thin module that simply
-//! exports the asset's path.
+//! exports the asset's path. Images can optionally export their dimensions
+//! and a blur placeholder as well, see [static_image].
 //!
 //! When referred to from CSS assets, the reference is replaced with the asset's
-//! path.
+//! path. Fonts can be inlined or preloaded instead, see [font].
+//!
+//! Images imported by React Native apps are registered with React Native's
+//! asset registry instead, see [react_native].
 
 #![feature(min_specialization)]
 #![feature(arbitrary_self_types)]
 #![feature(async_fn_in_trait)]
 
+pub mod directory;
 pub mod fixed;
+pub mod font;
+pub mod image;
+pub mod react_native;
+pub mod static_image;
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{Context, Result};
 use turbo_tasks::{ValueToString, Vc};
-use turbo_tasks_fs::FileContent;
 use turbopack_core::{
     asset::{Asset, AssetContent},
     chunk::{ChunkItem, ChunkType, ChunkableModule, ChunkingContext},
     context::AssetContext,
     ident::AssetIdent,
     module::Module,
-    output::{OutputAsset, OutputAssets},
+    output::{OutputAsset, OutputAssetMetadata, OutputAssets},
     reference::{ModuleReferences, SingleOutputAssetReference},
     source::Source,
 };
 use turbopack_css::embed::{CssEmbed, CssEmbeddable};
-use turbopack_ecmascript::{
-    chunk::{
-        EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkPlaceable,
-        EcmascriptChunkType, EcmascriptChunkingContext, EcmascriptExports,
-    },
-    utils::StringifyJs,
+use turbopack_ecmascript::chunk::{
+    asset_url_code, EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkPlaceable,
+    EcmascriptChunkType, EcmascriptChunkingContext, EcmascriptExports,
 };
 
 #[turbo_tasks::function]
@@ -132,31 +138,30 @@ impl CssEmbeddable for StaticModuleAsset {
 }
 
 #[turbo_tasks::value]
-struct StaticAsset {
-    chunking_context: Vc<Box<dyn ChunkingContext>>,
-    source: Vc<Box<dyn Source>>,
+pub(crate) struct StaticAsset {
+    pub(crate) chunking_context: Vc<Box<dyn ChunkingContext>>,
+    pub(crate) source: Vc<Box<dyn Source>>,
 }
 
 #[turbo_tasks::value_impl]
 impl OutputAsset for StaticAsset {
     #[turbo_tasks::function]
     async fn ident(&self) -> Result<Vc<AssetIdent>> {
-        let content = self.source.content();
-        let content_hash = if let AssetContent::File(file) = &*content.await? {
-            if let FileContent::Content(file) = &*file.await? {
-                turbo_tasks_hash::hash_xxh3_hash64(file.content())
-            } else {
-                return Err(anyhow!("StaticAsset::path: not found"));
-            }
-        } else {
-            return Err(anyhow!("StaticAsset::path: unsupported file content"));
-        };
-        let content_hash_b16 = turbo_tasks_hash::encode_hex(content_hash);
+        let content_hash = self
+            .chunking_context
+            .content_hash(self.source.content())
+            .await?;
         let asset_path = self
             .chunking_context
-            .asset_path(content_hash_b16, self.source.ident());
+            .asset_path(content_hash.clone_value(), self.source.ident());
         Ok(AssetIdent::from_path(asset_path))
     }
+
+    #[turbo_tasks::function]
+    fn metadata(self: Vc<Self>) -> Vc<OutputAssetMetadata> {
+        // The path contains a content hash.
+        OutputAssetMetadata::immutable(self.ident())
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -222,12 +227,7 @@ impl EcmascriptChunkItem for ModuleChunkItem {
         Ok(EcmascriptChunkItemContent {
             inner_code: format!(
                 "__turbopack_export_value__({path});",
-                path = StringifyJs(
-                    &self
-                        .chunking_context
-                        .asset_url(self.static_asset.ident())
-                        .await?
-                )
+                path = asset_url_code(self.chunking_context, self.static_asset.ident()).await?
             )
             .into(),
             ..Default::default()
@@ -259,5 +259,6 @@ pub fn register() {
     turbo_tasks_fs::register();
     turbopack_core::register();
     turbopack_ecmascript::register();
+    turbopack_image::register();
     include!(concat!(env!("OUT_DIR"), "/register.rs"));
 }
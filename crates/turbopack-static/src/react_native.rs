@@ -0,0 +1,78 @@
+//! Images which are registered with React Native's asset registry.
+//!
+//! React Native's `<Image>` doesn't accept urls for bundled images. Instead, a
+//! module exports the id returned by `AssetRegistry.registerAsset`, and the
+//! image is looked up by that id at runtime. The registered asset describes
+//! where the emitted file is served from and the dimensions of the image.
+
+use std::fmt::Write;
+
+use anyhow::Result;
+use indexmap::indexmap;
+use turbo_tasks::{Value, Vc};
+use turbo_tasks_fs::File;
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    context::AssetContext,
+    module::Module,
+    reference_type::{ReferenceType, UrlReferenceSubType},
+    source::Source,
+    virtual_source::VirtualSource,
+};
+use turbopack_ecmascript::utils::StringifyJs;
+use turbopack_image::process::sniff_meta_data;
+
+/// The request of React Native's asset registry, which is used when no other
+/// registry is configured.
+pub const DEFAULT_ASSET_REGISTRY: &str = "react-native/Libraries/Image/AssetRegistry";
+
+/// Creates a module for the image `source` which registers it with the
+/// `asset_registry` and exports the id of the registered asset.
+///
+/// The image itself is emitted as a static asset. Its url is split into the
+/// `httpServerLocation`, `name` and `type` of the registered asset, so React
+/// Native requests the emitted file instead of a scaled variant.
+#[turbo_tasks::function]
+pub async fn react_native_asset_module(
+    source: Vc<Box<dyn Source>>,
+    asset_context: Vc<Box<dyn AssetContext>>,
+    asset_registry: String,
+) -> Result<Vc<Box<dyn Module>>> {
+    let meta = sniff_meta_data(source.ident(), source.content().file_content(), None).await?;
+
+    let mut code = String::new();
+    writeln!(
+        code,
+        "import {{ registerAsset }} from {};",
+        StringifyJs(&asset_registry)
+    )?;
+    writeln!(code, "import url from \"ASSET\";")?;
+    writeln!(code, "const slash = url.lastIndexOf(\"/\");")?;
+    writeln!(code, "const file = url.slice(slash + 1);")?;
+    writeln!(code, "const dot = file.lastIndexOf(\".\");")?;
+    writeln!(code, "export default registerAsset({{")?;
+    writeln!(code, "  __packager_asset: true,")?;
+    writeln!(code, "  httpServerLocation: url.slice(0, slash),")?;
+    writeln!(code, "  name: file.slice(0, dot),")?;
+    writeln!(code, "  type: file.slice(dot + 1),")?;
+    writeln!(code, "  width: {},", meta.width)?;
+    writeln!(code, "  height: {},", meta.height)?;
+    writeln!(code, "  scales: [1],")?;
+    writeln!(code, "}});")?;
+
+    let asset = asset_context.process(
+        source,
+        Value::new(ReferenceType::Url(UrlReferenceSubType::Undefined)),
+    );
+    let source = VirtualSource::new(
+        source.ident().path().append(".js".to_string()),
+        AssetContent::file(File::from(code).into()),
+    );
+
+    Ok(asset_context.process(
+        Vc::upcast(source),
+        Value::new(ReferenceType::Internal(Vc::cell(indexmap! {
+            "ASSET".to_string() => asset,
+        }))),
+    ))
+}
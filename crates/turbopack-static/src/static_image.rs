@@ -0,0 +1,222 @@
+//! Static images that export metadata alongside their url.
+//!
+//! The dimensions are read from the image header, so frameworks can reserve
+//! space for the image and avoid layout shift without a separate loader.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use turbo_tasks::{ValueToString, Vc};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{ChunkItem, ChunkType, ChunkableModule, ChunkingContext},
+    context::AssetContext,
+    ident::AssetIdent,
+    module::Module,
+    output::OutputAsset,
+    reference::{ModuleReferences, SingleOutputAssetReference},
+    source::Source,
+};
+use turbopack_ecmascript::{
+    chunk::{
+        asset_url_code, EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkPlaceable,
+        EcmascriptChunkType, EcmascriptChunkingContext, EcmascriptExports,
+    },
+    utils::StringifyJs,
+};
+use turbopack_image::process::{sniff_meta_data, BlurPlaceholderOptions};
+
+use crate::StaticAsset;
+
+#[turbo_tasks::function]
+fn modifier() -> Vc<String> {
+    Vc::cell("static image".to_string())
+}
+
+#[turbo_tasks::function]
+fn blur_placeholder_options() -> Vc<BlurPlaceholderOptions> {
+    BlurPlaceholderOptions {
+        quality: 70,
+        size: 8,
+    }
+    .cell()
+}
+
+/// A static image module which exports an object with the url of the image,
+/// its `width` and `height`, and a `blurDataURL` if a placeholder can be
+/// computed for the image format.
+#[turbo_tasks::value]
+#[derive(Clone)]
+pub struct StaticImageModuleAsset {
+    pub source: Vc<Box<dyn Source>>,
+    pub asset_context: Vc<Box<dyn AssetContext>>,
+}
+
+#[turbo_tasks::value_impl]
+impl StaticImageModuleAsset {
+    #[turbo_tasks::function]
+    pub fn new(source: Vc<Box<dyn Source>>, asset_context: Vc<Box<dyn AssetContext>>) -> Vc<Self> {
+        Self::cell(StaticImageModuleAsset {
+            source,
+            asset_context,
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn static_asset(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<Vc<StaticAsset>> {
+        Ok(StaticAsset::cell(StaticAsset {
+            chunking_context,
+            source: self.await?.source,
+        }))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Module for StaticImageModuleAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.source
+            .ident()
+            .with_modifier(modifier())
+            .with_layer(self.asset_context.layer())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for StaticImageModuleAsset {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        self.source.content()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModule for StaticImageModuleAsset {
+    #[turbo_tasks::function]
+    async fn as_chunk_item(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<Vc<Box<dyn ChunkItem>>> {
+        let chunking_context =
+            Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkingContext>>(chunking_context)
+                .await?
+                .context(
+                    "chunking context must impl EcmascriptChunkingContext to use \
+                     StaticImageModuleAsset",
+                )?;
+        Ok(Vc::upcast(StaticImageChunkItem::cell(
+            StaticImageChunkItem {
+                module: self,
+                chunking_context,
+                static_asset: self.static_asset(Vc::upcast(chunking_context)),
+            },
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for StaticImageModuleAsset {
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> Vc<EcmascriptExports> {
+        EcmascriptExports::Value.into()
+    }
+}
+
+#[turbo_tasks::value]
+struct StaticImageChunkItem {
+    module: Vc<StaticImageModuleAsset>,
+    chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    static_asset: Vc<StaticAsset>,
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for StaticImageChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> Vc<AssetIdent> {
+        self.module.ident()
+    }
+
+    #[turbo_tasks::function]
+    async fn references(&self) -> Result<Vc<ModuleReferences>> {
+        Ok(Vc::cell(vec![Vc::upcast(SingleOutputAssetReference::new(
+            Vc::upcast(self.static_asset),
+            Vc::cell(format!(
+                "static(url) {}",
+                self.static_asset.ident().to_string().await?
+            )),
+        ))]))
+    }
+
+    #[turbo_tasks::function]
+    async fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        Vc::upcast(self.chunking_context)
+    }
+
+    #[turbo_tasks::function]
+    async fn ty(&self) -> Result<Vc<Box<dyn ChunkType>>> {
+        Ok(Vc::upcast(
+            Vc::<EcmascriptChunkType>::default().resolve().await?,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        Vc::upcast(self.module)
+    }
+}
+
+/// The value exported by a [StaticImageModuleAsset], without its `src`. The
+/// url is added as code, as it may be resolved at runtime.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StaticImageExport<'a> {
+    width: u32,
+    height: u32,
+    #[serde(rename = "blurDataURL", skip_serializing_if = "Option::is_none")]
+    blur_data_url: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    blur_width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    blur_height: Option<u32>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for StaticImageChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn EcmascriptChunkingContext>> {
+        self.chunking_context
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<EcmascriptChunkItemContent>> {
+        let module = self.module.await?;
+        let src = asset_url_code(self.chunking_context, self.static_asset.ident()).await?;
+        let meta = sniff_meta_data(
+            module.source.ident(),
+            module.source.content().file_content(),
+            Some(blur_placeholder_options()),
+        )
+        .await?;
+        let blur = meta.blur_placeholder.as_ref();
+
+        let export = StaticImageExport {
+            width: meta.width,
+            height: meta.height,
+            blur_data_url: blur.map(|blur| blur.data_url.as_str()),
+            blur_width: blur.map(|blur| blur.width),
+            blur_height: blur.map(|blur| blur.height),
+        };
+
+        Ok(EcmascriptChunkItemContent {
+            inner_code: format!(
+                "__turbopack_export_value__({{ src: {src}, ...{} }});",
+                StringifyJs(&export)
+            )
+            .into(),
+            ..Default::default()
+        }
+        .into())
+    }
+}
@@ -63,6 +63,7 @@ pub async fn snapshot_issues<I: IntoIterator<Item = ReadRef<PlainIssue>>>(
                 show_all: true,
                 log_detail: true,
                 log_level: IssueSeverity::Info,
+                issue_policy: Default::default(),
             },
         );
 
@@ -4,10 +4,11 @@
 
 mod util;
 
-use std::{collections::HashMap, path::PathBuf};
+use std::{collections::HashMap, fs, path::PathBuf};
 
 use anyhow::{Context, Result};
 use dunce::canonicalize;
+use serde::Deserialize;
 use turbo_tasks::{Completion, TryJoinIterExt, TurboTasks, Value, Vc};
 use turbo_tasks_bytes::stream::SingleValue;
 use turbo_tasks_env::CommandLineProcessEnv;
@@ -17,8 +18,9 @@ use turbo_tasks_fs::{
 };
 use turbo_tasks_memory::MemoryBackend;
 use turbopack::{
-    condition::ContextCondition, module_options::ModuleOptionsContext,
-    resolve_options_context::ResolveOptionsContext, ModuleAssetContext,
+    condition::ContextCondition, ecmascript::HotStatePatterns,
+    module_options::ModuleOptionsContext, resolve_options_context::ResolveOptionsContext,
+    stories::StoriesIndexes, ModuleAssetContext,
 };
 use turbopack_core::{
     chunk::{EvaluatableAssetExt, EvaluatableAssets},
@@ -54,6 +56,18 @@ struct JsResult {
     jest_result: JestRunResult,
 }
 
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct TestOptions {
+    /// Maps the requests of stories indexes to the globs of their stories,
+    /// relative to the `input` directory.
+    #[serde(default)]
+    stories_indexes: HashMap<String, String>,
+    /// Whether the state of top level stores is preserved across HMR updates.
+    #[serde(default)]
+    preserve_hot_state: bool,
+}
+
 enum IssueSnapshotMode {
     Snapshots,
     NoSnapshots,
@@ -170,6 +184,12 @@ async fn run_test(resource: String) -> Result<Vc<RunTestResult>> {
         resource_path.to_str().unwrap()
     );
 
+    let options_file = fs::read_to_string(resource_path.join("options.json"));
+    let options: TestOptions = match options_file {
+        Err(_) => TestOptions::default(),
+        Ok(options_str) => parse_json_with_source_context(&options_str)?,
+    };
+
     let root_fs = DiskFileSystem::new("workspace".to_string(), REPO_ROOT.clone());
     let project_fs = DiskFileSystem::new("project".to_string(), REPO_ROOT.clone());
     let project_root = project_fs.root();
@@ -212,6 +232,9 @@ async fn run_test(resource: String) -> Result<Vc<RunTestResult>> {
         ModuleOptionsContext {
             enable_typescript_transform: Some(Default::default()),
             preset_env_versions: Some(env),
+            enable_hot_state_preservation: options
+                .preserve_hot_state
+                .then(HotStatePatterns::defaults),
             rules: vec![(
                 ContextCondition::InDirectory("node_modules".to_string()),
                 ModuleOptionsContext {
@@ -226,6 +249,13 @@ async fn run_test(resource: String) -> Result<Vc<RunTestResult>> {
             enable_typescript: true,
             enable_node_modules: Some(project_root),
             custom_conditions: vec!["development".to_string()],
+            stories_indexes: (!options.stories_indexes.is_empty()).then(|| {
+                StoriesIndexes {
+                    dir: project_path.join("input".to_string()),
+                    globs: options.stories_indexes.into_iter().collect(),
+                }
+                .cell()
+            }),
             rules: vec![(
                 ContextCondition::InDirectory("node_modules".to_string()),
                 ResolveOptionsContext {
@@ -0,0 +1,34 @@
+function locale(lang) {
+  return import(`./locales/${lang}.json`);
+}
+
+function eagerLocale(lang) {
+  return import(/* webpackMode: "eager" */ `./locales/${lang}.json`);
+}
+
+function filteredLocale(lang) {
+  return import(
+    /* webpackInclude: /\.json$/, webpackExclude: /draft/ */ `./locales/${lang}.json`
+  );
+}
+
+it("should require files of a context module", () => {
+  const lang = "de";
+  expect(require("./locales/" + lang + ".json")).toEqual({ hello: "Hallo" });
+});
+
+it("should import files of a context module", async () => {
+  const { default: en } = await locale("en");
+  expect(en).toEqual({ hello: "Hello" });
+});
+
+it("should import files of an eager context module", async () => {
+  const { default: de } = await eagerLocale("de");
+  expect(de).toEqual({ hello: "Hallo" });
+});
+
+it("should reject requests excluded by magic comments", async () => {
+  const { default: en } = await filteredLocale("en");
+  expect(en).toEqual({ hello: "Hello" });
+  await expect(filteredLocale("draft")).rejects.toThrow();
+});
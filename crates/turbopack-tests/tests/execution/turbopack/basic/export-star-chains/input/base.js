@@ -0,0 +1,2 @@
+export const shared = "base";
+export const shadowed = "base";
@@ -0,0 +1,12 @@
+import * as barrel from "./barrel.js";
+import { shared } from "./barrel.js";
+
+it("should bind names re-exported through multiple paths once", () => {
+  expect(shared).toBe("base");
+  expect(barrel.shared).toBe("base");
+});
+
+it("should not export names which are ambiguous on one of the paths", () => {
+  // `left.js` shadows `shadowed` of `base.js`, which `right.js` re-exports.
+  expect(barrel).not.toHaveProperty("shadowed");
+});
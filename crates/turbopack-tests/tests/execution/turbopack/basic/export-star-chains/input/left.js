@@ -0,0 +1,2 @@
+export * from "./base.js";
+export const shadowed = "left";
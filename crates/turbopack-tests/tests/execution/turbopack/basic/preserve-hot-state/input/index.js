@@ -0,0 +1,41 @@
+import { createStore } from "zustand/vanilla";
+import { store, invalidate } from "./store.js";
+
+const localStore = createStore(() => ({ count: 1 }));
+
+it("creates top level stores before the first HMR update", () => {
+  expect(localStore.getState()).toEqual({ count: 1 });
+  localStore.setState({ count: 2 });
+  expect(localStore.getState()).toEqual({ count: 2 });
+});
+
+it("preserves the state of top level stores across HMR updates", async () => {
+  store.setState({ count: 2 });
+
+  const updated = new Promise((resolve) => {
+    import.meta.hot.accept("./store.js", resolve);
+  });
+  invalidate();
+  applyUpdate();
+  const module = await updated;
+
+  expect(module.store).toBe(store);
+  expect(module.store.getState()).toEqual({ count: 2 });
+});
+
+// Applies an update without changes, which re-instantiates the invalidated
+// modules like an update from the dev server would.
+function applyUpdate() {
+  const path = "preserve-hot-state";
+  globalThis.TURBOPACK_CHUNK_LISTS.push({ path, chunks: [], source: "dynamic" });
+  const [, handleUpdate] = globalThis.TURBOPACK_CHUNK_UPDATE_LISTENERS.find(
+    ([chunkListPath]) => chunkListPath === path
+  );
+  handleUpdate({
+    type: "partial",
+    instruction: {
+      type: "ChunkListUpdate",
+      merged: [{ type: "EcmascriptMergedUpdate" }],
+    },
+  });
+}
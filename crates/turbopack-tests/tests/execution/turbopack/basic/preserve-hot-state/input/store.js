@@ -0,0 +1,7 @@
+import { createStore } from "zustand/vanilla";
+
+export const store = createStore(() => ({ count: 1 }));
+
+export function invalidate() {
+  import.meta.hot.invalidate();
+}
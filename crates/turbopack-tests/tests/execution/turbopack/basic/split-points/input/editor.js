@@ -0,0 +1,6 @@
+/* @turbopack-chunk: "editor" */
+import { format } from "./format.js";
+
+export function render(text) {
+  return format(text);
+}
@@ -0,0 +1,5 @@
+import { render } from "./editor.js";
+
+it("should load statically imported split points", () => {
+  expect(render("hello")).toBe("<hello>");
+});
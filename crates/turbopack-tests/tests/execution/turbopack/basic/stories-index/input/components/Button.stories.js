@@ -0,0 +1,9 @@
+/**
+ * Buttons in all variants.
+ */
+
+export default { title: "Button" };
+
+export const Primary = () => "primary";
+
+export const Secondary = () => "secondary";
@@ -0,0 +1,18 @@
+import stories from "virtual:stories";
+
+it("should index the modules matching the glob", () => {
+  expect(Object.keys(stories)).toEqual(["./components/Button.stories.js"]);
+});
+
+it("should extract the metadata of stories", () => {
+  const story = stories["./components/Button.stories.js"];
+  expect(story.exports).toEqual(["Primary", "Secondary"]);
+  expect(story.description).toBe("Buttons in all variants.");
+});
+
+it("should load stories lazily", async () => {
+  const story = stories["./components/Button.stories.js"];
+  const module = await story.load();
+  expect(module.Primary()).toBe("primary");
+  expect(module.default.title).toBe("Button");
+});
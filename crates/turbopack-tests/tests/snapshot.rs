@@ -241,6 +241,7 @@ async fn run_test(resource: String) -> Result<Vc<FileSystemPath>> {
                 )) as _),
             ],
             output_transforms: vec![],
+            analyzers: vec![],
         },
     ));
     let asset_context: Vc<Box<dyn AssetContext>> = Vc::upcast(ModuleAssetContext::new(
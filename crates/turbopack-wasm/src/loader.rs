@@ -1,24 +1,25 @@
 use std::fmt::Write;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
 use indoc::{formatdoc, writedoc};
 use turbo_tasks::Vc;
-use turbo_tasks_fs::File;
-use turbopack_core::{asset::AssetContent, source::Source, virtual_source::VirtualSource};
+use turbo_tasks_fs::{File, FileContent};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    source::Source,
+    virtual_source::VirtualSource,
+};
 use turbopack_ecmascript::utils::StringifyJs;
 
-use crate::{analysis::analyze, source::WebAssemblySource};
-
-/// Create a javascript loader to instantiate the WebAssembly module with the
-/// necessary imports and exports to be processed by [turbopack_ecmascript].
-#[turbo_tasks::function]
-pub(crate) async fn instantiating_loader_source(
-    source: Vc<WebAssemblySource>,
-) -> Result<Vc<Box<dyn Source>>> {
-    let analysis = analyze(source).await?;
-
-    let mut code = String::new();
+use crate::{
+    analysis::{analyze, WebAssemblyAnalysis},
+    source::WebAssemblySource,
+};
 
+/// Writes the imports of a WebAssembly module to `code` and returns the
+/// imports object which is passed to the instantiation.
+fn write_imports(code: &mut String, analysis: &WebAssemblyAnalysis) -> Result<String> {
     let mut imports_obj = "{".to_string();
     for (path, items) in &analysis.imports {
         writeln!(
@@ -35,6 +36,20 @@ pub(crate) async fn instantiating_loader_source(
         writeln!(imports_obj, "    }},")?;
     }
     writeln!(imports_obj, "}}")?;
+    Ok(imports_obj)
+}
+
+/// Create a javascript loader to instantiate the WebAssembly module with the
+/// necessary imports and exports to be processed by [turbopack_ecmascript].
+#[turbo_tasks::function]
+pub(crate) async fn instantiating_loader_source(
+    source: Vc<WebAssemblySource>,
+) -> Result<Vc<Box<dyn Source>>> {
+    let analysis = analyze(source).await?;
+
+    let mut code = String::new();
+
+    let imports_obj = write_imports(&mut code, &analysis)?;
 
     writeln!(code, "import wasmPath from \"WASM_PATH\";")?;
 
@@ -57,6 +72,50 @@ pub(crate) async fn instantiating_loader_source(
     )))
 }
 
+/// Create a javascript loader which embeds the WebAssembly module and
+/// instantiates it synchronously, so importers don't become async modules.
+#[turbo_tasks::function]
+pub(crate) async fn sync_instantiating_loader_source(
+    source: Vc<WebAssemblySource>,
+) -> Result<Vc<Box<dyn Source>>> {
+    let analysis = analyze(source).await?;
+    let content = source.content().file_content().await?;
+    let FileContent::Content(file) = &*content else {
+        bail!("WebAssembly file not found");
+    };
+    let wasm_base64 = STANDARD.encode(file.content().to_bytes()?);
+
+    let mut code = String::new();
+
+    let imports_obj = write_imports(&mut code, &analysis)?;
+
+    writeln!(code)?;
+
+    writedoc!(
+        code,
+        r#"
+            const wasmBase64 = {wasm_base64};
+            const wasmBytes = typeof Buffer !== "undefined"
+                ? Buffer.from(wasmBase64, "base64")
+                : Uint8Array.from(atob(wasmBase64), (c) => c.charCodeAt(0));
+            const {{ {exports} }} = new WebAssembly.Instance(
+                new WebAssembly.Module(wasmBytes),
+                {imports}
+            ).exports;
+
+            export {{ {exports} }};
+        "#,
+        wasm_base64 = StringifyJs(&wasm_base64),
+        imports = imports_obj,
+        exports = analysis.exports.join(", "),
+    )?;
+
+    Ok(Vc::upcast(VirtualSource::new(
+        source.ident().path().append("_.loader.mjs".to_string()),
+        AssetContent::file(File::from(code).into()),
+    )))
+}
+
 /// Create a javascript loader to compile the WebAssembly module and export it
 /// without instantiating.
 #[turbo_tasks::function]
@@ -78,3 +137,30 @@ pub(crate) async fn compiling_loader_source(
         AssetContent::file(File::from(code).into()),
     )))
 }
+
+/// Create a javascript loader which wires up the JS glue that wasm-bindgen
+/// generated for the WebAssembly module, like the `<name>.js` entry of
+/// wasm-bindgen's bundler target does, and re-exports the API of the glue.
+#[turbo_tasks::function]
+pub(crate) fn wasm_bindgen_loader_source(source: Vc<WebAssemblySource>) -> Vc<Box<dyn Source>> {
+    let code = formatdoc! {
+        r#"
+            import * as wasm from "WASM";
+            import * as glue from "GLUE";
+
+            if (typeof glue.__wbg_set_wasm === "function") {{
+                glue.__wbg_set_wasm(wasm);
+            }}
+            if (typeof wasm.__wbindgen_start === "function") {{
+                wasm.__wbindgen_start();
+            }}
+
+            export * from "GLUE";
+        "#
+    };
+
+    Vc::upcast(VirtualSource::new(
+        source.ident().path().append("_.bindgen.mjs".to_string()),
+        AssetContent::file(File::from(code).into()),
+    ))
+}
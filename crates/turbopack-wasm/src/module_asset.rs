@@ -1,15 +1,17 @@
 use anyhow::{bail, Context, Result};
 use indexmap::indexmap;
-use turbo_tasks::{Value, Vc};
-use turbo_tasks_fs::FileSystemPath;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, TaskInput, Value, Vc};
+use turbo_tasks_fs::{FileSystemEntryType, FileSystemPath};
 use turbopack_core::{
     asset::{Asset, AssetContent},
     chunk::{AsyncModuleInfo, ChunkItem, ChunkType, ChunkableModule, ChunkingContext},
     context::AssetContext,
+    file_source::FileSource,
     ident::AssetIdent,
     module::{Module, OptionModule},
     reference::ModuleReferences,
-    reference_type::ReferenceType,
+    reference_type::{InnerAssets, ReferenceType},
     resolve::{origin::ResolveOrigin, parse::Request},
     source::Source,
 };
@@ -24,7 +26,10 @@ use turbopack_ecmascript::{
 };
 
 use crate::{
-    loader::{compiling_loader_source, instantiating_loader_source},
+    loader::{
+        compiling_loader_source, instantiating_loader_source, sync_instantiating_loader_source,
+        wasm_bindgen_loader_source,
+    },
     output_asset::WebAssemblyAsset,
     raw::RawWebAssemblyModuleAsset,
     source::WebAssemblySource,
@@ -35,13 +40,51 @@ fn modifier() -> Vc<String> {
     Vc::cell("wasm module".to_string())
 }
 
+/// How a WebAssembly module which is imported as an ES module is
+/// instantiated.
+#[derive(
+    PartialOrd,
+    Ord,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    Default,
+    Copy,
+    Clone,
+    Serialize,
+    Deserialize,
+    TaskInput,
+    TraceRawVcs,
+)]
+pub enum WebAssemblyInstantiation {
+    /// The binary is loaded and instantiated asynchronously, which makes the
+    /// module and its importers async modules.
+    #[default]
+    Async,
+    /// The binary is embedded into the loader and instantiated synchronously.
+    /// Browsers only allow this for small modules on the main thread, so it's
+    /// mostly useful for server environments.
+    Sync,
+}
+
+#[turbo_tasks::function]
+fn wasm_bindgen_modifier() -> Vc<String> {
+    Vc::cell("wasm-bindgen module".to_string())
+}
+
 /// Creates a javascript loader which instantiates the WebAssembly source and
-/// re-exports its exports.
+/// re-exports its exports, like the WebAssembly ES module integration.
+///
+/// With `wasm_bindgen`, the API of the sibling `<name>.js` glue which
+/// wasm-bindgen generated for a `<name>.wasm` file is exported instead.
 #[turbo_tasks::value]
 #[derive(Clone)]
 pub struct WebAssemblyModuleAsset {
     source: Vc<WebAssemblySource>,
     asset_context: Vc<Box<dyn AssetContext>>,
+    instantiation: WebAssemblyInstantiation,
+    wasm_bindgen: bool,
 }
 
 #[turbo_tasks::value_impl]
@@ -50,10 +93,14 @@ impl WebAssemblyModuleAsset {
     pub fn new(
         source: Vc<WebAssemblySource>,
         asset_context: Vc<Box<dyn AssetContext>>,
+        instantiation: WebAssemblyInstantiation,
+        wasm_bindgen: bool,
     ) -> Vc<Self> {
         Self::cell(WebAssemblyModuleAsset {
             source,
             asset_context,
+            instantiation,
+            wasm_bindgen,
         })
     }
 
@@ -66,18 +113,44 @@ impl WebAssemblyModuleAsset {
     async fn loader(&self) -> Result<Vc<EcmascriptModuleAsset>> {
         let query = &*self.source.ident().query().await?;
 
-        let loader_source = if query == "?module" {
-            compiling_loader_source(self.source)
+        let glue = if self.wasm_bindgen && query.is_empty() {
+            self.wasm_bindgen_glue().await?
         } else {
-            instantiating_loader_source(self.source)
+            None
         };
 
-        let module = self.asset_context.process(
-            loader_source,
-            Value::new(ReferenceType::Internal(Vc::cell(indexmap! {
-                "WASM_PATH".to_string() => Vc::upcast(RawWebAssemblyModuleAsset::new(self.source, self.asset_context)),
-            }))),
-        );
+        let module = if let Some(glue) = glue {
+            self.asset_context.process(
+                wasm_bindgen_loader_source(self.source),
+                Value::new(ReferenceType::Internal(Vc::cell(indexmap! {
+                    "WASM".to_string() => Vc::upcast(WebAssemblyModuleAsset::new(
+                        self.source,
+                        self.asset_context,
+                        self.instantiation,
+                        false,
+                    )),
+                    "GLUE".to_string() => glue,
+                }))),
+            )
+        } else if query != "?module" && self.instantiation == WebAssemblyInstantiation::Sync {
+            self.asset_context.process(
+                sync_instantiating_loader_source(self.source),
+                Value::new(ReferenceType::Internal(InnerAssets::empty())),
+            )
+        } else {
+            let loader_source = if query == "?module" {
+                compiling_loader_source(self.source)
+            } else {
+                instantiating_loader_source(self.source)
+            };
+
+            self.asset_context.process(
+                loader_source,
+                Value::new(ReferenceType::Internal(Vc::cell(indexmap! {
+                    "WASM_PATH".to_string() => Vc::upcast(RawWebAssemblyModuleAsset::new(self.source, self.asset_context)),
+                }))),
+            )
+        };
 
         let Some(esm_asset) =
             Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(module).await?
@@ -89,13 +162,37 @@ impl WebAssemblyModuleAsset {
     }
 }
 
+impl WebAssemblyModuleAsset {
+    /// The JS glue which wasm-bindgen generated next to the WebAssembly file,
+    /// i.e. `<name>.js` for `<name>.wasm`, if it exists.
+    async fn wasm_bindgen_glue(&self) -> Result<Option<Vc<Box<dyn Module>>>> {
+        let path = self.source.ident().path();
+        let path_value = path.await?;
+        let Some(name) = path_value.file_name().strip_suffix(".wasm") else {
+            return Ok(None);
+        };
+        let glue_path = path.parent().join(format!("{name}.js"));
+        if !matches!(*glue_path.get_type().await?, FileSystemEntryType::File) {
+            return Ok(None);
+        }
+        Ok(Some(self.asset_context.process(
+            Vc::upcast(FileSource::new(glue_path)),
+            Value::new(ReferenceType::Internal(InnerAssets::empty())),
+        )))
+    }
+}
+
 #[turbo_tasks::value_impl]
 impl Module for WebAssemblyModuleAsset {
     #[turbo_tasks::function]
     fn ident(&self) -> Vc<AssetIdent> {
         self.source
             .ident()
-            .with_modifier(modifier())
+            .with_modifier(if self.wasm_bindgen {
+                wasm_bindgen_modifier()
+            } else {
+                modifier()
+            })
             .with_layer(self.asset_context.layer())
     }
 
@@ -209,8 +306,17 @@ impl ChunkItem for ModuleChunkItem {
     }
 
     #[turbo_tasks::function]
-    fn is_self_async(self: Vc<Self>) -> Vc<bool> {
-        Vc::cell(true)
+    async fn is_self_async(&self) -> Result<Vc<bool>> {
+        let module = self.module.await?;
+        let query = &*module.source.ident().query().await?;
+        if query.is_empty() && module.wasm_bindgen && module.wasm_bindgen_glue().await?.is_some() {
+            // The wasm-bindgen loader only inherits the async module status of
+            // the raw module.
+            return Ok(Vc::cell(false));
+        }
+        Ok(Vc::cell(
+            query == "?module" || module.instantiation == WebAssemblyInstantiation::Async,
+        ))
     }
 }
 
@@ -0,0 +1,324 @@
+use anyhow::{bail, Result};
+use indexmap::IndexMap;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{File, FileSystemPath};
+use turbopack_core::{
+    asset::AssetContent,
+    resolve::{
+        options::{ImportMapResult, ImportMapping, ImportMappingReplacement, ResolveOptions},
+        parse::Request,
+        resolve, AliasPattern, ResolveResult,
+    },
+    source::Source,
+    virtual_source::VirtualSource,
+};
+use turbopack_ecmascript::utils::StringifyJs;
+
+/// The request of the module which shares the host's packages with the
+/// containers and loads exposed modules, see [runtime_module_code].
+const RUNTIME_REQUEST: &str = "__turbopack_federation__/runtime";
+
+/// The prefix of the requests of the namespace objects of exposed modules,
+/// see [namespace_module_code].
+const NAMESPACE_PREFIX: &str = "__turbopack_federation__/namespace/";
+
+/// The module federation containers which a build consumes modules from, see
+/// [crate::resolve_options_context::ResolveOptionsContext::federation_remotes].
+///
+/// A request like `app2/Button` loads the module which the container `app2`
+/// exposes as `./Button` at runtime, and `app2` loads the one exposed as `.`.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct FederationRemotes {
+    /// Maps the names of the containers to the URLs of their entries, e.g.
+    /// `app2` to `https://app2.example.com/remoteEntry.mjs`.
+    pub remotes: IndexMap<String, String>,
+    /// Maps the requests of packages which the host shares with the
+    /// containers to their versions, e.g. `react` to `18.2.0`. The host's own
+    /// imports of them go through the share scope too, so it uses the same
+    /// version as the containers when one of them loaded it first, e.g. a
+    /// singleton.
+    pub shared: IndexMap<String, String>,
+    /// The directory which the host's versions of the shared packages are
+    /// resolved from, e.g. the project.
+    pub project_path: Vc<FileSystemPath>,
+}
+
+impl FederationRemotes {
+    /// The patterns of the requests which are mapped to the modules of the
+    /// federation.
+    pub(crate) fn alias_patterns(&self) -> Vec<AliasPattern> {
+        let mut patterns = vec![
+            AliasPattern::exact(RUNTIME_REQUEST),
+            AliasPattern::wildcard(NAMESPACE_PREFIX, ""),
+        ];
+        for request in self.shared.keys() {
+            patterns.push(AliasPattern::exact(request.clone()));
+        }
+        for remote in self.remotes.keys() {
+            patterns.push(AliasPattern::exact(remote.clone()));
+            patterns.push(AliasPattern::wildcard(format!("{remote}/"), ""));
+        }
+        patterns
+    }
+}
+
+/// An import mapping for the requests of [FederationRemotes::alias_patterns],
+/// which resolve to virtual modules in the `__federation__` directory of the
+/// project.
+#[turbo_tasks::value]
+pub(crate) struct FederationMapping {
+    remotes: Vc<FederationRemotes>,
+    /// The resolve options of the host without the federation, which resolve
+    /// the requests of the shared packages to the host's versions.
+    resolve_options: Vc<ResolveOptions>,
+}
+
+#[turbo_tasks::value_impl]
+impl FederationMapping {
+    #[turbo_tasks::function]
+    pub fn new(remotes: Vc<FederationRemotes>, resolve_options: Vc<ResolveOptions>) -> Vc<Self> {
+        FederationMapping {
+            remotes,
+            resolve_options,
+        }
+        .cell()
+    }
+
+    /// The code of the runtime module, which refers to the host's versions of
+    /// the shared packages relative to `path`.
+    #[turbo_tasks::function]
+    async fn runtime_code(&self, path: Vc<FileSystemPath>) -> Result<Vc<String>> {
+        let remotes = self.remotes.await?;
+        let directory = path.parent().await?;
+
+        let mut shared = Vec::new();
+        for (request, version) in &remotes.shared {
+            let result = resolve(
+                remotes.project_path,
+                Request::parse_string(request.clone()),
+                self.resolve_options,
+            );
+            let Some(source) = *result.first_source().await? else {
+                bail!(
+                    "the shared package {request} can't be resolved from {}",
+                    remotes.project_path.await?
+                );
+            };
+            let source_path = source.ident().path().await?;
+            let Some(relative_path) = directory.get_relative_path_to(&source_path) else {
+                bail!(
+                    "cannot find a relative path from {} to the shared package {request} ({})",
+                    directory.to_string(),
+                    source_path.to_string()
+                );
+            };
+            shared.push((request.clone(), version.clone(), relative_path));
+        }
+
+        Ok(Vc::cell(runtime_module_code(&shared)))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMappingReplacement for FederationMapping {
+    #[turbo_tasks::function]
+    fn replace(self: Vc<Self>, _capture: String) -> Vc<ImportMapping> {
+        ImportMapping::Dynamic(Vc::upcast(self)).cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn result(
+        self: Vc<Self>,
+        _lookup_path: Vc<FileSystemPath>,
+        request: Vc<Request>,
+    ) -> Result<Vc<ImportMapResult>> {
+        let this = self.await?;
+        let remotes = this.remotes.await?;
+        let Some(request) = request.await?.request() else {
+            return Ok(ImportMapResult::NoEntry.cell());
+        };
+        let directory = remotes.project_path.join("__federation__".to_string());
+
+        let (path, code) = if request == RUNTIME_REQUEST {
+            let path = directory.join("runtime.js".to_string());
+            (path, self.runtime_code(path).await?.clone_value())
+        } else if let Some(key) = request.strip_prefix(NAMESPACE_PREFIX) {
+            (
+                directory.join(format!("namespaces/{key}.js")),
+                namespace_module_code(key),
+            )
+        } else if remotes.shared.contains_key(&request) {
+            (
+                directory.join(format!("shared/{request}.js")),
+                shared_module_code(&request),
+            )
+        } else {
+            let (remote, subpath) = match request.split_once('/') {
+                Some((remote, subpath)) => (remote, Some(subpath)),
+                None => (request.as_str(), None),
+            };
+            let Some(url) = remotes.remotes.get(remote) else {
+                return Ok(ImportMapResult::NoEntry.cell());
+            };
+            let expose = match subpath {
+                Some(subpath) => format!("./{subpath}"),
+                None => ".".to_string(),
+            };
+            (
+                directory.join(format!("remotes/{request}.mjs")),
+                remote_module_code(url, &expose, &request),
+            )
+        };
+
+        let source = VirtualSource::new(path, AssetContent::file(File::from(code).into()));
+        Ok(ImportMapResult::Result(ResolveResult::source(Vc::upcast(source)).cell()).cell())
+    }
+}
+
+/// The code of the runtime module, which registers the host's versions of the
+/// shared packages in the share scope and provides the other modules of the
+/// federation with:
+///
+/// * `useShared(request)`, which returns the version of a shared package which
+///   was loaded already, e.g. by a container, or loads the host's version.
+/// * `loadRemote(url, expose, key)`, which loads an exposed module and defines
+///   its exports on the namespace object of `key`.
+///
+/// `shared` lists the requests of the shared packages with the host's
+/// versions and their paths relative to the runtime module.
+fn runtime_module_code(shared: &[(String, String, String)]) -> String {
+    let shared = shared
+        .iter()
+        .map(|(request, version, path)| {
+            format!(
+                "  {}: {{ version: {}, load: () => require({}) }},\n",
+                StringifyJs(request),
+                StringifyJs(version),
+                StringifyJs(path)
+            )
+        })
+        .collect::<String>();
+
+    format!(
+        r#"const shareScope = (globalThis.__turbopack_share_scope__ ??= {{}});
+const namespaces = (globalThis.__turbopack_federation_namespaces__ ??= {{}});
+const shared = {{
+{shared}}};
+
+for (const [request, {{ version, load }}] of Object.entries(shared)) {{
+  const versions = (shareScope[request] ??= {{}});
+  versions[version] ??= {{ get: async () => load(), from: "host" }};
+}}
+
+function useShared(request) {{
+  const versions = shareScope[request];
+  const loaded = Object.values(versions).find((version) => "exports" in version);
+  if (loaded !== undefined) {{
+    return loaded.exports;
+  }}
+  const {{ version, load }} = shared[request];
+  const exports = load();
+  versions[version].loaded = Promise.resolve(exports);
+  versions[version].exports = exports;
+  return exports;
+}}
+
+async function loadRemote(url, expose, key) {{
+  const container = await import(/* turbopackIgnore: true */ url);
+  await container.init(shareScope);
+  const exports = (await container.get(expose))();
+  const namespace = (namespaces[key] ??= {{}});
+  for (const name of Reflect.ownKeys(exports)) {{
+    if (typeof name === "string" && name !== "default" && !(name in namespace)) {{
+      Object.defineProperty(namespace, name, {{
+        enumerable: true,
+        get: () => exports[name],
+      }});
+    }}
+  }}
+  return exports.__esModule ? exports.default : exports;
+}}
+
+module.exports = {{ useShared, loadRemote }};
+"#
+    )
+}
+
+/// The code of the namespace object of the exposed module with the `key`,
+/// which [runtime_module_code] defines the exports on once it's loaded. Its
+/// exports are only known at runtime, so it's re-exported with `export *`.
+fn namespace_module_code(key: &str) -> String {
+    format!(
+        "__turbopack_export_namespace__((globalThis.__turbopack_federation_namespaces__ ??= \
+         {{}})[{}] ??= {{}});\n",
+        StringifyJs(key)
+    )
+}
+
+/// The code of the module which the host's own imports of a shared package
+/// resolve to.
+fn shared_module_code(request: &str) -> String {
+    format!(
+        "module.exports = require({}).useShared({});\n",
+        StringifyJs(RUNTIME_REQUEST),
+        StringifyJs(request)
+    )
+}
+
+/// The code of the module which loads the module exposed as `expose` by the
+/// container at `url`, after sharing the host's packages with it. It
+/// re-exports the exports of the exposed module, which are identified by the
+/// request of the module as `key`.
+fn remote_module_code(url: &str, expose: &str, key: &str) -> String {
+    format!(
+        r#"import {{ loadRemote }} from {runtime};
+export * from {namespace};
+const remote = await loadRemote({url}, {expose}, {key});
+export {{ remote as default }};
+"#,
+        runtime = StringifyJs(RUNTIME_REQUEST),
+        namespace = StringifyJs(&format!("{NAMESPACE_PREFIX}{key}")),
+        url = StringifyJs(url),
+        expose = StringifyJs(expose),
+        key = StringifyJs(key),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{namespace_module_code, remote_module_code, shared_module_code};
+
+    #[test]
+    fn re_exports_the_exposed_module() {
+        assert_eq!(
+            remote_module_code(
+                "https://app2.example.com/remoteEntry.mjs",
+                "./Button",
+                "app2/Button"
+            ),
+            [
+                r#"import { loadRemote } from "__turbopack_federation__/runtime";"#,
+                r#"export * from "__turbopack_federation__/namespace/app2/Button";"#,
+                r#"const remote = await loadRemote("https://app2.example.com/remoteEntry.mjs", "./Button", "app2/Button");"#,
+                r#"export { remote as default };"#,
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            namespace_module_code("app2/Button"),
+            "__turbopack_export_namespace__((globalThis.__turbopack_federation_namespaces__ ??= \
+             {})[\"app2/Button\"] ??= {});\n"
+        );
+    }
+
+    #[test]
+    fn uses_shared_packages_through_the_share_scope() {
+        assert_eq!(
+            shared_module_code("react"),
+            "module.exports = \
+             require(\"__turbopack_federation__/runtime\").useShared(\"react\");\n"
+        );
+    }
+}
@@ -10,12 +10,16 @@
 
 pub mod condition;
 pub mod evaluate_context;
+pub mod federation;
 mod graph;
 pub mod module_options;
+pub(crate) mod node_builtins;
 pub mod rebase;
 pub mod resolve;
 pub mod resolve_options_context;
+pub mod stories;
 pub mod transition;
+pub mod type_check;
 pub(crate) mod unsupported_sass;
 
 use std::{
@@ -23,14 +27,18 @@ use std::{
     mem::swap,
 };
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use css::{CssModuleAsset, GlobalCssAsset, ModuleCssAsset};
 use ecmascript::{
+    references::external_module::ExternalModule, stories::StoriesIndexSource,
     typescript::resolve::TypescriptTypesAssetReference, EcmascriptModuleAsset,
     EcmascriptModuleAssetType,
 };
 use graph::{aggregate, AggregatedGraph, AggregatedGraphNodeContent};
-use module_options::{ModuleOptions, ModuleOptionsContext, ModuleRuleEffect, ModuleType};
+use module_options::{
+    webpack_loaders_evaluate_context, ModuleOptions, ModuleOptionsContext, ModuleRuleEffect,
+    ModuleType, RuleMatchInput,
+};
 pub use resolve::resolve_options;
 use turbo_tasks::{Completion, Value, Vc};
 use turbo_tasks_fs::FileSystemPath;
@@ -38,6 +46,8 @@ use turbopack_core::{
     asset::Asset,
     compile_time_info::CompileTimeInfo,
     context::AssetContext,
+    error::PrettyPrintError,
+    file_source::FileSource,
     ident::AssetIdent,
     issue::{Issue, IssueExt},
     module::Module,
@@ -45,16 +55,20 @@ use turbopack_core::{
     raw_module::RawModule,
     reference_type::{EcmaScriptModulesReferenceSubType, InnerAssets, ReferenceType},
     resolve::{
-        options::ResolveOptions, origin::PlainResolveOrigin, parse::Request, resolve,
-        AffectingResolvingAssetReference, ModulePart, ModuleResolveResult, ResolveResult,
+        find_context_file, options::ResolveOptions, origin::PlainResolveOrigin, package_json,
+        parse::Request, resolve, AffectingResolvingAssetReference, FindContextFileResult,
+        ModulePart, ModuleResolveResult, ModuleResolveResultItem, ResolveResult,
     },
+    security::SecurityScanners,
     source::Source,
+    source_transform::SourceTransform,
 };
 pub use turbopack_css as css;
 pub use turbopack_ecmascript as ecmascript;
 use turbopack_json::JsonModuleAsset;
-use turbopack_mdx::MdxModuleAsset;
-use turbopack_static::StaticModuleAsset;
+use turbopack_mdx::{MarkdownModuleAsset, MdxModuleAsset};
+use turbopack_node::transforms::webpack::{InlineLoaders, WebpackLoaders};
+use turbopack_static::{static_image::StaticImageModuleAsset, StaticModuleAsset};
 use turbopack_wasm::{module_asset::WebAssemblyModuleAsset, source::WebAssemblySource};
 
 use self::{
@@ -171,16 +185,25 @@ async fn apply_module_type(
             source,
             Vc::upcast(module_asset_context),
         )),
-        ModuleType::Css { ty, transforms } => Vc::upcast(CssModuleAsset::new(
+        ModuleType::Css {
+            ty,
+            transforms,
+            css_modules,
+        } => Vc::upcast(CssModuleAsset::new(
             source,
             Vc::upcast(module_asset_context),
             *transforms,
             *ty,
+            *css_modules,
         )),
         ModuleType::Static => Vc::upcast(StaticModuleAsset::new(
             source,
             Vc::upcast(module_asset_context),
         )),
+        ModuleType::StaticImage => Vc::upcast(StaticImageModuleAsset::new(
+            source,
+            Vc::upcast(module_asset_context),
+        )),
         ModuleType::Mdx {
             transforms,
             options,
@@ -190,9 +213,16 @@ async fn apply_module_type(
             *transforms,
             *options,
         )),
-        ModuleType::WebAssembly { source_ty } => Vc::upcast(WebAssemblyModuleAsset::new(
+        ModuleType::Markdown => Vc::upcast(MarkdownModuleAsset::new(source)),
+        ModuleType::WebAssembly {
+            source_ty,
+            instantiation,
+            wasm_bindgen,
+        } => Vc::upcast(WebAssemblyModuleAsset::new(
             WebAssemblySource::new(source, *source_ty),
             Vc::upcast(module_asset_context),
+            *instantiation,
+            *wasm_bindgen,
         )),
         ModuleType::Custom(custom) => custom.create_module(source, module_asset_context, part),
     })
@@ -288,8 +318,187 @@ impl ModuleAssetContext {
         source: Vc<Box<dyn Source>>,
         reference_type: Value<ReferenceType>,
     ) -> Vc<Box<dyn Module>> {
-        process_default(self, source, reference_type, Vec::new())
+        process_default(self, source, reference_type, None, Vec::new())
+    }
+
+    /// Processes `source`, which is imported by `issuer`. The issuer is only
+    /// passed on when the module rules of `source` match on it, so that
+    /// modules are not duplicated per issuer otherwise.
+    #[turbo_tasks::function]
+    async fn process_with_issuer(
+        self: Vc<Self>,
+        source: Vc<Box<dyn Source>>,
+        reference_type: Value<ReferenceType>,
+        issuer: Vc<FileSystemPath>,
+    ) -> Result<Vc<Box<dyn Module>>> {
+        let this = self.await?;
+        if let Some(transition) = this.transition {
+            return Ok(transition.process(source, self, reference_type));
+        }
+        let options =
+            ModuleOptions::new(source.ident().path().parent(), this.module_options_context);
+        let issuer = if *options.depends_on_issuer().await? {
+            Some(issuer)
+        } else {
+            None
+        };
+        Ok(process_default(
+            self,
+            source,
+            reference_type,
+            issuer,
+            Vec::new(),
+        ))
+    }
+
+    /// Like [ModuleAssetContext::process_with_issuer], but without applying
+    /// the configured webpack loaders, for inline loader requests which
+    /// disable them. Only `source` itself is affected, the modules it
+    /// references are processed with the configured loaders.
+    #[turbo_tasks::function]
+    async fn process_without_webpack_loaders(
+        self: Vc<Self>,
+        source: Vc<Box<dyn Source>>,
+        reference_type: Value<ReferenceType>,
+        issuer: Vc<FileSystemPath>,
+    ) -> Result<Vc<Box<dyn Module>>> {
+        let this = self.await?;
+        if let Some(transition) = this.transition {
+            return Ok(transition.process(source, self, reference_type));
+        }
+        let options =
+            ModuleOptions::new(source.ident().path().parent(), this.module_options_context);
+        let issuer = if *options.depends_on_issuer().await? {
+            Some(issuer)
+        } else {
+            None
+        };
+        // The webpack loader rules are skipped like rules which have already
+        // been applied.
+        Ok(process_default(
+            self,
+            source,
+            reference_type,
+            issuer,
+            options.await?.webpack_loader_rules.clone(),
+        ))
+    }
+
+    /// Resolves the resource of a request with inline webpack loaders, e.g.
+    /// `!raw-loader!./file.txt`, and applies the loaders to it.
+    #[turbo_tasks::function]
+    async fn resolve_with_inline_loaders(
+        self: Vc<Self>,
+        origin_path: Vc<FileSystemPath>,
+        inline_loaders: Vc<InlineLoaders>,
+        resolve_options: Vc<ResolveOptions>,
+        reference_type: Value<ReferenceType>,
+    ) -> Result<Vc<ModuleResolveResult>> {
+        let module_options_context = self.await?.module_options_context.await?;
+        let (Some(webpack_loaders_options), Some(execution_context)) = (
+            module_options_context.enable_webpack_loaders,
+            module_options_context.execution_context,
+        ) else {
+            bail!("Inline loaders require webpack loaders to be enabled");
+        };
+        let webpack_loaders_options = webpack_loaders_options.await?;
+        let inline_loaders = inline_loaders.await?;
+        let context_path = origin_path.parent().resolve().await?;
+
+        let request = Request::parse_string(inline_loaders.request.clone());
+        let mut result = resolve(context_path, request, resolve_options);
+        if !inline_loaders.loaders.is_empty() {
+            let transform = WebpackLoaders::inline(
+                webpack_loaders_evaluate_context(
+                    execution_context,
+                    webpack_loaders_options.loader_runner_package,
+                    context_path,
+                ),
+                execution_context,
+                Vc::cell(inline_loaders.loaders.clone()),
+                webpack_loaders_options.emit_path,
+            );
+            result = result
+                .await?
+                .map(
+                    |source| async move { Ok(transform.transform(source)) },
+                    |source| async move { Ok(source) },
+                )
+                .await?
+                .cell();
+        }
+
+        Ok(self.process_resolve_result_with_issuer(
+            result.resolve().await?,
+            reference_type,
+            Some(origin_path),
+            inline_loaders.disable_configured_loaders,
+        ))
+    }
+
+    /// Processes the sources of `result`. `skip_webpack_loaders` requires an
+    /// `issuer`, see [ModuleAssetContext::process_without_webpack_loaders].
+    #[turbo_tasks::function]
+    async fn process_resolve_result_with_issuer(
+        self: Vc<Self>,
+        result: Vc<ResolveResult>,
+        reference_type: Value<ReferenceType>,
+        issuer: Option<Vc<FileSystemPath>>,
+        skip_webpack_loaders: bool,
+    ) -> Result<Vc<ModuleResolveResult>> {
+        let mut result = result
+            .await?
+            .map_module(
+                |source| {
+                    let reference_type = reference_type.clone();
+                    async move {
+                        let module = if let Some(issuer) = issuer {
+                            if skip_webpack_loaders {
+                                self.process_without_webpack_loaders(source, reference_type, issuer)
+                            } else {
+                                self.process_with_issuer(source, reference_type, issuer)
+                            }
+                        } else {
+                            self.process(source, reference_type)
+                        };
+                        Ok(Vc::upcast(module.resolve().await?))
+                    }
+                },
+                |i| async move { Ok(Vc::upcast(AffectingResolvingAssetReference::new(i))) },
+            )
+            .await?;
+        for item in result.primary.iter_mut() {
+            if let ModuleResolveResultItem::External(name, ty) = item {
+                *item = ModuleResolveResultItem::Module(Vc::upcast(ExternalModule::new(
+                    name.clone(),
+                    Value::new(*ty),
+                )));
+            }
+        }
+        Ok(result.into())
+    }
+}
+
+/// Parses the inline webpack loaders of `request`, when webpack loaders are
+/// enabled.
+async fn inline_loaders(
+    module_asset_context: Vc<ModuleAssetContext>,
+    request: Vc<Request>,
+) -> Result<Option<InlineLoaders>> {
+    let Some(request_str) = request.await?.request() else {
+        return Ok(None);
+    };
+    if !request_str.contains('!')
+        || module_asset_context
+            .module_options_context()
+            .await?
+            .enable_webpack_loaders
+            .is_none()
+    {
+        return Ok(None);
     }
+    let query = request.query().await?;
+    InlineLoaders::parse(&format!("{request_str}{query}"))
 }
 
 #[turbo_tasks::function]
@@ -297,8 +506,35 @@ async fn process_default(
     module_asset_context: Vc<ModuleAssetContext>,
     source: Vc<Box<dyn Source>>,
     reference_type: Value<ReferenceType>,
+    issuer: Option<Vc<FileSystemPath>>,
     processed_rules: Vec<usize>,
 ) -> Result<Vc<Box<dyn Module>>> {
+    if processed_rules.is_empty() {
+        if let Some(security_scanners) = module_asset_context
+            .module_options_context()
+            .await?
+            .security_scanners
+        {
+            // A failing scanner shouldn't fail the module.
+            if let Err(err) = security_scan(security_scanners, source).await {
+                ModuleIssue {
+                    ident: source.ident(),
+                    title: Vc::cell("Security scan failed".to_string()),
+                    description: Vc::cell(format!("{}", PrettyPrintError(&err))),
+                }
+                .cell()
+                .emit();
+            }
+        }
+    }
+
+    // Stories indexes are generated from the modules matching their glob, which
+    // are processed in the same context.
+    if let Some(stories_index) = Vc::try_resolve_downcast_type::<StoriesIndexSource>(source).await?
+    {
+        return Ok(stories_index.module(Vc::upcast(module_asset_context)));
+    }
+
     let ident = source.ident().resolve().await?;
     let options = ModuleOptions::new(
         ident.path().parent(),
@@ -318,86 +554,45 @@ async fn process_default(
     };
     let mut current_source = source;
     let mut current_module_type = None;
+    let path = ident.path().await?;
+    let issuer_path = match issuer {
+        Some(issuer) => Some(issuer.await?),
+        None => None,
+    };
+    let mut input = RuleMatchInput::from_source(source, &path, &reference_type).await?;
+    if let Some(issuer_path) = &issuer_path {
+        input = input.with_issuer(issuer_path);
+    }
     for (i, rule) in options.await?.rules.iter().enumerate() {
         if processed_rules.contains(&i) {
             continue;
         }
-        if rule
-            .matches(source, &*ident.path().await?, &reference_type)
-            .await?
-        {
+        if rule.matches_input(&input) {
             for effect in rule.effects() {
-                match effect {
-                    ModuleRuleEffect::SourceTransforms(transforms) => {
-                        current_source = transforms.transform(current_source);
-                        if current_source.ident().resolve().await? != ident {
-                            // The ident has been changed, so we need to apply new rules.
-                            let mut processed_rules = processed_rules.clone();
-                            processed_rules.push(i);
-                            return Ok(process_default(
-                                module_asset_context,
-                                current_source,
-                                Value::new(reference_type),
-                                processed_rules,
-                            ));
-                        }
-                    }
-                    ModuleRuleEffect::ModuleType(module) => {
-                        current_module_type = Some(*module);
+                if let ModuleRuleEffect::SourceTransforms(transforms) = effect {
+                    current_source = transforms.transform(current_source);
+                    if current_source.ident().resolve().await? != ident {
+                        // The ident has been changed, so we need to apply new rules.
+                        let mut processed_rules = processed_rules.clone();
+                        processed_rules.push(i);
+                        return Ok(process_default(
+                            module_asset_context,
+                            current_source,
+                            Value::new(reference_type),
+                            issuer,
+                            processed_rules,
+                        ));
                     }
-                    ModuleRuleEffect::AddEcmascriptTransforms(additional_transforms) => {
-                        current_module_type = match current_module_type {
-                            Some(ModuleType::Ecmascript {
-                                transforms,
-                                options,
-                            }) => Some(ModuleType::Ecmascript {
-                                transforms: transforms.extend(*additional_transforms),
-                                options,
-                            }),
-                            Some(ModuleType::Typescript {
-                                transforms,
-                                options,
-                            }) => Some(ModuleType::Typescript {
-                                transforms: transforms.extend(*additional_transforms),
-                                options,
-                            }),
-                            Some(ModuleType::TypescriptWithTypes {
-                                transforms,
-                                options,
-                            }) => Some(ModuleType::TypescriptWithTypes {
-                                transforms: transforms.extend(*additional_transforms),
-                                options,
-                            }),
-                            Some(module_type) => {
-                                ModuleIssue {
-                                    ident,
-                                    title: Vc::cell("Invalid module type".to_string()),
-                                    description: Vc::cell(
-                                        "The module type must be Ecmascript or Typescript to add \
-                                         Ecmascript transforms"
-                                            .to_string(),
-                                    ),
-                                }
-                                .cell()
-                                .emit();
-                                Some(module_type)
-                            }
-                            None => {
-                                ModuleIssue {
-                                    ident,
-                                    title: Vc::cell("Missing module type".to_string()),
-                                    description: Vc::cell(
-                                        "The module type effect must be applied before adding \
-                                         Ecmascript transforms"
-                                            .to_string(),
-                                    ),
-                                }
-                                .cell()
-                                .emit();
-                                None
-                            }
-                        };
+                }
+                match effect.apply_to_module_type(current_module_type) {
+                    Ok(module_type) => current_module_type = module_type,
+                    Err(err) => ModuleIssue {
+                        ident,
+                        title: Vc::cell(err.title().to_string()),
+                        description: Vc::cell(err.description().to_string()),
                     }
+                    .cell()
+                    .emit(),
                 }
             }
         }
@@ -414,6 +609,24 @@ async fn process_default(
     ))
 }
 
+/// Scans `source` and the `package.json` of the package it belongs to with the
+/// configured [SecurityScanners].
+#[turbo_tasks::function]
+async fn security_scan(
+    security_scanners: Vc<SecurityScanners>,
+    source: Vc<Box<dyn Source>>,
+) -> Result<Vc<Completion>> {
+    security_scanners.scan(source).await?;
+    if let FindContextFileResult::Found(package_json_path, _) =
+        *find_context_file(source.ident().path().parent(), package_json()).await?
+    {
+        security_scanners
+            .scan(Vc::upcast(FileSource::new(package_json_path)))
+            .await?;
+    }
+    Ok(Completion::new())
+}
+
 #[turbo_tasks::value_impl]
 impl AssetContext for ModuleAssetContext {
     #[turbo_tasks::function]
@@ -453,10 +666,24 @@ impl AssetContext for ModuleAssetContext {
         resolve_options: Vc<ResolveOptions>,
         reference_type: Value<ReferenceType>,
     ) -> Result<Vc<ModuleResolveResult>> {
+        if let Some(inline_loaders) = inline_loaders(self, request).await? {
+            return Ok(self.resolve_with_inline_loaders(
+                origin_path,
+                inline_loaders.cell(),
+                resolve_options,
+                reference_type,
+            ));
+        }
+
         let context_path = origin_path.parent().resolve().await?;
 
         let result = resolve(context_path, request, resolve_options);
-        let mut result = self.process_resolve_result(result.resolve().await?, reference_type);
+        let mut result = self.process_resolve_result_with_issuer(
+            result.resolve().await?,
+            reference_type,
+            Some(origin_path),
+            false,
+        );
 
         if *self.is_types_resolving_enabled().await? {
             let types_reference = TypescriptTypesAssetReference::new(
@@ -471,39 +698,12 @@ impl AssetContext for ModuleAssetContext {
     }
 
     #[turbo_tasks::function]
-    async fn process_resolve_result(
+    fn process_resolve_result(
         self: Vc<Self>,
         result: Vc<ResolveResult>,
         reference_type: Value<ReferenceType>,
-    ) -> Result<Vc<ModuleResolveResult>> {
-        let this = self.await?;
-        let transition = this.transition;
-        Ok(result
-            .await?
-            .map_module(
-                |source| {
-                    let reference_type = reference_type.clone();
-                    async move {
-                        if let Some(transition) = transition {
-                            Ok(Vc::upcast(
-                                transition
-                                    .process(source, self, reference_type)
-                                    .resolve()
-                                    .await?,
-                            ))
-                        } else {
-                            Ok(Vc::upcast(
-                                self.process_default(source, reference_type)
-                                    .resolve()
-                                    .await?,
-                            ))
-                        }
-                    }
-                },
-                |i| async move { Ok(Vc::upcast(AffectingResolvingAssetReference::new(i))) },
-            )
-            .await?
-            .into())
+    ) -> Vc<ModuleResolveResult> {
+        self.process_resolve_result_with_issuer(result, reference_type, None, false)
     }
 
     #[turbo_tasks::function]
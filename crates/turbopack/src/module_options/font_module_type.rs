@@ -0,0 +1,40 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbopack_core::{module::Module, resolve::ModulePart, source::Source};
+use turbopack_static::font::FontModuleAsset;
+
+use super::{CustomModuleType, FontOptions};
+use crate::ModuleAssetContext;
+
+/// Creates [FontModuleAsset]s for fonts referenced from CSS.
+#[turbo_tasks::value]
+pub struct FontModuleType {
+    options: Vc<FontOptions>,
+}
+
+#[turbo_tasks::value_impl]
+impl FontModuleType {
+    #[turbo_tasks::function]
+    pub fn new(options: Vc<FontOptions>) -> Vc<Self> {
+        Self::cell(FontModuleType { options })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CustomModuleType for FontModuleType {
+    #[turbo_tasks::function]
+    async fn create_module(
+        &self,
+        source: Vc<Box<dyn Source>>,
+        module_asset_context: Vc<ModuleAssetContext>,
+        _part: Option<Vc<ModulePart>>,
+    ) -> Result<Vc<Box<dyn Module>>> {
+        let options = self.options.await?;
+        Ok(Vc::upcast(FontModuleAsset::new(
+            source,
+            Vc::upcast(module_asset_context),
+            options.inline_limit,
+            options.preload,
+        )))
+    }
+}
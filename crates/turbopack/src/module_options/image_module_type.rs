@@ -0,0 +1,47 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbopack_core::{module::Module, resolve::ModulePart, source::Source};
+use turbopack_image::process::BlurPlaceholderOptions;
+use turbopack_static::image::ProcessedImageModule;
+
+use super::{CustomModuleType, ImageOptimizationOptions};
+use crate::ModuleAssetContext;
+
+/// Creates [ProcessedImageModule]s for images imported with a resource query
+/// like `?w=640&format=webp`.
+#[turbo_tasks::value]
+pub struct ProcessedImageModuleType {
+    options: Vc<ImageOptimizationOptions>,
+}
+
+#[turbo_tasks::value_impl]
+impl ProcessedImageModuleType {
+    #[turbo_tasks::function]
+    pub fn new(options: Vc<ImageOptimizationOptions>) -> Vc<Self> {
+        Self::cell(ProcessedImageModuleType { options })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CustomModuleType for ProcessedImageModuleType {
+    #[turbo_tasks::function]
+    async fn create_module(
+        &self,
+        source: Vc<Box<dyn Source>>,
+        module_asset_context: Vc<ModuleAssetContext>,
+        _part: Option<Vc<ModulePart>>,
+    ) -> Result<Vc<Box<dyn Module>>> {
+        let blur_placeholder = self.options.await?.blur_placeholder.then(|| {
+            BlurPlaceholderOptions {
+                quality: 70,
+                size: 8,
+            }
+            .cell()
+        });
+        Ok(Vc::upcast(ProcessedImageModule::new(
+            source,
+            Vc::upcast(module_asset_context),
+            blur_placeholder,
+        )))
+    }
+}
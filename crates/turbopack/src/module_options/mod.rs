@@ -1,23 +1,36 @@
 pub(crate) mod custom_module_type;
+pub(crate) mod font_module_type;
+#[cfg(feature = "image_optimization")]
+pub(crate) mod image_module_type;
 pub mod module_options_context;
 pub mod module_rule;
+pub(crate) mod react_native_asset_module_type;
 pub mod rule_condition;
+pub mod rule_testing;
 
 use anyhow::{Context, Result};
 pub use custom_module_type::CustomModuleType;
 pub use module_options_context::*;
 pub use module_rule::*;
 pub use rule_condition::*;
-use turbo_tasks::Vc;
+use turbo_tasks::{ReadRef, Vc};
 use turbo_tasks_fs::{glob::Glob, FileSystemPath};
 use turbopack_core::{
+    context::AssetContext,
     reference_type::{CssReferenceSubType, ReferenceType, UrlReferenceSubType},
     resolve::options::{ImportMap, ImportMapping},
+    source_transform::SourceTransform,
+};
+use turbopack_css::{
+    css_modules::CssModulesOptions, lightningcss::LightningCssTransform, CssInputTransform,
+    CssModuleAssetType,
 };
-use turbopack_css::{CssInputTransform, CssModuleAssetType};
 use turbopack_ecmascript::{EcmascriptInputTransform, EcmascriptOptions, SpecifiedModuleType};
 use turbopack_mdx::MdxTransformOptions;
-use turbopack_node::transforms::{postcss::PostCssTransform, webpack::WebpackLoaders};
+use turbopack_node::{
+    execution_context::ExecutionContext,
+    transforms::{postcss::PostCssTransform, tailwind::TailwindTransform, webpack::WebpackLoaders},
+};
 use turbopack_wasm::source::WebAssemblySourceType;
 
 use crate::evaluate_context::node_evaluate_asset_context;
@@ -36,7 +49,7 @@ async fn package_import_map_from_import_mapping(
 }
 
 #[turbo_tasks::function]
-async fn package_import_map_from_context(
+pub(crate) async fn package_import_map_from_context(
     package_name: String,
     context_path: Vc<FileSystemPath>,
 ) -> Result<Vc<ImportMap>> {
@@ -48,13 +61,62 @@ async fn package_import_map_from_context(
     Ok(import_map.cell())
 }
 
+/// Matches the basename of resources for globs without a `/`, and otherwise
+/// the path relative to the project.
+async fn loader_rule_glob_condition(
+    glob: &str,
+    project_path: &ReadRef<FileSystemPath>,
+) -> Result<ModuleRuleCondition> {
+    let is_base_path_glob = !glob.contains('/');
+    let glob = Glob::new(glob.to_string()).await?;
+    Ok(if is_base_path_glob {
+        ModuleRuleCondition::ResourceBasePathGlob(glob)
+    } else {
+        ModuleRuleCondition::ResourcePathGlob {
+            base: project_path.clone(),
+            glob,
+        }
+    })
+}
+
+/// The asset context in which webpack loaders are executed. `loader-runner` is
+/// resolved from `context_path` unless a package is configured.
+pub(crate) fn webpack_loaders_evaluate_context(
+    execution_context: Vc<ExecutionContext>,
+    loader_runner_package: Option<Vc<ImportMapping>>,
+    context_path: Vc<FileSystemPath>,
+) -> Vc<Box<dyn AssetContext>> {
+    let import_map = if let Some(loader_runner_package) = loader_runner_package {
+        package_import_map_from_import_mapping("loader-runner".to_string(), loader_runner_package)
+    } else {
+        package_import_map_from_context("loader-runner".to_string(), context_path)
+    };
+    node_evaluate_asset_context(
+        execution_context,
+        Some(import_map),
+        None,
+        "webpack_loaders".to_string(),
+    )
+}
+
 #[turbo_tasks::value(cell = "new", eq = "manual")]
 pub struct ModuleOptions {
     pub rules: Vec<ModuleRule>,
+    /// The indices of the [rules](ModuleOptions::rules) which apply the
+    /// configured webpack loaders.
+    pub webpack_loader_rules: Vec<usize>,
 }
 
 #[turbo_tasks::value_impl]
 impl ModuleOptions {
+    /// Whether any rule matches on the issuer of resources, see
+    /// [ModuleRuleCondition::depends_on_issuer].
+    #[turbo_tasks::function]
+    pub async fn depends_on_issuer(self: Vc<Self>) -> Result<Vc<bool>> {
+        let rules = &self.await?.rules;
+        Ok(Vc::cell(rules.iter().any(|rule| rule.depends_on_issuer())))
+    }
+
     #[turbo_tasks::function]
     pub async fn new(
         path: Vc<FileSystemPath>,
@@ -63,19 +125,36 @@ impl ModuleOptions {
         let ModuleOptionsContext {
             enable_jsx,
             enable_types,
+            ignore_type_only_imports,
+            ignore_type_declarations,
             enable_tree_shaking,
             ref enable_typescript_transform,
             ref decorators,
             enable_mdx,
             enable_mdx_rs,
+            enable_markdown,
+            wasm_instantiation,
+            enable_wasm_bindgen,
             enable_raw_css,
             ref enable_postcss_transform,
+            enable_lightningcss,
+            enable_tailwind,
+            css_modules,
             ref enable_webpack_loaders,
             preset_env_versions,
             ref custom_ecma_transform_plugins,
             ref custom_rules,
             execution_context,
             ref rules,
+            ref static_asset_extensions,
+            enable_static_image_metadata,
+            #[cfg(feature = "image_optimization")]
+            enable_image_optimization,
+            enable_font_handling,
+            enable_react_native_assets,
+            context_modules,
+            enable_hot_state_preservation,
+            cjs_interop,
             ..
         } = *module_options_context.await?;
         if !rules.is_empty() {
@@ -103,6 +182,13 @@ impl ModuleOptions {
                         .iter()
                         .cloned()
                         .map(EcmascriptInputTransform::Plugin)
+                        .chain(
+                            transform_plugins
+                                .analyzers
+                                .iter()
+                                .cloned()
+                                .map(EcmascriptInputTransform::Analyzer),
+                        )
                         .collect(),
                 )
             } else {
@@ -126,9 +212,26 @@ impl ModuleOptions {
             });
         }
 
+        if let Some(patterns) = enable_hot_state_preservation {
+            transforms.push(EcmascriptInputTransform::PreserveHotState(patterns));
+        }
+
+        let (context_include, context_exclude) = if let Some(context_modules) = context_modules {
+            let context_modules = context_modules.await?;
+            (
+                context_modules.include.clone().map(Glob::new),
+                context_modules.exclude.clone().map(Glob::new),
+            )
+        } else {
+            (None, None)
+        };
+
         let ecmascript_options = EcmascriptOptions {
             split_into_parts: enable_tree_shaking,
             import_parts: enable_tree_shaking,
+            context_include,
+            context_exclude,
+            cjs_interop,
             ..Default::default()
         };
 
@@ -140,6 +243,8 @@ impl ModuleOptions {
             let options = options.await?;
             Some(EcmascriptInputTransform::TypeScript {
                 use_define_for_class_fields: options.use_define_for_class_fields,
+                verbatim_module_syntax: options.verbatim_module_syntax,
+                inline_const_enums: options.inline_const_enums,
             })
         } else {
             None
@@ -160,6 +265,11 @@ impl ModuleOptions {
             None
         };
 
+        // Type-only imports are removed before any other transform, so they are
+        // never seen by the analysis.
+        let type_only_transform =
+            ignore_type_only_imports.then_some(EcmascriptInputTransform::StripTypeOnlyImports);
+
         let vendor_transforms = Vc::cell(vec![]);
         let ts_app_transforms = if let Some(transform) = &ts_transform {
             let base_transforms = if let Some(decorators_transform) = &decorators_transform {
@@ -168,18 +278,48 @@ impl ModuleOptions {
                 vec![transform.clone()]
             };
             Vc::cell(
-                base_transforms
+                type_only_transform
                     .iter()
+                    .chain(base_transforms.iter())
                     .cloned()
                     .chain(transforms.iter().cloned())
                     .chain(after_transform_plugins.iter().cloned())
                     .collect(),
             )
         } else {
-            Vc::cell(transforms.clone())
+            Vc::cell(
+                type_only_transform
+                    .iter()
+                    .cloned()
+                    .chain(transforms.iter().cloned())
+                    .collect(),
+            )
         };
+        let ts_declaration_transforms = Vc::cell(type_only_transform.iter().cloned().collect());
 
-        let css_transforms = Vc::cell(vec![CssInputTransform::Nested]);
+        // Lightning CSS compiles nesting away itself.
+        let lightningcss_transform: Option<Vc<Box<dyn SourceTransform>>> =
+            if let Some(options) = enable_lightningcss {
+                Some(Vc::upcast(LightningCssTransform::new(
+                    preset_env_versions,
+                    options.await?.minify,
+                )))
+            } else {
+                None
+            };
+        let css_modules = css_modules.unwrap_or_else(CssModulesOptions::default);
+        let css_transforms = Vc::cell(if lightningcss_transform.is_some() {
+            vec![]
+        } else {
+            vec![CssInputTransform::Nested]
+        });
+        let css_effects = |module_type: ModuleType| {
+            lightningcss_transform
+                .map(|transform| ModuleRuleEffect::SourceTransforms(Vc::cell(vec![transform])))
+                .into_iter()
+                .chain([ModuleRuleEffect::ModuleType(module_type)])
+                .collect::<Vec<_>>()
+        };
         let mdx_transforms = Vc::cell(
             if let Some(transform) = &ts_transform {
                 if let Some(decorators_transform) = &decorators_transform {
@@ -218,6 +358,18 @@ impl ModuleOptions {
             .collect(),
         );
 
+        let static_asset_conditions = match static_asset_extensions {
+            Some(extensions) => extensions
+                .iter()
+                .map(|ext| ext.trim_start_matches('.'))
+                .map(|ext| ModuleRuleCondition::ResourcePathEndsWith(format!(".{ext}")))
+                .collect(),
+            None => DEFAULT_STATIC_ASSET_EXTENSIONS
+                .iter()
+                .map(|ext| ModuleRuleCondition::ResourcePathEndsWith(format!(".{ext}")))
+                .collect(),
+        };
+
         let mut rules = vec![
             ModuleRule::new(
                 ModuleRuleCondition::ResourcePathEndsWith(".json".to_string()),
@@ -319,25 +471,18 @@ impl ModuleOptions {
             ModuleRule::new(
                 ModuleRuleCondition::ResourcePathEndsWith(".d.ts".to_string()),
                 vec![ModuleRuleEffect::ModuleType(
-                    ModuleType::TypescriptDeclaration {
-                        transforms: vendor_transforms,
-                        options: ecmascript_options,
+                    if ignore_type_declarations && !enable_types {
+                        ModuleType::Raw
+                    } else {
+                        ModuleType::TypescriptDeclaration {
+                            transforms: ts_declaration_transforms,
+                            options: ecmascript_options,
+                        }
                     },
                 )],
             ),
             ModuleRule::new(
-                ModuleRuleCondition::any(vec![
-                    ModuleRuleCondition::ResourcePathEndsWith(".apng".to_string()),
-                    ModuleRuleCondition::ResourcePathEndsWith(".avif".to_string()),
-                    ModuleRuleCondition::ResourcePathEndsWith(".gif".to_string()),
-                    ModuleRuleCondition::ResourcePathEndsWith(".ico".to_string()),
-                    ModuleRuleCondition::ResourcePathEndsWith(".jpg".to_string()),
-                    ModuleRuleCondition::ResourcePathEndsWith(".jpeg".to_string()),
-                    ModuleRuleCondition::ResourcePathEndsWith(".png".to_string()),
-                    ModuleRuleCondition::ResourcePathEndsWith(".svg".to_string()),
-                    ModuleRuleCondition::ResourcePathEndsWith(".webp".to_string()),
-                    ModuleRuleCondition::ResourcePathEndsWith(".woff2".to_string()),
-                ]),
+                ModuleRuleCondition::any(static_asset_conditions),
                 vec![ModuleRuleEffect::ModuleType(ModuleType::Static)],
             ),
             ModuleRule::new(
@@ -346,6 +491,8 @@ impl ModuleOptions {
                 )]),
                 vec![ModuleRuleEffect::ModuleType(ModuleType::WebAssembly {
                     source_ty: WebAssemblySourceType::Binary,
+                    instantiation: wasm_instantiation,
+                    wasm_bindgen: false,
                 })],
             ),
             ModuleRule::new(
@@ -354,6 +501,8 @@ impl ModuleOptions {
                 )]),
                 vec![ModuleRuleEffect::ModuleType(ModuleType::WebAssembly {
                     source_ty: WebAssemblySourceType::Text,
+                    instantiation: wasm_instantiation,
+                    wasm_bindgen: false,
                 })],
             ),
             ModuleRule::new(
@@ -371,28 +520,161 @@ impl ModuleOptions {
             ),
         ];
 
+        if enable_wasm_bindgen {
+            rules.push(ModuleRule::new(
+                ModuleRuleCondition::all(vec![
+                    ModuleRuleCondition::ResourcePathEndsWith("_bg.wasm".to_string()),
+                    // The glue itself imports the raw module.
+                    ModuleRuleCondition::not(ModuleRuleCondition::IssuerInResourceDirectory),
+                    ModuleRuleCondition::not(ModuleRuleCondition::ReferenceType(
+                        ReferenceType::Url(UrlReferenceSubType::Undefined),
+                    )),
+                ]),
+                vec![ModuleRuleEffect::ModuleType(ModuleType::WebAssembly {
+                    source_ty: WebAssemblySourceType::Binary,
+                    instantiation: wasm_instantiation,
+                    wasm_bindgen: true,
+                })],
+            ));
+        }
+
+        if enable_static_image_metadata {
+            rules.push(ModuleRule::new(
+                ModuleRuleCondition::all(vec![
+                    ModuleRuleCondition::any(
+                        [
+                            "apng", "avif", "gif", "ico", "jpeg", "jpg", "png", "svg", "webp",
+                        ]
+                        .into_iter()
+                        .map(|ext| ModuleRuleCondition::ResourcePathEndsWith(format!(".{ext}")))
+                        .collect(),
+                    ),
+                    // `new URL()` and CSS `url()` references expect the plain url.
+                    ModuleRuleCondition::not(ModuleRuleCondition::ReferenceType(
+                        ReferenceType::Url(UrlReferenceSubType::Undefined),
+                    )),
+                ]),
+                vec![ModuleRuleEffect::ModuleType(ModuleType::StaticImage)],
+            ));
+        }
+
+        if let Some(options) = enable_font_handling {
+            rules.push(ModuleRule::new(
+                ModuleRuleCondition::all(vec![
+                    ModuleRuleCondition::any(
+                        ["otf", "ttf", "woff", "woff2"]
+                            .into_iter()
+                            .map(|ext| ModuleRuleCondition::ResourcePathEndsWith(format!(".{ext}")))
+                            .collect(),
+                    ),
+                    ModuleRuleCondition::ReferenceType(ReferenceType::Url(
+                        UrlReferenceSubType::CssUrl,
+                    )),
+                ]),
+                vec![ModuleRuleEffect::ModuleType(ModuleType::Custom(
+                    Vc::upcast(font_module_type::FontModuleType::new(options)),
+                ))],
+            ));
+        }
+
+        if let Some(options) = enable_react_native_assets {
+            rules.push(ModuleRule::new(
+                ModuleRuleCondition::all(vec![
+                    ModuleRuleCondition::any(
+                        ["bmp", "gif", "jpeg", "jpg", "png", "webp"]
+                            .into_iter()
+                            .map(|ext| ModuleRuleCondition::ResourcePathEndsWith(format!(".{ext}")))
+                            .collect(),
+                    ),
+                    // The registered asset references the emitted image by its url.
+                    ModuleRuleCondition::not(ModuleRuleCondition::ReferenceType(
+                        ReferenceType::Url(UrlReferenceSubType::Undefined),
+                    )),
+                ]),
+                vec![ModuleRuleEffect::ModuleType(ModuleType::Custom(
+                    Vc::upcast(
+                        react_native_asset_module_type::ReactNativeAssetModuleType::new(options),
+                    ),
+                ))],
+            ));
+        }
+
         if enable_raw_css {
             rules.extend([
                 ModuleRule::new(
                     ModuleRuleCondition::all(vec![ModuleRuleCondition::ResourcePathEndsWith(
                         ".css".to_string(),
                     )]),
-                    vec![ModuleRuleEffect::ModuleType(ModuleType::Css {
+                    css_effects(ModuleType::Css {
                         ty: CssModuleAssetType::Default,
                         transforms: css_transforms,
-                    })],
+                        css_modules,
+                    }),
                 ),
                 ModuleRule::new(
                     ModuleRuleCondition::all(vec![ModuleRuleCondition::ResourcePathEndsWith(
                         ".module.css".to_string(),
                     )]),
-                    vec![ModuleRuleEffect::ModuleType(ModuleType::Css {
+                    css_effects(ModuleType::Css {
                         ty: CssModuleAssetType::Module,
                         transforms: css_transforms,
-                    })],
+                        css_modules,
+                    }),
                 ),
             ]);
         } else {
+            let postcss_effect = if let Some(options) = enable_postcss_transform {
+                let execution_context = execution_context
+                    .context("execution_context is required for the postcss_transform")?;
+
+                let import_map = if let Some(postcss_package) = options.postcss_package {
+                    package_import_map_from_import_mapping("postcss".to_string(), postcss_package)
+                } else {
+                    package_import_map_from_context("postcss".to_string(), path)
+                };
+                Some(ModuleRuleEffect::SourceTransforms(Vc::cell(vec![
+                    Vc::upcast(PostCssTransform::new(
+                        node_evaluate_asset_context(
+                            execution_context,
+                            Some(import_map),
+                            None,
+                            "postcss".to_string(),
+                        ),
+                        execution_context,
+                        Vc::cell(serde_json::Value::Object(options.plugin_options.clone())),
+                    )),
+                ])))
+            } else {
+                None
+            };
+            let tailwind_effect = if let Some(options) = enable_tailwind {
+                let execution_context = execution_context
+                    .context("execution_context is required for the tailwind transform")?;
+                let options = options.await?;
+
+                let import_map = if let Some(tailwind_package) = options.tailwind_package {
+                    package_import_map_from_import_mapping(
+                        "tailwindcss".to_string(),
+                        tailwind_package,
+                    )
+                } else {
+                    package_import_map_from_context("tailwindcss".to_string(), path)
+                };
+                Some(ModuleRuleEffect::SourceTransforms(Vc::cell(vec![
+                    Vc::upcast(TailwindTransform::new(
+                        node_evaluate_asset_context(
+                            execution_context,
+                            Some(import_map),
+                            None,
+                            "tailwind".to_string(),
+                        ),
+                        execution_context,
+                        Vc::cell(options.content.clone()),
+                    )),
+                ])))
+            } else {
+                None
+            };
             rules.extend([
                 ModuleRule::new(
                     ModuleRuleCondition::all(vec![
@@ -402,40 +684,12 @@ impl ModuleOptions {
                             ReferenceType::Css(CssReferenceSubType::AtImport),
                         )),
                     ]),
-                    [
-                        if let Some(options) = enable_postcss_transform {
-                            let execution_context = execution_context.context(
-                                "execution_context is required for the postcss_transform",
-                            )?;
-
-                            let import_map = if let Some(postcss_package) = options.postcss_package
-                            {
-                                package_import_map_from_import_mapping(
-                                    "postcss".to_string(),
-                                    postcss_package,
-                                )
-                            } else {
-                                package_import_map_from_context("postcss".to_string(), path)
-                            };
-                            Some(ModuleRuleEffect::SourceTransforms(Vc::cell(vec![
-                                Vc::upcast(PostCssTransform::new(
-                                    node_evaluate_asset_context(
-                                        execution_context,
-                                        Some(import_map),
-                                        None,
-                                        "postcss".to_string(),
-                                    ),
-                                    execution_context,
-                                )),
-                            ])))
-                        } else {
-                            None
-                        },
-                        Some(ModuleRuleEffect::ModuleType(ModuleType::CssGlobal)),
-                    ]
-                    .into_iter()
-                    .flatten()
-                    .collect(),
+                    tailwind_effect
+                        .clone()
+                        .into_iter()
+                        .chain(postcss_effect)
+                        .chain(css_effects(ModuleType::CssGlobal))
+                        .collect(),
                 ),
                 ModuleRule::new(
                     ModuleRuleCondition::all(vec![
@@ -447,7 +701,7 @@ impl ModuleOptions {
                             ReferenceType::Css(CssReferenceSubType::AtImport),
                         )),
                     ]),
-                    vec![ModuleRuleEffect::ModuleType(ModuleType::CssModule)],
+                    css_effects(ModuleType::CssModule),
                 ),
                 ModuleRule::new(
                     ModuleRuleCondition::all(vec![
@@ -457,10 +711,14 @@ impl ModuleOptions {
                             CssReferenceSubType::AtImport,
                         )),
                     ]),
-                    vec![ModuleRuleEffect::ModuleType(ModuleType::Css {
-                        ty: CssModuleAssetType::Default,
-                        transforms: css_transforms,
-                    })],
+                    tailwind_effect
+                        .into_iter()
+                        .chain(css_effects(ModuleType::Css {
+                            ty: CssModuleAssetType::Default,
+                            transforms: css_transforms,
+                            css_modules,
+                        }))
+                        .collect(),
                 ),
                 ModuleRule::new(
                     ModuleRuleCondition::all(vec![
@@ -470,28 +728,31 @@ impl ModuleOptions {
                             CssReferenceSubType::AtImport,
                         )),
                     ]),
-                    vec![ModuleRuleEffect::ModuleType(ModuleType::Css {
+                    css_effects(ModuleType::Css {
                         ty: CssModuleAssetType::Module,
                         transforms: css_transforms,
-                    })],
+                        css_modules,
+                    }),
                 ),
                 ModuleRule::new_internal(
                     ModuleRuleCondition::all(vec![ModuleRuleCondition::ResourcePathEndsWith(
                         ".css".to_string(),
                     )]),
-                    vec![ModuleRuleEffect::ModuleType(ModuleType::Css {
+                    css_effects(ModuleType::Css {
                         ty: CssModuleAssetType::Default,
                         transforms: css_transforms,
-                    })],
+                        css_modules,
+                    }),
                 ),
                 ModuleRule::new_internal(
                     ModuleRuleCondition::all(vec![ModuleRuleCondition::ResourcePathEndsWith(
                         ".module.css".to_string(),
                     )]),
-                    vec![ModuleRuleEffect::ModuleType(ModuleType::Css {
+                    css_effects(ModuleType::Css {
                         ty: CssModuleAssetType::Module,
                         transforms: css_transforms,
-                    })],
+                        css_modules,
+                    }),
                 ),
             ]);
         }
@@ -514,14 +775,18 @@ impl ModuleOptions {
                 jsx_runtime,
                 jsx_import_source,
                 provider_import_source: mdx_options.provider_import_source.clone(),
+                frontmatter: mdx_options.frontmatter,
             })
             .cell();
 
+            let mut extensions = vec![ModuleRuleCondition::ResourcePathEndsWith(
+                ".mdx".to_string(),
+            )];
+            if !enable_markdown {
+                extensions.push(ModuleRuleCondition::ResourcePathEndsWith(".md".to_string()));
+            }
             rules.push(ModuleRule::new(
-                ModuleRuleCondition::any(vec![
-                    ModuleRuleCondition::ResourcePathEndsWith(".md".to_string()),
-                    ModuleRuleCondition::ResourcePathEndsWith(".mdx".to_string()),
-                ]),
+                ModuleRuleCondition::any(extensions),
                 vec![ModuleRuleEffect::ModuleType(ModuleType::Mdx {
                     transforms: mdx_transforms,
                     options: mdx_transform_options,
@@ -529,35 +794,46 @@ impl ModuleOptions {
             ));
         }
 
+        if enable_markdown {
+            rules.push(ModuleRule::new(
+                ModuleRuleCondition::ResourcePathEndsWith(".md".to_string()),
+                vec![ModuleRuleEffect::ModuleType(ModuleType::Markdown)],
+            ));
+        }
+
+        let mut webpack_loader_rules = Vec::new();
         if let Some(webpack_loaders_options) = enable_webpack_loaders {
             let webpack_loaders_options = webpack_loaders_options.await?;
             let execution_context =
                 execution_context.context("execution_context is required for webpack_loaders")?;
-            let import_map = if let Some(loader_runner_package) =
-                webpack_loaders_options.loader_runner_package
-            {
-                package_import_map_from_import_mapping(
-                    "loader-runner".to_string(),
-                    loader_runner_package,
-                )
-            } else {
-                package_import_map_from_context("loader-runner".to_string(), path)
-            };
+            let evaluate_context = webpack_loaders_evaluate_context(
+                execution_context,
+                webpack_loaders_options.loader_runner_package,
+                path,
+            );
+            let project_path = execution_context.project_path().await?;
             for (glob, rule) in webpack_loaders_options.rules.await?.iter() {
+                let mut conditions = vec![
+                    loader_rule_glob_condition(glob, &project_path).await?,
+                    ModuleRuleCondition::not(ModuleRuleCondition::ResourceIsVirtualSource),
+                ];
+                if let Some(exclude) = &rule.exclude {
+                    conditions.push(ModuleRuleCondition::not(
+                        loader_rule_glob_condition(exclude, &project_path).await?,
+                    ));
+                }
+                if let Some(resource_query) = &rule.resource_query {
+                    conditions.push(ModuleRuleCondition::query_regex(resource_query.clone()));
+                }
+                if let Some(issuer) = &rule.issuer {
+                    conditions.push(ModuleRuleCondition::IssuerPathGlob {
+                        base: project_path.clone(),
+                        glob: Glob::new(issuer.clone()).await?,
+                    });
+                }
+                webpack_loader_rules.push(rules.len());
                 rules.push(ModuleRule::new(
-                    ModuleRuleCondition::All(vec![
-                        if !glob.contains('/') {
-                            ModuleRuleCondition::ResourceBasePathGlob(
-                                Glob::new(glob.clone()).await?,
-                            )
-                        } else {
-                            ModuleRuleCondition::ResourcePathGlob {
-                                base: execution_context.project_path().await?,
-                                glob: Glob::new(glob.clone()).await?,
-                            }
-                        },
-                        ModuleRuleCondition::not(ModuleRuleCondition::ResourceIsVirtualSource),
-                    ]),
+                    ModuleRuleCondition::All(conditions),
                     vec![
                         // By default, loaders are expected to return ecmascript code.
                         // This can be overriden by specifying e. g. `as: "*.css"` in the rule.
@@ -567,15 +843,11 @@ impl ModuleOptions {
                         }),
                         ModuleRuleEffect::SourceTransforms(Vc::cell(vec![Vc::upcast(
                             WebpackLoaders::new(
-                                node_evaluate_asset_context(
-                                    execution_context,
-                                    Some(import_map),
-                                    None,
-                                    "webpack_loaders".to_string(),
-                                ),
+                                evaluate_context,
                                 execution_context,
                                 rule.loaders,
                                 rule.rename_as.clone(),
+                                webpack_loaders_options.emit_path,
                             ),
                         )])),
                     ],
@@ -583,8 +855,31 @@ impl ModuleOptions {
             }
         }
 
+        #[cfg(feature = "image_optimization")]
+        if let Some(options) = enable_image_optimization {
+            rules.push(ModuleRule::new(
+                ModuleRuleCondition::all(vec![
+                    ModuleRuleCondition::any(
+                        ["avif", "gif", "jpeg", "jpg", "png", "webp"]
+                            .into_iter()
+                            .map(|ext| ModuleRuleCondition::ResourcePathEndsWith(format!(".{ext}")))
+                            .collect(),
+                    ),
+                    ModuleRuleCondition::ResourceQueryRegex(turbo_tasks::primitives::Regex(
+                        regex::Regex::new(r"(^\?|&)(w|width|format)=").unwrap(),
+                    )),
+                ]),
+                vec![ModuleRuleEffect::ModuleType(ModuleType::Custom(
+                    Vc::upcast(image_module_type::ProcessedImageModuleType::new(options)),
+                ))],
+            ));
+        }
+
         rules.extend(custom_rules.iter().cloned());
 
-        Ok(ModuleOptions::cell(ModuleOptions { rules }))
+        Ok(ModuleOptions::cell(ModuleOptions {
+            rules,
+            webpack_loader_rules,
+        }))
     }
 }
@@ -1,11 +1,17 @@
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
-use turbo_tasks::{trace::TraceRawVcs, ValueDefault, Vc};
-use turbopack_core::{environment::Environment, resolve::options::ImportMapping};
-use turbopack_ecmascript::TransformPlugin;
+use turbo_tasks::{primitives::Regex, trace::TraceRawVcs, ValueDefault, Vc};
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::{
+    environment::Environment, resolve::options::ImportMapping, security::SecurityScanners,
+};
+use turbopack_css::css_modules::CssModulesOptions;
+use turbopack_ecmascript::{AnalyzerPlugin, CjsInterop, HotStatePatterns, TransformPlugin};
 use turbopack_node::{
     execution_context::ExecutionContext, transforms::webpack::WebpackLoaderItems,
+    type_check::TypeCheckOptions,
 };
+use turbopack_wasm::module_asset::WebAssemblyInstantiation;
 
 use super::ModuleRule;
 use crate::condition::ContextCondition;
@@ -13,6 +19,11 @@ use crate::condition::ContextCondition;
 #[derive(Default, Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
 pub struct PostCssTransformOptions {
     pub postcss_package: Option<Vc<ImportMapping>>,
+    /// Options of PostCSS plugins by plugin name, which take precedence over
+    /// the options in the PostCSS config. Plugins which aren't in the config
+    /// are added, and `false` disables a plugin.
+    #[turbo_tasks(trace_ignore)]
+    pub plugin_options: serde_json::Map<String, serde_json::Value>,
     pub placeholder_for_future_extensions: (),
 }
 
@@ -20,6 +31,17 @@ pub struct PostCssTransformOptions {
 pub struct LoaderRuleItem {
     pub loaders: Vc<WebpackLoaderItems>,
     pub rename_as: Option<String>,
+    /// webpack's `issuer`: Only applies the loaders to resources imported by
+    /// modules matching this glob, relative to the project path, e.g.
+    /// `**/*.tsx`.
+    pub issuer: Option<String>,
+    /// webpack's `resourceQuery`: Only applies the loaders to resources whose
+    /// query, including the leading `?`, matches this regex.
+    #[turbo_tasks(trace_ignore)]
+    pub resource_query: Option<Regex>,
+    /// webpack's `exclude`: Doesn't apply the loaders to resources matching
+    /// this glob, which is matched like the glob of the rule.
+    pub exclude: Option<String>,
 }
 
 #[derive(Default)]
@@ -35,6 +57,10 @@ pub struct OptionWebpackRules(Option<Vc<WebpackRules>>);
 pub struct WebpackLoadersOptions {
     pub rules: Vc<WebpackRules>,
     pub loader_runner_package: Option<Vc<ImportMapping>>,
+    /// The directory of the files which loaders emit with `this.emitFile`,
+    /// like webpack's `output.path`. The files are referenced by the modules
+    /// and written along with the chunks. They are discarded when unset.
+    pub emit_path: Option<Vc<FileSystemPath>>,
 }
 
 #[derive(Default)]
@@ -84,6 +110,13 @@ impl ValueDefault for DecoratorsOptions {
 #[derive(Default, Clone, Debug)]
 pub struct TypescriptTransformOptions {
     pub use_define_for_class_fields: bool,
+    /// `compilerOptions.verbatimModuleSyntax`: keeps imports and exports
+    /// without a `type` modifier, even if they are only used as types.
+    pub verbatim_module_syntax: bool,
+    /// Inlines the members of `const enum`s imported from other TypeScript
+    /// modules, like `tsc` does unless `compilerOptions.isolatedModules` is
+    /// set.
+    pub inline_const_enums: bool,
 }
 
 #[turbo_tasks::value_impl]
@@ -114,6 +147,9 @@ pub struct CustomEcmascriptTransformPlugins {
     /// List of plugins to be applied after the main transform.
     /// Transform will be applied in the order of the list.
     pub output_transforms: Vec<Vc<TransformPlugin>>,
+    /// List of analyzers which inspect the program after all transforms,
+    /// e.g. to lint modules during the build.
+    pub analyzers: Vec<Vc<AnalyzerPlugin>>,
 }
 
 #[turbo_tasks::value(shared)]
@@ -124,6 +160,8 @@ pub struct MdxTransformModuleOptions {
     /// The provider must export a useMDXComponents, which is called to access
     /// an object of components.
     pub provider_import_source: Option<String>,
+    /// Exports the YAML frontmatter of mdx modules as `frontmatter`.
+    pub frontmatter: bool,
 }
 
 #[turbo_tasks::value_impl]
@@ -134,17 +172,154 @@ impl MdxTransformModuleOptions {
     }
 }
 
+/// Options for resizing and converting images that are imported with a
+/// resource query like `?w=640&format=webp`.
+#[turbo_tasks::value(shared)]
+#[derive(Default, Clone)]
+#[serde(default)]
+pub struct ImageOptimizationOptions {
+    /// Whether to compute a blur placeholder, which is exported as
+    /// `blurDataURL` along with its dimensions.
+    pub blur_placeholder: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl ImageOptimizationOptions {
+    #[turbo_tasks::function]
+    pub fn default() -> Vc<Self> {
+        Self::cell(Default::default())
+    }
+}
+
+/// Options for fonts which are referenced from CSS `@font-face` rules.
+#[turbo_tasks::value(shared)]
+#[derive(Default, Clone)]
+#[serde(default)]
+pub struct FontOptions {
+    /// Fonts of at most this many bytes are inlined as `data:` urls instead of
+    /// being emitted as separate files.
+    pub inline_limit: Option<u64>,
+    /// Whether emitted fonts are marked for preloading, so they are listed in
+    /// the chunk group's `chunk_group_preloads`.
+    pub preload: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl FontOptions {
+    #[turbo_tasks::function]
+    pub fn default() -> Vc<Self> {
+        Self::cell(Default::default())
+    }
+}
+
+/// Options for images which are registered with React Native's asset
+/// registry.
+#[turbo_tasks::value(shared)]
+#[derive(Default, Clone)]
+#[serde(default)]
+pub struct ReactNativeAssetOptions {
+    /// The request of the asset registry module. Defaults to
+    /// `react-native/Libraries/Image/AssetRegistry`.
+    pub asset_registry: Option<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl ReactNativeAssetOptions {
+    #[turbo_tasks::function]
+    pub fn default() -> Vc<Self> {
+        Self::cell(Default::default())
+    }
+}
+
+/// Options for processing CSS with Lightning CSS, see
+/// [ModuleOptionsContext::enable_lightningcss].
+#[turbo_tasks::value(shared)]
+#[derive(Default, Clone)]
+#[serde(default)]
+pub struct LightningCssOptions {
+    /// Minifies the CSS of every module. Chunks are minified separately.
+    pub minify: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl LightningCssOptions {
+    #[turbo_tasks::function]
+    pub fn default() -> Vc<Self> {
+        Self::cell(Default::default())
+    }
+}
+
+/// Options for generating Tailwind CSS utilities without PostCSS, see
+/// [ModuleOptionsContext::enable_tailwind].
+#[turbo_tasks::value(shared)]
+#[derive(Default, Clone)]
+#[serde(default)]
+pub struct TailwindOptions {
+    /// Globs relative to the project path which select the files that are
+    /// scanned for class names, like Tailwind's `content` config.
+    pub content: Vec<String>,
+    /// The package which provides the Tailwind CSS engine, `tailwindcss` by
+    /// default.
+    pub tailwind_package: Option<Vc<ImportMapping>>,
+}
+
+#[turbo_tasks::value_impl]
+impl TailwindOptions {
+    #[turbo_tasks::function]
+    pub fn default() -> Vc<Self> {
+        Self::cell(Default::default())
+    }
+}
+
+/// Globs which select the files of context modules, i.e. of the modules which
+/// are generated for partially dynamic requests like
+/// `import("./locales/" + lang + ".json")`. The globs are matched against the
+/// path of a file relative to the directory of the request.
+#[turbo_tasks::value(shared)]
+#[derive(Default, Clone)]
+#[serde(default)]
+pub struct ContextModuleOptions {
+    /// Only files matching this glob are included.
+    pub include: Option<String>,
+    /// Files matching this glob are excluded.
+    pub exclude: Option<String>,
+}
+
 #[turbo_tasks::value(shared)]
 #[derive(Default, Clone)]
 #[serde(default)]
 pub struct ModuleOptionsContext {
     pub enable_jsx: Option<Vc<JsxTransformOptions>>,
     pub enable_postcss_transform: Option<PostCssTransformOptions>,
+    /// Parses, lowers and optionally minifies CSS natively with Lightning CSS
+    /// for the browsers of `preset_env_versions`, instead of transforming
+    /// nesting with SWC. It runs after PostCSS, if that is enabled.
+    pub enable_lightningcss: Option<Vc<LightningCssOptions>>,
+    /// Generates the utilities of CSS files and CSS modules which use Tailwind
+    /// CSS v4 directly with its engine, before PostCSS runs.
+    pub enable_tailwind: Option<Vc<TailwindOptions>>,
+    /// How the class names of CSS modules are scoped and exported.
+    pub css_modules: Option<Vc<CssModulesOptions>>,
     pub enable_webpack_loaders: Option<Vc<WebpackLoadersOptions>>,
     pub enable_types: bool,
+    /// Removes `import type` and other type-only imports and re-exports from
+    /// TypeScript modules, so they never create references in the module
+    /// graph.
+    pub ignore_type_only_imports: bool,
+    /// Treats `.d.ts` files as raw modules when `enable_types` is off, so they
+    /// aren't parsed and add no references to the module graph.
+    pub ignore_type_declarations: bool,
     pub enable_typescript_transform: Option<Vc<TypescriptTransformOptions>>,
     pub decorators: Option<Vc<DecoratorsOptions>>,
     pub enable_mdx: bool,
+    /// Compiles `.md` files to modules which export their HTML as a string,
+    /// instead of treating them as MDX.
+    pub enable_markdown: bool,
+    /// How imported WebAssembly modules are instantiated in this environment.
+    pub wasm_instantiation: WebAssemblyInstantiation,
+    /// Makes `*_bg.wasm` files which wasm-bindgen generated export the API of
+    /// their JS glue, so they can be imported without the glue.
+    pub enable_wasm_bindgen: bool,
     /// This skips `GlobalCss` and `ModuleCss` module assets from being
     /// generated in the module graph, generating only `Css` module assets.
     ///
@@ -164,8 +339,45 @@ pub struct ModuleOptionsContext {
     pub rules: Vec<(ContextCondition, Vc<ModuleOptionsContext>)>,
     pub placeholder_for_future_extensions: (),
     pub enable_tree_shaking: bool,
+    /// File extensions (without the leading dot) of files that are emitted as
+    /// static assets. Replaces [DEFAULT_STATIC_ASSET_EXTENSIONS] when set.
+    pub static_asset_extensions: Option<Vec<String>>,
+    /// Makes imported static images export an object with their `src`,
+    /// `width`, `height` and `blurDataURL` instead of only their url.
+    pub enable_static_image_metadata: bool,
+    /// Processes images imported with a resource query like
+    /// `?w=640&format=webp`. Only has an effect when the `image_optimization`
+    /// feature is enabled.
+    pub enable_image_optimization: Option<Vc<ImageOptimizationOptions>>,
+    /// Inlines or preloads fonts referenced from CSS `@font-face` rules.
+    pub enable_font_handling: Option<Vc<FontOptions>>,
+    /// Makes imported images export the id of an asset registered with React
+    /// Native's asset registry instead of their url.
+    pub enable_react_native_assets: Option<Vc<ReactNativeAssetOptions>>,
+    /// Restricts the files which are included in context modules.
+    pub context_modules: Option<Vc<ContextModuleOptions>>,
+    /// Scanners which inspect every processed source and the `package.json`
+    /// of its package, and report their findings as security issues.
+    pub security_scanners: Option<Vc<SecurityScanners>>,
+    /// Keeps stores and other values matching the patterns, which are created
+    /// at the top level of app modules, across HMR updates.
+    pub enable_hot_state_preservation: Option<Vc<HotStatePatterns>>,
+    /// Type checks the TypeScript modules of the module graph with the
+    /// `typescript` package of the project, see [crate::type_check]. Requires
+    /// `execution_context`.
+    pub enable_type_checking: Option<Vc<TypeCheckOptions>>,
+    /// How default imports of CommonJS modules are synthesized. Use `rules`
+    /// to choose it for specific packages.
+    pub cjs_interop: CjsInterop,
 }
 
+/// File extensions of files that are emitted as static assets, unless
+/// [ModuleOptionsContext::static_asset_extensions] is set.
+pub const DEFAULT_STATIC_ASSET_EXTENSIONS: &[&str] = &[
+    "apng", "avif", "eot", "gif", "ico", "jpeg", "jpg", "mp3", "mp4", "otf", "pdf", "png", "svg",
+    "ttf", "wav", "webm", "webp", "woff", "woff2",
+];
+
 #[turbo_tasks::value_impl]
 impl ValueDefault for ModuleOptionsContext {
     #[turbo_tasks::function]
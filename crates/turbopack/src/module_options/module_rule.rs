@@ -5,12 +5,12 @@ use turbo_tasks_fs::FileSystemPath;
 use turbopack_core::{
     reference_type::ReferenceType, source::Source, source_transform::SourceTransforms,
 };
-use turbopack_css::{CssInputTransforms, CssModuleAssetType};
+use turbopack_css::{css_modules::CssModulesOptions, CssInputTransforms, CssModuleAssetType};
 use turbopack_ecmascript::{EcmascriptInputTransforms, EcmascriptOptions};
 use turbopack_mdx::MdxTransformOptions;
-use turbopack_wasm::source::WebAssemblySourceType;
+use turbopack_wasm::{module_asset::WebAssemblyInstantiation, source::WebAssemblySourceType};
 
-use super::{CustomModuleType, ModuleRuleCondition};
+use super::{CustomModuleType, ModuleRuleCondition, RuleMatchInput};
 
 #[derive(Debug, Clone, Serialize, Deserialize, TraceRawVcs, PartialEq, Eq)]
 pub struct ModuleRule {
@@ -79,6 +79,15 @@ impl ModuleRule {
         Ok(self.match_mode.matches(reference_type)
             && self.condition.matches(source, path, reference_type).await?)
     }
+
+    pub fn matches_input(&self, input: &RuleMatchInput) -> bool {
+        self.match_mode.matches(input.reference_type) && self.condition.matches_input(input)
+    }
+
+    /// See [ModuleRuleCondition::depends_on_issuer].
+    pub fn depends_on_issuer(&self) -> bool {
+        self.condition.depends_on_issuer()
+    }
 }
 
 #[turbo_tasks::value(shared)]
@@ -89,6 +98,73 @@ pub enum ModuleRuleEffect {
     SourceTransforms(Vc<SourceTransforms>),
 }
 
+/// An effect which can't be applied to the module type determined by the
+/// previous effects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleRuleEffectError {
+    InvalidModuleType,
+    MissingModuleType,
+}
+
+impl ModuleRuleEffectError {
+    pub fn title(&self) -> &'static str {
+        match self {
+            ModuleRuleEffectError::InvalidModuleType => "Invalid module type",
+            ModuleRuleEffectError::MissingModuleType => "Missing module type",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            ModuleRuleEffectError::InvalidModuleType => {
+                "The module type must be Ecmascript or Typescript to add Ecmascript transforms"
+            }
+            ModuleRuleEffectError::MissingModuleType => {
+                "The module type effect must be applied before adding Ecmascript transforms"
+            }
+        }
+    }
+}
+
+impl ModuleRuleEffect {
+    /// Applies the effect to the module type determined by the previous
+    /// effects. Source transforms don't affect the module type.
+    pub fn apply_to_module_type(
+        &self,
+        module_type: Option<ModuleType>,
+    ) -> Result<Option<ModuleType>, ModuleRuleEffectError> {
+        Ok(match self {
+            ModuleRuleEffect::SourceTransforms(_) => module_type,
+            ModuleRuleEffect::ModuleType(module_type) => Some(*module_type),
+            ModuleRuleEffect::AddEcmascriptTransforms(additional_transforms) => match module_type {
+                Some(ModuleType::Ecmascript {
+                    transforms,
+                    options,
+                }) => Some(ModuleType::Ecmascript {
+                    transforms: transforms.extend(*additional_transforms),
+                    options,
+                }),
+                Some(ModuleType::Typescript {
+                    transforms,
+                    options,
+                }) => Some(ModuleType::Typescript {
+                    transforms: transforms.extend(*additional_transforms),
+                    options,
+                }),
+                Some(ModuleType::TypescriptWithTypes {
+                    transforms,
+                    options,
+                }) => Some(ModuleType::TypescriptWithTypes {
+                    transforms: transforms.extend(*additional_transforms),
+                    options,
+                }),
+                Some(_) => return Err(ModuleRuleEffectError::InvalidModuleType),
+                None => return Err(ModuleRuleEffectError::MissingModuleType),
+            },
+        })
+    }
+}
+
 #[turbo_tasks::value(serialization = "auto_for_input", shared)]
 #[derive(PartialOrd, Ord, Hash, Debug, Copy, Clone)]
 pub enum ModuleType {
@@ -118,15 +194,25 @@ pub enum ModuleType {
         transforms: Vc<EcmascriptInputTransforms>,
         options: Vc<MdxTransformOptions>,
     },
+    /// Markdown which is compiled to HTML and exported as a string.
+    Markdown,
     CssGlobal,
     CssModule,
     Css {
         ty: CssModuleAssetType,
         transforms: Vc<CssInputTransforms>,
+        css_modules: Vc<CssModulesOptions>,
     },
     Static,
+    /// A static image which exports its dimensions and a blur placeholder
+    /// alongside its url.
+    StaticImage,
     WebAssembly {
         source_ty: WebAssemblySourceType,
+        instantiation: WebAssemblyInstantiation,
+        /// Wires up the JS glue which wasm-bindgen generated for the module
+        /// and exports its API instead of the raw exports.
+        wasm_bindgen: bool,
     },
     Custom(Vc<Box<dyn CustomModuleType>>),
 }
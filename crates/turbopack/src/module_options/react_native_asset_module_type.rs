@@ -0,0 +1,42 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbopack_core::{module::Module, resolve::ModulePart, source::Source};
+use turbopack_static::react_native::{react_native_asset_module, DEFAULT_ASSET_REGISTRY};
+
+use super::{CustomModuleType, ReactNativeAssetOptions};
+use crate::ModuleAssetContext;
+
+/// Creates modules which register images with React Native's asset registry.
+#[turbo_tasks::value]
+pub struct ReactNativeAssetModuleType {
+    options: Vc<ReactNativeAssetOptions>,
+}
+
+#[turbo_tasks::value_impl]
+impl ReactNativeAssetModuleType {
+    #[turbo_tasks::function]
+    pub fn new(options: Vc<ReactNativeAssetOptions>) -> Vc<Self> {
+        Self::cell(ReactNativeAssetModuleType { options })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CustomModuleType for ReactNativeAssetModuleType {
+    #[turbo_tasks::function]
+    async fn create_module(
+        &self,
+        source: Vc<Box<dyn Source>>,
+        module_asset_context: Vc<ModuleAssetContext>,
+        _part: Option<Vc<ModulePart>>,
+    ) -> Result<Vc<Box<dyn Module>>> {
+        let options = self.options.await?;
+        Ok(react_native_asset_module(
+            source,
+            Vc::upcast(module_asset_context),
+            options
+                .asset_registry
+                .clone()
+                .unwrap_or_else(|| DEFAULT_ASSET_REGISTRY.to_string()),
+        ))
+    }
+}
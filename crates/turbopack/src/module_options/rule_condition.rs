@@ -1,5 +1,4 @@
 use anyhow::Result;
-use async_recursion::async_recursion;
 use serde::{Deserialize, Serialize};
 use turbo_tasks::{primitives::Regex, trace::TraceRawVcs, ReadRef, Vc};
 use turbo_tasks_fs::{glob::Glob, FileSystemPath};
@@ -32,6 +31,20 @@ pub enum ModuleRuleCondition {
         glob: ReadRef<Glob>,
     },
     ResourceBasePathGlob(#[turbo_tasks(trace_ignore)] ReadRef<Glob>),
+    /// Matches the query of the resource, including the leading `?`.
+    ResourceQueryRegex(#[turbo_tasks(trace_ignore)] Regex),
+    /// Matches the path of the module which imports the resource, like
+    /// [ModuleRuleCondition::ResourcePathGlob] does for the resource. Doesn't
+    /// match when the issuer is unknown, e.g. for entries.
+    IssuerPathGlob {
+        base: ReadRef<FileSystemPath>,
+        #[turbo_tasks(trace_ignore)]
+        glob: ReadRef<Glob>,
+    },
+    /// Matches when the module which imports the resource is in the same
+    /// directory, e.g. the JS glue which wasm-bindgen generates next to a
+    /// WebAssembly file. Doesn't match when the issuer is unknown.
+    IssuerInResourceDirectory,
 }
 
 impl ModuleRuleCondition {
@@ -47,36 +60,141 @@ impl ModuleRuleCondition {
     pub fn not(condition: ModuleRuleCondition) -> ModuleRuleCondition {
         ModuleRuleCondition::Not(Box::new(condition))
     }
+
+    pub fn reference_type(reference_type: ReferenceType) -> ModuleRuleCondition {
+        ModuleRuleCondition::ReferenceType(reference_type)
+    }
+
+    pub fn path_ends_with(end: impl Into<String>) -> ModuleRuleCondition {
+        ModuleRuleCondition::ResourcePathEndsWith(end.into())
+    }
+
+    pub fn in_directory(dir: impl Into<String>) -> ModuleRuleCondition {
+        ModuleRuleCondition::ResourcePathInDirectory(dir.into())
+    }
+
+    pub fn path_regex(regex: Regex) -> ModuleRuleCondition {
+        ModuleRuleCondition::ResourcePathRegex(regex)
+    }
+
+    pub fn query_regex(regex: Regex) -> ModuleRuleCondition {
+        ModuleRuleCondition::ResourceQueryRegex(regex)
+    }
+
+    /// Whether the condition matches on the issuer of the resource. Resources
+    /// are only processed per issuer when a rule depends on it.
+    pub fn depends_on_issuer(&self) -> bool {
+        match self {
+            ModuleRuleCondition::All(conditions) | ModuleRuleCondition::Any(conditions) => {
+                conditions
+                    .iter()
+                    .any(|condition| condition.depends_on_issuer())
+            }
+            ModuleRuleCondition::Not(condition) => condition.depends_on_issuer(),
+            ModuleRuleCondition::IssuerPathGlob { .. }
+            | ModuleRuleCondition::IssuerInResourceDirectory => true,
+            _ => false,
+        }
+    }
+}
+
+/// Matches `path` relative to `base`, see
+/// [ModuleRuleCondition::ResourcePathGlob].
+fn path_glob_matches(base: &FileSystemPath, glob: &Glob, path: &FileSystemPath) -> bool {
+    if let Some(path) = base.get_relative_path_to(path) {
+        glob.execute(&path)
+    } else {
+        glob.execute(&path.path)
+    }
+}
+
+fn dirname(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+/// The properties of a source that [ModuleRuleCondition]s are matched
+/// against.
+///
+/// Matching on an input is synchronous and doesn't need the source itself, so
+/// rules can be evaluated against inputs constructed in tests, see
+/// [super::rule_testing].
+#[derive(Debug, Clone)]
+pub struct RuleMatchInput<'a> {
+    pub path: &'a FileSystemPath,
+    /// The query of the resource, including the leading `?`.
+    pub query: String,
+    pub is_virtual_source: bool,
+    pub reference_type: &'a ReferenceType,
+    /// The path of the module which imports the resource, if known.
+    pub issuer: Option<&'a FileSystemPath>,
+}
+
+impl<'a> RuleMatchInput<'a> {
+    /// Creates an input for a non-virtual resource without a query.
+    pub fn new(path: &'a FileSystemPath, reference_type: &'a ReferenceType) -> Self {
+        RuleMatchInput {
+            path,
+            query: String::new(),
+            is_virtual_source: false,
+            reference_type,
+            issuer: None,
+        }
+    }
+
+    /// Reads the query and the kind of `source`.
+    pub async fn from_source(
+        source: Vc<Box<dyn Source>>,
+        path: &'a FileSystemPath,
+        reference_type: &'a ReferenceType,
+    ) -> Result<RuleMatchInput<'a>> {
+        Ok(RuleMatchInput {
+            path,
+            query: source.ident().query().await?.clone_value(),
+            is_virtual_source: Vc::try_resolve_downcast_type::<VirtualSource>(source)
+                .await?
+                .is_some(),
+            reference_type,
+            issuer: None,
+        })
+    }
+
+    pub fn with_query(mut self, query: impl Into<String>) -> Self {
+        self.query = query.into();
+        self
+    }
+
+    pub fn with_virtual_source(mut self) -> Self {
+        self.is_virtual_source = true;
+        self
+    }
+
+    pub fn with_issuer(mut self, issuer: &'a FileSystemPath) -> Self {
+        self.issuer = Some(issuer);
+        self
+    }
 }
 
 impl ModuleRuleCondition {
-    #[async_recursion]
     pub async fn matches(
         &self,
         source: Vc<Box<dyn Source>>,
         path: &FileSystemPath,
         reference_type: &ReferenceType,
     ) -> Result<bool> {
-        Ok(match self {
-            ModuleRuleCondition::All(conditions) => {
-                for condition in conditions {
-                    if !condition.matches(source, path, reference_type).await? {
-                        return Ok(false);
-                    }
-                }
-                true
-            }
-            ModuleRuleCondition::Any(conditions) => {
-                for condition in conditions {
-                    if condition.matches(source, path, reference_type).await? {
-                        return Ok(true);
-                    }
-                }
-                false
-            }
-            ModuleRuleCondition::Not(condition) => {
-                !condition.matches(source, path, reference_type).await?
-            }
+        let input = RuleMatchInput::from_source(source, path, reference_type).await?;
+        Ok(self.matches_input(&input))
+    }
+
+    pub fn matches_input(&self, input: &RuleMatchInput) -> bool {
+        let path = input.path;
+        match self {
+            ModuleRuleCondition::All(conditions) => conditions
+                .iter()
+                .all(|condition| condition.matches_input(input)),
+            ModuleRuleCondition::Any(conditions) => conditions
+                .iter()
+                .any(|condition| condition.matches_input(input)),
+            ModuleRuleCondition::Not(condition) => !condition.matches_input(input),
             ModuleRuleCondition::ResourcePathEquals(other) => path == &**other,
             ModuleRuleCondition::ResourcePathEndsWith(end) => path.path.ends_with(end),
             ModuleRuleCondition::ResourcePathHasNoExtension => {
@@ -97,19 +215,11 @@ impl ModuleRuleCondition {
                 path.is_inside_ref(parent_path)
             }
             ModuleRuleCondition::ReferenceType(condition_ty) => {
-                condition_ty.includes(reference_type)
-            }
-            ModuleRuleCondition::ResourceIsVirtualSource => {
-                Vc::try_resolve_downcast_type::<VirtualSource>(source)
-                    .await?
-                    .is_some()
+                condition_ty.includes(input.reference_type)
             }
+            ModuleRuleCondition::ResourceIsVirtualSource => input.is_virtual_source,
             ModuleRuleCondition::ResourcePathGlob { glob, base } => {
-                if let Some(path) = base.get_relative_path_to(path) {
-                    glob.execute(&path)
-                } else {
-                    glob.execute(&path.path)
-                }
+                path_glob_matches(base, glob, path)
             }
             ModuleRuleCondition::ResourceBasePathGlob(glob) => {
                 let basename = path
@@ -118,7 +228,14 @@ impl ModuleRuleCondition {
                     .map_or(path.path.as_str(), |(_, b)| b);
                 glob.execute(basename)
             }
-            _ => todo!("not implemented yet"),
-        })
+            ModuleRuleCondition::ResourceQueryRegex(regex) => regex.is_match(&input.query),
+            ModuleRuleCondition::ResourcePathRegex(regex) => regex.is_match(&path.path),
+            ModuleRuleCondition::IssuerPathGlob { glob, base } => input
+                .issuer
+                .is_some_and(|issuer| path_glob_matches(base, glob, issuer)),
+            ModuleRuleCondition::IssuerInResourceDirectory => input.issuer.is_some_and(|issuer| {
+                issuer.fs == path.fs && dirname(&issuer.path) == dirname(&path.path)
+            }),
+        }
     }
 }
@@ -0,0 +1,184 @@
+//! Helpers to unit test [ModuleRule]s and [ModuleRuleCondition]s, e.g. the
+//! rules a framework adds with custom module options.
+//!
+//! Rules are evaluated against [RuleMatchInput]s instead of sources, so no
+//! asset contexts, transforms or chunking are involved. Creating the paths of
+//! the inputs still requires a turbo-tasks context, but a lightweight one like
+//! `turbo-tasks-testing`'s is sufficient.
+//!
+//! [ModuleRuleCondition]: super::ModuleRuleCondition
+
+use anyhow::Result;
+use turbo_tasks::{ReadRef, Vc};
+use turbo_tasks_fs::{FileSystem, FileSystemPath, VirtualFileSystem};
+use turbopack_core::source_transform::SourceTransforms;
+
+use super::{ModuleRule, ModuleRuleEffect, ModuleRuleEffectError, ModuleType, RuleMatchInput};
+
+/// Creates the paths of [RuleMatchInput]s. All paths are in the same virtual
+/// file system, so they can be compared with each other.
+pub struct RuleFixtures {
+    root: Vc<FileSystemPath>,
+}
+
+impl RuleFixtures {
+    /// Must be called within a turbo-tasks context.
+    pub fn new() -> Self {
+        RuleFixtures {
+            root: VirtualFileSystem::new().root(),
+        }
+    }
+
+    /// The root of the fixture file system, e.g. to create conditions on
+    /// exact paths.
+    pub fn root(&self) -> Vc<FileSystemPath> {
+        self.root
+    }
+
+    /// Resolves `path`, relative to the root, to a path which can be passed to
+    /// [RuleMatchInput::new].
+    pub async fn path(&self, path: &str) -> Result<ReadRef<FileSystemPath>> {
+        self.root.join(path.to_string()).await
+    }
+}
+
+impl Default for RuleFixtures {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The result of evaluating a list of rules for a [RuleMatchInput].
+#[derive(Debug, Clone, Default)]
+pub struct RuleEvaluation {
+    /// The indices of the rules which matched the input.
+    pub matched_rules: Vec<usize>,
+    /// The module type after applying the effects of all matched rules, or
+    /// `None` when no rule set one. Resources without a module type are
+    /// processed as [ModuleType::Raw].
+    pub module_type: Option<ModuleType>,
+    /// The source transforms of the matched rules, in order.
+    pub source_transforms: Vec<Vc<SourceTransforms>>,
+    /// Effects which could not be applied. These are reported as issues in a
+    /// build.
+    pub errors: Vec<ModuleRuleEffectError>,
+}
+
+/// Evaluates `rules` for `input` and merges the effects of the matching
+/// rules, the same way modules are processed in a build.
+///
+/// Unlike in a build, source transforms are not executed. A build applies the
+/// rules again to a transformed source whose ident has changed, skipping the
+/// rule with the transform.
+pub fn evaluate_rules(rules: &[ModuleRule], input: &RuleMatchInput) -> RuleEvaluation {
+    let mut evaluation = RuleEvaluation::default();
+    for (i, rule) in rules.iter().enumerate() {
+        if !rule.matches_input(input) {
+            continue;
+        }
+        evaluation.matched_rules.push(i);
+        for effect in rule.effects() {
+            if let ModuleRuleEffect::SourceTransforms(transforms) = effect {
+                evaluation.source_transforms.push(*transforms);
+            }
+            match effect.apply_to_module_type(evaluation.module_type) {
+                Ok(module_type) => evaluation.module_type = module_type,
+                Err(err) => evaluation.errors.push(err),
+            }
+        }
+    }
+    evaluation
+}
+
+#[cfg(test)]
+mod tests {
+    use lazy_static::lazy_static;
+    use turbo_tasks_testing::run;
+    use turbopack_core::reference_type::ReferenceType;
+    use turbopack_ecmascript::EcmascriptInputTransforms;
+
+    use super::{evaluate_rules, RuleFixtures};
+    use crate::module_options::{
+        ModuleRule, ModuleRuleCondition, ModuleRuleEffect, ModuleRuleEffectError, ModuleType,
+        RuleMatchInput,
+    };
+
+    lazy_static! {
+        static ref REGISTER: () = crate::register();
+    }
+
+    #[tokio::test]
+    async fn evaluates_matching_rules_in_order() {
+        run! {
+            let fixtures = RuleFixtures::new();
+            let rules = vec![
+                ModuleRule::new(
+                    ModuleRuleCondition::path_ends_with(".json"),
+                    vec![ModuleRuleEffect::ModuleType(ModuleType::Json)],
+                ),
+                ModuleRule::new(
+                    ModuleRuleCondition::in_directory("assets"),
+                    vec![ModuleRuleEffect::ModuleType(ModuleType::Raw)],
+                ),
+            ];
+            let reference_type = ReferenceType::Undefined;
+
+            let path = fixtures.path("src/data.json").await?;
+            let evaluation = evaluate_rules(&rules, &RuleMatchInput::new(&path, &reference_type));
+            assert_eq!(evaluation.matched_rules, vec![0]);
+            assert_eq!(evaluation.module_type, Some(ModuleType::Json));
+
+            let path = fixtures.path("src/assets/data.json").await?;
+            let evaluation = evaluate_rules(&rules, &RuleMatchInput::new(&path, &reference_type));
+            assert_eq!(evaluation.matched_rules, vec![0, 1]);
+            assert_eq!(evaluation.module_type, Some(ModuleType::Raw));
+
+            let path = fixtures.path("src/index.js").await?;
+            let evaluation = evaluate_rules(&rules, &RuleMatchInput::new(&path, &reference_type));
+            assert!(evaluation.matched_rules.is_empty());
+            assert_eq!(evaluation.module_type, None);
+        }
+    }
+
+    #[tokio::test]
+    async fn matches_virtual_sources() {
+        run! {
+            let fixtures = RuleFixtures::new();
+            let rules = vec![ModuleRule::new(
+                ModuleRuleCondition::ResourceIsVirtualSource,
+                vec![ModuleRuleEffect::ModuleType(ModuleType::Raw)],
+            )];
+            let reference_type = ReferenceType::Undefined;
+            let path = fixtures.path("virtual.js").await?;
+
+            let evaluation = evaluate_rules(&rules, &RuleMatchInput::new(&path, &reference_type));
+            assert!(evaluation.matched_rules.is_empty());
+
+            let input = RuleMatchInput::new(&path, &reference_type).with_virtual_source();
+            let evaluation = evaluate_rules(&rules, &input);
+            assert_eq!(evaluation.matched_rules, vec![0]);
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_effects_which_cannot_be_applied() {
+        run! {
+            let fixtures = RuleFixtures::new();
+            let rules = vec![ModuleRule::new(
+                ModuleRuleCondition::path_ends_with(".js"),
+                vec![ModuleRuleEffect::AddEcmascriptTransforms(
+                    EcmascriptInputTransforms::empty(),
+                )],
+            )];
+            let reference_type = ReferenceType::Undefined;
+            let path = fixtures.path("index.js").await?;
+
+            let evaluation = evaluate_rules(&rules, &RuleMatchInput::new(&path, &reference_type));
+            assert_eq!(evaluation.matched_rules, vec![0]);
+            assert_eq!(
+                evaluation.errors,
+                vec![ModuleRuleEffectError::MissingModuleType]
+            );
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use anyhow::Result;
+use turbo_tasks::{Value, Vc};
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::{
+    environment::EdgeRuntime,
+    issue::{Issue, IssueExt, IssueSeverity},
+    resolve::{
+        options::{ImportMapResult, ImportMapping, ImportMappingReplacement},
+        parse::Request,
+        ResolveResult, ResolveResultItem,
+    },
+};
+
+/// The browser polyfill packages webpack 4 aliased Node.js built-in modules
+/// to, which `enable_node_polyfills` maps them to.
+pub(crate) const NODE_POLYFILLS: &[(&str, &str)] = &[
+    ("assert", "assert/"),
+    ("buffer", "buffer/"),
+    ("console", "console-browserify"),
+    ("constants", "constants-browserify"),
+    ("crypto", "crypto-browserify"),
+    ("domain", "domain-browser"),
+    ("events", "events/"),
+    ("http", "stream-http"),
+    ("https", "https-browserify"),
+    ("os", "os-browserify/browser.js"),
+    ("path", "path-browserify"),
+    ("process", "process/browser.js"),
+    ("punycode", "punycode/"),
+    ("querystring", "querystring-es3"),
+    ("stream", "stream-browserify"),
+    ("string_decoder", "string_decoder/"),
+    ("sys", "util/"),
+    ("timers", "timers-browserify"),
+    ("tty", "tty-browserify"),
+    ("url", "url/"),
+    ("util", "util/"),
+    ("vm", "vm-browserify"),
+    ("zlib", "browserify-zlib"),
+];
+
+/// An import mapping for Node.js built-in modules which are not available in
+/// the target environment and have no polyfill. Requests to them are reported
+/// as errors and resolve to an empty module.
+#[turbo_tasks::value]
+pub(crate) struct UnavailableNodeBuiltinMapping {
+    /// The edge runtime which is targeted, whose available built-ins are
+    /// suggested instead.
+    edge_runtime: Option<EdgeRuntime>,
+}
+
+#[turbo_tasks::value_impl]
+impl UnavailableNodeBuiltinMapping {
+    #[turbo_tasks::function]
+    pub fn new() -> Vc<Self> {
+        UnavailableNodeBuiltinMapping { edge_runtime: None }.cell()
+    }
+
+    #[turbo_tasks::function]
+    pub fn for_edge_runtime(edge_runtime: Value<EdgeRuntime>) -> Vc<Self> {
+        UnavailableNodeBuiltinMapping {
+            edge_runtime: Some(edge_runtime.into_value()),
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMappingReplacement for UnavailableNodeBuiltinMapping {
+    #[turbo_tasks::function]
+    fn replace(self: Vc<Self>, _capture: String) -> Vc<ImportMapping> {
+        ImportMapping::Dynamic(Vc::upcast(self)).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn result(&self, lookup_path: Vc<FileSystemPath>, request: Vc<Request>) -> Vc<ImportMapResult> {
+        UnavailableNodeBuiltinIssue {
+            file_path: lookup_path,
+            request,
+            edge_runtime: self.edge_runtime,
+        }
+        .cell()
+        .emit();
+
+        ImportMapResult::Result(ResolveResult::primary(ResolveResultItem::Empty).cell()).cell()
+    }
+}
+
+#[turbo_tasks::value(shared)]
+struct UnavailableNodeBuiltinIssue {
+    file_path: Vc<FileSystemPath>,
+    request: Vc<Request>,
+    edge_runtime: Option<EdgeRuntime>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for UnavailableNodeBuiltinIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("resolve".to_string())
+    }
+
+    #[turbo_tasks::function]
+    async fn title(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell(format!(
+            "Node.js built-in module {} is not available in this environment",
+            self.request.await?.request().as_deref().unwrap_or("N/A")
+        )))
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        let Some(edge_runtime) = self.edge_runtime else {
+            return Vc::cell(
+                "Node.js built-in modules can only be used when targeting Node.js. Configure a \
+                 polyfill for it, or avoid importing it from code which runs in this environment."
+                    .to_string(),
+            );
+        };
+        let available = edge_runtime
+            .node_builtins()
+            .iter()
+            .map(|builtin| format!("node:{builtin}"))
+            .collect::<Vec<_>>();
+        let mut description = format!(
+            "{} can't load Node.js built-in modules which they don't provide. Use a Web API \
+             instead, e.g. `fetch` or `crypto.subtle`, configure a polyfill for it, or move this \
+             code to a Node.js function.",
+            edge_runtime.as_str()
+        );
+        if !available.is_empty() {
+            description.push_str(&format!(" Only {} are available", available.join(", ")));
+            if let EdgeRuntime::Workerd = edge_runtime {
+                description.push_str(" with the `nodejs_compat` compatibility flag");
+            }
+            description.push('.');
+        }
+        Vc::cell(description)
+    }
+}
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use turbo_tasks::Vc;
+use turbo_tasks::{Value, Vc};
 use turbo_tasks_fs::{FileSystem, FileSystemPath};
 use turbopack_core::resolve::{
     find_context_file,
@@ -7,13 +7,18 @@ use turbopack_core::resolve::{
         ConditionValue, ImportMap, ImportMapping, ResolutionConditions, ResolveInPackage,
         ResolveIntoPackage, ResolveModules, ResolveOptions,
     },
-    AliasMap, AliasPattern, FindContextFileResult,
+    AliasMap, AliasPattern, FindContextFileResult, ResolveResult,
 };
-use turbopack_ecmascript::typescript::resolve::{
-    apply_tsconfig_resolve_options, tsconfig, tsconfig_resolve_options,
+use turbopack_ecmascript::{
+    stories::StoriesIndexSource,
+    typescript::resolve::{apply_tsconfig_resolve_options, tsconfig, tsconfig_resolve_options},
 };
 
-use crate::resolve_options_context::ResolveOptionsContext;
+use crate::{
+    federation::FederationMapping,
+    node_builtins::{UnavailableNodeBuiltinMapping, NODE_POLYFILLS},
+    resolve_options_context::{NodeBuiltinsHandling, ResolveOptionsContext, TsConfigHandling},
+};
 
 const NODE_EXTERNALS: [&str; 51] = [
     "assert",
@@ -69,6 +74,63 @@ const NODE_EXTERNALS: [&str; 51] = [
     "pnpapi",
 ];
 
+/// The export conditions of `opt`. `import` and `require` are unknown,
+/// because they depend on the request, unless they are set explicitly.
+pub(crate) async fn resolution_conditions(
+    opt: &ResolveOptionsContext,
+) -> Result<ResolutionConditions> {
+    let mut conditions: ResolutionConditions = [
+        ("import".to_string(), ConditionValue::Unknown),
+        ("require".to_string(), ConditionValue::Unknown),
+    ]
+    .into_iter()
+    .collect();
+    if let Some(active_conditions) = &opt.conditions {
+        for condition in active_conditions.iter() {
+            conditions.insert(condition.to_string(), ConditionValue::Set);
+        }
+    } else {
+        if opt.browser {
+            conditions.insert("browser".to_string(), ConditionValue::Set);
+        }
+        if opt.module {
+            conditions.insert("module".to_string(), ConditionValue::Set);
+        }
+        if let Some(environment) = opt.emulate_environment {
+            for condition in environment.resolve_conditions().await?.iter() {
+                conditions.insert(condition.to_string(), ConditionValue::Set);
+            }
+        }
+    }
+    for condition in opt.custom_conditions.iter() {
+        conditions.insert(condition.to_string(), ConditionValue::Set);
+    }
+    // Infer some well-known conditions
+    let dev = conditions.get("development").cloned();
+    let prod = conditions.get("production").cloned();
+    if prod.is_none() {
+        conditions.insert(
+            "production".to_string(),
+            if matches!(dev, Some(ConditionValue::Set)) {
+                ConditionValue::Unset
+            } else {
+                ConditionValue::Unknown
+            },
+        );
+    }
+    if dev.is_none() {
+        conditions.insert(
+            "development".to_string(),
+            if matches!(prod, Some(ConditionValue::Set)) {
+                ConditionValue::Unset
+            } else {
+                ConditionValue::Unknown
+            },
+        );
+    }
+    Ok(conditions)
+}
+
 #[turbo_tasks::function]
 async fn base_resolve_options(
     resolve_path: Vc<FileSystemPath>,
@@ -88,15 +150,83 @@ async fn base_resolve_options(
     } else {
         opt.enable_node_externals
     };
-    if node_externals {
-        for req in NODE_EXTERNALS {
+    let mut insert_node_builtin = |req: &str, mapping: Vc<ImportMapping>| {
+        direct_mappings.insert(AliasPattern::exact(req), mapping);
+        direct_mappings.insert(AliasPattern::exact(format!("node:{req}")), mapping);
+    };
+    match &opt.node_builtins {
+        NodeBuiltinsHandling::Auto if !node_externals => {
+            let edge_runtime = match emulating {
+                Some(environment) => *environment.edge_runtime().await?,
+                None => None,
+            };
+            // Edge runtimes only provide some built-ins, as `node:` imports.
+            if let Some(edge_runtime) = edge_runtime {
+                let unavailable = ImportMapping::Dynamic(Vc::upcast(
+                    UnavailableNodeBuiltinMapping::for_edge_runtime(Value::new(edge_runtime)),
+                ))
+                .cell();
+                for req in NODE_EXTERNALS {
+                    if edge_runtime.node_builtins().contains(&req) {
+                        insert_node_builtin(
+                            req,
+                            ImportMapping::External(Some(format!("node:{req}"))).cell(),
+                        );
+                    } else {
+                        insert_node_builtin(req, unavailable);
+                    }
+                }
+            }
+        }
+        NodeBuiltinsHandling::Auto | NodeBuiltinsHandling::Externalize => {
+            for req in NODE_EXTERNALS {
+                insert_node_builtin(req, ImportMapping::External(None).cell());
+            }
+        }
+        NodeBuiltinsHandling::Error => {
+            for req in NODE_EXTERNALS {
+                insert_node_builtin(
+                    req,
+                    ImportMapping::Dynamic(Vc::upcast(UnavailableNodeBuiltinMapping::new())).cell(),
+                );
+            }
+        }
+        NodeBuiltinsHandling::Polyfill(polyfills) => {
+            for req in NODE_EXTERNALS {
+                let mapping = match polyfills.get(req) {
+                    Some(polyfill) => ImportMapping::PrimaryAlternative(polyfill.clone(), None),
+                    None => {
+                        ImportMapping::Dynamic(Vc::upcast(UnavailableNodeBuiltinMapping::new()))
+                    }
+                };
+                insert_node_builtin(req, mapping.cell());
+            }
+        }
+    }
+    if opt.enable_node_polyfills {
+        for &(req, polyfill) in NODE_POLYFILLS {
+            insert_node_builtin(
+                req,
+                ImportMapping::PrimaryAlternative(polyfill.to_string(), None).cell(),
+            );
+        }
+    }
+    if let Some(environment) = emulating {
+        for req in environment.runtime_externals().await?.iter() {
             direct_mappings.insert(
-                AliasPattern::exact(req),
+                AliasPattern::exact(req.clone()),
                 ImportMapping::External(None).into(),
             );
+        }
+    }
+
+    if let Some(stories_indexes) = opt.stories_indexes {
+        let stories_indexes = stories_indexes.await?;
+        for (request, glob) in stories_indexes.globs.iter() {
+            let source = StoriesIndexSource::new(stories_indexes.dir, glob.clone());
             direct_mappings.insert(
-                AliasPattern::exact(format!("node:{req}")),
-                ImportMapping::External(None).into(),
+                AliasPattern::exact(request.clone()),
+                ImportMapping::Direct(ResolveResult::source(Vc::upcast(source)).cell()).cell(),
             );
         }
     }
@@ -110,52 +240,7 @@ async fn base_resolve_options(
 
     let plugins = opt.plugins.clone();
 
-    let conditions = {
-        let mut conditions: ResolutionConditions = [
-            ("import".to_string(), ConditionValue::Unknown),
-            ("require".to_string(), ConditionValue::Unknown),
-        ]
-        .into_iter()
-        .collect();
-        if opt.browser {
-            conditions.insert("browser".to_string(), ConditionValue::Set);
-        }
-        if opt.module {
-            conditions.insert("module".to_string(), ConditionValue::Set);
-        }
-        if let Some(environment) = emulating {
-            for condition in environment.resolve_conditions().await?.iter() {
-                conditions.insert(condition.to_string(), ConditionValue::Set);
-            }
-        }
-        for condition in opt.custom_conditions.iter() {
-            conditions.insert(condition.to_string(), ConditionValue::Set);
-        }
-        // Infer some well-known conditions
-        let dev = conditions.get("development").cloned();
-        let prod = conditions.get("production").cloned();
-        if prod.is_none() {
-            conditions.insert(
-                "production".to_string(),
-                if matches!(dev, Some(ConditionValue::Set)) {
-                    ConditionValue::Unset
-                } else {
-                    ConditionValue::Unknown
-                },
-            );
-        }
-        if dev.is_none() {
-            conditions.insert(
-                "development".to_string(),
-                if matches!(prod, Some(ConditionValue::Set)) {
-                    ConditionValue::Unset
-                } else {
-                    ConditionValue::Unknown
-                },
-            );
-        }
-        conditions
-    };
+    let conditions = resolution_conditions(&opt).await?;
 
     Ok(ResolveOptions {
         extensions: if let Some(environment) = emulating {
@@ -180,15 +265,23 @@ async fn base_resolve_options(
         },
         modules: if let Some(environment) = emulating {
             if *environment.resolve_node_modules().await? {
-                vec![ResolveModules::Nested(
+                let mut mods = Vec::new();
+                if let Some(dir) = opt.enable_yarn_pnp {
+                    mods.push(ResolveModules::YarnPnp(dir));
+                }
+                mods.push(ResolveModules::Nested(
                     root,
                     vec!["node_modules".to_string()],
-                )]
+                ));
+                mods
             } else {
                 Vec::new()
             }
         } else {
             let mut mods = Vec::new();
+            if let Some(dir) = opt.enable_yarn_pnp {
+                mods.push(ResolveModules::YarnPnp(dir));
+            }
             if let Some(dir) = opt.enable_node_modules {
                 mods.push(ResolveModules::Nested(
                     dir,
@@ -202,6 +295,11 @@ async fn base_resolve_options(
                 conditions: conditions.clone(),
                 unspecified_conditions: ConditionValue::Unset,
             }];
+            if let Some(environment) = emulating {
+                for field in environment.resolve_main_fields().await?.iter() {
+                    resolve_into.push(ResolveIntoPackage::MainField(field.clone()));
+                }
+            }
             if opt.browser {
                 resolve_into.push(ResolveIntoPackage::MainField("browser".to_string()));
             }
@@ -224,7 +322,10 @@ async fn base_resolve_options(
         },
         import_map: Some(import_map),
         resolved_map: opt.resolved_map,
+        externals: opt.externals,
         plugins,
+        dedupe_packages: opt.dedupe_packages,
+        preserve_symlinks: opt.preserve_symlinks,
         ..Default::default()
     }
     .into())
@@ -247,16 +348,24 @@ pub async fn resolve_options(
 
     let resolve_options = base_resolve_options(resolve_path, options_context);
 
-    let resolve_options = if options_context_value.enable_typescript {
-        let tsconfig = find_context_file(resolve_path, tsconfig()).await?;
-        match *tsconfig {
-            FindContextFileResult::Found(path, _) => {
-                apply_tsconfig_resolve_options(resolve_options, tsconfig_resolve_options(path))
+    let tsconfig_options = if options_context_value.enable_typescript {
+        match &options_context_value.tsconfig {
+            TsConfigHandling::Nearest => {
+                match *find_context_file(resolve_path, tsconfig()).await? {
+                    FindContextFileResult::Found(path, _) => Some(tsconfig_resolve_options(path)),
+                    FindContextFileResult::NotFound(_) => None,
+                }
             }
-            FindContextFileResult::NotFound(_) => resolve_options,
+            TsConfigHandling::File(path) => Some(tsconfig_resolve_options(*path)),
+            TsConfigHandling::Options(options) => Some(*options),
+            TsConfigHandling::Disabled => None,
         }
     } else {
-        resolve_options
+        None
+    };
+    let resolve_options = match tsconfig_options {
+        Some(tsconfig_options) => apply_tsconfig_resolve_options(resolve_options, tsconfig_options),
+        None => resolve_options,
     };
 
     // Make sure to always apply `options_context.import_map` last, so it properly
@@ -265,6 +374,14 @@ pub async fn resolve_options(
         .import_map
         .map(|import_map| resolve_options.with_extended_import_map(import_map))
         .unwrap_or(resolve_options);
+    // Except for the federation, so that the host's imports of shared packages
+    // go through the share scope even when they are aliased.
+    let resolve_options = if options_context_value.federation_remotes.is_some() {
+        resolve_options
+            .with_extended_import_map(federation_import_map(resolve_path, options_context))
+    } else {
+        resolve_options
+    };
     // And the same for the fallback_import_map
     let resolve_options = options_context_value
         .fallback_import_map
@@ -275,3 +392,37 @@ pub async fn resolve_options(
 
     Ok(resolve_options)
 }
+
+/// The import map which maps the requests of the module federation of a host
+/// to the modules of [FederationMapping].
+#[turbo_tasks::function]
+async fn federation_import_map(
+    resolve_path: Vc<FileSystemPath>,
+    options_context: Vc<ResolveOptionsContext>,
+) -> Result<Vc<ImportMap>> {
+    let opt = options_context.await?;
+    let mut import_map = ImportMap::empty();
+    let Some(federation_remotes) = opt.federation_remotes else {
+        return Ok(import_map.cell());
+    };
+
+    // The host's versions of the shared packages are resolved without the
+    // federation, since their requests are mapped to the share scope.
+    let host_resolve_options = resolve_options(
+        resolve_path,
+        ResolveOptionsContext {
+            federation_remotes: None,
+            ..(*opt).clone()
+        }
+        .cell(),
+    );
+    let mapping = ImportMapping::Dynamic(Vc::upcast(FederationMapping::new(
+        federation_remotes,
+        host_resolve_options,
+    )))
+    .cell();
+    for pattern in federation_remotes.await?.alias_patterns() {
+        import_map.insert_alias(pattern, mapping);
+    }
+    Ok(import_map.cell())
+}
@@ -1,15 +1,61 @@
+use std::collections::BTreeMap;
+
 use anyhow::Result;
-use turbo_tasks::{ValueDefault, Vc};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, ValueDefault, Vc};
 use turbo_tasks_fs::FileSystemPath;
 use turbopack_core::{
     environment::Environment,
     resolve::{
-        options::{ImportMap, ResolvedMap},
+        options::{ConditionValue, Externals, ImportMap, ResolvedMap},
         plugin::ResolvePlugin,
     },
 };
+use turbopack_ecmascript::typescript::resolve::TsConfigResolveOptions;
+
+use crate::{
+    condition::ContextCondition, federation::FederationRemotes, resolve::resolution_conditions,
+    stories::StoriesIndexes,
+};
 
-use crate::condition::ContextCondition;
+/// How requests to Node.js built-in modules like `fs` or `node:fs` are handled.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TraceRawVcs, PartialEq, Eq)]
+pub enum NodeBuiltinsHandling {
+    /// Built-ins are externalized when the environment supports Node.js
+    /// externals, or `enable_node_externals` is set. When emulating an edge
+    /// runtime, the built-ins it provides are loaded as `node:` imports and
+    /// requests to others are reported as errors. Otherwise they are resolved
+    /// like any other request.
+    #[default]
+    Auto,
+    /// Built-ins are loaded using native `require`, e.g. when targeting
+    /// Node.js.
+    Externalize,
+    /// Requests to built-ins are reported as errors, e.g. when targeting the
+    /// browser.
+    Error,
+    /// Built-ins are replaced by the polyfill packages they are mapped to,
+    /// e.g. `buffer` to `buffer/`. Requests to other built-ins are reported as
+    /// errors.
+    Polyfill(BTreeMap<String, String>),
+}
+
+/// Where the `compilerOptions.baseUrl`, `paths` and `rootDirs` used for
+/// resolving come from, when `enable_typescript` is set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TraceRawVcs, PartialEq, Eq)]
+pub enum TsConfigHandling {
+    /// Uses the `tsconfig.json` or `jsconfig.json` nearest to the directory
+    /// the request is resolved from.
+    #[default]
+    Nearest,
+    /// Uses this `tsconfig.json`, and the configs it extends, for all
+    /// requests.
+    File(Vc<FileSystemPath>),
+    /// Uses these options instead of reading a `tsconfig.json`.
+    Options(Vc<TsConfigResolveOptions>),
+    /// Ignores `tsconfig.json` files.
+    Disabled,
+}
 
 #[turbo_tasks::value(shared)]
 #[derive(Default, Clone)]
@@ -21,6 +67,9 @@ pub struct ResolveOptionsContext {
     #[serde(default)]
     pub enable_typescript: bool,
     #[serde(default)]
+    /// Where the tsconfig.json options for resolving are read from.
+    pub tsconfig: TsConfigHandling,
+    #[serde(default)]
     pub enable_react: bool,
     #[serde(default)]
     pub enable_node_native_modules: bool,
@@ -28,17 +77,38 @@ pub struct ResolveOptionsContext {
     /// Enable resolving of the node_modules folder when within the provided
     /// directory
     pub enable_node_modules: Option<Vc<FileSystemPath>>,
+    /// Enable resolving packages with the Yarn Plug'n'Play manifest
+    /// (`.pnp.cjs` or `.pnp.data.json`) in the provided directory. Packages
+    /// in zip archives are read through a
+    /// [turbo_tasks_fs::ZipFileSystem]. Takes precedence over
+    /// `enable_node_modules`.
+    pub enable_yarn_pnp: Option<Vc<FileSystemPath>>,
     #[serde(default)]
     /// Mark well-known Node.js modules as external imports and load them using
     /// native `require`. e.g. url, querystring, os
     pub enable_node_externals: bool,
     #[serde(default)]
+    /// How requests to Node.js built-in modules are handled.
+    pub node_builtins: NodeBuiltinsHandling,
+    #[serde(default)]
+    /// Alias Node.js built-in modules to the browser polyfill packages webpack
+    /// 4 used, like [NodeBuiltinsHandling::Polyfill] with the mappings of
+    /// `NODE_POLYFILLS`. The packages need to be installed. Takes precedence
+    /// over `node_builtins` for these modules.
+    pub enable_node_polyfills: bool,
+    #[serde(default)]
     /// Enables the "browser" field and export condition in package.json
     pub browser: bool,
     #[serde(default)]
     /// Enables the "module" field and export condition in package.json
     pub module: bool,
     #[serde(default)]
+    /// The export conditions to use instead of the ones enabled by `browser`,
+    /// `module` and the emulated environment, e.g. `["worker", "browser",
+    /// "module"]`.
+    pub conditions: Option<Vec<String>>,
+    #[serde(default)]
+    /// Additional export conditions, e.g. `react-server` or `deno`.
     pub custom_conditions: Vec<String>,
     #[serde(default)]
     /// An additional import map to use when resolving modules.
@@ -58,14 +128,37 @@ pub struct ResolveOptionsContext {
     /// An additional resolved map to use after modules have been resolved.
     pub resolved_map: Option<Vc<ResolvedMap>>,
     #[serde(default)]
+    /// Requests which aren't bundled, but loaded at runtime with `import()`,
+    /// `require()` or from a global variable, e.g. packages which read files
+    /// relative to their own location in server builds.
+    pub externals: Option<Vc<Externals>>,
+    #[serde(default)]
+    /// Module federation containers whose exposed modules are loaded at
+    /// runtime, e.g. `app2/Button` from the container `app2`, and the
+    /// packages which the host shares with them.
+    pub federation_remotes: Option<Vc<FederationRemotes>>,
+    #[serde(default)]
+    /// Generated indexes of the modules matching a glob, e.g. the stories of
+    /// component explorers, which can be imported with their requests.
+    pub stories_indexes: Option<Vc<StoriesIndexes>>,
+    #[serde(default)]
     /// A list of rules to use a different resolve option context for certain
     /// context paths. The first matching is used.
     pub rules: Vec<(ContextCondition, Vc<ResolveOptionsContext>)>,
     #[serde(default)]
-    /// A list of plugins which get applied before (in the future) and after
-    /// resolving.
+    /// A list of plugins which get applied before and after resolving.
     pub plugins: Vec<Vc<Box<dyn ResolvePlugin>>>,
     #[serde(default)]
+    /// Prefer the highest version of packages which are installed multiple
+    /// times along the lookup path. See `ResolveOptions::dedupe_packages`.
+    pub dedupe_packages: bool,
+    #[serde(default)]
+    /// Keep symlinked paths of modules and packages instead of resolving them
+    /// to their real paths, e.g. for pnpm workspaces where the real path of a
+    /// package changes which `node_modules` its dependencies are resolved
+    /// from. See `ResolveOptions::preserve_symlinks`.
+    pub preserve_symlinks: bool,
+    #[serde(default)]
     pub placeholder_for_future_extensions: (),
 }
 
@@ -79,6 +172,41 @@ impl ResolveOptionsContext {
         Ok(Self::cell(clone))
     }
 
+    /// Returns a new [Vc<ResolveOptionsContext>] with the given export
+    /// conditions added to its custom conditions, e.g. for a transition into
+    /// a server component.
+    #[turbo_tasks::function]
+    pub async fn with_custom_conditions(
+        self: Vc<Self>,
+        conditions: Vec<String>,
+    ) -> Result<Vc<Self>> {
+        let mut resolve_options_context = self.await?.clone_value();
+        for condition in conditions {
+            if !resolve_options_context
+                .custom_conditions
+                .contains(&condition)
+            {
+                resolve_options_context.custom_conditions.push(condition);
+            }
+        }
+        Ok(resolve_options_context.into())
+    }
+
+    /// The export conditions which are set when resolving with this context,
+    /// sorted by name. The conditions of [ResolveOptionsContext::rules] are
+    /// not considered.
+    #[turbo_tasks::function]
+    pub async fn active_conditions(self: Vc<Self>) -> Result<Vc<Vec<String>>> {
+        let conditions = resolution_conditions(&*self.await?).await?;
+        Ok(Vc::cell(
+            conditions
+                .into_iter()
+                .filter(|(_, value)| matches!(value, ConditionValue::Set))
+                .map(|(condition, _)| condition)
+                .collect(),
+        ))
+    }
+
     /// Returns a new [Vc<ResolveOptionsContext>] with its import map extended
     /// to include the given import map.
     #[turbo_tasks::function]
@@ -0,0 +1,19 @@
+use indexmap::IndexMap;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileSystemPath;
+
+/// The generated indexes of "stories" modules which can be imported, see
+/// [crate::resolve_options_context::ResolveOptionsContext::stories_indexes].
+///
+/// A request like `virtual:stories` resolves to a module which default exports
+/// an entry for each module matching its glob, see
+/// [turbopack_ecmascript::stories::StoriesIndex].
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct StoriesIndexes {
+    /// The directory the globs are relative to.
+    pub dir: Vc<FileSystemPath>,
+    /// Maps the requests of the indexes to their globs, e.g.
+    /// `virtual:stories` to `src/**/*.stories.tsx`.
+    pub globs: IndexMap<String, String>,
+}
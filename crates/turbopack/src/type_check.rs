@@ -0,0 +1,77 @@
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use turbo_tasks::{Completion, TryJoinIterExt, Vc};
+use turbopack_core::{
+    module::{Module, Modules},
+    reference::all_modules_from_entries,
+};
+use turbopack_node::type_check::type_check;
+
+use crate::{
+    evaluate_context::node_evaluate_asset_context,
+    module_options::{package_import_map_from_context, ModuleOptionsContext},
+};
+
+/// Extensions of the TypeScript modules which are type checked. Type
+/// declarations are only checked when they are referenced by these.
+const TYPESCRIPT_EXTENSIONS: &[&str] = &[".ts", ".tsx", ".mts", ".cts"];
+
+/// Type checks the TypeScript modules reachable from `entries` when
+/// [ModuleOptionsContext::enable_type_checking] is set, and emits the
+/// diagnostics as issues. Modules in `node_modules` and outside of the project
+/// path are skipped.
+///
+/// Only the options of `module_options_context` itself are considered, not
+/// those of its [ModuleOptionsContext::rules].
+#[turbo_tasks::function]
+pub async fn type_check_module_graph(
+    entries: Vc<Modules>,
+    module_options_context: Vc<ModuleOptionsContext>,
+) -> Result<Vc<Completion>> {
+    let ModuleOptionsContext {
+        enable_type_checking: Some(options),
+        execution_context: Some(execution_context),
+        ..
+    } = *module_options_context.await?
+    else {
+        return Ok(Completion::immutable());
+    };
+    let project_path = execution_context.project_path();
+    let project_path_value = project_path.await?;
+
+    let files = all_modules_from_entries(entries)
+        .await?
+        .iter()
+        .map(|module| async move { Ok(module.ident().path().await?) })
+        .try_join()
+        .await?
+        .into_iter()
+        .filter_map(|path| {
+            let path = project_path_value.get_path_to(&path)?;
+            let is_typescript = TYPESCRIPT_EXTENSIONS
+                .iter()
+                .any(|extension| path.ends_with(extension))
+                && !path.ends_with(".d.ts");
+            (is_typescript && !path.split('/').any(|segment| segment == "node_modules"))
+                .then(|| path.to_string())
+        })
+        .collect::<BTreeSet<_>>();
+
+    let evaluate_context = node_evaluate_asset_context(
+        execution_context,
+        Some(package_import_map_from_context(
+            "typescript".to_string(),
+            project_path,
+        )),
+        None,
+        "type_check".to_string(),
+    );
+
+    Ok(type_check(
+        evaluate_context,
+        execution_context,
+        Vc::cell(files.into_iter().collect()),
+        options,
+    ))
+}
@@ -280,19 +280,23 @@ fn node_file_trace_memory(#[case] input: CaseInput) {
 
 #[cfg(feature = "test_persistent_cache")]
 #[apply(test_cases)]
-fn node_file_trace_rocksdb(#[case] input: CaseInput) {
-    use turbo_tasks_memory::MemoryBackendWithPersistedGraph;
-    use turbo_tasks_rocksdb::RocksDbPersistedGraph;
+fn node_file_trace_persistent(#[case] input: CaseInput) {
+    use turbo_tasks_memory::{DiskPersistedGraph, MemoryBackendWithPersistedGraph};
 
     node_file_trace(
         input,
-        "rockdb",
+        "persistent",
         false,
         2,
         240,
         |directory_path| {
             TurboTasks::new(MemoryBackendWithPersistedGraph::new(
-                RocksDbPersistedGraph::new(directory_path.join(".db")).unwrap(),
+                DiskPersistedGraph::new(
+                    directory_path.join(".cache/turbo-tasks"),
+                    env!("CARGO_PKG_VERSION").to_string(),
+                )
+                .unwrap()
+                .with_volatile_functions(DiskFileSystem::volatile_functions()),
             ))
         },
         |_| {},